@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChatCitation } from "./ChatCitation";
+
+export type AskLibraryResult = { answer: string, citations: Array<ChatCitation>, };
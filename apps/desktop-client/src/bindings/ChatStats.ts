@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ChatStats = { prompt_tokens: number, generated_tokens: number, prompt_ms: number, generation_ms: number, tokens_per_second: number, };
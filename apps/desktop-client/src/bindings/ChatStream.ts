@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChatStats } from "./ChatStats";
 
-export type ChatStream = { "type": "LoadingPrompt" } | { "type": "ChatStart" } | { "type": "Token", "content": string } | { "type": "ChatDone" };
+export type ChatStream = { "type": "LoadingPrompt" } | { "type": "ChatStart" } | { "type": "Token", "content": string } | { "type": "ContextTruncated", "content": Array<string> } | { "type": "Error", "content": string } | { "type": "Cancelled" } | { "type": "ChatDone", "content": ChatStats };
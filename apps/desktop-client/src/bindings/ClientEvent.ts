@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ClientEvent = "ClearSearch" | "ChatEvent" | "FocusWindow" | "FolderChosen" | "LensInstalled" | "LensUninstalled" | "Navigate" | "RefreshConnections" | "RefreshDiscover" | "RefreshLensLibrary" | "RefreshPluginManager" | "RefreshSearchResults" | "StartupProgress" | "UpdateLensFinished";
+export type ClientEvent = "ClearSearch" | "ChatEvent" | "EmbeddingProgress" | "FocusWindow" | "FolderChosen" | "LensBootstrapFinished" | "LensBootstrapProgress" | "LensInstalled" | "LensUninstalled" | "Navigate" | "RefreshConnections" | "RefreshDiscover" | "RefreshLensLibrary" | "RefreshPluginManager" | "RefreshSearchResults" | "StartupProgress" | "UpdateLensFinished";
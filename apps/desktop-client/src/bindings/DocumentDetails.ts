@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DocumentDetails = {
+/**
+ * Document ID
+ */
+doc_id: string,
+/**
+ * URI used to crawl this document
+ */
+crawl_uri: string, domain: string, title: string, description: string, url: string,
+/**
+ * Indexed content. Capped at a preview size unless the full body was
+ * requested; `is_truncated` is set when content was cut off.
+ */
+content: string, is_truncated: boolean, tags: Array<[string, string]>,
+/**
+ * Names of the lenses this document belongs to.
+ */
+lenses: Array<string>,
+/**
+ * When the document was published, if known.
+ */
+published_at: string | null,
+/**
+ * When the document was last modified, if known.
+ */
+last_modified: string | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GenerationParams = { temperature: number | null, top_p: number | null, max_tokens: number, stop: Array<string>, seed: number | null, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { ChatMessage } from "./ChatMessage";
+import type { GenerationParams } from "./GenerationParams";
 
-export type LlmSession = { messages: Array<ChatMessage>, };
+export type LlmSession = { messages: Array<ChatMessage>, params: GenerationParams, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type SearchMeta = { query: string, num_docs: number, wall_time_ms: number, };
+export type SearchMeta = { query: string, num_docs: number, wall_time_ms: number,
+/**
+ * Total number of documents matching the query, used for paging.
+ */
+total_hits: number, };
@@ -8,4 +8,20 @@ doc_id: string,
 /**
  * URI used to crawl this result
  */
-crawl_uri: string, domain: string, title: string, description: string, url: string, tags: Array<[string, string]>, score: number, };
+crawl_uri: string, domain: string, title: string, description: string, url: string, tags: Array<[string, string]>, score: number,
+/**
+ * When the document was published, if known.
+ */
+published_at: string | null,
+/**
+ * When the document was last modified, if known.
+ */
+last_modified: string | null,
+/**
+ * Short content snippet centered around the matched terms.
+ */
+snippet: string,
+/**
+ * Byte ranges of the matched terms within `snippet`.
+ */
+highlights: Array<[number, number]>, };
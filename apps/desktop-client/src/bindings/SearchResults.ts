@@ -2,4 +2,14 @@
 import type { SearchMeta } from "./SearchMeta";
 import type { SearchResult } from "./SearchResult";
 
-export type SearchResults = { results: Array<SearchResult>, meta: SearchMeta, };
+export type SearchResults = { results: Array<SearchResult>, meta: SearchMeta, 
+/**
+ * Number of matching documents per "label:value" tag pair, when
+ * requested via `SearchParam::return_facets`.
+ */
+facets: { [key: string]: number },
+/**
+ * Alternate queries ("did you mean?") built from the index's term
+ * dictionaries. Only populated when `results` is empty.
+ */
+suggestions: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result ordering for `SearchParam`.
+ */
+export type SearchSort = "Relevance" | "NewestFirst" | "OldestFirst";
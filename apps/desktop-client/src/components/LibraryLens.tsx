@@ -9,6 +9,7 @@ import {
 } from "@heroicons/react/24/solid";
 import { Btn } from "./Btn";
 import { BtnType, LensStatus } from "./_constants";
+import { LensBootstrapProgressPayload } from "../bindings/LensBootstrapProgressPayload";
 import { LensType } from "../bindings/LensType";
 import { useNavigate } from "react-router-dom";
 
@@ -19,6 +20,8 @@ interface Props {
   label: string;
   name: string;
   lensType?: LensType;
+  // Live crawl progress for the lens, if it's currently being crawled.
+  progress?: LensBootstrapProgressPayload | null;
 
   status: LensStatus;
 
@@ -35,6 +38,7 @@ export function LibraryLens({
   status,
   lensType = "Lens",
   categories = [],
+  progress = null,
   onCategoryClick = () => {},
   onInstall = () => {},
   onUninstall = () => {},
@@ -80,6 +84,22 @@ export function LibraryLens({
           </a>
         </div>
         <div className="text-sm text-neutral-400 mt-1">{description}</div>
+        {progress ? (
+          <div className="mt-2 text-xs text-neutral-400">
+            <div>
+              {`Crawling: ${progress.crawled.toLocaleString()} of ${progress.total.toLocaleString()} pages`}
+              {progress.rate ? ` (${progress.rate.toFixed(1)}/s)` : ""}
+            </div>
+            <div className="mt-1 h-1 w-full rounded bg-neutral-600">
+              <div
+                className="h-1 rounded bg-cyan-500"
+                style={{
+                  width: `${Math.min(100, (progress.crawled * 100) / Math.max(progress.total, 1))}%`,
+                }}
+              />
+            </div>
+          </div>
+        ) : null}
         {categoryTags}
       </div>
       <LensActionBar
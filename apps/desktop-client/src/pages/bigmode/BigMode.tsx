@@ -78,7 +78,7 @@ export function BigMode() {
       await invoke("escape");
     } else if (resultMode === ResultDisplayMode.Lenses) {
       const selected = lensResults[selectedIdx];
-      setSelectedLenses((lenses) => [...lenses, selected.label]);
+      setSelectedLenses((lenses) => [...lenses, selected.name]);
       clearQuery();
     }
   };
@@ -95,7 +95,7 @@ export function BigMode() {
         // Handle tab completion for len search/results
         if (resultMode === ResultDisplayMode.Lenses) {
           const selected = lensResults[selectedIdx];
-          setSelectedLenses((lenses) => [...lenses, selected.label]);
+          setSelectedLenses((lenses) => [...lenses, selected.name]);
           clearQuery();
         }
         break;
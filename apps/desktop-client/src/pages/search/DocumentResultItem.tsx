@@ -41,16 +41,50 @@ export function DocumentResultItem({ id, onClick, result, isSelected }: Props) {
         <h2 className="text-base truncate font-semibold w-[30rem]">
           {result.title}
         </h2>
-        <div
-          className="text-sm leading-relaxed text-neutral-400 max-h-10 overflow-hidden"
-          dangerouslySetInnerHTML={{ __html: result.description }}
-        />
+        {result.snippet ? (
+          <div className="text-sm leading-relaxed text-neutral-400 max-h-10 overflow-hidden">
+            <SnippetPreview
+              snippet={result.snippet}
+              highlights={result.highlights}
+            />
+          </div>
+        ) : (
+          <div
+            className="text-sm leading-relaxed text-neutral-400 max-h-10 overflow-hidden"
+            dangerouslySetInnerHTML={{ __html: result.description }}
+          />
+        )}
         <DocumentMeta result={result} />
       </div>
     </a>
   );
 }
 
+function SnippetPreview({
+  snippet,
+  highlights,
+}: {
+  snippet: string;
+  highlights: Array<[number, number]>;
+}) {
+  const pieces: ReactNode[] = [];
+  let cursor = 0;
+  highlights.forEach(([start, end], idx) => {
+    if (start >= cursor && end <= snippet.length) {
+      pieces.push(<span key={`pre-${idx}`}>{snippet.slice(cursor, start)}</span>);
+      pieces.push(
+        <b key={`match-${idx}`} className="font-bold text-white">
+          {snippet.slice(start, end)}
+        </b>,
+      );
+      cursor = end;
+    }
+  });
+  pieces.push(<span key="rest">{snippet.slice(cursor)}</span>);
+
+  return <>{pieces}</>;
+}
+
 function DocumentIcon({ result }: { result: SearchResult }) {
   const url = new URL(result.crawl_uri);
   const iconStyles = ["w-8", "h-8", "m-auto", "mt-2"];
@@ -5,6 +5,8 @@ import { LensResult } from "../../bindings/LensResult";
 import { SearchResults } from "../../bindings/SearchResults";
 import { SearchMeta } from "../../bindings/SearchMeta";
 import { SearchResult } from "../../bindings/SearchResult";
+import { SearchSort } from "../../bindings/SearchSort";
+import { TagCountResult } from "../../bindings/TagCountResult";
 import { SearchStatus } from "./SearchStatus";
 import { UserActionSettings } from "../../bindings/UserActionSettings";
 import { ActionListButton, ActionsList } from "./ActionsList";
@@ -14,6 +16,7 @@ import {
   QUERY_DEBOUNCE_MS,
   ResultDisplayMode,
   SEARCH_MIN_CHARS,
+  TAG_SEARCH_PREFIX,
 } from "./constants";
 import Handlebars from "handlebars";
 import { ContextActions } from "../../bindings/ContextActions";
@@ -21,6 +24,7 @@ import { includeAction, resultToTemplate } from "./utils";
 import { CustomTitleBar } from "../../components/CustomTitleBar";
 import { SearchInput } from "./SearchInput";
 import { ResultListView } from "./ResultListView";
+import { TagEditor } from "./TagEditor";
 
 export function SearchPage() {
   const searchWrapperRef = useRef<HTMLDivElement>(null);
@@ -36,6 +40,7 @@ export function SearchPage() {
 
   const [isThinking, setIsThinking] = useState<boolean>(false);
   const [showActions, setShowActions] = useState<boolean>(false);
+  const [showTagEditor, setShowTagEditor] = useState<boolean>(false);
 
   const [userActions, setUserActions] = useState<UserActionDefinition[]>([]);
   const [currentContextActions, setCurrentContextActions] = useState<
@@ -45,9 +50,30 @@ export function SearchPage() {
 
   const [selectedActionIdx, setSelectedActionIdx] = useState<number>(0);
   const [searchMeta, setSearchMeta] = useState<SearchMeta | null>(null);
+  const [suggestions, setSuggestions] = useState<string[]>([]);
+  const [tagSuggestions, setTagSuggestions] = useState<TagCountResult[]>([]);
   const [offset, setOffset] = useState<number>(0);
 
   const [query, setQuery] = useState<string>("");
+  const [sort, setSort] = useState<SearchSort>("Relevance");
+  // Page size is the user's max_search_results setting; learned from the
+  // first page of results rather than hard-coding it.
+  const [pageSize, setPageSize] = useState<number>(5);
+
+  // Cycle through the available sort orders & redo the current search.
+  const toggleSort = useCallback(() => {
+    setSort((current) => {
+      if (current === "Relevance") {
+        return "NewestFirst";
+      } else if (current === "NewestFirst") {
+        return "OldestFirst";
+      }
+      return "Relevance";
+    });
+    setDocResults([]);
+    setSelectedIdx(0);
+    setOffset(0);
+  }, []);
 
   const requestResize = async () => {
     if (searchWrapperRef.current) {
@@ -63,12 +89,26 @@ export function SearchPage() {
     setDocResults([]);
     setLensResults([]);
     setShowActions(false);
+    setShowTagEditor(false);
     setSelectedActionIdx(0);
     setSearchMeta(null);
+    setSuggestions([]);
+    setTagSuggestions([]);
     setOffset(0);
     await requestResize();
   }, []);
 
+  // Replace the partial `tag:` token at the end of the query w/ the
+  // selected tag.
+  const completeTag = useCallback((value: string) => {
+    setQuery((query) => {
+      const tokens = query.split(" ");
+      tokens[tokens.length - 1] = `${TAG_SEARCH_PREFIX}${value}`;
+      return `${tokens.join(" ")} `;
+    });
+    setTagSuggestions([]);
+  }, []);
+
   // Clear search queries & results
   const clearQuery = useCallback(async () => {
     setQuery("");
@@ -121,13 +161,47 @@ export function SearchPage() {
         await invoke("escape");
       } else if (resultMode === ResultDisplayMode.Lenses) {
         const selected = lensResults[selectedIdx];
-        setSelectedLenses((lenses) => [...lenses, selected.label]);
+        setSelectedLenses((lenses) => [...lenses, selected.name]);
         clearQuery();
       }
     }
   };
 
+  // Toggle the favorite state of the currently selected result.
+  const toggleFavorite = async () => {
+    if (resultMode !== ResultDisplayMode.Documents) {
+      return;
+    }
+
+    const selected = docResults[selectedIdx];
+    if (!selected) {
+      return;
+    }
+
+    const favorited = selected.tags.some(([label]) => label === "favorited");
+    await invoke("toggle_favorite", { id: selected.doc_id, favorited });
+    // Update the result in place so another toggle doesn't need a refresh.
+    setDocResults((results) =>
+      results.map((doc, idx) =>
+        idx === selectedIdx
+          ? {
+              ...doc,
+              tags: favorited
+                ? doc.tags.filter(([label]) => label !== "favorited")
+                : [...doc.tags, ["favorited", "favorited"] as [string, string]],
+            }
+          : doc,
+      ),
+    );
+  };
+
   const handleKeyEvent = async (event: KeyboardEvent) => {
+    if (event.key === "d" && (event.metaKey || event.ctrlKey)) {
+      event.preventDefault();
+      await toggleFavorite();
+      return;
+    }
+
     switch (event.key) {
       case "ArrowUp":
         moveSelectionUp();
@@ -150,7 +224,7 @@ export function SearchPage() {
         if (resultMode === ResultDisplayMode.Lenses) {
           const selected = lensResults[selectedIdx];
           if (selected) {
-            setSelectedLenses((lenses) => [...lenses, selected.label]);
+            setSelectedLenses((lenses) => [...lenses, selected.name]);
           } else {
             console.error("Unable to select lens.", selected, selectedIdx);
           }
@@ -168,8 +242,18 @@ export function SearchPage() {
     console.debug("handling action: ", action);
     // Get the context for the action execution
     const selectedDoc = docResults[selectedIdx];
-    // open in application
-    if ("OpenApplication" in action.action) {
+    // show similar documents
+    if (action.action === "ShowSimilar") {
+      const similar = await invoke<SearchResult[]>("get_similar_documents", {
+        docId: selectedDoc.doc_id,
+      });
+      setDocResults(similar);
+      setSelectedIdx(0);
+      // edit tags on the selected result
+    } else if (action.action === "EditTags") {
+      setShowTagEditor(true);
+      // open in application
+    } else if ("OpenApplication" in action.action) {
       const url = selectedDoc.url;
       const [app] = action.action.OpenApplication;
       await invoke("open_result", { url, application: app });
@@ -199,19 +283,20 @@ export function SearchPage() {
       const doc_count = docResults.length;
       const max = doc_count - 1;
       if (selectedIdx === max) {
-        const remainder = doc_count % 5;
+        const remainder = doc_count % pageSize;
         if (remainder === 0) {
           setOffset(doc_count);
         }
       }
     }
-  }, [selectedIdx, resultMode, docResults.length]);
+  }, [selectedIdx, resultMode, docResults.length, pageSize]);
 
   useEffect(() => {
     invoke<SearchResults>("search_docs", {
       query,
       lenses: selectedLenses,
       offset,
+      sort,
     }).then((resp: SearchResults) => {
       setDocResults((results: SearchResult[]) => {
         const values = [...results];
@@ -244,21 +329,38 @@ export function SearchPage() {
         setLensResults(results);
         setIsThinking(false);
       } else if (query.length >= SEARCH_MIN_CHARS) {
+        // Autocomplete a trailing `tag:` scope from the tags in the index.
+        const lastToken = query.split(" ").pop() ?? "";
+        if (lastToken.startsWith(TAG_SEARCH_PREFIX)) {
+          const tags = await invoke<TagCountResult[]>("list_tags", {
+            label: null,
+            prefix: lastToken.substring(TAG_SEARCH_PREFIX.length),
+          });
+          setTagSuggestions(tags);
+        } else {
+          setTagSuggestions([]);
+        }
+
         setIsThinking(true);
         // search docs
         const resp = await invoke<SearchResults>("search_docs", {
           query,
           lenses: selectedLenses,
           offset: 0,
+          sort,
         });
         setResultMode(ResultDisplayMode.Documents);
         setDocResults(resp.results);
+        if (resp.results.length > 0) {
+          setPageSize(resp.results.length);
+        }
+        setSuggestions(resp.suggestions);
         setSearchMeta(resp.meta);
         setIsThinking(false);
       }
     }, QUERY_DEBOUNCE_MS);
     return () => clearTimeout(timer);
-  }, [query, selectedLenses, clearResults]);
+  }, [query, selectedLenses, clearResults, sort]);
 
   useEffect(() => {
     const newActions = [...userActions];
@@ -278,7 +380,7 @@ export function SearchPage() {
 
   useEffect(() => {
     requestResize();
-  }, [docResults, lensResults]);
+  }, [docResults, lensResults, showTagEditor]);
 
   useEffect(() => {
     // get_action_list
@@ -324,11 +426,58 @@ export function SearchPage() {
         lensResults={lensResults}
         selectedIdx={selectedIdx}
       />
+      {showTagEditor && docResults[selectedIdx] ? (
+        <TagEditor
+          doc={docResults[selectedIdx]}
+          onUpdated={(tags) =>
+            setDocResults((results) =>
+              results.map((doc, idx) =>
+                idx === selectedIdx ? { ...doc, tags } : doc,
+              ),
+            )
+          }
+          onClose={() => setShowTagEditor(false)}
+        />
+      ) : null}
+      {tagSuggestions.length > 0 ? (
+        <div className="flex flex-row gap-2 items-center bg-neutral-800 px-4 py-2 text-sm text-neutral-400 border-t border-neutral-600">
+          <span>Tags:</span>
+          {tagSuggestions.map((tag) => (
+            <button
+              key={`${tag.label}:${tag.value}`}
+              className="rounded-full bg-neutral-700 px-3 py-0.5 text-white hover:bg-cyan-600"
+              onClick={() => completeTag(tag.value)}
+            >
+              {tag.value}
+              <span className="ml-1 text-neutral-400">{tag.doc_count}</span>
+            </button>
+          ))}
+        </div>
+      ) : null}
+      {suggestions.length > 0 ? (
+        <div className="flex flex-row gap-2 items-center bg-neutral-800 px-4 py-2 text-sm text-neutral-400 border-t border-neutral-600">
+          <span>Did you mean:</span>
+          {suggestions.map((suggestion) => (
+            <button
+              key={suggestion}
+              className="rounded-full bg-neutral-700 px-3 py-0.5 text-white hover:bg-cyan-600"
+              onClick={() => setQuery(suggestion)}
+            >
+              {suggestion}
+            </button>
+          ))}
+        </div>
+      ) : null}
       <div
         data-tauri-drag-region
         className="flex flex-row w-full items-center bg-neutral-900 h-8 p-0"
       >
-        <SearchStatus meta={searchMeta} isThinking={isThinking} />
+        <SearchStatus
+          meta={searchMeta}
+          isThinking={isThinking}
+          sort={sort}
+          onToggleSort={toggleSort}
+        />
         {searchMeta ? (
           <ActionListButton
             isActive={showActions}
@@ -1,14 +1,23 @@
 import { ArrowPathIcon } from "@heroicons/react/24/solid";
 import { SearchMeta } from "../../bindings/SearchMeta";
+import { SearchSort } from "../../bindings/SearchSort";
 import { KeyComponent } from "../../components/KeyComponent";
 import { ArrowDownIcon, ArrowUpIcon } from "@heroicons/react/16/solid";
 
 interface Props {
   meta: SearchMeta | null;
   isThinking: boolean;
+  sort: SearchSort;
+  onToggleSort: () => void;
 }
 
-export function SearchStatus({ meta, isThinking }: Props) {
+const SORT_LABELS: { [key in SearchSort]: string } = {
+  Relevance: "Relevance",
+  NewestFirst: "Newest first",
+  OldestFirst: "Oldest first",
+};
+
+export function SearchStatus({ meta, isThinking, sort, onToggleSort }: Props) {
   if (isThinking) {
     return (
       <>
@@ -37,6 +46,12 @@ export function SearchStatus({ meta, isThinking }: Props) {
             {" ms."}
           </span>
         </div>
+        <button
+          className="text-cyan-600 hover:text-cyan-400 cursor-pointer"
+          onClick={onToggleSort}
+        >
+          {`Sort: ${SORT_LABELS[sort]}`}
+        </button>
         <div className="flex flex-row align-middle items-center gap-1">
           {"Use"}
           <KeyComponent>
@@ -0,0 +1,115 @@
+import { KeyboardEvent, useEffect, useRef, useState } from "react";
+import { invoke } from "../../glue";
+import { SearchResult } from "../../bindings/SearchResult";
+import { TagCountResult } from "../../bindings/TagCountResult";
+
+// Label used for tags typed w/o an explicit "label:value" prefix.
+const DEFAULT_LABEL = "tag";
+
+interface Props {
+  doc: SearchResult;
+  onUpdated: (tags: [string, string][]) => void;
+  onClose: () => void;
+}
+
+// Inline editor for a result's tags: remove existing tags by clicking them,
+// add new ones with autocomplete from the tags already in the index.
+export function TagEditor({ doc, onUpdated, onClose }: Props) {
+  const inputRef = useRef<HTMLInputElement>(null);
+  const [pending, setPending] = useState<string>("");
+  const [suggestions, setSuggestions] = useState<TagCountResult[]>([]);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+  }, []);
+
+  useEffect(() => {
+    if (pending.length === 0) {
+      setSuggestions([]);
+      return;
+    }
+
+    invoke<TagCountResult[]>("list_tags", {
+      label: null,
+      prefix: pending.split(":").pop() ?? pending,
+    }).then(setSuggestions);
+  }, [pending]);
+
+  const applyChange = async (
+    add: [string, string][],
+    remove: [string, string][],
+  ) => {
+    await invoke("update_doc_tags", { id: doc.doc_id, add, remove });
+    const tags = doc.tags.filter(
+      ([label, value]) =>
+        !remove.some(([rmLabel, rmValue]) => label === rmLabel && value === rmValue),
+    );
+    onUpdated([...tags, ...add]);
+  };
+
+  const addTag = async (label: string, value: string) => {
+    if (value.length === 0) {
+      return;
+    }
+    await applyChange([[label, value]], []);
+    setPending("");
+    setSuggestions([]);
+  };
+
+  // Accepts either "value" or an explicit "label:value".
+  const addPending = async () => {
+    const [label, value] = pending.includes(":")
+      ? [pending.split(":")[0], pending.split(":").slice(1).join(":")]
+      : [DEFAULT_LABEL, pending];
+    await addTag(label.trim(), value.trim());
+  };
+
+  const handleKeyEvent = async (event: KeyboardEvent) => {
+    if (event.key === "Enter") {
+      await addPending();
+    } else if (event.key === "Escape") {
+      onClose();
+    }
+  };
+
+  return (
+    <div className="flex flex-col gap-2 bg-neutral-800 px-4 py-2 text-sm border-t border-neutral-600">
+      <div className="flex flex-row flex-wrap gap-2 items-center">
+        <span className="text-neutral-400">Tags:</span>
+        {doc.tags.map(([label, value]) => (
+          <button
+            key={`${label}:${value}`}
+            title="Remove tag"
+            className="rounded-full bg-neutral-700 px-3 py-0.5 text-white hover:bg-red-700"
+            onClick={() => applyChange([], [[label, value]])}
+          >
+            {label}:{value} ×
+          </button>
+        ))}
+        <input
+          ref={inputRef}
+          type="text"
+          className="bg-neutral-800 text-white flex-1 min-w-32 border-none caret-white focus:outline-none"
+          placeholder="Add tag"
+          value={pending}
+          onChange={(event) => setPending(event.target.value)}
+          onKeyDown={handleKeyEvent}
+          spellCheck={false}
+        />
+      </div>
+      {suggestions.length > 0 ? (
+        <div className="flex flex-row flex-wrap gap-2 items-center text-neutral-400">
+          {suggestions.map((tag) => (
+            <button
+              key={`${tag.label}:${tag.value}`}
+              className="rounded-full bg-neutral-700 px-3 py-0.5 text-white hover:bg-cyan-600"
+              onClick={() => addTag(tag.label, tag.value)}
+            >
+              {tag.label}:{tag.value}
+            </button>
+          ))}
+        </div>
+      ) : null}
+    </div>
+  );
+}
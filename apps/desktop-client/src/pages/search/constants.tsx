@@ -1,6 +1,7 @@
 import { UserActionDefinition } from "../../bindings/UserActionDefinition";
 
 export const LENS_SEARCH_PREFIX: string = "/";
+export const TAG_SEARCH_PREFIX: string = "tag:";
 export const QUERY_DEBOUNCE_MS: number = 256;
 export const SEARCH_MIN_CHARS: number = 2;
 
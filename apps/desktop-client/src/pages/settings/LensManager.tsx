@@ -8,6 +8,9 @@ import { Btn } from "../../components/Btn";
 import { Header } from "./Header";
 import { useEffect, useState } from "react";
 import { invoke, listen } from "../../glue";
+import { EmbeddingProgressPayload } from "../../bindings/EmbeddingProgressPayload";
+import { LensBootstrapFinishedPayload } from "../../bindings/LensBootstrapFinishedPayload";
+import { LensBootstrapProgressPayload } from "../../bindings/LensBootstrapProgressPayload";
 import { LensResult } from "../../bindings/LensResult";
 import { LibraryLens } from "../../components/LibraryLens";
 import { LensStatus } from "../../components/_constants";
@@ -16,6 +19,11 @@ export function LensManager() {
   const [inProgress, setInProgress] = useState<boolean>(false);
   const [lenses, setLenses] = useState<LensResult[]>([]);
   const [uninstalling, setUninstalling] = useState<string[]>([]);
+  const [embeddingStatus, setEmbeddingStatus] =
+    useState<EmbeddingProgressPayload | null>(null);
+  const [crawlStatus, setCrawlStatus] = useState<
+    Record<string, LensBootstrapProgressPayload>
+  >({});
 
   const handleOpenFolder = async () => {
     await invoke("open_lens_folder");
@@ -52,6 +60,22 @@ export function LensManager() {
         listen<string>("LensUninstalled", (event) =>
           handleUninstallDone(event.payload),
         ),
+        listen<EmbeddingProgressPayload>("EmbeddingProgress", (event) =>
+          setEmbeddingStatus(event.payload.remaining > 0 ? event.payload : null),
+        ),
+        listen<LensBootstrapProgressPayload>(
+          "LensBootstrapProgress",
+          (event) =>
+            setCrawlStatus((status) => ({
+              ...status,
+              [event.payload.lens]: event.payload,
+            })),
+        ),
+        listen<LensBootstrapFinishedPayload>(
+          "LensBootstrapFinished",
+          (event) =>
+            setCrawlStatus(({ [event.payload.lens]: _done, ...rest }) => rest),
+        ),
       ]);
     };
 
@@ -85,6 +109,12 @@ export function LensManager() {
           Update
         </Btn>
       </Header>
+      {embeddingStatus ? (
+        <div className="px-4 pt-2 text-xs text-neutral-400">
+          {`Generating embeddings: ${embeddingStatus.remaining} documents remaining`}
+          {embeddingStatus.rate ? ` (${embeddingStatus.rate.toFixed(1)}/s)` : ""}
+        </div>
+      ) : null}
       <div className="flex flex-col gap-2 p-4">
         {lenses.map((x) => (
           <LibraryLens
@@ -95,6 +125,7 @@ export function LensManager() {
             name={x.name}
             description={x.description}
             lensType={x.lens_type}
+            progress={crawlStatus[x.name] ?? null}
             status={
               uninstalling.includes(x.name)
                 ? LensStatus.Uninstalling
@@ -142,12 +142,24 @@ pub async fn search_docs<'r>(
     lenses: Vec<String>,
     query: &str,
     offset: u32,
+    sort: Option<request::SearchSort>,
 ) -> Result<SearchResults, String> {
     if let Some(rpc) = win.app_handle().try_state::<rpc::RpcMutex>() {
         let data = request::SearchParam {
             lenses,
+            languages: Vec::new(),
             query: query.to_string(),
             offset: Some(offset),
+            limit: None,
+            fuzzy: None,
+            recency_boost_days: None,
+            published_after: None,
+            published_before: None,
+            favorites_only: false,
+            sort: sort.unwrap_or_default(),
+            return_facets: false,
+            // The searchbar queries as the user types.
+            is_partial: true,
         };
 
         let rpc = rpc.lock().await;
@@ -163,6 +175,29 @@ pub async fn search_docs<'r>(
     }
 }
 
+#[tauri::command]
+pub async fn get_similar_documents<'r>(
+    win: tauri::Window,
+    doc_id: &str,
+) -> Result<Vec<response::SearchResult>, String> {
+    if let Some(rpc) = win.app_handle().try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        match rpc
+            .client
+            .get_similar_documents(doc_id.to_string(), None)
+            .await
+        {
+            Ok(resp) => Ok(resp),
+            Err(err) => {
+                log::error!("get_similar_documents err: {}", err);
+                Err(err.to_string())
+            }
+        }
+    } else {
+        Err("Unable to reach backend".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn search_lenses<'r>(
     win: tauri::Window,
@@ -186,6 +221,27 @@ pub async fn search_lenses<'r>(
     }
 }
 
+#[tauri::command]
+pub async fn list_tags<'r>(
+    win: tauri::Window,
+    label: Option<String>,
+    prefix: Option<String>,
+) -> Result<Vec<response::TagCountResult>, String> {
+    if let Some(rpc) = win.app_handle().try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        // Enough for a row of filter chips in the searchbar.
+        match rpc.client.list_tags(label, prefix, Some(8)).await {
+            Ok(resp) => Ok(resp),
+            Err(err) => {
+                log::error!("list_tags err: {}", err.to_string());
+                Ok(Vec::new())
+            }
+        }
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 #[tauri::command]
 pub async fn delete_doc<'r>(window: tauri::Window, id: &str) -> Result<(), String> {
     if let Some(rpc) = window.app_handle().try_state::<rpc::RpcMutex>() {
@@ -203,6 +259,58 @@ pub async fn delete_doc<'r>(window: tauri::Window, id: &str) -> Result<(), Strin
     Ok(())
 }
 
+#[tauri::command]
+pub async fn toggle_favorite<'r>(
+    window: tauri::Window,
+    id: &str,
+    favorited: bool,
+) -> Result<(), String> {
+    if let Some(rpc) = window.app_handle().try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        let result = if favorited {
+            rpc.client.unfavorite_document(id.to_string()).await
+        } else {
+            rpc.client.favorite_document(id.to_string()).await
+        };
+
+        match result {
+            Ok(_) => {
+                let _ = window.emit(ClientEvent::RefreshSearchResults.as_ref(), true);
+            }
+            Err(err) => {
+                log::error!("toggle_favorite err: {}", err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_doc_tags<'r>(
+    window: tauri::Window,
+    id: &str,
+    add: Vec<(String, String)>,
+    remove: Vec<(String, String)>,
+) -> Result<(), String> {
+    if let Some(rpc) = window.app_handle().try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        match rpc
+            .client
+            .update_document_tags(id.to_string(), add, remove)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("update_doc_tags err: {}", err);
+                Err(err.to_string())
+            }
+        }
+    } else {
+        Err("Unable to reach backend".to_string())
+    }
+}
+
 #[tauri::command]
 pub async fn network_change(
     win: tauri::Window,
@@ -22,6 +22,9 @@ pub async fn save_user_settings(
 
     let config_list: Vec<(String, SettingOpts)> = config.user_settings.clone().into();
     let setting_configs: HashMap<String, SettingOpts> = config_list.into_iter().collect();
+    // Plugin settings are validated against the types declared in each
+    // plugin's manifest.
+    let plugin_configs = config.load_plugin_config();
     let mut errors: HashMap<String, String> = HashMap::new();
 
     let mut fields_updated: usize = 0;
@@ -102,6 +105,40 @@ pub async fn save_user_settings(
                         }
                     }
                 }
+            } else {
+                match plugin_configs
+                    .get(parent)
+                    .and_then(|plugin| plugin.user_settings.get(field))
+                {
+                    Some(opt) => {
+                        // Clearing a value falls back to the plugin default.
+                        let validated = if value.trim().is_empty() {
+                            Ok(String::new())
+                        } else {
+                            opt.form_type.validate(value)
+                        };
+
+                        match validated {
+                            Ok(val) => {
+                                fields_updated += 1;
+                                current_settings
+                                    .plugin_settings
+                                    .entry(parent.to_string())
+                                    .or_default()
+                                    .insert(field.to_string(), val);
+                            }
+                            Err(err) => {
+                                errors.insert(key.to_string(), err);
+                            }
+                        }
+                    }
+                    None => {
+                        errors.insert(
+                            key.to_string(),
+                            format!("{parent} does not declare a setting named {field}"),
+                        );
+                    }
+                }
             }
         }
     }
@@ -142,7 +142,9 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             cmd::delete_doc,
             cmd::get_library_stats,
             cmd::get_shortcut,
+            cmd::get_similar_documents,
             cmd::list_connections,
+            cmd::list_tags,
             cmd::load_action_settings,
             cmd::load_user_settings,
             cmd::network_change,
@@ -156,7 +158,9 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             cmd::save_user_settings,
             cmd::search_docs,
             cmd::search_lenses,
+            cmd::toggle_favorite,
             cmd::update_and_restart,
+            cmd::update_doc_tags,
             cmd::llm::ask_clippy,
             cmd::window::escape,
             cmd::window::navigate,
@@ -167,6 +171,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             plugins::lens_updater::list_installable_lenses,
             plugins::lens_updater::list_installed_lenses,
             plugins::lens_updater::run_lens_updater,
+            plugins::lens_updater::toggle_lens,
             plugins::lens_updater::uninstall_lens,
             plugins::startup::get_startup_progress,
         ])
@@ -8,11 +8,18 @@ use tauri::{
     AppHandle, Manager, PackageInfo, Wry,
 };
 
-use crate::{pause_crawler, platform::os_open, window};
+use crate::{pause_crawler, platform::os_open, rpc::RpcMutex, window};
+use spyglass_rpc::RpcClient;
+
+/// Menu event id prefix for the per-plugin "run now" items; the rest of the
+/// id is the plugin name.
+pub const RUN_PLUGIN_PREFIX: &str = "run_plugin:";
 
 #[derive(Clone)]
 pub struct MenuState {
     pub pause_toggle: MenuItem<Wry>,
+    pub embedding_status: MenuItem<Wry>,
+    pub plugins_menu: Submenu<Wry>,
 }
 
 #[derive(Display, Debug, EnumString)]
@@ -21,6 +28,7 @@ pub enum MenuID {
     CRAWL_STATUS,
     DEV_SHOW_CONSOLE,
     DISCOVER,
+    EMBEDDING_STATUS,
     JOIN_DISCORD,
     OPEN_CONNECTION_MANAGER,
     OPEN_LENS_MANAGER,
@@ -69,6 +77,10 @@ pub fn get_tray_menu(
         ],
     )?;
 
+    // Filled in once the backend is up & we know which plugins are
+    // installed; see `populate_plugins_menu`.
+    let plugins_menu = Submenu::with_items(app, "Run plugin now", true, &[])?;
+
     let pause_status = MenuItem::with_id(
         app,
         MenuID::CRAWL_STATUS.to_string(),
@@ -76,9 +88,19 @@ pub fn get_tray_menu(
         true,
         None::<&str>,
     )?;
-    // manage the pause status menu item so we can update it later.
+    let embedding_status = MenuItem::with_id(
+        app,
+        MenuID::EMBEDDING_STATUS.to_string(),
+        "Embedding queue: idle",
+        false,
+        None::<&str>,
+    )?;
+
+    // manage the status menu items so we can update them later.
     app.manage(MenuState {
         pause_toggle: pause_status.clone(),
+        embedding_status: embedding_status.clone(),
+        plugins_menu: plugins_menu.clone(),
     });
 
     tray.append_items(&[
@@ -90,6 +112,7 @@ pub fn get_tray_menu(
             Some(user_settings.shortcut.clone()),
         )?,
         &pause_status,
+        &embedding_status,
         &PredefinedMenuItem::separator(app)?,
         &MenuItem::with_id(
             app,
@@ -113,6 +136,7 @@ pub fn get_tray_menu(
             None::<&str>,
         )?,
         &settings_menu,
+        &plugins_menu,
         &MenuItem::with_id(
             app,
             MenuID::OPEN_LOGS_FOLDER.to_string(),
@@ -212,6 +236,33 @@ pub fn get_app_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
     }
 }
 
+/// Fills in the "Run plugin now" submenu once the backend is up.
+pub async fn populate_plugins_menu(app: AppHandle) {
+    let rpc = match app.try_state::<RpcMutex>() {
+        Some(rpc) => rpc,
+        None => return,
+    };
+
+    let plugins = {
+        let rpc = rpc.lock().await;
+        rpc.client.list_plugins().await.unwrap_or_default()
+    };
+
+    if let Some(state) = app.try_state::<MenuState>() {
+        for plugin in plugins {
+            if let Ok(item) = MenuItem::with_id(
+                &app,
+                format!("{RUN_PLUGIN_PREFIX}{}", plugin.title),
+                &plugin.title,
+                true,
+                None::<&str>,
+            ) {
+                let _ = state.plugins_menu.append(&item);
+            }
+        }
+    }
+}
+
 pub fn handle_tray_icon_events(tray: &TrayIcon, event: TrayIconEvent) {
     // Only occurs on Windows.
     if let TrayIconEvent::DoubleClick { .. } = event {
@@ -221,6 +272,21 @@ pub fn handle_tray_icon_events(tray: &TrayIcon, event: TrayIconEvent) {
 }
 
 pub fn handle_tray_menu_events(app: &AppHandle, event: MenuEvent) {
+    // Per-plugin items carry the plugin name in the event id.
+    if let Some(plugin_name) = event.id.as_ref().strip_prefix(RUN_PLUGIN_PREFIX) {
+        let app = app.clone();
+        let plugin_name = plugin_name.to_string();
+        tauri::async_runtime::spawn(async move {
+            if let Some(rpc) = app.try_state::<RpcMutex>() {
+                let rpc = rpc.lock().await;
+                if let Err(err) = rpc.client.run_plugin_now(plugin_name).await {
+                    log::warn!("Error sending RPC: {}", err);
+                }
+            }
+        });
+        return;
+    }
+
     let menu_id = if let Ok(menu_id) = MenuID::from_str(event.id.as_ref()) {
         menu_id
     } else {
@@ -62,6 +62,7 @@ async fn check_for_lens_updates(app_handle: &AppHandle) -> anyhow::Result<()> {
 
     // Get installed lenses
     let installed = get_installed_lenses(app_handle).await?;
+    let auto_update = lens_auto_update(app_handle).await;
 
     // Loop through each one and check if it needs an update
     let mut lenses_updated = 0;
@@ -70,6 +71,13 @@ async fn check_for_lens_updates(app_handle: &AppHandle) -> anyhow::Result<()> {
             // Compare hash from index to local hash
             let latest = lens_index_map.get(&lens.name).expect("already checked");
             if latest.sha != lens.hash {
+                if !auto_update {
+                    // Surface the update as a diff for review instead of
+                    // applying it; the user confirms via a normal install.
+                    let _ = notify_update_available(app_handle, &lens.name).await;
+                    continue;
+                }
+
                 log::info!(
                     "Found newer version of: {}, updating from: {}",
                     lens.name,
@@ -90,6 +98,39 @@ async fn check_for_lens_updates(app_handle: &AppHandle) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Whether lens updates should be applied w/o review.
+async fn lens_auto_update(app_handle: &AppHandle) -> bool {
+    if let Some(rpc) = app_handle.try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        if let Ok(settings) = rpc.client.user_settings().await {
+            return settings.lens_auto_update;
+        }
+    }
+
+    true
+}
+
+/// Asks the server for a diff of the pending update & emits it to the client
+/// for review.
+async fn notify_update_available(app_handle: &AppHandle, name: &str) -> anyhow::Result<()> {
+    let mutex = app_handle
+        .try_state::<rpc::RpcMutex>()
+        .ok_or_else(|| anyhow::anyhow!("Unable to get RpcMutex"))?;
+
+    let rpc = mutex.lock().await;
+    match rpc.client.check_lens_update(name.to_string()).await {
+        Ok(diff) => {
+            log::info!("lens update available for {}, awaiting review", name);
+            let _ = app_handle.emit(ClientEvent::LensUpdateAvailable.as_ref(), diff);
+            Ok(())
+        }
+        Err(err) => {
+            log::error!("Unable to check lens update: {} {}", name, err.to_string());
+            Ok(())
+        }
+    }
+}
+
 fn http_client() -> reqwest::Client {
     reqwest::Client::builder()
         .user_agent(shared::constants::APP_USER_AGENT)
@@ -221,6 +262,22 @@ pub async fn run_lens_updater(win: tauri::Window) -> Result<(), String> {
     }
 }
 
+/// Enable/disable a lens w/o uninstalling it
+#[tauri::command]
+pub async fn toggle_lens(win: tauri::Window, name: &str, enabled: bool) -> Result<(), String> {
+    let app_handle = win.app_handle();
+    if let Some(rpc) = app_handle.try_state::<rpc::RpcMutex>() {
+        let rpc = rpc.lock().await;
+        if let Err(err) = rpc.client.toggle_lens(name.to_string(), enabled).await {
+            log::error!("Unable to toggle lens: {}", err.to_string());
+        } else {
+            let _ = app_handle.emit(ClientEvent::RefreshLensLibrary.as_ref(), Value::Null);
+        }
+    }
+
+    Ok(())
+}
+
 /// Uninstall lens from the backend
 #[tauri::command]
 pub async fn uninstall_lens(win: tauri::Window, name: &str) -> Result<(), String> {
@@ -3,7 +3,10 @@ use crate::{rpc, AppEvent};
 use anyhow::anyhow;
 use jsonrpsee::core::client::Subscription;
 use shared::event::ClientEvent;
-use spyglass_rpc::{ModelDownloadStatusPayload, RpcClient, RpcEvent, RpcEventType};
+use spyglass_rpc::{
+    EmbeddingProgressPayload, ModelDownloadStatusPayload, PluginDisabledPayload, RpcClient,
+    RpcEvent, RpcEventType,
+};
 use tauri::Emitter;
 use tauri::{async_runtime::JoinHandle, AppHandle, Manager};
 use tokio::sync::broadcast;
@@ -35,9 +38,14 @@ async fn _subscribe(app: &AppHandle) -> anyhow::Result<Subscription<RpcEvent>> {
         .subscribe_events(vec![
             RpcEventType::ChatStream,
             RpcEventType::ConnectionSyncFinished,
+            RpcEventType::EmbeddingProgress,
+            RpcEventType::LensBootstrapFinished,
+            RpcEventType::LensBootstrapProgress,
             RpcEventType::LensInstalled,
             RpcEventType::LensUninstalled,
             RpcEventType::ModelDownloadStatus,
+            RpcEventType::PluginDisabled,
+            RpcEventType::PluginStatus,
         ])
         .await?;
 
@@ -83,10 +91,46 @@ async fn setup_notification_handler(app: AppHandle) {
                                 }
                                 None
                             },
+                            RpcEventType::EmbeddingProgress => {
+                                if let Some(payload) = event.payload {
+                                    if let Ok(progress) = serde_json::from_value::<EmbeddingProgressPayload>(payload.clone()) {
+                                        // Update the tray status item & forward
+                                        // the payload to the client windows.
+                                        if let Some(state) = app.try_state::<crate::menu::MenuState>() {
+                                            let label = if progress.remaining > 0 {
+                                                match progress.rate {
+                                                    Some(rate) if rate > 0.0 => format!("Embedding queue: {} remaining ({:.1}/s)", progress.remaining, rate),
+                                                    _ => format!("Embedding queue: {} remaining", progress.remaining),
+                                                }
+                                            } else {
+                                                "Embedding queue: idle".into()
+                                            };
+                                            let _ = state.embedding_status.set_text(label);
+                                        }
+
+                                        let _ = app.emit(ClientEvent::EmbeddingProgress.as_ref(), payload);
+                                    }
+                                }
+                                None
+                            },
                             RpcEventType::ConnectionSyncFinished => Some((
                                 "Sync Completed".into(),
                                 event.payload.map(|p| p.to_string()).unwrap_or_default()
                             )),
+                            // Crawl progress is chatty; forward it straight
+                            // to the client windows for the library page.
+                            RpcEventType::LensBootstrapProgress => {
+                                if let Some(payload) = event.payload {
+                                    let _ = app.emit(ClientEvent::LensBootstrapProgress.as_ref(), payload);
+                                }
+                                None
+                            },
+                            RpcEventType::LensBootstrapFinished => {
+                                if let Some(payload) = event.payload {
+                                    let _ = app.emit(ClientEvent::LensBootstrapFinished.as_ref(), payload);
+                                }
+                                None
+                            },
                             RpcEventType::LensInstalled => {
                                 let _ = app.emit(ClientEvent::LensInstalled.as_ref(), event.payload.clone());
                                 log::debug!("lens installed {:?}", &event.payload);
@@ -101,6 +145,26 @@ async fn setup_notification_handler(app: AppHandle) {
                                 log::debug!("lens removed {:?}", &event.payload);
                                 Some(("Lens Uninstalled".into(), format!("{} was removed from your library", event.payload.map(|p| p.to_string()).unwrap_or_default())))
                             },
+                            RpcEventType::PluginStatus => {
+                                // Statuses are chatty; just nudge the plugin
+                                // manager page to re-fetch `list_plugins`.
+                                let _ = app.emit(ClientEvent::RefreshPluginManager.as_ref(), event.payload.clone());
+                                None
+                            },
+                            RpcEventType::PluginDisabled => {
+                                if let Some(payload) = &event.payload {
+                                    if let Ok(status) = serde_json::from_value::<PluginDisabledPayload>(payload.clone()) {
+                                        Some((
+                                            "Plugin Disabled".into(),
+                                            format!("{} was disabled: {}", status.plugin_name, status.reason)
+                                        ))
+                                    } else {
+                                        None
+                                    }
+                                } else {
+                                    None
+                                }
+                            },
                             RpcEventType::ModelDownloadStatus => {
                                 if let Some(payload) = event.payload {
                                     if let Ok(status) = serde_json::from_value::<ModelDownloadStatusPayload>(payload) {
@@ -133,6 +197,8 @@ async fn setup_notification_handler(app: AppHandle) {
                                     None
                                 }
                             }
+                            // Events we don't subscribe to here.
+                            _ => None,
                         };
 
                         if let Some((title, blurb)) = notif {
@@ -89,6 +89,10 @@ async fn run_and_check_backend(app_handle: AppHandle) {
     let app_events = app_handle.state::<broadcast::Sender<AppEvent>>();
     let _ = app_events.send(AppEvent::BackendConnected);
 
+    // Fill in the per-plugin tray actions now that we can ask the backend
+    // what's installed.
+    tauri::async_runtime::spawn(crate::menu::populate_plugins_menu(app_handle.clone()));
+
     // Watch and restart backend if it goes down
     tauri::async_runtime::spawn(SpyglassServerClient::daemon_eyes(
         rpc_mutex,
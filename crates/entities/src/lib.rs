@@ -3,7 +3,9 @@ pub mod models;
 pub mod test;
 
 pub use sea_orm;
-use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, FromQueryResult, Statement};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, Statement,
+};
 use shared::response::LibraryStats;
 
 pub const BATCH_SIZE: usize = 3000;
@@ -15,8 +17,62 @@ pub struct CountByStatus {
     status: String,
 }
 
+#[derive(Debug, FromQueryResult)]
+struct DbSize {
+    size: i64,
+}
+
+/// Size on disk of the database, from sqlite's own page accounting.
+pub async fn get_db_size(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let size = DbSize::find_by_statement(Statement::from_string(
+        db.get_database_backend(),
+        r#"SELECT page_count * page_size as "size" FROM pragma_page_count(), pragma_page_size();"#
+            .to_string(),
+    ))
+    .one(db)
+    .await?;
+
+    Ok(size.map(|row| row.size.max(0) as u64).unwrap_or(0))
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct DomainCount {
+    pub domain: String,
+    pub count: i64,
+}
+
+/// Visit counts by domain across everything queued or indexed since the
+/// given time, most visited first. Used to propose lenses from browsing
+/// history.
+pub async fn get_domain_counts(
+    db: &DatabaseConnection,
+    min_visits: u64,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<DomainCount>, DbErr> {
+    let since = since.unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+    DomainCount::find_by_statement(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+            SELECT domain as "domain", count(*) as "count"
+            FROM (
+                SELECT domain, created_at FROM crawl_queue
+                UNION ALL
+                SELECT domain, created_at FROM indexed_document
+            )
+            WHERE domain != "" AND created_at >= $1
+            GROUP BY domain
+            HAVING count(*) >= $2
+            ORDER BY count(*) DESC;
+        "#,
+        vec![since.into(), (min_visits as i64).into()],
+    ))
+    .all(db)
+    .await
+}
+
 pub async fn get_library_stats(
     db: &DatabaseConnection,
+    index_size_bytes: u64,
 ) -> Result<HashMap<String, LibraryStats>, DbErr> {
     let counts = CountByStatus::find_by_statement(Statement::from_string(
         db.get_database_backend(),
@@ -35,7 +91,17 @@ pub async fn get_library_stats(
             LEFT JOIN document_tag on indexed_document.id = document_tag.indexed_document_id
             LEFT JOIN tags on tags.id = document_tag.tag_id
             WHERE tags.label = "lens"
-            group by lower(tags.value), lower(status);
+            group by lower(tags.value), lower(status)
+            UNION
+            SELECT
+                count(*) as "count", tags.value as "name", "UserQueued" as status
+            FROM crawl_queue
+            LEFT JOIN crawl_tag on crawl_queue.id = crawl_tag.crawl_queue_id
+            LEFT JOIN tags on tags.id = crawl_tag.tag_id
+            WHERE tags.label = "lens"
+                AND crawl_queue.initiated_by IS NOT NULL
+                AND status IN ("Queued", "Processing")
+            GROUP BY lower(tags.value);
         "#
         .to_string(),
     ))
@@ -61,9 +127,33 @@ pub async fn get_library_stats(
             "Indexed" => {
                 entry.indexed += count.count;
             }
+            "UserQueued" => {
+                entry.user_enqueued += count.count;
+            }
             _ => {}
         }
     }
 
+    // Attach the recrawl scheduler's refresh times for lenses that track
+    // them.
+    for lens in models::lens::Entity::find().all(db).await? {
+        if let Some(entry) = stats.get_mut(&lens.name) {
+            entry.last_refreshed = lens.last_refreshed;
+            entry.next_refresh = lens.next_refresh;
+        }
+    }
+
+    // Apportion the on-disk totals by document count. Neither tantivy nor
+    // sqlite track per-document sizes, so this is an approximation.
+    let db_size_bytes = get_db_size(db).await?;
+    let total_indexed: i64 = stats.values().map(|entry| entry.indexed as i64).sum();
+    if total_indexed > 0 {
+        for entry in stats.values_mut() {
+            let share = entry.indexed as f64 / total_indexed as f64;
+            entry.index_size_bytes = (index_size_bytes as f64 * share) as u64;
+            entry.db_size_bytes = (db_size_bytes as f64 * share) as u64;
+        }
+    }
+
     Ok(stats)
 }
@@ -0,0 +1,70 @@
+use sea_orm::{prelude::*, QueryOrder, Set};
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Eq)]
+#[sea_orm(table_name = "chat_messages")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    #[sea_orm(indexed)]
+    pub session_id: i64,
+    /// Who sent the message: "system", "user" or "assistant", matching the
+    /// serialized form of `shared::llm::ChatRole`.
+    pub role: String,
+    pub content: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    ChatSession,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::ChatSession => Entity::belongs_to(super::chat_session::Entity)
+                .from(Column::SessionId)
+                .to(super::chat_session::Column::Id)
+                .into(),
+        }
+    }
+}
+
+impl Related<super::chat_session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatSession.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            created_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
+
+pub async fn append(
+    db: &DatabaseConnection,
+    session_id: i64,
+    role: &str,
+    content: &str,
+) -> Result<Model, DbErr> {
+    let mut message = ActiveModel::new();
+    message.session_id = Set(session_id);
+    message.role = Set(role.to_string());
+    message.content = Set(content.to_string());
+    message.insert(db).await
+}
+
+/// Messages for a session, in conversation order.
+pub async fn for_session(db: &DatabaseConnection, session_id: i64) -> Result<Vec<Model>, DbErr> {
+    Entity::find()
+        .filter(Column::SessionId.eq(session_id))
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await
+}
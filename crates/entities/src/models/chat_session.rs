@@ -0,0 +1,94 @@
+use sea_orm::{prelude::*, QueryOrder, Set};
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Eq)]
+#[sea_orm(table_name = "chat_sessions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Document the conversation is about.
+    pub doc_id: String,
+    pub created_at: DateTimeUtc,
+    /// Bumped whenever a message is appended; retention pruning keys off
+    /// this so active conversations stick around.
+    pub updated_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    ChatMessage,
+}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::ChatMessage => Entity::has_many(super::chat_message::Entity).into(),
+        }
+    }
+}
+
+impl Related<super::chat_message::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatMessage.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            created_at: Set(chrono::Utc::now()),
+            updated_at: Set(chrono::Utc::now()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
+
+pub async fn create(db: &DatabaseConnection, doc_id: &str) -> Result<Model, DbErr> {
+    let mut session = ActiveModel::new();
+    session.doc_id = Set(doc_id.to_string());
+    session.insert(db).await
+}
+
+/// All sessions, most recently used first.
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<Model>, DbErr> {
+    Entity::find()
+        .order_by_desc(Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// Bumps a session's `updated_at` so retention pruning sees it as active.
+pub async fn touch(db: &DatabaseConnection, session_id: i64) -> Result<(), DbErr> {
+    let _ = Entity::update_many()
+        .col_expr(Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(Column::Id.eq(session_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Removes sessions (& their messages) that haven't been touched in
+/// `retention_days` days. Returns the number of sessions pruned.
+pub async fn prune(db: &DatabaseConnection, retention_days: u64) -> Result<u64, DbErr> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
+    let stale = Entity::find()
+        .filter(Column::UpdatedAt.lt(cutoff))
+        .all(db)
+        .await?;
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let ids = stale.iter().map(|session| session.id).collect::<Vec<i64>>();
+    let _ = super::chat_message::Entity::delete_many()
+        .filter(super::chat_message::Column::SessionId.is_in(ids.clone()))
+        .exec(db)
+        .await?;
+
+    let result = Entity::delete_many()
+        .filter(Column::Id.is_in(ids))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
@@ -2,8 +2,8 @@ use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
 use sea_orm::entity::prelude::*;
 use sea_orm::sea_query::{OnConflict, Query, SqliteQueryBuilder};
 use sea_orm::{
-    sea_query, ConnectionTrait, FromJsonQueryResult, FromQueryResult, InsertResult, QueryTrait,
-    Set, Statement,
+    sea_query, ConnectionTrait, FromJsonQueryResult, FromQueryResult, InsertResult, JoinType,
+    QuerySelect, QueryTrait, Set, Statement,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -14,10 +14,13 @@ use super::crawl_tag;
 use super::indexed_document;
 use super::tag::{self, get_or_create, TagPair};
 use crate::BATCH_SIZE;
-use shared::config::{LensConfig, LensRule, Limit, UrlSanitizeConfig, UserSettings};
+use shared::config::{LensConfig, LensRule, Limit, UrlRegexAction, UrlSanitizeConfig, UserSettings};
 use shared::regex::{regex_for_domain, regex_for_prefix};
 
 const MAX_RETRIES: u8 = 5;
+/// Base delay applied when a server rate limits us without a `Retry-After`
+/// hint. Doubled for each retry of the task.
+const RATE_LIMIT_BACKOFF_MS: i64 = 60 * 1000;
 
 #[derive(Debug, Error)]
 pub enum EnqueueError {
@@ -101,6 +104,12 @@ pub struct Model {
     /// When this task was last updated.
     pub updated_at: DateTimeUtc,
     pub pipeline: Option<String>,
+    /// Request id of the user action that enqueued this task. Background
+    /// work (bootstrapping, recrawls, etc.) leaves this unset.
+    pub initiated_by: Option<String>,
+    /// Set when the server rate limited us (HTTP 429). Task should not be
+    /// handed out again before this timestamp.
+    pub retry_after: Option<DateTimeUtc>,
 }
 
 impl Related<super::tag::Entity> for Entity {
@@ -222,6 +231,7 @@ fn gen_dequeue_sql(db: &DatabaseConnection, user_settings: &UserSettings) -> Sta
         vec![
             user_settings.domain_crawl_limit.value().into(),
             user_settings.inflight_domain_limit.value().into(),
+            chrono::Utc::now().into(),
         ],
     )
 }
@@ -256,10 +266,14 @@ fn create_ruleset_from_lens(lens: &LensConfig) -> LensRuleSets {
             LensRule::SkipURL(_) => {
                 skip_list.push(rule.to_regex());
             }
-            LensRule::LimitURLDepth(_, _) => {
+            LensRule::LimitURLDepth(_, _) | LensRule::LimitURLDepthFrom(_, _) => {
                 restrict_list.push(rule.to_regex());
             }
             LensRule::SanitizeUrls(_, _) => {}
+            LensRule::UrlRegex { action, .. } => match action {
+                UrlRegexAction::Allow => allow_list.push(rule.to_regex()),
+                UrlRegexAction::Skip => skip_list.push(rule.to_regex()),
+            },
         }
     }
 
@@ -286,6 +300,37 @@ pub async fn num_of_files_in_progress(db: &DatabaseConnection) -> anyhow::Result
         .await
 }
 
+/// A lens & how many of its tasks completed in some window.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Eq)]
+pub struct CompletedPerLens {
+    pub lens: String,
+    pub count: i64,
+}
+
+/// How many tasks completed per lens since `since`, used to report crawl
+/// batches. Tasks w/o a lens tag aren't counted.
+pub async fn num_completed_by_lens(
+    db: &DatabaseConnection,
+    since: DateTimeUtc,
+) -> anyhow::Result<Vec<CompletedPerLens>, DbErr> {
+    Entity::find()
+        .select_only()
+        .column_as(tag::Column::Value, "lens")
+        .column_as(Column::Id.count(), "count")
+        .join(
+            JoinType::InnerJoin,
+            super::crawl_tag::Relation::CrawlQueue.def().rev(),
+        )
+        .join(JoinType::InnerJoin, super::crawl_tag::Relation::Tag.def())
+        .filter(tag::Column::Label.eq(tag::TagType::Lens.to_string()))
+        .filter(Column::Status.eq(CrawlStatus::Completed))
+        .filter(Column::UpdatedAt.gte(since))
+        .group_by(tag::Column::Value)
+        .into_model::<CompletedPerLens>()
+        .all(db)
+        .await
+}
+
 /// Get the next url in the crawl queue
 pub async fn dequeue(
     db: &DatabaseConnection,
@@ -301,18 +346,35 @@ pub async fn dequeue(
         }
     }
 
-    // Prioritize any bootstrapping tasks first.
+    // Anything the user explicitly asked for is handled before background
+    // work, then prioritize any bootstrapping tasks.
+    let now = chrono::Utc::now();
     let entity = {
-        let result = Entity::find()
-            .filter(Column::CrawlType.eq(CrawlType::Bootstrap))
+        let user_initiated = Entity::find()
+            .filter(Column::InitiatedBy.is_not_null())
             .filter(Column::Status.eq(CrawlStatus::Queued))
+            .filter(Column::RetryAfter.is_null().or(Column::RetryAfter.lte(now)))
             .one(db)
             .await?;
 
+        let result = if user_initiated.is_some() {
+            user_initiated
+        } else {
+            Entity::find()
+                .filter(Column::CrawlType.eq(CrawlType::Bootstrap))
+                .filter(Column::Status.eq(CrawlStatus::Queued))
+                .filter(Column::RetryAfter.is_null().or(Column::RetryAfter.lte(now)))
+                .one(db)
+                .await?
+        };
+
         if let Some(task) = result {
             Some(task)
         } else {
-            // Otherwise, grab a URL off the stack & send it back.
+            // Otherwise, grab a URL off the stack & send it back. Domains are
+            // served round-robin (least recently crawled domain first, oldest
+            // task within that domain) so a lens with a huge queue can't
+            // starve everything else.
             Entity::find()
                 .from_raw_sql(gen_dequeue_sql(db, user_settings))
                 .one(db)
@@ -352,11 +414,25 @@ pub async fn dequeue_files(
         }
     }
 
-    let entity = Entity::find()
-        .filter(Column::Status.eq(CrawlStatus::Queued))
-        .filter(Column::Url.starts_with("file:"))
-        .one(db)
-        .await?;
+    // Prefer user-initiated file imports over background scans.
+    let entity = {
+        let user_initiated = Entity::find()
+            .filter(Column::InitiatedBy.is_not_null())
+            .filter(Column::Status.eq(CrawlStatus::Queued))
+            .filter(Column::Url.starts_with("file:"))
+            .one(db)
+            .await?;
+
+        if user_initiated.is_some() {
+            user_initiated
+        } else {
+            Entity::find()
+                .filter(Column::Status.eq(CrawlStatus::Queued))
+                .filter(Column::Url.starts_with("file:"))
+                .one(db)
+                .await?
+        }
+    };
 
     // Grab new entity and immediately mark in-progress
     if let Some(task) = entity {
@@ -389,6 +465,38 @@ pub struct EnqueueSettings {
     pub tags: Vec<TagPair>,
     pub force_allow: bool,
     pub is_recrawl: bool,
+    /// Set when a user request (e.g. an RPC enqueue/import) kicked off these
+    /// tasks. User-initiated tasks are prioritized over background work.
+    pub initiated_by: Option<String>,
+}
+
+/// Query parameters that only exist for tracking & should never change the
+/// content of a page. `utm_*` params are matched by prefix.
+const TRACKING_PARAMS: [&str; 8] = [
+    "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "msclkid", "twclid",
+];
+
+/// Normalize a URL so variants that differ only by tracking params or
+/// fragments collapse into a single document: strips the fragment, drops
+/// known tracking parameters & sorts whatever query params remain.
+pub fn normalize_url(url: &mut Url) {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return;
+    }
+    url.set_fragment(None);
+
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name.as_ref()))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+
+    if params.is_empty() {
+        url.set_query(None);
+    } else {
+        params.sort();
+        url.query_pairs_mut().clear().extend_pairs(params);
+    }
 }
 
 fn url_is_allowed(
@@ -477,7 +585,13 @@ fn filter_urls(
             // Always ignore fragments, otherwise crawling
             // https://wikipedia.org/Rust#Blah would be considered different than
             // https://wikipedia.org/Rust
-            url.set_fragment(None);
+            if settings.disable_url_normalization {
+                url.set_fragment(None);
+            } else {
+                // Also strips tracking params & sorts the query string so
+                // variants of the same page dedupe to one task.
+                normalize_url(&mut url);
+            }
 
             for (regex, config) in &sanitize_rules {
                 if regex.is_match(url.as_str()) {
@@ -516,6 +630,13 @@ fn filter_urls(
         })
         .collect::<Vec<String>>();
 
+    // Variants of the same URL may have collapsed during normalization.
+    let mut seen: HashSet<String> = HashSet::with_capacity(res.len());
+    let res = res
+        .into_iter()
+        .filter(|url| seen.insert(url.clone()))
+        .collect::<Vec<String>>();
+
     Ok(res)
 }
 
@@ -534,6 +655,7 @@ pub async fn enqueue_local_files(
                 status: Set(CrawlStatus::Initial),
                 url: Set(url.to_string()),
                 pipeline: Set(pipeline.clone()),
+                initiated_by: Set(overrides.initiated_by.clone()),
                 ..Default::default()
             })
             .collect::<Vec<ActiveModel>>();
@@ -615,6 +737,7 @@ pub async fn enqueue_all<C: ConnectionTrait>(
                         crawl_type: Set(overrides.crawl_type.clone()),
                         url: Set(url.to_string()),
                         pipeline: Set(pipeline.clone()),
+                        initiated_by: Set(overrides.initiated_by.clone()),
                         ..Default::default()
                     });
                 }
@@ -722,6 +845,54 @@ pub async fn mark_failed(db: &DatabaseConnection, id: i64, retry: bool) {
     }
 }
 
+/// Requeue a task that was rate limited (HTTP 429) with a not-before
+/// timestamp. If the server sent a `Retry-After` hint, use that; otherwise
+/// progressively back off based on the number of retries so far. Any other
+/// queued tasks for the same domain are pushed back as well so we don't keep
+/// hammering a host that's already told us to slow down.
+pub async fn mark_rate_limited(db: &DatabaseConnection, id: i64, retry_after_ms: i64) {
+    if let Ok(Some(crawl)) = Entity::find_by_id(id).one(db).await {
+        if crawl.num_retries > MAX_RETRIES {
+            log::warn!("{} hit max retries while rate limited, giving up", crawl.url);
+            mark_failed(db, id, false).await;
+            return;
+        }
+
+        let delay_ms = if retry_after_ms > 0 {
+            retry_after_ms
+        } else {
+            RATE_LIMIT_BACKOFF_MS * 2_i64.pow(crawl.num_retries as u32)
+        };
+
+        let retry_after = chrono::Utc::now() + chrono::Duration::milliseconds(delay_ms);
+        log::warn!(
+            "`{}` is cooling down for {}s after a 429, requeueing {}",
+            crawl.domain,
+            delay_ms / 1000,
+            crawl.url
+        );
+
+        let mut updated: ActiveModel = crawl.clone().into();
+        updated.num_retries = Set(crawl.num_retries + 1);
+        updated.status = Set(CrawlStatus::Queued);
+        updated.retry_after = Set(Some(retry_after));
+        let _ = updated.update(db).await;
+
+        // Push back anything else queued for this domain & move it to the
+        // back of the queue so other domains aren't blocked.
+        let _ = Entity::update_many()
+            .col_expr(Column::RetryAfter, sea_query::Expr::value(retry_after))
+            .col_expr(
+                Column::UpdatedAt,
+                sea_query::Expr::value(chrono::Utc::now()),
+            )
+            .filter(Column::Domain.eq(crawl.domain))
+            .filter(Column::Status.eq(CrawlStatus::Queued))
+            .exec(db)
+            .await;
+    }
+}
+
 pub async fn insert_tags_by_id<C: ConnectionTrait>(
     db: &C,
     docs: &[Model],
@@ -872,6 +1043,28 @@ pub async fn update_or_remove_task(
 }
 
 /// Delete all crawl tasks associated with a lens.
+/// Marks every crawl entry tagged w/ the lens as queued again, e.g. when the
+/// lens' recrawl interval elapses. Returns the number of entries requeued.
+pub async fn mark_recrawl_by_lens(
+    db: &DatabaseConnection,
+    name: &str,
+) -> Result<u64, sea_orm::DbErr> {
+    let mut rows_affected = 0;
+    if let Ok(ids) = find_by_lens(db.clone(), name).await {
+        let dbids: Vec<i64> = ids.iter().map(|item| item.id).collect();
+        for chunk in dbids.chunks(BATCH_SIZE) {
+            let res = Entity::update_many()
+                .col_expr(Column::Status, sea_query::Expr::value(CrawlStatus::Queued))
+                .filter(Column::Id.is_in(chunk.to_owned()))
+                .exec(db)
+                .await?;
+            rows_affected += res.rows_affected;
+        }
+    }
+
+    Ok(rows_affected)
+}
+
 pub async fn delete_by_lens(db: DatabaseConnection, name: &str) -> Result<(), sea_orm::DbErr> {
     if let Ok(ids) = find_by_lens(db.clone(), name).await {
         let dbids: Vec<i64> = ids.iter().map(|item| item.id).collect();
@@ -1017,10 +1210,11 @@ mod test {
     use shared::regex::{regex_for_robots, WildcardType};
 
     use crate::models::crawl_queue::{CrawlStatus, CrawlType};
+    use crate::models::tag::TagType;
     use crate::models::{crawl_queue, indexed_document};
     use crate::test::setup_test_db;
 
-    use super::{filter_urls, gen_dequeue_sql, EnqueueSettings};
+    use super::{filter_urls, gen_dequeue_sql, normalize_url, EnqueueSettings};
 
     #[tokio::test]
     async fn test_insert() {
@@ -1054,7 +1248,7 @@ mod test {
         let sql = gen_dequeue_sql(&db, &settings);
         assert_eq!(
             sql.to_string(),
-            "WITH\nindexed AS (\n    SELECT\n        domain,\n        count(*) as count\n    FROM indexed_document\n    GROUP BY domain\n),\ninflight AS (\n    SELECT\n        domain,\n        count(*) as count\n    FROM crawl_queue\n    WHERE status = \"Processing\"\n    GROUP BY domain\n)\nSELECT\n    cq.*\nFROM crawl_queue cq\nLEFT JOIN indexed ON indexed.domain = cq.domain\nLEFT JOIN inflight ON inflight.domain = cq.domain\nWHERE\n    COALESCE(indexed.count, 0) < 500000 AND\n    COALESCE(inflight.count, 0) < 2 AND\n    status = \"Queued\" and\n    url not like \"file%\"\nORDER BY\n    cq.updated_at ASC"
+            "WITH\nindexed AS (\n    SELECT\n        domain,\n        count(*) as count\n    FROM indexed_document\n    GROUP BY domain\n),\ninflight AS (\n    SELECT\n        domain,\n        count(*) as count\n    FROM crawl_queue\n    WHERE status = \"Processing\"\n    GROUP BY domain\n),\nlast_crawl AS (\n    SELECT\n        domain,\n        max(updated_at) as last_crawl\n    FROM crawl_queue\n    WHERE status IN (\"Processing\", \"Completed\", \"Failed\")\n    GROUP BY domain\n)\nSELECT\n    cq.*\nFROM crawl_queue cq\nLEFT JOIN indexed ON indexed.domain = cq.domain\nLEFT JOIN inflight ON inflight.domain = cq.domain\nLEFT JOIN last_crawl ON last_crawl.domain = cq.domain\nWHERE\n    COALESCE(indexed.count, 0) < 500000 AND\n    COALESCE(inflight.count, 0) < 2 AND\n    status = \"Queued\" and\n    url not like \"file%\"\nORDER BY\n    COALESCE(last_crawl.last_crawl, \"\") ASC,\n    cq.updated_at ASC"
         );
     }
 
@@ -1088,6 +1282,107 @@ mod test {
         assert_eq!(crawl.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_dequeue_round_robins_domains() {
+        let settings = UserSettings::default();
+        let db = setup_test_db().await;
+
+        // One domain with a big backlog, one with a tiny one.
+        for idx in 0..6 {
+            let _ = crawl_queue::ActiveModel {
+                domain: Set("big.example.com".into()),
+                url: Set(format!("https://big.example.com/page-{idx}")),
+                status: Set(CrawlStatus::Queued),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await;
+        }
+
+        for idx in 0..2 {
+            let _ = crawl_queue::ActiveModel {
+                domain: Set("small.example.com".into()),
+                url: Set(format!("https://small.example.com/page-{idx}")),
+                status: Set(CrawlStatus::Queued),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await;
+        }
+
+        let mut order = Vec::new();
+        while let Some(task) = crawl_queue::dequeue(&db, &settings).await.unwrap() {
+            order.push(task.domain.clone());
+            let _ = crawl_queue::mark_done(&db, task.id, None).await;
+        }
+
+        // The small domain shouldn't have to wait for the big one's backlog.
+        assert_eq!(order.len(), 8);
+        assert_eq!(
+            &order[0..4],
+            &[
+                "big.example.com".to_string(),
+                "small.example.com".to_string(),
+                "big.example.com".to_string(),
+                "small.example.com".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        // Fragments & tracking params are stripped, remaining params sorted.
+        let mut url =
+            Url::parse("https://example.com/page?utm_source=feed&b=2&fbclid=abc&a=1#section")
+                .unwrap();
+        normalize_url(&mut url);
+        assert_eq!(url.as_str(), "https://example.com/page?a=1&b=2");
+
+        // Query is dropped entirely when only tracking params are present.
+        let mut url = Url::parse("https://example.com/page?gclid=123").unwrap();
+        normalize_url(&mut url);
+        assert_eq!(url.as_str(), "https://example.com/page");
+
+        // Non-http(s) URLs are left alone.
+        let mut url = Url::parse("file:///tmp/notes.md#heading").unwrap();
+        normalize_url(&mut url);
+        assert_eq!(url.as_str(), "file:///tmp/notes.md#heading");
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_normalizes_url_variants() {
+        let settings = UserSettings::default();
+        let db = setup_test_db().await;
+        let urls: Vec<String> = vec![
+            "https://oldschool.runescape.wiki/w/Abyssal_whip?utm_source=twitter".into(),
+            "https://oldschool.runescape.wiki/w/Abyssal_whip#History".into(),
+            "https://oldschool.runescape.wiki/w/Abyssal_whip".into(),
+        ];
+        let lens = LensConfig {
+            domains: vec!["oldschool.runescape.wiki".into()],
+            ..Default::default()
+        };
+
+        crawl_queue::enqueue_all(
+            &db,
+            &urls,
+            &[lens],
+            &settings,
+            &Default::default(),
+            Option::None,
+        )
+        .await
+        .unwrap();
+
+        // All three variants should collapse into a single row.
+        let crawl = crawl_queue::Entity::find().all(&db).await.unwrap();
+        assert_eq!(crawl.len(), 1);
+        assert_eq!(
+            crawl[0].url,
+            "https://oldschool.runescape.wiki/w/Abyssal_whip"
+        );
+    }
+
     #[tokio::test]
     async fn test_enqueue_with_recrawl() {
         let settings = UserSettings::default();
@@ -1190,6 +1485,95 @@ mod test {
         assert_eq!(queue.unwrap().url, url[0]);
     }
 
+    #[tokio::test]
+    async fn test_dequeue_prefers_user_initiated() {
+        let settings = UserSettings::default();
+        let db = setup_test_db().await;
+
+        // Background crawl queued first.
+        let background = crawl_queue::ActiveModel {
+            domain: Set("oldschool.runescape.wiki".into()),
+            url: Set("https://oldschool.runescape.wiki/".into()),
+            status: Set(CrawlStatus::Queued),
+            ..Default::default()
+        };
+        background.insert(&db).await.expect("Unable to insert");
+
+        // User-initiated import queued afterwards.
+        let user_initiated = crawl_queue::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/import".into()),
+            status: Set(CrawlStatus::Queued),
+            initiated_by: Set(Some("req-1234".into())),
+            ..Default::default()
+        };
+        user_initiated.insert(&db).await.expect("Unable to insert");
+
+        // The user-initiated task should be handed out first.
+        let first = crawl_queue::dequeue(&db, &settings)
+            .await
+            .unwrap()
+            .expect("Expected a task");
+        assert_eq!(first.url, "https://example.com/import");
+        assert_eq!(first.initiated_by, Some("req-1234".into()));
+
+        let second = crawl_queue::dequeue(&db, &settings)
+            .await
+            .unwrap()
+            .expect("Expected a task");
+        assert_eq!(second.url, "https://oldschool.runescape.wiki/");
+        assert_eq!(second.initiated_by, None);
+    }
+
+    #[tokio::test]
+    async fn test_mark_rate_limited() {
+        let settings = UserSettings::default();
+        let db = setup_test_db().await;
+
+        let task = crawl_queue::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/throttled".into()),
+            status: Set(CrawlStatus::Processing),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+
+        // Another task for the same domain should be pushed back as well.
+        let sibling = crawl_queue::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/other".into()),
+            status: Set(CrawlStatus::Queued),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+
+        crawl_queue::mark_rate_limited(&db, task.id, 30 * 1000).await;
+
+        let task = crawl_queue::Entity::find_by_id(task.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .expect("task should exist");
+        assert_eq!(task.status, CrawlStatus::Queued);
+        assert_eq!(task.num_retries, 1);
+        assert!(task.retry_after.expect("retry_after set") > chrono::Utc::now());
+
+        let sibling = crawl_queue::Entity::find_by_id(sibling.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .expect("task should exist");
+        assert!(sibling.retry_after.is_some());
+
+        // Nothing should be handed out while the domain is cooling down.
+        let queue = crawl_queue::dequeue(&db, &settings).await.unwrap();
+        assert!(queue.is_none());
+    }
+
     #[tokio::test]
     async fn test_dequeue_with_limit() {
         let settings = UserSettings {
@@ -1281,6 +1665,32 @@ mod test {
         assert!(block_list.is_match(invalid));
     }
 
+    #[tokio::test]
+    async fn test_create_ruleset_with_url_regex() {
+        let lens =
+            LensConfig::from_string(include_str!("../../../../fixtures/lens/url_regex.ron"))
+                .unwrap();
+
+        let rules = super::create_ruleset_from_lens(&lens);
+        let allow_list = regex::RegexSet::new(rules.allow_list).unwrap();
+        let block_list = regex::RegexSet::new(rules.skip_list).unwrap();
+
+        let article = "https://wiki.example.com/w/Rust";
+        assert!(allow_list.is_match(article));
+        assert!(!block_list.is_match(article));
+
+        // Talk pages & edit views match both lists; the skip wins.
+        let empty = regex::RegexSet::empty();
+        for url in [
+            "https://wiki.example.com/w/Talk:Rust",
+            "https://wiki.example.com/w/Rust?action=edit",
+        ] {
+            assert!(allow_list.is_match(url));
+            assert!(block_list.is_match(url));
+            assert!(!super::url_is_allowed(url, &allow_list, &empty, &block_list));
+        }
+    }
+
     #[tokio::test]
     async fn test_create_ruleset_with_limits() {
         let lens =
@@ -1390,4 +1800,60 @@ mod test {
         assert_eq!(res.id, first.id);
         assert_eq!(2, all_tasks.len());
     }
+
+    #[tokio::test]
+    async fn test_num_completed_by_lens() {
+        let db = setup_test_db().await;
+
+        let task = crawl_queue::ActiveModel {
+            domain: Set("example.com".to_string()),
+            url: Set("https://example.com/one".to_string()),
+            status: Set(CrawlStatus::Completed),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+        let _ = task
+            .insert_tags(&db, &[(TagType::Lens, "wiki".to_string())])
+            .await;
+
+        // Failed tasks & tasks w/o a lens tag shouldn't be counted.
+        let failed = crawl_queue::ActiveModel {
+            domain: Set("example.com".to_string()),
+            url: Set("https://example.com/two".to_string()),
+            status: Set(CrawlStatus::Failed),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+        let _ = failed
+            .insert_tags(&db, &[(TagType::Lens, "wiki".to_string())])
+            .await;
+        let _ = crawl_queue::ActiveModel {
+            domain: Set("example.com".to_string()),
+            url: Set("https://example.com/three".to_string()),
+            status: Set(CrawlStatus::Completed),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+
+        let since = chrono::Utc::now() - chrono::Duration::hours(1);
+        let counts = super::num_completed_by_lens(&db, since)
+            .await
+            .expect("Unable to query");
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].lens, "wiki");
+        assert_eq!(counts[0].count, 1);
+
+        // Nothing should match a window that starts after the completions.
+        let counts =
+            super::num_completed_by_lens(&db, chrono::Utc::now() + chrono::Duration::hours(1))
+                .await
+                .expect("Unable to query");
+        assert!(counts.is_empty());
+    }
 }
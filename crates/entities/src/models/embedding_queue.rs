@@ -31,6 +31,11 @@ pub struct Model {
     pub created_at: DateTimeUtc,
     /// When this task was last updated.
     pub updated_at: DateTimeUtc,
+    /// Request id of the user action that generated this work, if any.
+    pub initiated_by: Option<String>,
+    /// Whether this job was completed by reusing cached vectors instead of
+    /// running the model.
+    pub used_cache: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -121,7 +126,12 @@ pub struct Job {
     pub id: i64,
 }
 
-pub async fn check_for_embedding_jobs(db: &DatabaseConnection) -> Result<Option<Job>, DbErr> {
+/// Claims up to `limit` queued jobs for processing. Claimed as one batch so
+/// the worker can embed them in a single model pass.
+pub async fn check_for_embedding_jobs(
+    db: &DatabaseConnection,
+    limit: u64,
+) -> Result<Vec<Job>, DbErr> {
     let count = Entity::find()
         .filter(Column::Status.eq(QueueStatus::Processing))
         .count(db)
@@ -129,12 +139,13 @@ pub async fn check_for_embedding_jobs(db: &DatabaseConnection) -> Result<Option<
 
     if count >= 3 {
         log::debug!("Waiting for previous embedding tasks to finish");
-        return Ok(None);
+        return Ok(Vec::new());
     }
 
     let query = Statement::from_string(
         db.get_database_backend(),
-        r#"
+        format!(
+            r#"
            UPDATE embedding_queue AS eq
         SET
             status = 'Processing',
@@ -144,26 +155,93 @@ pub async fn check_for_embedding_jobs(db: &DatabaseConnection) -> Result<Option<
                 id
             FROM embedding_queue
             WHERE status = 'Queued'
-            ORDER By created_at
-            LIMIT 1
+            ORDER By (initiated_by IS NOT NULL) DESC, created_at
+            LIMIT {limit}
         )
         RETURNING id"#
-            .to_string(),
+        ),
     );
 
-    Job::find_by_statement(query).one(db).await
+    Job::find_by_statement(query).all(db).await
+}
+
+/// Attribute any queued embedding work for a document to the user request
+/// that kicked it off. Used so the embedding scheduler can prioritize
+/// user-initiated work.
+pub async fn mark_initiated_by(db: &DatabaseConnection, document_id: &str, request_id: &str) {
+    let _ = Entity::update_many()
+        .col_expr(Column::InitiatedBy, Expr::value(request_id))
+        .filter(Column::DocumentId.eq(document_id))
+        .filter(Column::Status.eq(QueueStatus::Queued))
+        .exec(db)
+        .await;
+}
+
+/// Requeues any jobs stuck in `Processing`, e.g. after a worker was
+/// interrupted mid-batch. Returns the number of jobs requeued.
+pub async fn reset_processing(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let result = Entity::update_many()
+        .col_expr(Column::Status, Expr::value(QueueStatus::Queued))
+        .filter(Column::Status.eq(QueueStatus::Processing))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected)
 }
 
-pub async fn mark_done(db: &DatabaseConnection, id: i64) {
+pub async fn mark_done(db: &DatabaseConnection, id: i64, used_cache: bool) {
     if let Ok(Some(embedding)) = Entity::find_by_id(id).one(db).await {
         let mut updated: ActiveModel = embedding.clone().into();
         updated.status = Set(QueueStatus::Completed);
         updated.content = Set(None);
         updated.errors = Set(None);
+        updated.used_cache = Set(used_cache);
         let _ = updated.update(db).await;
     }
 }
 
+#[derive(Debug, FromQueryResult)]
+pub struct CacheStats {
+    pub completed: i64,
+    pub cache_hits: i64,
+}
+
+/// How many completed embedding jobs reused cached vectors vs ran the model.
+pub async fn cache_stats(db: &DatabaseConnection) -> Result<Option<CacheStats>, DbErr> {
+    let query = Statement::from_string(
+        db.get_database_backend(),
+        r#"SELECT
+            COUNT(*) as "completed",
+            COUNT(CASE WHEN used_cache THEN 1 END) as "cache_hits"
+        FROM embedding_queue
+        WHERE status = 'Completed'"#
+            .to_string(),
+    );
+
+    CacheStats::find_by_statement(query).one(db).await
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct QueueCounts {
+    pub remaining: i64,
+    pub completed: i64,
+}
+
+/// How many embedding jobs are still waiting (queued or processing) vs
+/// completed, used for progress reporting.
+pub async fn queue_counts(db: &DatabaseConnection) -> Result<Option<QueueCounts>, DbErr> {
+    let query = Statement::from_string(
+        db.get_database_backend(),
+        r#"SELECT
+            COUNT(CASE WHEN status IN ('Queued', 'Processing') THEN 1 END) as "remaining",
+            COUNT(CASE WHEN status = 'Completed' THEN 1 END) as "completed"
+        FROM embedding_queue"#
+            .to_string(),
+    );
+
+    QueueCounts::find_by_statement(query).one(db).await
+}
+
 pub async fn mark_failed(db: &DatabaseConnection, id: i64, error: Option<String>) {
     if let Ok(Some(embedding)) = Entity::find_by_id(id).one(db).await {
         let mut updated: ActiveModel = embedding.clone().into();
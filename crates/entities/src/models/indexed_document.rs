@@ -30,6 +30,14 @@ pub struct Model {
     pub created_at: DateTimeUtc,
     /// When this was last updated
     pub updated_at: DateTimeUtc,
+    /// Hash of the content that was last indexed, used to skip reindexing
+    /// when a recrawl finds nothing changed.
+    pub content_hash: Option<String>,
+    /// Cached LLM-generated summary of the document.
+    pub summary: Option<String>,
+    /// Hash of the content the summary was generated from; the cache is
+    /// stale when this no longer matches `content_hash`.
+    pub summary_content_hash: Option<String>,
 }
 
 impl Related<super::tag::Entity> for Entity {
@@ -33,6 +33,11 @@ pub struct Model {
     pub hash: Option<String>,
     // Has this lens been disabled?
     pub is_enabled: bool,
+    // Set when the user explicitly disables a lens from their library. Kept
+    // separate from is_enabled, which tracks whether the lens file is
+    // present & is rewritten on every startup.
+    #[sea_orm(default_value = false)]
+    pub user_disabled: bool,
     // Whether this is a text-based or plugin based lens.
     pub lens_type: LensType,
     // Trigger doesn't have to be unique, we can have multiple lenses contributing to
@@ -42,6 +47,19 @@ pub struct Model {
     pub last_cache_update: Option<DateTimeUtc>,
     // Indicates the url of the remote source of the lens
     pub remote_url: Option<String>,
+    // Number of times the backing plugin has been terminated for blowing past
+    // its execution budget. Only used for plugin lenses.
+    #[sea_orm(default_value = 0)]
+    pub failure_count: i64,
+    // Latest status reported by the backing plugin ("running"/"idle"/"error")
+    // & the message that came w/ it. Only used for plugin lenses.
+    pub status_state: Option<String>,
+    pub status_message: Option<String>,
+    pub status_updated_at: Option<DateTimeUtc>,
+    // When the recrawl scheduler last refreshed this lens' documents & when
+    // the next refresh is due. Only used by lenses w/ a recrawl interval.
+    pub last_refreshed: Option<DateTimeUtc>,
+    pub next_refresh: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -108,6 +126,114 @@ pub async fn update_cache_time(
     Ok(false)
 }
 
+/// Increments the failure count for a lens & returns the new count, or `None`
+/// if no lens w/ that name exists.
+pub async fn record_failure(
+    lens_name: &str,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Option<i64>> {
+    let exists = Entity::find()
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .one(db)
+        .await?;
+
+    if let Some(existing) = exists {
+        let count = existing.failure_count + 1;
+        let mut updated: ActiveModel = existing.into();
+        updated.failure_count = Set(count);
+        updated.update(db).await?;
+        return Ok(Some(count));
+    }
+
+    Ok(None)
+}
+
+/// Clears the failure count for a lens, e.g. when the user explicitly
+/// re-enables a previously misbehaving plugin.
+pub async fn clear_failures(lens_name: &str, db: &DatabaseConnection) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::FailureCount, sea_query::Expr::value(0))
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the latest status reported by a lens' backing plugin.
+pub async fn update_status(
+    lens_name: &str,
+    state: &str,
+    message: &str,
+    db: &DatabaseConnection,
+) -> anyhow::Result<()> {
+    let exists = Entity::find()
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .one(db)
+        .await?;
+
+    if let Some(existing) = exists {
+        let mut updated: ActiveModel = existing.into();
+        updated.status_state = Set(Some(state.to_owned()));
+        updated.status_message = Set(Some(message.to_owned()));
+        updated.status_updated_at = Set(Some(chrono::Utc::now()));
+        updated.update(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Records when a lens' documents were last refreshed by the recrawl
+/// scheduler & when the next refresh is due.
+pub async fn update_refresh_schedule(
+    lens_name: &str,
+    last_refreshed: Option<DateTimeUtc>,
+    next_refresh: Option<DateTimeUtc>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<()> {
+    let exists = Entity::find()
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .one(db)
+        .await?;
+
+    if let Some(existing) = exists {
+        let mut updated: ActiveModel = existing.into();
+        updated.last_refreshed = Set(last_refreshed);
+        updated.next_refresh = Set(next_refresh);
+        updated.update(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Flips the user-facing enabled flag for a lens. Unlike `is_enabled`, the
+/// flag survives restarts & lens reloads, so a lens stays off until the user
+/// turns it back on.
+pub async fn set_user_enabled(
+    lens_name: &str,
+    enabled: bool,
+    db: &DatabaseConnection,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::UserDisabled, sea_query::Expr::value(!enabled))
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Disables a lens by name.
+pub async fn disable(lens_name: &str, db: &DatabaseConnection) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::IsEnabled, sea_query::Expr::value(false))
+        .filter(Column::Name.eq(lens_name.to_owned()))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
 /// True if the lens was added, False if it already exists.
 pub async fn add_or_enable(
     db: &DatabaseConnection,
@@ -119,10 +245,9 @@ pub async fn add_or_enable(
         .one(db)
         .await?;
 
-    let trigger_label = if lens.trigger.is_empty() {
-        lens.name.clone()
-    } else {
-        lens.trigger.clone()
+    let trigger_label = match lens.trigger.primary() {
+        Some(trigger) => trigger,
+        None => lens.name.clone(),
     };
 
     // If it already exists & is not a plugin, simply enable it.
@@ -288,7 +413,7 @@ mod test {
         let db = setup_test_db().await;
         let mut lens = LensConfig {
             name: "test_lens".to_owned(),
-            trigger: "trigger".to_owned(),
+            trigger: "trigger".into(),
             urls: vec!["https://example.com".to_owned()],
             ..Default::default()
         };
@@ -305,7 +430,7 @@ mod test {
         assert_eq!(model.description, None);
 
         // Update & trying to insert again should update values
-        lens.trigger = "new_trigger".to_owned();
+        lens.trigger = "new_trigger".into();
         lens.description = Some("description".to_owned());
         let (is_new, _model) = add_or_enable(&db, &lens, super::LensType::Simple)
             .await
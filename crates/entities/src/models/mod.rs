@@ -2,6 +2,8 @@ use sea_orm::{ConnectOptions, Database, DatabaseConnection};
 use sqlite_vec::sqlite3_vec_init;
 
 pub mod bootstrap_queue;
+pub mod chat_message;
+pub mod chat_session;
 pub mod connection;
 pub mod crawl_queue;
 pub mod crawl_tag;
@@ -80,6 +80,33 @@ pub async fn remove_unmatched_paths(
     }
 }
 
+/// Helper method used to remove all file records under the given uri prefix,
+/// e.g. when a lens-declared folder is no longer watched. Returns the removed
+/// records so callers can clean up the matching documents.
+pub async fn remove_with_prefix(
+    db: &DatabaseConnection,
+    prefix: &str,
+) -> anyhow::Result<Vec<Model>> {
+    let items = Entity::find()
+        .filter(Column::FilePath.starts_with(prefix))
+        .all(db)
+        .await?;
+
+    log::debug!("Removing {:?} files under {}", items.len(), prefix);
+    let ids = items.iter().map(|model| model.id).collect::<Vec<i64>>();
+    for chunk in ids.chunks(BATCH_SIZE) {
+        if let Err(error) = Entity::delete_many()
+            .filter(Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await
+        {
+            log::warn!("Error deleting paths {:?}", error);
+        }
+    }
+
+    Ok(items)
+}
+
 #[derive(Debug, FromQueryResult)]
 struct FileUrls {
     pub url: String,
@@ -3,12 +3,12 @@ use std::fmt::Display;
 use sea_orm::{
     entity::prelude::*,
     sea_query::{Expr, Func},
-    Condition, ConnectionTrait, Set,
+    Condition, ConnectionTrait, FromQueryResult, JoinType, QueryOrder, QuerySelect, Set,
 };
 use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, Display, EnumString};
 
-use super::{crawl_queue, indexed_document};
+use super::{crawl_queue, document_tag, indexed_document};
 
 pub type TagPair = (TagType, String);
 
@@ -54,6 +54,9 @@ pub enum TagType {
     /// Pull from the lens categorization
     #[strum(serialize = "category")]
     Category,
+    /// Detected language of the document content, e.g. "en" or "de".
+    #[strum(serialize = "language")]
+    Language,
     /// Other custom generated TagTypes.
     #[strum(serialize = "Other(String)")]
     Other(String),
@@ -80,6 +83,7 @@ fn string_to_tag_type(v: &str) -> TagType {
         "repository" => TagType::Repository,
         "fileext" => TagType::FileExt,
         "category" => TagType::Category,
+        "language" => TagType::Language,
         other => TagType::Other(String::from(other)),
     }
 }
@@ -99,6 +103,7 @@ impl Display for TagType {
             Self::Repository => "repository",
             Self::FileExt => "fileext",
             Self::Category => "category",
+            Self::Language => "language",
             Self::Other(label) => label.as_str(),
         };
 
@@ -356,6 +361,37 @@ pub async fn copy_table(
     Ok(())
 }
 
+/// A tag & the number of documents carrying it.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Eq)]
+pub struct TagCount {
+    pub label: String,
+    pub value: String,
+    pub doc_count: i64,
+}
+
+/// All tags w/ their document counts, most used first. Tags that aren't
+/// applied to any document are excluded.
+pub async fn tags_with_counts(db: &DatabaseConnection) -> Result<Vec<TagCount>, DbErr> {
+    Entity::find()
+        .select_only()
+        .column(Column::Label)
+        .column(Column::Value)
+        .column_as(document_tag::Column::Id.count(), "doc_count")
+        .join_rev(
+            JoinType::InnerJoin,
+            document_tag::Entity::belongs_to(Entity)
+                .from(document_tag::Column::TagId)
+                .to(Column::Id)
+                .into(),
+        )
+        .group_by(Column::Id)
+        .order_by_desc(Expr::cust("doc_count"))
+        .order_by_asc(Expr::cust("lower(value)"))
+        .into_model::<TagCount>()
+        .all(db)
+        .await
+}
+
 pub async fn get_favorite_tag(db: &DatabaseConnection) -> Option<u64> {
     if let Ok(Some(favorited)) = Entity::find()
         .filter(Column::Label.eq(TagType::Favorited.to_string()))
@@ -384,9 +420,9 @@ pub async fn check_query_for_tags(db: &DatabaseConnection, search: &str) -> Vec<
 
 #[cfg(test)]
 mod test {
-    use crate::models::tag;
+    use crate::models::{indexed_document, tag};
     use crate::test::setup_test_db;
-    use sea_orm::{DbErr, EntityTrait, Set};
+    use sea_orm::{ActiveModelTrait, DbErr, EntityTrait, Set};
 
     #[tokio::test]
     async fn test_add_or_create() -> Result<(), DbErr> {
@@ -444,4 +480,53 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_tags_with_counts() -> Result<(), DbErr> {
+        let db = setup_test_db().await;
+
+        let doc_one = indexed_document::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/one".into()),
+            doc_id: Set("1".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+        let doc_two = indexed_document::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/two".into()),
+            doc_id: Set("2".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await?;
+
+        doc_one
+            .insert_tags(
+                &db,
+                &[
+                    (tag::TagType::Lens, "wiki".into()),
+                    (tag::TagType::Favorited, "favorited".into()),
+                ],
+            )
+            .await?;
+        doc_two
+            .insert_tags(&db, &[(tag::TagType::Lens, "wiki".into())])
+            .await?;
+
+        // Tags not applied to any document are excluded.
+        super::get_or_create(&db, tag::TagType::Source, "web").await?;
+
+        let counts = super::tags_with_counts(&db).await?;
+        assert_eq!(counts.len(), 2);
+        // Most used tag comes first.
+        assert_eq!(counts[0].label, "lens");
+        assert_eq!(counts[0].value, "wiki");
+        assert_eq!(counts[0].doc_count, 2);
+        assert_eq!(counts[1].label, "favorited");
+        assert_eq!(counts[1].doc_count, 1);
+
+        Ok(())
+    }
 }
@@ -63,6 +63,110 @@ where
     db.execute(statement).await
 }
 
+/// Copies a stored embedding to a new row, used when another document's
+/// cached vectors can be reused instead of rerunning the model.
+pub async fn copy_embedding<C>(db: &C, from_id: i64, to_id: i64) -> Result<ExecResult, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let statement = Statement::from_sql_and_values(
+        db.get_database_backend(),
+        r#"
+            insert into vec_documents(rowid, embedding)
+                select $2, embedding from vec_documents where rowid = $1
+        "#,
+        vec![from_id.into(), to_id.into()],
+    );
+
+    db.execute(statement).await
+}
+
+#[derive(Debug, FromQueryResult)]
+struct StoredEmbedding {
+    embedding: Vec<u8>,
+}
+
+/// Reads back stored vectors, decoded from sqlite-vec's little-endian
+/// float32 blob representation.
+pub async fn get_embeddings_by_ids<C>(db: &C, ids: &[i64]) -> Result<Vec<Vec<f32>>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let st = format!(
+        r#"
+        select embedding from vec_documents where rowid in ({})
+        "#,
+        ids.iter()
+            .map(|id| format!("{}", id))
+            .collect::<Vec<String>>()
+            .join(",")
+    );
+    let statement = Statement::from_string(db.get_database_backend(), st);
+
+    let rows = StoredEmbedding::find_by_statement(statement).all(db).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            row.embedding
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect()
+        })
+        .collect())
+}
+
+/// Averages a document's stored segment vectors into a single query vector,
+/// used for similar-document lookups. Returns None when the document has no
+/// stored embeddings.
+pub async fn average_embedding_for_document<C>(
+    db: &C,
+    indexed_id: i64,
+) -> Result<Option<Vec<f32>>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mappings = vec_to_indexed::Entity::find()
+        .filter(vec_to_indexed::Column::IndexedId.eq(indexed_id))
+        .all(db)
+        .await?;
+
+    let ids = mappings.iter().map(|row| row.id).collect::<Vec<i64>>();
+    if ids.is_empty() {
+        return Ok(None);
+    }
+
+    let vectors = get_embeddings_by_ids(db, &ids).await?;
+    let dims = match vectors.first() {
+        Some(first) => first.len(),
+        None => return Ok(None),
+    };
+
+    let mut averaged = vec![0f32; dims];
+    let mut count = 0usize;
+    for vector in &vectors {
+        // Skip anything w/ mismatched dimensions, shouldn't happen since a
+        // document's vectors all come from the same model pass.
+        if vector.len() != dims {
+            continue;
+        }
+
+        for (sum, val) in averaged.iter_mut().zip(vector) {
+            *sum += val;
+        }
+        count += 1;
+    }
+
+    if count == 0 {
+        return Ok(None);
+    }
+
+    for val in averaged.iter_mut() {
+        *val /= count as f32;
+    }
+
+    Ok(Some(averaged))
+}
+
 pub async fn delete_embedding_by_id<C>(db: &C, id: i64) -> Result<ExecResult, DbErr>
 where
     C: ConnectionTrait,
@@ -304,10 +408,15 @@ where
         })
 }
 
+/// Ranks a document's stored segments against `embedding`, returning the
+/// `top_k` closest. Segments w/ an L2 distance above `max_distance` are
+/// dropped so the caller doesn't stuff irrelevant context into a prompt.
 pub async fn get_context_for_doc<C>(
     db: &C,
     document_id: i64,
     embedding: &[f32],
+    top_k: usize,
+    max_distance: Option<f64>,
 ) -> Result<Vec<DocDistance>, DbErr>
 where
     C: ConnectionTrait,
@@ -370,6 +479,10 @@ where
         })
         .map(|mut segments| {
             segments.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            if let Some(max_distance) = max_distance {
+                segments.retain(|segment| segment.distance <= max_distance);
+            }
+            segments.truncate(top_k);
             segments
         })
 }
@@ -1,4 +1,4 @@
-use sea_orm::{entity::prelude::*, InsertResult, Set};
+use sea_orm::{entity::prelude::*, Condition, InsertResult, Set};
 use serde::Serialize;
 
 use super::{indexed_document, vec_documents};
@@ -15,6 +15,14 @@ pub struct Model {
     pub updated_at: DateTimeUtc,
     pub segment_start: i64,
     pub segment_end: i64,
+    /// Hash of the document content the segment vectors were generated from.
+    /// Used to reuse stored embeddings when the content hasn't changed.
+    pub content_hash: Option<String>,
+    /// Which embedding model generated the vectors. Rows from before this
+    /// was tracked were generated by the default model.
+    pub model_id: Option<String>,
+    /// Dimensions of the stored vector.
+    pub dims: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -55,17 +63,122 @@ impl ActiveModelBehavior for ActiveModel {
 pub async fn insert_embedding_mapping(
     db: &DatabaseConnection,
     indexed_id: i64,
+    model_id: &str,
+    content_hash: &str,
     start: usize,
     end: usize,
+    dims: usize,
 ) -> Result<InsertResult<ActiveModel>, DbErr> {
     let mut active_model = ActiveModel::new();
     active_model.indexed_id = Set(indexed_id);
+    active_model.model_id = Set(Some(model_id.to_string()));
+    active_model.content_hash = Set(Some(content_hash.to_string()));
     active_model.segment_start = Set(start as i64);
     active_model.segment_end = Set(end as i64);
+    active_model.dims = Set(Some(dims as i64));
 
     Entity::insert(active_model).exec(db).await
 }
 
+/// Finds stored segment mappings generated by this model from content w/
+/// this hash, from any document. Lets the embedding pipeline reuse vectors
+/// instead of rerunning the model.
+pub async fn find_by_content_hash(
+    db: &DatabaseConnection,
+    model_id: &str,
+    content_hash: &str,
+) -> Result<Vec<Model>, DbErr> {
+    Entity::find()
+        .filter(Column::ModelId.eq(model_id))
+        .filter(Column::ContentHash.eq(content_hash))
+        .all(db)
+        .await
+}
+
+/// Dimension of the vectors stored for `model_id`, if any exist. Every row
+/// for a model has the same dimension, so a single row is representative.
+/// Lets queries reject an embedding generated w/ a different
+/// `embedding_dimensions` setting than the stored vectors.
+pub async fn stored_dims(db: &DatabaseConnection, model_id: &str) -> Result<Option<i64>, DbErr> {
+    let row = Entity::find()
+        .filter(Column::ModelId.eq(model_id))
+        .one(db)
+        .await?;
+
+    Ok(row.and_then(|row| row.dims))
+}
+
+/// Removes stored vectors generated by a model other than `model_id`.
+/// Embeddings from different models can't be meaningfully compared, so the
+/// vector table only ever holds vectors from the active model; the affected
+/// documents are requeued for a re-embed by the caller. When
+/// `allow_untracked` is set, rows from before model ids were tracked are
+/// kept — they were generated by the default model.
+pub async fn delete_all_for_other_models(
+    db: &DatabaseConnection,
+    model_id: &str,
+    allow_untracked: bool,
+) -> Result<usize, DbErr> {
+    let mut condition = Condition::any().add(Column::ModelId.ne(model_id));
+    if !allow_untracked {
+        condition = condition.add(Column::ModelId.is_null());
+    }
+
+    let stale = Entity::find().filter(condition).all(db).await?;
+    if !stale.is_empty() {
+        let ids = stale.iter().map(|val| val.id).collect::<Vec<i64>>();
+        let _ = vec_documents::delete_embedding_by_ids(db, &ids).await?;
+
+        let _ = Entity::delete_many()
+            .filter(Column::Id.is_in(ids))
+            .exec(db)
+            .await;
+    }
+
+    Ok(stale.len())
+}
+
+/// Removes every stored vector & mapping. Used when the segmentation config
+/// changes, since existing embeddings cover chunk boundaries that no longer
+/// match; the caller is expected to requeue all documents for a re-embed.
+pub async fn delete_all(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let rows = Entity::find().all(db).await?;
+    if !rows.is_empty() {
+        let ids = rows.iter().map(|val| val.id).collect::<Vec<i64>>();
+        let _ = vec_documents::delete_embedding_by_ids(db, &ids).await?;
+
+        let _ = Entity::delete_many()
+            .filter(Column::Id.is_in(ids))
+            .exec(db)
+            .await;
+    }
+
+    Ok(rows.len())
+}
+
+/// Removes the stored vectors & mappings for a set of documents, used to
+/// force a re-embed of just those documents.
+pub async fn delete_all_for_documents(
+    db: &DatabaseConnection,
+    indexed_ids: &[i64],
+) -> Result<usize, DbErr> {
+    let rows = Entity::find()
+        .filter(Column::IndexedId.is_in(indexed_ids.to_vec()))
+        .all(db)
+        .await?;
+    if !rows.is_empty() {
+        let ids = rows.iter().map(|val| val.id).collect::<Vec<i64>>();
+        let _ = vec_documents::delete_embedding_by_ids(db, &ids).await?;
+
+        let _ = Entity::delete_many()
+            .filter(Column::Id.is_in(ids))
+            .exec(db)
+            .await;
+    }
+
+    Ok(rows.len())
+}
+
 pub async fn delete_all_for_document(
     db: &DatabaseConnection,
     indexed_id: i64,
@@ -89,6 +202,28 @@ pub async fn delete_all_for_document(
     }
 }
 
+pub async fn delete_all_by_indexed_ids(
+    db: &DatabaseConnection,
+    indexed_ids: &[i64],
+) -> Result<(), DbErr> {
+    let documents = Entity::find()
+        .filter(Column::IndexedId.is_in(indexed_ids.to_owned()))
+        .all(db)
+        .await?;
+
+    if !documents.is_empty() {
+        let ids = documents.iter().map(|val| val.id).collect::<Vec<i64>>();
+        let _ = vec_documents::delete_embedding_by_ids(db, &ids).await?;
+
+        let _ = Entity::delete_many()
+            .filter(Column::Id.is_in(ids))
+            .exec(db)
+            .await;
+    }
+
+    Ok(())
+}
+
 pub async fn delete_all_by_urls(db: &DatabaseConnection, urls: &[String]) -> Result<(), DbErr> {
     let documents = indexed_document::Entity::find()
         .filter(indexed_document::Column::Url.is_in(urls))
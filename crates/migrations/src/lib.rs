@@ -34,6 +34,17 @@ mod m20241029_000001_add_vector;
 mod m20241105_000001_add_embeddings_table;
 mod m20241115_000001_embedding_to_indexed_document;
 mod m20241119_000001_segment_columns;
+mod m20260830_000001_add_initiated_by_columns;
+mod m20260830_000002_add_retry_after_column;
+mod m20260830_000003_add_content_hash_column;
+mod m20260830_000004_add_embedding_cache_columns;
+mod m20260830_000005_add_embedding_model_columns;
+mod m20260830_000006_add_chat_tables;
+mod m20260830_000007_add_summary_columns;
+mod m20260830_000008_add_plugin_failure_count;
+mod m20260830_000009_add_plugin_status_columns;
+mod m20260830_000010_add_lens_refresh_columns;
+mod m20260830_000011_add_lens_user_disabled;
 mod utils;
 
 pub struct Migrator;
@@ -73,6 +84,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20241105_000001_add_embeddings_table::Migration),
             Box::new(m20241115_000001_embedding_to_indexed_document::Migration),
             Box::new(m20241119_000001_segment_columns::Migration),
+            Box::new(m20260830_000001_add_initiated_by_columns::Migration),
+            Box::new(m20260830_000002_add_retry_after_column::Migration),
+            Box::new(m20260830_000003_add_content_hash_column::Migration),
+            Box::new(m20260830_000004_add_embedding_cache_columns::Migration),
+            Box::new(m20260830_000005_add_embedding_model_columns::Migration),
+            Box::new(m20260830_000006_add_chat_tables::Migration),
+            Box::new(m20260830_000007_add_summary_columns::Migration),
+            Box::new(m20260830_000008_add_plugin_failure_count::Migration),
+            Box::new(m20260830_000009_add_plugin_status_columns::Migration),
+            Box::new(m20260830_000010_add_lens_refresh_columns::Migration),
+            Box::new(m20260830_000011_add_lens_user_disabled::Migration),
         ]
     }
 }
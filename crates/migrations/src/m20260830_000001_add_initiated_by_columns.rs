@@ -0,0 +1,41 @@
+use entities::models::{crawl_queue, embedding_queue};
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000001_add_initiated_by_columns"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Track which user request (if any) enqueued a crawl task.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(crawl_queue::Entity)
+                    .add_column(ColumnDef::new(Alias::new("initiated_by")).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Same for embedding work generated by user-initiated crawls.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(embedding_queue::Entity)
+                    .add_column(ColumnDef::new(Alias::new("initiated_by")).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use entities::models::{embedding_queue, vec_to_indexed};
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000004_add_embedding_cache_columns"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Hash of the content the stored segment vectors were generated from,
+        // used to skip regenerating embeddings when a recrawl finds the
+        // content unchanged.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(vec_to_indexed::Entity)
+                    .add_column(ColumnDef::new(Alias::new("content_hash")).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Whether a job was completed by reusing cached vectors instead of
+        // running the model, used to report cache hit rates.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(embedding_queue::Entity)
+                    .add_column(
+                        ColumnDef::new(Alias::new("used_cache"))
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use entities::models::vec_to_indexed;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000005_add_embedding_model_columns"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Which embedding model generated the stored segment vectors. Vectors
+        // from different models can't be meaningfully compared, so this is
+        // used to purge stale vectors when the user switches models. Rows
+        // from before this column existed were generated by the default
+        // model.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(vec_to_indexed::Entity)
+                    .add_column(ColumnDef::new(Alias::new("model_id")).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Dimensions of the stored vector, recorded alongside the model id.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(vec_to_indexed::Entity)
+                    .add_column(ColumnDef::new(Alias::new("dims")).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, sea_orm::Statement};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum ChatSessions {
+    #[iden = "chat_sessions"]
+    Table,
+    Id,
+    DocId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum ChatMessages {
+    #[iden = "chat_messages"]
+    Table,
+    Id,
+    SessionId,
+    Role,
+    Content,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Persisted chat conversations, one session per document, so
+        // follow-up questions keep their prior turns.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatSessions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChatSessions::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ChatSessions::DocId).string().not_null())
+                    .col(
+                        ColumnDef::new(ChatSessions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ChatSessions::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatMessages::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChatMessages::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ChatMessages::SessionId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ChatMessages::Role).string().not_null())
+                    .col(ColumnDef::new(ChatMessages::Content).string().not_null())
+                    .col(
+                        ColumnDef::new(ChatMessages::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        if let Ok(true) = manager.has_table("chat_messages").await {
+            let session_id = r#"
+                CREATE INDEX IF NOT EXISTS "idx-chat_messages-session_id" ON chat_messages (session_id);"#;
+
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    manager.get_database_backend(),
+                    session_id.to_string(),
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use entities::models::lens;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000008_add_plugin_failure_count"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Number of times the backing plugin has been terminated for blowing
+        // past its execution budget; used to auto-disable repeat offenders.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(lens::Entity)
+                    .add_column(
+                        ColumnDef::new(Alias::new("failure_count"))
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use entities::models::lens;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000009_add_plugin_status_columns"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Latest status reported by the backing plugin; surfaced in the
+        // client's plugin manager.
+        for column in ["status_state", "status_message"] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(lens::Entity)
+                        .add_column(ColumnDef::new(Alias::new(column)).string())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(lens::Entity)
+                    .add_column(ColumnDef::new(Alias::new("status_updated_at")).timestamp())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
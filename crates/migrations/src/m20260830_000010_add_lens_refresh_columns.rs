@@ -0,0 +1,34 @@
+use entities::models::lens;
+use sea_orm_migration::prelude::*;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20260830_000010_add_lens_refresh_columns"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Tracks the per-lens recrawl schedule: when documents were last
+        // refreshed & when the next refresh is due.
+        for column in ["last_refreshed", "next_refresh"] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(lens::Entity)
+                        .add_column(ColumnDef::new(Alias::new(column)).timestamp())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use crate::form::{FormType, SettingOpts};
+use crate::plugin::{PluginConfig, PLUGIN_LOG_LEVEL_SETTING};
 use diff::Diff;
 use directories::ProjectDirs;
 use embeddings::{embedding_setting_opts, EmbeddingSettings};
+use llm::{llm_setting_opts, LlmSettings};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -10,16 +12,21 @@ use ts_rs::TS;
 use uuid::Uuid;
 
 pub use spyglass_lens::{
-    types::{LensFilters, LensRule, LensSource, UrlSanitizeConfig},
+    types::{LensFilters, LensRule, LensSource, LensTrigger, UrlRegexAction, UrlSanitizeConfig},
     LensConfig, PipelineConfiguration,
 };
 
 mod audio;
 mod embeddings;
 mod filesystem;
+mod llm;
+mod remote;
+mod sync;
 mod user_actions;
 pub use audio::*;
 pub use filesystem::*;
+pub use remote::*;
+pub use sync::*;
 pub use user_actions::*;
 
 pub const MAX_TOTAL_INFLIGHT: u32 = 100;
@@ -71,6 +78,11 @@ pub enum UserAction {
     OpenApplication(String, String),
     OpenUrl(String),
     CopyToClipboard(String),
+    /// Shows documents similar to the selected result, based on its stored
+    /// embedding vectors.
+    ShowSimilar,
+    /// Opens the inline tag editor for the selected result.
+    EditTags,
 }
 
 pub type PluginSettings = HashMap<String, HashMap<String, String>>;
@@ -104,10 +116,45 @@ pub struct UserSettings {
     /// Should we disable telemetry
     #[serde(default)]
     pub disable_telemetry: bool,
+    /// Disable global URL normalization (stripping fragments/tracking params)
+    /// when enqueueing & indexing documents.
+    #[serde(default)]
+    pub disable_url_normalization: bool,
+    /// Use fuzzy term matching for typo tolerance in searches.
+    #[serde(default = "UserSettings::default_fuzzy_search")]
+    pub fuzzy_search: bool,
+    /// Max number of results returned per search, clamped to 5 - 100.
+    #[serde(default = "UserSettings::default_max_search_results")]
+    pub max_search_results: u32,
+    /// Boost recently modified documents in search rankings; decay half-life
+    /// in days. Off by default.
+    #[serde(default)]
+    pub recency_boost_days: Option<f32>,
+    /// Languages (two-letter codes, e.g. "en", "de") used to pick the index
+    /// tokenizer. Changing this requires a reindex to take effect.
+    #[serde(default = "UserSettings::default_index_languages")]
+    pub index_languages: Vec<String>,
     #[serde(default)]
     pub filesystem_settings: FileSystemSettings,
+    /// Per-plugin user settings, keyed by plugin name. Values override the
+    /// defaults declared in the plugin's manifest.
+    #[serde(default)]
+    pub plugin_settings: PluginSettings,
+    /// Max wasm instructions a plugin may execute per call before it's
+    /// terminated. Guards against buggy plugins stuck in an infinite loop.
+    #[serde(default = "UserSettings::default_plugin_fuel_budget")]
+    pub plugin_fuel_budget: u64,
+    /// Max size in bytes of an http response body delivered to a plugin;
+    /// anything past this is dropped & the response marked as truncated.
+    #[serde(default = "UserSettings::default_plugin_http_max_bytes")]
+    pub plugin_http_max_bytes: u64,
     #[serde(default)]
     pub disable_autolaunch: bool,
+    /// Apply remote lens updates as soon as they're found. When disabled,
+    /// updates are surfaced as a diff of added/removed domains, urls & rules
+    /// for review before applying.
+    #[serde(default = "UserSettings::default_lens_auto_update")]
+    pub lens_auto_update: bool,
     #[serde(default = "UserSettings::default_port")]
     pub port: u16,
     #[serde(default)]
@@ -116,6 +163,12 @@ pub struct UserSettings {
     pub audio_settings: AudioSettings,
     #[serde(default)]
     pub embedding_settings: EmbeddingSettings,
+    #[serde(default)]
+    pub llm_settings: LlmSettings,
+    #[serde(default)]
+    pub sync_settings: SyncSettings,
+    #[serde(default)]
+    pub remote_index_settings: RemoteIndexSettings,
     // /// Hide the app icon from the dock/taskbar while running. Will still show up
     // /// in the menubar/systemtray.
     // #[serde(default)]
@@ -135,6 +188,31 @@ impl UserSettings {
         4664
     }
 
+    pub fn default_fuzzy_search() -> bool {
+        true
+    }
+
+    pub fn default_max_search_results() -> u32 {
+        5
+    }
+
+    pub fn default_index_languages() -> Vec<String> {
+        vec!["en".to_string()]
+    }
+
+    pub fn default_plugin_fuel_budget() -> u64 {
+        // Roughly a couple seconds of execution.
+        1_000_000_000
+    }
+
+    pub fn default_plugin_http_max_bytes() -> u64 {
+        10 * 1024 * 1024
+    }
+
+    pub fn default_lens_auto_update() -> bool {
+        true
+    }
+
     pub fn constraint_limits(&mut self) {
         // Make sure crawler limits are reasonable
         match self.inflight_crawl_limit {
@@ -150,6 +228,9 @@ impl UserSettings {
                 self.inflight_domain_limit = Limit::Finite(limit.min(MAX_DOMAIN_INFLIGHT))
             }
         }
+
+        // Keep the search bar result count inside a renderable range.
+        self.max_search_results = self.max_search_results.clamp(5, 100);
     }
 }
 
@@ -179,6 +260,13 @@ impl From<UserSettings> for Vec<(String, SettingOpts)> {
                 restart_required: true,
                 help_text: Some("Close the search bar window instead of minimizing it. Note that using this setting will make it impossible to close the search bar using the shortcut to open it, so you will need to use `Escape` instead. This will require a restart.".into())
             }),
+            ("_.lens_auto_update".into(), SettingOpts {
+                label: "Automatically update lenses".into(),
+                value: serde_json::to_string(&settings.lens_auto_update).expect("Unable to ser lens_auto_update value"),
+                form_type: FormType::Bool,
+                restart_required: false,
+                help_text: Some("Apply lens updates from the community directory as soon as they're found. When disabled, updates are shown as a diff of added & removed domains for review before applying.".into())
+            }),
             ("_.shortcut".into(), SettingOpts {
                 label: "Global Shortcut".into(),
                 value: settings.shortcut.clone(),
@@ -193,6 +281,13 @@ impl From<UserSettings> for Vec<(String, SettingOpts)> {
                 restart_required: false,
                 help_text: Some("Stop sending data to any 3rd-party service. See https://spyglass.fyi/telemetry for more info. This will require a restart.".into())
             }),
+            ("_.max_search_results".into(), SettingOpts {
+                label: "Max search results".into(),
+                value: settings.max_search_results.to_string(),
+                form_type: FormType::Number,
+                restart_required: false,
+                help_text: Some("Maximum number of results shown in the search bar. Clamped between 5 and 100.".into())
+            }),
             ("_.port".into(), SettingOpts {
                 label: "Spyglass Daemon Port".into(),
                 value: settings.port.to_string(),
@@ -235,6 +330,8 @@ impl From<UserSettings> for Vec<(String, SettingOpts)> {
         config.extend(fs_setting_opts(&settings));
         config.extend(audio_setting_opts(&settings));
         config.extend(embedding_setting_opts(&settings));
+        config.extend(llm_setting_opts(&settings));
+        config.extend(sync_setting_opts(&settings));
 
         config
     }
@@ -259,12 +356,24 @@ impl Default for UserSettings {
             data_directory: UserSettings::default_data_dir(),
             crawl_external_links: false,
             disable_telemetry: false,
+            disable_url_normalization: false,
+            fuzzy_search: UserSettings::default_fuzzy_search(),
+            max_search_results: UserSettings::default_max_search_results(),
+            recency_boost_days: None,
+            index_languages: UserSettings::default_index_languages(),
             filesystem_settings: FileSystemSettings::default(),
+            plugin_settings: PluginSettings::default(),
+            plugin_fuel_budget: UserSettings::default_plugin_fuel_budget(),
+            plugin_http_max_bytes: UserSettings::default_plugin_http_max_bytes(),
             disable_autolaunch: false,
+            lens_auto_update: UserSettings::default_lens_auto_update(),
             port: UserSettings::default_port(),
             user_action_settings: UserActionSettings::default(),
             audio_settings: AudioSettings::default(),
             embedding_settings: EmbeddingSettings::default(),
+            llm_settings: LlmSettings::default(),
+            sync_settings: SyncSettings::default(),
+            remote_index_settings: RemoteIndexSettings::default(),
         }
     }
 }
@@ -351,6 +460,13 @@ impl Config {
         self.data_dir().join("logs")
     }
 
+    /// Log file holding a single plugin's own log output.
+    pub fn plugin_log_file(&self, plugin_name: &str) -> PathBuf {
+        self.logs_dir()
+            .join("plugins")
+            .join(format!("{plugin_name}.log"))
+    }
+
     pub fn model_dir(&self) -> PathBuf {
         self.data_dir().join("models")
     }
@@ -363,6 +479,10 @@ impl Config {
         self.model_dir().join("llm")
     }
 
+    pub fn rerank_model_dir(&self) -> PathBuf {
+        self.model_dir().join("reranker")
+    }
+
     pub fn prefs_dir() -> PathBuf {
         let proj_dirs = ProjectDirs::from("com", "athlabs", &Config::app_identifier())
             .expect("Unable to find a suitable settings directory");
@@ -374,6 +494,38 @@ impl Config {
         Self::prefs_dir().join("settings.ron")
     }
 
+    /// User-editable stop word overrides, merged w/ the built-in list. One
+    /// word per line, a `-` prefix removes a default stop word.
+    pub fn stop_words_file() -> PathBuf {
+        Self::prefs_dir().join("stopwords.txt")
+    }
+
+    /// Contents of the stop word overrides file, if the user created one.
+    pub fn load_stop_word_overrides() -> Option<String> {
+        let path = Self::stop_words_file();
+        if path.exists() {
+            fs::read_to_string(path).ok()
+        } else {
+            None
+        }
+    }
+
+    /// User-defined synonym groups, expanded at query time. A RON list of
+    /// equivalence groups, e.g. `[["k8s", "kube", "kubernetes"]]`.
+    pub fn synonyms_file() -> PathBuf {
+        Self::prefs_dir().join("synonyms.ron")
+    }
+
+    /// Contents of the synonyms file, if the user created one.
+    pub fn load_synonyms() -> Option<String> {
+        let path = Self::synonyms_file();
+        if path.exists() {
+            fs::read_to_string(path).ok()
+        } else {
+            None
+        }
+    }
+
     pub fn plugins_dir(&self) -> PathBuf {
         self.data_dir().join("plugins")
     }
@@ -390,6 +542,63 @@ impl Config {
         self.data_dir().join("pipelines")
     }
 
+    /// Loads plugin manifests from the plugins directory. Each plugin lives
+    /// in its own folder holding a `manifest.ron` & the compiled `main.wasm`.
+    pub fn load_plugin_config(&self) -> HashMap<String, PluginConfig> {
+        let mut plugins = HashMap::new();
+        let entries = match fs::read_dir(self.plugins_dir()) {
+            Ok(entries) => entries,
+            Err(_) => return plugins,
+        };
+
+        for entry in entries.flatten() {
+            let plugin_dir = entry.path();
+            if !plugin_dir.is_dir() {
+                continue;
+            }
+
+            let manifest_path = plugin_dir.join("manifest.ron");
+            let wasm_path = plugin_dir.join("main.wasm");
+            if !manifest_path.exists() || !wasm_path.exists() {
+                log::warn!(
+                    "Skipping plugin folder w/ missing manifest or wasm: {}",
+                    plugin_dir.display()
+                );
+                continue;
+            }
+
+            let manifest = fs::read_to_string(&manifest_path).unwrap_or_default();
+            match ron::from_str::<PluginConfig>(&manifest) {
+                Ok(mut plugin_config) => {
+                    plugin_config.path = Some(wasm_path);
+                    // Every plugin gets a log-level setting, whether or not
+                    // the manifest declares one.
+                    plugin_config
+                        .user_settings
+                        .entry(PLUGIN_LOG_LEVEL_SETTING.into())
+                        .or_insert_with(|| SettingOpts {
+                            label: "Log level".into(),
+                            value: "info".into(),
+                            form_type: FormType::Text,
+                            help_text: Some(
+                                "Minimum level written to the plugin's log file (debug, info, warn, error)."
+                                    .into(),
+                            ),
+                            restart_required: false,
+                        });
+                    plugins.insert(plugin_config.name.clone(), plugin_config);
+                }
+                Err(err) => log::error!(
+                    "Unable to parse plugin manifest {}: {}",
+                    manifest_path.display(),
+                    err
+                ),
+            }
+        }
+
+        plugins
+    }
+
     pub fn new() -> Self {
         let prefs_dir = Config::prefs_dir();
         fs::create_dir_all(prefs_dir).expect("Unable to create config folder");
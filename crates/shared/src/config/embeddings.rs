@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use diff::Diff;
 use serde::{Deserialize, Serialize};
 
@@ -5,25 +7,246 @@ use crate::form::{FormType, SettingOpts};
 
 use super::UserSettings;
 
-#[derive(Clone, Debug, Serialize, Deserialize, Diff, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Diff)]
 pub struct EmbeddingSettings {
     pub enable_embeddings: bool,
+    /// Which embedding model to use. Either a HuggingFace repo
+    /// (e.g. "intfloat/multilingual-e5-small") downloaded on demand or a path
+    /// to a local directory containing model.safetensors, config.json &
+    /// tokenizer.json. Uses the default model when unset.
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    /// Which GPU (CUDA/Metal ordinal) to run the embedding model on. Defaults
+    /// to the first one found. Fails to load w/ an error if the requested
+    /// device doesn't exist rather than silently falling back.
+    #[serde(default)]
+    pub device: Option<usize>,
+    /// Run the embedding model on the CPU even if a GPU is available.
+    #[serde(default)]
+    pub force_cpu: bool,
+    /// Target number of tokens per document segment. Clamped to the model's
+    /// max sequence length at embed time.
+    #[serde(default = "default_segment_tokens")]
+    pub segment_tokens: usize,
+    /// Number of tokens neighboring segments overlap.
+    #[serde(default)]
+    pub segment_overlap_tokens: usize,
+    /// Prefer splitting segments at markdown headings & paragraph breaks.
+    #[serde(default)]
+    pub split_on_boundaries: bool,
+    /// Truncate pooled embeddings to this many dimensions before storage
+    /// (matryoshka truncation). Only useful w/ models trained for it, e.g.
+    /// nomic-embed or jina v3; stores the model's full output when unset.
+    /// Changing this will re-embed your documents.
+    #[serde(default)]
+    pub embedding_dimensions: Option<usize>,
+    /// Max number of context segments included in the prompt when asking
+    /// questions about a document.
+    #[serde(default = "default_context_top_k")]
+    pub context_top_k: usize,
+    /// Drop context segments w/ an L2 distance above this. Zero disables the
+    /// threshold & always includes the closest segments.
+    #[serde(default)]
+    pub context_max_distance: f64,
+    /// Rerun the top search results through a cross-encoder & reorder them
+    /// by the rerank score. More accurate ranking at the cost of latency.
+    #[serde(default)]
+    pub enable_reranking: bool,
+}
+
+fn default_segment_tokens() -> usize {
+    2048
+}
+
+fn default_context_top_k() -> usize {
+    2
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            enable_embeddings: false,
+            embedding_model: None,
+            device: None,
+            force_cpu: false,
+            segment_tokens: default_segment_tokens(),
+            segment_overlap_tokens: 0,
+            split_on_boundaries: false,
+            embedding_dimensions: None,
+            context_top_k: default_context_top_k(),
+            context_max_distance: 0.0,
+            enable_reranking: false,
+        }
+    }
+}
+
+impl EmbeddingSettings {
+    /// Resolves the directory the configured model lives in. Local paths are
+    /// used as-is; HuggingFace repos get their own subdirectory under
+    /// `default_dir` so switching models doesn't clobber a previous download.
+    pub fn model_root(&self, default_dir: &Path) -> PathBuf {
+        match &self.embedding_model {
+            Some(model) => {
+                let local = Path::new(model);
+                if local.is_dir() {
+                    local.to_path_buf()
+                } else {
+                    default_dir.join(model.replace(['/', '\\'], "_"))
+                }
+            }
+            None => default_dir.to_path_buf(),
+        }
+    }
+
+    /// Identifier recorded alongside generated vectors, used to keep
+    /// embeddings from different models out of the same vector table.
+    pub fn model_id(&self) -> String {
+        self.embedding_model
+            .clone()
+            .unwrap_or_else(|| crate::constants::DEFAULT_EMBEDDING_MODEL.to_string())
+    }
+
+    /// Whether the segmentation config differs from `other`, meaning existing
+    /// embeddings were generated w/ different chunk boundaries & need to be
+    /// regenerated.
+    pub fn segmentation_changed(&self, other: &EmbeddingSettings) -> bool {
+        self.segment_tokens != other.segment_tokens
+            || self.segment_overlap_tokens != other.segment_overlap_tokens
+            || self.split_on_boundaries != other.split_on_boundaries
+    }
+
+    /// Whether the stored-vector dimension differs from `other`. Vectors of
+    /// different lengths can't be compared, so existing embeddings need to be
+    /// regenerated.
+    pub fn dimensions_changed(&self, other: &EmbeddingSettings) -> bool {
+        self.embedding_dimensions != other.embedding_dimensions
+    }
 }
 
 #[allow(dead_code)]
 pub fn embedding_setting_opts(settings: &UserSettings) -> Vec<(String, SettingOpts)> {
-    vec![(
-        "_.embedding_settings.enable_embeddings".into(),
-        SettingOpts {
-            label: "Beta: Enable Similarity Search".into(),
-            value: settings.embedding_settings.enable_embeddings.to_string(),
-            form_type: FormType::Bool,
-            restart_required: false,
-            help_text: Some(
-                r#"Embeddings are generated for documents and search will check for
+    vec![
+        (
+            "_.embedding_settings.enable_embeddings".into(),
+            SettingOpts {
+                label: "Beta: Enable Similarity Search".into(),
+                value: settings.embedding_settings.enable_embeddings.to_string(),
+                form_type: FormType::Bool,
+                restart_required: false,
+                help_text: Some(
+                    r#"Embeddings are generated for documents and search will check for
                    semantic similarity as well as standard search."#
-                    .into(),
-            ),
-        },
-    )]
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.embedding_model".into(),
+            SettingOpts {
+                label: "Embedding Model".into(),
+                value: settings
+                    .embedding_settings
+                    .embedding_model
+                    .clone()
+                    .unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: false,
+                help_text: Some(
+                    r#"HuggingFace repo (e.g. "intfloat/multilingual-e5-small") or path to a
+                   local model directory. Leave blank for the default model. Switching
+                   models will re-embed your documents."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.segment_tokens".into(),
+            SettingOpts {
+                label: "Segment Size (tokens)".into(),
+                value: settings.embedding_settings.segment_tokens.to_string(),
+                form_type: FormType::Number,
+                restart_required: false,
+                help_text: Some(
+                    r#"Target number of tokens per document segment. Smaller segments give
+                   more precise matches, larger ones keep more context together.
+                   Changing this will re-embed your documents."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.segment_overlap_tokens".into(),
+            SettingOpts {
+                label: "Segment Overlap (tokens)".into(),
+                value: settings
+                    .embedding_settings
+                    .segment_overlap_tokens
+                    .to_string(),
+                form_type: FormType::Number,
+                restart_required: false,
+                help_text: Some(
+                    r#"Number of tokens neighboring segments share so context isn't lost at
+                   segment boundaries. Changing this will re-embed your documents."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.split_on_boundaries".into(),
+            SettingOpts {
+                label: "Split Segments at Headings & Paragraphs".into(),
+                value: settings.embedding_settings.split_on_boundaries.to_string(),
+                form_type: FormType::Bool,
+                restart_required: false,
+                help_text: Some(
+                    r#"Prefer cutting segments at markdown headings & paragraph breaks
+                   instead of mid-sentence. Changing this will re-embed your documents."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.enable_reranking".into(),
+            SettingOpts {
+                label: "Rerank Search Results".into(),
+                value: settings.embedding_settings.enable_reranking.to_string(),
+                form_type: FormType::Bool,
+                restart_required: false,
+                help_text: Some(
+                    r#"Rerun the top search results through a cross-encoder model &
+                   reorder them by the rerank score. More accurate ranking at the
+                   cost of some latency per search."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.context_top_k".into(),
+            SettingOpts {
+                label: "Context Segments per Question".into(),
+                value: settings.embedding_settings.context_top_k.to_string(),
+                form_type: FormType::Number,
+                restart_required: false,
+                help_text: Some(
+                    r#"Max number of document segments included as context when asking
+                   questions about a document."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.embedding_settings.force_cpu".into(),
+            SettingOpts {
+                label: "Run Embedding Model on CPU".into(),
+                value: settings.embedding_settings.force_cpu.to_string(),
+                form_type: FormType::Bool,
+                restart_required: false,
+                help_text: Some(
+                    r#"Generate embeddings on the CPU even when a GPU is available.
+                   Slower, but leaves the GPU free for other applications."#
+                        .into(),
+                ),
+            },
+        ),
+    ]
 }
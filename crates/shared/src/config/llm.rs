@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use diff::Diff;
+use serde::{Deserialize, Serialize};
+
+use super::UserSettings;
+use crate::form::{FormType, SettingOpts};
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Diff)]
+pub struct LlmSettings {
+    /// Path to the gguf model file used for chat & question answering.
+    /// Defaults to the bundled Llama 3.2 3B model under the app's model
+    /// directory when unset.
+    #[serde(default)]
+    pub model_path: Option<String>,
+    /// Prompt template chat sessions are rendered w/, e.g.
+    /// "llama3-instruct.txt". Picked based on the model's gguf metadata when
+    /// unset.
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    /// Base URL of an OpenAI-compatible chat completions server (Ollama,
+    /// llama.cpp, etc.), e.g. "http://192.168.1.10:11434/v1". Chats are sent
+    /// there instead of running the local model when set.
+    #[serde(default)]
+    pub remote_url: Option<String>,
+    /// API key sent w/ requests to the remote server, if it needs one.
+    /// Only editable in the settings file.
+    #[serde(default)]
+    pub remote_api_key: Option<String>,
+    /// Model name passed to the remote server.
+    #[serde(default)]
+    pub remote_model: Option<String>,
+    /// Delete chat sessions that haven't been touched in this many days.
+    /// Unset (or zero) keeps them forever.
+    #[serde(default)]
+    pub chat_retention_days: Option<u64>,
+}
+
+impl LlmSettings {
+    /// Resolves the gguf file to load, falling back to the default model
+    /// under `default_dir`.
+    pub fn gguf_path(&self, default_dir: &Path) -> PathBuf {
+        match &self.model_path {
+            Some(path) => PathBuf::from(path),
+            None => default_dir
+                .join("llama3")
+                .join("Llama-3.2-3B-Instruct.Q5_K_M.gguf"),
+        }
+    }
+}
+
+pub fn llm_setting_opts(settings: &UserSettings) -> Vec<(String, SettingOpts)> {
+    vec![
+        (
+            "_.llm_settings.model_path".into(),
+            SettingOpts {
+                label: "Chat Model Path".into(),
+                value: settings.llm_settings.model_path.clone().unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: false,
+                help_text: Some(
+                    r#"Path to the gguf model used for chat & question answering.
+                   Leave blank for the default model."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.llm_settings.prompt_template".into(),
+            SettingOpts {
+                label: "Chat Prompt Template".into(),
+                value: settings
+                    .llm_settings
+                    .prompt_template
+                    .clone()
+                    .unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: false,
+                help_text: Some(
+                    r#"Prompt template the chat model was trained on, e.g.
+                   "llama3-instruct.txt", "chatml.txt", "mistral-instruct.txt"
+                   or "gemma.txt". Leave blank to pick one based on the
+                   model's metadata."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.llm_settings.remote_url".into(),
+            SettingOpts {
+                label: "Remote Chat Server URL".into(),
+                value: settings.llm_settings.remote_url.clone().unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: false,
+                help_text: Some(
+                    r#"Base URL of an OpenAI-compatible chat completions server, e.g. an
+                   Ollama or llama.cpp instance. Chats are sent there instead of
+                   running the local model. Leave blank for local inference."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.llm_settings.remote_model".into(),
+            SettingOpts {
+                label: "Remote Chat Model".into(),
+                value: settings
+                    .llm_settings
+                    .remote_model
+                    .clone()
+                    .unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: false,
+                help_text: Some(
+                    r#"Model name passed to the remote chat server. Servers w/ a single
+                   loaded model ignore this."#
+                        .into(),
+                ),
+            },
+        ),
+    ]
+}
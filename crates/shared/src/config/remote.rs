@@ -0,0 +1,36 @@
+use diff::Diff;
+use serde::{Deserialize, Serialize};
+
+/// Settings for a shared remote index (e.g. a Quickwit install) that several
+/// Spyglass clients can point at instead of their local index. Read by
+/// `HttpSearcher::from_settings`; deliberately not surfaced in the settings
+/// UI until the backend is wired into index selection at startup.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Diff)]
+pub struct RemoteIndexSettings {
+    pub enable_remote_index: bool,
+    /// Base URL of the ES-compatible REST API, e.g. "http://nas.local:7280".
+    pub url: Option<String>,
+    /// Optional bearer token sent w/ every request.
+    pub auth_token: Option<String>,
+    /// Index name on the remote server.
+    pub index_name: Option<String>,
+}
+
+impl RemoteIndexSettings {
+    pub fn is_configured(&self) -> bool {
+        self.enable_remote_index
+            && self
+                .url
+                .as_ref()
+                .map(|url| !url.is_empty())
+                .unwrap_or(false)
+    }
+
+    /// Index name on the remote server, falling back to "spyglass".
+    pub fn index_name(&self) -> String {
+        self.index_name
+            .clone()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "spyglass".into())
+    }
+}
@@ -0,0 +1,84 @@
+use diff::Diff;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::UserSettings;
+use crate::form::{FormType, SettingOpts};
+
+/// Settings for syncing user-created data (favorites, settings, etc.) between
+/// machines through a user-provided folder (e.g. Syncthing/Dropbox). Data is
+/// encrypted with a key derived from the passphrase so the sync provider never
+/// sees plaintext.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Diff)]
+pub struct SyncSettings {
+    pub enable_sync: bool,
+    /// Folder shared between machines, watched for changes from other devices.
+    pub sync_directory: Option<PathBuf>,
+    /// Passphrase used to derive the encryption key. Must match on all devices.
+    pub passphrase: Option<String>,
+}
+
+impl SyncSettings {
+    pub fn is_configured(&self) -> bool {
+        self.enable_sync
+            && self.sync_directory.is_some()
+            && self
+                .passphrase
+                .as_ref()
+                .map(|p| !p.is_empty())
+                .unwrap_or(false)
+    }
+}
+
+pub fn sync_setting_opts(settings: &UserSettings) -> Vec<(String, SettingOpts)> {
+    vec![
+        (
+            "_.sync_settings.enable_sync".into(),
+            SettingOpts {
+                label: "Beta: Enable Folder Sync".into(),
+                value: settings.sync_settings.enable_sync.to_string(),
+                form_type: FormType::Bool,
+                restart_required: true,
+                help_text: Some(
+                    r#"Sync your favorites & settings between machines through a
+                    folder you already sync (Syncthing, Dropbox, etc.). Data is
+                    encrypted before it's written to the folder."#
+                        .into(),
+                ),
+            },
+        ),
+        (
+            "_.sync_settings.sync_directory".into(),
+            SettingOpts {
+                label: "Sync Folder".into(),
+                value: settings
+                    .sync_settings
+                    .sync_directory
+                    .as_ref()
+                    .and_then(|p| p.to_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                form_type: FormType::Path,
+                restart_required: true,
+                help_text: Some("Folder shared between your machines.".into()),
+            },
+        ),
+        (
+            "_.sync_settings.passphrase".into(),
+            SettingOpts {
+                label: "Sync Passphrase".into(),
+                value: settings
+                    .sync_settings
+                    .passphrase
+                    .clone()
+                    .unwrap_or_default(),
+                form_type: FormType::Text,
+                restart_required: true,
+                help_text: Some(
+                    "Passphrase used to encrypt your synced data. Use the same one on every machine."
+                        .into(),
+                ),
+            },
+        ),
+    ]
+}
@@ -25,6 +25,18 @@ pub const WHISPER_MODEL_CONFIG: &str =
 pub const WHISPER_MODEL_TOKENIZER: &str =
     "https://huggingface.co/openai/whisper-base.en/resolve/main/tokenizer.json";
 
+/// The embedding model used when the user hasn't configured one.
+pub const DEFAULT_EMBEDDING_MODEL: &str = "nomic-ai/nomic-embed-text-v1";
+
+pub const RERANK_MODEL: &str =
+    "https://huggingface.co/cross-encoder/ms-marco-MiniLM-L-6-v2/resolve/main/model.safetensors";
+
+pub const RERANK_MODEL_CONFIG: &str =
+    "https://huggingface.co/cross-encoder/ms-marco-MiniLM-L-6-v2/resolve/main/config.json";
+
+pub const RERANK_MODEL_TOKENIZER: &str =
+    "https://huggingface.co/cross-encoder/ms-marco-MiniLM-L-6-v2/resolve/main/tokenizer.json";
+
 pub const EMBEDDING_MODEL: &str =
     "https://huggingface.co/nomic-ai/nomic-embed-text-v1/resolve/main/model.safetensors";
 
@@ -33,3 +45,48 @@ pub const EMBEDDING_MODEL_CONFIG: &str =
 
 pub const EMBEDDING_MODEL_TOKENIZER: &str =
     "https://huggingface.co/nomic-ai/nomic-embed-text-v1/resolve/main/tokenizer.json";
+
+/// One downloadable chat model in the curated catalog.
+pub struct LlmModelSource {
+    pub name: &'static str,
+    /// Subdirectory under the LLM model dir the files are stored in.
+    pub dir: &'static str,
+    /// File name the gguf is saved as.
+    pub file: &'static str,
+    pub url: &'static str,
+    /// The model's tokenizer.json, saved next to the gguf where the loader
+    /// expects it.
+    pub tokenizer_url: &'static str,
+}
+
+/// Curated chat models installable via the `install_llm_model` RPC.
+pub const LLM_MODEL_CATALOG: &[LlmModelSource] = &[
+    LlmModelSource {
+        name: "llama-3.2-3b-instruct",
+        dir: "llama3",
+        file: "Llama-3.2-3B-Instruct.Q5_K_M.gguf",
+        url: "https://huggingface.co/bartowski/Llama-3.2-3B-Instruct-GGUF/resolve/main/Llama-3.2-3B-Instruct-Q5_K_M.gguf",
+        tokenizer_url: "https://huggingface.co/unsloth/Llama-3.2-3B-Instruct/resolve/main/tokenizer.json",
+    },
+    LlmModelSource {
+        name: "qwen-2.5-3b-instruct",
+        dir: "qwen2",
+        file: "Qwen2.5-3B-Instruct.Q5_K_M.gguf",
+        url: "https://huggingface.co/bartowski/Qwen2.5-3B-Instruct-GGUF/resolve/main/Qwen2.5-3B-Instruct-Q5_K_M.gguf",
+        tokenizer_url: "https://huggingface.co/Qwen/Qwen2.5-3B-Instruct/resolve/main/tokenizer.json",
+    },
+    LlmModelSource {
+        name: "gemma-2-2b-instruct",
+        dir: "gemma2",
+        file: "gemma-2-2b-it.Q5_K_M.gguf",
+        url: "https://huggingface.co/bartowski/gemma-2-2b-it-GGUF/resolve/main/gemma-2-2b-it-Q5_K_M.gguf",
+        tokenizer_url: "https://huggingface.co/unsloth/gemma-2-2b-it/resolve/main/tokenizer.json",
+    },
+    LlmModelSource {
+        name: "mistral-7b-instruct-v0.3",
+        dir: "mistral",
+        file: "Mistral-7B-Instruct-v0.3.Q4_K_M.gguf",
+        url: "https://huggingface.co/bartowski/Mistral-7B-Instruct-v0.3-GGUF/resolve/main/Mistral-7B-Instruct-v0.3-Q4_K_M.gguf",
+        tokenizer_url: "https://huggingface.co/unsloth/mistral-7b-instruct-v0.3/resolve/main/tokenizer.json",
+    },
+];
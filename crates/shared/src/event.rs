@@ -12,10 +12,18 @@ pub struct ListenPayload<T> {
 pub enum ClientEvent {
     ClearSearch,
     ChatEvent,
+    EmbeddingProgress,
     FocusWindow,
     FolderChosen,
+    /// A lens' crawl queue drained; payload is a `LensBootstrapFinishedPayload`.
+    LensBootstrapFinished,
+    /// Per-lens crawl progress; payload is a `LensBootstrapProgressPayload`.
+    LensBootstrapProgress,
     LensInstalled,
     LensUninstalled,
+    /// A lens update is available but auto-update is disabled; the payload is
+    /// a `LensUpdateDiff` for the user to review.
+    LensUpdateAvailable,
     Navigate,
     RefreshConnections,
     /// Request a refresh of the discover lens page when a lens is succesfully installed.
@@ -14,6 +14,8 @@ pub enum FormType {
     Number,
     Path,
     PathList,
+    /// Free-form text rendered w/ a masked input (API keys, tokens).
+    Secret,
     StringList,
     Text,
     KeyBinding,
@@ -72,7 +74,7 @@ impl FormType {
                     Err(e) => Err(e.to_string()),
                 }
             }
-            FormType::Text => {
+            FormType::Secret | FormType::Text => {
                 if value.is_empty() {
                     return Err("Value cannot be empty".into());
                 }
@@ -25,6 +25,8 @@ pub enum ClientInvoke {
     GetLibraryStats,
     #[serde(rename = "get_shortcut")]
     GetShortcut,
+    #[serde(rename = "get_similar_documents")]
+    GetSimilarDocuments,
     #[serde(rename = "get_startup_progress")]
     GetStartupProgressText,
     #[serde(rename = "install_lens")]
@@ -37,6 +39,8 @@ pub enum ClientInvoke {
     ListInstallableLenses,
     #[serde(rename = "list_plugins")]
     ListPlugins,
+    #[serde(rename = "list_tags")]
+    ListTags,
     #[serde(rename = "load_user_settings")]
     LoadUserSettings,
     #[serde(rename = "load_action_settings")]
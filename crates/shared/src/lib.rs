@@ -8,6 +8,7 @@ pub mod keyboard;
 pub mod llm;
 #[cfg(feature = "metrics")]
 pub mod metrics;
+pub mod plugin;
 pub mod regex;
 pub mod request;
 pub mod response;
@@ -2,14 +2,43 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 // Generation is roughly the order things happen.
-#[derive(Deserialize, Serialize, PartialEq, Eq, TS)]
+#[derive(Deserialize, Serialize, PartialEq, TS)]
 #[serde(tag = "type", content = "content")]
 #[ts(export)]
 pub enum ChatStream {
     LoadingPrompt,
     ChatStart,
     Token(String),
-    ChatDone,
+    /// Part of the prompt didn't fit in the model's context window & was
+    /// dropped before generation; each entry describes one omitted piece so
+    /// clients can show a "some context omitted" warning.
+    ContextTruncated(Vec<String>),
+    /// Generation failed w/ this reason. Emitted by both the local & remote
+    /// backends so listeners don't need to care which one is active.
+    Error(String),
+    /// Generation was cancelled mid-stream (e.g. the client unsubscribed or
+    /// hit ctrl-c); no `ChatDone` follows.
+    Cancelled,
+    ChatDone(ChatStats),
+}
+
+/// Token usage & latency for a finished generation, handy for prompt tuning.
+/// The remote backend can't see the server's tokenizer, so it approximates
+/// `generated_tokens` (one per streamed delta) & leaves `prompt_tokens` at
+/// zero.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct ChatStats {
+    /// Number of tokens in the rendered prompt.
+    pub prompt_tokens: usize,
+    /// Number of tokens sampled for the reply.
+    pub generated_tokens: usize,
+    /// Time spent processing the prompt.
+    pub prompt_ms: u64,
+    /// Time spent sampling the reply.
+    pub generation_ms: u64,
+    /// Sampling throughput, excluding prompt processing.
+    pub tokens_per_second: f32,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, TS)]
@@ -30,8 +59,51 @@ pub struct ChatMessage {
     pub content: String,
 }
 
+/// Sampler knobs for a generation request. The defaults match the sampler's
+/// historical fixed behavior: greedy (argmax) decoding, up to 1024 tokens &
+/// no stop sequences.
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct GenerationParams {
+    /// Unset (or zero) keeps greedy decoding.
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling cutoff; only applies when `temperature` is set.
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Maximum number of tokens to sample.
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: usize,
+    /// Generation ends as soon as one of these strings shows up in the
+    /// decoded output; the stop string itself is trimmed from the reply.
+    #[serde(default)]
+    pub stop: Vec<String>,
+    /// RNG seed used when sampling, for reproducible generations.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_max_tokens() -> usize {
+    1024
+}
+
+impl Default for GenerationParams {
+    fn default() -> Self {
+        Self {
+            temperature: None,
+            top_p: None,
+            max_tokens: default_max_tokens(),
+            stop: Vec::new(),
+            seed: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, TS)]
 #[ts(export)]
 pub struct LlmSession {
     pub messages: Vec<ChatMessage>,
+    /// Sampler parameters for this session.
+    #[serde(default)]
+    pub params: GenerationParams,
 }
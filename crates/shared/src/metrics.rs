@@ -45,6 +45,13 @@ pub enum Event {
         domains: Vec<String>,
         wall_time_ms: u64,
     },
+    #[strum(serialize = "chat_completion")]
+    ChatCompletion {
+        prompt_tokens: usize,
+        generated_tokens: usize,
+        prompt_ms: u64,
+        generation_ms: u64,
+    },
     #[strum(serialize = "update_check")]
     UpdateCheck { current_version: String },
     #[strum(serialize = "wizard_finished")]
@@ -151,6 +158,23 @@ impl Metrics {
                 data.properties
                     .insert("wall_time_ms".into(), wall_time_ms.to_owned().into());
             }
+            Event::ChatCompletion {
+                prompt_tokens,
+                generated_tokens,
+                prompt_ms,
+                generation_ms,
+            } => {
+                data.properties
+                    .insert("prompt_tokens".into(), prompt_tokens.to_owned().into());
+                data.properties.insert(
+                    "generated_tokens".into(),
+                    generated_tokens.to_owned().into(),
+                );
+                data.properties
+                    .insert("prompt_ms".into(), prompt_ms.to_owned().into());
+                data.properties
+                    .insert("generation_ms".into(), generation_ms.to_owned().into());
+            }
             Event::ResultActionTriggered {
                 action,
                 is_default_action,
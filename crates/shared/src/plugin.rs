@@ -16,6 +16,32 @@ pub enum PluginType {
 
 pub type PluginUserSettings = HashMap<String, SettingOpts>;
 
+/// Setting (& plugin env var) holding the minimum level written to the
+/// plugin's log file. Injected into every plugin's settings by the host.
+pub const PLUGIN_LOG_LEVEL_SETTING: &str = "SPYGLASS_LOG_LEVEL";
+
+/// Permissions a plugin declares in its manifest. The host denies requests
+/// outside of these w/ a `PluginEvent::PermissionDenied` event; everything
+/// defaults to denied so plugins have to ask for what they use.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PluginPermissions {
+    /// Hosts the plugin is allowed to make HTTP requests to. Entries match a
+    /// host exactly, `*.example.com` matches subdomains & `*` matches
+    /// everything.
+    #[serde(default)]
+    pub http_hosts: Vec<String>,
+    /// Allowed to sync files from the host filesystem into its data dir &
+    /// watch host paths for changes.
+    #[serde(default)]
+    pub sync_files: bool,
+    /// Allowed to sync & query sqlite databases (e.g. browser history).
+    #[serde(default)]
+    pub sqlite: bool,
+    /// Allowed to add urls to the crawl queue.
+    #[serde(default)]
+    pub enqueue: bool,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PluginConfig {
     pub name: String,
@@ -29,6 +55,8 @@ pub struct PluginConfig {
     pub plugin_type: PluginType,
     pub user_settings: PluginUserSettings,
     #[serde(default)]
+    pub permissions: PluginPermissions,
+    #[serde(default)]
     pub is_enabled: bool,
 }
 
@@ -42,6 +70,29 @@ impl PluginConfig {
             .join("data")
     }
 
+    /// Validates every setting value against the form type declared in the
+    /// manifest. Settings left empty are skipped (the plugin falls back to
+    /// its own default); otherwise one `(setting name, error)` pair is
+    /// returned per invalid value.
+    pub fn validate_user_settings(&self) -> Result<(), Vec<(String, String)>> {
+        let mut errors = Vec::new();
+        for (name, opts) in &self.user_settings {
+            if opts.value.trim().is_empty() {
+                continue;
+            }
+
+            if let Err(error) = opts.form_type.validate(&opts.value) {
+                errors.push((name.clone(), error));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Update the plugin config based on user settings
     pub fn set_user_config(&mut self, user_settings: &UserSettings) {
         let plugin_user_settings = &user_settings.plugin_settings;
@@ -1,12 +1,59 @@
 use crate::response::DocMetadata;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
+use ts_rs::TS;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SearchParam {
     pub lenses: Vec<String>,
+    /// Only return results in these languages (two-letter codes, e.g. "en").
+    #[serde(default)]
+    pub languages: Vec<String>,
     pub query: String,
     pub offset: Option<u32>,
+    /// Max number of results to return, defaults to the user's
+    /// `max_search_results` setting.
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Override the user's fuzzy matching setting for this request.
+    #[serde(default)]
+    pub fuzzy: Option<bool>,
+    /// Override the user's recency boost for this request; decay half-life
+    /// in days, 0 disables it.
+    #[serde(default)]
+    pub recency_boost_days: Option<f32>,
+    /// Only include documents published after this date.
+    #[serde(default)]
+    pub published_after: Option<DateTime<Utc>>,
+    /// Only include documents published before this date.
+    #[serde(default)]
+    pub published_before: Option<DateTime<Utc>>,
+    /// Only return favorited documents.
+    #[serde(default)]
+    pub favorites_only: bool,
+    /// How results should be ordered.
+    #[serde(default)]
+    pub sort: SearchSort,
+    /// Include per-tag match counts in the response.
+    #[serde(default)]
+    pub return_facets: bool,
+    /// Treat the final token as a prefix while the user is still typing.
+    #[serde(default)]
+    pub is_partial: bool,
+}
+
+/// Result ordering for `SearchParam`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub enum SearchSort {
+    /// Order by relevance score (the default).
+    #[default]
+    Relevance,
+    /// Newest published/modified documents first.
+    NewestFirst,
+    /// Oldest published/modified documents first.
+    OldestFirst,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -50,6 +97,9 @@ pub struct RawDocumentRequest {
     pub doc_type: RawDocType,
     pub source: RawDocSource,
     pub tags: Vec<(String, String)>,
+    /// Optional client-provided id, echoed back in related progress events.
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -57,6 +107,9 @@ pub struct BatchDocumentRequest {
     pub urls: Vec<String>,
     pub source: RawDocSource,
     pub tags: Vec<(String, String)>,
+    /// Optional client-provided id, echoed back in related progress events.
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -1,6 +1,8 @@
 use crate::url_to_file_path;
+use chrono::{DateTime, Utc};
 use num_format::{Buffer, Locale};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use ts_rs::TS;
 use url::Url;
@@ -8,6 +10,53 @@ use url::Url;
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AppStatus {
     pub num_docs: u64,
+    /// Embedding jobs still waiting in the queue.
+    pub embeddings_queued: u64,
+    /// Embedding jobs completed.
+    pub embeddings_completed: u64,
+}
+
+/// A source document that contributed context to an `ask_library` answer.
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct ChatCitation {
+    pub doc_id: String,
+    pub url: String,
+}
+
+/// Answer to a library-wide question, w/ the sources used to build it so
+/// clients can render clickable citations.
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct AskLibraryResult {
+    pub answer: String,
+    /// Deduped, in relevance order.
+    pub citations: Vec<ChatCitation>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct ChatSessionResult {
+    pub id: i64,
+    /// Document the conversation is about.
+    pub doc_id: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub num_messages: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct LlmModelResult {
+    /// Catalog name, or the path relative to the LLM model dir for manually
+    /// installed ggufs.
+    pub name: String,
+    /// Whether the gguf file is on disk.
+    pub installed: bool,
+    /// Whether this is the model chats currently run against.
+    pub active: bool,
+    /// Full path to the gguf, when installed.
+    pub path: Option<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, TS)]
@@ -113,6 +162,10 @@ pub struct LensResult {
     pub hash: String,
     /// For installed lenses.
     pub file_path: Option<PathBuf>,
+    /// Whether the lens is enabled. Disabled lenses keep their documents
+    /// indexed but are excluded from default searches & no longer crawled.
+    #[serde(default)]
+    pub is_enabled: bool,
     // Only relevant for installable lenses
     #[serde(default)]
     pub categories: Vec<String>,
@@ -122,12 +175,64 @@ pub struct LensResult {
     pub lens_type: LensType,
 }
 
+/// Changelog-style diff between the installed version of a lens & a remote
+/// update, so clients can show what the update changes before applying it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct LensUpdateDiff {
+    pub name: String,
+    pub old_version: String,
+    pub new_version: String,
+    pub added_domains: Vec<String>,
+    pub removed_domains: Vec<String>,
+    pub added_urls: Vec<String>,
+    pub removed_urls: Vec<String>,
+    /// Rules rendered w/ their `Display` impl, e.g. `SkipURL("...")`.
+    pub added_rules: Vec<String>,
+    pub removed_rules: Vec<String>,
+}
+
+impl LensUpdateDiff {
+    pub fn has_changes(&self) -> bool {
+        !self.added_domains.is_empty()
+            || !self.removed_domains.is_empty()
+            || !self.added_urls.is_empty()
+            || !self.removed_urls.is_empty()
+            || !self.added_rules.is_empty()
+            || !self.removed_rules.is_empty()
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct PluginResult {
     pub author: String,
     pub title: String,
     pub description: String,
     pub is_enabled: bool,
+    /// Permissions the plugin manifest asks for, so clients can show them
+    /// before the plugin is enabled.
+    #[serde(default)]
+    pub permissions: crate::plugin::PluginPermissions,
+    /// When the plugin last ran its interval update, if it's subscribed.
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+    /// When the next interval update is scheduled.
+    #[serde(default)]
+    pub next_run: Option<DateTime<Utc>>,
+    /// Latest status reported by the plugin: "running" / "idle" / "error".
+    #[serde(default)]
+    pub status_state: Option<String>,
+    /// Human-readable detail that came w/ the status, e.g.
+    /// "Imported 1,204 bookmarks".
+    #[serde(default)]
+    pub status_message: Option<String>,
+    /// When the status was reported.
+    #[serde(default)]
+    pub status_updated_at: Option<DateTime<Utc>>,
+    /// The plugin's own log file, so clients can offer an "open logs"
+    /// shortcut.
+    #[serde(default)]
+    pub log_path: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, TS)]
@@ -136,6 +241,9 @@ pub struct SearchMeta {
     pub query: String,
     pub num_docs: u32,
     pub wall_time_ms: u32,
+    /// Total number of documents matching the query, used for paging.
+    #[serde(default)]
+    pub total_hits: u32,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, TS)]
@@ -151,6 +259,60 @@ pub struct SearchResult {
     pub url: String,
     pub tags: Vec<(String, String)>,
     pub score: f32,
+    /// When the document was published, if known.
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// When the document was last modified, if known.
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Short content snippet centered around the matched terms.
+    #[serde(default)]
+    pub snippet: String,
+    /// Byte ranges of the matched terms within `snippet`.
+    #[serde(default)]
+    pub highlights: Vec<(usize, usize)>,
+}
+
+/// A tag & how many documents carry it, used for filter chips & `tag:`
+/// autocomplete in the searchbar.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct TagCountResult {
+    pub label: String,
+    pub value: String,
+    pub doc_count: u64,
+}
+
+/// Full details for a single document, returned by `get_document` & used by
+/// clients to build a preview pane w/o running a search.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct DocumentDetails {
+    /// Document ID
+    pub doc_id: String,
+    /// URI used to crawl this document
+    pub crawl_uri: String,
+    pub domain: String,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    /// Indexed content. Capped at a preview size unless the full body was
+    /// requested; `is_truncated` is set when content was cut off.
+    pub content: String,
+    pub is_truncated: bool,
+    pub tags: Vec<(String, String)>,
+    /// Names of the lenses this document belongs to.
+    pub lenses: Vec<String>,
+    /// When the document was published, if known.
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// When the document was last modified, if known.
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub last_modified: Option<DateTime<Utc>>,
 }
 
 // The search result template is used to provide extra
@@ -242,6 +404,60 @@ impl From<SearchResult> for SearchResultTemplate {
 pub struct SearchResults {
     pub results: Vec<SearchResult>,
     pub meta: SearchMeta,
+    /// Number of matching documents per "label:value" tag pair, when
+    /// requested via `SearchParam::return_facets`.
+    #[serde(default)]
+    pub facets: HashMap<String, u64>,
+    /// Alternate queries ("did you mean?") built from the index's term
+    /// dictionaries. Only populated when `results` is empty.
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+}
+
+/// Breakdown of why a document scored the way it did for a query,
+/// returned by the `explain_search` RPC.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ExplainResult {
+    pub doc_id: String,
+    pub query: String,
+    /// Final score, including boosts & any recency decay.
+    pub score: f32,
+    /// Raw BM25 contribution per field ("title", "content", ...).
+    pub field_scores: HashMap<String, f32>,
+    /// Human readable descriptions of the boosts applied to the query.
+    pub boosts: Vec<String>,
+    /// Recency decay multiplier, when a recency boost was applied.
+    pub recency_decay: Option<f32>,
+    /// Synonym expansions applied to the query ("term -> synonym").
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    /// Cross-encoder relevance score, when reranking is enabled.
+    #[serde(default)]
+    pub rerank_score: Option<f32>,
+}
+
+/// Result of a `backup` run.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BackupResult {
+    pub archive_path: PathBuf,
+    pub bytes_written: u64,
+}
+
+/// Result of an `export_lens` run.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LensExportResult {
+    pub archive_path: PathBuf,
+    /// Number of documents written into the archive.
+    pub num_docs: u64,
+    pub bytes_written: u64,
+}
+
+/// Result of an `optimize_index` run.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct OptimizeResult {
+    pub segments_before: u32,
+    pub segments_after: u32,
+    pub bytes_reclaimed: u64,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -256,6 +472,25 @@ pub struct LibraryStats {
     pub enqueued: i32,
     pub indexed: i32,
     pub failed: i32,
+    /// Subset of `enqueued` that was explicitly requested by the user,
+    /// surfaced ahead of background crawl stats.
+    #[serde(default)]
+    pub user_enqueued: i32,
+    /// Approximate share of the tantivy index used by this lens, apportioned
+    /// from the on-disk total by document count.
+    #[serde(default)]
+    pub index_size_bytes: u64,
+    /// Approximate share of the database used by this lens, apportioned from
+    /// the on-disk total by document count.
+    #[serde(default)]
+    pub db_size_bytes: u64,
+    /// When the recrawl scheduler last refreshed this lens' documents.
+    #[serde(default)]
+    pub last_refreshed: Option<DateTime<Utc>>,
+    /// When the next scheduled refresh is due, for lenses that set a
+    /// `recrawl_interval_hours`.
+    #[serde(default)]
+    pub next_refresh: Option<DateTime<Utc>>,
 }
 
 impl LibraryStats {
@@ -266,6 +501,11 @@ impl LibraryStats {
             enqueued: 0,
             indexed: 0,
             failed: 0,
+            user_enqueued: 0,
+            index_size_bytes: 0,
+            db_size_bytes: 0,
+            last_refreshed: None,
+            next_refresh: None,
         }
     }
 
@@ -282,6 +522,13 @@ impl LibraryStats {
     }
 
     pub fn status_string(&self) -> String {
+        // Surface the user's own requests ahead of background crawl stats.
+        if self.user_enqueued > 0 {
+            let mut remaining = Buffer::default();
+            remaining.write_formatted(&self.user_enqueued, &Locale::en);
+            return format!("Processing your request: {remaining} left");
+        }
+
         // For plugins/connections where we don't know exactly how many there are
         if self.enqueued == 0 {
             let mut indexed = Buffer::default();
@@ -298,6 +545,18 @@ impl LibraryStats {
     }
 }
 
+/// A lens proposed from the user's browsing history by `suggest_lens`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SuggestedLensResult {
+    /// Suggested name for the lens.
+    pub name: String,
+    /// The proposed lens file as RON text, ready to edit & save into the
+    /// lens directory.
+    pub config: String,
+    /// Domains included in the proposal & how often each was seen.
+    pub domains: Vec<(String, u64)>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, TS)]
 #[ts(export)]
 pub struct DefaultIndices {
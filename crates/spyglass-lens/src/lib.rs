@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use blake2::{Blake2s256, Digest};
@@ -6,7 +7,7 @@ use serde::{Deserialize, Serialize};
 pub mod pipeline;
 pub mod types;
 mod utils;
-use types::{LensFilters, LensRule, LensSource};
+use types::{LensFilters, LensRule, LensSource, LensTrigger, UrlRegexAction};
 
 pub use crate::pipeline::PipelineConfiguration;
 use utils::{regex_for_domain, regex_for_prefix};
@@ -33,8 +34,10 @@ pub struct LensConfig {
     /// Rules to skip/constrain what URLs are indexed
     #[serde(default)]
     pub rules: Vec<LensRule>,
+    /// Searchbar trigger(s). Accepts a single string or a list of aliases
+    /// that all map to this lens; the first alias is the canonical one.
     #[serde(default)]
-    pub trigger: String,
+    pub trigger: LensTrigger,
     #[serde(default)]
     pub pipeline: Option<String>,
     #[serde(default)]
@@ -45,6 +48,35 @@ pub struct LensConfig {
     /// Tags to automatically apply to any URLs indexed by this lens
     #[serde(default)]
     pub tags: Vec<(String, String)>,
+    /// Regex matched against a document's url; `tags` values may reference
+    /// its capture groups (e.g. `("org", "$1")`). Urls that don't match only
+    /// get the static tags.
+    #[serde(default)]
+    pub url_pattern: Option<String>,
+    /// Names of other lenses in the lens directory whose domains, urls,
+    /// rules & tags are merged into this one, so shared rules live in one
+    /// base lens instead of being copy-pasted.
+    #[serde(default)]
+    pub extends: Vec<String>,
+    /// CSS selector that scopes content extraction to a specific container,
+    /// e.g. `#main-content`. Pages where nothing matches fall back to
+    /// whole-page extraction.
+    #[serde(default)]
+    pub content_selector: Option<String>,
+    /// CSS selectors stripped out before content extraction (sidebars,
+    /// comment sections, etc).
+    #[serde(default)]
+    pub exclude_selectors: Vec<String>,
+    /// Local folder trees indexed by this lens. Folders are watched
+    /// independently of the global filesystem scanning setting & resulting
+    /// documents are tagged w/ the lens tags. Tilde expansion & path
+    /// validation happen at lens load.
+    #[serde(default)]
+    pub folders: Vec<PathBuf>,
+    /// How often (in hours) documents indexed by this lens should be
+    /// recrawled. Unset means documents are never automatically refreshed.
+    #[serde(default)]
+    pub recrawl_interval_hours: Option<u32>,
     // Fields that are used internally & should not be serialized/deserialized
     #[serde(skip)]
     pub file_path: PathBuf,
@@ -86,8 +118,13 @@ impl LensConfig {
         for rule in &self.rules {
             match rule {
                 LensRule::LimitURLDepth { .. } => allowed.push(rule.to_regex()),
+                LensRule::LimitURLDepthFrom { .. } => allowed.push(rule.to_regex()),
                 LensRule::SkipURL(_) => skipped.push(rule.to_regex()),
                 LensRule::SanitizeUrls(_, _) => {}
+                LensRule::UrlRegex { action, .. } => match action {
+                    UrlRegexAction::Allow => allowed.push(rule.to_regex()),
+                    UrlRegexAction::Skip => skipped.push(rule.to_regex()),
+                },
             }
         }
 
@@ -102,12 +139,44 @@ impl LensConfig {
         match ron::from_str::<LensConfig>(contents) {
             Ok(mut lens) => {
                 lens.hash = hash_hex;
+                lens.validate()?;
                 Ok(lens)
             }
             Err(e) => Err(anyhow::Error::msg(e.to_string())),
         }
     }
 
+    /// Checks rule contents the deserializer can't, e.g. regex patterns.
+    /// Errors name the offending rule so lens authors can find it.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (idx, rule) in self.rules.iter().enumerate() {
+            match rule {
+                LensRule::UrlRegex { pattern, .. } => {
+                    if let Err(err) = regex::Regex::new(pattern) {
+                        return Err(anyhow::anyhow!("rule #{idx} has an invalid regex: {err}"));
+                    }
+                }
+                // An empty rule string would panic when converted to a regex.
+                LensRule::SkipURL(raw)
+                | LensRule::SanitizeUrls(raw, _)
+                | LensRule::LimitURLDepthFrom(raw, _) => {
+                    if raw.is_empty() {
+                        return Err(anyhow::anyhow!("rule #{idx} is empty"));
+                    }
+                }
+                LensRule::LimitURLDepth(_, _) => {}
+            }
+        }
+
+        if let Some(pattern) = &self.url_pattern {
+            if let Err(err) = regex::Regex::new(pattern) {
+                return Err(anyhow::anyhow!("url_pattern is an invalid regex: {err}"));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn from_path(path: PathBuf) -> anyhow::Result<Self> {
         let contents = std::fs::read_to_string(path.clone())?;
         match Self::from_string(&contents) {
@@ -126,14 +195,157 @@ impl LensConfig {
         for cat in self.categories.iter() {
             tags.push(("category".into(), cat.clone()));
         }
-        tags.extend(self.tags.clone());
+
+        // Templated tag values (e.g. `"$1"`) are resolved per-url w/
+        // `template_tags_for_url`, not applied wholesale.
+        for tag in &self.tags {
+            if !tag.1.contains('$') {
+                tags.push(tag.clone());
+            }
+        }
+
+        tags
+    }
+
+    /// Resolves tag templates against a document's url: tag values
+    /// referencing capture groups from `url_pattern` (e.g. `("org", "$1")`)
+    /// are expanded w/ the pattern's captures. Returns nothing when no
+    /// pattern is set or the url doesn't match; static tags are handled by
+    /// `all_tags`.
+    pub fn template_tags_for_url(&self, url: &str) -> Vec<(String, String)> {
+        let re = match &self.url_pattern {
+            Some(pattern) => match regex::Regex::new(pattern) {
+                Ok(re) => re,
+                Err(_) => return Vec::new(),
+            },
+            None => return Vec::new(),
+        };
+
+        let captures = match re.captures(url) {
+            Some(captures) => captures,
+            None => return Vec::new(),
+        };
+
+        let mut tags = Vec::new();
+        for (label, value) in &self.tags {
+            if !value.contains('$') {
+                continue;
+            }
+
+            let mut expanded = String::new();
+            captures.expand(value, &mut expanded);
+            // A group that didn't participate expands to nothing; an empty
+            // tag value is never valid.
+            if !expanded.is_empty() {
+                tags.push((label.clone(), expanded));
+            }
+        }
 
         tags
     }
+
+    /// Returns a copy of this lens w/ config contributed by its `extends`
+    /// parents merged in. Parents are resolved by name from `lenses` (the
+    /// other lenses in the lens directory) & may themselves extend other
+    /// lenses. The child's own entries are kept first so they win where
+    /// order matters; scalar fields (name, trigger, etc.) always come from
+    /// the child. Errors on unknown parents or a reference cycle.
+    pub fn resolve_extends(&self, lenses: &HashMap<String, LensConfig>) -> anyhow::Result<Self> {
+        let mut merged = self.clone();
+        let mut visiting = vec![self.name.clone()];
+        merged.apply_parents(lenses, &mut visiting)?;
+        Ok(merged)
+    }
+
+    fn apply_parents(
+        &mut self,
+        lenses: &HashMap<String, LensConfig>,
+        visiting: &mut Vec<String>,
+    ) -> anyhow::Result<()> {
+        for parent_name in std::mem::take(&mut self.extends) {
+            if visiting.contains(&parent_name) {
+                return Err(anyhow::anyhow!(
+                    "cycle detected in lens extends chain: \"{}\" -> \"{}\"",
+                    self.name,
+                    parent_name
+                ));
+            }
+
+            let parent = lenses.get(&parent_name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "lens \"{}\" extends \"{}\", which is not in the lens directory",
+                    self.name,
+                    parent_name
+                )
+            })?;
+
+            // Resolve grandparents first so the parent is fully merged
+            // before its config is folded into ours.
+            let mut parent = parent.clone();
+            visiting.push(parent_name);
+            parent.apply_parents(lenses, visiting)?;
+            visiting.pop();
+
+            self.merge_from(&parent);
+        }
+
+        Ok(())
+    }
+
+    /// Folds a parent's config into this lens. Entries already present are
+    /// skipped so overlapping parents don't pile up duplicates.
+    fn merge_from(&mut self, parent: &LensConfig) {
+        for domain in &parent.domains {
+            if !self.domains.contains(domain) {
+                self.domains.push(domain.clone());
+            }
+        }
+
+        for url in &parent.urls {
+            if !self.urls.contains(url) {
+                self.urls.push(url.clone());
+            }
+        }
+
+        for rule in &parent.rules {
+            if !self
+                .rules
+                .iter()
+                .any(|existing| existing.to_string() == rule.to_string())
+            {
+                self.rules.push(rule.clone());
+            }
+        }
+
+        for tag in &parent.tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+
+        for folder in &parent.folders {
+            if !self.folders.contains(folder) {
+                self.folders.push(folder.clone());
+            }
+        }
+
+        // Selectors are overrides: the parent's only apply when the child
+        // doesn't declare its own.
+        if self.content_selector.is_none() {
+            self.content_selector = parent.content_selector.clone();
+        }
+        if self.exclude_selectors.is_empty() {
+            self.exclude_selectors = parent.exclude_selectors.clone();
+        }
+        if self.url_pattern.is_none() {
+            self.url_pattern = parent.url_pattern.clone();
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::types::{LensRule, UrlRegexAction};
     use super::LensConfig;
 
     #[test]
@@ -156,6 +368,30 @@ mod test {
             .contains(&"^https://oldschool.runescape.wiki/w/.*".to_string()));
     }
 
+    #[test]
+    fn test_url_regex_rules() {
+        let lens_str = include_str!("../../../fixtures/lens/url_regex.ron");
+        let config = LensConfig::from_string(lens_str).expect("Unable to parse lens");
+
+        let regexes = config.into_regexes();
+        assert_eq!(
+            regexes.allowed,
+            vec!["^https://wiki\\.example\\.com/w/.*".to_string()]
+        );
+        assert_eq!(regexes.skipped, vec!["(/Talk:|\\?action=)".to_string()]);
+
+        // Invalid patterns are rejected at load time, naming the rule.
+        let invalid = LensConfig {
+            rules: vec![LensRule::UrlRegex {
+                pattern: "(unclosed".into(),
+                action: UrlRegexAction::Skip,
+            }],
+            ..Default::default()
+        };
+        let err = invalid.validate().expect_err("should be invalid");
+        assert!(err.to_string().contains("rule #0"));
+    }
+
     #[test]
     fn test_load_from_file() {
         let lens_str = include_str!("../../../fixtures/lens/extra_fields.ron");
@@ -166,6 +402,120 @@ mod test {
         assert_eq!(config.name, "extra_fields");
     }
 
+    #[test]
+    fn test_resolve_extends() {
+        use std::collections::HashMap;
+
+        let base = LensConfig {
+            name: "base-wiki".into(),
+            domains: vec!["example.com".into()],
+            rules: vec![LensRule::SkipURL("https://example.com/Talk:*".into())],
+            tags: vec![("source".into(), "wiki".into())],
+            ..Default::default()
+        };
+        let child = LensConfig {
+            name: "child".into(),
+            urls: vec!["https://wiki.example.com/w/".into()],
+            extends: vec!["base-wiki".into()],
+            tags: vec![("topic".into(), "games".into())],
+            ..Default::default()
+        };
+
+        let lenses = HashMap::from([
+            (base.name.clone(), base),
+            (child.name.clone(), child.clone()),
+        ]);
+        let merged = child.resolve_extends(&lenses).expect("resolve failed");
+
+        // Parent domains & rules are picked up alongside the child's urls.
+        let regexes = merged.into_regexes();
+        assert!(regexes
+            .allowed
+            .contains(&"^(http://|https://)example\\.com.*".to_string()));
+        assert!(regexes
+            .allowed
+            .contains(&"^https://wiki.example.com/w/.*".to_string()));
+        assert_eq!(regexes.skipped.len(), 1);
+
+        // Tags merge; the lens tag keeps the child's name.
+        let tags = merged.all_tags();
+        assert!(tags.contains(&("lens".into(), "child".into())));
+        assert!(tags.contains(&("topic".into(), "games".into())));
+        assert!(tags.contains(&("source".into(), "wiki".into())));
+    }
+
+    #[test]
+    fn test_resolve_extends_errors() {
+        use std::collections::HashMap;
+
+        // Missing parents are a clear error.
+        let orphan = LensConfig {
+            name: "orphan".into(),
+            extends: vec!["no-such-lens".into()],
+            ..Default::default()
+        };
+        let lenses = HashMap::from([(orphan.name.clone(), orphan.clone())]);
+        let err = orphan.resolve_extends(&lenses).expect_err("should fail");
+        assert!(err.to_string().contains("no-such-lens"));
+
+        // Cycles are detected rather than recursing forever.
+        let a = LensConfig {
+            name: "a".into(),
+            extends: vec!["b".into()],
+            ..Default::default()
+        };
+        let b = LensConfig {
+            name: "b".into(),
+            extends: vec!["a".into()],
+            ..Default::default()
+        };
+        let lenses = HashMap::from([(a.name.clone(), a.clone()), (b.name.clone(), b)]);
+        let err = a.resolve_extends(&lenses).expect_err("should fail");
+        assert!(err.to_string().contains("cycle"));
+    }
+
+    #[test]
+    fn test_template_tags_for_url() {
+        let config = LensConfig {
+            name: "github".into(),
+            url_pattern: Some(r"^https://github\.com/([^/]+)/([^/]+)".into()),
+            tags: vec![
+                ("source".into(), "github".into()),
+                ("org".into(), "$1".into()),
+                ("repo".into(), "$2".into()),
+            ],
+            ..Default::default()
+        };
+
+        // all_tags only carries the static tags; templated values resolve
+        // per-url.
+        let tags = config.all_tags();
+        assert!(tags.contains(&("source".into(), "github".into())));
+        assert!(!tags.iter().any(|(_, value)| value.contains('$')));
+
+        let resolved =
+            config.template_tags_for_url("https://github.com/spyglass-search/spyglass/issues/1");
+        assert_eq!(
+            resolved,
+            vec![
+                ("org".into(), "spyglass-search".into()),
+                ("repo".into(), "spyglass".into())
+            ]
+        );
+
+        // Urls that don't match the pattern only get the static tags.
+        assert!(config
+            .template_tags_for_url("https://example.com/")
+            .is_empty());
+
+        // Invalid patterns fail validation.
+        let invalid = LensConfig {
+            url_pattern: Some("(unclosed".into()),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_all_tags() {
         let config = LensConfig {
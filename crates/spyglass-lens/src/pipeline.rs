@@ -6,6 +6,95 @@ pub struct CollectorConfiguration {}
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct ParserConfiguration {}
 
+/// A single regex find/replace applied to scraped content.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct FindReplaceRule {
+    /// Regex to search for. Capture groups can be referenced in `replace`
+    /// w/ `$1`, `$2`, etc.
+    pub find: String,
+    /// Replacement text; defaults to removing the match.
+    #[serde(default)]
+    pub replace: String,
+}
+
+/// Content post-processing applied after parsing & before indexing, e.g. to
+/// strip "Edit this page" boilerplate that would otherwise pollute snippets.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PostProcessConfiguration {
+    /// Regex find/replace rules, applied in order.
+    #[serde(default)]
+    pub replace: Vec<FindReplaceRule>,
+    /// Lines matching any of these regexes are dropped entirely.
+    #[serde(default)]
+    pub strip_lines_matching: Vec<String>,
+    /// Content is truncated to at most this many characters.
+    #[serde(default)]
+    pub max_content_length: Option<usize>,
+}
+
+impl PostProcessConfiguration {
+    /// Runs content through each configured transform. Invalid regexes are
+    /// skipped; `validate` reports them at load time.
+    pub fn process(&self, content: &str) -> String {
+        let mut content = content.to_string();
+
+        for rule in &self.replace {
+            if let Ok(re) = regex::Regex::new(&rule.find) {
+                content = re.replace_all(&content, rule.replace.as_str()).to_string();
+            }
+        }
+
+        if !self.strip_lines_matching.is_empty() {
+            let matchers = self
+                .strip_lines_matching
+                .iter()
+                .filter_map(|pattern| regex::Regex::new(pattern).ok())
+                .collect::<Vec<regex::Regex>>();
+            content = content
+                .lines()
+                .filter(|line| !matchers.iter().any(|re| re.is_match(line)))
+                .collect::<Vec<&str>>()
+                .join("\n");
+        }
+
+        if let Some(max_length) = self.max_content_length {
+            if content.len() > max_length {
+                // Truncate on a char boundary so multi-byte text can't panic.
+                let cutoff = (0..=max_length)
+                    .rev()
+                    .find(|idx| content.is_char_boundary(*idx))
+                    .unwrap_or(0);
+                content.truncate(cutoff);
+            }
+        }
+
+        content
+    }
+
+    /// Checks each configured regex, naming the offending pattern so pipeline
+    /// authors can find it.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for rule in &self.replace {
+            if let Err(err) = regex::Regex::new(&rule.find) {
+                return Err(anyhow::anyhow!(
+                    "replace rule \"{}\" has an invalid regex: {err}",
+                    rule.find
+                ));
+            }
+        }
+
+        for pattern in &self.strip_lines_matching {
+            if let Err(err) = regex::Regex::new(pattern) {
+                return Err(anyhow::anyhow!(
+                    "strip_lines_matching \"{pattern}\" is an invalid regex: {err}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 // Pipeline user configuration
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct PipelineConfiguration {
@@ -14,4 +103,78 @@ pub struct PipelineConfiguration {
     pub collector: Option<CollectorConfiguration>,
     #[serde(default)]
     pub parser: Option<ParserConfiguration>,
+    /// Content post-processing applied before indexing.
+    #[serde(default)]
+    pub post_process: Option<PostProcessConfiguration>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FindReplaceRule, PipelineConfiguration, PostProcessConfiguration};
+
+    #[test]
+    fn test_post_process_replace() {
+        let config = PostProcessConfiguration {
+            replace: vec![
+                FindReplaceRule {
+                    find: "Edit this page on GitHub".into(),
+                    replace: "".into(),
+                },
+                FindReplaceRule {
+                    find: r"(\w+)@example\.com".into(),
+                    replace: "$1".into(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let content = "Some docs. Edit this page on GitHub. Contact bob@example.com";
+        assert_eq!(config.process(content), "Some docs. . Contact bob");
+    }
+
+    #[test]
+    fn test_post_process_strip_lines() {
+        let config = PostProcessConfiguration {
+            strip_lines_matching: vec!["^Last updated".into(), "©".into()],
+            ..Default::default()
+        };
+
+        let content = "Real content\nLast updated 2023-01-01\n© Example Corp\nMore content";
+        assert_eq!(config.process(content), "Real content\nMore content");
+    }
+
+    #[test]
+    fn test_post_process_max_length() {
+        let config = PostProcessConfiguration {
+            max_content_length: Some(7),
+            ..Default::default()
+        };
+
+        assert_eq!(config.process("0123456789"), "0123456");
+        // Truncation never splits a multi-byte char.
+        assert_eq!(config.process("héllo wörld"), "héllo ");
+        // Short content is left alone.
+        assert_eq!(config.process("short"), "short");
+    }
+
+    #[test]
+    fn test_post_process_fixture() {
+        let config: PipelineConfiguration =
+            ron::from_str(include_str!("../../../fixtures/pipeline/post_process.ron"))
+                .expect("Unable to parse pipeline");
+        assert_eq!(config.kind, "default");
+
+        let post_process = config.post_process.expect("Expected post_process stage");
+        post_process.validate().expect("Invalid regexes in fixture");
+        let processed =
+            post_process.process("A page\nEdit this page on GitHub\nLast updated yesterday");
+        assert_eq!(processed, "A page");
+
+        // Invalid regexes are reported by validate.
+        let invalid = PostProcessConfiguration {
+            strip_lines_matching: vec!["(unclosed".into()],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
 }
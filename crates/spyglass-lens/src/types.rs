@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use crate::utils::regex_for_robots;
+use crate::utils::{regex_for_depth_from, regex_for_robots};
 
 pub struct LensFilters {
     pub allowed: Vec<String>,
@@ -17,10 +17,87 @@ pub enum LensRule {
     ///  - LimitURLDepth("https://example.com/", 2) will limit it to https://example.com/<path 1>/<path 2>
     ///  - etc.
     LimitURLDepth(String, u8),
+    /// Like `LimitURLDepth` but the depth is counted from the given base url
+    /// (e.g. a seed prefix) instead of the domain root.
+    /// For example:
+    ///  - LimitURLDepthFrom("https://site.com/docs/v2/", 2) will limit it to
+    ///    https://site.com/docs/v2/<path 1>/<path 2>
+    LimitURLDepthFrom(String, u8),
     /// Skips are applied when bootstrapping & crawling
     SkipURL(String),
-    /// Modifies the url to walk, applied when bootstrapping & crawling   
+    /// Modifies the url to walk, applied when bootstrapping & crawling
     SanitizeUrls(String, UrlSanitizeConfig),
+    /// Full regex matched against the URL, for filters that can't be
+    /// expressed w/ the glob-ish syntax above. `Allow` adds matches to the
+    /// allow list, `Skip` filters them out; skips win when both match.
+    UrlRegex {
+        pattern: String,
+        action: UrlRegexAction,
+    },
+}
+
+/// Whether URLs matching a `UrlRegex` rule are allowed or skipped.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum UrlRegexAction {
+    Allow,
+    Skip,
+}
+
+/// One or more searchbar triggers for a lens. Deserializes from either a
+/// plain string (the historical format) or a list of aliases; the first
+/// alias is the canonical one stored in the database.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum LensTrigger {
+    Single(String),
+    Aliases(Vec<String>),
+}
+
+impl Default for LensTrigger {
+    fn default() -> Self {
+        Self::Single(String::new())
+    }
+}
+
+impl LensTrigger {
+    /// All non-empty aliases for this trigger.
+    pub fn aliases(&self) -> Vec<String> {
+        match self {
+            Self::Single(trigger) => {
+                if trigger.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![trigger.clone()]
+                }
+            }
+            Self::Aliases(aliases) => aliases
+                .iter()
+                .filter(|alias| !alias.is_empty())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// The canonical trigger, i.e. the first alias.
+    pub fn primary(&self) -> Option<String> {
+        self.aliases().first().cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases().is_empty()
+    }
+}
+
+impl From<String> for LensTrigger {
+    fn from(trigger: String) -> Self {
+        Self::Single(trigger)
+    }
+}
+
+impl From<&str> for LensTrigger {
+    fn from(trigger: &str) -> Self {
+        Self::Single(trigger.to_owned())
+    }
 }
 
 /// Defines Url Sanitization Configuration. This configuration allows urls to be modified to
@@ -45,8 +122,14 @@ impl fmt::Display for LensRule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::LimitURLDepth(url, depth) => write!(f, "LimitURLDepth(\"{url}\", {depth})"),
+            Self::LimitURLDepthFrom(url, depth) => {
+                write!(f, "LimitURLDepthFrom(\"{url}\", {depth})")
+            }
             Self::SkipURL(url) => write!(f, "SkipURL(\"{url}\")",),
             Self::SanitizeUrls(url, config) => write!(f, "SanitizeUrls(\"{url}\", {config}"),
+            Self::UrlRegex { pattern, action } => {
+                write!(f, "UrlRegex(\"{pattern}\", {action:?})")
+            }
         }
     }
 }
@@ -59,12 +142,17 @@ impl LensRule {
                 let regex = format!("^{prefix}/?(/[^/]+/?){{0, {max_depth}}}$");
                 regex
             }
+            LensRule::LimitURLDepthFrom(base, max_depth) => {
+                regex_for_depth_from(base, *max_depth)
+            }
             LensRule::SkipURL(rule_str) => {
                 regex_for_robots(rule_str).expect("Invalid SkipURL regex")
             }
             LensRule::SanitizeUrls(rule_str, _) => {
                 regex_for_robots(rule_str).expect("Invalid SanitizeUrls regex")
             }
+            // Already a full regex; patterns are validated at lens load time.
+            LensRule::UrlRegex { pattern, .. } => pattern.clone(),
         }
     }
 }
@@ -88,8 +176,26 @@ pub enum LensSource {
 #[cfg(test)]
 mod test {
     use super::LensRule;
+    use super::LensTrigger;
     use super::UrlSanitizeConfig;
 
+    #[test]
+    fn test_trigger_aliases() {
+        // Historical single-string format.
+        let trigger: LensTrigger = ron::from_str(r#""gh""#).expect("Unable to parse");
+        assert_eq!(trigger, LensTrigger::Single("gh".into()));
+        assert_eq!(trigger.primary(), Some("gh".to_string()));
+
+        // List of aliases; the first one is canonical.
+        let trigger: LensTrigger = ron::from_str(r#"["gh", "github"]"#).expect("Unable to parse");
+        assert_eq!(trigger.aliases(), vec!["gh".to_string(), "github".to_string()]);
+        assert_eq!(trigger.primary(), Some("gh".to_string()));
+
+        // Empty strings don't count as triggers.
+        assert!(LensTrigger::default().is_empty());
+        assert!(LensTrigger::Aliases(vec!["".into()]).is_empty());
+    }
+
     #[test]
     fn test_rules_display() {
         let rule = LensRule::SkipURL("http://example.com".to_string());
@@ -108,5 +214,44 @@ mod test {
             rule.to_string(),
             "SanitizeUrls(\"www.hello.com\", UrlSanitizeConfig { remove_query_parameter: true }"
         );
+
+        let rule = LensRule::UrlRegex {
+            pattern: "(/Talk:|\\?action=)".to_string(),
+            action: super::UrlRegexAction::Skip,
+        };
+        assert_eq!(rule.to_string(), "UrlRegex(\"(/Talk:|\\?action=)\", Skip)");
+        // The pattern is used as-is.
+        assert_eq!(rule.to_regex(), "(/Talk:|\\?action=)");
+    }
+
+    #[test]
+    fn test_limit_url_depth_from() {
+        let rule = LensRule::LimitURLDepthFrom("https://site.com/docs/v2/".to_string(), 2);
+        assert_eq!(
+            rule.to_string(),
+            "LimitURLDepthFrom(\"https://site.com/docs/v2/\", 2)"
+        );
+
+        // The seed behaves the same w/ or w/o a trailing slash.
+        let no_slash = LensRule::LimitURLDepthFrom("https://site.com/docs/v2".to_string(), 2);
+        assert_eq!(rule.to_regex(), no_slash.to_regex());
+
+        let re = regex::Regex::new(&rule.to_regex()).expect("Invalid regex");
+        for url in [
+            "https://site.com/docs/v2",
+            "https://site.com/docs/v2/",
+            "https://site.com/docs/v2/getting-started",
+            "https://site.com/docs/v2/getting-started/install/",
+        ] {
+            assert!(re.is_match(url), "expected {url} to match");
+        }
+
+        for url in [
+            "https://site.com/docs/v2/a/b/c",
+            "https://site.com/docs/v1/getting-started",
+            "https://site.com/docs",
+        ] {
+            assert!(!re.is_match(url), "expected {url} to not match");
+        }
     }
 }
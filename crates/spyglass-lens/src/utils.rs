@@ -20,6 +20,15 @@ pub fn regex_for_prefix(prefix: &str) -> String {
     format!("^{prefix}.*")
 }
 
+/// Builds a regex limiting how many path segments may follow `base`, e.g.
+/// "https://site.com/docs/v2/" w/ a depth of 2 allows
+/// https://site.com/docs/v2/<path 1>/<path 2> & nothing deeper. Behaves the
+/// same whether or not the base ends w/ a trailing slash.
+pub fn regex_for_depth_from(base: &str, max_depth: u8) -> String {
+    let base = base.trim_end_matches('/');
+    format!("^{base}/?(/[^/]+/?){{0,{max_depth}}}$")
+}
+
 /// Convert a robots.txt rule into a proper regex string
 pub fn regex_for_robots(rule: &str) -> Option<String> {
     if rule.is_empty() {
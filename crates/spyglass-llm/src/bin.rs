@@ -1,4 +1,4 @@
-use shared::llm::{ChatMessage, ChatRole, ChatStream, LlmSession};
+use shared::llm::{ChatMessage, ChatRole, ChatStream, GenerationParams, LlmSession};
 use spyglass_llm::LlmClient;
 /// This is mainly for testing the llm client, should remove this after it's comfortably
 /// integrated with the other code.
@@ -26,6 +26,7 @@ pub async fn main() -> Result<(), anyhow::Error> {
                 content: "What is the capital of Zimbabwe?".into(),
             },
         ],
+        params: GenerationParams::default(),
     };
 
     let (tx, mut rx) = mpsc::channel(10);
@@ -43,9 +44,26 @@ pub async fn main() -> Result<(), anyhow::Error> {
                     print!("{tok}");
                     std::io::stdout().flush().unwrap();
                 }
-                ChatStream::ChatDone => {
+                ChatStream::ContextTruncated(dropped) => {
+                    log::warn!("some context omitted: {}", dropped.join(", "));
+                }
+                ChatStream::Error(err) => {
+                    log::error!("chat failed: {err}");
+                }
+                ChatStream::Cancelled => {
+                    log::info!("chat cancelled");
+                }
+                ChatStream::ChatDone(stats) => {
                     println!("🤖");
                     log::info!("DONE!");
+                    log::info!(
+                        "{} prompt + {} generated tokens, {}ms + {}ms ({:.2} token/s)",
+                        stats.prompt_tokens,
+                        stats.generated_tokens,
+                        stats.prompt_ms,
+                        stats.generation_ms,
+                        stats.tokens_per_second
+                    );
                 }
             }
         }
@@ -53,7 +71,7 @@ pub async fn main() -> Result<(), anyhow::Error> {
 
     match LlmClient::new("assets/models/llm/llama3/Llama-3.2-3B-Instruct.Q5_K_M.gguf".into()) {
         Ok(mut client) => {
-            client.chat(&prompt, Some(tx)).await?;
+            client.chat(&prompt, Some(tx), None).await?;
         }
         Err(error) => {
             log::error!("Error loading model {error}");
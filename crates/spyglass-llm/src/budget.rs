@@ -0,0 +1,154 @@
+//! Prompt budgeting for RAG chats. When retrieved segments plus chat history
+//! exceed the model's context window the tokenizer silently truncates the
+//! prompt & answers end up referencing text the model never saw. The budgeter
+//! drops pieces until the prompt fits w/ headroom for the reply.
+use shared::llm::ChatMessage;
+
+/// Context window assumed for models that don't report one (e.g. remote
+/// servers).
+pub const DEFAULT_CONTEXT_LENGTH: usize = 8_192;
+
+/// Rough allowance for template control tokens & role markers.
+const TEMPLATE_HEADROOM: usize = 64;
+
+/// Rough token count used when the model's tokenizer isn't available; ~4
+/// chars per token is a decent approximation for English text.
+pub fn estimate_tokens(content: &str) -> usize {
+    content.chars().count().div_ceil(4)
+}
+
+/// The parts of a prompt that survived budgeting.
+pub struct BudgetedPrompt {
+    /// Context segments that fit, still in rank order.
+    pub segments: Vec<String>,
+    /// History turns that fit, still oldest first.
+    pub history: Vec<ChatMessage>,
+    /// Human-readable description of each dropped piece, for a "some context
+    /// omitted" warning. Empty when everything fit.
+    pub dropped: Vec<String>,
+}
+
+/// Fits `segments` (ranked best first) & `history` (oldest first) into
+/// `context_length` tokens, always keeping the `fixed` content (e.g. the
+/// system prompt) & reserving `max_tokens` for the reply. Lowest-ranked
+/// segments are dropped first, then the oldest history turns.
+pub fn budget_prompt(
+    context_length: usize,
+    max_tokens: usize,
+    count: impl Fn(&str) -> usize,
+    fixed: &[&str],
+    segments: &[String],
+    history: &[ChatMessage],
+) -> BudgetedPrompt {
+    let budget = context_length.saturating_sub(max_tokens + TEMPLATE_HEADROOM);
+
+    let segment_tokens = segments
+        .iter()
+        .map(|segment| count(segment))
+        .collect::<Vec<usize>>();
+    let history_tokens = history
+        .iter()
+        .map(|msg| count(&msg.content))
+        .collect::<Vec<usize>>();
+    let mut total = fixed.iter().map(|content| count(content)).sum::<usize>()
+        + segment_tokens.iter().sum::<usize>()
+        + history_tokens.iter().sum::<usize>();
+
+    let mut num_segments = segments.len();
+    while total > budget && num_segments > 0 {
+        num_segments -= 1;
+        total -= segment_tokens[num_segments];
+    }
+
+    // The last turn is the question being asked; dropping it would leave
+    // nothing to answer.
+    let mut oldest_kept = 0;
+    while total > budget && oldest_kept + 1 < history.len() {
+        total -= history_tokens[oldest_kept];
+        oldest_kept += 1;
+    }
+
+    let mut dropped = Vec::new();
+    for (idx, tokens) in segment_tokens.iter().enumerate().skip(num_segments) {
+        dropped.push(format!("context segment #{} (~{tokens} tokens)", idx + 1));
+    }
+    for (idx, tokens) in history_tokens.iter().enumerate().take(oldest_kept) {
+        dropped.push(format!("history turn #{} (~{tokens} tokens)", idx + 1));
+    }
+
+    BudgetedPrompt {
+        segments: segments[..num_segments].to_vec(),
+        history: history[oldest_kept..].to_vec(),
+        dropped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{budget_prompt, estimate_tokens, TEMPLATE_HEADROOM};
+    use shared::llm::{ChatMessage, ChatRole};
+
+    fn msg(role: ChatRole, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_budget_prompt_everything_fits() {
+        let segments = vec!["first segment".to_string(), "second segment".to_string()];
+        let history = vec![msg(ChatRole::User, "what is this?")];
+        // One token per char keeps the test math simple.
+        let budgeted = budget_prompt(100_000, 1024, |c| c.len(), &["sys"], &segments, &history);
+        assert_eq!(budgeted.segments, segments);
+        assert_eq!(budgeted.history.len(), 1);
+        assert!(budgeted.dropped.is_empty());
+    }
+
+    #[test]
+    fn test_budget_prompt_drops_lowest_ranked_segments_first() {
+        // Budget of 20 tokens after the reply reservation & headroom.
+        let context_length = TEMPLATE_HEADROOM + 16 + 20;
+        let segments = vec!["aaaaaaaaaa".to_string(), "bbbbbbbbbb".to_string()];
+        let history = vec![msg(ChatRole::User, "ccccc")];
+        let budgeted = budget_prompt(context_length, 16, |c| c.len(), &["sys"], &segments, &history);
+        // 3 + 20 + 5 tokens is over budget; the lowest ranked segment goes.
+        assert_eq!(budgeted.segments, vec!["aaaaaaaaaa".to_string()]);
+        assert_eq!(budgeted.history.len(), 1);
+        assert_eq!(budgeted.dropped, vec!["context segment #2 (~10 tokens)"]);
+    }
+
+    #[test]
+    fn test_budget_prompt_drops_oldest_history_but_keeps_last_turn() {
+        // Budget of 4 tokens; no segments, so old history turns have to go.
+        let context_length = TEMPLATE_HEADROOM + 16 + 4;
+        let history = vec![
+            msg(ChatRole::User, "aaaa"),
+            msg(ChatRole::Assistant, "bbbb"),
+            msg(ChatRole::User, "cc"),
+        ];
+        let budgeted = budget_prompt(context_length, 16, |c| c.len(), &[], &[], &history);
+        assert_eq!(budgeted.history.len(), 1);
+        assert_eq!(budgeted.history[0].content, "cc");
+        assert_eq!(
+            budgeted.dropped,
+            vec![
+                "history turn #1 (~4 tokens)".to_string(),
+                "history turn #2 (~4 tokens)".to_string(),
+            ]
+        );
+
+        // The current question is never dropped, even when over budget.
+        let budgeted = budget_prompt(0, 16, |c| c.len(), &[], &[], &history[2..]);
+        assert_eq!(budgeted.history.len(), 1);
+        assert!(budgeted.dropped.is_empty());
+    }
+}
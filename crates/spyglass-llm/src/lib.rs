@@ -1,47 +1,165 @@
 use anyhow::Result;
 use lazy_static::lazy_static;
 use model::LLMModel;
-use shared::llm::{ChatMessage, ChatRole, ChatStream, LlmSession};
+use shared::llm::{ChatMessage, ChatRole, ChatStats, ChatStream, LlmSession};
 use std::path::PathBuf;
 use tera::{Context, Tera};
 
+pub mod budget;
 pub mod model;
+pub mod remote;
 pub mod sampler;
 mod token_output_stream;
 
+/// Signal used to stop an in-flight generation: flip the paired
+/// `watch::Sender` to `true` & the backend ends its sampling loop w/ a
+/// `ChatStream::Cancelled` event.
+pub type CancelToken = tokio::sync::watch::Receiver<bool>;
+
+pub(crate) fn is_cancelled(cancel: &Option<CancelToken>) -> bool {
+    cancel
+        .as_ref()
+        .map(|cancel| *cancel.borrow())
+        .unwrap_or(false)
+}
+
+/// A chat backend. Local gguf inference & remote OpenAI-compatible servers
+/// implement the same streaming interface so callers don't need to care
+/// which one is active.
+#[async_trait::async_trait]
+pub trait LlmBackend: Send + Sync {
+    async fn chat(
+        &mut self,
+        session: &LlmSession,
+        stream: Option<tokio::sync::mpsc::Sender<ChatStream>>,
+        cancel: Option<CancelToken>,
+    ) -> Result<ChatMessage>;
+
+    /// Context window of the model in tokens, when the backend knows it.
+    fn context_length(&self) -> Option<usize> {
+        None
+    }
+}
+
 lazy_static! {
     pub static ref TEMPLATES: Tera = {
-        match Tera::new("assets/templates/llm/*.txt") {
-            Ok(t) => t,
-            Err(err) => {
-                eprintln!("Parsing error: {err}");
-                ::std::process::exit(1);
-            }
+        // Templates are embedded in the binary so rendering doesn't depend on
+        // the working directory.
+        let mut tera = Tera::default();
+        let templates = vec![
+            (
+                "chatml.txt",
+                include_str!("../../../assets/templates/llm/chatml.txt"),
+            ),
+            (
+                "gemma.txt",
+                include_str!("../../../assets/templates/llm/gemma.txt"),
+            ),
+            (
+                "llama3-instruct.txt",
+                include_str!("../../../assets/templates/llm/llama3-instruct.txt"),
+            ),
+            (
+                "mistral-instruct.txt",
+                include_str!("../../../assets/templates/llm/mistral-instruct.txt"),
+            ),
+            (
+                "phi3.5-instruct.txt",
+                include_str!("../../../assets/templates/llm/phi3.5-instruct.txt"),
+            ),
+        ];
+
+        if let Err(err) = tera.add_raw_templates(templates) {
+            eprintln!("Parsing error: {err}");
+            ::std::process::exit(1);
         }
+
+        tera
     };
 }
 
+/// Picks the prompt template matching the model family recorded in the gguf
+/// metadata. Unknown (or missing) architectures fall back to the llama3
+/// template.
+pub fn template_for_architecture(architecture: Option<&str>) -> &'static str {
+    match architecture {
+        Some("phi3") => "phi3.5-instruct.txt",
+        Some("qwen2") | Some("qwen2moe") => "chatml.txt",
+        Some("gemma") | Some("gemma2") => "gemma.txt",
+        _ => "llama3-instruct.txt",
+    }
+}
+
+/// Picks the prompt template for a model. The chat template embedded in the
+/// gguf metadata is the most reliable signal (Mistral ggufs report a "llama"
+/// architecture, for example); the architecture is the fallback.
+pub fn detect_template(architecture: Option<&str>, chat_template: Option<&str>) -> &'static str {
+    if let Some(chat_template) = chat_template {
+        if chat_template.contains("<|im_start|>") {
+            return "chatml.txt";
+        } else if chat_template.contains("<start_of_turn>") {
+            return "gemma.txt";
+        } else if chat_template.contains("<|start_header_id|>") {
+            return "llama3-instruct.txt";
+        } else if chat_template.contains("[INST]") {
+            return "mistral-instruct.txt";
+        }
+    }
+
+    template_for_architecture(architecture)
+}
+
+/// Renders a chat session into the prompt format the model was trained on.
+pub fn render_prompt(template: &str, session: &LlmSession) -> Result<String> {
+    Ok(TEMPLATES.render(template, &Context::from_serialize(session)?)?)
+}
+
 #[derive(Clone)]
 pub struct LlmClient {
     llm: LLMModel,
+    template: String,
 }
 
 impl LlmClient {
     pub fn new(gguf_path: PathBuf) -> Result<Self> {
-        Ok(Self {
-            llm: LLMModel::new(gguf_path)?,
-        })
+        Self::with_template(gguf_path, None)
+    }
+
+    /// Loads a model w/ an explicit prompt template. When `template` is
+    /// unset, one is picked based on the architecture recorded in the gguf
+    /// metadata.
+    pub fn with_template(gguf_path: PathBuf, template: Option<String>) -> Result<Self> {
+        let llm = LLMModel::new(gguf_path)?;
+        let template = template.unwrap_or_else(|| {
+            detect_template(llm.architecture.as_deref(), llm.chat_template.as_deref()).to_string()
+        });
+
+        if !TEMPLATES.get_template_names().any(|name| name == template) {
+            return Err(anyhow::format_err!(
+                "Unknown prompt template \"{}\", expected one of: {}",
+                template,
+                TEMPLATES
+                    .get_template_names()
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ));
+        }
+
+        Ok(Self { llm, template })
     }
 
     pub async fn chat(
         &mut self,
         session: &LlmSession,
         stream: Option<tokio::sync::mpsc::Sender<ChatStream>>,
+        cancel: Option<CancelToken>,
     ) -> Result<ChatMessage> {
+        let params = &session.params;
+
         // Encode the prompt.
         let mut all_tokens = vec![];
         let mut content_buffer = String::new();
-        let mut sampler = self.llm.sampler();
+        let mut sampler = self.llm.sampler(params);
 
         // process prompt
         let mut timer = std::time::Instant::now();
@@ -49,61 +167,258 @@ impl LlmClient {
             let _ = stream.send(ChatStream::LoadingPrompt).await;
         }
 
-        let prompt_contents =
-            TEMPLATES.render("llama3-instruct.txt", &Context::from_serialize(session)?)?;
+        let prompt_contents = render_prompt(&self.template, session)?;
         let next_token = sampler.load_prompt(&prompt_contents)?;
-        log::info!("processing prompt in {:.3}s", timer.elapsed().as_secs_f32());
+        // After the prompt pass, everything the sampler has seen is a prompt
+        // token.
+        let prompt_tokens = sampler.num_sampled();
+        let prompt_ms = timer.elapsed().as_millis() as u64;
+        log::info!("processing prompt in {:.3}s", prompt_ms as f64 / 1e3);
 
         if let Some(stream) = &stream {
             let _ = stream.send(ChatStream::ChatStart).await;
         }
 
         all_tokens.push(next_token);
+        let mut stopped = false;
         if let Some(t) = self.llm.stream.next_token(next_token)? {
-            content_buffer.push_str(&t);
-            if let Some(stream) = &stream {
-                let _ = stream.send(ChatStream::Token(t)).await;
-            }
+            stopped = push_token(&mut content_buffer, &t, &params.stop, &stream).await;
         }
 
         timer = std::time::Instant::now();
         let mut sampled = 1;
-        let num_tokens_to_sample = 1024;
 
-        for _ in 0..num_tokens_to_sample {
-            let next_token = sampler.next_token()?;
-            all_tokens.push(next_token);
-            if let Some(t) = self.llm.stream.next_token(next_token)? {
-                content_buffer.push_str(&t);
+        let mut cancelled = false;
+        if !stopped {
+            for _ in 0..params.max_tokens {
+                if is_cancelled(&cancel) {
+                    log::info!("chat cancelled, stopping generation");
+                    cancelled = true;
+                    break;
+                }
+
+                // The receiver went away (e.g. an RPC subscription dropped
+                // mid-generation); no point sampling tokens nobody will read.
                 if let Some(stream) = &stream {
-                    let _ = stream.send(ChatStream::Token(t)).await;
+                    if stream.is_closed() {
+                        log::info!("chat stream closed, stopping generation");
+                        break;
+                    }
                 }
-            }
 
-            sampled += 1;
-            if sampler.is_done() {
-                break;
-            };
-        }
+                let next_token = sampler.next_token()?;
+                all_tokens.push(next_token);
+                if let Some(t) = self.llm.stream.next_token(next_token)? {
+                    if push_token(&mut content_buffer, &t, &params.stop, &stream).await {
+                        stopped = true;
+                        break;
+                    }
+                }
 
-        if let Some(rest) = self.llm.stream.decode_rest().map_err(candle::Error::msg)? {
-            if let Some(stream) = &stream {
-                let _ = stream.send(ChatStream::Token(rest)).await;
+                sampled += 1;
+                if sampler.is_done() {
+                    break;
+                };
             }
         }
 
-        if let Some(stream) = &stream {
-            let _ = stream.send(ChatStream::ChatDone).await;
+        if !stopped && !cancelled {
+            if let Some(rest) = self.llm.stream.decode_rest().map_err(candle::Error::msg)? {
+                if let Some(stream) = &stream {
+                    let _ = stream.send(ChatStream::Token(rest)).await;
+                }
+            }
         }
 
+        let generation_ms = timer.elapsed().as_millis() as u64;
+        let stats = ChatStats {
+            prompt_tokens,
+            generated_tokens: sampled,
+            prompt_ms,
+            generation_ms,
+            tokens_per_second: sampled as f32 / timer.elapsed().as_secs_f32(),
+        };
         log::info!(
             "{sampled:4} tokens generated: {:.2} token/s",
-            sampled as f64 / timer.elapsed().as_secs_f64(),
+            stats.tokens_per_second,
         );
 
+        if let Some(stream) = &stream {
+            if cancelled {
+                let _ = stream.send(ChatStream::Cancelled).await;
+            } else {
+                let _ = stream.send(ChatStream::ChatDone(stats)).await;
+            }
+        }
+
         Ok(ChatMessage {
             role: ChatRole::Assistant,
             content: content_buffer,
         })
     }
 }
+
+/// Byte offset of the earliest stop sequence in `content`, if one appears.
+fn stop_index(content: &str, stop: &[String]) -> Option<usize> {
+    stop.iter()
+        .filter(|pattern| !pattern.is_empty())
+        .filter_map(|pattern| content.find(pattern.as_str()))
+        .min()
+}
+
+/// Appends `token` to `buffer`, streaming out only the portion that precedes
+/// a stop sequence. Stop strings can span token boundaries, so the whole
+/// buffer is checked. Returns true when generation should end.
+async fn push_token(
+    buffer: &mut String,
+    token: &str,
+    stop: &[String],
+    stream: &Option<tokio::sync::mpsc::Sender<ChatStream>>,
+) -> bool {
+    let start = buffer.len();
+    buffer.push_str(token);
+
+    match stop_index(buffer, stop) {
+        Some(idx) => {
+            // Anything before `start` has already been streamed.
+            if idx > start {
+                if let Some(stream) = stream {
+                    let _ = stream
+                        .send(ChatStream::Token(buffer[start..idx].to_string()))
+                        .await;
+                }
+            }
+            buffer.truncate(idx);
+            true
+        }
+        None => {
+            if let Some(stream) = stream {
+                let _ = stream.send(ChatStream::Token(token.to_string())).await;
+            }
+            false
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmBackend for LlmClient {
+    async fn chat(
+        &mut self,
+        session: &LlmSession,
+        stream: Option<tokio::sync::mpsc::Sender<ChatStream>>,
+        cancel: Option<CancelToken>,
+    ) -> Result<ChatMessage> {
+        let result = LlmClient::chat(self, session, stream.clone(), cancel).await;
+        // Surface failures as a stream event, matching the remote backend.
+        if let (Err(error), Some(stream)) = (&result, &stream) {
+            let _ = stream.send(ChatStream::Error(error.to_string())).await;
+        }
+
+        result
+    }
+
+    fn context_length(&self) -> Option<usize> {
+        self.llm.context_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_template, render_prompt, stop_index, template_for_architecture};
+    use shared::llm::{ChatMessage, ChatRole, GenerationParams, LlmSession};
+
+    fn test_session() -> LlmSession {
+        LlmSession {
+            messages: vec![
+                ChatMessage {
+                    role: ChatRole::System,
+                    content: "You are a helpful AI assistant".into(),
+                },
+                ChatMessage {
+                    role: ChatRole::User,
+                    content: "What is the capital of Zimbabwe?".into(),
+                },
+            ],
+            params: GenerationParams::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_prompt_embedded_templates() {
+        // Templates are embedded, so this renders even though there's no
+        // assets/ dir relative to the test working directory.
+        let prompt = render_prompt("llama3-instruct.txt", &test_session())
+            .expect("Unable to render prompt");
+        assert!(prompt.contains("<|start_header_id|>user<|end_header_id|>"));
+        assert!(prompt.contains("What is the capital of Zimbabwe?"));
+
+        let prompt = render_prompt("phi3.5-instruct.txt", &test_session())
+            .expect("Unable to render prompt");
+        assert!(prompt.contains("<|user|>"));
+        assert!(prompt.contains("<|assistant|>"));
+
+        let prompt =
+            render_prompt("chatml.txt", &test_session()).expect("Unable to render prompt");
+        assert!(prompt.contains("<|im_start|>system"));
+        assert!(prompt.contains("<|im_start|>user"));
+        assert!(prompt.contains("<|im_end|>"));
+        assert!(prompt.contains("<|im_start|>assistant"));
+
+        let prompt =
+            render_prompt("mistral-instruct.txt", &test_session()).expect("Unable to render prompt");
+        assert!(prompt.starts_with("<s>"));
+        assert!(prompt.contains("[INST] What is the capital of Zimbabwe? [/INST]"));
+
+        let prompt = render_prompt("gemma.txt", &test_session()).expect("Unable to render prompt");
+        // Gemma has no system role; everything non-assistant is a user turn.
+        assert!(prompt.contains("<start_of_turn>user"));
+        assert!(prompt.contains("<end_of_turn>"));
+        assert!(prompt.contains("<start_of_turn>model"));
+    }
+
+    #[test]
+    fn test_detect_template() {
+        // The embedded chat template wins over the architecture.
+        assert_eq!(
+            detect_template(Some("llama"), Some("{{ '<|im_start|>' + role }}")),
+            "chatml.txt"
+        );
+        assert_eq!(
+            detect_template(Some("llama"), Some("[INST] {{ content }} [/INST]")),
+            "mistral-instruct.txt"
+        );
+        assert_eq!(
+            detect_template(Some("llama"), Some("<start_of_turn>user")),
+            "gemma.txt"
+        );
+        assert_eq!(
+            detect_template(Some("llama"), Some("<|start_header_id|>")),
+            "llama3-instruct.txt"
+        );
+        // Unknown template contents fall back to the architecture.
+        assert_eq!(detect_template(Some("qwen2"), Some("???")), "chatml.txt");
+        assert_eq!(detect_template(Some("gemma2"), None), "gemma.txt");
+        assert_eq!(detect_template(None, None), "llama3-instruct.txt");
+    }
+
+    #[test]
+    fn test_stop_index() {
+        let stop = vec!["</answer>".to_string(), "\n\n".to_string()];
+        assert_eq!(stop_index("no stops here", &stop), None);
+        assert_eq!(stop_index("done</answer>ignored", &stop), Some(4));
+        // Earliest stop sequence wins.
+        assert_eq!(stop_index("one\n\ntwo</answer>", &stop), Some(3));
+        // Empty patterns would match everywhere; they're skipped.
+        assert_eq!(stop_index("anything", &[String::new()]), None);
+    }
+
+    #[test]
+    fn test_template_for_architecture() {
+        assert_eq!(
+            template_for_architecture(Some("phi3")),
+            "phi3.5-instruct.txt"
+        );
+        assert_eq!(template_for_architecture(Some("llama")), "llama3-instruct.txt");
+        assert_eq!(template_for_architecture(None), "llama3-instruct.txt");
+    }
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use candle::{backend::BackendDevice, quantized::gguf_file, Device, MetalDevice};
 use candle_transformers::models::quantized_llama::ModelWeights;
+use shared::llm::GenerationParams;
 use tokenizers::Tokenizer;
 
 use crate::{sampler::Sampler, token_output_stream::TokenOutputStream};
@@ -25,6 +26,14 @@ pub struct LLMModel {
     pub weights: ModelWeights,
     pub stream: TokenOutputStream,
     pub eos_token: u32,
+    /// Model family recorded in the gguf metadata (e.g. "llama", "phi3"),
+    /// used to pick a prompt template when none is configured.
+    pub architecture: Option<String>,
+    /// The chat template (jinja) embedded in the gguf metadata, used to
+    /// recognize model families the architecture field can't distinguish.
+    pub chat_template: Option<String>,
+    /// Context window recorded in the gguf metadata, in tokens.
+    pub context_length: Option<usize>,
 }
 
 impl LLMModel {
@@ -51,9 +60,26 @@ impl LLMModel {
             candle::Device::Cpu
         };
 
-        let mut file = std::fs::File::open(gguf_path)?;
+        let mut file = std::fs::File::open(&gguf_path)?;
         let model = gguf_file::Content::read(&mut file)?;
-        dbg!(model.metadata.keys());
+
+        let architecture = model
+            .metadata
+            .get("general.architecture")
+            .and_then(|value| value.to_string().ok())
+            .cloned();
+        let chat_template = model
+            .metadata
+            .get("tokenizer.chat_template")
+            .and_then(|value| value.to_string().ok())
+            .cloned();
+        // The context length key is prefixed w/ the architecture, e.g.
+        // "llama.context_length".
+        let context_length = architecture
+            .as_deref()
+            .and_then(|arch| model.metadata.get(&format!("{arch}.context_length")))
+            .and_then(|value| value.to_u32().ok())
+            .map(|value| value as usize);
 
         let mut total_size_in_bytes = 0;
         for (_, tensor) in model.tensor_infos.iter() {
@@ -72,21 +98,44 @@ impl LLMModel {
         // todo: load tokenizer from gguf file itself.
         log::info!("loading tokenizer & weights");
         timer = std::time::Instant::now();
-        let tokenizer = Tokenizer::from_file("assets/models/llm/llama3/tokenizer.json")
-            .map_err(anyhow::Error::msg)?;
+        // The tokenizer is expected to sit next to the gguf file, so custom
+        // model paths don't depend on the working directory.
+        let tokenizer_path = gguf_path
+            .parent()
+            .map(|dir| dir.join("tokenizer.json"))
+            .unwrap_or_else(|| PathBuf::from("tokenizer.json"));
+        let tokenizer = Tokenizer::from_file(tokenizer_path).map_err(anyhow::Error::msg)?;
 
         let weights = ModelWeights::from_gguf(model, &mut file, &device)?;
         let tos = TokenOutputStream::new(tokenizer.clone());
         log::info!("total load took: {:.3}s", timer.elapsed().as_secs_f32());
 
-        let eos_token = "<|eot_id|>";
-        let eos_token = *tos.tokenizer().get_vocab(true).get(eos_token).unwrap();
+        // Llama-style models end their turns w/ <|eot_id|>, phi w/ <|end|>,
+        // ChatML models w/ <|im_end|> & gemma w/ <end_of_turn>. Mistral ggufs
+        // report a "llama" architecture but only ship </s>, so fall through
+        // the candidates until one is in the vocab.
+        let eos_candidates: &[&str] = match architecture.as_deref() {
+            Some("phi3") => &["<|end|>"],
+            Some("qwen2") | Some("qwen2moe") => &["<|im_end|>"],
+            Some("gemma") | Some("gemma2") => &["<end_of_turn>"],
+            _ => &["<|eot_id|>", "</s>"],
+        };
+        let vocab = tos.tokenizer().get_vocab(true);
+        let eos_token = *eos_candidates
+            .iter()
+            .find_map(|token| vocab.get(*token))
+            .ok_or_else(|| {
+                anyhow::format_err!("Tokenizer is missing the {:?} token", eos_candidates)
+            })?;
 
         Ok(Self {
             device,
             weights,
             eos_token,
             stream: tos,
+            architecture,
+            chat_template,
+            context_length,
         })
     }
 
@@ -103,7 +152,7 @@ impl LLMModel {
         Ok(tokens.get_ids().to_vec())
     }
 
-    pub fn sampler(&self) -> Sampler {
-        Sampler::new(self)
+    pub fn sampler(&self, params: &GenerationParams) -> Sampler {
+        Sampler::new(self, params)
     }
 }
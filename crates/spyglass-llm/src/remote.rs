@@ -0,0 +1,187 @@
+use anyhow::Result;
+use serde::Deserialize;
+use shared::llm::{ChatMessage, ChatRole, ChatStats, ChatStream, LlmSession};
+use tokio::sync::mpsc;
+
+use crate::{is_cancelled, CancelToken, LlmBackend};
+
+/// Client for an OpenAI-compatible chat completions server (Ollama,
+/// llama.cpp, etc.), used instead of local gguf inference when a remote URL
+/// is configured.
+pub struct RemoteClient {
+    base_url: String,
+    api_key: Option<String>,
+    model: Option<String>,
+    client: reqwest::Client,
+}
+
+/// One server-sent chunk of a streamed chat completion.
+#[derive(Deserialize)]
+struct CompletionChunk {
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: ChunkDelta,
+}
+
+#[derive(Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl RemoteClient {
+    pub fn new(base_url: &str, api_key: Option<String>, model: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn chat_inner(
+        &self,
+        session: &LlmSession,
+        stream: &Option<mpsc::Sender<ChatStream>>,
+        cancel: &Option<CancelToken>,
+    ) -> Result<ChatMessage> {
+        let params = &session.params;
+        let mut body = serde_json::json!({
+            // Servers w/ a single loaded model ignore this.
+            "model": self.model.as_deref().unwrap_or("default"),
+            "messages": session.messages,
+            "stream": true,
+            "max_tokens": params.max_tokens,
+        });
+        // Only forward knobs that were explicitly set so the server's own
+        // defaults apply otherwise.
+        if let Some(temperature) = params.temperature {
+            body["temperature"] = serde_json::json!(temperature);
+        }
+        if let Some(top_p) = params.top_p {
+            body["top_p"] = serde_json::json!(top_p);
+        }
+        if !params.stop.is_empty() {
+            body["stop"] = serde_json::json!(params.stop);
+        }
+        if let Some(seed) = params.seed {
+            body["seed"] = serde_json::json!(seed);
+        }
+
+        if let Some(stream) = stream {
+            let _ = stream.send(ChatStream::LoadingPrompt).await;
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let mut response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow::format_err!(
+                "Remote LLM request failed w/ {status}: {body}"
+            ));
+        }
+
+        // Responses stream as server-sent events, one "data: {json}" line per
+        // token delta, terminated w/ "data: [DONE]".
+        let mut buffer = String::new();
+        let mut content_buffer = String::new();
+        let mut started = false;
+        let mut cancelled = false;
+        let mut generated_tokens = 0;
+        let timer = std::time::Instant::now();
+        'stream: while let Some(chunk) = response.chunk().await? {
+            if is_cancelled(cancel) {
+                log::info!("chat cancelled, dropping remote stream");
+                cancelled = true;
+                break 'stream;
+            }
+
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(newline) = buffer.find('\n') {
+                let line = buffer[..newline].trim().to_string();
+                buffer.drain(..=newline);
+
+                let data = match line.strip_prefix("data:") {
+                    Some(data) => data.trim(),
+                    None => continue,
+                };
+
+                if data == "[DONE]" {
+                    break 'stream;
+                }
+
+                let parsed = serde_json::from_str::<CompletionChunk>(data)
+                    .map_err(|err| anyhow::format_err!("Invalid completion chunk: {err}"))?;
+                let token = parsed
+                    .choices
+                    .first()
+                    .and_then(|choice| choice.delta.content.clone());
+                if let Some(token) = token {
+                    if !started {
+                        started = true;
+                        if let Some(stream) = stream {
+                            let _ = stream.send(ChatStream::ChatStart).await;
+                        }
+                    }
+
+                    generated_tokens += 1;
+                    content_buffer.push_str(&token);
+                    if let Some(stream) = stream {
+                        let _ = stream.send(ChatStream::Token(token)).await;
+                    }
+                }
+            }
+        }
+
+        if let Some(stream) = stream {
+            if cancelled {
+                let _ = stream.send(ChatStream::Cancelled).await;
+            } else {
+                // We can't see the server's tokenizer, so each streamed delta
+                // counts as one token & prompt_tokens stays at zero.
+                let generation_ms = timer.elapsed().as_millis() as u64;
+                let stats = ChatStats {
+                    generated_tokens,
+                    generation_ms,
+                    tokens_per_second: generated_tokens as f32 / timer.elapsed().as_secs_f32(),
+                    ..Default::default()
+                };
+                let _ = stream.send(ChatStream::ChatDone(stats)).await;
+            }
+        }
+
+        Ok(ChatMessage {
+            role: ChatRole::Assistant,
+            content: content_buffer,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmBackend for RemoteClient {
+    async fn chat(
+        &mut self,
+        session: &LlmSession,
+        stream: Option<mpsc::Sender<ChatStream>>,
+        cancel: Option<CancelToken>,
+    ) -> Result<ChatMessage> {
+        let result = self.chat_inner(session, &stream, &cancel).await;
+        // Surface failures as a stream event, matching the local backend.
+        if let (Err(error), Some(stream)) = (&result, &stream) {
+            let _ = stream.send(ChatStream::Error(error.to_string())).await;
+        }
+
+        result
+    }
+}
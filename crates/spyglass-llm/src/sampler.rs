@@ -1,6 +1,7 @@
 use anyhow::Result;
 use candle::Tensor;
 use candle_transformers::generation::{LogitsProcessor, Sampling};
+use shared::llm::GenerationParams;
 
 use crate::model::LLMModel;
 
@@ -12,10 +13,20 @@ pub struct Sampler {
 }
 
 impl Sampler {
-    pub fn new(model: &LLMModel) -> Self {
+    pub fn new(model: &LLMModel, params: &GenerationParams) -> Self {
+        // An unset (or zero) temperature keeps the historical greedy
+        // decoding behavior.
+        let sampling = match params.temperature {
+            Some(temperature) if temperature > 0.0 => match params.top_p {
+                Some(p) => Sampling::TopP { p, temperature },
+                None => Sampling::All { temperature },
+            },
+            _ => Sampling::ArgMax,
+        };
+
         Self {
             model: model.clone(),
-            processor: LogitsProcessor::from_sampling(0, Sampling::ArgMax),
+            processor: LogitsProcessor::from_sampling(params.seed.unwrap_or(0), sampling),
             num_sampled: 0,
             last_token: None,
         }
@@ -52,6 +63,11 @@ impl Sampler {
         Ok(next_token)
     }
 
+    /// Number of tokens processed so far (prompt tokens + sampled tokens).
+    pub fn num_sampled(&self) -> usize {
+        self.num_sampled
+    }
+
     pub fn is_done(&self) -> bool {
         self.last_token
             .map(|x| x == self.model.eos_token)
@@ -6,10 +6,38 @@ use crate::{batch, load_tokenizer, Backend, CandleBackend, Embedding, ModelType,
 
 const MAX_TOKENS: usize = 2048;
 
+/// How long documents are split into segments before embedding.
+#[derive(Clone, Debug)]
+pub struct SegmentationConfig {
+    /// Target number of tokens per segment, clamped to the model's max
+    /// sequence length.
+    pub target_tokens: usize,
+    /// Number of tokens neighboring segments overlap, preserving context
+    /// across segment boundaries.
+    pub overlap_tokens: usize,
+    /// Prefer cutting segments at markdown headings & paragraph breaks
+    /// instead of mid-sentence.
+    pub split_on_boundaries: bool,
+}
+
+impl Default for SegmentationConfig {
+    fn default() -> Self {
+        Self {
+            target_tokens: MAX_TOKENS,
+            overlap_tokens: 0,
+            split_on_boundaries: false,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EmbeddingApi {
     backend: Arc<CandleBackend>,
     tokenizer: Tokenizer,
+    segmentation: SegmentationConfig,
+    /// Truncate pooled embeddings to this many dimensions (matryoshka
+    /// truncation). `None` keeps the model's full output.
+    dimensions: Option<usize>,
 }
 
 pub enum EmbeddingContentType {
@@ -22,77 +50,111 @@ pub struct SegmentEmbedding {
     pub embedding: Vec<f32>,
     pub start: usize,
     pub end: usize,
+    /// Whether the segment was truncated to the model's max sequence length
+    /// when tokenized. Truncated docs can be re-chunked w/ a smaller segment
+    /// size instead of silently losing the tail.
+    pub truncated: bool,
 }
 
 impl EmbeddingApi {
-    pub fn new(model_root: PathBuf) -> anyhow::Result<Self> {
+    pub fn new(
+        model_root: PathBuf,
+        segmentation: SegmentationConfig,
+        dimensions: Option<usize>,
+        device: Option<usize>,
+        force_cpu: bool,
+    ) -> anyhow::Result<Self> {
         let tokenizer = load_tokenizer(&model_root)?;
+        // No explicit dtype; the backend picks bf16 on devices that support
+        // it & falls back to f32 on the CPU.
         let backend = CandleBackend::new(
             model_root,
-            "float32".to_string(),
+            None,
             ModelType::Embedding(Pool::Mean),
+            device,
+            force_cpu,
         )?;
 
         Ok(EmbeddingApi {
             backend: Arc::new(backend),
             tokenizer,
+            segmentation,
+            dimensions,
         })
     }
 
-    pub fn embed(
-        &self,
-        content: &str,
-        content_type: EmbeddingContentType,
-    ) -> anyhow::Result<Vec<SegmentEmbedding>> {
-        let doc_content = match content_type {
+    /// Name of the device the model is running on, see
+    /// `CandleBackend::device_name`.
+    pub fn device_name(&self) -> String {
+        self.backend.device_name()
+    }
+
+    /// Name of the dtype the model weights were loaded as, see
+    /// `CandleBackend::dtype_name`.
+    pub fn dtype_name(&self) -> String {
+        self.backend.dtype_name()
+    }
+
+    /// Applies the content-type prefix the model was trained with.
+    fn prefixed(content: &str, content_type: &EmbeddingContentType) -> String {
+        match content_type {
             EmbeddingContentType::Document => {
                 format!("search_document: {}", content.trim())
             }
             EmbeddingContentType::Query => {
                 format!("search_query: {}", content.trim())
             }
-        };
+        }
+    }
 
+    /// Tokenizes `content`, splitting it into multiple chunks when it's too
+    /// long to embed in a single pass. Returns each encoding along w/ the
+    /// char range of `content` it covers.
+    fn tokenize_chunks(
+        &self,
+        content: &str,
+        content_type: &EmbeddingContentType,
+    ) -> anyhow::Result<Vec<(Encoding, usize, usize)>> {
         let tokens = self
             .tokenizer
-            .encode(doc_content, false)
+            .encode(Self::prefixed(content, content_type), false)
             .map_err(|err| anyhow::format_err!("Error tokenizing {:?}", err))?;
-        let token_length = tokens.len();
+        // The tokenizer truncates to the model's max sequence length, count
+        // the overflow so chunking still sees the document's true length.
+        let token_length = tokens.len()
+            + tokens
+                .get_overflowing()
+                .iter()
+                .map(|overflow| overflow.len())
+                .sum::<usize>();
+
+        let target_tokens = self.segmentation.target_tokens.clamp(1, MAX_TOKENS);
+
         let mut content_chunks = Vec::new();
-        if token_length > MAX_TOKENS {
-            let segment_count = token_length.div_ceil(MAX_TOKENS);
-            let char_per_segment = content.len().div_ceil(segment_count);
-
-            let chunks: Vec<(String, usize, usize)> = content
-                .trim()
-                .chars()
-                .collect::<Vec<char>>()
-                .chunks(char_per_segment)
-                .enumerate()
-                .map(|(i, chunk)| {
-                    let start_index = i * char_per_segment;
-                    let end_index = start_index + chunk.len() - 1;
-
-                    (chunk.iter().collect::<String>(), start_index, end_index)
-                })
-                .collect();
+        if token_length > target_tokens {
+            let trimmed = content.trim();
+            let chars = trimmed.chars().collect::<Vec<char>>();
+
+            // Approximate chars-per-token ratio for this document, used to
+            // map the token targets onto char ranges.
+            let chars_per_token = (chars.len() as f32 / token_length as f32).max(1.0);
+            let target_chars = ((target_tokens as f32 * chars_per_token) as usize).max(1);
+            let overlap_chars = (self.segmentation.overlap_tokens as f32 * chars_per_token) as usize;
 
             log::debug!(
-                "Splitting text into chunks of {} chars long",
-                char_per_segment
+                "Splitting text into chunks of ~{} chars long",
+                target_chars
             );
-            for (chunk, start_index, end_index) in chunks {
-                let doc_content = match content_type {
-                    EmbeddingContentType::Document => {
-                        format!("search_document: {}", chunk)
-                    }
-                    EmbeddingContentType::Query => {
-                        format!("search_query: {}", chunk)
-                    }
-                };
+            for (start_index, end_index) in segment_ranges(
+                trimmed,
+                target_chars,
+                overlap_chars,
+                self.segmentation.split_on_boundaries,
+            ) {
+                let chunk = chars[start_index..=end_index].iter().collect::<String>();
                 let tokens = self
                     .tokenizer
-                    .encode(doc_content, false)
+                    .encode(Self::prefixed(&chunk, content_type), false)
                     .map_err(|err| anyhow::format_err!("Error tokenizing {:?}", err))?;
                 log::trace!("Chunk was {} tokens long", tokens.len());
                 content_chunks.push((tokens, start_index, end_index));
@@ -101,19 +163,100 @@ impl EmbeddingApi {
             content_chunks.push((tokens, 0, content.len() - 1));
         }
 
+        Ok(content_chunks)
+    }
+
+    pub fn embed(
+        &self,
+        content: &str,
+        content_type: EmbeddingContentType,
+    ) -> anyhow::Result<Vec<SegmentEmbedding>> {
         let mut embeddings = Vec::new();
-        for (chunk, start, end) in content_chunks {
+        for (chunk, start, end) in self.tokenize_chunks(content, &content_type)? {
+            let truncated = !chunk.get_overflowing().is_empty();
+            if truncated {
+                log::warn!(
+                    "Segment {}..{} truncated to the model's max sequence length",
+                    start,
+                    end
+                );
+            }
+
             let embedding = self.embed_tokens(chunk.to_owned())?;
             embeddings.push(SegmentEmbedding {
                 embedding,
                 start,
                 end,
+                truncated,
             });
         }
 
         Ok(embeddings)
     }
 
+    /// Embeds several inputs in one go, packing chunk encodings into batches
+    /// up to the backend's `max_batch_size` so the model overhead is paid
+    /// once per batch rather than once per document. Results come back in
+    /// input order, one `Vec<SegmentEmbedding>` per input.
+    pub fn embed_batch(
+        &self,
+        texts: &[String],
+        content_type: EmbeddingContentType,
+    ) -> anyhow::Result<Vec<Vec<SegmentEmbedding>>> {
+        // Tokenize & chunk every input up front, remembering which input
+        // each chunk belongs to. Long inputs are already split to the max
+        // sequence length by `tokenize_chunks`.
+        let mut chunks: Vec<(usize, Encoding, usize, usize)> = Vec::new();
+        for (input_idx, text) in texts.iter().enumerate() {
+            for (encoding, start, end) in self.tokenize_chunks(text, &content_type)? {
+                chunks.push((input_idx, encoding, start, end));
+            }
+        }
+
+        let max_batch = self
+            .backend
+            .max_batch_size()
+            .unwrap_or_else(|| chunks.len().max(1));
+
+        let mut results: Vec<Vec<SegmentEmbedding>> = vec![Vec::new(); texts.len()];
+        for batch_chunks in chunks.chunks(max_batch) {
+            let encodings = batch_chunks
+                .iter()
+                .map(|(_, encoding, _, _)| encoding.clone())
+                .collect::<Vec<Encoding>>();
+            let pooled_indices = (0..encodings.len() as u32).collect::<Vec<u32>>();
+            let num_tokens: usize = encodings.iter().map(|encoding| encoding.len()).sum();
+            let input_batch = batch(encodings, pooled_indices, vec![]);
+
+            let start = Instant::now();
+            let embedded = self
+                .backend
+                .embed(input_batch)
+                .map_err(|err| anyhow::format_err!("Embedding failed {:?}", err))?;
+            log::debug!(
+                "Embedding batch of {} chunks ({} tokens) took {}",
+                batch_chunks.len(),
+                num_tokens,
+                start.elapsed().as_millis()
+            );
+
+            for (batch_idx, (input_idx, encoding, start, end)) in batch_chunks.iter().enumerate() {
+                if let Some(Embedding::Pooled(embedding)) = embedded.get(&batch_idx) {
+                    results[*input_idx].push(SegmentEmbedding {
+                        embedding: truncate_embedding(embedding.to_owned(), self.dimensions),
+                        start: *start,
+                        end: *end,
+                        truncated: !encoding.get_overflowing().is_empty(),
+                    });
+                } else {
+                    return Err(anyhow::format_err!("Unable to process embedding"));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
     pub fn embed_tokens(&self, tokens: Encoding) -> anyhow::Result<Vec<f32>> {
         let token_length = tokens.len();
         let input_batch = batch(vec![tokens], [0].to_vec(), vec![]);
@@ -128,7 +271,7 @@ impl EmbeddingApi {
                 );
 
                 if let Some(Embedding::Pooled(embedding)) = embed.get(&0) {
-                    Ok(embedding.to_owned())
+                    Ok(truncate_embedding(embedding.to_owned(), self.dimensions))
                 } else {
                     Err(anyhow::format_err!("Unable to process embedding"))
                 }
@@ -146,3 +289,163 @@ impl EmbeddingApi {
         }
     }
 }
+
+/// Truncates a pooled embedding to `dims` dimensions & re-normalizes it to
+/// unit length (matryoshka truncation). No-op when `dims` is unset, zero, or
+/// not shorter than the embedding.
+fn truncate_embedding(mut embedding: Vec<f32>, dims: Option<usize>) -> Vec<f32> {
+    if let Some(dims) = dims {
+        if dims > 0 && dims < embedding.len() {
+            embedding.truncate(dims);
+            let norm = embedding
+                .iter()
+                .map(|value| value * value)
+                .sum::<f32>()
+                .sqrt();
+            if norm > 0.0 {
+                for value in embedding.iter_mut() {
+                    *value /= norm;
+                }
+            }
+        }
+    }
+
+    embedding
+}
+
+/// Splits `content` into segments of roughly `target_chars` chars, returning
+/// inclusive (start, end) char ranges. Each segment after the first starts
+/// `overlap_chars` before the previous one ended. When `split_on_boundaries`
+/// is set, segments prefer to end right before a markdown heading or
+/// paragraph break instead of mid-sentence.
+fn segment_ranges(
+    content: &str,
+    target_chars: usize,
+    overlap_chars: usize,
+    split_on_boundaries: bool,
+) -> Vec<(usize, usize)> {
+    let total = content.chars().count();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let target_chars = target_chars.max(1);
+    let boundaries = if split_on_boundaries {
+        block_boundaries(content)
+    } else {
+        Vec::new()
+    };
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < total {
+        let mut end = (start + target_chars).min(total);
+        if end < total {
+            // Pull the cut back to the closest block boundary, but only if
+            // it doesn't leave the segment less than half full.
+            if let Some(boundary) = boundaries
+                .iter()
+                .filter(|&&idx| idx > start + target_chars / 2 && idx < end)
+                .next_back()
+            {
+                end = *boundary;
+            }
+        }
+
+        let range_start = if ranges.is_empty() {
+            start
+        } else {
+            start.saturating_sub(overlap_chars)
+        };
+        ranges.push((range_start, end - 1));
+        start = end;
+    }
+
+    ranges
+}
+
+/// Char indices where a new block of text begins: markdown headings & lines
+/// following a blank line.
+fn block_boundaries(content: &str) -> Vec<usize> {
+    let mut boundaries = Vec::new();
+    let mut idx = 0;
+    let mut prev_blank = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if idx > 0 && !trimmed.is_empty() && (trimmed.starts_with('#') || prev_blank) {
+            boundaries.push(idx);
+        }
+        prev_blank = trimmed.is_empty();
+        idx += line.chars().count();
+    }
+
+    boundaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_boundaries, segment_ranges, truncate_embedding};
+
+    #[test]
+    fn test_segment_ranges_overlap() {
+        let content = "a".repeat(100);
+        let ranges = segment_ranges(&content, 40, 10, false);
+        // Each segment after the first should back up 10 chars into the
+        // previous one, while the cut points advance by the full target.
+        assert_eq!(ranges, vec![(0, 39), (30, 79), (70, 99)]);
+    }
+
+    #[test]
+    fn test_segment_ranges_no_overlap() {
+        let content = "a".repeat(100);
+        let ranges = segment_ranges(&content, 50, 0, false);
+        assert_eq!(ranges, vec![(0, 49), (50, 99)]);
+    }
+
+    #[test]
+    fn test_segment_ranges_markdown_boundaries() {
+        let content =
+            "# Title\nIntro paragraph text.\n\n## Section\nMore text under the section heading.";
+        assert_eq!(block_boundaries(content), vec![31]);
+
+        // W/ boundary splitting the cut lands right before "## Section".
+        let ranges = segment_ranges(content, 50, 0, true);
+        assert_eq!(ranges, vec![(0, 30), (31, 77)]);
+
+        // Without it we cut mid-sentence at the char target.
+        let ranges = segment_ranges(content, 50, 0, false);
+        assert_eq!(ranges, vec![(0, 49), (50, 77)]);
+    }
+
+    #[test]
+    fn test_segment_ranges_long_input() {
+        // Regression: a very long document splits into contiguous segments
+        // covering the full input instead of erroring out mid-embed.
+        let content = "a".repeat(100_000);
+        let ranges = segment_ranges(&content, 2048, 0, false);
+        assert_eq!(ranges.first().map(|range| range.0), Some(0));
+        assert_eq!(ranges.last().map(|range| range.1), Some(99_999));
+        for window in ranges.windows(2) {
+            assert_eq!(window[0].1 + 1, window[1].0);
+        }
+    }
+
+    #[test]
+    fn test_truncate_embedding() {
+        let embedding = vec![3.0, 4.0, 100.0, 100.0];
+
+        // Truncated to 2 dims & re-normalized to unit length.
+        let truncated = truncate_embedding(embedding.clone(), Some(2));
+        assert_eq!(truncated, vec![0.6, 0.8]);
+
+        // Unset or not shorter than the embedding leaves it untouched.
+        assert_eq!(truncate_embedding(embedding.clone(), None), embedding);
+        assert_eq!(truncate_embedding(embedding.clone(), Some(8)), embedding);
+    }
+
+    #[test]
+    fn test_block_boundaries_paragraphs() {
+        let content = "First paragraph.\n\nSecond paragraph.\nStill second.\n\nThird.";
+        assert_eq!(block_boundaries(content), vec![18, 51]);
+    }
+}
@@ -3,6 +3,7 @@ mod alibi;
 #[cfg(feature = "cuda")]
 mod compute_cap;
 pub mod embedding_api;
+pub mod rerank_api;
 // #[cfg(feature = "cuda")]
 // mod flash_attn;
 mod layers;
@@ -13,8 +14,8 @@ use crate::compute_cap::{
     compatible_compute_cap, get_compile_compute_cap, get_runtime_compute_cap,
 };
 use crate::models::{
-    BertConfig, BertModel, DistilBertConfig, DistilBertModel, GTEConfig, JinaBertModel,
-    JinaCodeBertModel, MistralConfig, Model, NomicBertModel, NomicConfig, Qwen2Config,
+    BertConfig, BertModel, DistilBertConfig, DistilBertModel, GTEConfig, GTEModel, JinaBertModel,
+    JinaCodeBertModel, MistralConfig, Model, NomicBertModel, NomicConfig, Qwen2Config, Qwen2Model,
 };
 // #[cfg(feature = "cuda")]
 // use crate::models::{
@@ -22,7 +23,7 @@ use crate::models::{
 //     FlashJinaCodeBertModel, FlashMistralModel, FlashNomicBertModel, FlashQwen2Model,
 // };
 use anyhow::Context;
-use candle::{DType, Device};
+use candle::{DType, Device, DeviceLocation};
 use candle_nn::VarBuilder;
 // #[cfg(feature = "clap")]
 // use clap::ValueEnum;
@@ -36,7 +37,7 @@ use std::path::PathBuf;
 use thiserror::Error;
 use tokenizers::pre_tokenizers::metaspace::PrependScheme;
 use tokenizers::pre_tokenizers::sequence::Sequence;
-use tokenizers::{Encoding, PreTokenizerWrapper, Tokenizer};
+use tokenizers::{Encoding, PreTokenizerWrapper, Tokenizer, TruncationParams};
 
 pub mod whisper;
 
@@ -159,16 +160,44 @@ enum Config {
     Qwen2(Qwen2Config),
 }
 
+/// Maps a user-provided dtype string to a candle `DType`. When no dtype is
+/// given, defaults to bf16 on devices w/ native support & f32 on the CPU.
+fn parse_dtype(dtype: Option<&str>, device: &Device) -> Result<DType, BackendError> {
+    match dtype {
+        Some("float32") => Ok(DType::F32),
+        Some("float16") => Ok(DType::F16),
+        Some("bfloat16") => Ok(DType::BF16),
+        Some(other) => Err(BackendError::Start(format!(
+            "DType {other} is not supported"
+        ))),
+        None => match device {
+            Device::Cpu => Ok(DType::F32),
+            #[cfg(feature = "cuda")]
+            Device::Cuda(_) => {
+                // bf16 needs an Ampere (SM80) or newer card.
+                match get_runtime_compute_cap() {
+                    Ok(cap) if cap >= 80 => Ok(DType::BF16),
+                    _ => Ok(DType::F32),
+                }
+            }
+            _ => Ok(DType::BF16),
+        },
+    }
+}
+
 pub struct CandleBackend {
     device: Device,
+    dtype: DType,
     model: Box<dyn Model + Send + Sync>,
 }
 
 impl CandleBackend {
     pub fn new(
         model_path: PathBuf,
-        dtype: String,
+        dtype: Option<String>,
         model_type: ModelType,
+        device: Option<usize>,
+        force_cpu: bool,
     ) -> Result<Self, BackendError> {
         // Default files
         let default_safetensors = model_path.join("model.safetensors");
@@ -226,11 +255,29 @@ impl CandleBackend {
             .context("Model is not supported")
             .map_err(|err| BackendError::Start(format!("{err:?}")))?;
 
-        // Get candle device
-        let device = if candle::utils::cuda_is_available() {
+        // Get candle device. When the user picked a specific ordinal we error
+        // out if it can't be used rather than silently falling back to the
+        // CPU.
+        let requested = device;
+        let device = if force_cpu {
+            Ok(Device::Cpu)
+        } else if candle::utils::cuda_is_available() {
             #[cfg(feature = "cuda")]
             match compatible_compute_cap() {
-                Ok(true) => Device::new_cuda(0),
+                Ok(true) => {
+                    let ordinal = requested.unwrap_or(0);
+                    Device::new_cuda(ordinal).map_err(|err| {
+                        BackendError::Start(format!(
+                            "Requested CUDA device {ordinal} is not available: {err}"
+                        ))
+                    })
+                }
+                Ok(false) if requested.is_some() => Err(BackendError::Start(format!(
+                    "Requested CUDA device {} but runtime compute cap {} is not compatible with compile time compute cap {}",
+                    requested.unwrap(),
+                    get_runtime_compute_cap().unwrap(),
+                    get_compile_compute_cap().unwrap()
+                ))),
                 Ok(false) => {
                     log::error!(
                         "Runtime compute cap {} is not compatible with compile time compute cap {}",
@@ -239,6 +286,10 @@ impl CandleBackend {
                     );
                     Ok(Device::Cpu)
                 }
+                Err(err) if requested.is_some() => Err(BackendError::Start(format!(
+                    "Requested CUDA device {} but no compatible CUDA device was found on host: {err:?}",
+                    requested.unwrap()
+                ))),
                 Err(err) => {
                     tracing::warn!("Could not find a compatible CUDA device on host: {err:?}");
                     tracing::warn!("Using CPU instead");
@@ -248,22 +299,23 @@ impl CandleBackend {
             #[cfg(not(feature = "cuda"))]
             Ok(Device::Cpu)
         } else if candle::utils::metal_is_available() {
-            Device::new_metal(0)
+            let ordinal = requested.unwrap_or(0);
+            Device::new_metal(ordinal).map_err(|err| {
+                BackendError::Start(format!(
+                    "Requested Metal device {ordinal} is not available: {err}"
+                ))
+            })
+        } else if let Some(ordinal) = requested {
+            Err(BackendError::Start(format!(
+                "Requested device {ordinal} but no CUDA or Metal device was found on this host"
+            )))
         } else {
             Ok(Device::Cpu)
-        }
-        .map_err(|err| BackendError::Start(err.to_string()))?;
+        }?;
 
         // Get candle dtype
-        let dtype = if &dtype == "float32" {
-            Ok(DType::F32)
-        } else if &dtype == "float16" {
-            Ok(DType::F16)
-        } else {
-            Err(BackendError::Start(format!(
-                "DType {dtype} is not supported"
-            )))
-        }?;
+        let dtype = parse_dtype(dtype.as_deref(), &device)?;
+        tracing::info!("Loading model weights as {dtype:?}");
 
         let vb = if model_files.len() == 1 && model_files[0].extension().unwrap() == "bin" {
             VarBuilder::from_pth(&model_files[0], dtype, &device)
@@ -312,21 +364,37 @@ impl CandleBackend {
                 "Mistral is only supported on Cuda devices in fp16 with flash attention enabled"
                     .to_string(),
             )),
-            (Config::Gte(_), _) => Err(BackendError::Start(
-                "GTE is only supported on Cuda devices in fp16 with flash attention enabled"
-                    .to_string(),
-            )),
-            (Config::Qwen2(_), _) => Err(BackendError::Start(
-                "Qwen2 is only supported on Cuda devices in fp16 with flash attention enabled"
-                    .to_string(),
-            )),
+            (Config::Gte(config), _) => {
+                tracing::info!("Starting GTE model on {:?}", device);
+                Ok(Box::new(GTEModel::load(vb, &config, model_type).s()?))
+            }
+            (Config::Qwen2(config), _) => {
+                tracing::info!("Starting Qwen2 model on {:?}", device);
+                Ok(Box::new(Qwen2Model::load(vb, &config, model_type).s()?))
+            }
         };
 
         Ok(Self {
             device,
+            dtype,
             model: model?,
         })
     }
+
+    /// Human readable name of the device the model ended up on, e.g. "cpu",
+    /// "cuda:0", or "metal:0".
+    pub fn device_name(&self) -> String {
+        match self.device.location() {
+            DeviceLocation::Cpu => "cpu".to_string(),
+            DeviceLocation::Cuda { gpu_id } => format!("cuda:{gpu_id}"),
+            DeviceLocation::Metal { gpu_id } => format!("metal:{gpu_id}"),
+        }
+    }
+
+    /// Name of the dtype the model weights were loaded as, e.g. "f32".
+    pub fn dtype_name(&self) -> String {
+        format!("{:?}", self.dtype).to_lowercase()
+    }
 }
 
 impl Backend for CandleBackend {
@@ -464,9 +532,34 @@ pub fn load_tokenizer(model_root: &Path) -> anyhow::Result<Tokenizer> {
     }
 
     tokenizer.with_padding(None);
+    // Truncate over-length inputs to the model's max sequence length instead
+    // of letting the forward pass error out on them. Truncated tokens are
+    // kept as overflow encodings so callers can tell truncation happened.
+    if let Some(max_length) = max_sequence_length(model_root) {
+        log::debug!("Truncating tokenizer inputs to {} tokens", max_length);
+        let _ = tokenizer.with_truncation(Some(TruncationParams {
+            max_length,
+            ..Default::default()
+        }));
+    }
+
     Ok(tokenizer)
 }
 
+/// Max sequence length the model supports, read from
+/// `max_position_embeddings` in config.json.
+fn max_sequence_length(model_root: &Path) -> Option<usize> {
+    #[derive(Deserialize)]
+    struct PositionConfig {
+        max_position_embeddings: Option<usize>,
+    }
+
+    let config = std::fs::read_to_string(model_root.join("config.json")).ok()?;
+    serde_json::from_str::<PositionConfig>(&config)
+        .ok()?
+        .max_position_embeddings
+}
+
 pub fn batch(encodings: Vec<Encoding>, pooled_indices: Vec<u32>, raw_indices: Vec<u32>) -> Batch {
     let mut input_ids = Vec::new();
     let mut token_type_ids = Vec::new();
@@ -497,3 +590,71 @@ pub fn batch(encodings: Vec<Encoding>, pooled_indices: Vec<u32>, raw_indices: Ve
         raw_indices,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{max_sequence_length, parse_dtype, DType, Device};
+    use candle::Tensor;
+    use candle_nn::VarBuilder;
+
+    #[test]
+    fn test_max_sequence_length() {
+        let dir = std::env::temp_dir().join("spyglass-test-model-config");
+        let _ = std::fs::create_dir_all(&dir);
+
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{"model_type": "bert", "max_position_embeddings": 512}"#,
+        )
+        .unwrap();
+        assert_eq!(max_sequence_length(&dir), Some(512));
+
+        // Configs without the field don't configure truncation.
+        std::fs::write(dir.join("config.json"), r#"{"model_type": "bert"}"#).unwrap();
+        assert_eq!(max_sequence_length(&dir), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_dtype() {
+        let cpu = Device::Cpu;
+        assert_eq!(parse_dtype(Some("float32"), &cpu).unwrap(), DType::F32);
+        assert_eq!(parse_dtype(Some("float16"), &cpu).unwrap(), DType::F16);
+        assert_eq!(parse_dtype(Some("bfloat16"), &cpu).unwrap(), DType::BF16);
+        assert!(parse_dtype(Some("int8"), &cpu).is_err());
+        // No explicit dtype on the CPU falls back to f32.
+        assert_eq!(parse_dtype(None, &cpu).unwrap(), DType::F32);
+    }
+
+    #[test]
+    fn test_load_bf16_safetensors() {
+        let path = std::env::temp_dir().join("spyglass-test-bf16.safetensors");
+
+        // Build a tiny bf16 fixture & load it back the same way
+        // `CandleBackend::new` loads model weights.
+        let tensor = Tensor::from_slice(&[0.5f32, 1.0, -2.0, 4.0], (2, 2), &Device::Cpu)
+            .unwrap()
+            .to_dtype(DType::BF16)
+            .unwrap();
+        tensor.save_safetensors("weight", &path).unwrap();
+
+        let vb = unsafe {
+            VarBuilder::from_mmaped_safetensors(&[path.clone()], DType::BF16, &Device::Cpu).unwrap()
+        };
+        let loaded = vb.get((2, 2), "weight").unwrap();
+        assert_eq!(loaded.dtype(), DType::BF16);
+
+        // These values are all exactly representable in bf16.
+        let values = loaded
+            .to_dtype(DType::F32)
+            .unwrap()
+            .flatten_all()
+            .unwrap()
+            .to_vec1::<f32>()
+            .unwrap();
+        assert_eq!(values, vec![0.5, 1.0, -2.0, 4.0]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,5 +1,9 @@
-use crate::layers::HiddenAct;
-use crate::models::PositionEmbeddingType;
+use crate::layers::{HiddenAct, LayerNorm, Linear};
+use crate::models::nomic::{cos_sin, inv_freqs};
+use crate::models::{Model, PositionEmbeddingType};
+use crate::{Batch, ModelType, Pool};
+use candle::{DType, Device, IndexOp, Module, Result, Tensor, D};
+use candle_nn::{Embedding, VarBuilder};
 use serde::Deserialize;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -33,3 +37,475 @@ pub struct GTEConfig {
     #[serde(default)]
     pub logn_attention_clip1: bool,
 }
+
+struct GTEAttention {
+    qkv_linear: Linear,
+    o_proj: Linear,
+
+    num_attention_heads: usize,
+    attention_head_size: usize,
+
+    softmax_scale: f64,
+
+    span: tracing::Span,
+}
+
+impl GTEAttention {
+    pub fn load(vb: VarBuilder, config: &GTEConfig) -> Result<Self> {
+        let num_attention_heads = config.num_attention_heads;
+        let attention_head_size = config.hidden_size / config.num_attention_heads;
+        let hidden_size = config.hidden_size;
+
+        let qkv_weight = vb
+            .pp("qkv_proj")
+            .get((hidden_size * 3, hidden_size), "weight")?;
+        let qkv_bias = vb.pp("qkv_proj").get(hidden_size * 3, "bias")?;
+
+        let qkv_linear = Linear::new(qkv_weight, Some(qkv_bias), None);
+
+        let o_proj_weight = vb.pp("o_proj").get((hidden_size, hidden_size), "weight")?;
+        let o_proj_bias = vb.pp("o_proj").get(hidden_size, "bias")?;
+
+        let o_proj = Linear::new(o_proj_weight, Some(o_proj_bias), None);
+
+        let softmax_scale = 1. / (attention_head_size as f64).sqrt();
+
+        Ok(Self {
+            qkv_linear,
+            o_proj,
+            num_attention_heads,
+            attention_head_size,
+            softmax_scale,
+            span: tracing::span!(tracing::Level::TRACE, "attention"),
+        })
+    }
+
+    fn apply_rotary(&self, x: &Tensor, cos: &Tensor, sin: &Tensor) -> Result<Tensor> {
+        let dim = self.attention_head_size / 2;
+        let x1 = x.narrow(D::Minus1, 0, dim)?;
+        let x2 = x.narrow(D::Minus1, dim, dim)?;
+        let rotate_x = Tensor::cat(&[&x2.neg()?, &x1], D::Minus1)?;
+        let rope = (x.broadcast_mul(cos)? + rotate_x.broadcast_mul(sin)?)?;
+        Ok(rope)
+    }
+
+    pub fn forward(
+        &self,
+        hidden_states: &Tensor,
+        attention_bias: Option<&Tensor>,
+        cos: &Tensor,
+        sin: &Tensor,
+    ) -> Result<Tensor> {
+        let _enter = self.span.enter();
+
+        let qkv = self.qkv_linear.forward(hidden_states)?;
+
+        // Reshape to [batch, heads * 3, seq_length, head_size]
+        let mut new_qkv_shape = qkv.dims().to_vec();
+        new_qkv_shape.pop();
+        new_qkv_shape.push(self.num_attention_heads * 3);
+        new_qkv_shape.push(self.attention_head_size);
+        let qkv = qkv.reshape(new_qkv_shape.as_slice())?.transpose(1, 2)?;
+
+        // Split qkv tensor
+        let query_layer = qkv.narrow(1, 0, self.num_attention_heads)?.contiguous()?;
+        let key_layer = qkv
+            .narrow(1, self.num_attention_heads, self.num_attention_heads)?
+            .contiguous()?;
+        let value_layer = qkv.narrow(1, self.num_attention_heads * 2, self.num_attention_heads)?;
+
+        let query_layer = self.apply_rotary(&query_layer, cos, sin)?;
+        let key_layer = self.apply_rotary(&key_layer, cos, sin)?;
+
+        let attention_scores = query_layer.matmul(&key_layer.t()?)?;
+        let mut attention_scores = (attention_scores * self.softmax_scale)?;
+
+        if let Some(attention_bias) = attention_bias {
+            attention_scores = attention_scores.add(attention_bias)?;
+        }
+
+        let attention_probs = candle_nn::ops::softmax_last_dim(&attention_scores)?;
+        let context_layer = attention_probs.matmul(&value_layer.contiguous()?)?;
+
+        let context_layer = context_layer.transpose(1, 2)?.flatten_from(D::Minus2)?;
+
+        self.o_proj.forward(&context_layer)
+    }
+}
+
+struct GTEMLP {
+    up_gate_proj: Linear,
+    down_proj: Linear,
+
+    act: HiddenAct,
+    intermediate_size: usize,
+
+    span: tracing::Span,
+}
+
+impl GTEMLP {
+    pub fn load(vb: VarBuilder, config: &GTEConfig) -> Result<Self> {
+        let intermediate_size = config.intermediate_size;
+
+        let up_gate_proj_weight = vb
+            .pp("up_gate_proj")
+            .get((intermediate_size * 2, config.hidden_size), "weight")?;
+
+        let up_gate_proj = Linear::new(up_gate_proj_weight, None, None);
+
+        let down_proj_weight = vb
+            .pp("down_proj")
+            .get((config.hidden_size, intermediate_size), "weight")?;
+        let down_proj_bias = vb.pp("down_proj").get(config.hidden_size, "bias")?;
+        let down_proj = Linear::new(down_proj_weight, Some(down_proj_bias), None);
+
+        Ok(Self {
+            up_gate_proj,
+            down_proj,
+            intermediate_size,
+            act: config.hidden_act.clone(),
+            span: tracing::span!(tracing::Level::TRACE, "mlp"),
+        })
+    }
+
+    pub fn forward(&self, hidden_states: &Tensor) -> Result<Tensor> {
+        let _enter = self.span.enter();
+
+        let up_gate_states = self.up_gate_proj.forward(hidden_states)?;
+        let up_states = up_gate_states.narrow(D::Minus1, 0, self.intermediate_size)?;
+        let gate_states =
+            up_gate_states.narrow(D::Minus1, self.intermediate_size, self.intermediate_size)?;
+
+        let gate_states = match self.act {
+            HiddenAct::Gelu => gate_states.gelu(),
+            HiddenAct::Relu => gate_states.relu(),
+            HiddenAct::Swiglu => gate_states.silu(),
+        }?;
+        self.down_proj.forward(&(gate_states * up_states)?)
+    }
+}
+
+struct GTELayer {
+    attention: GTEAttention,
+    mlp: GTEMLP,
+    attention_layer_norm: LayerNorm,
+    mlp_layer_norm: LayerNorm,
+
+    span: tracing::Span,
+}
+
+impl GTELayer {
+    pub fn load(vb: VarBuilder, config: &GTEConfig) -> Result<Self> {
+        let attention = GTEAttention::load(vb.pp("attention"), config)?;
+        let mlp = GTEMLP::load(vb.pp("mlp"), config)?;
+
+        let attention_layer_norm =
+            LayerNorm::load(vb.pp("attn_ln"), config.hidden_size, config.layer_norm_eps)?;
+        let mlp_layer_norm =
+            LayerNorm::load(vb.pp("mlp_ln"), config.hidden_size, config.layer_norm_eps)?;
+
+        Ok(Self {
+            attention,
+            mlp,
+            attention_layer_norm,
+            mlp_layer_norm,
+            span: tracing::span!(tracing::Level::TRACE, "layer"),
+        })
+    }
+
+    pub fn forward(
+        &self,
+        hidden_states: &Tensor,
+        attention_bias: Option<&Tensor>,
+        cos: &Tensor,
+        sin: &Tensor,
+    ) -> Result<Tensor> {
+        let _enter = self.span.enter();
+
+        let attn_output = self
+            .attention
+            .forward(hidden_states, attention_bias, cos, sin)?;
+        let normed_attn_res_output = self
+            .attention_layer_norm
+            .forward(&attn_output, Some(hidden_states))?;
+
+        let mlp_output = self.mlp.forward(&normed_attn_res_output)?;
+        self.mlp_layer_norm
+            .forward(&mlp_output, Some(&normed_attn_res_output))
+    }
+}
+
+pub struct GTEModel {
+    word_embeddings: Embedding,
+    token_type_embeddings: Option<Embedding>,
+    embeddings_norm: LayerNorm,
+    layers: Vec<GTELayer>,
+    pool: Pool,
+    pub device: Device,
+    dtype: DType,
+
+    rotary_dim: usize,
+    rotary_cache: (Tensor, Tensor),
+
+    num_attention_heads: usize,
+
+    span: tracing::Span,
+}
+
+impl GTEModel {
+    pub fn load(vb: VarBuilder, config: &GTEConfig, model_type: ModelType) -> Result<Self> {
+        if config.logn_attention_clip1 {
+            candle::bail!("`logn_attention_clip1` is not supported");
+        }
+        if config.logn_attention_scale {
+            candle::bail!("`logn_attention_scale` is not supported");
+        }
+
+        if config.position_embedding_type != PositionEmbeddingType::Rope {
+            candle::bail!("Only `PositionEmbeddingType::Rope` is supported");
+        }
+
+        let pool = match model_type {
+            ModelType::Classifier => {
+                candle::bail!("`classifier` model type is not supported for GTE")
+            }
+            ModelType::Embedding(pool) => {
+                if pool == Pool::Splade {
+                    candle::bail!("`splade` is not supported for GTE")
+                }
+                pool
+            }
+        };
+
+        let word_embeddings = Embedding::new(
+            vb.pp("embeddings.word_embeddings")
+                .get((config.vocab_size, config.hidden_size), "weight")?,
+            config.hidden_size,
+        );
+
+        let token_type_embeddings = if config.type_vocab_size > 0 {
+            Some(Embedding::new(
+                vb.pp("embeddings.token_type_embeddings")
+                    .get((config.type_vocab_size, config.hidden_size), "weight")?,
+                config.hidden_size,
+            ))
+        } else {
+            None
+        };
+
+        let layers = (0..config.num_hidden_layers)
+            .map(|index| GTELayer::load(vb.pp(format!("encoder.layer.{index}")), config))
+            .collect::<Result<Vec<_>>>()?;
+
+        let embeddings_norm = LayerNorm::load(
+            vb.pp("embeddings.LayerNorm"),
+            config.hidden_size,
+            config.layer_norm_eps,
+        )?;
+
+        let rotary_dim = layers[0].attention.attention_head_size;
+        let inv_freqs_tensor =
+            if let Some(RopeScaling::Ntk(NTKScaling { factor })) = config.rope_scaling {
+                let inv_freqs_tensor =
+                    inv_freqs(rotary_dim, config.rope_theta * factor, vb.device())?;
+                let s = factor.powf(2.0 / rotary_dim as f32) as f64;
+                (inv_freqs_tensor / s)?
+            } else {
+                inv_freqs(rotary_dim, config.rope_theta, vb.device())?
+            };
+        let rotary_cache = cos_sin(config.max_position_embeddings, &inv_freqs_tensor, vb.dtype())?;
+
+        Ok(Self {
+            word_embeddings,
+            token_type_embeddings,
+            embeddings_norm,
+            layers,
+            pool,
+            rotary_dim,
+            rotary_cache,
+            num_attention_heads: config.num_attention_heads,
+            device: vb.device().clone(),
+            dtype: vb.dtype(),
+            span: tracing::span!(tracing::Level::TRACE, "model"),
+        })
+    }
+
+    pub fn forward(&self, batch: Batch) -> Result<(Option<Tensor>, Option<Tensor>)> {
+        let _enter = self.span.enter();
+
+        let batch_size = batch.len();
+        let max_length = batch.max_length as usize;
+
+        let shape = (batch_size, max_length);
+
+        let (input_ids, type_ids, position_ids, attention_bias) = if batch_size > 1 {
+            // Prepare padded batch
+            let elems = batch_size * max_length;
+
+            let mut input_ids = Vec::with_capacity(elems);
+            let mut type_ids = Vec::with_capacity(elems);
+            let mut position_ids = Vec::with_capacity(elems);
+            let mut attention_bias = Vec::with_capacity(elems);
+            // Bool to know if we need to use the attention mask
+            let mut masking = false;
+
+            for i in 0..batch_size {
+                let start = batch.cumulative_seq_lengths[i] as usize;
+                let end = batch.cumulative_seq_lengths[i + 1] as usize;
+                let seq_length = (end - start) as u32;
+
+                // Copy values
+                for j in start..end {
+                    input_ids.push(batch.input_ids[j]);
+                    type_ids.push(batch.token_type_ids[j]);
+                    position_ids.push(batch.position_ids[j]);
+                    attention_bias.push(0.0);
+                }
+
+                // Add padding if needed
+                let padding = batch.max_length - seq_length;
+                if padding > 0 {
+                    // Set bool to use attention mask
+                    masking = true;
+                    for _ in 0..padding {
+                        input_ids.push(0);
+                        type_ids.push(0);
+                        position_ids.push(0);
+                        attention_bias.push(f32::NEG_INFINITY);
+                    }
+                }
+            }
+
+            let attention_bias = if masking {
+                let attention_bias =
+                    Tensor::from_vec(attention_bias, (batch_size, 1, 1, max_length), &self.device)?
+                        .to_dtype(self.dtype)?;
+                // Broadcast once instead of at every layer
+                let attention_bias = attention_bias
+                    .broadcast_as((
+                        batch_size,
+                        self.num_attention_heads,
+                        max_length,
+                        max_length,
+                    ))?
+                    .contiguous()?;
+                Some(attention_bias)
+            } else {
+                None
+            };
+
+            (input_ids, type_ids, position_ids, attention_bias)
+        } else {
+            (
+                batch.input_ids.clone(),
+                batch.token_type_ids.clone(),
+                batch.position_ids.clone(),
+                None,
+            )
+        };
+
+        // Create CPU tensors
+        let input_ids = Tensor::from_vec(input_ids, shape, &self.device)?;
+        let type_ids = Tensor::from_vec(type_ids, shape, &self.device)?;
+        let position_ids = Tensor::from_vec(position_ids, batch_size * max_length, &self.device)?;
+
+        let cos = self.rotary_cache.0.index_select(&position_ids, 0)?;
+        let sin = self.rotary_cache.1.index_select(&position_ids, 0)?;
+
+        let cos = cos.reshape((batch_size, 1, max_length, self.rotary_dim))?;
+        let sin = sin.reshape((batch_size, 1, max_length, self.rotary_dim))?;
+
+        let word_embeddings = self.word_embeddings.forward(&input_ids)?;
+        let token_type_embeddings = self
+            .token_type_embeddings
+            .as_ref()
+            .map(|emb| emb.forward(&type_ids))
+            .transpose()?;
+
+        let mut hidden_states = self
+            .embeddings_norm
+            .forward(&word_embeddings, token_type_embeddings.as_ref())?;
+
+        for layer in &self.layers {
+            hidden_states = layer.forward(&hidden_states, attention_bias.as_ref(), &cos, &sin)?;
+        }
+
+        let outputs = hidden_states;
+
+        let has_pooling_requests = !batch.pooled_indices.is_empty();
+        let has_raw_requests = !batch.raw_indices.is_empty();
+
+        let pooled_embeddings = if has_pooling_requests {
+            // Pool each selected member of the batch, skipping its padding
+            // tokens
+            let results: Result<Vec<Tensor>> = batch
+                .pooled_indices
+                .iter()
+                .map(|i| {
+                    let i = *i as usize;
+                    let length = (batch.cumulative_seq_lengths[i + 1]
+                        - batch.cumulative_seq_lengths[i]) as usize;
+                    let tokens = outputs.i(i)?;
+
+                    match self.pool {
+                        Pool::Cls => tokens.i(0)?.unsqueeze(0),
+                        Pool::LastToken => tokens.i(length - 1)?.unsqueeze(0),
+                        Pool::Mean => tokens.narrow(0, 0, length)?.sum_keepdim(0)? / (length as f64),
+                        Pool::Splade => unreachable!(),
+                    }
+                })
+                .collect();
+
+            Some(Tensor::cat(&results?, 0)?)
+        } else {
+            None
+        };
+
+        let raw_embeddings = if has_raw_requests {
+            // Reshape outputs
+            let (b, l, h) = outputs.shape().dims3()?;
+            let outputs = outputs.reshape((b * l, h))?;
+
+            // We need to remove the padding tokens only if batch_size > 1 and
+            // there are some members of the batch that require pooling or have
+            // different lengths
+            if batch_size > 1 {
+                let mut final_indices: Vec<u32> = Vec::with_capacity(batch_size * max_length);
+
+                for i in batch.raw_indices.into_iter() {
+                    let start = i * batch.max_length;
+                    let i = i as usize;
+                    let length =
+                        batch.cumulative_seq_lengths[i + 1] - batch.cumulative_seq_lengths[i];
+
+                    for j in start..start + length {
+                        // Add indices for the tokens of this specific member of the batch
+                        final_indices.push(j);
+                    }
+                }
+
+                let final_indices_length = final_indices.len();
+                let final_indices =
+                    Tensor::from_vec(final_indices, final_indices_length, &self.device)?;
+
+                // Select the tokens with final indices
+                Some(outputs.index_select(&final_indices, 0)?)
+            } else {
+                Some(outputs)
+            }
+        } else {
+            None
+        };
+
+        Ok((pooled_embeddings, raw_embeddings))
+    }
+}
+
+impl Model for GTEModel {
+    fn is_padded(&self) -> bool {
+        false
+    }
+    fn embed(&self, batch: Batch) -> Result<(Option<Tensor>, Option<Tensor>)> {
+        self.forward(batch)
+    }
+}
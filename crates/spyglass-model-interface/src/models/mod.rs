@@ -34,12 +34,12 @@ pub use bert::{BertConfig, BertModel, PositionEmbeddingType};
 use candle::{Result, Tensor};
 pub use distilbert::{DistilBertConfig, DistilBertModel};
 #[allow(unused_imports)]
-pub use gte::{GTEConfig, NTKScaling, RopeScaling};
+pub use gte::{GTEConfig, GTEModel, NTKScaling, RopeScaling};
 pub use jina::JinaBertModel;
 pub use jina_code::JinaCodeBertModel;
 pub use mistral::MistralConfig;
 pub use nomic::{NomicBertModel, NomicConfig};
-pub use qwen2::Qwen2Config;
+pub use qwen2::{Qwen2Config, Qwen2Model};
 
 // #[cfg(feature = "cuda")]
 // pub use flash_bert::FlashBertModel;
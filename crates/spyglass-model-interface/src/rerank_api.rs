@@ -0,0 +1,95 @@
+use std::{path::PathBuf, sync::Arc, time::Instant};
+
+use tokenizers::{Tokenizer, TruncationDirection};
+
+use crate::{batch, load_tokenizer, Backend, CandleBackend, ModelType};
+
+/// Max sequence length for the (query, passage) pair fed to the
+/// cross-encoder; longer passages are truncated from the right.
+const MAX_TOKENS: usize = 512;
+
+/// Cross-encoder reranker. Unlike the embedding model, which scores a query
+/// & document independently, the cross-encoder reads the (query, passage)
+/// pair together & predicts a relevance score. Slower per document but more
+/// accurate, so it's only run over a small candidate set after retrieval.
+#[derive(Clone)]
+pub struct RerankApi {
+    backend: Arc<CandleBackend>,
+    tokenizer: Tokenizer,
+}
+
+impl RerankApi {
+    pub fn new(model_root: PathBuf, device: Option<usize>, force_cpu: bool) -> anyhow::Result<Self> {
+        let tokenizer = load_tokenizer(&model_root)?;
+        let backend = CandleBackend::new(
+            model_root,
+            None,
+            ModelType::Classifier,
+            device,
+            force_cpu,
+        )?;
+
+        Ok(RerankApi {
+            backend: Arc::new(backend),
+            tokenizer,
+        })
+    }
+
+    /// Name of the device the model is running on, see
+    /// `CandleBackend::device_name`.
+    pub fn device_name(&self) -> String {
+        self.backend.device_name()
+    }
+
+    /// Scores each passage against `query`, higher is more relevant. Scores
+    /// come back in input order, one per passage.
+    pub fn rerank(&self, query: &str, passages: &[String]) -> anyhow::Result<Vec<f32>> {
+        let mut encodings = Vec::new();
+        for passage in passages {
+            let mut encoding = self
+                .tokenizer
+                .encode((query.to_string(), passage.to_string()), true)
+                .map_err(|err| anyhow::format_err!("Error tokenizing {:?}", err))?;
+            encoding.truncate(MAX_TOKENS, 0, TruncationDirection::Right);
+            encodings.push(encoding);
+        }
+
+        let max_batch = self
+            .backend
+            .max_batch_size()
+            .unwrap_or_else(|| encodings.len().max(1));
+
+        let mut scores = Vec::with_capacity(passages.len());
+        for batch_encodings in encodings.chunks(max_batch) {
+            let pooled_indices = (0..batch_encodings.len() as u32).collect::<Vec<u32>>();
+            let num_tokens: usize = batch_encodings
+                .iter()
+                .map(|encoding| encoding.len())
+                .sum();
+            let input_batch = batch(batch_encodings.to_vec(), pooled_indices, vec![]);
+
+            let start = Instant::now();
+            let predictions = self
+                .backend
+                .predict(input_batch)
+                .map_err(|err| anyhow::format_err!("Rerank failed {:?}", err))?;
+            log::debug!(
+                "Reranking batch of {} pairs ({} tokens) took {}",
+                batch_encodings.len(),
+                num_tokens,
+                start.elapsed().as_millis()
+            );
+
+            for batch_idx in 0..batch_encodings.len() {
+                match predictions.get(&batch_idx).and_then(|logits| logits.first()) {
+                    Some(score) => scores.push(*score),
+                    None => {
+                        return Err(anyhow::format_err!("Unable to process rerank prediction"));
+                    }
+                }
+            }
+        }
+
+        Ok(scores)
+    }
+}
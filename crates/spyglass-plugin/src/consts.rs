@@ -0,0 +1,12 @@
+/// Environment variables provided to every plugin on startup, alongside the
+/// user settings declared in its manifest.
+pub mod env {
+    /// Host configuration directory, e.g. `~/.config` on Linux.
+    pub const BASE_CONFIG_DIR: &str = "BASE_CONFIG_DIR";
+    /// Host data directory, e.g. `~/.local/share` on Linux.
+    pub const BASE_DATA_DIR: &str = "BASE_DATA_DIR";
+    /// Host home directory.
+    pub const HOST_HOME_DIR: &str = "HOST_HOME_DIR";
+    /// Host operating system, e.g. "linux", "macos" or "windows".
+    pub const HOST_OS: &str = "HOST_OS";
+}
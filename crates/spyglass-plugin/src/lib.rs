@@ -0,0 +1,72 @@
+//! Guest-side API for spyglass plugins. Plugins are compiled to wasm32-wasi
+//! & run sandboxed inside the spyglass server. Communication w/ the host goes
+//! over stdin/stdout as ron-serialized messages, so plugins must never write
+//! to stdout directly; use [`log`] for debug output.
+pub mod consts;
+mod shims;
+mod types;
+pub mod utils;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub use shims::*;
+pub use types::*;
+
+pub trait SpyglassPlugin {
+    /// Called once on plugin startup. Set up any configuration you need here
+    /// as well as any subscriptions.
+    fn load(&mut self);
+    /// Called when an asynchronous event is received: responses to requests
+    /// made by the plugin or subscription updates.
+    fn update(&mut self, event: PluginEvent);
+    /// Called when this plugin's lens is triggered in a search, to restrict
+    /// the results shown.
+    fn search_filters(&mut self) -> Vec<SearchFilter> {
+        Vec::new()
+    }
+}
+
+/// Wires a `SpyglassPlugin` impl up to the exports the host expects. The
+/// plugin type must implement `Default`, it's constructed on startup before
+/// `load` is called.
+#[macro_export]
+macro_rules! register_plugin {
+    ($plug:ty) => {
+        thread_local! {
+            static PLUGIN: std::cell::RefCell<$plug> =
+                std::cell::RefCell::new(<$plug>::default());
+        }
+
+        fn main() {
+            PLUGIN.with(|plugin| plugin.borrow_mut().load());
+        }
+
+        #[no_mangle]
+        pub fn update() {
+            if let Ok(event) = $crate::object_from_stdin::<$crate::PluginEvent>() {
+                PLUGIN.with(|plugin| plugin.borrow_mut().update(event));
+            }
+        }
+
+        #[no_mangle]
+        pub fn search_filter() {
+            let filters = PLUGIN.with(|plugin| plugin.borrow_mut().search_filters());
+            $crate::object_to_stdout(&filters);
+        }
+    };
+}
+
+/// Reads a ron-serialized object sent by the host from stdin.
+pub fn object_from_stdin<T: DeserializeOwned>() -> Result<T, ron::error::SpannedError> {
+    let mut buf = String::new();
+    let _ = std::io::stdin().read_line(&mut buf);
+    ron::from_str(&buf)
+}
+
+/// Writes a ron-serialized object to stdout for the host to pick up.
+pub fn object_to_stdout(obj: &impl Serialize) {
+    if let Ok(serialized) = ron::to_string(obj) {
+        println!("{serialized}");
+    }
+}
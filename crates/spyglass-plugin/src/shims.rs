@@ -0,0 +1,284 @@
+use crate::{
+    Authentication, DocumentQuery, DocumentUpdate, HttpMethod, LogLevel, PluginCommandRequest,
+    PluginState, Tag, TagModification,
+};
+
+#[link(wasm_import_module = "spyglass")]
+extern "C" {
+    fn plugin_cmd();
+    fn plugin_log();
+}
+
+/// Writes a log line to the plugin's log file (& the spyglass server log).
+/// stdout/stderr are used for host <-> plugin comms, so `println!` & friends
+/// won't show up anywhere; the `log*` shims are the only way for a plugin to
+/// log. `log` itself writes at info level.
+pub fn log(msg: &str) {
+    write_log(LogLevel::Info, msg);
+}
+
+/// Logs at debug level; only written when the plugin's log-level setting is
+/// turned down to "debug".
+pub fn log_debug(msg: &str) {
+    write_log(LogLevel::Debug, msg);
+}
+
+/// Logs at warn level.
+pub fn log_warn(msg: &str) {
+    write_log(LogLevel::Warn, msg);
+}
+
+/// Logs at error level.
+pub fn log_error(msg: &str) {
+    write_log(LogLevel::Error, msg);
+}
+
+/// Lines are sent to the host as `<level>\t<message>`.
+fn write_log(level: LogLevel, msg: &str) {
+    println!("{}\t{}", level.as_str(), msg);
+    unsafe {
+        plugin_log();
+    }
+}
+
+/// Serializes a command to stdout & pings the host to read it.
+fn send_command(cmd: &PluginCommandRequest) -> Result<(), ron::Error> {
+    println!("{}", ron::to_string(cmd)?);
+    unsafe {
+        plugin_cmd();
+    }
+    Ok(())
+}
+
+/// Permanently removes the document w/ this url from the index.
+pub fn delete_doc(url: &str) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::DeleteDoc {
+        url: url.to_string(),
+    })
+}
+
+/// Permanently removes every document matching the query from the index.
+/// The number of documents removed is delivered via
+/// `PluginEvent::DeleteResponse`. Empty queries are rejected by the host so
+/// a buggy plugin can't wipe the whole library.
+pub fn delete_docs_by_query(query: DocumentQuery) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::DeleteDocsByQuery { query })
+}
+
+/// Adds the urls to the crawl queue to be fetched & indexed. Urls already
+/// queued or indexed are skipped; the number actually added comes back via
+/// `PluginEvent::EnqueueResponse`.
+pub fn enqueue_all(urls: &[String]) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::Enqueue {
+        urls: urls.to_vec(),
+    })
+}
+
+/// Adds documents directly to the index. Documents w/ a url that already
+/// exists are updated instead of created. `tags` are applied to every
+/// document in the batch, on top of each document's own tags.
+pub fn add_document(documents: Vec<DocumentUpdate>, tags: Vec<Tag>) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::AddDocuments { documents, tags })
+}
+
+/// Adds and/or removes tags on all documents matching the query.
+pub fn modify_tags(
+    documents: DocumentQuery,
+    tag_modifications: TagModification,
+) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::ModifyTags {
+        documents,
+        tag_modifications,
+    })
+}
+
+/// Runs a document query once. Results are delivered asynchronously via
+/// `PluginEvent::DocumentResponse`.
+pub fn query_documents(query: DocumentQuery) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::QueryDocuments {
+        query,
+        subscribe: false,
+    })
+}
+
+/// Asks for another page of an earlier query. `request_id` & the available
+/// `page_count` come w/ each `PluginEvent::DocumentResponse`; pages past the
+/// end return an empty document list.
+pub fn request_document_page(request_id: &str, page: usize) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::RequestDocumentPage {
+        request_id: request_id.to_string(),
+        page,
+    })
+}
+
+/// Runs a document query at a regular interval for as long as the plugin is
+/// enabled. Results are delivered via `PluginEvent::DocumentResponse`.
+pub fn subscribe_for_documents(query: DocumentQuery) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::QueryDocuments {
+        query,
+        subscribe: true,
+    })
+}
+
+/// Fetches the indexed content for these document ids, e.g. ids from an
+/// earlier `PluginEvent::DocumentResponse`. Content is delivered via
+/// `PluginEvent::DocumentContentResponse` & is size-capped by the host, so
+/// very large documents come back w/ the `truncated` flag set.
+pub fn get_document_content(ids: &[String]) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::GetDocumentContent { ids: ids.to_vec() })
+}
+
+/// Reports the plugin's current status to the host. The latest status is
+/// shown in the client's plugin manager, so report something meaningful
+/// after each sync (e.g. "Imported 1,204 bookmarks") & on errors the user
+/// can act on. `progress` is a percentage for long-running work.
+pub fn report_status(
+    state: PluginState,
+    message: &str,
+    progress: Option<u8>,
+) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::ReportStatus {
+        state,
+        message: message.to_string(),
+        progress,
+    })
+}
+
+/// Asks the host to call `update` w/ `PluginEvent::IntervalUpdate` at the
+/// host's default interval. Use this to poll external resources.
+pub fn subscribe_for_updates() -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::SubscribeForUpdates {
+        interval_seconds: None,
+    })
+}
+
+/// Like [`subscribe_for_updates`] but w/ a requested interval. The host
+/// enforces a minimum, so very small values are rounded up.
+pub fn subscribe_for_updates_every(interval_seconds: u64) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::SubscribeForUpdates {
+        interval_seconds: Some(interval_seconds),
+    })
+}
+
+/// Runs a read-only query against a sqlite database previously synced into
+/// the plugin data directory (see [`sync_file`]). `params` are bound to
+/// `?1`, `?2`, ... placeholders in the query — always use placeholders
+/// instead of interpolating values (especially user settings) into the
+/// query string. Rows are delivered via `PluginEvent::SqlResponse` w/ the
+/// given `request_id` echoed back.
+pub fn sqlite3_query(
+    request_id: &str,
+    db: &str,
+    query: &str,
+    params: &[serde_json::Value],
+) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::SqliteQuery {
+        request_id: request_id.to_string(),
+        db: db.to_string(),
+        query: query.to_string(),
+        params: params.to_vec(),
+    })
+}
+
+/// Asks the host to copy a file into the plugin's mounted data directory.
+/// `dst` is a folder inside the plugin data dir (i.e. under `/`), `src` an
+/// absolute path on the host; the file keeps its name. Plugins can't read
+/// the host filesystem directly, so this is how importers get at files like
+/// browser bookmark databases.
+pub fn sync_file(dst: &str, src: &str) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::SyncFile {
+        dst: dst.to_string(),
+        src: src.to_string(),
+    })
+}
+
+/// Subscribes to filesystem events under `path`. Raw notifications are
+/// debounced by the host & delivered as `PluginEvent::FileCreated` /
+/// `FileUpdated` / `FileDeleted`, one event per changed file. `extensions`
+/// limits events to files w/ those extensions; an empty list receives
+/// everything. Set `recursive` to also watch subdirectories. The
+/// subscription lasts until the plugin is disabled.
+pub fn watch_path(path: &str, extensions: &[String], recursive: bool) -> Result<(), ron::Error> {
+    send_command(&PluginCommandRequest::WatchPath {
+        path: path.to_string(),
+        extensions: extensions.to_vec(),
+        recursive,
+    })
+}
+
+/// Builder for an http request made through the host. The wasm runtime has
+/// no direct network access, so libraries like reqwest won't work inside a
+/// plugin; responses are delivered via `PluginEvent::HttpResponse`.
+pub struct HttpRequest {
+    headers: Vec<(String, String)>,
+    method: HttpMethod,
+    url: String,
+    body: Option<String>,
+    auth: Option<Authentication>,
+}
+
+pub struct Http;
+
+impl Http {
+    pub fn request(url: &str) -> HttpRequest {
+        HttpRequest {
+            headers: Vec::new(),
+            method: HttpMethod::GET,
+            url: url.to_string(),
+            body: None,
+            auth: None,
+        }
+    }
+}
+
+impl HttpRequest {
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn get(self) -> Self {
+        self.method(HttpMethod::GET)
+    }
+
+    pub fn post(self) -> Self {
+        self.method(HttpMethod::POST)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: &str) -> Self {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    pub fn basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.auth = Some(Authentication::BASIC(
+            username.to_string(),
+            password.map(|pw| pw.to_string()),
+        ));
+        self
+    }
+
+    pub fn bearer_auth(mut self, token: &str) -> Self {
+        self.auth = Some(Authentication::BEARER(token.to_string()));
+        self
+    }
+
+    /// Sends the request. The response comes back asynchronously via
+    /// `PluginEvent::HttpResponse` w/ the request url attached. Binary
+    /// bodies are base64-encoded & bodies past the host's configured size
+    /// limit are truncated; see `HttpResponse`.
+    pub fn run(self) {
+        let _ = send_command(&PluginCommandRequest::HttpRequest {
+            headers: self.headers,
+            method: self.method,
+            url: self.url,
+            body: self.body,
+            auth: self.auth,
+        });
+    }
+}
@@ -0,0 +1,392 @@
+use serde::{Deserialize, Serialize};
+
+/// A tag is a `(label, value)` pair, e.g. `("lens", "files")`.
+pub type Tag = (String, String);
+
+/// Events sent by the host to a plugin's `update` handler.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PluginEvent {
+    /// A file under a [`watch_path`](crate::watch_path) subscription was
+    /// created. The payload is the `file://` uri of the file.
+    FileCreated(String),
+    /// A file under a `watch_path` subscription was modified.
+    FileUpdated(String),
+    /// A file under a `watch_path` subscription was removed.
+    FileDeleted(String),
+    /// Documents matching a query requested via `query_documents` or
+    /// `subscribe_for_documents`.
+    DocumentResponse {
+        request_id: String,
+        page_count: usize,
+        page: usize,
+        documents: Vec<DocumentResult>,
+    },
+    /// Indexed content for documents requested via `get_document_content`.
+    DocumentContentResponse { documents: Vec<DocumentContent> },
+    /// Number of documents removed by a `delete_docs_by_query` request.
+    DeleteResponse { count: usize },
+    /// Number of urls actually added by an `enqueue_all` request, after the
+    /// host dropped anything already queued or indexed.
+    EnqueueResponse { count: usize },
+    /// Response (or error) for a request made via [`Http`](crate::Http).
+    HttpResponse {
+        url: String,
+        result: Result<HttpResponse, String>,
+    },
+    /// Interval tick, requested via `subscribe_for_updates`.
+    IntervalUpdate,
+    /// Rows for a query made via [`sqlite3_query`](crate::sqlite3_query).
+    /// Values come back as JSON scalars; blobs are hex-encoded strings.
+    SqlResponse {
+        request_id: String,
+        columns: Vec<String>,
+        rows: Vec<Vec<serde_json::Value>>,
+    },
+    /// A request was denied because it isn't covered by the permissions
+    /// declared in the plugin manifest.
+    PermissionDenied {
+        /// Which request was denied, e.g. `HttpRequest`.
+        command: String,
+        reason: String,
+    },
+}
+
+/// Requests a plugin can make of the host.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PluginCommandRequest {
+    DeleteDoc {
+        url: String,
+    },
+    /// Delete every document matching the query from the index. The number
+    /// of documents removed is delivered via `PluginEvent::DeleteResponse`.
+    /// Empty queries are rejected rather than deleting everything.
+    DeleteDocsByQuery {
+        query: DocumentQuery,
+    },
+    Enqueue {
+        urls: Vec<String>,
+    },
+    QueryDocuments {
+        query: DocumentQuery,
+        /// Re-run the query on an interval instead of once.
+        subscribe: bool,
+    },
+    /// Ask for another page of an earlier `QueryDocuments` request. The
+    /// `request_id` comes from the `PluginEvent::DocumentResponse`.
+    RequestDocumentPage {
+        request_id: String,
+        page: usize,
+    },
+    HttpRequest {
+        headers: Vec<(String, String)>,
+        method: HttpMethod,
+        url: String,
+        body: Option<String>,
+        auth: Option<Authentication>,
+    },
+    ModifyTags {
+        documents: DocumentQuery,
+        tag_modifications: TagModification,
+    },
+    AddDocuments {
+        documents: Vec<DocumentUpdate>,
+        tags: Vec<Tag>,
+    },
+    SubscribeForUpdates {
+        /// Requested seconds between `IntervalUpdate`s. Clamped to a
+        /// host-enforced minimum; `None` uses the host default.
+        interval_seconds: Option<u64>,
+    },
+    /// Report the plugin's current status. The host stores the latest one
+    /// per plugin & surfaces it in the client's plugin manager.
+    ReportStatus {
+        state: PluginState,
+        message: String,
+        /// Percentage for long-running work, if known.
+        progress: Option<u8>,
+    },
+    /// Run a read-only query against a sqlite database in the plugin data
+    /// directory. Rows are delivered via `PluginEvent::SqlResponse`.
+    SqliteQuery {
+        /// Plugin-chosen id echoed back w/ the response.
+        request_id: String,
+        /// Database file inside the plugin data directory.
+        db: String,
+        query: String,
+        /// Values bound to `?1`, `?2`, ... placeholders in the query.
+        params: Vec<serde_json::Value>,
+    },
+    /// Copy a file from the host filesystem into the plugin's mounted data
+    /// directory so the plugin can read it.
+    SyncFile {
+        /// Destination folder inside the plugin data directory.
+        dst: String,
+        /// Source file on the host.
+        src: String,
+    },
+    /// Fetch the indexed content for these document ids. Responses are
+    /// size-capped by the host; check `DocumentContent::truncated`.
+    GetDocumentContent {
+        ids: Vec<String>,
+    },
+    /// Watch `path` for filesystem changes. Events are debounced by the host
+    /// & delivered as `PluginEvent::FileCreated` / `FileUpdated` /
+    /// `FileDeleted`.
+    WatchPath {
+        path: String,
+        /// Only report files w/ these extensions; empty reports everything.
+        extensions: Vec<String>,
+        recursive: bool,
+    },
+}
+
+/// Coarse state a plugin can report about itself via `report_status`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum PluginState {
+    /// Working through something, e.g. mid-sync.
+    Running,
+    /// Nothing left to do until the next update.
+    Idle,
+    /// Something went wrong; the message has the details.
+    Error,
+}
+
+impl PluginState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginState::Running => "running",
+            PluginState::Idle => "idle",
+            PluginState::Error => "error",
+        }
+    }
+}
+
+/// Severity of a plugin log line; used by the `log_*` shims & the
+/// per-plugin log-level setting. Variants are ordered by severity.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Filters applied to search results when a lens is triggered.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum SearchFilter {
+    URLRegexAllow(String),
+    URLRegexSkip(String),
+}
+
+/// Defines a document query. Fields set to `None` are ignored; the rest are
+/// and'd together.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct DocumentQuery {
+    /// Match against the document url. Since a single document can only have
+    /// one url these entries are or'd together.
+    pub urls: Option<Vec<String>>,
+    /// Match against the document id. Since a single document can only have
+    /// one id these entries are or'd together.
+    pub ids: Option<Vec<String>>,
+    /// Matches only documents that have the specified tags.
+    pub has_tags: Option<Vec<Tag>>,
+    /// Matches only documents that do not have the specified tags.
+    pub exclude_tags: Option<Vec<Tag>>,
+    /// Number of documents per response page. Unset keeps every match on a
+    /// single page; use `request_document_page` to walk the other pages.
+    #[serde(default)]
+    pub page_size: Option<usize>,
+}
+
+impl DocumentQuery {
+    /// True when no constraints are set; such a query matches every
+    /// document in the index.
+    pub fn is_empty(&self) -> bool {
+        self.urls.as_ref().map_or(true, |urls| urls.is_empty())
+            && self.ids.as_ref().map_or(true, |ids| ids.is_empty())
+            && self.has_tags.as_ref().map_or(true, |tags| tags.is_empty())
+            && self
+                .exclude_tags
+                .as_ref()
+                .map_or(true, |tags| tags.is_empty())
+    }
+}
+
+/// Defines a tag modification request. Tags can be added or removed.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TagModification {
+    pub add: Option<Vec<Tag>>,
+    pub remove: Option<Vec<Tag>>,
+}
+
+/// A document returned for a `DocumentQuery`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DocumentResult {
+    pub doc_id: String,
+    pub domain: String,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub tags: Vec<Tag>,
+}
+
+/// Indexed content for a single document, returned for a
+/// `GetDocumentContent` request.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DocumentContent {
+    pub doc_id: String,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub content: String,
+    /// True when `content` was cut short to fit the host's size caps.
+    pub truncated: bool,
+}
+
+/// A document to add to (or update in) the index.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DocumentUpdate {
+    /// Uniquely identifies the document; updates a document w/ the same url.
+    pub url: String,
+    /// Url opened when the result is selected, defaults to `url`.
+    pub open_url: Option<String>,
+    pub content: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<Tag>,
+    /// When the document was published, as an RFC 3339 timestamp. Invalid
+    /// timestamps are dropped by the host.
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum HttpMethod {
+    DELETE,
+    GET,
+    HEAD,
+    OPTIONS,
+    PATCH,
+    POST,
+    PUT,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Authentication {
+    /// Basic auth w/ a username & an optional password.
+    BASIC(String, Option<String>),
+    /// Bearer token.
+    BEARER(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct HttpResponse {
+    pub headers: Vec<(String, String)>,
+    /// Body decoded as UTF-8 text. `None` for binary bodies; see
+    /// `body_base64`.
+    pub response: Option<String>,
+    /// Base64-encoded body for binary payloads (favicons, archives, etc).
+    #[serde(default)]
+    pub body_base64: Option<String>,
+    /// Content type reported by the server, if any.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// True when the body was cut off at the host's max response size.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl HttpResponse {
+    /// Parses the response body as JSON, if there was one.
+    pub fn as_json(&self) -> Option<serde_json::Value> {
+        self.response
+            .as_ref()
+            .and_then(|body| serde_json::from_str(body).ok())
+    }
+
+    /// Decodes the body of a binary response.
+    pub fn as_bytes(&self) -> Option<Vec<u8>> {
+        use base64::prelude::*;
+        self.body_base64
+            .as_ref()
+            .and_then(|body| BASE64_STANDARD.decode(body).ok())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DocumentQuery, HttpResponse, LogLevel};
+
+    #[test]
+    fn test_log_level() {
+        // Ordered by severity so levels can be compared for filtering.
+        assert!(LogLevel::Debug < LogLevel::Info);
+        assert!(LogLevel::Warn < LogLevel::Error);
+        assert_eq!(LogLevel::parse("WARN"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse(" error "), Some(LogLevel::Error));
+        assert_eq!(LogLevel::parse("verbose"), None);
+    }
+
+    #[test]
+    fn test_document_query_is_empty() {
+        assert!(DocumentQuery::default().is_empty());
+        // An empty constraint list is just as unconstrained as `None`.
+        assert!(DocumentQuery {
+            urls: Some(Vec::new()),
+            ..Default::default()
+        }
+        .is_empty());
+        assert!(!DocumentQuery {
+            has_tags: Some(vec![("lens".into(), "bookmarks".into())]),
+            ..Default::default()
+        }
+        .is_empty());
+    }
+
+    #[test]
+    fn test_http_response_bodies() {
+        let text = HttpResponse {
+            headers: Vec::new(),
+            response: Some(r#"{"hello": "world"}"#.into()),
+            body_base64: None,
+            content_type: Some("application/json".into()),
+            truncated: false,
+        };
+        assert_eq!(text.as_json().unwrap()["hello"], "world");
+        assert!(text.as_bytes().is_none());
+
+        // A little-endian u32 1 followed by 0xff; not valid UTF-8.
+        let binary = HttpResponse {
+            headers: Vec::new(),
+            response: None,
+            body_base64: Some("AQAAAP8=".into()),
+            content_type: Some("application/octet-stream".into()),
+            truncated: false,
+        };
+        assert!(binary.as_json().is_none());
+        assert_eq!(binary.as_bytes().unwrap(), vec![1, 0, 0, 0, 0xff]);
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+use url::Url;
+
+/// Converts a path into a `file://` uri, matching the form spyglass uses for
+/// indexed files.
+pub fn path_to_uri(path: &Path) -> String {
+    path_string_to_uri(&path.display().to_string())
+}
+
+pub fn path_string_to_uri(path_str: &str) -> String {
+    let mut new_url = Url::parse("file://").expect("Base URI");
+    let _ = new_url.set_host(Some(""));
+    // Fixes issues handling windows drive letters
+    let path_str = path_str.replace(':', "%3A");
+    // Fixes an issue where DirEntry adds too many escapes.
+    let path_str = path_str.replace(r"\\\\", r"\");
+    let path_str = path_str.replace(r"\\", r"\");
+
+    new_url.set_path(&path_str);
+    new_url.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::path_string_to_uri;
+
+    #[test]
+    fn test_path_string_to_uri() {
+        #[cfg(target_os = "windows")]
+        assert_eq!(
+            path_string_to_uri("C:\\tmp\\path_to_uri\\test.txt"),
+            "file:///C%3A/tmp/path_to_uri/test.txt"
+        );
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(
+            path_string_to_uri("/tmp/path_to_uri/test.txt"),
+            "file:///tmp/path_to_uri/test.txt"
+        );
+    }
+}
@@ -1,13 +1,34 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum RpcEventType {
     ChatStream,
     ConnectionSyncFinished,
+    /// A batch of crawls was committed to the index.
+    CrawlBatchCompleted,
+    /// A document's tags or metadata changed; payload is the doc id.
+    DocumentUpdated,
+    EmbeddingProgress,
+    /// Pending index changes were flushed to disk.
+    IndexCommitted,
+    IndexOptimization,
+    /// A lens started bootstrapping (discovering & enqueuing its URLs).
+    LensBootstrapStarted,
+    /// Periodic per-lens crawl progress while a lens has queued work.
+    LensBootstrapProgress,
+    /// A lens' crawl queue drained.
+    LensBootstrapFinished,
     LensUninstalled,
     LensInstalled,
+    LensLoadError,
     ModelDownloadStatus,
+    PluginDisabled,
+    PluginStatus,
+    Reindex,
+    SyncConflict,
+    TaskProgress,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -18,6 +39,150 @@ pub struct RpcEvent {
     pub payload: Option<Value>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TaskProgressPayload {
+    /// Echoes the request_id the client passed in when the work was enqueued,
+    /// so user-initiated work can be correlated with its progress events.
+    pub request_id: Option<String>,
+    pub url: String,
+    pub status: String,
+}
+
+/// Two devices changed the same synced item within the conflict window. Both
+/// versions are kept; clients should surface this so the user can pick one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SyncConflictPayload {
+    /// Identifier of the conflicting item, e.g. "favorite:<url>".
+    pub item_id: String,
+    /// Device that wrote the winning version.
+    pub winner_device: String,
+    /// Device that wrote the conflicting version.
+    pub loser_device: String,
+}
+
+/// Progress of an `optimize_index` run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum IndexOptimizationPayload {
+    Started,
+    Finished {
+        segments_before: u32,
+        segments_after: u32,
+        bytes_reclaimed: u64,
+    },
+    Error {
+        msg: String,
+    },
+}
+
+/// Progress of a `reindex` run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ReindexPayload {
+    Started {
+        total_docs: u64,
+    },
+    Progress {
+        processed: u64,
+        total_docs: u64,
+    },
+    Finished {
+        num_docs: u64,
+        /// Documents w/ no cached content to rebuild from; these need a
+        /// recrawl.
+        skipped: u64,
+    },
+    Error {
+        msg: String,
+    },
+}
+
+/// Progress of the background embedding task, published periodically while
+/// there's work in the embedding queue. A final event w/ `remaining` at zero
+/// is sent when the queue drains so clients can clear any progress UI.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EmbeddingProgressPayload {
+    /// Jobs still waiting in the queue (queued or processing).
+    pub remaining: u64,
+    /// Jobs completed so far.
+    pub completed: u64,
+    /// Jobs completed per second since the last report, if known.
+    pub rate: Option<f32>,
+}
+
+/// A lens kicked off its bootstrap process. `LensBootstrapProgress` events
+/// follow while the lens' crawl queue has work, then a single
+/// `LensBootstrapFinished` once it drains.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LensBootstrapStartedPayload {
+    pub lens: String,
+}
+
+/// Per-lens crawl progress, published periodically while the lens has queued
+/// work. Enough to render a "2,301 of 12,000 pages" progress bar without
+/// polling `get_library_stats`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LensBootstrapProgressPayload {
+    pub lens: String,
+    /// Pages crawled so far, including failures.
+    pub crawled: u64,
+    /// Total pages known for the lens (crawled + still queued).
+    pub total: u64,
+    /// Pages crawled per second since the last report, if known.
+    pub rate: Option<f32>,
+}
+
+/// A lens' crawl queue drained; clients can finalize any progress UI.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LensBootstrapFinishedPayload {
+    pub lens: String,
+    /// Documents indexed for the lens.
+    pub indexed: u64,
+}
+
+/// A batch of crawled documents was added to the index, w/ how many each
+/// lens contributed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrawlBatchCompletedPayload {
+    /// Documents added/updated per lens. Documents w/o a lens tag aren't
+    /// counted here.
+    pub lenses: HashMap<String, u64>,
+    /// Total documents added/updated in the batch.
+    pub num_docs: u64,
+}
+
+/// Pending index changes were flushed to disk & are now searchable.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexCommittedPayload {
+    pub num_docs: u64,
+}
+
+/// A lens file in the lens directory failed validation & was not loaded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LensLoadErrorPayload {
+    /// File that failed validation.
+    pub path: String,
+    /// Human-readable problems found in the file.
+    pub errors: Vec<String>,
+}
+
+/// Latest status reported by a plugin through `ReportStatus`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PluginStatusPayload {
+    pub plugin_name: String,
+    /// "running" / "idle" / "error"
+    pub state: String,
+    pub message: String,
+    /// Percentage for long-running work, if the plugin reported one.
+    pub progress: Option<u8>,
+}
+
+/// A plugin was automatically disabled after repeatedly running past its
+/// execution budget.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PluginDisabledPayload {
+    pub plugin_name: String,
+    pub reason: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum ModelDownloadStatusPayload {
     Finished { model_name: String },
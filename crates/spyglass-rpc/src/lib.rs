@@ -1,13 +1,17 @@
+use chrono::{DateTime, Utc};
 use jsonrpsee::core::{JsonValue, RpcResult, SubscriptionResult};
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::{ErrorObject, ErrorObjectOwned};
 use serde::Serialize;
 use shared::config::UserSettings;
-use shared::llm::{ChatMessage, LlmSession};
+use shared::llm::{ChatMessage, ChatStream, LlmSession};
 use shared::request::{BatchDocumentRequest, RawDocumentRequest, SearchLensesParam, SearchParam};
 use shared::response::{
-    AppStatus, DefaultIndices, LensResult, LibraryStats, ListConnectionResult, PluginResult,
-    SearchLensesResp, SearchResults,
+    AppStatus, AskLibraryResult, BackupResult, ChatSessionResult, DefaultIndices, DocumentDetails,
+    LensExportResult, LensResult, LensUpdateDiff, LibraryStats, ListConnectionResult,
+    ExplainResult, LlmModelResult,
+    OptimizeResult, PluginResult, SearchLensesResp, SearchResult, SearchResults,
+    SuggestedLensResult, TagCountResult,
 };
 use std::collections::HashMap;
 
@@ -43,6 +47,13 @@ pub trait Rpc {
     #[method(name = "index.is_document_indexed")]
     async fn is_document_indexed(&self, url: String) -> RpcResult<bool>;
 
+    /// Batch form of `is_document_indexed`: checks a whole set of URLs w/ a
+    /// single query. `http`/`https` & trailing-slash variants resolve to the
+    /// indexed copy. Batches are capped; oversized requests are rejected w/
+    /// an error naming the limit.
+    #[method(name = "index.are_documents_indexed")]
+    async fn are_documents_indexed(&self, urls: Vec<String>) -> RpcResult<HashMap<String, bool>>;
+
     /// Permanently deletes a document from the spyglass index and any associated
     /// data.
     #[method(name = "index.delete_document")]
@@ -51,60 +62,246 @@ pub trait Rpc {
     #[method(name = "index.delete_document_by_url")]
     async fn delete_document_by_url(&self, url: String) -> RpcResult<()>;
 
+    /// Merges index segments & garbage collects unreferenced files. Progress
+    /// is emitted via `RpcEventType::IndexOptimization` events.
+    #[method(name = "index.optimize")]
+    async fn optimize_index(&self) -> RpcResult<OptimizeResult>;
+
+    /// Rebuilds the index from the database & cached document content into a
+    /// fresh directory that's swapped in once complete. Progress is emitted
+    /// via `RpcEventType::Reindex` events; returns the number of documents
+    /// written. A restart is needed to pick up the rebuilt index.
+    #[method(name = "index.reindex")]
+    async fn reindex(&self) -> RpcResult<u64>;
+
+    /// Exports the index, database & lens files into a portable archive at
+    /// `path`. Logs & model weights are excluded.
+    #[method(name = "backup")]
+    async fn backup(&self, path: String) -> RpcResult<BackupResult>;
+
     #[method(name = "authorize_connection")]
     async fn authorize_connection(&self, id: String) -> RpcResult<()>;
 
     #[method(name = "app_status")]
     async fn app_status(&self) -> RpcResult<AppStatus>;
 
+    /// Asks a question across the whole library: the question is embedded,
+    /// the closest segments (optionally restricted to `lenses`) are pulled in
+    /// as context & the answer is streamed as `ChatStream` events. The result
+    /// includes citations for the source documents used.
+    #[method(name = "ask_library")]
+    async fn ask_library(
+        &self,
+        question: String,
+        lenses: Vec<String>,
+    ) -> RpcResult<AskLibraryResult>;
+
     #[method(name = "chat_completion")]
     async fn chat_completion(&self, session: LlmSession) -> RpcResult<ChatMessage>;
 
+    /// Streams `ChatStream` events (LoadingPrompt, ChatStart, Token,
+    /// ChatDone) for `session` directly to the subscriber. Dropping the
+    /// subscription mid-generation cancels the sampler loop.
+    #[subscription(name = "chat", unsubscribe = "chat_unsubscribe", item = ChatStream)]
+    async fn chat(&self, session: LlmSession) -> SubscriptionResult;
+
+    /// Diffs the installed version of a lens against the latest version in
+    /// the lens directory (added/removed domains, urls & rules) w/o applying
+    /// the update.
+    #[method(name = "check_lens_update")]
+    async fn check_lens_update(&self, name: String) -> RpcResult<LensUpdateDiff>;
+
+    /// Starts a persisted chat session about a document. Follow-up questions
+    /// go through `append_chat_message` & keep their prior turns.
+    #[method(name = "create_chat_session")]
+    async fn create_chat_session(&self, doc_id: String) -> RpcResult<ChatSessionResult>;
+
+    /// Appends a user message to a session & runs the LLM w/ the accumulated
+    /// history plus context retrieved for this turn. Returns the assistant's
+    /// reply, which is also persisted to the session.
+    #[method(name = "append_chat_message")]
+    async fn append_chat_message(
+        &self,
+        session_id: i64,
+        content: String,
+    ) -> RpcResult<ChatMessage>;
+
+    /// Lists persisted chat sessions, most recently used first.
+    #[method(name = "list_chat_sessions")]
+    async fn list_chat_sessions(&self) -> RpcResult<Vec<ChatSessionResult>>;
+
     #[method(name = "default_indices")]
     async fn default_indices(&self) -> RpcResult<DefaultIndices>;
 
+    /// Explains why a document ranks where it does for a query: per-field
+    /// score contributions & the boosts applied.
+    #[method(name = "explain_search")]
+    async fn explain_search(
+        &self,
+        doc_id: String,
+        query: String,
+        lenses: Vec<String>,
+    ) -> RpcResult<ExplainResult>;
+
+    /// Exports a lens & its indexed documents into a shareable archive at
+    /// `path` that `install_lens`/`LoadArchive` can ingest, so a pre-crawled
+    /// lens can be handed to another install w/o re-crawling.
+    #[method(name = "export_lens")]
+    async fn export_lens(&self, name: String, path: String) -> RpcResult<LensExportResult>;
+
+    /// Marks a document as a favorite so it's boosted in search results.
+    #[method(name = "favorite_document")]
+    async fn favorite_document(&self, doc_id: String) -> RpcResult<()>;
+
+    /// A single document's content & metadata, e.g. for a preview pane.
+    /// Content is capped at a preview size unless `full_content` is set;
+    /// `is_truncated` on the result indicates it was cut off.
+    #[method(name = "get_document")]
+    async fn get_document(&self, doc_id: String, full_content: bool)
+        -> RpcResult<DocumentDetails>;
+
     #[method(name = "get_library_stats")]
     async fn get_library_stats(&self) -> RpcResult<HashMap<String, LibraryStats>>;
 
+    /// Finds documents similar to `doc_id` based on its stored embedding
+    /// vectors. Results are ordered by similarity; `score` holds the
+    /// similarity, higher is more similar.
+    #[method(name = "get_similar_documents")]
+    async fn get_similar_documents(
+        &self,
+        doc_id: String,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<SearchResult>>;
+
     #[method(name = "install_lens")]
     async fn install_lens(&self, lens_name: String) -> RpcResult<()>;
 
+    /// Downloads a chat model: either a name from the curated catalog or a
+    /// direct URL to a gguf file. The download runs in the background &
+    /// reports progress via `RpcEventType::ModelDownloadStatus` events.
+    #[method(name = "install_llm_model")]
+    async fn install_llm_model(&self, name_or_url: String) -> RpcResult<()>;
+
     #[method(name = "list_connections")]
     async fn list_connections(&self) -> RpcResult<ListConnectionResult>;
 
+    /// Lists all favorited documents.
+    #[method(name = "list_favorites")]
+    async fn list_favorites(&self) -> RpcResult<Vec<SearchResult>>;
+
     #[method(name = "list_installed_lenses")]
     async fn list_installed_lenses(&self) -> RpcResult<Vec<LensResult>>;
 
+    /// Lists chat models: the curated catalog plus any ggufs already in the
+    /// model dir, noting which one is active.
+    #[method(name = "list_llm_models")]
+    async fn list_llm_models(&self) -> RpcResult<Vec<LlmModelResult>>;
+
     #[method(name = "list_plugins")]
     async fn list_plugins(&self) -> RpcResult<Vec<PluginResult>>;
 
+    /// Lists tags w/ document counts for filter chips & `tag:` autocomplete.
+    /// Optionally filtered to one label (e.g. "lens") and/or a
+    /// case-insensitive value prefix.
+    #[method(name = "list_tags")]
+    async fn list_tags(
+        &self,
+        label_filter: Option<String>,
+        prefix: Option<String>,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<TagCountResult>>;
+
     #[method(name = "recrawl_domain")]
     async fn recrawl_domain(&self, domain: String) -> RpcResult<()>;
 
+    /// Immediately requeues a lens' documents for a recrawl & resets its
+    /// refresh timer.
+    #[method(name = "refresh_lens")]
+    async fn refresh_lens(&self, name: String) -> RpcResult<()>;
+
+    /// Deletes an installed chat model's files. The currently active model
+    /// can't be removed.
+    #[method(name = "remove_llm_model")]
+    async fn remove_llm_model(&self, name: String) -> RpcResult<()>;
+
     #[method(name = "resync_connection")]
     async fn resync_connection(&self, id: String, account: String) -> RpcResult<()>;
 
     #[method(name = "revoke_connection")]
     async fn revoke_connection(&self, id: String, account: String) -> RpcResult<()>;
 
+    /// Fires an immediate `IntervalUpdate` for one plugin instead of waiting
+    /// for its next scheduled run.
+    #[method(name = "run_plugin_now")]
+    async fn run_plugin_now(&self, name: String) -> RpcResult<()>;
+
     #[method(name = "search_docs")]
     async fn search_docs(&self, query: SearchParam) -> RpcResult<SearchResults>;
 
     #[method(name = "search_lenses")]
     async fn search_lenses(&self, query: SearchLensesParam) -> RpcResult<SearchLensesResp>;
 
+    /// Switches the embedding model. Either a HuggingFace repo or a path to a
+    /// local model directory; an empty string switches back to the default
+    /// model. The model is downloaded if necessary & existing documents are
+    /// queued for a re-embed.
+    #[method(name = "set_embedding_model")]
+    async fn set_embedding_model(&self, name: String) -> RpcResult<()>;
+
+    /// Proposes a lens generated from the user's browsing history: domains
+    /// seen at least `min_visits` times (optionally only counting activity
+    /// after `since`), returned as RON text the client can edit & save into
+    /// the lens directory.
+    #[method(name = "suggest_lens")]
+    async fn suggest_lens(
+        &self,
+        min_visits: u64,
+        since: Option<DateTime<Utc>>,
+    ) -> RpcResult<SuggestedLensResult>;
+
+    /// Summarizes a document w/ the LLM, streaming the result as
+    /// `ChatStream` events. Summaries are cached per document & regenerated
+    /// when the indexed content changes. `max_words` caps the summary length
+    /// (default 150).
+    #[method(name = "summarize_document")]
+    async fn summarize_document(
+        &self,
+        doc_id: String,
+        max_words: Option<u64>,
+    ) -> RpcResult<String>;
+
     #[method(name = "update_user_settings")]
     async fn update_user_settings(&self, user_settings: UserSettings) -> RpcResult<UserSettings>;
 
     #[method(name = "user_settings")]
     async fn user_settings(&self) -> RpcResult<UserSettings>;
 
+    /// Enables or disables an installed lens w/o uninstalling it. Disabled
+    /// lenses keep their documents indexed but are excluded from default
+    /// searches & no longer crawled.
+    #[method(name = "toggle_lens")]
+    async fn toggle_lens(&self, name: String, enabled: bool) -> RpcResult<()>;
+
     #[method(name = "toggle_pause")]
     async fn toggle_pause(&self, is_paused: bool) -> RpcResult<()>;
 
+    /// Removes the favorite mark from a document.
+    #[method(name = "unfavorite_document")]
+    async fn unfavorite_document(&self, doc_id: String) -> RpcResult<()>;
+
     #[method(name = "uninstall_lens")]
     async fn uninstall_lens(&self, name: String) -> RpcResult<()>;
 
+    /// Adds &/or removes (label, value) tags on a document. Emits a
+    /// `DocumentUpdated` event once the document has been reindexed.
+    #[method(name = "update_document_tags")]
+    async fn update_document_tags(
+        &self,
+        doc_id: String,
+        add: Vec<(String, String)>,
+        remove: Vec<(String, String)>,
+    ) -> RpcResult<()>;
+
     #[subscription(name = "subscribe_events", item = RpcEvent)]
     async fn subscribe_events(&self, events: Vec<RpcEventType>) -> SubscriptionResult;
 }
@@ -0,0 +1,340 @@
+use anyhow::anyhow;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::config::RemoteIndexSettings;
+use std::time::Instant;
+use tantivy::Document;
+use url::Url;
+
+use crate::{
+    document_to_struct, Boost, DeleteQuery, QueryBoost, RetrievedDocument, SearchError,
+    SearchQueryResult, SearchTrait, SearcherResult, WriteTrait,
+};
+
+/// Client for a remote, Elasticsearch-compatible index (e.g. Quickwit) so a
+/// household can point several Spyglass installs at one shared index.
+/// Documents are stored w/ the same field names as the local schema so either
+/// backend can serve the same data.
+#[derive(Clone, Debug)]
+pub struct HttpSearcher {
+    client: reqwest::Client,
+    endpoint: Url,
+    index_name: String,
+    auth_token: Option<String>,
+}
+
+impl HttpSearcher {
+    /// Constructs a client from the user's remote index settings.
+    pub fn from_settings(settings: &RemoteIndexSettings) -> SearcherResult<Self> {
+        let url = settings
+            .url
+            .clone()
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| SearchError::Other(anyhow!("No remote index URL configured")))?;
+
+        let endpoint = Url::parse(&url)
+            .map_err(|err| SearchError::Other(anyhow!("Invalid remote index URL {url}: {err}")))?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            index_name: settings.index_name(),
+            auth_token: settings.auth_token.clone(),
+        })
+    }
+
+    fn api_url(&self, path: &str) -> SearcherResult<Url> {
+        let base = self.endpoint.as_str().trim_end_matches('/');
+        Url::parse(&format!("{base}/{path}")).map_err(|err| {
+            SearchError::Other(anyhow!("Invalid remote index request path {path}: {err}"))
+        })
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let mut request = self.client.request(method, url);
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+
+        request
+    }
+
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> SearcherResult<T> {
+        let resp = request
+            .send()
+            .await
+            .map_err(|err| anyhow!("Unable to reach remote index: {err}"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(SearchError::Other(anyhow!(
+                "Remote index returned {status}: {body}"
+            )));
+        }
+
+        resp.json::<T>()
+            .await
+            .map_err(|err| SearchError::Other(anyhow!("Unable to parse remote response: {err}")))
+    }
+
+    async fn search_request(&self, body: &serde_json::Value) -> SearcherResult<SearchResponse> {
+        let url = self.api_url(&format!("{}/_search", self.index_name))?;
+        self.execute(self.request(Method::POST, url).json(body))
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl SearchTrait for HttpSearcher {
+    /// Get a single document by id
+    async fn get(&self, doc_id: &str) -> Option<RetrievedDocument> {
+        let body = json!({
+            "query": { "term": { "doc_id": doc_id } },
+            "size": 1,
+        });
+
+        match self.search_request(&body).await {
+            Ok(resp) => resp
+                .hits
+                .hits
+                .into_iter()
+                .next()
+                .map(|hit| hit.source.into()),
+            Err(err) => {
+                log::error!("Unable to fetch {doc_id} from remote index: {err}");
+                None
+            }
+        }
+    }
+
+    /// Runs a search against the remote index
+    async fn search(
+        &self,
+        query_string: &str,
+        filters: &[QueryBoost],
+        _boosts: &[QueryBoost],
+        num_results: usize,
+        offset: usize,
+    ) -> SearchQueryResult {
+        let start = Instant::now();
+
+        // Only exact-match filters translate to the remote query DSL; score
+        // boosts are left to the server's relevance model.
+        let mut filter = Vec::new();
+        let mut must_not = Vec::new();
+        for item in filters {
+            match &item.field {
+                Boost::Tag(tag_id) => filter.push(json!({ "term": { "tags": tag_id } })),
+                Boost::AnyTag(tag_ids) => filter.push(json!({ "terms": { "tags": tag_ids } })),
+                Boost::ExcludeTag(tag_ids) => {
+                    must_not.push(json!({ "terms": { "tags": tag_ids } }))
+                }
+                Boost::DocId(doc_id) => filter.push(json!({ "term": { "doc_id": doc_id } })),
+                Boost::Url(url) => filter.push(json!({ "term": { "url": url } })),
+                _ => {}
+            }
+        }
+
+        let body = json!({
+            "query": {
+                "bool": {
+                    "must": [{
+                        "query_string": {
+                            "query": query_string,
+                            "fields": ["title^2", "content"],
+                        }
+                    }],
+                    "filter": filter,
+                    "must_not": must_not,
+                }
+            },
+            "from": offset,
+            "size": num_results,
+        });
+
+        let empty = SearchQueryResult {
+            wall_time_ms: 0,
+            num_docs: 0,
+            term_counts: 0,
+            documents: Vec::new(),
+            total_hits: 0,
+            facets: None,
+        };
+
+        match self.search_request(&body).await {
+            Ok(resp) => {
+                let documents = resp
+                    .hits
+                    .hits
+                    .into_iter()
+                    .map(|hit| (hit.score.unwrap_or(1.0), hit.source.into()))
+                    .collect::<Vec<_>>();
+
+                SearchQueryResult {
+                    wall_time_ms: start.elapsed().as_millis(),
+                    num_docs: resp.hits.total.value as u64,
+                    term_counts: 0,
+                    documents,
+                    total_hits: resp.hits.total.value,
+                    facets: None,
+                }
+            }
+            Err(err) => {
+                log::error!("Unable to search remote index: {err}");
+                SearchQueryResult {
+                    wall_time_ms: start.elapsed().as_millis(),
+                    ..empty
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl WriteTrait for HttpSearcher {
+    async fn delete_many_by_id(&self, doc_ids: &[String]) -> SearcherResult<usize> {
+        if doc_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let body = json!({ "query": { "terms": { "doc_id": doc_ids } } });
+        let url = self.api_url(&format!("{}/_delete_by_query", self.index_name))?;
+        let _resp: serde_json::Value = self
+            .execute(self.request(Method::POST, url).json(&body))
+            .await?;
+
+        Ok(doc_ids.len())
+    }
+
+    async fn delete_by_query(&self, query: DeleteQuery) -> SearcherResult<usize> {
+        let body = match &query {
+            DeleteQuery::Domain(domain) => json!({ "query": { "term": { "domain": domain } } }),
+            DeleteQuery::Tag(tag_id) => json!({ "query": { "term": { "tags": tag_id } } }),
+        };
+
+        let url = self.api_url(&format!("{}/_delete_by_query", self.index_name))?;
+        let resp: serde_json::Value = self
+            .execute(self.request(Method::POST, url).json(&body))
+            .await?;
+
+        Ok(resp
+            .get("deleted")
+            .and_then(|value| value.as_u64())
+            .unwrap_or_default() as usize)
+    }
+
+    async fn upsert_many(&self, updates: &[Document]) -> SearcherResult<Vec<String>> {
+        let mut payload = String::new();
+        let mut upserted = Vec::new();
+
+        // NDJSON bulk format: an action line followed by the document source.
+        for doc in updates {
+            let Some(retrieved) = document_to_struct(doc) else {
+                continue;
+            };
+
+            let stored: StoredDocument = retrieved.into();
+            let action = json!({ "index": { "_index": &self.index_name, "_id": &stored.doc_id } });
+            payload.push_str(&serde_json::to_string(&action).unwrap_or_default());
+            payload.push('\n');
+            payload.push_str(&serde_json::to_string(&stored).unwrap_or_default());
+            payload.push('\n');
+
+            upserted.push(stored.doc_id);
+        }
+
+        if upserted.is_empty() {
+            return Ok(upserted);
+        }
+
+        let url = self.api_url("_bulk")?;
+        let _resp: serde_json::Value = self
+            .execute(
+                self.request(Method::POST, url)
+                    .header(CONTENT_TYPE, "application/x-ndjson")
+                    .body(payload),
+            )
+            .await?;
+
+        Ok(upserted)
+    }
+}
+
+/// Wire format for documents in the remote index. Matches the local schema's
+/// field names.
+#[derive(Debug, Deserialize, Serialize)]
+struct StoredDocument {
+    doc_id: String,
+    domain: String,
+    title: String,
+    description: String,
+    content: String,
+    url: String,
+    #[serde(default)]
+    tags: Vec<u64>,
+}
+
+impl From<RetrievedDocument> for StoredDocument {
+    fn from(doc: RetrievedDocument) -> Self {
+        StoredDocument {
+            doc_id: doc.doc_id,
+            domain: doc.domain,
+            title: doc.title,
+            description: doc.description,
+            content: doc.content,
+            url: doc.url,
+            tags: doc.tags,
+        }
+    }
+}
+
+impl From<StoredDocument> for RetrievedDocument {
+    fn from(doc: StoredDocument) -> Self {
+        RetrievedDocument {
+            doc_id: doc.doc_id,
+            domain: doc.domain,
+            title: doc.title,
+            description: doc.description,
+            content: doc.content,
+            url: doc.url,
+            tags: doc.tags,
+            // The remote index doesn't store dates (yet).
+            published_at: None,
+            last_modified: None,
+            snippet: String::new(),
+            highlights: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    hits: SearchHits,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchHits {
+    #[serde(default)]
+    total: TotalHits,
+    #[serde(default)]
+    hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TotalHits {
+    value: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchHit {
+    #[serde(rename = "_score")]
+    score: Option<f32>,
+    #[serde(rename = "_source")]
+    source: StoredDocument,
+}
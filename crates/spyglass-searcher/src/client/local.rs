@@ -1,29 +1,168 @@
+use std::collections::HashMap;
 use std::fmt::{Debug, Error, Formatter};
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use tantivy::collector::TopDocs;
+use tantivy::collector::{Collector, Count, SegmentCollector, TopDocs};
+use tantivy::fastfield::MultiValuedFastFieldReader;
 use tantivy::directory::error::LockError;
-use tantivy::query::TermQuery;
+use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+use tantivy::SnippetGenerator;
 use tantivy::{schema::*, TantivyError};
 use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy};
 use uuid::Uuid;
 
-use crate::query::{build_document_query, build_query, terms_for_field, QueryOptions};
+use crate::query::{
+    build_document_query, build_query, parse_synonyms, terms_for_field, QueryOptions, SynonymMap,
+};
 use crate::schema::{self, DocFields, SearchDocument};
 use crate::{
-    document_to_struct, field_to_string, Boost, IndexBackend, QueryBoost, RetrievedDocument, Score,
-    SearchError, SearchQueryResult, SearchTrait, SearcherResult, WriteTrait,
+    document_to_struct, field_to_string, Boost, DeleteQuery, IndexBackend, QueryBoost,
+    RetrievedDocument, Score, ScoreExplanation, SearchError, SearchOptions, SearchQueryResult,
+    SearchTrait, SearcherResult, SortMode, WriteTrait,
 };
 
 pub const SPYGLASS_NS: Uuid = uuid::uuid!("5fdfe40a-de2c-11ed-bfa7-00155deae876");
 
+/// Exponential decay on document age: 1.0 for a doc modified just now,
+/// halving every `half_life_days`. Floored so very old & undated docs are
+/// demoted rather than disappearing entirely.
+fn recency_decay(now_micros: i64, timestamp_micros: i64, half_life_days: f32) -> f32 {
+    const FLOOR: f32 = 0.01;
+    if timestamp_micros <= 0 || half_life_days <= 0.0 {
+        return FLOOR;
+    }
+
+    let age_days =
+        now_micros.saturating_sub(timestamp_micros) as f32 / (86_400.0 * 1_000_000.0);
+    (0.5_f32).powf(age_days.max(0.0) / half_life_days).max(FLOOR)
+}
+
+/// Edit distance between two terms, bounded by `max`. Returns None when the
+/// distance exceeds the bound so the caller can skip the candidate early.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = usize::from(a_char != b_char);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+            row_min = row_min.min(current[j + 1]);
+        }
+
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    (prev[b.len()] <= max).then_some(prev[b.len()])
+}
+
+/// Counts matching documents per tag id using the multi-valued tags fast
+/// field.
+struct TagFacetCollector {
+    field: Field,
+}
+
+impl Collector for TagFacetCollector {
+    type Fruit = HashMap<u64, u64>;
+    type Child = TagFacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: u32,
+        segment: &tantivy::SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(TagFacetSegmentCollector {
+            reader: segment.fast_fields().u64s(self.field)?,
+            counts: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> tantivy::Result<Self::Fruit> {
+        let mut merged: HashMap<u64, u64> = HashMap::new();
+        for fruit in segment_fruits {
+            for (tag_id, count) in fruit {
+                *merged.entry(tag_id).or_insert(0) += count;
+            }
+        }
+        Ok(merged)
+    }
+}
+
+struct TagFacetSegmentCollector {
+    reader: MultiValuedFastFieldReader<u64>,
+    counts: HashMap<u64, u64>,
+}
+
+impl SegmentCollector for TagFacetSegmentCollector {
+    type Fruit = HashMap<u64, u64>;
+
+    fn collect(&mut self, doc: tantivy::DocId, _score: tantivy::Score) {
+        let mut tag_ids = Vec::new();
+        self.reader.get_vals(doc, &mut tag_ids);
+        for tag_id in tag_ids {
+            *self.counts.entry(tag_id).or_insert(0) += 1;
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.counts
+    }
+}
+
+/// Controls when buffered writes are committed, see
+/// [`Searcher::enable_write_buffering`].
+#[derive(Clone, Debug)]
+pub struct WriteBufferSettings {
+    /// Commit after this many documents have been buffered.
+    pub max_docs: usize,
+    /// Commit when this much time has passed since the last commit, however
+    /// few documents are buffered.
+    pub max_age: Duration,
+}
+
+impl Default for WriteBufferSettings {
+    fn default() -> Self {
+        WriteBufferSettings {
+            max_docs: 10_000,
+            max_age: Duration::from_secs(30),
+        }
+    }
+}
+
+struct WriteBufferState {
+    settings: WriteBufferSettings,
+    /// Documents upserted since the last commit.
+    buffered: usize,
+    last_commit: Instant,
+}
+
 /// Tantivy searcher client
 #[derive(Clone)]
 pub struct Searcher {
     pub index: Index,
     pub reader: IndexReader,
     pub writer: Option<Arc<Mutex<IndexWriter>>>,
+    /// User-defined synonym groups, expanded at query time. Shared across
+    /// clones so a reload is picked up everywhere.
+    pub synonyms: Arc<Mutex<SynonymMap>>,
+    /// When set, upserts accumulate & commits only happen once enough
+    /// documents or time has built up. See [`Searcher::enable_write_buffering`].
+    write_buffer: Arc<Mutex<Option<WriteBufferState>>>,
 }
 
 impl Debug for Searcher {
@@ -49,6 +188,28 @@ impl WriteTrait for Searcher {
         Ok(doc_ids.len())
     }
 
+    async fn delete_by_query(&self, query: DeleteQuery) -> SearcherResult<usize> {
+        let fields = DocFields::as_fields();
+        let term = match &query {
+            DeleteQuery::Domain(domain) => Term::from_field_text(fields.domain, domain),
+            DeleteQuery::Tag(tag_id) => Term::from_field_u64(fields.tags, *tag_id),
+        };
+
+        // Count the matches first so we can report how many were removed;
+        // delete_term itself doesn't tell us.
+        let searcher = self.reader.searcher();
+        let term_query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+        let num_docs = searcher.search(&term_query, &Count).unwrap_or_default();
+
+        {
+            let writer = self.lock_writer()?;
+            writer.delete_term(term);
+        }
+
+        self.save().await?;
+        Ok(num_docs)
+    }
+
     async fn upsert_many(&self, updates: &[Document]) -> SearcherResult<Vec<String>> {
         let mut upserted = Vec::new();
         let fields = DocFields::as_fields();
@@ -61,6 +222,7 @@ impl WriteTrait for Searcher {
             upserted.push(doc_id.clone());
         }
 
+        self.maybe_commit(updates.len()).await?;
         Ok(upserted)
     }
 }
@@ -102,6 +264,30 @@ impl SearchTrait for Searcher {
         boosts: &[QueryBoost],
         num_results: usize,
         offset: usize,
+    ) -> SearchQueryResult {
+        self.search_with_options(
+            query_string,
+            filters,
+            boosts,
+            num_results,
+            offset,
+            SearchOptions::default(),
+        )
+        .await
+    }
+}
+
+impl Searcher {
+    /// Runs a search against the index w/ the provided options (fuzzy
+    /// matching, result ordering).
+    pub async fn search_with_options(
+        &self,
+        query_string: &str,
+        filters: &[QueryBoost],
+        boosts: &[QueryBoost],
+        num_results: usize,
+        offset: usize,
+        options: SearchOptions,
     ) -> SearchQueryResult {
         let start_timer = Instant::now();
 
@@ -109,19 +295,105 @@ impl SearchTrait for Searcher {
         let reader = &self.reader;
         let searcher = reader.searcher();
 
-        let (term_counts, query) = build_query(
-            index,
-            query_string,
-            filters,
-            boosts,
-            QueryOptions::default(),
-        );
-
-        let collector = TopDocs::with_limit(num_results).and_offset(offset);
+        let mut opts = if options.use_fuzzy {
+            QueryOptions::with_fuzzy()
+        } else {
+            QueryOptions::default()
+        };
+        if options.is_partial {
+            opts = opts.partial_matching();
+        }
+        // Expand query terms into any user-defined synonym groups.
+        if let Ok(synonyms) = self.synonyms.lock() {
+            if !synonyms.is_empty() {
+                opts = opts.with_synonyms(synonyms.clone());
+            }
+        }
+        let (term_counts, query) = build_query(index, query_string, filters, boosts, opts);
 
-        let top_docs = searcher
-            .search(&query, &collector)
-            .expect("Unable to execute query");
+        let fields = DocFields::as_fields();
+        // Opt-in recency decay, applied on top of BM25 while sorting by
+        // relevance.
+        let recency_half_life = boosts.iter().find_map(|boost| match &boost.field {
+            Boost::Recency { half_life_days } => Some(*half_life_days),
+            _ => None,
+        });
+
+        let (top_docs, total_hits) = match options.sort {
+            SortMode::Relevance if recency_half_life.is_some() => {
+                let half_life_days = recency_half_life.unwrap_or_default();
+                let modified_field = fields.lastmodified;
+                let now = chrono::Utc::now().timestamp_micros();
+
+                let collector = TopDocs::with_limit(num_results).and_offset(offset).tweak_score(
+                    move |segment_reader: &tantivy::SegmentReader| {
+                        let modified = segment_reader.fast_fields().date(modified_field).ok();
+                        move |doc_id: tantivy::DocId, original_score: Score| {
+                            let timestamp = modified
+                                .as_ref()
+                                .map(|reader| reader.get_val(doc_id).into_timestamp_micros())
+                                .unwrap_or(0);
+
+                            original_score * recency_decay(now, timestamp, half_life_days)
+                        }
+                    },
+                );
+
+                searcher
+                    .search(&query, &(collector, Count))
+                    .expect("Unable to execute query")
+            }
+            SortMode::Relevance => {
+                let collector = TopDocs::with_limit(num_results).and_offset(offset);
+                searcher
+                    .search(&query, &(collector, Count))
+                    .expect("Unable to execute query")
+            }
+            SortMode::NewestFirst | SortMode::OldestFirst => {
+                let ascending = options.sort == SortMode::OldestFirst;
+                let published_field = fields.published;
+                let modified_field = fields.lastmodified;
+
+                let collector = TopDocs::with_limit(num_results).and_offset(offset).custom_score(
+                    move |segment_reader: &tantivy::SegmentReader| {
+                        let published = segment_reader.fast_fields().date(published_field).ok();
+                        let modified = segment_reader.fast_fields().date(modified_field).ok();
+                        move |doc_id: tantivy::DocId| {
+                            let mut timestamp = published
+                                .as_ref()
+                                .map(|reader| reader.get_val(doc_id).into_timestamp_micros())
+                                .unwrap_or(0);
+                            if timestamp == 0 {
+                                timestamp = modified
+                                    .as_ref()
+                                    .map(|reader| reader.get_val(doc_id).into_timestamp_micros())
+                                    .unwrap_or(0);
+                            }
+
+                            // Docs w/o either date always sort last.
+                            if timestamp == 0 {
+                                i64::MIN
+                            } else if ascending {
+                                -timestamp
+                            } else {
+                                timestamp
+                            }
+                        }
+                    },
+                );
+
+                let (sorted, count) = searcher
+                    .search(&query, &(collector, Count))
+                    .expect("Unable to execute query");
+                // Relevance scores don't apply here, but keep the tuple shape
+                // the rest of the pipeline expects.
+                let sorted = sorted
+                    .into_iter()
+                    .map(|(_, addr)| (1.0_f32, addr))
+                    .collect::<Vec<_>>();
+                (sorted, count)
+            }
+        };
 
         log::debug!(
             "query `{}` returned {} results from {} docs in {} ms",
@@ -131,17 +403,42 @@ impl SearchTrait for Searcher {
             Instant::now().duration_since(start_timer).as_millis()
         );
 
+        // Optional second collection pass to count matching docs per tag id.
+        let facets = if options.return_facets {
+            searcher
+                .search(&query, &TagFacetCollector { field: fields.tags })
+                .ok()
+        } else {
+            None
+        };
+
+        // Used to generate a snippet w/ highlights around the matched terms
+        // for each result.
+        let snippet_generator = SnippetGenerator::create(&searcher, &query, fields.content).ok();
+
         let doc_reader = self.reader.searcher();
         let docs = top_docs
             .into_iter()
             // Filter out negative scores
             .filter(|(score, _)| *score > 0.0)
             .flat_map(|(score, addr)| {
-                if let Ok(Some(doc)) = doc_reader.doc(addr).map(|x| document_to_struct(&x)) {
-                    Some((score, doc))
-                } else {
-                    None
+                if let Ok(raw_doc) = doc_reader.doc(addr) {
+                    if let Some(mut doc) = document_to_struct(&raw_doc) {
+                        if let Some(generator) = &snippet_generator {
+                            let snippet = generator.snippet_from_doc(&raw_doc);
+                            doc.highlights = snippet
+                                .highlighted()
+                                .iter()
+                                .map(|range| (range.start, range.end))
+                                .collect();
+                            doc.snippet = snippet.fragment().to_string();
+                        }
+
+                        return Some((score, doc));
+                    }
                 }
+
+                None
             })
             .collect();
 
@@ -150,15 +447,30 @@ impl SearchTrait for Searcher {
             num_docs: searcher.num_docs(),
             term_counts,
             documents: docs,
+            total_hits,
+            facets,
         }
     }
-}
 
-impl Searcher {
     pub fn is_readonly(&self) -> bool {
         self.writer.is_none()
     }
 
+    /// Re-register the tokenizers w/ the given stop word overrides. Affects
+    /// both newly indexed documents & queries from this point on.
+    pub fn reload_stop_words(&self, overrides: Option<&str>) {
+        schema::register_tokenizers_with_stopwords(&self.index, overrides);
+    }
+
+    /// Replaces the user-defined synonym groups used for query expansion,
+    /// e.g. after the `synonyms.ron` file changes.
+    pub fn reload_synonyms(&self, raw: Option<&str>) {
+        let parsed = raw.map(parse_synonyms).unwrap_or_default();
+        if let Ok(mut synonyms) = self.synonyms.lock() {
+            *synonyms = parsed;
+        }
+    }
+
     pub fn lock_writer(&self) -> SearcherResult<MutexGuard<IndexWriter>> {
         if let Some(index) = &self.writer {
             match index.lock() {
@@ -171,11 +483,111 @@ impl Searcher {
     }
 
     pub async fn save(&self) -> SearcherResult<()> {
+        // An explicit commit empties the write buffer too.
+        if let Ok(mut buffer) = self.write_buffer.lock() {
+            if let Some(state) = buffer.as_mut() {
+                state.buffered = 0;
+                state.last_commit = Instant::now();
+            }
+        }
+
         let mut writer = self.lock_writer()?;
         writer.commit()?;
         Ok(())
     }
 
+    /// Buffer index commits: upserts accumulate & a commit only happens once
+    /// `max_docs` documents or `max_age` time has built up. Committing every
+    /// small batch during a large import produces a pile of tiny segments
+    /// that all have to be merged again later; buffering writes ~200k doc
+    /// archive loads spend most of their time committing otherwise.
+    pub fn enable_write_buffering(&self, settings: WriteBufferSettings) {
+        if let Ok(mut buffer) = self.write_buffer.lock() {
+            *buffer = Some(WriteBufferState {
+                settings,
+                buffered: 0,
+                last_commit: Instant::now(),
+            });
+        }
+    }
+
+    /// Turn off write buffering. Doesn't commit; use [`Searcher::flush`]
+    /// first if buffered documents need to be visible.
+    pub fn disable_write_buffering(&self) {
+        if let Ok(mut buffer) = self.write_buffer.lock() {
+            *buffer = None;
+        }
+    }
+
+    pub fn is_write_buffered(&self) -> bool {
+        self.write_buffer
+            .lock()
+            .map(|buffer| buffer.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Commits any buffered writes. Used at the end of an import & before
+    /// searches that need to see documents that may still be buffered.
+    pub async fn flush(&self) -> SearcherResult<()> {
+        let dirty = self
+            .write_buffer
+            .lock()
+            .map(|buffer| {
+                buffer
+                    .as_ref()
+                    .map(|state| state.buffered > 0)
+                    // Without a buffer there's no bookkeeping; commit to be
+                    // safe.
+                    .unwrap_or(true)
+            })
+            .unwrap_or(true);
+
+        if dirty {
+            self.save().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Tracks `added` documents against the write buffer & commits when a
+    /// threshold is hit. No-op when buffering is off: callers own the commit.
+    async fn maybe_commit(&self, added: usize) -> SearcherResult<()> {
+        let should_commit = match self.write_buffer.lock() {
+            Ok(mut buffer) => match buffer.as_mut() {
+                Some(state) => {
+                    state.buffered += added;
+                    state.buffered >= state.settings.max_docs
+                        || state.last_commit.elapsed() >= state.settings.max_age
+                }
+                None => false,
+            },
+            Err(_) => false,
+        };
+
+        if should_commit {
+            self.save().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Merge all searchable segments down to a single one & garbage collect
+    /// files no longer referenced by the index. Returns the segment counts
+    /// before & after the merge.
+    pub async fn optimize(&self) -> SearcherResult<(usize, usize)> {
+        let before = self.index.searchable_segment_ids()?;
+        {
+            let mut writer = self.lock_writer()?;
+            if before.len() > 1 {
+                writer.merge(&before).wait()?;
+            }
+            writer.garbage_collect_files().wait()?;
+        }
+
+        let after = self.index.searchable_segment_ids()?.len();
+        Ok((before.len(), after))
+    }
+
     /// Constructs a new Searcher object w/ the index @ `index_path`
     pub fn with_index(
         index_path: &IndexBackend,
@@ -185,7 +597,12 @@ impl Searcher {
         let index = match index_path {
             IndexBackend::LocalPath(path) => schema::initialize_index(schema, path)?,
             IndexBackend::Memory => schema::initialize_in_memory_index(schema),
-            IndexBackend::Http(_) => unimplemented!(""),
+            IndexBackend::Http(_) => {
+                // Remote indexes are handled by `HttpSearcher`, see client::http.
+                return Err(SearchError::Other(anyhow::anyhow!(
+                    "IndexBackend::Http is not backed by a local index, use HttpSearcher"
+                )));
+            }
         };
 
         // Should only be one writer at a time. This single IndexWriter is already
@@ -214,6 +631,8 @@ impl Searcher {
             index,
             reader,
             writer,
+            synonyms: Arc::new(Mutex::new(SynonymMap::new())),
+            write_buffer: Arc::new(Mutex::new(None)),
         })
     }
 
@@ -258,24 +677,54 @@ impl Searcher {
         query_string: &str,
         boosts: &[QueryBoost],
     ) -> Option<f32> {
+        self.explain_search(doc_id, query_string, boosts)
+            .await
+            .map(|explanation| explanation.score)
+    }
+
+    /// Scores a single document against `query_string` & breaks the result
+    /// down per field so ranking oddities can be diagnosed.
+    pub async fn explain_search(
+        &self,
+        doc_id: String,
+        query_string: &str,
+        boosts: &[QueryBoost],
+    ) -> Option<ScoreExplanation> {
         let index = &self.index;
         let reader = &self.reader;
         let fields = DocFields::as_fields();
 
         let tantivy_searcher = reader.searcher();
         let filters = vec![QueryBoost::new(Boost::DocId(doc_id.clone()))];
-        let (_, final_query) = build_query(
-            &self.index,
-            query_string,
-            &filters,
-            boosts,
-            QueryOptions::default(),
-        );
 
         let tokenizers = index.tokenizers();
+
+        // Apply the user's synonym groups the same way a normal search would
+        // & keep a human readable record of the expansion for the output.
+        let mut opts = QueryOptions::default();
+        let mut expanded = Vec::new();
+        if let Ok(synonyms) = self.synonyms.lock() {
+            if !synonyms.is_empty() {
+                for word in query_string.split_whitespace() {
+                    let word = word.to_lowercase();
+                    if let Some(group) = synonyms.get(&word) {
+                        for synonym in group {
+                            expanded.push(format!("{word} -> {synonym}"));
+                        }
+                    }
+                }
+                opts = opts.with_synonyms(synonyms.clone());
+            }
+        }
+
+        let (_, final_query) = build_query(&self.index, query_string, &filters, boosts, opts);
+
         let content_terms =
             terms_for_field(&index.schema(), tokenizers, query_string, fields.content);
         log::info!("Content Tokens {:?}", content_terms);
+        if !expanded.is_empty() {
+            log::info!("Synonym expansions {:?}", expanded);
+        }
 
         let collector = tantivy::collector::TopDocs::with_limit(1);
         let docs = tantivy_searcher
@@ -285,7 +734,7 @@ impl Searcher {
         for (score, addr) in docs {
             if let Ok(Some(result)) = tantivy_searcher.doc(addr).map(|x| document_to_struct(&x)) {
                 if result.doc_id == doc_id {
-                    for t in content_terms {
+                    for t in &content_terms {
                         let info = tantivy_searcher
                             .segment_reader(addr.segment_ord)
                             .inverted_index(fields.content)
@@ -294,10 +743,196 @@ impl Searcher {
                         log::info!("Term {:?} Info {:?} ", t, info);
                     }
 
-                    return Some(score);
+                    // Score each field's terms on their own to see where the
+                    // final score came from. The id clause pins the document
+                    // but is zero-boosted so it doesn't contribute.
+                    let mut field_scores = Vec::new();
+                    for (name, field) in [
+                        ("title", fields.title),
+                        ("content", fields.content),
+                        ("description", fields.description),
+                    ] {
+                        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(
+                            Occur::Must,
+                            Box::new(BoostQuery::new(
+                                Box::new(TermQuery::new(
+                                    Term::from_field_text(fields.id, &doc_id),
+                                    IndexRecordOption::Basic,
+                                )),
+                                0.0,
+                            )),
+                        )];
+
+                        for (_, term) in
+                            terms_for_field(&index.schema(), tokenizers, query_string, field)
+                        {
+                            clauses.push((
+                                Occur::Should,
+                                Box::new(TermQuery::new(
+                                    term,
+                                    IndexRecordOption::WithFreqsAndPositions,
+                                )),
+                            ));
+                        }
+
+                        let contribution = tantivy_searcher
+                            .search(&BooleanQuery::new(clauses), &TopDocs::with_limit(1))
+                            .ok()
+                            .and_then(|hits| hits.first().map(|(score, _)| *score))
+                            .unwrap_or(0.0);
+                        field_scores.push((name.to_string(), contribution));
+                    }
+
+                    // Surface the recency decay that would be applied to this
+                    // score during a normal search.
+                    let mut recency = None;
+                    if let Some(half_life_days) = boosts.iter().find_map(|b| match &b.field {
+                        Boost::Recency { half_life_days } => Some(*half_life_days),
+                        _ => None,
+                    }) {
+                        if let Ok(modified) = tantivy_searcher
+                            .segment_reader(addr.segment_ord)
+                            .fast_fields()
+                            .date(fields.lastmodified)
+                        {
+                            let timestamp = modified.get_val(addr.doc_id).into_timestamp_micros();
+                            let decay = recency_decay(
+                                chrono::Utc::now().timestamp_micros(),
+                                timestamp,
+                                half_life_days,
+                            );
+                            log::info!(
+                                "Recency decay ({half_life_days}d half-life): {decay} -> {}",
+                                score * decay
+                            );
+                            recency = Some(decay);
+                        }
+                    }
+
+                    return Some(ScoreExplanation {
+                        score: score * recency.unwrap_or(1.0),
+                        field_scores,
+                        recency_decay: recency,
+                        synonyms: expanded,
+                    });
                 }
             }
         }
         None
     }
+
+    /// Suggests alternate queries for terms that don't occur in the index,
+    /// e.g. "did you mean frankenstein?" for "frankenstien". Corrections come
+    /// from the index's own term dictionaries so a suggested term is
+    /// guaranteed to have at least one match.
+    pub fn spelling_suggestions(&self, query: &str) -> Vec<String> {
+        const MAX_SUGGESTIONS: usize = 3;
+
+        let fields = DocFields::as_fields();
+        let tokenizer = match self.index.tokenizer_for_field(fields.content) {
+            Ok(tokenizer) => tokenizer,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut tokens: Vec<String> = Vec::new();
+        let mut stream = tokenizer.token_stream(query);
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+
+        let searcher = self.reader.searcher();
+        // Candidates per misspelled token, ranked by edit distance first &
+        // document frequency second.
+        let mut corrections: Vec<(String, Vec<String>)> = Vec::new();
+        for token in &tokens {
+            let known = [fields.title, fields.content].iter().any(|field| {
+                searcher
+                    .doc_freq(&Term::from_field_text(*field, token))
+                    .unwrap_or(0)
+                    > 0
+            });
+            if known {
+                continue;
+            }
+
+            // Shorter terms get a tighter bound so we don't "correct" them
+            // into something completely different.
+            let max_distance = if token.chars().count() <= 4 { 1 } else { 2 };
+            let mut candidates: HashMap<String, (usize, u64)> = HashMap::new();
+            for segment in searcher.segment_readers() {
+                for field in [fields.title, fields.content] {
+                    let inverted = match segment.inverted_index(field) {
+                        Ok(inverted) => inverted,
+                        Err(_) => continue,
+                    };
+
+                    let terms = inverted.terms();
+                    let mut stream = match terms.stream() {
+                        Ok(stream) => stream,
+                        Err(_) => continue,
+                    };
+
+                    while stream.advance() {
+                        let term = match std::str::from_utf8(stream.key()) {
+                            Ok(term) => term,
+                            Err(_) => continue,
+                        };
+
+                        if let Some(distance) = edit_distance(token, term, max_distance) {
+                            let doc_freq = stream.value().doc_freq as u64;
+                            let entry = candidates.entry(term.to_string()).or_insert((distance, 0));
+                            entry.1 += doc_freq;
+                        }
+                    }
+                }
+            }
+
+            let mut ranked = candidates.into_iter().collect::<Vec<_>>();
+            ranked.sort_by(|(a_term, (a_dist, a_freq)), (b_term, (b_dist, b_freq))| {
+                a_dist
+                    .cmp(b_dist)
+                    .then(b_freq.cmp(a_freq))
+                    .then(a_term.cmp(b_term))
+            });
+
+            corrections.push((
+                token.clone(),
+                ranked
+                    .into_iter()
+                    .take(MAX_SUGGESTIONS)
+                    .map(|(term, _)| term)
+                    .collect(),
+            ));
+        }
+
+        if corrections.is_empty() || corrections.iter().all(|(_, terms)| terms.is_empty()) {
+            return Vec::new();
+        }
+
+        // Build full queries so a suggestion can be rerun as-is: every
+        // misspelled token is swapped for its best candidate & alternates
+        // come from varying the first misspelled token.
+        let base = query.to_lowercase();
+        let mut suggestions = Vec::new();
+        if let Some((first_token, first_candidates)) =
+            corrections.iter().find(|(_, terms)| !terms.is_empty())
+        {
+            for candidate in first_candidates {
+                let mut suggestion = base.replace(first_token.as_str(), candidate);
+                for (token, terms) in &corrections {
+                    if token != first_token {
+                        if let Some(best) = terms.first() {
+                            suggestion = suggestion.replace(token.as_str(), best);
+                        }
+                    }
+                }
+
+                if suggestion != base && !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+
+        suggestions
+    }
 }
@@ -1,2 +1,4 @@
+mod http;
 mod local;
+pub use self::http::*;
 pub use self::local::*;
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::path::PathBuf;
@@ -11,6 +12,7 @@ pub mod stop_word_filter;
 use schema::{DocFields, SearchDocument};
 
 mod query;
+pub use query::scoped_tag_values;
 pub mod similarity;
 pub mod utils;
 
@@ -40,8 +42,12 @@ impl QueryBoost {
             Boost::DocId(_) => 3.0,
             Boost::Favorite { .. } => 3.0,
             Boost::Tag(_) => 1.5,
+            Boost::AnyTag(_) => 1.5,
+            Boost::ExcludeTag(_) => 0.0,
             Boost::Url(_) => 3.0,
             Boost::CustomField { .. } => 0.0,
+            Boost::DateRange { .. } => 0.0,
+            Boost::Recency { .. } => 0.0,
         };
 
         QueryBoost {
@@ -61,11 +67,78 @@ impl QueryBoost {
 #[derive(Clone, Serialize, Deserialize)]
 pub enum Boost {
     // If required is set to true, _only_ favorites will be searched.
-    Favorite { id: u64, required: bool },
+    Favorite {
+        id: u64,
+        required: bool,
+    },
     Url(String),
     DocId(String),
     Tag(u64),
-    CustomField { field_name: String, value: u64 },
+    /// Documents matching _any_ of these tag ids. Used to OR together tags
+    /// that share a label (e.g. searching across multiple lenses at once)
+    /// while separate filters stay and'd.
+    AnyTag(Vec<u64>),
+    /// Documents tagged w/ _any_ of these tag ids are dropped from the
+    /// result set, e.g. lenses the user has disabled. Only considered in
+    /// filters.
+    ExcludeTag(Vec<u64>),
+    CustomField {
+        field_name: String,
+        value: u64,
+    },
+    /// Only documents w/ the date field inside the (inclusive) range. Only
+    /// considered in filters, open ends are unbounded.
+    DateRange {
+        field: DateField,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+    },
+    /// Multiply BM25 scores by an exponential decay on the last-modified
+    /// date so fresh documents rank above stale ones. Only considered in
+    /// boosts; applied by the collector rather than the query itself.
+    Recency { half_life_days: f32 },
+}
+
+/// Date fields that can be used in a `Boost::DateRange` filter.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DateField {
+    Published,
+    LastModified,
+}
+
+/// Fields that can be targeted by `WriteTrait::delete_by_query`.
+#[derive(Clone, Debug)]
+pub enum DeleteQuery {
+    Domain(String),
+    Tag(u64),
+}
+
+/// Options controlling how a search is executed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchOptions {
+    /// Tolerate typos in the query w/ fuzzy term matches.
+    pub use_fuzzy: bool,
+    /// How results should be ordered.
+    pub sort: SortMode,
+    /// Count matching documents per tag id. Adds a second collection pass,
+    /// so only enabled when the caller wants the counts.
+    pub return_facets: bool,
+    /// Treat the final token as a prefix (search-as-you-type). Set by the
+    /// searchbar while the user is still typing.
+    pub is_partial: bool,
+}
+
+/// Result ordering for a search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Order by relevance score (the default).
+    #[default]
+    Relevance,
+    /// Order by the published/last-modified date fields, newest first.
+    /// Documents without either date sort last.
+    NewestFirst,
+    /// As above, oldest first.
+    OldestFirst,
 }
 
 /// Contains stats & results for a search request
@@ -75,6 +148,12 @@ pub struct SearchQueryResult {
     pub num_docs: u64,
     pub term_counts: usize,
     pub documents: Vec<(Score, RetrievedDocument)>,
+    /// Total number of hits for the query, regardless of the number of
+    /// documents returned.
+    pub total_hits: usize,
+    /// Number of matching documents per tag id, when requested via
+    /// `SearchOptions::return_facets`.
+    pub facets: Option<std::collections::HashMap<u64, u64>>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -116,6 +195,11 @@ pub trait WriteTrait {
     /// Delete documents from the index by id, returning the number of docs deleted.
     async fn delete_many_by_id(&self, doc_ids: &[String]) -> SearcherResult<usize>;
 
+    /// Delete all documents matching `query` in a single pass, returning the
+    /// number of docs deleted. Much cheaper than enumerating doc ids when
+    /// removing a whole lens or domain.
+    async fn delete_by_query(&self, query: DeleteQuery) -> SearcherResult<usize>;
+
     async fn upsert(&self, single_doc: &Document) -> SearcherResult<String> {
         let upserted = self.upsert_many(&[single_doc.clone()]).await?;
         Ok(upserted.first().expect("Expected a single doc").to_owned())
@@ -127,6 +211,20 @@ pub trait WriteTrait {
 
 type SearcherResult<T> = Result<T, SearchError>;
 
+/// Breakdown of a single document's score for a query, returned by
+/// `Searcher::explain_search`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Final score, including boosts & any recency decay.
+    pub score: f32,
+    /// Raw BM25 contribution per field.
+    pub field_scores: Vec<(String, f32)>,
+    /// Recency decay multiplier, when a recency boost was requested.
+    pub recency_decay: Option<f32>,
+    /// Synonym expansions applied to the query ("term -> synonym").
+    pub synonyms: Vec<String>,
+}
+
 #[derive(Clone, Serialize, Debug)]
 pub struct RetrievedDocument {
     pub doc_id: String,
@@ -136,6 +234,15 @@ pub struct RetrievedDocument {
     pub content: String,
     pub url: String,
     pub tags: Vec<u64>,
+    /// When the document was published, if known.
+    pub published_at: Option<DateTime<Utc>>,
+    /// When the document was last modified, if known.
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Content snippet around the matched terms. Only filled in when the
+    /// document was retrieved as part of a search.
+    pub snippet: String,
+    /// Byte ranges of the matched terms within `snippet`.
+    pub highlights: Vec<(usize, usize)>,
 }
 
 // Helper method used to get the string value from a field
@@ -151,6 +258,14 @@ fn field_to_u64vec(doc: &Document, field: Field) -> Vec<u64> {
     doc.get_all(field).filter_map(|val| val.as_u64()).collect()
 }
 
+// Helper method used to get the date value from a field. Documents indexed
+// before dates were stored simply have no value here.
+fn field_to_datetime(doc: &Document, field: Field) -> Option<DateTime<Utc>> {
+    doc.get_first(field)
+        .and_then(|val| val.as_date())
+        .and_then(|date| DateTime::from_timestamp_micros(date.into_timestamp_micros()))
+}
+
 /// Helper method used to convert the provided document to a struct
 pub fn document_to_struct(doc: &Document) -> Option<RetrievedDocument> {
     let fields = DocFields::as_fields();
@@ -165,6 +280,8 @@ pub fn document_to_struct(doc: &Document) -> Option<RetrievedDocument> {
     let url = field_to_string(doc, fields.url);
     let content = field_to_string(doc, fields.content);
     let tags = field_to_u64vec(doc, fields.tags);
+    let published_at = field_to_datetime(doc, fields.published);
+    let last_modified = field_to_datetime(doc, fields.lastmodified);
 
     Some(RetrievedDocument {
         doc_id,
@@ -174,6 +291,10 @@ pub fn document_to_struct(doc: &Document) -> Option<RetrievedDocument> {
         content,
         url,
         tags,
+        published_at,
+        last_modified,
+        snippet: String::new(),
+        highlights: Vec::new(),
     })
 }
 
@@ -181,7 +302,10 @@ pub fn document_to_struct(doc: &Document) -> Option<RetrievedDocument> {
 mod test {
     use crate::client::Searcher;
     use crate::schema::{DocFields, DocumentUpdate, SearchDocument, ToDocument};
-    use crate::{Boost, IndexBackend, QueryBoost, SearchTrait, WriteTrait};
+    use crate::{
+        Boost, DateField, IndexBackend, QueryBoost, SearchOptions, SearchTrait, SortMode,
+        WriteTrait,
+    };
 
     async fn _build_test_index(searcher: &mut Searcher) {
         searcher
@@ -287,6 +411,13 @@ mod test {
         let filters = vec![QueryBoost::new(Boost::Tag(2_u64))];
         let results = searcher.search(query, &filters, &[], 5, 0).await;
         assert_eq!(results.documents.len(), 1);
+
+        // A snippet centered on the matched term should come back with the doc.
+        let doc = &results.documents[0].1;
+        assert!(doc.snippet.contains("Salinas"));
+        assert!(!doc.highlights.is_empty());
+        let (start, end) = doc.highlights[0];
+        assert_eq!(&doc.snippet[start..end], "Salinas");
     }
 
     #[tokio::test]
@@ -302,6 +433,624 @@ mod test {
         assert_eq!(results.documents.len(), 1);
     }
 
+    #[tokio::test]
+    pub async fn test_multi_lens_search() {
+        let searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+
+        for (url, tags) in [
+            ("https://example.com/lens_a", vec![1_i64]),
+            ("https://example.com/lens_b", vec![2_i64]),
+            ("https://example.com/both", vec![1_i64, 2_i64]),
+        ] {
+            searcher
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: None,
+                        title: "Stargazing",
+                        domain: "example.com",
+                        url,
+                        content: "Stargazing with a telescope on a clear night.",
+                        tags: &tags,
+                        published_at: None,
+                        last_modified: None,
+                    }
+                    .to_document(),
+                )
+                .await
+                .expect("Unable to add doc");
+        }
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // Individual tag filters are and'd together.
+        let filters = vec![
+            QueryBoost::new(Boost::Tag(1_u64)),
+            QueryBoost::new(Boost::Tag(2_u64)),
+        ];
+        let results = searcher.search("telescope", &filters, &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(results.documents[0].1.url, "https://example.com/both");
+
+        // Searching multiple lenses at once returns the union.
+        let filters = vec![QueryBoost::new(Boost::AnyTag(vec![1_u64, 2_u64]))];
+        let results = searcher.search("telescope", &filters, &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 3);
+    }
+
+    #[tokio::test]
+    pub async fn test_date_range_filter() {
+        use chrono::TimeZone;
+
+        let searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+
+        for (title, url, published_at) in [
+            (
+                "Old post",
+                "https://example.com/old_post",
+                chrono::Utc.with_ymd_and_hms(2019, 6, 1, 0, 0, 0).unwrap(),
+            ),
+            (
+                "New post",
+                "https://example.com/new_post",
+                chrono::Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            ),
+        ] {
+            searcher
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: None,
+                        title,
+                        domain: "example.com",
+                        url,
+                        content: "Stargazing with a telescope on a clear night.",
+                        tags: &[1_i64],
+                        published_at: Some(published_at),
+                        last_modified: None,
+                    }
+                    .to_document(),
+                )
+                .await
+                .expect("Unable to add doc");
+        }
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // No filter, both docs match.
+        let results = searcher.search("telescope", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 2);
+
+        // Stored dates round-trip back out on the retrieved documents.
+        for (_, doc) in &results.documents {
+            assert!(doc.published_at.is_some());
+            assert!(doc.last_modified.is_none());
+        }
+
+        // Docs outside the range are excluded, not just down-ranked.
+        let filters = vec![QueryBoost::new(Boost::DateRange {
+            field: DateField::Published,
+            start: Some(chrono::Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()),
+            end: None,
+        })];
+        let results = searcher.search("telescope", &filters, &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(results.documents[0].1.url, "https://example.com/new_post");
+
+        let filters = vec![QueryBoost::new(Boost::DateRange {
+            field: DateField::Published,
+            start: Some(chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            end: None,
+        })];
+        let results = searcher.search("telescope", &filters, &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 0);
+    }
+
+    #[tokio::test]
+    pub async fn test_language_tokenizer() {
+        use crate::schema::{schema_for_languages, tokenizer_name_for_languages, TOKENIZER_NAME};
+
+        // First recognized language picks the tokenizer, unknown codes fall
+        // back to English.
+        assert_eq!(
+            tokenizer_name_for_languages(&["de".to_string()]),
+            "spyglass_tokenizer_de"
+        );
+        assert_eq!(
+            tokenizer_name_for_languages(&["xx".to_string()]),
+            TOKENIZER_NAME
+        );
+
+        let searcher = Searcher::with_index(
+            &IndexBackend::Memory,
+            schema_for_languages(&["de".to_string()]),
+            false,
+        )
+        .expect("Unable to open index");
+
+        searcher
+            .upsert(
+                &DocumentUpdate {
+                    doc_id: None,
+                    title: "Kategorien",
+                    domain: "example.com",
+                    url: "https://example.com/kategorien",
+                    content: "Alle Kategorien auf einen Blick.",
+                    tags: &[1_i64],
+                    published_at: None,
+                    last_modified: None,
+                }
+                .to_document(),
+            )
+            .await
+            .expect("Unable to add doc");
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // "kategorie" only stems to the same root as "kategorien" w/ the
+        // German stemmer.
+        let results = searcher.search("kategorie", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+    }
+
+    #[tokio::test]
+    pub async fn test_facet_counts() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // Facets are pay-for-what-you-use, nothing comes back by default.
+        let results = searcher.search("salinas", &[], &[], 5, 0).await;
+        assert!(results.facets.is_none());
+
+        let options = SearchOptions {
+            return_facets: true,
+            ..Default::default()
+        };
+        let results = searcher
+            .search_with_options("salinas", &[], &[], 5, 0, options)
+            .await;
+        // One matching doc is tagged w/ 1, the other w/ 2.
+        let facets = results.facets.expect("No facets returned");
+        assert_eq!(facets.get(&1_u64), Some(&1_u64));
+        assert_eq!(facets.get(&2_u64), Some(&1_u64));
+    }
+
+    #[tokio::test]
+    pub async fn test_field_scoped_search() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // Scope a query to the title field.
+        let results = searcher.search("title:frankenstein", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/frankenstein"
+        );
+
+        // Restrict a term query to a single domain.
+        let results = searcher.search("salinas", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 2);
+        let results = searcher
+            .search("salinas domain:en.wikipedia.org", &[], &[], 5, 0)
+            .await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://en.wikipedia.org/mice_and_men"
+        );
+
+        // Url scopes match the exact stored url.
+        let results = searcher
+            .search("url:https://example.com/mice_and_men", &[], &[], 5, 0)
+            .await;
+        assert_eq!(results.documents.len(), 1);
+
+        // Unknown prefixes are treated as literal text & tokenized as usual.
+        let results = searcher.search("weird:salinas", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 2);
+    }
+
+    #[tokio::test]
+    pub async fn test_negated_search() {
+        let searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+
+        for (title, url, content) in [
+            (
+                "Rust async with tokio",
+                "https://example.com/rust_tokio",
+                "Async programming in rust using the tokio runtime and its error handling story.",
+            ),
+            (
+                "Rust threads",
+                "https://example.com/rust_threads",
+                "Concurrency in rust with plain threads, channels and error codes.",
+            ),
+        ] {
+            searcher
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: None,
+                        title,
+                        domain: "example.com",
+                        url,
+                        content,
+                        tags: &[1_i64],
+                        published_at: None,
+                        last_modified: None,
+                    }
+                    .to_document(),
+                )
+                .await
+                .expect("Unable to add doc");
+        }
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // Both docs match without the negation.
+        let results = searcher.search("rust", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 2);
+
+        // `-term` excludes the tokio doc even though it matches "rust".
+        let results = searcher.search("rust -tokio", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/rust_threads"
+        );
+
+        // Negated phrases only exclude the exact phrase; "error codes" in the
+        // other doc is untouched.
+        let results = searcher
+            .search("rust -\"error handling\"", &[], &[], 5, 0)
+            .await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/rust_threads"
+        );
+
+        // A negated stop word can never match the stop-word-free index, but
+        // it must not exclude everything or error out.
+        let results = searcher.search("rust -the", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 2);
+    }
+
+    #[tokio::test]
+    pub async fn test_sort_by_date() {
+        use chrono::TimeZone;
+
+        let searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+
+        for (title, url, published_at) in [
+            (
+                "Old post",
+                "https://example.com/old_post",
+                Some(chrono::Utc.with_ymd_and_hms(2019, 6, 1, 0, 0, 0).unwrap()),
+            ),
+            (
+                "New post",
+                "https://example.com/new_post",
+                Some(chrono::Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()),
+            ),
+            ("Undated post", "https://example.com/undated_post", None),
+        ] {
+            searcher
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: None,
+                        title,
+                        domain: "example.com",
+                        url,
+                        content: "Stargazing with a telescope on a clear night.",
+                        tags: &[1_i64],
+                        published_at,
+                        last_modified: None,
+                    }
+                    .to_document(),
+                )
+                .await
+                .expect("Unable to add doc");
+        }
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        let urls = |result: &crate::SearchQueryResult| {
+            result
+                .documents
+                .iter()
+                .map(|(_, doc)| doc.url.clone())
+                .collect::<Vec<String>>()
+        };
+
+        // Relevance (default) returns everything.
+        let results = searcher.search("telescope", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 3);
+
+        let newest = SearchOptions {
+            sort: SortMode::NewestFirst,
+            ..Default::default()
+        };
+        let results = searcher
+            .search_with_options("telescope", &[], &[], 5, 0, newest)
+            .await;
+        assert_eq!(
+            urls(&results),
+            vec![
+                "https://example.com/new_post",
+                "https://example.com/old_post",
+                // Docs w/o a date always sort last.
+                "https://example.com/undated_post",
+            ]
+        );
+
+        let oldest = SearchOptions {
+            sort: SortMode::OldestFirst,
+            ..Default::default()
+        };
+        let results = searcher
+            .search_with_options("telescope", &[], &[], 5, 0, oldest)
+            .await;
+        assert_eq!(
+            urls(&results),
+            vec![
+                "https://example.com/old_post",
+                "https://example.com/new_post",
+                "https://example.com/undated_post",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    pub async fn test_search_pagination() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // Two docs match "salinas", page through them one at a time.
+        let page_one = searcher.search("salinas", &[], &[], 1, 0).await;
+        assert_eq!(page_one.documents.len(), 1);
+        assert_eq!(page_one.total_hits, 2);
+
+        let page_two = searcher.search("salinas", &[], &[], 1, 1).await;
+        assert_eq!(page_two.documents.len(), 1);
+        assert_eq!(page_two.total_hits, 2);
+
+        assert_ne!(page_one.documents[0].1.url, page_two.documents[0].1.url);
+    }
+
+    #[tokio::test]
+    pub async fn test_fuzzy_search() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        let fuzzy_options = SearchOptions {
+            use_fuzzy: true,
+            ..Default::default()
+        };
+
+        // One-character typo, no results without fuzzy matching.
+        let query = "frankenstien";
+        let results = searcher.search(query, &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 0);
+
+        let results = searcher
+            .search_with_options(query, &[], &[], 5, 0, fuzzy_options)
+            .await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/frankenstein"
+        );
+
+        // Exact matches should still rank above fuzzy ones.
+        let results = searcher
+            .search_with_options("salinas", &[], &[], 5, 0, fuzzy_options)
+            .await;
+        assert!(results.documents.len() >= 2);
+        assert!(results.documents[0]
+            .1
+            .content
+            .to_lowercase()
+            .contains("salinas"));
+    }
+
+    #[tokio::test]
+    pub async fn test_spelling_suggestions() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // One-character typo, corrected from the index's term dictionary.
+        let suggestions = searcher.spelling_suggestions("frankenstien");
+        assert!(suggestions.contains(&"frankenstein".to_string()));
+
+        // Terms that exist in the index need no correction.
+        assert!(searcher.spelling_suggestions("salinas").is_empty());
+
+        // Nothing close enough to suggest.
+        assert!(searcher.spelling_suggestions("zzzzqqqq").is_empty());
+    }
+
+    #[tokio::test]
+    pub async fn test_write_buffering() {
+        use crate::client::WriteBufferSettings;
+
+        let searcher = Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+            .expect("Unable to open index");
+        searcher.enable_write_buffering(WriteBufferSettings {
+            max_docs: 2,
+            max_age: std::time::Duration::from_secs(600),
+        });
+
+        let add_doc = |searcher: Searcher, url: &'static str| async move {
+            searcher
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: None,
+                        title: "Buffered doc",
+                        domain: "example.com",
+                        url,
+                        content: "Stargazing with a telescope on a clear night.",
+                        tags: &[1_i64],
+                        published_at: None,
+                        last_modified: None,
+                    }
+                    .to_document(),
+                )
+                .await
+                .expect("Unable to add doc");
+        };
+
+        // A single buffered doc isn't committed yet.
+        add_doc(searcher.clone(), "https://example.com/one").await;
+        let _ = searcher.reader.reload();
+        assert_eq!(searcher.reader.searcher().num_docs(), 0);
+
+        // The second doc crosses `max_docs` & triggers a commit.
+        add_doc(searcher.clone(), "https://example.com/two").await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+        let _ = searcher.reader.reload();
+        assert_eq!(searcher.reader.searcher().num_docs(), 2);
+
+        // A trailing doc stays buffered until an explicit flush.
+        add_doc(searcher.clone(), "https://example.com/three").await;
+        let _ = searcher.reader.reload();
+        assert_eq!(searcher.reader.searcher().num_docs(), 2);
+
+        searcher.flush().await.expect("Unable to flush");
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+        let _ = searcher.reader.reload();
+        assert_eq!(searcher.reader.searcher().num_docs(), 3);
+    }
+
+    #[tokio::test]
+    pub async fn test_synonym_expansion() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // Without synonyms only the terms from the document itself match.
+        let results = searcher.search("undertaking", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        let results = searcher.search("enterprise", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        let results = searcher.search("venture", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 0);
+
+        // "venture" now expands into its group & matches both terms.
+        searcher.reload_synonyms(Some(r#"[["venture", "enterprise", "undertaking"]]"#));
+        let results = searcher.search("venture", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+
+        // An exact match still outranks a synonym-only match.
+        let exact = searcher.search("enterprise", &[], &[], 5, 0).await;
+        let expanded = searcher.search("venture", &[], &[], 5, 0).await;
+        assert!(exact.documents[0].0 > expanded.documents[0].0);
+
+        // Clearing the file removes the expansion again.
+        searcher.reload_synonyms(None);
+        let results = searcher.search("venture", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 0);
+    }
+
+    #[tokio::test]
+    pub async fn test_partial_search() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        let partial_options = SearchOptions {
+            is_partial: true,
+            ..Default::default()
+        };
+
+        // Half-typed word, matches one title ("Frankenstein: ...") but no
+        // content. Nothing without prefix expansion.
+        let query = "franken";
+        let results = searcher.search(query, &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 0);
+
+        let results = searcher
+            .search_with_options(query, &[], &[], 5, 0, partial_options)
+            .await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/frankenstein"
+        );
+
+        // Exact-term matches should still rank above prefix expansions:
+        // "salinas" matches two docs exactly & is a prefix of nothing else.
+        let results = searcher
+            .search_with_options("salinas", &[], &[], 5, 0, partial_options)
+            .await;
+        assert!(results.documents.len() >= 2);
+        assert!(results.documents[0]
+            .1
+            .content
+            .to_lowercase()
+            .contains("salinas"));
+    }
+
+    #[tokio::test]
+    pub async fn test_phrase_search() {
+        let mut searcher =
+            Searcher::with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+                .expect("Unable to open index");
+        _build_test_index(&mut searcher).await;
+
+        // "water runs deep" only shows up here, but the individual words all
+        // appear in the "Of Mice and Men" docs as well.
+        searcher
+            .upsert(
+                &DocumentUpdate {
+                    doc_id: None,
+                    title: "River Guide",
+                    domain: "example.com",
+                    url: "https://example.com/river_guide",
+                    content: "Where the river water runs deep, fishing from the bank is best
+            left to those who know the currents.",
+                    tags: &[1_i64],
+                    published_at: None,
+                    last_modified: None,
+                }
+                .to_document(),
+            )
+            .await
+            .expect("Unable to add doc");
+        let _ = searcher.save().await;
+        std::thread::sleep(std::time::Duration::from_millis(1000));
+
+        // Unquoted, the words match wherever they appear.
+        let results = searcher.search("water runs deep", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 3);
+
+        // Quoted, only the exact phrase matches.
+        let results = searcher.search("\"water runs deep\"", &[], &[], 5, 0).await;
+        assert_eq!(results.documents.len(), 1);
+        assert_eq!(
+            results.documents[0].1.url,
+            "https://example.com/river_guide"
+        );
+    }
+
     #[tokio::test]
     pub async fn test_singular_url_lens_search() {
         let mut searcher =
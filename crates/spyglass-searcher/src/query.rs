@@ -1,15 +1,54 @@
-use tantivy::query::{BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, TermQuery};
+use std::collections::HashMap;
+use std::ops::Bound;
+
+use tantivy::query::{
+    BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, PhraseQuery, Query, RangeQuery, RegexQuery,
+    TermQuery,
+};
 use tantivy::tokenizer::*;
 use tantivy::Score;
 use tantivy::{schema::*, Index};
 
 use crate::schema::SearchDocument;
-use crate::{Boost, QueryBoost};
+use crate::{Boost, DateField, QueryBoost};
 
 use super::DocFields;
 
 type QueryVec = Vec<(Occur, Box<dyn Query>)>;
 
+/// Term -> the other members of its synonym group, all lowercased.
+pub type SynonymMap = HashMap<String, Vec<String>>;
+
+/// Parses the user's `synonyms.ron` file: a RON list of equivalence groups,
+/// e.g. `[["k8s", "kube", "kubernetes"], ["js", "javascript"]]`. Every member
+/// of a group maps to the rest of its group.
+pub fn parse_synonyms(raw: &str) -> SynonymMap {
+    let groups: Vec<Vec<String>> = match ron::from_str(raw) {
+        Ok(groups) => groups,
+        Err(err) => {
+            log::warn!("Unable to parse synonyms file: {err}");
+            return SynonymMap::new();
+        }
+    };
+
+    let mut map = SynonymMap::new();
+    for group in &groups {
+        for term in group {
+            let term = term.to_lowercase();
+            let others = group
+                .iter()
+                .map(|other| other.to_lowercase())
+                .filter(|other| *other != term)
+                .collect::<Vec<String>>();
+            if !others.is_empty() {
+                map.entry(term).or_insert_with(Vec::new).extend(others);
+            }
+        }
+    }
+
+    map
+}
+
 fn _boosted_term(term: Term, boost: Score) -> Box<BoostQuery> {
     Box::new(BoostQuery::new(
         Box::new(TermQuery::new(
@@ -21,6 +60,14 @@ fn _boosted_term(term: Term, boost: Score) -> Box<BoostQuery> {
     ))
 }
 
+fn _fuzzy_term(term: Term, distance: u8, boost: Score) -> Box<BoostQuery> {
+    Box::new(BoostQuery::new(
+        // Transpositions count as a single edit, the most common typo.
+        Box::new(FuzzyTermQuery::new(term, distance, true)),
+        boost,
+    ))
+}
+
 fn _boosted_phrase(terms: Vec<(usize, Term)>, boost: Score) -> Box<BoostQuery> {
     let slop = terms
         .last()
@@ -41,6 +88,41 @@ pub struct QueryOptions {
     title_boost: f32,
     /// full phrase matches in the title
     title_phrase_boost: f32,
+    /// add fuzzy term matches for typo tolerance
+    use_fuzzy: bool,
+    /// multiplier applied to fuzzy matches so exact hits always rank first
+    fuzzy_boost: f32,
+    /// treat the final token of the query as a prefix (search-as-you-type)
+    prefix_last_token: bool,
+    /// multiplier applied to prefix expansions so exact terms rank first
+    prefix_boost: f32,
+    /// user-defined synonym groups, expanded at query time
+    synonyms: SynonymMap,
+    /// multiplier applied to synonym expansions so the typed term ranks first
+    synonym_boost: f32,
+}
+
+impl QueryOptions {
+    pub fn with_fuzzy() -> Self {
+        QueryOptions {
+            use_fuzzy: true,
+            ..Default::default()
+        }
+    }
+
+    /// Expand the final token as a prefix, used by the searchbar while the
+    /// user is still typing.
+    pub fn partial_matching(mut self) -> Self {
+        self.prefix_last_token = true;
+        self
+    }
+
+    /// Expand query terms into their user-defined synonym groups, see
+    /// [`parse_synonyms`].
+    pub fn with_synonyms(mut self, synonyms: SynonymMap) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
 }
 
 impl Default for QueryOptions {
@@ -51,10 +133,155 @@ impl Default for QueryOptions {
             // weight title matches a little more
             title_boost: 2.0,
             title_phrase_boost: 2.5,
+            use_fuzzy: false,
+            fuzzy_boost: 0.5,
+            prefix_last_token: false,
+            prefix_boost: 0.5,
+            synonyms: SynonymMap::new(),
+            synonym_boost: 0.7,
+        }
+    }
+}
+
+/// Fields that can be scoped to from the searchbar w/ `field:value` syntax,
+/// e.g. `title:invoice` or `domain:github.com`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScopedField {
+    Title,
+    Url,
+    Domain,
+    /// Tag ids live in the database, so `tag:` scopes are resolved by the
+    /// caller into tag filters. See [`scoped_tag_values`].
+    Tag,
+}
+
+impl ScopedField {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_ascii_lowercase().as_str() {
+            "title" => Some(Self::Title),
+            "url" => Some(Self::Url),
+            "domain" => Some(Self::Domain),
+            "tag" => Some(Self::Tag),
+            _ => None,
         }
     }
 }
 
+/// Pull the values of any `tag:` scoped tokens out of a raw query string so
+/// they can be resolved against the tag database.
+pub fn scoped_tag_values(query: &str) -> Vec<String> {
+    parse_query_string(query)
+        .scoped
+        .into_iter()
+        .filter_map(|(scope, value)| (scope == ScopedField::Tag).then_some(value))
+        .collect()
+}
+
+/// First parsing pass over a raw query string: double-quoted phrases,
+/// `-` negated terms & phrases, `field:value` scoped tokens, and whatever
+/// plain terms remain.
+#[derive(Default)]
+struct ParsedQuery {
+    phrases: Vec<String>,
+    negated_phrases: Vec<String>,
+    negated_terms: Vec<String>,
+    scoped: Vec<(ScopedField, String)>,
+    remainder: String,
+}
+
+/// Split a raw query string into double-quoted phrases, negated (`-` prefixed,
+/// outside quotes) terms/phrases & whatever remains. An unmatched quote is
+/// left in the remainder & handled like any other punctuation by the
+/// tokenizer.
+fn parse_query_string(query: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    let mut outside = String::new();
+
+    let mut rest = query;
+    while let Some(start) = rest.find('"') {
+        if let Some(len) = rest[start + 1..].find('"') {
+            let mut before = &rest[..start];
+            // A `-` hugging the opening quote negates the whole phrase.
+            let negated = before.ends_with('-')
+                && before[..before.len() - 1]
+                    .chars()
+                    .next_back()
+                    .map_or(true, |ch| ch.is_whitespace());
+            if negated {
+                before = &before[..before.len() - 1];
+            }
+
+            outside.push_str(before);
+            let phrase = &rest[start + 1..start + 1 + len];
+            if !phrase.trim().is_empty() {
+                if negated {
+                    parsed.negated_phrases.push(phrase.to_string());
+                } else {
+                    parsed.phrases.push(phrase.to_string());
+                }
+            }
+            rest = &rest[start + len + 2..];
+        } else {
+            break;
+        }
+    }
+    outside.push_str(rest);
+
+    // Pull out `field:value` scopes & `-term` negations, everything else
+    // (incl. hyphenated words like "well-known" or unknown prefixes like
+    // "foo:bar") stays in the remainder as literal text.
+    for token in outside.split_whitespace() {
+        if let Some((prefix, value)) = token.split_once(':') {
+            if !value.is_empty() {
+                if let Some(scope) = ScopedField::from_prefix(prefix) {
+                    parsed.scoped.push((scope, value.to_string()));
+                    continue;
+                }
+            }
+        }
+
+        if let Some(negated) = token.strip_prefix('-') {
+            if !negated.is_empty() {
+                parsed.negated_terms.push(negated.to_string());
+                continue;
+            }
+        }
+
+        if !parsed.remainder.is_empty() {
+            parsed.remainder.push(' ');
+        }
+        parsed.remainder.push_str(token);
+    }
+
+    parsed
+}
+
+/// Turns the tokenized form of a negated term or phrase into the query that
+/// documents must NOT match.
+/// Escape regex metacharacters so a typed token is matched literally.
+fn _regex_escape(token: &str) -> String {
+    let mut escaped = String::with_capacity(token.len());
+    for ch in token.chars() {
+        if !ch.is_alphanumeric() {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+fn _negated_clause(terms: Vec<(usize, Term)>) -> Option<Box<dyn Query>> {
+    match terms.len() {
+        0 => None,
+        1 => Some(Box::new(TermQuery::new(
+            terms[0].1.clone(),
+            IndexRecordOption::Basic,
+        ))),
+        _ => Some(Box::new(PhraseQuery::new_with_offset(terms))),
+    }
+}
+
 pub fn build_query(
     index: &Index,
     query_string: &str,
@@ -69,10 +296,54 @@ pub fn build_query(
     let tokenizers = index.tokenizers();
     let fields = DocFields::as_fields();
 
-    let content_terms = terms_for_field(&schema, tokenizers, query_string, fields.content);
-    let title_terms = terms_for_field(&schema, tokenizers, query_string, fields.title);
+    // Double-quoted substrings are required to match as exact phrases,
+    // `-` prefixed terms & phrases are excluded, anything else keeps the
+    // usual term-by-term handling.
+    let parsed = parse_query_string(query_string);
+    let phrases = &parsed.phrases;
+    let unquoted = &parsed.remainder;
+
+    let content_terms = terms_for_field(&schema, tokenizers, unquoted, fields.content);
+    let title_terms = terms_for_field(&schema, tokenizers, unquoted, fields.title);
+
+    let mut term_count = content_terms.len();
+
+    // Each phrase must appear in at least one of the text fields.
+    let mut phrase_clauses: QueryVec = Vec::new();
+    for phrase in phrases {
+        let mut field_queries: QueryVec = Vec::new();
+        for (field, boost) in [
+            (fields.content, opts.content_phrase_boost),
+            (fields.title, opts.title_phrase_boost),
+            (fields.description, opts.content_phrase_boost),
+        ] {
+            let terms = terms_for_field(&schema, tokenizers, phrase, field);
+            if field == fields.content {
+                term_count += terms.len();
+            }
+
+            match terms.len() {
+                0 => {}
+                // A single-word "phrase" is just an exact term match.
+                1 => field_queries.push((
+                    Occur::Should,
+                    _boosted_term(terms[0].1.clone(), boost),
+                )),
+                _ => field_queries.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        // No slop here, quotes mean an exact phrase.
+                        Box::new(PhraseQuery::new_with_offset(terms)),
+                        boost,
+                    )),
+                )),
+            }
+        }
 
-    let term_count = content_terms.len();
+        if !field_queries.is_empty() {
+            phrase_clauses.push((Occur::Must, Box::new(BooleanQuery::new(field_queries))));
+        }
+    }
 
     let mut term_query: QueryVec = Vec::new();
 
@@ -101,6 +372,84 @@ pub fn build_query(
         term_query.push((Occur::Should, _boosted_term(term, opts.title_boost)));
     }
 
+    // Expand each term into its user-defined synonym group (synonyms.ron),
+    // or'd in & weighted below the typed term so exact matches rank first.
+    // The lookup uses the raw (lowercased) words, not the analyzer output;
+    // the stemmer would turn "venture" into "ventur" & miss the group key.
+    if !opts.synonyms.is_empty() {
+        for word in unquoted.split_whitespace() {
+            if let Some(group) = opts.synonyms.get(&word.to_lowercase()) {
+                for synonym in group {
+                    for (field, boost) in [
+                        (fields.content, opts.content_boost * opts.synonym_boost),
+                        (fields.title, opts.title_boost * opts.synonym_boost),
+                    ] {
+                        // Run the synonym through the same tokenizer as the
+                        // query so multi-word synonyms still line up w/ the
+                        // indexed terms.
+                        for (_, term) in terms_for_field(&schema, tokenizers, synonym, field) {
+                            term_query.push((Occur::Should, _boosted_term(term, boost)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Search-as-you-type: expand the final token as a prefix so "kuber"
+    // already matches "kubernetes". Weighted below exact terms so finished
+    // words still rank first. The raw token is used on purpose; the
+    // tokenizer would drop a half-typed stop word like "the" (theory...).
+    if opts.prefix_last_token {
+        if let Some(token) = unquoted.split_whitespace().last() {
+            let pattern = format!("{}.*", _regex_escape(&token.to_lowercase()));
+            for (field, boost) in [
+                (fields.content, opts.content_boost * opts.prefix_boost),
+                (fields.title, opts.title_boost * opts.prefix_boost),
+            ] {
+                if let Ok(prefix) = RegexQuery::from_pattern(&pattern, field) {
+                    term_query.push((
+                        Occur::Should,
+                        Box::new(BoostQuery::new(Box::new(prefix), boost)),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Tolerate typos in longer terms w/ fuzzy matches, weighted well below
+    // exact hits so precise matches still rank first.
+    if opts.use_fuzzy {
+        let analyzer = tokenizers
+            .get(crate::schema::TOKENIZER_NAME)
+            .expect("Unable to get query tokenizer");
+        let mut token_stream = analyzer.token_stream(unquoted);
+        token_stream.process(&mut |token| {
+            let distance = match token.text.chars().count() {
+                0..=4 => return,
+                5..=8 => 1,
+                _ => 2,
+            };
+
+            term_query.push((
+                Occur::Should,
+                _fuzzy_term(
+                    Term::from_field_text(fields.content, &token.text),
+                    distance,
+                    opts.content_boost * opts.fuzzy_boost,
+                ),
+            ));
+            term_query.push((
+                Occur::Should,
+                _fuzzy_term(
+                    Term::from_field_text(fields.title, &token.text),
+                    distance,
+                    opts.title_boost * opts.fuzzy_boost,
+                ),
+            ));
+        });
+    }
+
     // Boost fields that happen to have a value, such as
     // - Tags that might be represented by search terms (e.g. "repository" or "file")
     // - Certain URLs or documents we want to focus on
@@ -112,10 +461,23 @@ pub fn build_query(
             }
             // Only considered in filters
             Boost::Favorite { .. } => continue,
+            Boost::DateRange { .. } => continue,
+            Boost::ExcludeTag(_) => continue,
+            // Applied by the collector, see `search_with_options`.
+            Boost::Recency { .. } => continue,
             Boost::Tag(tag_id) => {
                 // Defaults to 1.5
                 _boosted_term(Term::from_field_u64(fields.tags, *tag_id), boost.value)
             }
+            Boost::AnyTag(tag_ids) => {
+                for tag_id in tag_ids {
+                    term_query.push((
+                        Occur::Should,
+                        _boosted_term(Term::from_field_u64(fields.tags, *tag_id), boost.value),
+                    ));
+                }
+                continue;
+            }
             // todo: handle regex/prefixes?
             Boost::Url(url) => {
                 // Originally boosted to 3.0
@@ -133,8 +495,75 @@ pub fn build_query(
         term_query.push((Occur::Should, term));
     }
 
-    // Must hit at least one of the terms
-    let mut combined: QueryVec = vec![(Occur::Must, Box::new(BooleanQuery::new(term_query)))];
+    // Must hit at least one of the terms. Skipped for phrase-only or
+    // scope-only queries, where those clauses are the whole query.
+    let mut combined: QueryVec = Vec::new();
+    if !term_query.is_empty() || (phrase_clauses.is_empty() && parsed.scoped.is_empty()) {
+        combined.push((Occur::Must, Box::new(BooleanQuery::new(term_query))));
+    }
+    combined.append(&mut phrase_clauses);
+
+    // Field-scoped tokens restrict matches to an exact value (url/domain) or
+    // term/phrase (title) on that field.
+    for (scope, value) in &parsed.scoped {
+        match scope {
+            ScopedField::Title => {
+                let terms = terms_for_field(&schema, tokenizers, value, fields.title);
+                match terms.len() {
+                    0 => {}
+                    1 => combined.push((
+                        Occur::Must,
+                        _boosted_term(terms[0].1.clone(), opts.title_boost),
+                    )),
+                    _ => combined.push((
+                        Occur::Must,
+                        Box::new(PhraseQuery::new_with_offset(terms)),
+                    )),
+                }
+            }
+            // Non-zero boost so that scope-only queries still score above the
+            // result cut-off.
+            ScopedField::Url => combined.push((
+                Occur::Must,
+                _boosted_term(Term::from_field_text(fields.url, value), 1.0),
+            )),
+            ScopedField::Domain => combined.push((
+                Occur::Must,
+                _boosted_term(Term::from_field_text(fields.domain, value), 1.0),
+            )),
+            // Resolved by the caller into tag filters, see `scoped_tag_values`.
+            ScopedField::Tag => {}
+        }
+    }
+
+    // Negated terms & phrases exclude any document matching them in one of
+    // the text fields.
+    for negated in &parsed.negated_terms {
+        for field in [fields.content, fields.title] {
+            let mut terms = terms_for_field(&schema, tokenizers, negated, field);
+            if terms.is_empty() {
+                // The stop-word filter would silently drop terms like "the".
+                // Fall back to the raw (lowercased) term so an explicit
+                // negation is never ignored; stop words are also removed at
+                // index time so the clause is a no-op rather than a surprise.
+                terms.push((0, Term::from_field_text(field, &negated.to_lowercase())));
+            }
+
+            if let Some(clause) = _negated_clause(terms) {
+                combined.push((Occur::MustNot, clause));
+            }
+        }
+    }
+
+    for phrase in &parsed.negated_phrases {
+        for field in [fields.content, fields.title, fields.description] {
+            if let Some(clause) = _negated_clause(terms_for_field(&schema, tokenizers, phrase, field))
+            {
+                combined.push((Occur::MustNot, clause));
+            }
+        }
+    }
+
     // Must have one of these, will filter out stuff that doesn't
     for filter in filters {
         let term = match &filter.field {
@@ -160,6 +589,34 @@ pub fn build_query(
                 // Defaults to 1.5
                 _boosted_term(Term::from_field_u64(fields.tags, *tag_id), 0.0)
             }
+            Boost::AnyTag(tag_ids) => {
+                // Or'd together so searching multiple lenses returns the
+                // union of their documents.
+                let mut clauses: QueryVec = Vec::new();
+                for tag_id in tag_ids {
+                    clauses.push((
+                        Occur::Should,
+                        _boosted_term(Term::from_field_u64(fields.tags, *tag_id), 0.0),
+                    ));
+                }
+
+                if !clauses.is_empty() {
+                    combined.push((Occur::Must, Box::new(BooleanQuery::new(clauses))));
+                }
+
+                continue;
+            }
+            Boost::ExcludeTag(tag_ids) => {
+                // Anything tagged w/ one of these is dropped outright.
+                for tag_id in tag_ids {
+                    combined.push((
+                        Occur::MustNot,
+                        _boosted_term(Term::from_field_u64(fields.tags, *tag_id), 0.0),
+                    ));
+                }
+
+                continue;
+            }
             // todo: handle regex/prefixes?
             Boost::Url(url) => {
                 // Originally boosted to 3.0
@@ -172,6 +629,37 @@ pub fn build_query(
                     continue;
                 }
             }
+            // Only affects scoring, not the doc set.
+            Boost::Recency { .. } => continue,
+            Boost::DateRange { field, start, end } => {
+                let field = match field {
+                    DateField::Published => fields.published,
+                    DateField::LastModified => fields.lastmodified,
+                };
+
+                let lower = start
+                    .map(|date| {
+                        Bound::Included(Term::from_field_date(
+                            field,
+                            tantivy::DateTime::from_timestamp_micros(date.timestamp_micros()),
+                        ))
+                    })
+                    .unwrap_or(Bound::Unbounded);
+                let upper = end
+                    .map(|date| {
+                        Bound::Included(Term::from_field_date(
+                            field,
+                            tantivy::DateTime::from_timestamp_micros(date.timestamp_micros()),
+                        ))
+                    })
+                    .unwrap_or(Bound::Unbounded);
+
+                combined.push((
+                    Occur::Must,
+                    Box::new(RangeQuery::new_term_bounds(field, Type::Date, &lower, &upper)),
+                ));
+                continue;
+            }
         };
 
         combined.push((Occur::Must, term));
@@ -257,3 +745,47 @@ pub fn terms_for_field(
 
     terms
 }
+
+#[cfg(test)]
+mod test {
+    use super::{parse_query_string, scoped_tag_values, ScopedField};
+
+    #[test]
+    fn test_parse_scoped_fields() {
+        let parsed = parse_query_string("title:invoice domain:github.com rust");
+        assert_eq!(
+            parsed.scoped,
+            vec![
+                (ScopedField::Title, "invoice".into()),
+                (ScopedField::Domain, "github.com".into()),
+            ]
+        );
+        assert_eq!(parsed.remainder, "rust");
+    }
+
+    #[test]
+    fn test_parse_unknown_prefix_is_literal() {
+        // Unrecognized prefixes & empty values are left as literal text.
+        let parsed = parse_query_string("foo:bar title:");
+        assert!(parsed.scoped.is_empty());
+        assert_eq!(parsed.remainder, "foo:bar title:");
+    }
+
+    #[test]
+    fn test_parse_negations_and_phrases() {
+        let parsed = parse_query_string("rust -tokio \"error handling\" -\"borrow checker\"");
+        assert_eq!(parsed.remainder, "rust");
+        assert_eq!(parsed.negated_terms, vec!["tokio".to_string()]);
+        assert_eq!(parsed.phrases, vec!["error handling".to_string()]);
+        assert_eq!(parsed.negated_phrases, vec!["borrow checker".to_string()]);
+    }
+
+    #[test]
+    fn test_scoped_tag_values() {
+        // Only the colon after the field prefix splits, urls keep theirs.
+        assert_eq!(
+            scoped_tag_values("tag:web rust url:https://example.com"),
+            vec!["web".to_string()]
+        );
+    }
+}
@@ -8,7 +8,7 @@ use tantivy::{
         AsciiFoldingFilter, Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer,
         TextAnalyzer,
     },
-    Index,
+    Index, TantivyError,
 };
 use uuid::Uuid;
 
@@ -18,6 +18,55 @@ pub type FieldName = String;
 
 pub const TOKENIZER_NAME: &str = "spyglass_tokenizer_en";
 
+/// Language codes (two-letter, ISO 639-1) w/ a stemmer available for the
+/// index tokenizer.
+const SUPPORTED_LANGUAGES: &[(&str, Language)] = &[
+    ("ar", Language::Arabic),
+    ("da", Language::Danish),
+    ("de", Language::German),
+    ("el", Language::Greek),
+    ("en", Language::English),
+    ("es", Language::Spanish),
+    ("fi", Language::Finnish),
+    ("fr", Language::French),
+    ("hu", Language::Hungarian),
+    ("it", Language::Italian),
+    ("nl", Language::Dutch),
+    ("no", Language::Norwegian),
+    ("pt", Language::Portuguese),
+    ("ro", Language::Romanian),
+    ("ru", Language::Russian),
+    ("sv", Language::Swedish),
+    ("ta", Language::Tamil),
+    ("tr", Language::Turkish),
+];
+
+/// Stemmer language for a two-letter language code, if we support it.
+pub fn language_from_code(code: &str) -> Option<Language> {
+    let code = code.to_ascii_lowercase();
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|(supported, _)| *supported == code)
+        .map(|(_, language)| *language)
+}
+
+/// Name the tokenizer for a language is registered under, e.g.
+/// "spyglass_tokenizer_de".
+pub fn tokenizer_name_for_language(code: &str) -> String {
+    format!("spyglass_tokenizer_{}", code.to_ascii_lowercase())
+}
+
+/// Tokenizer used for the content fields given the configured index
+/// languages. Tantivy tokenizers are set per-field in the schema, so the
+/// first recognized language drives the analyzer; English is the fallback.
+pub fn tokenizer_name_for_languages(languages: &[String]) -> String {
+    languages
+        .iter()
+        .find(|code| language_from_code(code).is_some())
+        .map(|code| tokenizer_name_for_language(code))
+        .unwrap_or_else(|| TOKENIZER_NAME.to_string())
+}
+
 /// The current schema version
 pub const SCHEMA_VERSION: &str = "4";
 pub struct SchemaMapping {
@@ -60,8 +109,24 @@ pub fn mapping_to_schema(mapping: &SchemaMapping) -> Schema {
 /// Helper used to create and configure an index from a path
 pub fn initialize_index(schema: Schema, index_path: &PathBuf) -> anyhow::Result<Index> {
     let dir = MmapDirectory::open(index_path)?;
-    let index = Index::open_or_create(dir, schema)?;
-    register_tokenizer(&index);
+    let index = match Index::open_or_create(dir, schema.clone()) {
+        Ok(index) => index,
+        // An existing index built w/ a different schema, most likely a
+        // changed content tokenizer after an `index_languages` edit. Open w/
+        // the stored schema instead of failing; the configured languages
+        // take effect once the user reindexes (surfaced via system_health).
+        Err(TantivyError::SchemaError(err)) => {
+            log::warn!(
+                "Index schema doesn't match the configured one ({err}), \
+                 opening w/ the stored schema. A reindex is required for \
+                 the configured index languages to take effect."
+            );
+            let dir = MmapDirectory::open(index_path)?;
+            Index::open(dir)?
+        }
+        Err(err) => return Err(err.into()),
+    };
+    register_tokenizers(&index);
 
     Ok(index)
 }
@@ -69,23 +134,59 @@ pub fn initialize_index(schema: Schema, index_path: &PathBuf) -> anyhow::Result<
 /// Helper used to create and configure an in memory index
 pub fn initialize_in_memory_index(schema: Schema) -> Index {
     let index = Index::create_in_ram(schema);
-    register_tokenizer(&index);
+    register_tokenizers(&index);
 
     index
 }
 
-/// Register custom tokenizer
-pub fn register_tokenizer(index: &Index) {
-    let full_content_tokenizer_en = TextAnalyzer::from(SimpleTokenizer)
-        .filter(RemoveLongFilter::limit(40))
-        .filter(LowerCaser)
-        .filter(AsciiFoldingFilter)
-        .filter(StopWordFilter::default())
-        .filter(Stemmer::new(Language::English));
+/// Register custom tokenizers, one per supported stemming language. Which one
+/// is actually used is baked into the schema the index was built with, see
+/// [`schema_for_languages`].
+pub fn register_tokenizers(index: &Index) {
+    register_tokenizers_with_stopwords(index, None)
+}
 
-    index
-        .tokenizers()
-        .register(TOKENIZER_NAME, full_content_tokenizer_en);
+/// Same as [`register_tokenizers`], w/ user stop word overrides applied (see
+/// `StopWordFilter::with_overrides`). Registering replaces any existing
+/// analyzers, so this can also be used to reload the list on a live index.
+pub fn register_tokenizers_with_stopwords(index: &Index, stop_word_overrides: Option<&str>) {
+    let stop_words = match stop_word_overrides {
+        Some(overrides) => StopWordFilter::with_overrides(overrides),
+        None => StopWordFilter::default(),
+    };
+
+    for (code, language) in SUPPORTED_LANGUAGES {
+        let analyzer = TextAnalyzer::from(SimpleTokenizer)
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .filter(AsciiFoldingFilter)
+            .filter(stop_words.clone())
+            .filter(Stemmer::new(*language));
+
+        index
+            .tokenizers()
+            .register(&tokenizer_name_for_language(code), analyzer);
+    }
+}
+
+/// Schema w/ the content fields tokenized for the configured languages. Since
+/// the tokenizer is part of the schema, changing languages requires a reindex
+/// of existing documents to take effect.
+pub fn schema_for_languages(languages: &[String]) -> Schema {
+    mapping_to_schema(&DocFields::field_vec_with_tokenizer(
+        &tokenizer_name_for_languages(languages),
+    ))
+}
+
+/// Name of the tokenizer a schema's content field was built with.
+pub fn content_tokenizer(schema: &Schema) -> Option<String> {
+    let field = schema.get_field("content")?;
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Str(opts) => opts
+            .get_indexing_options()
+            .map(|opts| opts.tokenizer().to_string()),
+        _ => None,
+    }
 }
 
 pub trait ToDocument {
@@ -128,6 +229,20 @@ impl<'a> ToDocument for DocumentUpdate<'a> {
             doc.add_u64(fields.tags, *t as u64);
         }
 
+        if let Some(published_at) = &self.published_at {
+            doc.add_date(
+                fields.published,
+                tantivy::DateTime::from_timestamp_micros(published_at.timestamp_micros()),
+            );
+        }
+
+        if let Some(last_modified) = &self.last_modified {
+            doc.add_date(
+                fields.lastmodified,
+                tantivy::DateTime::from_timestamp_micros(last_modified.timestamp_micros()),
+            );
+        }
+
         doc
     }
 }
@@ -145,10 +260,11 @@ pub struct DocFields {
     pub lastmodified: Field,
 }
 
-impl SearchDocument for DocFields {
-    fn as_field_vec() -> SchemaMapping {
+impl DocFields {
+    /// The field mapping w/ the content fields using the given tokenizer.
+    fn field_vec_with_tokenizer(tokenizer: &str) -> SchemaMapping {
         let text_field_indexing = TextFieldIndexing::default()
-            .set_tokenizer(TOKENIZER_NAME)
+            .set_tokenizer(tokenizer)
             .set_index_option(IndexRecordOption::WithFreqsAndPositions);
         let text_options = TextOptions::default()
             .set_indexing_options(text_field_indexing)
@@ -203,6 +319,12 @@ impl SearchDocument for DocFields {
             )]),
         }
     }
+}
+
+impl SearchDocument for DocFields {
+    fn as_field_vec() -> SchemaMapping {
+        Self::field_vec_with_tokenizer(TOKENIZER_NAME)
+    }
 
     fn as_fields() -> Self {
         let schema = Self::as_schema();
@@ -13,6 +13,14 @@ pub struct StopWordFilter {
     words: StopWordHashSet,
 }
 
+/// Stop words baked into the tokenizer by default.
+pub const DEFAULT_STOP_WORDS: [&str; 44] = [
+    "a", "about", "an", "and", "are", "as", "at", "be", "but", "by", "com", "for", "from", "how",
+    "if", "I", "in", "into", "is", "it", "no", "not", "of", "on", "or", "such", "that", "the",
+    "their", "then", "there", "these", "they", "this", "to", "was", "what", "when", "where", "who",
+    "will", "with", "the", "www",
+];
+
 impl StopWordFilter {
     /// Creates a `StopWordFilter` given a list of words to remove
     pub fn remove(words: Vec<String>) -> StopWordFilter {
@@ -26,14 +34,36 @@ impl StopWordFilter {
     }
 
     fn english() -> StopWordFilter {
-        let words: [&'static str; 44] = [
-            "a", "about", "an", "and", "are", "as", "at", "be", "but", "by", "com", "for", "from",
-            "how", "if", "I", "in", "into", "is", "it", "no", "not", "of", "on", "or", "such",
-            "that", "the", "their", "then", "there", "these", "they", "this", "to", "was", "what",
-            "when", "where", "who", "will", "with", "the", "www",
-        ];
-
-        StopWordFilter::remove(words.iter().map(|&s| s.to_string()).collect())
+        StopWordFilter::remove(DEFAULT_STOP_WORDS.iter().map(|&s| s.to_string()).collect())
+    }
+
+    /// Merge the default list w/ user overrides: one word per line, a `-`
+    /// prefix un-stops a default word, `#` comments & blank lines are
+    /// ignored. Words are lowercased to match the tokenizer pipeline.
+    pub fn with_overrides(overrides: &str) -> StopWordFilter {
+        let mut words: StopWordHashSet = StopWordFilter::english().words;
+
+        for line in overrides.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(word) = line.strip_prefix('-') {
+                words.remove(&word.trim().to_lowercase());
+            } else {
+                words.insert(line.to_lowercase());
+            }
+        }
+
+        StopWordFilter { words }
+    }
+
+    /// The effective stop word list, sorted for display.
+    pub fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = self.words.iter().cloned().collect();
+        words.sort();
+        words
     }
 }
 
@@ -81,3 +111,22 @@ impl Default for StopWordFilter {
         StopWordFilter::english()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::StopWordFilter;
+
+    #[test]
+    fn test_with_overrides() {
+        let filter =
+            StopWordFilter::with_overrides("# domain jargon\nwiki\nDoc\n\n-the\n-about\n");
+
+        let words = filter.words();
+        assert!(words.contains(&"wiki".to_string()));
+        assert!(words.contains(&"doc".to_string()));
+        // un-stopped defaults are gone, untouched ones remain
+        assert!(!words.contains(&"the".to_string()));
+        assert!(!words.contains(&"about".to_string()));
+        assert!(words.contains(&"and".to_string()));
+    }
+}
@@ -137,6 +137,23 @@ pub fn generate_highlight_preview(tokenizer: &TextAnalyzer, query: &str, content
     format!("<span>{}</span>", desc.join(" "))
 }
 
+/// Total size on disk of the files directly inside `index_path` (a tantivy
+/// index directory is flat). 0 for in-memory or missing indexes.
+pub fn index_size_bytes(index_path: &std::path::Path) -> u64 {
+    let mut size = 0;
+    if let Ok(entries) = std::fs::read_dir(index_path) {
+        for entry in entries.flatten() {
+            if let Ok(meta) = entry.metadata() {
+                if meta.is_file() {
+                    size += meta.len();
+                }
+            }
+        }
+    }
+
+    size
+}
+
 pub fn group_urls_by_scheme(urls: Vec<&str>) -> HashMap<&str, Vec<&str>> {
     let mut grouping: HashMap<&str, Vec<&str>> = HashMap::new();
     urls.iter().for_each(|url| {
@@ -1,24 +1,26 @@
 use anyhow::anyhow;
 use clap::{Parser, Subcommand};
-use entities::models::vec_documents::{self, DocDistance};
+use entities::models::vec_documents;
 use entities::models::{self, indexed_document::DocumentIdentifier, tag::check_query_for_tags};
+use entities::sea_orm::{ActiveModelBehavior, ColumnTrait, EntityTrait, QueryFilter, Set};
+use libspyglass::documents::embeddings::processing_embedding_batch;
 use libspyglass::documents::DocumentQuery;
 use libspyglass::state::AppState;
 use ron::ser::PrettyConfig;
 use shared::config::Config;
-use shared::llm::{ChatMessage, ChatRole, ChatStream, LlmSession};
-use spyglass_llm::LlmClient;
-use spyglass_model_interface::embedding_api::EmbeddingApi;
+use shared::llm::{ChatMessage, ChatRole, ChatStream, GenerationParams, LlmSession};
+use spyglass_llm::{remote::RemoteClient, LlmBackend, LlmClient};
+use spyglass_model_interface::embedding_api::{EmbeddingApi, SegmentationConfig};
 use std::collections::HashMap;
 use std::{path::PathBuf, process::ExitCode};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tracing_log::LogTracer;
 use tracing_subscriber::{fmt, layer::SubscriberExt, EnvFilter};
 
 use libspyglass::pipeline::cache_pipeline::process_update;
-use spyglass_searcher::schema::SearchDocument;
+use spyglass_searcher::stop_word_filter::StopWordFilter;
 use spyglass_searcher::SearchTrait;
-use spyglass_searcher::{client::Searcher, schema::DocFields, Boost, IndexBackend, QueryBoost};
+use spyglass_searcher::{client::Searcher, schema::schema_for_languages, Boost, IndexBackend, QueryBoost};
 use std::io::Write;
 
 #[cfg(debug_assertions)]
@@ -52,15 +54,69 @@ enum Command {
         id_or_url: String,
         query: String,
     },
-    /// Load a local lens archive into the index
+    /// Validates lens files, reporting syntax errors w/ their line & column,
+    /// invalid rules, bad urls & duplicate names. Exits non-zero if any file
+    /// fails
+    LensCheck {
+        /// A lens file or a directory of lens files; defaults to the app's
+        /// lens directory
+        path: Option<PathBuf>,
+    },
+    /// Load a local lens archive into the index. Handles both bare parsed
+    /// caches & the bundles written by export-lens
     LoadArchive {
         name: String,
         archive_path: PathBuf,
     },
+    /// Exports a lens & its indexed documents into a shareable archive that
+    /// load-archive can ingest on another install
+    ExportLens {
+        name: String,
+        archive_path: PathBuf,
+    },
     AskDocument {
         id_or_url: String,
         question: String,
     },
+    /// Prints the effective stop word list (defaults merged w/ any user
+    /// overrides from stopwords.txt)
+    StopWords,
+    /// Exports the index, database & lens files into a portable archive
+    Backup {
+        archive_path: PathBuf,
+    },
+    /// Restores a backup archive into the data dir & runs pending migrations
+    Restore {
+        archive_path: PathBuf,
+        /// Overwrite a non-empty install
+        #[arg(long)]
+        force: bool,
+    },
+    /// Rebuilds the search index from the database & cached document
+    /// content, swapping the fresh index in once complete
+    Reindex,
+    /// Reports how often embedding jobs reused cached vectors instead of
+    /// rerunning the model
+    EmbeddingCacheStats,
+    /// Wipes stored embeddings & regenerates them, optionally limited to a
+    /// single lens or to recently updated documents. Safe to interrupt;
+    /// rerunning picks up where it left off
+    Reembed {
+        /// Only re-embed documents belonging to this lens
+        #[arg(long)]
+        lens: Option<String>,
+        /// Only re-embed documents updated on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+    },
+    /// Lists documents similar to the given document based on its stored
+    /// embedding vectors
+    SimilarDocuments {
+        id_or_url: String,
+        /// Number of results to show
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
 }
 
 #[tokio::main]
@@ -120,7 +176,7 @@ async fn main() -> anyhow::Result<ExitCode> {
             let doc_details =
                 models::indexed_document::get_document_details(&db, identifier).await?;
 
-            let schema = DocFields::as_schema();
+            let schema = schema_for_languages(&config.user_settings.index_languages);
             println!("## Document Details ##");
             match doc_details {
                 Some((doc, tags)) => {
@@ -152,6 +208,10 @@ async fn main() -> anyhow::Result<ExitCode> {
                                 ron::ser::to_string_pretty(&doc, PrettyConfig::new())
                                     .unwrap_or_default()
                             );
+                            match libspyglass::documents::detect_language(&doc.content) {
+                                Some(lang) => println!("Detected Language: {}", lang),
+                                None => println!("Detected Language: unknown"),
+                            }
                         }
                     }
                 }
@@ -177,9 +237,27 @@ async fn main() -> anyhow::Result<ExitCode> {
                             print!("{tok}");
                             std::io::stdout().flush().unwrap();
                         }
-                        ChatStream::ChatDone => {
+                        ChatStream::ContextTruncated(dropped) => {
+                            println!("some context omitted: {}", dropped.join(", "));
+                        }
+                        ChatStream::Error(err) => {
+                            eprintln!("chat failed: {err}");
+                        }
+                        ChatStream::Cancelled => {
+                            println!();
+                            println!("cancelled!");
+                        }
+                        ChatStream::ChatDone(stats) => {
                             println!("🤖");
                             println!("DONE!");
+                            println!(
+                                "{} prompt + {} generated tokens, {}ms + {}ms ({:.2} token/s)",
+                                stats.prompt_tokens,
+                                stats.generated_tokens,
+                                stats.prompt_ms,
+                                stats.generation_ms,
+                                stats.tokens_per_second
+                            );
                         }
                     }
                 }
@@ -197,7 +275,7 @@ async fn main() -> anyhow::Result<ExitCode> {
                 models::indexed_document::get_document_details(&db, identifier).await?;
 
             if let Some(doc_details) = doc_details {
-                let schema = DocFields::as_schema();
+                let schema = schema_for_languages(&config.user_settings.index_languages);
                 let index = Searcher::with_index(
                     &IndexBackend::LocalPath(config.index_dir()),
                     schema,
@@ -205,21 +283,84 @@ async fn main() -> anyhow::Result<ExitCode> {
                 )
                 .expect("Unable to open index.");
 
-                let embedding_api = EmbeddingApi::new(config.embedding_model_dir()).unwrap();
+                let embedding_api = EmbeddingApi::new(
+                    config
+                        .user_settings
+                        .embedding_settings
+                        .model_root(&config.embedding_model_dir()),
+                    SegmentationConfig {
+                        target_tokens: config.user_settings.embedding_settings.segment_tokens,
+                        overlap_tokens: config
+                            .user_settings
+                            .embedding_settings
+                            .segment_overlap_tokens,
+                        split_on_boundaries: config
+                            .user_settings
+                            .embedding_settings
+                            .split_on_boundaries,
+                    },
+                    config.user_settings.embedding_settings.embedding_dimensions,
+                    config.user_settings.embedding_settings.device,
+                    config.user_settings.embedding_settings.force_cpu,
+                )
+                .unwrap();
                 if let Ok(embeddings) = embedding_api.embed(
                     &question,
                     spyglass_model_interface::embedding_api::EmbeddingContentType::Query,
                 ) {
                     if let Some(embedding) = embeddings.first() {
-                        if let Ok(mut segments) = vec_documents::get_context_for_doc(
+                        let settings = &config.user_settings.embedding_settings;
+
+                        // Stored vectors were generated w/ a different
+                        // `embedding_dimensions` setting; comparing them
+                        // against this query would be meaningless.
+                        if let Ok(Some(dims)) =
+                            models::vec_to_indexed::stored_dims(&db, &settings.model_id()).await
+                        {
+                            if dims as usize != embedding.embedding.len() {
+                                return Err(anyhow!(
+                                    "Query embedding has {} dims but stored vectors have {}, wait for the re-embed to finish (or run the re-embed command)",
+                                    embedding.embedding.len(),
+                                    dims
+                                ));
+                            }
+                        }
+
+                        let max_distance = if settings.context_max_distance > 0.0 {
+                            Some(settings.context_max_distance)
+                        } else {
+                            None
+                        };
+
+                        if let Ok(segments) = vec_documents::get_context_for_doc(
                             &db,
                             doc_details.0.id,
                             &embedding.embedding,
+                            settings.context_top_k.max(1),
+                            max_distance,
                         )
                         .await
                         {
-                            let _ = segments.split_off(2.min(segments.len()));
-                            let context = concat_context(&segments, &index).await;
+                            println!("## Context Segments ##");
+                            for segment in &segments {
+                                println!(
+                                    "distance {:.3}\tchars {}..{}",
+                                    segment.distance, segment.segment_start, segment.segment_end
+                                );
+                            }
+
+                            let context = if segments.is_empty() {
+                                // Nothing within the distance threshold,
+                                // fall back to the document description.
+                                println!("No segments within the distance threshold, using the document description.");
+                                index
+                                    .get(&doc_details.0.doc_id)
+                                    .await
+                                    .map(|doc| doc.description)
+                                    .unwrap_or_default()
+                            } else {
+                                libspyglass::llm::concat_context(&segments, &index).await
+                            };
                             let prompt = LlmSession {
                                     messages: vec![
                                         ChatMessage {
@@ -235,19 +376,37 @@ async fn main() -> anyhow::Result<ExitCode> {
                                             content: format!("Here is my question: {}", question),
                                         },
                                     ],
+                                    params: GenerationParams::default(),
                                 };
 
-                            match LlmClient::new(
-                                config
-                                    .llm_model_dir()
-                                    .join("llama3")
-                                    .join("Llama-3.2-3B-Instruct.Q5_K_M.gguf"),
-                            ) {
-                                Ok(mut client) => {
-                                    client.chat(&prompt, Some(tx)).await?;
+                            // Cancel generation on ctrl-c instead of leaving
+                            // the CPU pinned until the token budget runs out.
+                            let (cancel_tx, cancel_rx) = watch::channel(false);
+                            tokio::spawn(async move {
+                                if tokio::signal::ctrl_c().await.is_ok() {
+                                    let _ = cancel_tx.send(true);
                                 }
-                                Err(error) => {
-                                    log::error!("Error loading model {error}");
+                            });
+
+                            let llm_settings = &config.user_settings.llm_settings;
+                            if let Some(remote_url) = &llm_settings.remote_url {
+                                let mut client = RemoteClient::new(
+                                    remote_url,
+                                    llm_settings.remote_api_key.clone(),
+                                    llm_settings.remote_model.clone(),
+                                );
+                                client.chat(&prompt, Some(tx), Some(cancel_rx)).await?;
+                            } else {
+                                match LlmClient::with_template(
+                                    llm_settings.gguf_path(&config.llm_model_dir()),
+                                    llm_settings.prompt_template.clone(),
+                                ) {
+                                    Ok(mut client) => {
+                                        client.chat(&prompt, Some(tx), Some(cancel_rx)).await?;
+                                    }
+                                    Err(error) => {
+                                        log::error!("Error loading model {error}");
+                                    }
                                 }
                             }
                         }
@@ -270,7 +429,7 @@ async fn main() -> anyhow::Result<ExitCode> {
                 }
             };
 
-            let schema = DocFields::as_schema();
+            let schema = schema_for_languages(&config.user_settings.index_languages);
             let index =
                 Searcher::with_index(&IndexBackend::LocalPath(config.index_dir()), schema, true)
                     .expect("Unable to open index.");
@@ -306,6 +465,64 @@ async fn main() -> anyhow::Result<ExitCode> {
                 }
             }
         }
+        Command::LensCheck { path } => {
+            let target = path.unwrap_or_else(|| config.lenses_dir());
+
+            let mut files = Vec::new();
+            if target.is_dir() {
+                for entry in (std::fs::read_dir(&target)?).flatten() {
+                    let path = entry.path();
+                    if path.is_file() && path.extension().unwrap_or_default() == "ron" {
+                        files.push(path);
+                    }
+                }
+                files.sort();
+            } else {
+                files.push(target.clone());
+            }
+
+            if files.is_empty() {
+                println!("No lens files found in {}", target.display());
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let mut num_errors = 0;
+            let mut names: HashMap<String, PathBuf> = HashMap::new();
+            for file in &files {
+                let check = libspyglass::task::lens::check_lens_file(file);
+
+                let mut errors = check.errors;
+                if let Some(name) = &check.name {
+                    if let Some(other) = names.insert(name.clone(), file.clone()) {
+                        errors.push(format!(
+                            "duplicate lens name \"{name}\", also used by {}",
+                            other.display()
+                        ));
+                    }
+                }
+
+                if errors.is_empty() && check.warnings.is_empty() {
+                    println!("{}: OK", file.display());
+                } else {
+                    println!("{}:", file.display());
+                    for error in &errors {
+                        println!("  error: {error}");
+                    }
+                    for warning in &check.warnings {
+                        println!("  warning: {warning}");
+                    }
+                }
+
+                num_errors += errors.len();
+            }
+
+            if num_errors > 0 {
+                println!("{num_errors} error(s) found");
+                return Ok(ExitCode::FAILURE);
+            }
+
+            println!("All lens files check out");
+        }
         Command::LoadArchive { name, archive_path } => {
             if !archive_path.exists() {
                 eprintln!("{} does not exist!", archive_path.display());
@@ -315,92 +532,311 @@ async fn main() -> anyhow::Result<ExitCode> {
             let config = Config::new();
             let state = AppState::new(&config, false).await;
 
-            let lens = shared::config::LensConfig {
-                author: "spyglass-search".into(),
-                name: name.clone(),
-                label: name,
-                ..Default::default()
-            };
+            // Bundles written by export-lens carry their own config; bare
+            // parsed caches fall back to a stub lens w/ the provided name.
+            if libspyglass::export::read_archive_lens(&archive_path).is_some() {
+                match libspyglass::export::import_lens_archive(&state, &archive_path).await {
+                    Ok(lens) => {
+                        println!(
+                            "Imported lens \"{}\" from {}",
+                            lens.name,
+                            archive_path.display()
+                        );
+                    }
+                    Err(err) => {
+                        eprintln!("Unable to import archive: {err}");
+                        return Err(anyhow!("Unable to import archive"));
+                    }
+                }
+            } else {
+                let lens = shared::config::LensConfig {
+                    author: "spyglass-search".into(),
+                    name: name.clone(),
+                    label: name,
+                    ..Default::default()
+                };
 
-            process_update(state.clone(), &lens, archive_path, true).await;
-            let _ = state.index.save().await;
+                process_update(state.clone(), &lens, archive_path, true).await;
+                let _ = state.index.save().await;
+            }
         }
-    }
-
-    Ok(ExitCode::SUCCESS)
-}
-
-#[allow(dead_code)]
-pub async fn concat_context(distances: &[DocDistance], searcher: &Searcher) -> String {
-    let mut map = HashMap::<String, usize>::new();
-    let mut sorted: Vec<Vec<&DocDistance>> = Vec::new();
-    // documents are already ordered now we just want to group documents by
-    // uuid incase there are multiple results per document
-    for distance in distances {
-        match map.get(&distance.doc_id) {
-            Some(index) => {
-                if let Some(vec) = sorted.get_mut(*index) {
-                    vec.push(distance);
+        Command::ExportLens { name, archive_path } => {
+            let state = AppState::new(&config, false).await;
+            match libspyglass::export::export_lens(&state, &name, &archive_path).await {
+                Ok(result) => {
+                    println!(
+                        "Exported {} documents to {} ({} bytes)",
+                        result.num_docs,
+                        result.archive_path.display(),
+                        result.bytes_written
+                    );
+                }
+                Err(err) => {
+                    eprintln!("Unable to export lens: {err}");
+                    return Err(anyhow!("Unable to export lens"));
                 }
             }
-            None => {
-                let index = sorted.len();
-                sorted.push(vec![distance]);
-                map.insert(distance.doc_id.clone(), index);
+        }
+        Command::Backup { archive_path } => {
+            let state = AppState::new(&config, false).await;
+            match libspyglass::backup::create_backup(&state, &archive_path).await {
+                Ok(result) => {
+                    println!(
+                        "Backup written to {} ({} bytes)",
+                        result.archive_path.display(),
+                        result.bytes_written
+                    );
+                }
+                Err(err) => {
+                    eprintln!("Unable to create backup: {err}");
+                    return Err(anyhow!("Unable to create backup"));
+                }
             }
         }
-    }
+        Command::Restore {
+            archive_path,
+            force,
+        } => {
+            if !archive_path.exists() {
+                eprintln!("{} does not exist!", archive_path.display());
+                return Err(anyhow!("ARCHIVE_PATH does not exist"));
+            }
 
-    let mut context_text = "Context for all documents\n".to_string();
-    for grouped_results in sorted {
-        let first = grouped_results.first();
-        if let Some(first) = first {
-            context_text.push_str(
-                "\n\n-----------------------------------------------------------------\n\n",
-            );
-            context_text.push_str(&format!(
-                "Document UUID: {} URL: {} \n\n ",
-                first.doc_id, first.url,
-            ));
+            match libspyglass::backup::restore_backup(&config, &archive_path, force).await {
+                Ok(num_docs) => {
+                    println!(
+                        "Restored {num_docs} docs from {}",
+                        archive_path.display()
+                    );
+                }
+                Err(err) => {
+                    eprintln!("Unable to restore backup: {err}");
+                    return Err(anyhow!("Unable to restore backup"));
+                }
+            }
         }
+        Command::Reindex => {
+            let state = AppState::new(&config, false).await;
+            match libspyglass::reindex::reindex(&state).await {
+                Ok(num_docs) => {
+                    println!("Reindexed {num_docs} docs. Restart spyglass to pick up the new index.");
+                }
+                Err(err) => {
+                    eprintln!("Unable to reindex: {err}");
+                    return Err(anyhow!("Unable to reindex"));
+                }
+            }
+        }
+        Command::EmbeddingCacheStats => {
+            let db = models::create_connection(&config, false).await?;
+            match models::embedding_queue::cache_stats(&db).await {
+                Ok(Some(stats)) if stats.completed > 0 => {
+                    let hit_rate = stats.cache_hits as f64 / stats.completed as f64 * 100.0;
+                    println!(
+                        "{} of {} completed embedding jobs reused cached vectors ({hit_rate:.1}% hit rate)",
+                        stats.cache_hits, stats.completed
+                    );
+                }
+                Ok(_) => println!("No completed embedding jobs found."),
+                Err(err) => {
+                    eprintln!("Unable to read embedding stats: {err}");
+                    return Err(anyhow!("Unable to read embedding stats"));
+                }
+            }
+        }
+        Command::Reembed { lens, since } => {
+            let since = match &since {
+                Some(date) => match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) => Some(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()),
+                    Err(_) => {
+                        eprintln!("Invalid --since date {date}, expected YYYY-MM-DD");
+                        return Err(anyhow!("Invalid --since date"));
+                    }
+                },
+                None => None,
+            };
+
+            let state = AppState::new(&config, false).await;
+            if state.embedding_api.load_full().is_none() {
+                eprintln!("Embedding model not loaded. Enable embeddings in your settings & make sure the model files exist.");
+                return Err(anyhow!("Embedding model not loaded"));
+            }
+
+            // Collect the documents to re-embed.
+            let mut query = models::indexed_document::Entity::find();
+            if let Some(since) = since {
+                query = query.filter(models::indexed_document::Column::UpdatedAt.gte(since));
+            }
+            if let Some(lens) = &lens {
+                let lens_ids = models::indexed_document::find_by_lens(state.db.clone(), lens)
+                    .await?
+                    .iter()
+                    .map(|doc| doc.id)
+                    .collect::<Vec<i64>>();
+                if lens_ids.is_empty() {
+                    println!("No documents found for lens \"{lens}\"");
+                    return Ok(ExitCode::SUCCESS);
+                }
+
+                query = query.filter(models::indexed_document::Column::Id.is_in(lens_ids));
+            }
+
+            let docs = query.all(&state.db).await?;
+            if docs.is_empty() {
+                println!("No matching documents found.");
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            // Wipe the stored vectors & requeue, pulling content from the
+            // index like the embedding worker does.
+            println!("Queueing {} documents for re-embedding...", docs.len());
+            for chunk in docs.chunks(1000) {
+                let ids = chunk.iter().map(|doc| doc.id).collect::<Vec<i64>>();
+                let _ = models::vec_to_indexed::delete_all_for_documents(&state.db, &ids).await?;
+
+                let doc_ids = chunk
+                    .iter()
+                    .map(|doc| doc.doc_id.clone())
+                    .collect::<Vec<String>>();
+                let indexed = state.index.search_by_query(None, Some(doc_ids), &[], &[]).await;
+                let mut content_map: HashMap<String, String> = HashMap::new();
+                for (_, result) in indexed {
+                    content_map.insert(result.doc_id.to_owned(), result.content.to_owned());
+                }
 
-        for (i, doc_distance) in grouped_results.iter().enumerate() {
-            if let Some(context) = pull_context(doc_distance, searcher).await {
-                context_text.push_str(&format!(
-                    "Context Segment -- #{} -- score #{}\n\n Context Text: {} \n\n",
-                    i, doc_distance.distance, context
-                ));
+                let to_add = chunk
+                    .iter()
+                    .filter_map(|doc| {
+                        content_map.get(&doc.doc_id).map(|content| {
+                            let mut model = models::embedding_queue::ActiveModel::new();
+                            model.document_id = Set(doc.doc_id.clone());
+                            model.indexed_document_id = Set(doc.id);
+                            model.content = Set(Some(content.clone()));
+                            model
+                        })
+                    })
+                    .collect::<Vec<models::embedding_queue::ActiveModel>>();
+                if !to_add.is_empty() {
+                    models::embedding_queue::add_to_queue(&state.db, &to_add).await?;
+                }
+            }
+
+            // Jobs left in `Processing` by an interrupted run would block
+            // the queue, put them back.
+            let reset = models::embedding_queue::reset_processing(&state.db).await?;
+            if reset > 0 {
+                println!("Requeued {reset} jobs from an interrupted run");
+            }
+
+            let total = match models::embedding_queue::queue_counts(&state.db).await? {
+                Some(counts) => counts.remaining.max(0) as usize,
+                None => 0,
+            };
+
+            let start = std::time::Instant::now();
+            let mut processed: usize = 0;
+            loop {
+                let jobs = models::embedding_queue::check_for_embedding_jobs(&state.db, 8).await?;
+                if jobs.is_empty() {
+                    break;
+                }
+
+                let job_ids = jobs.iter().map(|job| job.id).collect::<Vec<i64>>();
+                processing_embedding_batch(state.clone(), job_ids).await;
+                processed += jobs.len();
+
+                let width: usize = 40;
+                let filled = if total > 0 {
+                    width * processed.min(total) / total
+                } else {
+                    width
+                };
+                print!(
+                    "\r[{}{}] {}/{}",
+                    "#".repeat(filled),
+                    "-".repeat(width - filled),
+                    processed.min(total),
+                    total
+                );
+                std::io::stdout().flush()?;
+            }
+            println!();
+
+            let elapsed = start.elapsed().as_secs_f64();
+            if processed > 0 && elapsed > 0.0 {
+                println!(
+                    "Re-embedded {processed} documents in {elapsed:.1}s ({:.1} docs/s)",
+                    processed as f64 / elapsed
+                );
+            } else {
+                println!("Nothing to re-embed.");
             }
         }
-    }
-    context_text
-}
+        Command::SimilarDocuments { id_or_url, limit } => {
+            let db = models::create_connection(&config, false).await?;
 
-#[allow(dead_code)]
-async fn pull_context(distance: &DocDistance, searcher: &Searcher) -> Option<String> {
-    if let Some(document) = searcher.get(&distance.doc_id).await {
-        if distance.segment_start == 0
-            && distance.segment_end == ((document.content.len() - 1) as i64)
-        {
-            Some(document.content)
-        } else {
-            let segment = document
-                .content
-                .trim()
-                .char_indices()
-                .filter_map(|(i, c)| {
-                    let index = i as i64;
-                    if index >= distance.segment_start && index < distance.segment_end {
-                        Some(c)
-                    } else {
-                        None
+            let identifier = if id_or_url.contains("://") {
+                DocumentIdentifier::Url(&id_or_url)
+            } else {
+                DocumentIdentifier::DocId(&id_or_url)
+            };
+
+            let doc_details =
+                models::indexed_document::get_document_details(&db, identifier).await?;
+
+            match doc_details {
+                Some((doc, _)) => {
+                    let embedding =
+                        vec_documents::average_embedding_for_document(&db, doc.id).await?;
+                    match embedding {
+                        Some(embedding) => {
+                            // Ask for one extra result since the source
+                            // document is its own nearest neighbor.
+                            let distances = vec_documents::get_document_distance(
+                                &db,
+                                &[],
+                                &embedding,
+                                limit + 1,
+                            )
+                            .await?;
+
+                            println!("## Similar Documents ##");
+                            for distance in distances
+                                .iter()
+                                .filter(|distance| distance.doc_id != doc.doc_id)
+                                .take(limit as usize)
+                            {
+                                let similarity = 1.0 / (1.0 + distance.distance);
+                                println!(
+                                    "{similarity:.3}\t{}\t{}",
+                                    distance.doc_id, distance.url
+                                );
+                            }
+                        }
+                        None => {
+                            println!("No embeddings stored for document {}", doc.doc_id);
+                        }
                     }
-                })
-                .collect::<String>();
+                }
+                None => {
+                    println!("No document found for {}", id_or_url);
+                }
+            }
+        }
+        Command::StopWords => {
+            let overrides = Config::load_stop_word_overrides();
+            let filter = match &overrides {
+                Some(overrides) => StopWordFilter::with_overrides(overrides),
+                None => StopWordFilter::default(),
+            };
 
-            Some(segment)
+            println!("## Effective Stop Words ##");
+            println!("Overrides file: {}", Config::stop_words_file().display());
+            for word in filter.words() {
+                println!("{word}");
+            }
         }
-    } else {
-        None
     }
+
+    Ok(ExitCode::SUCCESS)
 }
@@ -3,35 +3,46 @@ use directories::UserDirs;
 use entities::get_library_stats;
 use entities::models::crawl_queue::{CrawlStatus, EnqueueSettings};
 use entities::models::lens::LensType;
-use entities::models::tag::TagType;
+use entities::models::tag::{TagType, TagValue};
 use entities::models::{
-    bootstrap_queue, connection::get_all_connections, crawl_queue, fetch_history, indexed_document,
-    lens,
+    bootstrap_queue, chat_message, chat_session, connection::get_all_connections, crawl_queue,
+    document_tag, embedding_queue, fetch_history, indexed_document, lens, tag, vec_documents,
+    vec_to_indexed,
 };
-use entities::sea_orm::{prelude::*, sea_query};
+use entities::sea_orm::{prelude::*, sea_query, Condition, Set};
 use jsonrpsee::core::RpcResult;
 use libnetrunner::parser::html::html_to_text;
 use libspyglass::connection::{self, credentials, handle_authorize_connection};
 use libspyglass::crawler::CrawlResult;
-use libspyglass::documents::process_crawl_results;
+use libspyglass::documents::{process_crawl_results, update_tags, TagModification};
 use libspyglass::filesystem;
+use libspyglass::model_files;
 use libspyglass::state::AppState;
-use libspyglass::task::{AppPause, UserSettingsChange};
+use libspyglass::task::{self, AppPause, CollectTask, ManagerCommand, UserSettingsChange};
 use num_format::{Locale, ToFormattedString};
 use shared::config::{self, Config, UserSettings};
-use shared::llm::{ChatMessage, ChatStream, LlmSession};
+use shared::llm::{ChatMessage, ChatRole, ChatStream, GenerationParams, LlmSession};
 use shared::metrics::Event;
 use shared::request::{BatchDocumentRequest, RawDocType, RawDocumentRequest};
 use shared::response::{
-    AppStatus, DefaultIndices, InstallStatus, LensResult, LibraryStats, ListConnectionResult,
-    PluginResult, SupportedConnection, UserConnection,
+    AppStatus, AskLibraryResult, BackupResult, ChatCitation, ChatSessionResult, DefaultIndices,
+    DocumentDetails, InstallStatus, LensExportResult, LensResult, LibraryStats,
+    ListConnectionResult, LlmModelResult, OptimizeResult, PluginResult, SearchResult,
+    SuggestedLensResult, SupportedConnection, TagCountResult, UserConnection,
 };
-use spyglass_llm::LlmClient;
-use spyglass_rpc::{server_error, RpcEvent, RpcEventType};
-use spyglass_searcher::WriteTrait;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use spyglass_llm::budget::{budget_prompt, estimate_tokens, DEFAULT_CONTEXT_LENGTH};
+use spyglass_llm::{remote::RemoteClient, CancelToken, LlmBackend, LlmClient};
+use spyglass_model_interface::embedding_api::EmbeddingContentType;
+use spyglass_rpc::{
+    server_error, IndexOptimizationPayload, ReindexPayload, RpcEvent, RpcEventType,
+    TaskProgressPayload,
+};
+use spyglass_searcher::{DeleteQuery, SearchTrait, WriteTrait};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::instrument;
 use url::Url;
 
@@ -63,6 +74,7 @@ pub async fn add_document_batch(state: &AppState, req: &BatchDocumentRequest) ->
         force_allow: true,
         is_recrawl: true,
         tags,
+        initiated_by: req.request_id.clone(),
         ..Default::default()
     };
 
@@ -129,8 +141,24 @@ pub async fn add_raw_document(state: &AppState, req: &RawDocumentRequest) -> Rpc
 
             // Add to index
             log::debug!("adding to index: {} - {:?}", crawl.url, crawl.tags);
+            let crawl_url = crawl.url.clone();
             if let Err(err) = process_crawl_results(state, &[crawl], &Vec::new()).await {
                 log::error!("Unable to add from webext: {}", err);
+            } else if req.request_id.is_some() {
+                // Let the client know their request has been processed.
+                state
+                    .publish_event(&RpcEvent {
+                        event_type: RpcEventType::TaskProgress,
+                        payload: Some(
+                            serde_json::to_value(&TaskProgressPayload {
+                                request_id: req.request_id.clone(),
+                                url: crawl_url,
+                                status: "Completed".into(),
+                            })
+                            .unwrap_or_default(),
+                        ),
+                    })
+                    .await;
             }
         }
         // No need to process anything, we can add this directly to the index.
@@ -144,6 +172,7 @@ pub async fn add_raw_document(state: &AppState, req: &RawDocumentRequest) -> Rpc
                 force_allow: true,
                 is_recrawl: true,
                 tags,
+                initiated_by: req.request_id.clone(),
                 ..Default::default()
             };
 
@@ -165,6 +194,99 @@ pub async fn add_raw_document(state: &AppState, req: &RawDocumentRequest) -> Rpc
     Ok(())
 }
 
+/// Max number of urls accepted per `are_documents_indexed` call.
+const INDEXED_LOOKUP_LIMIT: usize = 500;
+
+/// Equivalent variants of a normalized url: both schemes & w/ & w/o a
+/// trailing slash, so the lookup matches however the page was indexed.
+fn url_variants(url: &Url) -> Vec<String> {
+    let mut variants: HashSet<String> = HashSet::new();
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return vec![url.to_string()];
+    }
+
+    let mut url = url.clone();
+    for scheme in ["http", "https"] {
+        let _ = url.set_scheme(scheme);
+        variants.insert(url.to_string());
+
+        // Only toggle the trailing slash when there's no query to mangle.
+        if url.query().is_none() {
+            let path = url.path().to_string();
+            let mut alt = url.clone();
+            if path.ends_with('/') && path != "/" {
+                alt.set_path(path.trim_end_matches('/'));
+            } else if !path.ends_with('/') {
+                alt.set_path(&format!("{path}/"));
+            }
+            variants.insert(alt.to_string());
+        }
+    }
+
+    variants.into_iter().collect()
+}
+
+/// Batch form of `is_document_indexed`: resolves a whole set of urls w/ a
+/// single chunked query instead of one RPC round-trip per url. Urls are
+/// normalized the same way enqueue normalizes them so `http`/`https` &
+/// trailing-slash variants resolve to the indexed copy.
+#[instrument(skip(state, urls))]
+pub async fn are_documents_indexed(
+    state: AppState,
+    urls: Vec<String>,
+) -> RpcResult<HashMap<String, bool>> {
+    if urls.len() > INDEXED_LOOKUP_LIMIT {
+        return Err(server_error(
+            format!(
+                "Batch too large: {} urls, max is {INDEXED_LOOKUP_LIMIT} per call",
+                urls.len()
+            ),
+            None,
+        ));
+    }
+
+    // Map each variant back to the url(s) the caller sent; different
+    // requested urls can share a variant.
+    let mut candidates: HashMap<String, Vec<String>> = HashMap::new();
+    let mut results: HashMap<String, bool> = HashMap::new();
+    for raw in &urls {
+        results.insert(raw.clone(), false);
+        if let Ok(mut url) = Url::parse(raw) {
+            crawl_queue::normalize_url(&mut url);
+            for variant in url_variants(&url) {
+                candidates.entry(variant).or_default().push(raw.clone());
+            }
+        }
+    }
+
+    let variants: Vec<String> = candidates.keys().cloned().collect();
+    for chunk in variants.chunks(INDEXED_LOOKUP_LIMIT) {
+        let found = indexed_document::Entity::find()
+            .filter(
+                Condition::any()
+                    // checks against raw urls that have been added
+                    .add(indexed_document::Column::Url.is_in(chunk.to_vec()))
+                    // checks against URLs gathered through integrations.
+                    .add(indexed_document::Column::OpenUrl.is_in(chunk.to_vec())),
+            )
+            .all(&state.db)
+            .await
+            .map_err(|err| server_error(format!("Unable to query db: {err}"), None))?;
+
+        for doc in found {
+            for hit in [Some(doc.url), doc.open_url].into_iter().flatten() {
+                if let Some(originals) = candidates.get(&hit) {
+                    for original in originals {
+                        results.insert(original.clone(), true);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
 #[instrument(skip(state))]
 pub async fn authorize_connection(state: AppState, api_id: String) -> RpcResult<()> {
     log::debug!("authorizing <{}>", api_id);
@@ -189,11 +311,22 @@ pub async fn authorize_connection(state: AppState, api_id: String) -> RpcResult<
 #[instrument(skip(state))]
 pub async fn app_status(state: AppState) -> RpcResult<AppStatus> {
     // Grab details about index
-    let index = state.index;
-    let reader = index.reader.searcher();
+    let reader = state.index.reader.searcher();
+
+    let counts = embedding_queue::queue_counts(&state.db)
+        .await
+        .unwrap_or_default();
 
     Ok(AppStatus {
         num_docs: reader.num_docs(),
+        embeddings_queued: counts
+            .as_ref()
+            .map(|counts| counts.remaining.max(0) as u64)
+            .unwrap_or_default(),
+        embeddings_completed: counts
+            .as_ref()
+            .map(|counts| counts.completed.max(0) as u64)
+            .unwrap_or_default(),
     })
 }
 
@@ -209,20 +342,266 @@ pub async fn delete_document(state: AppState, id: String) -> RpcResult<()> {
     Ok(())
 }
 
+/// Export the index, database & lens files into a portable archive. See
+/// `backup::create_backup` for what's included.
 #[instrument(skip(state))]
-pub async fn chat_completion(state: AppState, session: &LlmSession) -> RpcResult<ChatMessage> {
-    let mut llm = state.llm.lock().await;
-    let client = match llm.as_mut() {
-        Some(client) => client,
+pub async fn backup(state: AppState, path: String) -> RpcResult<BackupResult> {
+    match libspyglass::backup::create_backup(&state, Path::new(&path)).await {
+        Ok(result) => Ok(result),
+        Err(err) => {
+            log::error!("Unable to create backup: {}", err);
+            Err(server_error(err.to_string(), None))
+        }
+    }
+}
+
+/// Export a lens & its indexed documents into a shareable archive. See
+/// `export::export_lens` for the bundle layout.
+#[instrument(skip(state))]
+pub async fn export_lens(
+    state: AppState,
+    name: String,
+    path: String,
+) -> RpcResult<LensExportResult> {
+    match libspyglass::export::export_lens(&state, &name, Path::new(&path)).await {
+        Ok(result) => Ok(result),
+        Err(err) => {
+            log::error!("Unable to export lens: {}", err);
+            Err(server_error(err.to_string(), None))
+        }
+    }
+}
+
+/// Merge index segments & garbage collect files no longer referenced by the
+/// index, reporting how much was reclaimed.
+#[instrument(skip(state))]
+pub async fn optimize_index(state: AppState) -> RpcResult<OptimizeResult> {
+    if state.readonly_mode || state.index.is_readonly() {
+        return Err(server_error("Index is in read-only mode".into(), None));
+    }
+
+    let index_dir = state.config.index_dir();
+    let bytes_before = spyglass_searcher::utils::index_size_bytes(&index_dir);
+
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::IndexOptimization,
+            payload: Some(
+                serde_json::to_value(&IndexOptimizationPayload::Started).unwrap_or_default(),
+            ),
+        })
+        .await;
+
+    match state.index.optimize().await {
+        Ok((before, after)) => {
+            let result = OptimizeResult {
+                segments_before: before as u32,
+                segments_after: after as u32,
+                bytes_reclaimed: bytes_before
+                    .saturating_sub(spyglass_searcher::utils::index_size_bytes(&index_dir)),
+            };
+
+            state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::IndexOptimization,
+                    payload: Some(
+                        serde_json::to_value(&IndexOptimizationPayload::Finished {
+                            segments_before: result.segments_before,
+                            segments_after: result.segments_after,
+                            bytes_reclaimed: result.bytes_reclaimed,
+                        })
+                        .unwrap_or_default(),
+                    ),
+                })
+                .await;
+
+            Ok(result)
+        }
+        Err(err) => {
+            log::error!("Unable to optimize index: {}", err);
+            state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::IndexOptimization,
+                    payload: Some(
+                        serde_json::to_value(&IndexOptimizationPayload::Error {
+                            msg: err.to_string(),
+                        })
+                        .unwrap_or_default(),
+                    ),
+                })
+                .await;
+
+            Err(server_error(err.to_string(), None))
+        }
+    }
+}
+
+/// Rebuild the index from the database & cached content, see
+/// `libspyglass::reindex`.
+#[instrument(skip(state))]
+pub async fn reindex(state: AppState) -> RpcResult<u64> {
+    if state.readonly_mode || state.index.is_readonly() {
+        return Err(server_error("Index is in read-only mode".into(), None));
+    }
+
+    match libspyglass::reindex::reindex(&state).await {
+        Ok(num_docs) => Ok(num_docs),
+        Err(err) => {
+            log::error!("Unable to reindex: {}", err);
+            state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::Reindex,
+                    payload: Some(
+                        serde_json::to_value(&ReindexPayload::Error {
+                            msg: err.to_string(),
+                        })
+                        .unwrap_or_default(),
+                    ),
+                })
+                .await;
+
+            Err(server_error(err.to_string(), None))
+        }
+    }
+}
+
+/// Asks a question across the whole library. The question is embedded, the
+/// closest segments across all documents (optionally restricted to `lenses`)
+/// are assembled into a context prompt & the answer is streamed out as
+/// `RpcEventType::ChatStream` events.
+#[instrument(skip(state))]
+pub async fn ask_library(
+    state: AppState,
+    question: String,
+    lenses: Vec<String>,
+) -> RpcResult<AskLibraryResult> {
+    let settings = state.user_settings.load();
+
+    // Surface a clear error up front instead of failing mid-generation when
+    // no chat backend is usable.
+    if settings.llm_settings.remote_url.is_none() {
+        let gguf_path = settings.llm_settings.gguf_path(&state.config.llm_model_dir());
+        if !gguf_path.exists() {
+            return Err(server_error(
+                format!("LLM model not installed (expected {})", gguf_path.display()),
+                None,
+            ));
+        }
+    }
+
+    let embedding_api = state.embedding_api.load_full();
+    let embedding_api = match embedding_api.as_ref() {
+        Some(api) => api,
+        None => {
+            return Err(server_error(
+                "Embeddings are not enabled, no context can be retrieved".into(),
+                None,
+            ));
+        }
+    };
+
+    let embeddings = embedding_api
+        .embed(&question, EmbeddingContentType::Query)
+        .map_err(|err| server_error(err.to_string(), None))?;
+    let embedding = match embeddings.first() {
+        Some(embedding) => embedding,
         None => {
-            let client =
-                LlmClient::new("assets/models/llm/llama3/Llama-3.2-3B-Instruct.Q5_K_M.gguf".into())
-                    .map_err(|e| server_error(e.to_string(), None))?;
-            *llm = Some(client);
-            llm.as_mut().unwrap()
+            return Err(server_error("Unable to embed question".into(), None));
         }
     };
 
+    // Stored vectors generated w/ a different `embedding_dimensions` setting
+    // can't be compared against this query.
+    if let Ok(Some(dims)) =
+        vec_to_indexed::stored_dims(&state.db, &settings.embedding_settings.model_id()).await
+    {
+        if dims as usize != embedding.embedding.len() {
+            return Err(server_error(
+                format!(
+                    "Query embedding has {} dims but stored vectors have {}, wait for the re-embed to finish",
+                    embedding.embedding.len(),
+                    dims
+                ),
+                None,
+            ));
+        }
+    }
+
+    let lens_ids = tag::Entity::find()
+        .filter(tag::Column::Label.eq(TagType::Lens.to_string()))
+        .filter(tag::Column::Value.is_in(lenses))
+        .all(&state.db)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|model| model.id as u64)
+        .collect::<Vec<u64>>();
+
+    let top_k = settings.embedding_settings.context_top_k.max(1) as u32;
+    let distances =
+        vec_documents::get_document_distance(&state.db, &lens_ids, &embedding.embedding, top_k)
+            .await
+            .map_err(|err| server_error(err.to_string(), None))?;
+
+    if distances.is_empty() {
+        return Err(server_error(
+            "No documents w/ stored embeddings matched the question".into(),
+            None,
+        ));
+    }
+
+    let context = libspyglass::llm::concat_context(&distances, &state.index).await;
+
+    let mut seen = HashSet::new();
+    let mut citations = Vec::new();
+    for distance in &distances {
+        if seen.insert(distance.doc_id.clone()) {
+            citations.push(ChatCitation {
+                doc_id: distance.doc_id.clone(),
+                url: distance.url.clone(),
+            });
+        }
+    }
+
+    let session = LlmSession {
+        messages: vec![
+            ChatMessage {
+                role: ChatRole::System,
+                content: "You are a helpful AI assistant that reviews possible relevant document context and answers questions about the documents".into(),
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: format!(
+                    "Here is the documents semantically related to the question:\n {}",
+                    context
+                ),
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: format!("Here is my question: {}", question),
+            },
+        ],
+        params: GenerationParams::default(),
+    };
+
+    let stream = chat_event_channel(&state);
+    let reply = chat_with_llm(&state, &session, stream, None).await?;
+
+    Ok(AskLibraryResult {
+        answer: reply.content,
+        citations,
+    })
+}
+
+#[instrument(skip(state))]
+pub async fn chat_completion(state: AppState, session: &LlmSession) -> RpcResult<ChatMessage> {
+    let stream = chat_event_channel(&state);
+    chat_with_llm(&state, session, stream, None).await
+}
+
+/// Bridges a chat token stream onto the RPC event bus as
+/// `RpcEventType::ChatStream` events.
+fn chat_event_channel(state: &AppState) -> tokio::sync::mpsc::Sender<ChatStream> {
     let (tx, mut rx) = tokio::sync::mpsc::channel::<ChatStream>(10);
     let state_clone = state.clone();
     tokio::spawn(async move {
@@ -234,21 +613,447 @@ pub async fn chat_completion(state: AppState, session: &LlmSession) -> RpcResult
                 })
                 .await;
 
-            if msg == ChatStream::ChatDone {
-                log::info!("finished streaming");
-                break;
+            match msg {
+                ChatStream::ChatDone(stats) => {
+                    state_clone
+                        .metrics
+                        .track(Event::ChatCompletion {
+                            prompt_tokens: stats.prompt_tokens,
+                            generated_tokens: stats.generated_tokens,
+                            prompt_ms: stats.prompt_ms,
+                            generation_ms: stats.generation_ms,
+                        })
+                        .await;
+                    log::info!("finished streaming");
+                    break;
+                }
+                ChatStream::Error(_) | ChatStream::Cancelled => {
+                    log::info!("finished streaming");
+                    break;
+                }
+                _ => {}
             }
         }
     });
+    tx
+}
+
+/// Loads the configured LLM backend into `state.llm` if it hasn't been
+/// loaded yet.
+async fn ensure_llm_backend(state: &AppState) -> RpcResult<()> {
+    let mut llm = state.llm.lock().await;
+    if llm.is_none() {
+        let settings = state.user_settings.load();
+        let client: Box<dyn LlmBackend> =
+            if let Some(remote_url) = &settings.llm_settings.remote_url {
+                Box::new(RemoteClient::new(
+                    remote_url,
+                    settings.llm_settings.remote_api_key.clone(),
+                    settings.llm_settings.remote_model.clone(),
+                ))
+            } else {
+                Box::new(
+                    LlmClient::with_template(
+                        settings.llm_settings.gguf_path(&state.config.llm_model_dir()),
+                        settings.llm_settings.prompt_template.clone(),
+                    )
+                    .map_err(|e| server_error(e.to_string(), None))?,
+                )
+            };
+        *llm = Some(client);
+    }
+
+    Ok(())
+}
+
+/// Runs `session` through the configured LLM backend, streaming tokens to
+/// `stream` & returning the assistant's reply. Flipping `cancel` ends
+/// generation early.
+pub async fn chat_with_llm(
+    state: &AppState,
+    session: &LlmSession,
+    stream: tokio::sync::mpsc::Sender<ChatStream>,
+    cancel: Option<CancelToken>,
+) -> RpcResult<ChatMessage> {
+    ensure_llm_backend(state).await?;
+
+    let mut llm = state.llm.lock().await;
+    match llm.as_mut() {
+        Some(client) => client
+            .chat(session, Some(stream), cancel)
+            .await
+            .map_err(|e| server_error(e.to_string(), None)),
+        None => Err(server_error("LLM backend is not loaded".into(), None)),
+    }
+}
 
-    let _ = client
-        .chat(session, Some(tx))
+/// Starts a persisted chat session about `doc_id`.
+#[instrument(skip(state))]
+pub async fn create_chat_session(state: AppState, doc_id: String) -> RpcResult<ChatSessionResult> {
+    let indexed = indexed_document::Entity::find()
+        .filter(indexed_document::Column::DocId.eq(doc_id.clone()))
+        .one(&state.db)
         .await
-        .map_err(|e| server_error(e.to_string(), None))?;
-    Ok(ChatMessage {
-        role: shared::llm::ChatRole::Assistant,
-        content: "hello".into(),
-    })
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    if indexed.is_none() {
+        return Err(server_error(format!("Document {} not found", doc_id), None));
+    }
+
+    prune_chat_sessions(&state).await;
+    let session = chat_session::create(&state.db, &doc_id)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    Ok(to_session_result(&state, &session).await)
+}
+
+/// Lists persisted chat sessions, most recently used first.
+#[instrument(skip(state))]
+pub async fn list_chat_sessions(state: AppState) -> RpcResult<Vec<ChatSessionResult>> {
+    prune_chat_sessions(&state).await;
+    let sessions = chat_session::list(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    let mut results = Vec::new();
+    for session in sessions {
+        results.push(to_session_result(&state, &session).await);
+    }
+    Ok(results)
+}
+
+/// Appends a user message to a session & runs the LLM w/ the accumulated
+/// history plus context retrieved for this turn. Both the user message & the
+/// assistant's reply are persisted so follow-up questions keep their history.
+#[instrument(skip(state, content))]
+pub async fn append_chat_message(
+    state: AppState,
+    session_id: i64,
+    content: String,
+) -> RpcResult<ChatMessage> {
+    let session = chat_session::Entity::find_by_id(session_id)
+        .one(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    let session = match session {
+        Some(session) => session,
+        None => {
+            return Err(server_error(
+                format!("Chat session {} not found", session_id),
+                None,
+            ));
+        }
+    };
+
+    let indexed = indexed_document::Entity::find()
+        .filter(indexed_document::Column::DocId.eq(session.doc_id.clone()))
+        .one(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    let indexed = match indexed {
+        Some(indexed) => indexed,
+        None => {
+            return Err(server_error(
+                format!("Document {} not found", session.doc_id),
+                None,
+            ));
+        }
+    };
+
+    let _ = chat_message::append(&state.db, session.id, "user", &content)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    // Context is re-retrieved each turn so follow-up questions pull segments
+    // relevant to *this* question, not the first one.
+    let segments = retrieve_chat_segments(&state, indexed.id, &session.doc_id, &content).await;
+
+    let history = chat_message::for_session(&state.db, session.id)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?
+        .iter()
+        .map(|msg| ChatMessage {
+            role: match msg.role.as_str() {
+                "system" => ChatRole::System,
+                "assistant" => ChatRole::Assistant,
+                _ => ChatRole::User,
+            },
+            content: msg.content.clone(),
+        })
+        .collect::<Vec<ChatMessage>>();
+
+    let system_prompt = "You are a helpful AI assistant that reviews possible relevant document context and answers questions about the documents";
+
+    // Fit the retrieved segments & accumulated history into the model's
+    // context window, leaving room for the reply.
+    ensure_llm_backend(&state).await?;
+    let context_length = state
+        .llm
+        .lock()
+        .await
+        .as_ref()
+        .and_then(|client| client.context_length())
+        .unwrap_or(DEFAULT_CONTEXT_LENGTH);
+
+    let params = GenerationParams::default();
+    let budgeted = budget_prompt(
+        context_length,
+        params.max_tokens,
+        estimate_tokens,
+        &[system_prompt],
+        &segments,
+        &history,
+    );
+
+    let mut messages = vec![ChatMessage {
+        role: ChatRole::System,
+        content: system_prompt.into(),
+    }];
+    if !budgeted.segments.is_empty() {
+        messages.push(ChatMessage {
+            role: ChatRole::User,
+            content: format!(
+                "Here is the documents semantically related to the question:\n {}",
+                budgeted.segments.join("")
+            ),
+        });
+    }
+    messages.extend(budgeted.history);
+
+    let stream = chat_event_channel(&state);
+    if !budgeted.dropped.is_empty() {
+        log::warn!(
+            "prompt over the context window, dropped: {}",
+            budgeted.dropped.join(", ")
+        );
+        let _ = stream
+            .send(ChatStream::ContextTruncated(budgeted.dropped))
+            .await;
+    }
+
+    let session_prompt = LlmSession { messages, params };
+    let reply = chat_with_llm(&state, &session_prompt, stream, None).await?;
+
+    if let Err(err) = chat_message::append(&state.db, session.id, "assistant", &reply.content).await
+    {
+        log::error!("Unable to save assistant reply: {err}");
+    }
+    if let Err(err) = chat_session::touch(&state.db, session.id).await {
+        log::error!("Unable to touch chat session: {err}");
+    }
+
+    Ok(reply)
+}
+
+/// Pulls the closest stored segments for `question` (ranked best first),
+/// falling back to the document description when embeddings are unavailable
+/// or nothing is within the distance threshold.
+async fn retrieve_chat_segments(
+    state: &AppState,
+    indexed_id: i64,
+    doc_id: &str,
+    question: &str,
+) -> Vec<String> {
+    let settings = state.user_settings.load();
+    if let Some(embedding_api) = state.embedding_api.load_full().as_ref() {
+        match embedding_api
+            .embed(question, EmbeddingContentType::Query)
+            .map(|embedding| embedding.first().map(|val| val.to_owned()))
+        {
+            Ok(Some(embedding)) => {
+                let max_distance = if settings.embedding_settings.context_max_distance > 0.0 {
+                    Some(settings.embedding_settings.context_max_distance)
+                } else {
+                    None
+                };
+
+                match vec_documents::get_context_for_doc(
+                    &state.db,
+                    indexed_id,
+                    &embedding.embedding,
+                    settings.embedding_settings.context_top_k.max(1),
+                    max_distance,
+                )
+                .await
+                {
+                    Ok(segments) if !segments.is_empty() => {
+                        return libspyglass::llm::context_segments(&segments, &state.index).await;
+                    }
+                    Ok(_) => {}
+                    Err(err) => log::error!("Unable to retrieve chat context: {err}"),
+                }
+            }
+            Ok(None) => {}
+            Err(err) => log::error!("Unable to embed chat question: {err}"),
+        }
+    }
+
+    state
+        .index
+        .get(doc_id)
+        .await
+        .map(|doc| vec![doc.description])
+        .unwrap_or_default()
+}
+
+/// Removes stale chat sessions when a retention period is configured.
+async fn prune_chat_sessions(state: &AppState) {
+    let retention = state.user_settings.load().llm_settings.chat_retention_days;
+    if let Some(days) = retention {
+        if days > 0 {
+            match chat_session::prune(&state.db, days).await {
+                Ok(0) => {}
+                Ok(num) => log::info!("pruned {num} stale chat sessions"),
+                Err(err) => log::error!("Unable to prune chat sessions: {err}"),
+            }
+        }
+    }
+}
+
+async fn to_session_result(state: &AppState, session: &chat_session::Model) -> ChatSessionResult {
+    let num_messages = chat_message::Entity::find()
+        .filter(chat_message::Column::SessionId.eq(session.id))
+        .count(&state.db)
+        .await
+        .unwrap_or_default();
+
+    ChatSessionResult {
+        id: session.id,
+        doc_id: session.doc_id.clone(),
+        created_at: session.created_at.to_rfc3339(),
+        updated_at: session.updated_at.to_rfc3339(),
+        num_messages,
+    }
+}
+
+/// Map-reduce chunk size for summarization, in characters. Roughly 3k
+/// tokens, comfortably inside the context window once the prompt is added.
+const SUMMARY_CHUNK_CHARS: usize = 12_000;
+
+/// Summarizes a document w/ the LLM, streaming the result out as
+/// `RpcEventType::ChatStream` events. Long documents are summarized
+/// map-reduce style: each chunk is summarized on its own & the partial
+/// summaries are combined in a final pass. Summaries are cached on the
+/// `indexed_document` row until the content hash changes.
+#[instrument(skip(state))]
+pub async fn summarize_document(
+    state: AppState,
+    doc_id: String,
+    max_words: Option<u64>,
+) -> RpcResult<String> {
+    let indexed = indexed_document::Entity::find()
+        .filter(indexed_document::Column::DocId.eq(doc_id.clone()))
+        .one(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    let indexed = match indexed {
+        Some(indexed) => indexed,
+        None => {
+            return Err(server_error(format!("Document {} not found", doc_id), None));
+        }
+    };
+
+    // Cached & generated from the currently indexed content?
+    if let (Some(summary), Some(hash)) = (&indexed.summary, &indexed.summary_content_hash) {
+        if indexed.content_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(summary.clone());
+        }
+    }
+
+    let doc = match state.index.get(&doc_id).await {
+        Some(doc) => doc,
+        None => {
+            return Err(server_error(
+                format!("Document {} not found in index", doc_id),
+                None,
+            ));
+        }
+    };
+
+    let max_words = max_words.unwrap_or(150);
+    let chunks = chunk_by_chars(&doc.content, SUMMARY_CHUNK_CHARS);
+    let summary = if chunks.len() == 1 {
+        summarize_text(&state, &chunks[0], max_words, true).await?
+    } else {
+        let mut partials = Vec::new();
+        for chunk in &chunks {
+            partials.push(summarize_text(&state, chunk, max_words, false).await?);
+        }
+        summarize_text(&state, &partials.join("\n\n"), max_words, true).await?
+    };
+
+    // Cache the summary so repeated requests are instant.
+    let content_hash = indexed.content_hash.clone();
+    let mut update: indexed_document::ActiveModel = indexed.into();
+    update.summary = Set(Some(summary.clone()));
+    update.summary_content_hash = Set(content_hash);
+    if let Err(err) = update.update(&state.db).await {
+        log::error!("Unable to cache summary: {err}");
+    }
+
+    Ok(summary)
+}
+
+/// Runs a single summarization prompt. Only the final (reduce) pass is
+/// streamed to the client; partial summaries are drained silently.
+async fn summarize_text(
+    state: &AppState,
+    content: &str,
+    max_words: u64,
+    stream_to_client: bool,
+) -> RpcResult<String> {
+    let session = LlmSession {
+        messages: vec![
+            ChatMessage {
+                role: ChatRole::System,
+                content: "You are a helpful AI assistant that writes concise summaries of documents".into(),
+            },
+            ChatMessage {
+                role: ChatRole::User,
+                content: format!(
+                    "Summarize the following content in at most {} words:\n{}",
+                    max_words, content
+                ),
+            },
+        ],
+        params: GenerationParams::default(),
+    };
+
+    let stream = if stream_to_client {
+        chat_event_channel(state)
+    } else {
+        // Keep the receiver alive so generation isn't treated as cancelled.
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<ChatStream>(10);
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+        tx
+    };
+
+    Ok(chat_with_llm(state, &session, stream, None).await?.content)
+}
+
+/// Splits `content` into chunks of at most `max_chars` characters, always
+/// breaking on a char boundary.
+fn chunk_by_chars(content: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut count = 0;
+    for ch in content.chars() {
+        current.push(ch);
+        count += 1;
+        if count >= max_chars {
+            chunks.push(std::mem::take(&mut current));
+            count = 0;
+        }
+    }
+
+    if !current.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+    chunks
 }
 
 /// Remove a domain from crawl queue & index
@@ -291,6 +1096,268 @@ pub async fn delete_domain(state: AppState, domain: String) -> RpcResult<()> {
     Ok(())
 }
 
+/// Max content bytes returned by `get_document` unless the full body is
+/// requested. Keeps preview panes from pulling multi-megabyte documents
+/// over the wire.
+const DOC_PREVIEW_LENGTH: usize = 32_768;
+
+/// Everything a client needs to render a single document: metadata & content
+/// from the index, plus tags & lens membership from the database.
+#[instrument(skip(state))]
+pub async fn get_document(
+    state: AppState,
+    doc_id: &str,
+    full_content: bool,
+) -> RpcResult<DocumentDetails> {
+    let doc = match state.index.get(doc_id).await {
+        Some(doc) => doc,
+        None => return Err(server_error(format!("Document {doc_id} not found"), None)),
+    };
+
+    let details = indexed_document::get_document_details(
+        &state.db,
+        indexed_document::DocumentIdentifier::DocId(doc_id),
+    )
+    .await
+    .map_err(|err| server_error(err.to_string(), None))?;
+
+    let (open_url, tags) = match details {
+        Some((indexed, tags)) => (
+            indexed.open_url,
+            tags.iter()
+                .map(|tag| (tag.label.to_string(), tag.value.clone()))
+                .collect::<Vec<(String, String)>>(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    let lens_label = TagType::Lens.to_string();
+    let lenses = tags
+        .iter()
+        .filter(|(label, _)| *label == lens_label)
+        .map(|(_, value)| value.clone())
+        .collect::<Vec<String>>();
+
+    let mut content = doc.content;
+    let mut is_truncated = false;
+    if !full_content && content.len() > DOC_PREVIEW_LENGTH {
+        // Truncate on a char boundary so multi-byte text can't panic.
+        let cutoff = (0..=DOC_PREVIEW_LENGTH)
+            .rev()
+            .find(|idx| content.is_char_boundary(*idx))
+            .unwrap_or(0);
+        content.truncate(cutoff);
+        is_truncated = true;
+    }
+
+    let crawl_uri = doc.url;
+    Ok(DocumentDetails {
+        doc_id: doc.doc_id,
+        crawl_uri: crawl_uri.clone(),
+        domain: doc.domain,
+        title: doc.title,
+        description: doc.description,
+        url: open_url.unwrap_or(crawl_uri),
+        content,
+        is_truncated,
+        tags,
+        lenses,
+        published_at: doc.published_at,
+        last_modified: doc.last_modified,
+    })
+}
+
+/// Adds the favorited tag to a document & reindexes it so the favorite
+/// boost applies on the next search.
+#[instrument(skip(state))]
+pub async fn favorite_document(state: AppState, doc_id: &str) -> RpcResult<()> {
+    match state.index.get(doc_id).await {
+        Some(doc) => update_tags(
+            &state,
+            &[doc],
+            &TagModification {
+                add: Some(vec![(
+                    TagType::Favorited.to_string(),
+                    TagValue::Favorited.to_string(),
+                )]),
+                remove: None,
+            },
+        )
+        .await
+        .map_err(|err| server_error(err.to_string(), None)),
+        None => Err(server_error(format!("Document {doc_id} not found"), None)),
+    }
+}
+
+/// Adds &/or removes tags on a document, updating the database rows & the
+/// indexed doc in one pass. Used by the searchbar's tagging action.
+#[instrument(skip(state))]
+pub async fn update_document_tags(
+    state: AppState,
+    doc_id: &str,
+    add: Vec<(String, String)>,
+    remove: Vec<(String, String)>,
+) -> RpcResult<()> {
+    let doc = match state.index.get(doc_id).await {
+        Some(doc) => doc,
+        None => return Err(server_error(format!("Document {doc_id} not found"), None)),
+    };
+
+    let modification = TagModification {
+        add: if add.is_empty() { None } else { Some(add) },
+        remove: if remove.is_empty() { None } else { Some(remove) },
+    };
+
+    update_tags(&state, &[doc], &modification)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    // Tag counts are stale now; drop the cache so `list_tags` picks up the
+    // change right away instead of after the TTL.
+    state.tag_cache.store(Arc::new(None));
+
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::DocumentUpdated,
+            payload: Some(serde_json::to_value(doc_id.to_string()).unwrap_or_default()),
+        })
+        .await;
+
+    Ok(())
+}
+
+/// Removes the favorited tag from a document.
+#[instrument(skip(state))]
+pub async fn unfavorite_document(state: AppState, doc_id: &str) -> RpcResult<()> {
+    match state.index.get(doc_id).await {
+        Some(doc) => update_tags(
+            &state,
+            &[doc],
+            &TagModification {
+                add: None,
+                remove: Some(vec![(
+                    TagType::Favorited.to_string(),
+                    TagValue::Favorited.to_string(),
+                )]),
+            },
+        )
+        .await
+        .map_err(|err| server_error(err.to_string(), None)),
+        None => Err(server_error(format!("Document {doc_id} not found"), None)),
+    }
+}
+
+/// All favorited documents.
+#[instrument(skip(state))]
+pub async fn list_favorites(state: AppState) -> RpcResult<Vec<SearchResult>> {
+    let favorited = match tag::get_favorite_tag(&state.db).await {
+        Some(tag_id) => tag_id,
+        None => return Ok(Vec::new()),
+    };
+
+    let doc_ids = document_tag::Entity::find()
+        .filter(document_tag::Column::TagId.eq(favorited as i64))
+        .all(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?
+        .iter()
+        .map(|row| row.indexed_document_id)
+        .collect::<Vec<i64>>();
+
+    let mut results = Vec::new();
+    for indexed in indexed_document::Entity::find()
+        .filter(indexed_document::Column::Id.is_in(doc_ids))
+        .all(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?
+    {
+        // Title & description live in the index, not the database.
+        let doc = match state.index.get(&indexed.doc_id).await {
+            Some(doc) => doc,
+            None => continue,
+        };
+
+        let tags = indexed
+            .find_related(tag::Entity)
+            .all(&state.db)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|tag| (tag.label.to_string(), tag.value.clone()))
+            .collect::<Vec<(String, String)>>();
+
+        let crawl_uri = doc.url;
+        results.push(SearchResult {
+            doc_id: doc.doc_id,
+            domain: doc.domain,
+            title: doc.title,
+            crawl_uri: crawl_uri.clone(),
+            description: doc.description,
+            url: indexed.open_url.clone().unwrap_or(crawl_uri),
+            tags,
+            score: 0.0,
+            published_at: doc.published_at,
+            last_modified: doc.last_modified,
+            snippet: String::new(),
+            highlights: Vec::new(),
+        });
+    }
+
+    Ok(results)
+}
+
+/// How long cached tag counts are served before the aggregate query is
+/// re-run. Tags change rarely compared to how often the searchbar asks.
+const TAG_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Default number of tags returned when the client doesn't ask for a limit.
+const DEFAULT_TAG_LIMIT: u32 = 25;
+
+/// Tags w/ document counts for filter chips & `tag:` autocomplete. The full
+/// count list is cached for a short TTL & filtered in memory so
+/// per-keystroke calls don't hammer the database.
+#[instrument(skip(state))]
+pub async fn list_tags(
+    state: AppState,
+    label_filter: Option<String>,
+    prefix: Option<String>,
+    limit: Option<u32>,
+) -> RpcResult<Vec<TagCountResult>> {
+    let cached = state.tag_cache.load_full();
+    let counts = match cached.as_ref() {
+        Some((fetched_at, counts)) if fetched_at.elapsed() < TAG_CACHE_TTL => counts.clone(),
+        _ => {
+            let counts = tag::tags_with_counts(&state.db)
+                .await
+                .map_err(|err| server_error(err.to_string(), None))?;
+            state
+                .tag_cache
+                .store(Arc::new(Some((Instant::now(), counts.clone()))));
+            counts
+        }
+    };
+
+    let prefix = prefix.map(|prefix| prefix.to_lowercase());
+    let limit = limit.unwrap_or(DEFAULT_TAG_LIMIT) as usize;
+    Ok(counts
+        .into_iter()
+        .filter(|tag| match &label_filter {
+            Some(label) => tag.label == *label,
+            None => true,
+        })
+        .filter(|tag| match &prefix {
+            Some(prefix) => tag.value.to_lowercase().starts_with(prefix),
+            None => true,
+        })
+        .take(limit)
+        .map(|tag| TagCountResult {
+            label: tag.label,
+            value: tag.value,
+            doc_count: tag.doc_count as u64,
+        })
+        .collect())
+}
+
 #[instrument(skip(state))]
 pub async fn list_connections(state: AppState) -> RpcResult<ListConnectionResult> {
     match entities::models::connection::Entity::find()
@@ -327,7 +1394,8 @@ pub async fn list_connections(state: AppState) -> RpcResult<ListConnectionResult
 /// List of installed lenses
 #[instrument(skip(state))]
 pub async fn list_installed_lenses(state: AppState) -> RpcResult<Vec<LensResult>> {
-    let stats = get_library_stats(&state.db).await.unwrap_or_default();
+    // Install status only needs the doc counts, skip the index size walk.
+    let stats = get_library_stats(&state.db, 0).await.unwrap_or_default();
     let mut lenses: Vec<LensResult> = state
         .lenses
         .iter()
@@ -363,6 +1431,7 @@ pub async fn list_installed_lenses(state: AppState) -> RpcResult<Vec<LensResult>
                 description: lens.description.clone().unwrap_or_else(|| "".into()),
                 hash: lens.hash.clone(),
                 file_path: Some(lens.file_path.clone()),
+                is_enabled: lens.is_enabled,
                 progress,
                 lens_type: shared::response::LensType::Lens,
                 ..Default::default()
@@ -417,6 +1486,7 @@ async fn add_connections_information(
                     name: api_id.clone(),
                     label: String::from(title),
                     description: String::from(description),
+                    is_enabled: true,
                     progress,
                     lens_type: shared::response::LensType::API,
                     ..Default::default()
@@ -469,6 +1539,7 @@ async fn build_filesystem_information(
             name: String::from("local-file-system"),
             label: String::from("Local File System"),
             description: String::from("All files are processed locally. Contents of supported file types will be indexed. All unsupported files/folders will be indexed based on their path, name, and extension."),
+            is_enabled: true,
             progress: status,
             lens_type: shared::response::LensType::Internal,
             ..Default::default()
@@ -477,6 +1548,189 @@ async fn build_filesystem_information(
     }
 }
 
+/// Lists chat models: the curated catalog plus any ggufs already in the
+/// model dir, noting which one is active.
+#[instrument(skip(state))]
+pub async fn list_llm_models(state: AppState) -> RpcResult<Vec<LlmModelResult>> {
+    let model_dir = state.config.llm_model_dir();
+    let active = state
+        .user_settings
+        .load()
+        .llm_settings
+        .gguf_path(&model_dir);
+
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+    for model in shared::constants::LLM_MODEL_CATALOG {
+        let path = model_dir.join(model.dir).join(model.file);
+        let installed = path.is_file();
+        results.push(LlmModelResult {
+            name: model.name.to_string(),
+            installed,
+            active: path == active,
+            path: installed.then(|| path.display().to_string()),
+        });
+        seen.insert(path);
+    }
+
+    // Manually installed ggufs show up under their path relative to the
+    // model dir.
+    for path in model_files::model_files_in(&model_dir) {
+        if seen.contains(&path) || path.extension().and_then(|ext| ext.to_str()) != Some("gguf") {
+            continue;
+        }
+
+        let name = path
+            .strip_prefix(&model_dir)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        results.push(LlmModelResult {
+            name,
+            installed: true,
+            active: path == active,
+            path: Some(path.display().to_string()),
+        });
+    }
+
+    Ok(results)
+}
+
+/// Starts downloading a chat model in the background: either a name from the
+/// curated catalog or a direct URL to a gguf file. Progress is reported via
+/// `RpcEventType::ModelDownloadStatus` events, like the other model
+/// downloads.
+#[instrument(skip(state))]
+pub async fn install_llm_model(state: AppState, name_or_url: String) -> RpcResult<()> {
+    let model_dir = state.config.llm_model_dir();
+
+    // (target path, url) pairs to fetch.
+    let (name, downloads) = if let Some(model) = shared::constants::LLM_MODEL_CATALOG
+        .iter()
+        .find(|model| model.name == name_or_url)
+    {
+        let dir = model_dir.join(model.dir);
+        (
+            model.name.to_string(),
+            vec![
+                (dir.join(model.file), model.url.to_string()),
+                (dir.join("tokenizer.json"), model.tokenizer_url.to_string()),
+            ],
+        )
+    } else if name_or_url.starts_with("http://") || name_or_url.starts_with("https://") {
+        let file = name_or_url
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if !file.ends_with(".gguf") {
+            return Err(server_error(
+                format!("Expected a URL to a gguf file, got \"{name_or_url}\""),
+                None,
+            ));
+        }
+
+        let path = model_dir.join("custom").join(&file);
+        (file, vec![(path, name_or_url.clone())])
+    } else {
+        return Err(server_error(
+            format!("Unknown model \"{name_or_url}\", expected a catalog name or a gguf URL"),
+            None,
+        ));
+    };
+
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        for (path, url) in downloads {
+            // Already downloaded (e.g. a retried install).
+            if path.is_file() {
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+
+            if let Err(error) = libspyglass::task::download_model(
+                &state_clone,
+                &format!("Chat Model ({name})"),
+                path,
+                &url,
+            )
+            .await
+            {
+                log::error!("Error downloading chat model: {error}");
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Deletes an installed chat model's files. The currently active model can't
+/// be removed.
+#[instrument(skip(state))]
+pub async fn remove_llm_model(state: AppState, name: String) -> RpcResult<()> {
+    // Names from `list_llm_models` are either catalog names or paths relative
+    // to the model dir; never anything outside of it.
+    if name.contains("..") {
+        return Err(server_error(format!("Invalid model name \"{name}\""), None));
+    }
+
+    let model_dir = state.config.llm_model_dir();
+    let gguf_path = match shared::constants::LLM_MODEL_CATALOG
+        .iter()
+        .find(|model| model.name == name)
+    {
+        Some(model) => model_dir.join(model.dir).join(model.file),
+        None => model_dir.join(&name),
+    };
+
+    if !gguf_path.is_file() {
+        return Err(server_error(
+            format!("Model \"{name}\" is not installed"),
+            None,
+        ));
+    }
+
+    let active = state
+        .user_settings
+        .load()
+        .llm_settings
+        .gguf_path(&model_dir);
+    if gguf_path == active {
+        return Err(server_error(
+            format!("Model \"{name}\" is in use, switch models before removing it"),
+            None,
+        ));
+    }
+
+    let _ = std::fs::remove_file(model_files::checksum_path(&gguf_path));
+    let _ = std::fs::remove_file(model_files::partial_path(&gguf_path));
+    std::fs::remove_file(&gguf_path).map_err(|err| server_error(err.to_string(), None))?;
+
+    // The sibling tokenizer only serves this gguf; drop it (& the directory)
+    // once no other model files remain.
+    if let Some(dir) = gguf_path.parent() {
+        if dir != model_dir.as_path() {
+            let remaining = model_files::model_files_in(dir)
+                .into_iter()
+                .filter(|path| {
+                    path.file_name().and_then(|name| name.to_str()) != Some("tokenizer.json")
+                })
+                .count();
+            if remaining == 0 {
+                let tokenizer = dir.join("tokenizer.json");
+                let _ = std::fs::remove_file(model_files::checksum_path(&tokenizer));
+                let _ = std::fs::remove_file(tokenizer);
+                let _ = std::fs::remove_dir(dir);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn list_plugins(state: AppState) -> RpcResult<Vec<PluginResult>> {
     let mut plugins = Vec::new();
     let result = lens::Entity::find()
@@ -484,13 +1738,27 @@ pub async fn list_plugins(state: AppState) -> RpcResult<Vec<PluginResult>> {
         .all(&state.db)
         .await;
 
+    // Permissions aren't stored in the db, pull them from the manifests.
+    let plugin_configs = state.config.load_plugin_config();
+    let manager = state.plugin_manager.lock().await;
     if let Ok(results) = result {
         for plugin in results {
+            let schedule = manager.update_schedule(&plugin.name);
             plugins.push(PluginResult {
                 author: plugin.author,
-                title: plugin.name,
                 description: plugin.description.clone().unwrap_or_default(),
                 is_enabled: plugin.is_enabled,
+                last_run: schedule.as_ref().and_then(|sub| sub.last_run),
+                next_run: schedule.as_ref().map(|sub| sub.next_run()),
+                permissions: plugin_configs
+                    .get(&plugin.name)
+                    .map(|config| config.permissions.clone())
+                    .unwrap_or_default(),
+                status_state: plugin.status_state,
+                status_message: plugin.status_message,
+                status_updated_at: plugin.status_updated_at,
+                log_path: Some(state.config.plugin_log_file(&plugin.name)),
+                title: plugin.name,
             });
         }
     }
@@ -528,6 +1796,28 @@ pub async fn recrawl_domain(state: AppState, domain: String) -> RpcResult<()> {
         .exec(db)
         .await;
 
+    // Remove the existing docs from the index & db in one pass; they'll be
+    // re-added as the domain is recrawled.
+    match state
+        .index
+        .delete_by_query(DeleteQuery::Domain(domain.clone()))
+        .await
+    {
+        Ok(num_docs) => log::info!("removed {num_docs} docs for domain {domain}"),
+        Err(err) => log::error!("Unable to remove docs for {domain}: {err}"),
+    }
+
+    let existing = indexed_document::Entity::find()
+        .filter(indexed_document::Column::Domain.eq(domain.clone()))
+        .all(db)
+        .await
+        .unwrap_or_default();
+    if !existing.is_empty() {
+        let dbids: Vec<i64> = existing.iter().map(|x| x.id).collect();
+        let _ = vec_to_indexed::delete_all_by_indexed_ids(db, &dbids).await;
+        let _ = indexed_document::delete_many_by_id(db, &dbids).await;
+    }
+
     let res = crawl_queue::Entity::update_many()
         .col_expr(
             crawl_queue::Column::Status,
@@ -545,6 +1835,102 @@ pub async fn recrawl_domain(state: AppState, domain: String) -> RpcResult<()> {
     Ok(())
 }
 
+/// Number of domains included in a lens suggested from browsing history.
+const SUGGESTED_LENS_MAX_DOMAINS: usize = 20;
+
+/// Proposes a lens built from the user's browsing history: the most visited
+/// domains w/ a few sensible skip rules, returned as RON text the client can
+/// edit & save into the lens directory.
+#[instrument(skip(state))]
+pub async fn suggest_lens(
+    state: AppState,
+    min_visits: u64,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> RpcResult<SuggestedLensResult> {
+    let counts = entities::get_domain_counts(&state.db, min_visits.max(1), since)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    // Fold `www.` hosts into their parent domain so both count as one.
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for row in counts {
+        let domain = row.domain.trim_start_matches("www.").to_string();
+        *totals.entry(domain).or_default() += row.count.max(0) as u64;
+    }
+
+    let mut domains = totals.into_iter().collect::<Vec<(String, u64)>>();
+    domains.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    domains.truncate(SUGGESTED_LENS_MAX_DOMAINS);
+
+    let lens = config::LensConfig {
+        author: String::from("spyglass"),
+        name: String::from("my-history"),
+        label: String::from("My History"),
+        description: Some(String::from(
+            "Generated from your most visited sites. Edit before saving!",
+        )),
+        domains: domains.iter().map(|(domain, _)| domain.clone()).collect(),
+        version: String::from("1"),
+        rules: vec![
+            // Auth pages & search result pages show up in everyone's history
+            // but make poor index material.
+            config::LensRule::SkipURL(String::from("*://*/login*")),
+            config::LensRule::SkipURL(String::from("*://*/signin*")),
+            config::LensRule::SkipURL(String::from("*://*/search?*")),
+        ],
+        ..Default::default()
+    };
+
+    match ron::ser::to_string_pretty(&lens, Default::default()) {
+        Ok(config) => Ok(SuggestedLensResult {
+            name: lens.name.clone(),
+            config,
+            domains,
+        }),
+        Err(err) => Err(server_error(err.to_string(), None)),
+    }
+}
+
+/// Immediately requeues a lens' documents for a recrawl & resets its refresh
+/// timer, instead of waiting for the lens' recrawl interval to elapse.
+#[instrument(skip(state))]
+pub async fn refresh_lens(state: AppState, name: &str) -> RpcResult<()> {
+    match task::lens::refresh_lens(&state, name).await {
+        Ok(requeued) => {
+            log::info!("refresh lens {name}: requeued {requeued} tasks");
+            Ok(())
+        }
+        Err(err) => Err(server_error(err.to_string(), None)),
+    }
+}
+
+/// Enables or disables an installed lens w/o uninstalling it. Disabled
+/// lenses keep their documents indexed but are excluded from default
+/// searches & no longer crawled.
+#[instrument(skip(state))]
+pub async fn toggle_lens(state: AppState, name: &str, enabled: bool) -> RpcResult<()> {
+    if let Err(err) = lens::set_user_enabled(name, enabled, &state.db).await {
+        return Err(server_error(err.to_string(), None));
+    }
+
+    if let Some(mut entry) = state.lenses.get_mut(name) {
+        entry.is_enabled = enabled;
+    }
+
+    if enabled {
+        // Catch up on anything added while the lens was off. Bootstrapping
+        // is idempotent, already queued or indexed urls are skipped.
+        let _ = state
+            .schedule_work(ManagerCommand::Collect(CollectTask::BootstrapLens {
+                lens: name.to_owned(),
+            }))
+            .await;
+    }
+
+    log::info!("lens {} {}", name, if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
 #[instrument(skip(state))]
 pub async fn toggle_pause(state: AppState, is_paused: bool) -> RpcResult<()> {
     // Scope so that the app_state mutex is correctly released.
@@ -559,6 +1945,23 @@ pub async fn toggle_pause(state: AppState, is_paused: bool) -> RpcResult<()> {
     Ok(())
 }
 
+/// Switches the embedding model. Routed through the settings channel so the
+/// configuration watcher downloads the model (when it's a HuggingFace repo)
+/// & queues a re-embed of existing documents.
+#[instrument(skip(app, config))]
+pub async fn set_embedding_model(app: &AppState, config: &Config, name: &str) -> RpcResult<()> {
+    let mut user_settings = app.user_settings.load().as_ref().clone();
+    user_settings.embedding_settings.embedding_model = if name.trim().is_empty() {
+        None
+    } else {
+        Some(name.trim().to_string())
+    };
+    user_settings.embedding_settings.enable_embeddings = true;
+
+    update_user_settings(app, config, &user_settings).await?;
+    Ok(())
+}
+
 #[instrument(skip(app, _config))]
 pub async fn update_user_settings(
     app: &AppState,
@@ -596,17 +1999,28 @@ pub async fn uninstall_lens(state: AppState, config: &Config, name: &str) -> Rpc
         .exec(&state.db)
         .await;
 
-    // - find relevant doc ids to remove
+    // - remove from the index in a single pass via the lens tag rather than
+    //   walking every doc id
+    if let Ok(Some(lens_tag)) = tag::Entity::find()
+        .filter(tag::Column::Label.eq(TagType::Lens.to_string()))
+        .filter(tag::Column::Value.eq(name))
+        .one(&state.db)
+        .await
+    {
+        match state
+            .index
+            .delete_by_query(DeleteQuery::Tag(lens_tag.id as u64))
+            .await
+        {
+            Ok(num_docs) => log::info!("removed {num_docs} docs for lens {name}"),
+            Err(err) => return Err(server_error(err.to_string(), None)),
+        }
+    }
+
+    // - remove the matching rows (and their embeddings) from the db
     if let Ok(ids) = indexed_document::find_by_lens(state.db.clone(), name).await {
-        // - remove from db & index
-        let doc_ids: Vec<String> = ids.iter().map(|x| x.doc_id.to_owned()).collect();
         let dbids: Vec<i64> = ids.iter().map(|x| x.id).collect();
-
-        // Remove from index
-        if let Err(err) = state.index.delete_many_by_id(&doc_ids).await {
-            return Err(server_error(err.to_string(), None));
-        }
-        // Remove from db
+        let _ = vec_to_indexed::delete_all_by_indexed_ids(&state.db, &dbids).await;
         let _ = indexed_document::delete_many_by_id(&state.db, &dbids).await;
     }
 
@@ -618,6 +2032,8 @@ pub async fn uninstall_lens(state: AppState, config: &Config, name: &str) -> Rpc
     // - remove seed urls from bootstrap queue table
     if let Some((_, config)) = config {
         let _ = bootstrap_queue::dequeue(&state.db, &config.name).await;
+        // Stop watching & clean up any local folders the lens declared.
+        filesystem::unwatch_lens_folders(&state, &config).await;
     }
 
     state
@@ -663,7 +2079,7 @@ pub async fn default_indices() -> DefaultIndices {
 
 #[cfg(test)]
 mod test {
-    use super::uninstall_lens;
+    use super::{are_documents_indexed, suggest_lens, uninstall_lens};
     use entities::models::tag::TagType;
     use entities::sea_orm::{ActiveModelTrait, EntityTrait, Set};
     use entities::{
@@ -739,4 +2155,75 @@ mod test {
         std::thread::sleep(std::time::Duration::from_millis(500));
         assert_eq!(state.index.reader.searcher().num_docs(), 0);
     }
+
+    #[tokio::test]
+    async fn test_suggest_lens() {
+        let db = setup_test_db().await;
+        let state = AppState::builder().with_db(db.clone()).build();
+
+        // `www.` hosts should fold into their parent domain; rarely visited
+        // domains should fall below `min_visits`.
+        let visits = [
+            ("example.com", "https://example.com/one"),
+            ("example.com", "https://example.com/two"),
+            ("www.example.com", "https://www.example.com/three"),
+            ("rarely-visited.com", "https://rarely-visited.com/once"),
+        ];
+        for (domain, url) in visits {
+            let doc = indexed_document::ActiveModel {
+                domain: Set(domain.into()),
+                url: Set(url.into()),
+                doc_id: Set(url.into()),
+                ..Default::default()
+            };
+            let _ = doc.insert(&db).await.expect("Unable to insert doc");
+        }
+
+        let result = suggest_lens(state, 2, None)
+            .await
+            .expect("Unable to suggest lens");
+        assert_eq!(result.domains, vec![("example.com".into(), 3)]);
+
+        // The proposed config should round-trip through the lens loader.
+        let lens = LensConfig::from_string(&result.config).expect("Invalid lens config");
+        assert_eq!(lens.name, "my-history");
+        assert_eq!(lens.domains, vec!["example.com".to_string()]);
+        assert_eq!(lens.rules.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_are_documents_indexed() {
+        let db = setup_test_db().await;
+        let state = AppState::builder().with_db(db.clone()).build();
+
+        let doc = indexed_document::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/test".into()),
+            doc_id: Set("test_id".into()),
+            ..Default::default()
+        };
+        let _ = doc.insert(&db).await.expect("Unable to insert doc");
+
+        let results = are_documents_indexed(
+            state.clone(),
+            vec![
+                "https://example.com/test".into(),
+                "http://example.com/test/".into(),
+                "https://example.com/missing".into(),
+            ],
+        )
+        .await
+        .expect("Unable to run lookup");
+
+        assert_eq!(results.get("https://example.com/test"), Some(&true));
+        // Scheme & trailing-slash variants resolve to the indexed copy.
+        assert_eq!(results.get("http://example.com/test/"), Some(&true));
+        assert_eq!(results.get("https://example.com/missing"), Some(&false));
+
+        // Oversized batches are rejected.
+        let too_many = (0..=super::INDEXED_LOOKUP_LIMIT)
+            .map(|idx| format!("https://example.com/{idx}"))
+            .collect::<Vec<String>>();
+        assert!(are_documents_indexed(state, too_many).await.is_err());
+    }
 }
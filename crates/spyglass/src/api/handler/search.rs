@@ -1,6 +1,6 @@
 use entities::models::tag::{check_query_for_tags, get_favorite_tag, TagType};
 use entities::models::vec_documents::DocDistance;
-use entities::models::{indexed_document, lens, tag, vec_documents};
+use entities::models::{indexed_document, lens, tag, vec_documents, vec_to_indexed};
 use entities::sea_orm::{
     self, prelude::*, sea_query::Expr, FromQueryResult, JoinType, QueryOrder, QuerySelect,
 };
@@ -9,15 +9,21 @@ use libspyglass::state::AppState;
 use libspyglass::task::{CleanupTask, ManagerCommand};
 use shared::metrics;
 use shared::request;
-use shared::response::{LensResult, SearchLensesResp, SearchMeta, SearchResult, SearchResults};
+use shared::response::{
+    ExplainResult, LensResult, SearchLensesResp, SearchMeta, SearchResult, SearchResults,
+};
+use spyglass_rpc::server_error;
 use spyglass_model_interface::embedding_api::EmbeddingContentType;
-use spyglass_searcher::client::Searcher;
 use spyglass_searcher::schema::{DocFields, SearchDocument};
-use spyglass_searcher::{Boost, QueryBoost, SearchTrait};
+use spyglass_searcher::{Boost, DateField, QueryBoost, SearchOptions, SearchTrait, SortMode};
 use std::collections::{HashMap, HashSet};
 use std::time::SystemTime;
 use tracing::instrument;
 
+/// How many candidates are retrieved for the cross-encoder when reranking
+/// is enabled.
+const RERANK_DEPTH: usize = 50;
+
 /// Search the user's indexed documents
 #[instrument(skip(state))]
 pub async fn search_docs(
@@ -36,6 +42,7 @@ pub async fn search_docs(
     let searcher = index.reader.searcher();
     let query = search_req.query.clone();
 
+    let is_default_search = search_req.lenses.is_empty();
     let lens_ids = tag::Entity::find()
         .filter(tag::Column::Label.eq(tag::TagType::Lens.to_string()))
         .filter(tag::Column::Value.is_in(search_req.lenses))
@@ -52,15 +59,103 @@ pub async fn search_docs(
     }
 
     let mut filters = Vec::new();
-    for lens in &lens_ids {
-        filters.push(QueryBoost::new(Boost::Tag(*lens)));
+    // Selected lenses are or'd together so searching multiple lenses at once
+    // returns the union of their documents.
+    if !lens_ids.is_empty() {
+        filters.push(QueryBoost::new(Boost::AnyTag(lens_ids.clone())));
+    }
+
+    // Documents from lenses the user has disabled are left out of default
+    // searches; explicitly selecting a disabled lens still searches it.
+    if is_default_search {
+        let disabled = state
+            .lenses
+            .iter()
+            .filter(|entry| !entry.value().is_enabled)
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<String>>();
+
+        if !disabled.is_empty() {
+            let disabled_ids = tag::Entity::find()
+                .filter(tag::Column::Label.eq(tag::TagType::Lens.to_string()))
+                .filter(tag::Column::Value.is_in(disabled))
+                .all(&state.db)
+                .await
+                .unwrap_or_default()
+                .iter()
+                .map(|model| model.id as u64)
+                .collect::<Vec<u64>>();
+
+            if !disabled_ids.is_empty() {
+                filters.push(QueryBoost::new(Boost::ExcludeTag(disabled_ids)));
+            }
+        }
+    }
+
+    // `tag:value` scopes in the query are resolved against the tag db &
+    // applied as filters, the rest of the scoped syntax is handled by the
+    // query builder itself.
+    for value in spyglass_searcher::scoped_tag_values(&query) {
+        for tag in check_query_for_tags(&state.db, &value).await {
+            filters.push(QueryBoost::new(Boost::Tag(tag)));
+        }
+    }
+
+    // Filter by detected document language, if requested.
+    if !search_req.languages.is_empty() {
+        let language_ids = tag::Entity::find()
+            .filter(tag::Column::Label.eq(tag::TagType::Language.to_string()))
+            .filter(tag::Column::Value.is_in(search_req.languages.clone()))
+            .all(&state.db)
+            .await
+            .unwrap_or_default();
+
+        // Or'd together, a document is only ever tagged w/ one language.
+        if !language_ids.is_empty() {
+            filters.push(QueryBoost::new(Boost::AnyTag(
+                language_ids.iter().map(|tag| tag.id as u64).collect(),
+            )));
+        }
+    }
+
+    // Restrict to a published date range, if requested.
+    if search_req.published_after.is_some() || search_req.published_before.is_some() {
+        filters.push(QueryBoost::new(Boost::DateRange {
+            field: DateField::Published,
+            start: search_req.published_after,
+            end: search_req.published_before,
+        }));
     }
 
     if let Some(tag_id) = get_favorite_tag(&state.db).await {
         filters.push(QueryBoost::new(Boost::Favorite {
             id: tag_id,
-            required: false,
+            required: search_req.favorites_only,
         }));
+    } else if search_req.favorites_only {
+        // Nothing has been favorited yet, so nothing can match.
+        return Ok(SearchResults {
+            results: Vec::new(),
+            meta: SearchMeta {
+                query: search_req.query.clone(),
+                num_docs: searcher.num_docs() as u32,
+                wall_time_ms: 0,
+                total_hits: 0,
+            },
+            facets: HashMap::new(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    // Per-request override, otherwise fall back to the user's setting. A
+    // half-life of 0 disables the boost either way.
+    let recency = search_req
+        .recency_boost_days
+        .or_else(|| state.user_settings.load().recency_boost_days);
+    if let Some(half_life_days) = recency {
+        if half_life_days > 0.0 {
+            boosts.push(QueryBoost::new(Boost::Recency { half_life_days }));
+        }
     }
 
     if let Some(embedding_api) = state.embedding_api.load_full().as_ref() {
@@ -70,13 +165,38 @@ pub async fn search_docs(
                 .map(|embedding| embedding.first().map(|val| val.to_owned()))
             {
                 Ok(Some(embedding)) => {
-                    let mut distances = vec_documents::get_document_distance(
-                        &state.db,
-                        &lens_ids,
-                        &embedding.embedding,
-                        10,
-                    )
-                    .await;
+                    // Distances between vectors of different lengths are
+                    // meaningless; skip the boost until the re-embed triggered
+                    // by an `embedding_dimensions` change catches up.
+                    let model_id = state.user_settings.load().embedding_settings.model_id();
+                    let mismatched = match vec_to_indexed::stored_dims(&state.db, &model_id).await
+                    {
+                        Ok(Some(dims)) if dims as usize != embedding.embedding.len() => {
+                            log::error!(
+                                "Query embedding has {} dims but stored vectors have {}, skipping semantic search until documents are re-embedded",
+                                embedding.embedding.len(),
+                                dims
+                            );
+                            true
+                        }
+                        Ok(_) => false,
+                        Err(error) => {
+                            log::error!("Error checking stored vector dims {:?}", error);
+                            true
+                        }
+                    };
+
+                    let mut distances = if mismatched {
+                        Ok(Vec::new())
+                    } else {
+                        vec_documents::get_document_distance(
+                            &state.db,
+                            &lens_ids,
+                            &embedding.embedding,
+                            10,
+                        )
+                        .await
+                    };
 
                     match distances.as_mut() {
                         Ok(distances) => {
@@ -123,9 +243,44 @@ pub async fn search_docs(
     }
 
     let offset = search_req.offset.unwrap_or(0);
+    let limit = search_req
+        .limit
+        .unwrap_or_else(|| state.user_settings.load().max_search_results);
+
+    // When reranking, retrieve a deeper candidate pool so the cross-encoder
+    // can promote results BM25 ranked below the cutoff. Reranking is skipped
+    // when paginating, rerank scores aren't stable across pages.
+    let rerank_api = state.rerank_api.load_full();
+    let rerank_enabled = rerank_api.is_some() && offset == 0 && !query.trim().is_empty();
+    let fetch_limit = if rerank_enabled {
+        RERANK_DEPTH.max(limit as usize)
+    } else {
+        limit as usize
+    };
+
+    let options = SearchOptions {
+        // Per-request override, otherwise fall back to the user's setting.
+        use_fuzzy: search_req
+            .fuzzy
+            .unwrap_or_else(|| state.user_settings.load().fuzzy_search),
+        sort: match search_req.sort {
+            request::SearchSort::Relevance => SortMode::Relevance,
+            request::SearchSort::NewestFirst => SortMode::NewestFirst,
+            request::SearchSort::OldestFirst => SortMode::OldestFirst,
+        },
+        return_facets: search_req.return_facets,
+        is_partial: search_req.is_partial,
+    };
     let search_result = state
         .index
-        .search(&query, &filters, &boosts, 5, offset as usize)
+        .search_with_options(
+            &query,
+            &filters,
+            &boosts,
+            fetch_limit,
+            offset as usize,
+            options,
+        )
         .await;
     log::debug!(
         "query {}: {} results from {} docs in {}ms",
@@ -135,9 +290,47 @@ pub async fn search_docs(
         search_result.wall_time_ms
     );
 
+    let mut documents = search_result.documents;
+    if rerank_enabled {
+        if let Some(rerank_api) = rerank_api.as_ref() {
+            let passages = documents
+                .iter()
+                .map(|(_, doc)| doc.content.clone())
+                .collect::<Vec<String>>();
+            match rerank_api.rerank(&query, &passages) {
+                Ok(scores) => {
+                    for ((score, _), rerank) in documents.iter_mut().zip(scores) {
+                        *score = rerank;
+                    }
+                    documents.sort_by(|a, b| b.0.total_cmp(&a.0));
+                }
+                Err(error) => log::error!("Error reranking results {:?}", error),
+            }
+        }
+
+        documents.truncate(limit as usize);
+    }
+
+    // Resolve facet counts from tag ids to "label:value" pairs.
+    let mut facets: HashMap<String, u64> = HashMap::new();
+    if let Some(counts) = &search_result.facets {
+        let tag_ids = counts.keys().map(|id| *id as i64).collect::<Vec<i64>>();
+        let tags = tag::Entity::find()
+            .filter(tag::Column::Id.is_in(tag_ids))
+            .all(&state.db)
+            .await
+            .unwrap_or_default();
+
+        for model in tags {
+            if let Some(count) = counts.get(&(model.id as u64)) {
+                facets.insert(format!("{}:{}", model.label, model.value), *count);
+            }
+        }
+    }
+
     let mut results: Vec<SearchResult> = Vec::new();
     let mut missing: Vec<(String, String)> = Vec::new();
-    for (score, doc) in search_result.documents {
+    for (score, doc) in documents {
         log::debug!("Got id with url {} {}", doc.doc_id, doc.url);
         let indexed = indexed_document::Entity::find()
             .filter(indexed_document::Column::DocId.eq(doc.doc_id.clone()))
@@ -177,6 +370,10 @@ pub async fn search_docs(
                     url: indexed.open_url.unwrap_or(crawl_uri),
                     tags,
                     score,
+                    published_at: doc.published_at,
+                    last_modified: doc.last_modified,
+                    snippet: doc.snippet,
+                    highlights: doc.highlights,
                 };
 
                 results.push(result);
@@ -196,6 +393,7 @@ pub async fn search_docs(
         query: search_req.query.clone(),
         num_docs: num_docs as u32,
         wall_time_ms: wall_time_ms as u32,
+        total_hits: search_result.total_hits as u32,
     };
 
     let domains: HashSet<String> = HashSet::from_iter(results.iter().map(|r| r.domain.clone()));
@@ -220,7 +418,20 @@ pub async fn search_docs(
         }
     }
 
-    Ok(SearchResults { results, meta })
+    // When nothing matched, offer "did you mean?" corrections built from the
+    // index's own term dictionaries.
+    let suggestions = if results.is_empty() {
+        state.index.spelling_suggestions(&query)
+    } else {
+        Vec::new()
+    };
+
+    Ok(SearchResults {
+        results,
+        meta,
+        facets,
+        suggestions,
+    })
 }
 
 #[derive(FromQueryResult)]
@@ -228,6 +439,7 @@ struct LensSearch {
     author: Option<String>,
     name: String,
     description: Option<String>,
+    user_disabled: Option<bool>,
 }
 
 /// Search the user's installed lenses
@@ -241,6 +453,7 @@ pub async fn search_lenses(
         .column_as(tag::Column::Value, "name")
         .column_as(lens::Column::Author, "author")
         .column_as(lens::Column::Description, "description")
+        .column_as(lens::Column::UserDisabled, "user_disabled")
         .filter(tag::Column::Label.eq(TagType::Lens.to_string()))
         .filter(tag::Column::Value.like(format!("%{}%", &param.query)))
         // Pull in lens metadata
@@ -258,188 +471,219 @@ pub async fn search_lenses(
         .await
         .unwrap_or_default();
 
+    let mut seen: HashSet<String> = HashSet::new();
     for lens in query_result {
         let label = lens.name.clone();
+        seen.insert(label.clone());
         results.push(LensResult {
             author: lens.author.unwrap_or("spyglass-search".into()),
             name: label.clone(),
             label,
             description: lens.description.unwrap_or_default(),
+            is_enabled: !lens.user_disabled.unwrap_or_default(),
             ..Default::default()
         });
     }
 
+    // Trigger aliases also match & return the canonical lens, labeled w/ the
+    // alias that matched so the searchbar shows what was typed.
+    let query = param.query.to_lowercase();
+    for entry in state.lenses.iter() {
+        let lens = entry.value();
+        if seen.contains(&lens.name) {
+            continue;
+        }
+
+        let matched = lens
+            .trigger
+            .aliases()
+            .iter()
+            .find(|alias| alias.to_lowercase().contains(&query))
+            .cloned();
+        if let Some(alias) = matched {
+            results.push(LensResult {
+                author: lens.author.clone(),
+                name: lens.name.clone(),
+                label: alias,
+                description: lens.description.clone().unwrap_or_default(),
+                is_enabled: lens.is_enabled,
+                ..Default::default()
+            });
+        }
+    }
+    results.sort_by_key(|result| result.label.to_lowercase());
+
     Ok(SearchLensesResp { results })
 }
 
-#[allow(dead_code)]
-pub async fn concat_context(distances: &[DocDistance], searcher: &Searcher) -> String {
-    let mut map = HashMap::<String, usize>::new();
-    let mut sorted: Vec<Vec<&DocDistance>> = Vec::new();
-    // documents are already ordered now we just want to group documents by
-    // uuid incase there are multiple results per document
-    for distance in distances {
-        match map.get(&distance.doc_id) {
-            Some(index) => {
-                if let Some(vec) = sorted.get_mut(*index) {
-                    vec.push(distance);
-                }
-            }
-            None => {
-                let index = sorted.len();
-                sorted.push(vec![distance]);
-                map.insert(distance.doc_id.clone(), index);
-            }
-        }
+/// Explains why `doc_id` ranks where it does for `query`, with the same
+/// boosts a regular search would apply.
+#[instrument(skip(state))]
+pub async fn explain_search(
+    state: AppState,
+    doc_id: String,
+    query: String,
+    lenses: Vec<String>,
+) -> RpcResult<ExplainResult> {
+    let mut boosts = Vec::new();
+    for tag in check_query_for_tags(&state.db, &query).await {
+        boosts.push(QueryBoost::new(Boost::Tag(tag)));
     }
 
-    let mut context_text = "Context for all documents\n".to_string();
-    for grouped_results in sorted {
-        let first = grouped_results.first();
-        if let Some(first) = first {
-            context_text.push_str(
-                "\n\n-----------------------------------------------------------------\n\n",
-            );
-            context_text.push_str(&format!(
-                "Document UUID: {} URL: {} \n\n ",
-                first.doc_id, first.url,
-            ));
-        }
+    let lens_ids = tag::Entity::find()
+        .filter(tag::Column::Label.eq(tag::TagType::Lens.to_string()))
+        .filter(tag::Column::Value.is_in(lenses))
+        .all(&state.db)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|model| model.id as u64)
+        .collect::<Vec<u64>>();
+    for lens in lens_ids {
+        boosts.push(QueryBoost::new(Boost::Tag(lens)));
+    }
 
-        for (i, doc_distance) in grouped_results.iter().enumerate() {
-            if let Some(context) = pull_context(doc_distance, searcher).await {
-                context_text.push_str(&format!(
-                    "Context Segment -- #{} -- score #{}\n\n Context Text: {} \n\n",
-                    i, doc_distance.distance, context
-                ));
-            }
+    if let Some(half_life_days) = state.user_settings.load().recency_boost_days {
+        if half_life_days > 0.0 {
+            boosts.push(QueryBoost::new(Boost::Recency { half_life_days }));
         }
     }
-    context_text
-}
 
-#[allow(dead_code)]
-async fn pull_context(distance: &DocDistance, searcher: &Searcher) -> Option<String> {
-    if let Some(document) = searcher.get(&distance.doc_id).await {
-        if distance.segment_start == 0
-            && distance.segment_end == ((document.content.len() - 1) as i64)
-        {
-            Some(document.content)
-        } else {
-            let segment = document
-                .content
-                .trim()
-                .char_indices()
-                .filter_map(|(i, c)| {
-                    let index = i as i64;
-                    if index >= distance.segment_start && index < distance.segment_end {
-                        Some(c)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<String>();
+    let applied = boosts
+        .iter()
+        .filter_map(|boost| {
+            serde_json::to_string(&boost.field)
+                .ok()
+                .map(|field| format!("{field} x{:.1}", boost.value))
+        })
+        .collect::<Vec<String>>();
+
+    // Score the (query, document) pair w/ the cross-encoder when reranking
+    // is enabled, so score changes from the rerank stage are visible.
+    let rerank_score = match state.rerank_api.load_full().as_ref() {
+        Some(rerank_api) => match state.index.get(&doc_id).await {
+            Some(doc) => rerank_api
+                .rerank(&query, std::slice::from_ref(&doc.content))
+                .ok()
+                .and_then(|scores| scores.first().copied()),
+            None => None,
+        },
+        None => None,
+    };
 
-            Some(segment)
-        }
-    } else {
-        None
+    match state
+        .index
+        .explain_search(doc_id.clone(), &query, &boosts)
+        .await
+    {
+        Some(explanation) => Ok(ExplainResult {
+            doc_id,
+            query,
+            score: explanation.score,
+            field_scores: explanation.field_scores.into_iter().collect(),
+            boosts: applied,
+            recency_decay: explanation.recency_decay,
+            synonyms: explanation.synonyms,
+            rerank_score,
+        }),
+        None => Err(server_error(
+            format!("Document {doc_id} does not match `{query}`"),
+            None,
+        )),
     }
 }
 
-#[cfg(test)]
-mod test {
-    use crate::api::handler::search::concat_context;
-    use entities::models::vec_documents::DocDistance;
-    use entities::test::setup_test_db;
-    use libspyglass::state::AppState;
-    use spyglass_searcher::schema::DocFields;
-    use spyglass_searcher::schema::SearchDocument;
-    use tantivy::Document;
-
-    #[tokio::test]
-    pub async fn test_concat_context() {
-        let expected_txt = "Context for all documents\n\n\n-----------------------------------------------------------------\n\nDocument UUID: 1234 URL: ahhh \n\n Context Segment -- #0 -- score #2\n\n Context Text: What is this that we \n\nContext Segment -- #1 -- score #4\n\n Context Text: are doing. Well not \n\n\n\n-----------------------------------------------------------------\n\nDocument UUID: 1234_abc URL: http://1234_abc \n\n Context Segment -- #0 -- score #3\n\n Context Text: Here is another docu \n\n\n\n-----------------------------------------------------------------\n\nDocument UUID: 1234_abc_567 URL: http://1234_abc_567 \n\n Context Segment -- #0 -- score #5\n\n Context Text: Got one last documen \n\n";
-
-        let doc_distance = vec![
-            DocDistance {
-                distance: 2.0,
-                doc_id: "1234".to_string(),
-                url: "ahhh".to_string(),
-                id: 1,
-                segment_end: 20,
-                segment_start: 0,
-            },
-            DocDistance {
-                distance: 3.0,
-                doc_id: "1234_abc".to_string(),
-                url: "http://1234_abc".to_string(),
-                id: 1,
-                segment_end: 20,
-                segment_start: 0,
-            },
-            DocDistance {
-                distance: 4.0,
-                doc_id: "1234".to_string(),
-                url: "ahhh".to_string(),
-                id: 1,
-                segment_end: 40,
-                segment_start: 21,
-            },
-            DocDistance {
-                distance: 5.0,
-                doc_id: "1234_abc_567".to_string(),
-                url: "http://1234_abc_567".to_string(),
-                id: 1,
-                segment_end: 20,
-                segment_start: 0,
-            },
-        ];
+/// Finds documents similar to `doc_id` based on its stored embedding
+/// vectors. The document's segment vectors are averaged into a single query
+/// vector & run through a nearest-neighbor search, excluding the document
+/// itself. `score` holds the similarity, higher is more similar.
+#[instrument(skip(state))]
+pub async fn get_similar_documents(
+    state: AppState,
+    doc_id: &str,
+    limit: Option<u32>,
+) -> RpcResult<Vec<SearchResult>> {
+    let limit = limit.unwrap_or(10);
+
+    let indexed = indexed_document::Entity::find()
+        .filter(indexed_document::Column::DocId.eq(doc_id))
+        .one(&state.db)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
 
-        let db = setup_test_db().await;
-        let state = AppState::builder().with_db(db).build();
+    let indexed = match indexed {
+        Some(indexed) => indexed,
+        None => {
+            return Err(server_error(format!("Document {} not found", doc_id), None));
+        }
+    };
 
-        if let Ok(mut writer) = state.index.lock_writer() {
-            if let Err(error) = writer.add_document(create_document(
-                "1234",
-                "What is this that we are doing. Well not sure yet maybe something",
-            )) {
-                println!("Error creating doc {:?}", error);
-            }
-            let _ = writer.add_document(create_document(
-                "1234_abc",
-                "Here is another document that we have here so this is abc",
-            ));
-            let _ = writer.add_document(create_document(
-                "1234_abc_567",
-                "Got one last document for this test and we have at least 20 chars",
+    let embedding = vec_documents::average_embedding_for_document(&state.db, indexed.id)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
+
+    let embedding = match embedding {
+        Some(embedding) => embedding,
+        None => {
+            return Err(server_error(
+                format!("No embeddings stored for document {}", doc_id),
+                None,
             ));
-
-            match writer.commit() {
-                Ok(val) => {
-                    println!("updated {:?}", val);
-                }
-                Err(error) => {
-                    println!("Error committing {:?}", error);
-                    assert!(false);
-                }
-            }
         }
+    };
 
-        if let Err(error) = state.index.reader.reload() {
-            println!("Error reloading {:?}", error);
-        }
+    // Ask for one extra result since the source document is its own nearest
+    // neighbor.
+    let distances = vec_documents::get_document_distance(&state.db, &[], &embedding, limit + 1)
+        .await
+        .map_err(|err| server_error(err.to_string(), None))?;
 
-        let context = concat_context(&doc_distance, &state.index).await;
+    let mut results = Vec::new();
+    for distance in distances
+        .iter()
+        .filter(|distance| distance.doc_id != doc_id)
+        .take(limit as usize)
+    {
+        let docs = state
+            .index
+            .search_by_query(None, Some(vec![distance.doc_id.clone()]), &[], &[])
+            .await;
 
-        assert_eq!(expected_txt, context);
-    }
+        if let Some((_, doc)) = docs.first() {
+            let indexed = indexed_document::Entity::find()
+                .filter(indexed_document::Column::DocId.eq(doc.doc_id.clone()))
+                .one(&state.db)
+                .await;
 
-    fn create_document(uuid: &str, content: &str) -> Document {
-        let schema = DocFields::as_schema();
-        let mut new_doc = Document::default();
-        new_doc.add_text(schema.get_field("id").unwrap(), uuid);
-        new_doc.add_text(schema.get_field("content").unwrap(), content);
-        new_doc
+            if let Ok(Some(indexed)) = indexed {
+                let tags = indexed
+                    .find_related(tag::Entity)
+                    .all(&state.db)
+                    .await
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|tag| (tag.label.to_string(), tag.value.clone()))
+                    .collect::<Vec<(String, String)>>();
+
+                let crawl_uri = doc.url.clone();
+                results.push(SearchResult {
+                    doc_id: doc.doc_id.clone(),
+                    domain: doc.domain.clone(),
+                    title: doc.title.clone(),
+                    crawl_uri: crawl_uri.clone(),
+                    description: doc.description.clone(),
+                    url: indexed.open_url.clone().unwrap_or(crawl_uri),
+                    tags,
+                    // L2 distance flipped into a similarity.
+                    score: (1.0 / (1.0 + distance.distance)) as f32,
+                    published_at: doc.published_at,
+                    last_modified: doc.last_modified,
+                    snippet: String::new(),
+                    highlights: Vec::new(),
+                });
+            }
+        }
     }
+
+    Ok(results)
 }
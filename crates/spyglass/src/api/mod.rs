@@ -1,4 +1,5 @@
-use crate::task::lens::install_lens;
+use crate::task::lens::{check_lens_update, install_lens};
+use chrono::{DateTime, Utc};
 use entities::get_library_stats;
 use entities::models::indexed_document;
 use entities::sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter};
@@ -9,7 +10,7 @@ use jsonrpsee::{PendingSubscriptionSink, SubscriptionMessage};
 use libspyglass::state::AppState;
 use libspyglass::task::{CollectTask, ManagerCommand};
 use shared::config::{Config, UserSettings};
-use shared::llm::{ChatMessage, LlmSession};
+use shared::llm::{ChatMessage, ChatStream, LlmSession};
 use shared::request::{BatchDocumentRequest, RawDocumentRequest, SearchLensesParam, SearchParam};
 use shared::response::{self as resp, DefaultIndices, LibraryStats};
 use spyglass_rpc::{server_error, RpcEventType, RpcServer};
@@ -32,7 +33,55 @@ impl RpcServer for SpyglassRpc {
     }
 
     fn system_health(&self) -> RpcResult<JsonValue> {
-        Ok(serde_json::json!({ "health": true }))
+        // Changing index languages requires a reindex, so surface whether the
+        // current index was built w/ the configured ones.
+        let configured = self.state.user_settings.load().index_languages.clone();
+        let expected = spyglass_searcher::schema::tokenizer_name_for_languages(&configured);
+        let actual = spyglass_searcher::schema::content_tokenizer(&self.state.index.index.schema());
+
+        // Flags downloaded model files whose contents no longer match the
+        // checksum recorded at download time.
+        let corrupt_models = libspyglass::model_files::verify_models(&self.config)
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<String>>();
+
+        let embedding_api = self.state.embedding_api.load_full();
+        let embedding_api = embedding_api.as_ref();
+        let embedding_status = self.state.embedding_status.load_full();
+
+        // Which model answers chat requests: a remote server when one is
+        // configured, otherwise the local gguf (which may still need to be
+        // downloaded).
+        let llm_settings = self.state.user_settings.load().llm_settings.clone();
+        let active_model = llm_settings.gguf_path(&self.config.llm_model_dir());
+
+        Ok(serde_json::json!({
+            "health": true,
+            "index_languages": configured,
+            "index_matches_languages": actual.as_deref() == Some(expected.as_str()),
+            // Lets users confirm a restore actually brought their docs back.
+            "num_docs": self.state.index.reader.searcher().num_docs(),
+            // Which device the embedding model ended up on (null when
+            // embeddings are disabled or the model failed to load).
+            "embedding_device": embedding_api.as_ref().map(|api| api.device_name()),
+            // Lets clients tell users why semantic search is unavailable
+            // instead of it silently falling back to keyword search.
+            "embeddings": {
+                "model": self.state.user_settings.load().embedding_settings.model_id(),
+                "device": embedding_api.as_ref().map(|api| api.device_name()),
+                "dtype": embedding_api.as_ref().map(|api| api.dtype_name()),
+                "status": embedding_status.as_str(),
+                "error": embedding_status.error(),
+            },
+            "llm": {
+                "remote_url": llm_settings.remote_url,
+                "active_model": active_model.display().to_string(),
+                "model_installed": active_model.is_file(),
+            },
+            "models_verified": corrupt_models.is_empty(),
+            "corrupt_model_files": corrupt_models,
+        }))
     }
 
     async fn add_raw_document(&self, req: RawDocumentRequest) -> RpcResult<()> {
@@ -43,6 +92,10 @@ impl RpcServer for SpyglassRpc {
         handler::add_document_batch(&self.state, &req).await
     }
 
+    async fn backup(&self, path: String) -> RpcResult<resp::BackupResult> {
+        handler::backup(self.state.clone(), path).await
+    }
+
     async fn authorize_connection(&self, id: String) -> RpcResult<()> {
         handler::authorize_connection(self.state.clone(), id).await
     }
@@ -51,15 +104,135 @@ impl RpcServer for SpyglassRpc {
         handler::app_status(self.state.clone()).await
     }
 
+    async fn ask_library(
+        &self,
+        question: String,
+        lenses: Vec<String>,
+    ) -> RpcResult<resp::AskLibraryResult> {
+        handler::ask_library(self.state.clone(), question, lenses).await
+    }
+
     async fn chat_completion(&self, session: LlmSession) -> RpcResult<ChatMessage> {
         handler::chat_completion(self.state.clone(), &session).await
     }
 
+    async fn chat(&self, sink: PendingSubscriptionSink, session: LlmSession) -> SubscriptionResult {
+        let sink = match sink.accept().await {
+            Ok(sink) => sink,
+            Err(err) => {
+                log::warn!("Unable to accept subscription: {err}");
+                return Err(StringError::from("SubscriptionEmptyError"));
+            }
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<ChatStream>(10);
+        let (cancel_tx, cancel_rx) = tokio::sync::watch::channel(false);
+        // Forward tokens to the subscriber. When the subscription is dropped
+        // mid-generation the cancel token is flipped so the sampler loop
+        // stops.
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if sink.is_closed() {
+                    log::debug!("chat subscription dropped, cancelling generation");
+                    break;
+                }
+
+                match SubscriptionMessage::from_json(&msg) {
+                    Ok(sub_msg) => {
+                        if let Err(err) = sink.send(sub_msg).await {
+                            log::warn!("unable to send to sub: {err}");
+                            break;
+                        }
+                    }
+                    Err(err) => log::warn!("unable to serialize: {err}"),
+                }
+
+                if matches!(
+                    msg,
+                    ChatStream::ChatDone(_) | ChatStream::Error(_) | ChatStream::Cancelled
+                ) {
+                    break;
+                }
+            }
+
+            // Harmless once generation has already finished.
+            let _ = cancel_tx.send(true);
+        });
+
+        let state = self.state.clone();
+        let err_tx = tx.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handler::chat_with_llm(&state, &session, tx, Some(cancel_rx)).await {
+                // Model load failures never make it onto the stream, so
+                // surface them here.
+                let _ = err_tx.send(ChatStream::Error(err.to_string())).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn check_lens_update(&self, name: String) -> RpcResult<resp::LensUpdateDiff> {
+        match check_lens_update(&self.state, &name).await {
+            Ok(diff) => Ok(diff),
+            Err(err) => Err(server_error(err.to_string(), None)),
+        }
+    }
+
+    async fn create_chat_session(&self, doc_id: String) -> RpcResult<resp::ChatSessionResult> {
+        handler::create_chat_session(self.state.clone(), doc_id).await
+    }
+
+    async fn append_chat_message(
+        &self,
+        session_id: i64,
+        content: String,
+    ) -> RpcResult<ChatMessage> {
+        handler::append_chat_message(self.state.clone(), session_id, content).await
+    }
+
+    async fn list_chat_sessions(&self) -> RpcResult<Vec<resp::ChatSessionResult>> {
+        handler::list_chat_sessions(self.state.clone()).await
+    }
+
     /// Default folders used in the local file indexer
     async fn default_indices(&self) -> RpcResult<DefaultIndices> {
         Ok(handler::default_indices().await)
     }
 
+    async fn explain_search(
+        &self,
+        doc_id: String,
+        query: String,
+        lenses: Vec<String>,
+    ) -> RpcResult<resp::ExplainResult> {
+        handler::search::explain_search(self.state.clone(), doc_id, query, lenses).await
+    }
+
+    async fn export_lens(&self, name: String, path: String) -> RpcResult<resp::LensExportResult> {
+        handler::export_lens(self.state.clone(), name, path).await
+    }
+
+    async fn favorite_document(&self, doc_id: String) -> RpcResult<()> {
+        handler::favorite_document(self.state.clone(), &doc_id).await
+    }
+
+    async fn get_document(
+        &self,
+        doc_id: String,
+        full_content: bool,
+    ) -> RpcResult<resp::DocumentDetails> {
+        handler::get_document(self.state.clone(), &doc_id, full_content).await
+    }
+
+    async fn list_favorites(&self) -> RpcResult<Vec<resp::SearchResult>> {
+        handler::list_favorites(self.state.clone()).await
+    }
+
+    async fn unfavorite_document(&self, doc_id: String) -> RpcResult<()> {
+        handler::unfavorite_document(self.state.clone(), &doc_id).await
+    }
+
     /// Delete a single doc
     async fn delete_document(&self, id: String) -> RpcResult<()> {
         handler::delete_document(self.state.clone(), id).await
@@ -77,8 +250,26 @@ impl RpcServer for SpyglassRpc {
         }
     }
 
+    async fn optimize_index(&self) -> RpcResult<resp::OptimizeResult> {
+        handler::optimize_index(self.state.clone()).await
+    }
+
+    async fn reindex(&self) -> RpcResult<u64> {
+        handler::reindex(self.state.clone()).await
+    }
+
+    async fn get_similar_documents(
+        &self,
+        doc_id: String,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<resp::SearchResult>> {
+        handler::search::get_similar_documents(self.state.clone(), &doc_id, limit).await
+    }
+
     async fn get_library_stats(&self) -> RpcResult<HashMap<String, LibraryStats>> {
-        match get_library_stats(&self.state.db).await {
+        let index_size =
+            spyglass_searcher::utils::index_size_bytes(&self.state.config.index_dir());
+        match get_library_stats(&self.state.db, index_size).await {
             Ok(stats) => Ok(stats),
             Err(err) => {
                 log::error!("Unable to get library stats: {}", err);
@@ -87,6 +278,10 @@ impl RpcServer for SpyglassRpc {
         }
     }
 
+    async fn are_documents_indexed(&self, urls: Vec<String>) -> RpcResult<HashMap<String, bool>> {
+        handler::are_documents_indexed(self.state.clone(), urls).await
+    }
+
     async fn is_document_indexed(&self, url: String) -> RpcResult<bool> {
         // Normalize URL
         if let Ok(mut url) = url::Url::parse(&url) {
@@ -122,6 +317,18 @@ impl RpcServer for SpyglassRpc {
         handler::list_installed_lenses(self.state.clone()).await
     }
 
+    async fn list_llm_models(&self) -> RpcResult<Vec<resp::LlmModelResult>> {
+        handler::list_llm_models(self.state.clone()).await
+    }
+
+    async fn install_llm_model(&self, name_or_url: String) -> RpcResult<()> {
+        handler::install_llm_model(self.state.clone(), name_or_url).await
+    }
+
+    async fn remove_llm_model(&self, name: String) -> RpcResult<()> {
+        handler::remove_llm_model(self.state.clone(), name).await
+    }
+
     async fn install_lens(&self, lens_name: String) -> RpcResult<()> {
         if let Err(error) = install_lens(&self.state, &self.config, lens_name).await {
             return Err(server_error(error.to_string(), None));
@@ -133,10 +340,23 @@ impl RpcServer for SpyglassRpc {
         handler::list_plugins(self.state.clone()).await
     }
 
+    async fn list_tags(
+        &self,
+        label_filter: Option<String>,
+        prefix: Option<String>,
+        limit: Option<u32>,
+    ) -> RpcResult<Vec<resp::TagCountResult>> {
+        handler::list_tags(self.state.clone(), label_filter, prefix, limit).await
+    }
+
     async fn recrawl_domain(&self, domain: String) -> RpcResult<()> {
         handler::recrawl_domain(self.state.clone(), domain).await
     }
 
+    async fn refresh_lens(&self, name: String) -> RpcResult<()> {
+        handler::refresh_lens(self.state.clone(), &name).await
+    }
+
     async fn resync_connection(&self, api_id: String, account: String) -> RpcResult<()> {
         let _ = self
             .state
@@ -175,6 +395,19 @@ impl RpcServer for SpyglassRpc {
         Ok(())
     }
 
+    async fn run_plugin_now(&self, name: String) -> RpcResult<()> {
+        let tx = self.state.plugin_cmd_tx.lock().await;
+        match tx.as_ref() {
+            Some(tx) => {
+                let _ = tx
+                    .send(libspyglass::plugin::PluginCommand::RunPluginNow(name))
+                    .await;
+                Ok(())
+            }
+            None => Err(server_error("Plugin manager not running".into(), None)),
+        }
+    }
+
     async fn search_docs(&self, query: SearchParam) -> RpcResult<resp::SearchResults> {
         handler::search::search_docs(self.state.clone(), query).await
     }
@@ -183,6 +416,22 @@ impl RpcServer for SpyglassRpc {
         handler::search::search_lenses(self.state.clone(), query).await
     }
 
+    async fn set_embedding_model(&self, name: String) -> RpcResult<()> {
+        handler::set_embedding_model(&self.state, &self.config, &name).await
+    }
+
+    async fn suggest_lens(
+        &self,
+        min_visits: u64,
+        since: Option<DateTime<Utc>>,
+    ) -> RpcResult<resp::SuggestedLensResult> {
+        handler::suggest_lens(self.state.clone(), min_visits, since).await
+    }
+
+    async fn toggle_lens(&self, name: String, enabled: bool) -> RpcResult<()> {
+        handler::toggle_lens(self.state.clone(), &name, enabled).await
+    }
+
     async fn toggle_pause(&self, is_paused: bool) -> RpcResult<()> {
         handler::toggle_pause(self.state.clone(), is_paused).await
     }
@@ -191,6 +440,23 @@ impl RpcServer for SpyglassRpc {
         handler::uninstall_lens(self.state.clone(), &self.config, &name).await
     }
 
+    async fn update_document_tags(
+        &self,
+        doc_id: String,
+        add: Vec<(String, String)>,
+        remove: Vec<(String, String)>,
+    ) -> RpcResult<()> {
+        handler::update_document_tags(self.state.clone(), &doc_id, add, remove).await
+    }
+
+    async fn summarize_document(
+        &self,
+        doc_id: String,
+        max_words: Option<u64>,
+    ) -> RpcResult<String> {
+        handler::summarize_document(self.state.clone(), doc_id, max_words).await
+    }
+
     async fn update_user_settings(&self, settings: UserSettings) -> RpcResult<UserSettings> {
         handler::update_user_settings(&self.state, &self.config, &settings).await
     }
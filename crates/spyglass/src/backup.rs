@@ -0,0 +1,181 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use entities::sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use migration::{Migrator, MigratorTrait};
+use serde::{Deserialize, Serialize};
+use shared::config::Config;
+use shared::response::BackupResult;
+use spyglass_searcher::client::Searcher;
+use spyglass_searcher::schema::schema_for_languages;
+use spyglass_searcher::IndexBackend;
+use std::fs::File;
+use std::path::Path;
+
+use crate::state::AppState;
+
+/// Name of the manifest entry inside the archive.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Written into the archive so a restore can check compatibility before
+/// unpacking anything.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackupManifest {
+    pub created_at: DateTime<Utc>,
+    /// Version of the app that wrote the backup.
+    pub app_version: String,
+    /// Index schema version, see `spyglass_searcher::schema::SCHEMA_VERSION`.
+    pub schema_version: String,
+    /// Latest applied database migration, if any.
+    pub migration_version: Option<String>,
+}
+
+/// Snapshots the index, database & lens files into a single `tar.zst`
+/// archive at `dest`. Only named paths are added, which keeps logs & model
+/// weights out of the archive; both are recreated on a fresh install.
+///
+/// The index writer lock is held for the duration of the copy so segments
+/// can't be merged or committed underneath us.
+pub async fn create_backup(state: &AppState, dest: &Path) -> anyhow::Result<BackupResult> {
+    let config = &state.config;
+
+    // Flush any pending index writes before snapshotting.
+    state.index.save().await?;
+    let _writer = state.index.lock_writer()?;
+
+    let manifest = BackupManifest {
+        created_at: Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: spyglass_searcher::schema::SCHEMA_VERSION.to_string(),
+        migration_version: latest_migration(&state.db).await,
+    };
+
+    // Checkpoint the WAL so a copy of the main db file alone is complete.
+    let checkpoint = Statement::from_string(
+        state.db.get_database_backend(),
+        "PRAGMA wal_checkpoint(TRUNCATE);".to_string(),
+    );
+    let _ = state.db.execute(checkpoint).await;
+
+    let file = File::create(dest)?;
+    let encoder = zstd::stream::Encoder::new(file, 0)?;
+    let mut archive = tar::Builder::new(encoder);
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST_FILE, manifest_json.as_slice())?;
+
+    archive.append_dir_all("index", config.index_dir())?;
+
+    let db_path = config.data_dir().join("db.sqlite");
+    if db_path.exists() {
+        archive.append_path_with_name(&db_path, "db.sqlite")?;
+    }
+
+    if config.lenses_dir().exists() {
+        archive.append_dir_all("lenses", config.lenses_dir())?;
+    }
+
+    let file = archive.into_inner()?.finish()?;
+    file.sync_all()?;
+    let bytes_written = file.metadata()?.len();
+
+    Ok(BackupResult {
+        archive_path: dest.to_path_buf(),
+        bytes_written,
+    })
+}
+
+/// Restores an archive created by `create_backup` into the data dir and runs
+/// any migrations that landed since the backup was taken. Refuses to restore
+/// over a non-empty install unless `force` is set. Returns the number of
+/// documents in the restored index so the caller can confirm it worked.
+pub async fn restore_backup(config: &Config, archive: &Path, force: bool) -> anyhow::Result<u64> {
+    let manifest = read_manifest(archive)?;
+
+    // A backup written by a newer build may reference migrations this one
+    // doesn't know about; refuse rather than end up w/ a half-migrated db.
+    if let Some(version) = &manifest.migration_version {
+        let known = Migrator::migrations().iter().any(|m| m.name() == version);
+        if !known {
+            return Err(anyhow!(
+                "Backup was created by a newer version (unknown migration: {version})"
+            ));
+        }
+    }
+
+    let db_path = config.data_dir().join("db.sqlite");
+    if !force && (db_path.exists() || dir_has_files(&config.index_dir())) {
+        return Err(anyhow!(
+            "Data directory {} is not empty, use --force to overwrite",
+            config.data_dir().display()
+        ));
+    }
+
+    // Clear out whatever is there so stale segment files can't outlive the
+    // restore.
+    if config.index_dir().exists() {
+        std::fs::remove_dir_all(config.index_dir())?;
+    }
+    if db_path.exists() {
+        std::fs::remove_file(&db_path)?;
+    }
+
+    std::fs::create_dir_all(config.data_dir())?;
+    let mut tarball = tar::Archive::new(zstd::stream::Decoder::new(File::open(archive)?)?);
+    for entry in tarball.entries()? {
+        let mut entry = entry?;
+        // The manifest only describes the archive, don't unpack it.
+        if entry.path()?.as_ref() == Path::new(MANIFEST_FILE) {
+            continue;
+        }
+
+        entry.unpack_in(config.data_dir())?;
+    }
+
+    // Apply any migrations that landed since the backup was taken.
+    let db = entities::models::create_connection(config, false).await?;
+    Migrator::up(&db, None).await?;
+
+    let index = Searcher::with_index(
+        &IndexBackend::LocalPath(config.index_dir()),
+        schema_for_languages(&config.user_settings.index_languages),
+        true,
+    )?;
+
+    Ok(index.reader.searcher().num_docs())
+}
+
+/// Reads just the manifest entry out of a backup archive.
+pub fn read_manifest(archive: &Path) -> anyhow::Result<BackupManifest> {
+    let mut tarball = tar::Archive::new(zstd::stream::Decoder::new(File::open(archive)?)?);
+    for entry in tarball.entries()? {
+        let entry = entry?;
+        if entry.path()?.as_ref() == Path::new(MANIFEST_FILE) {
+            return Ok(serde_json::from_reader(entry)?);
+        }
+    }
+
+    Err(anyhow!("No {MANIFEST_FILE} found in archive"))
+}
+
+fn dir_has_files(path: &Path) -> bool {
+    std::fs::read_dir(path)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false)
+}
+
+/// Latest applied migration from sea-orm's bookkeeping table.
+async fn latest_migration(db: &DatabaseConnection) -> Option<String> {
+    let query = Statement::from_string(
+        db.get_database_backend(),
+        "SELECT version FROM seaql_migrations ORDER BY version DESC LIMIT 1".to_string(),
+    );
+
+    match db.query_one(query).await {
+        Ok(Some(row)) => row.try_get::<String>("", "version").ok(),
+        _ => None,
+    }
+}
@@ -145,6 +145,7 @@ impl Connection for DriveConnection {
                     tags: self.default_tags(),
                     force_allow: true,
                     is_recrawl: true,
+                    ..Default::default()
                 };
 
                 if let Err(err) = crawl_queue::enqueue_all(
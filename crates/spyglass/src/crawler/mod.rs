@@ -1,6 +1,7 @@
 use addr::parse_domain_name;
 use anyhow::Result;
 use chrono::prelude::*;
+use chrono::DateTime;
 use chrono::Duration;
 use entities::models::tag::TagPair;
 use entities::models::tag::TagType;
@@ -10,18 +11,23 @@ use governor::clock::QuantaClock;
 use governor::state::keyed::DashMapStateStore;
 use governor::Quota;
 use governor::RateLimiter;
-use libnetrunner::crawler::handle_crawl;
+use libnetrunner::archive::ArchiveRecord;
 use libnetrunner::parser::html::{html_to_text, DEFAULT_DESC_LENGTH};
 use nonzero_ext::nonzero;
 use percent_encoding::percent_decode_str;
-use reqwest::Client;
+use regex::RegexSet;
+use reqwest::{Client, StatusCode};
+use scraper::{Html, Selector};
 use sha2::{Digest, Sha256};
+use shared::config::LensConfig;
 use std::collections::HashSet;
 use std::num::NonZeroU32;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use thiserror::Error;
+use tokio_retry::strategy::ExponentialBackoff;
+use tokio_retry::RetryIf;
 use url::{Host, Url};
 
 use crate::connection::load_connection;
@@ -67,6 +73,9 @@ pub enum CrawlError {
     NotModified,
     #[error("document was recently fetched")]
     RecentlyFetched,
+    /// Server rate limited us (HTTP 429), crawler will back off & try again.
+    #[error("rate limited, retry after {retry_after_ms}ms")]
+    RateLimited { retry_after_ms: i64 },
     /// Request timeout, crawler will try again later.
     #[error("document request timed out")]
     Timeout,
@@ -97,6 +106,9 @@ pub struct CrawlResult {
     pub links: HashSet<String>,
     /// Tags to apply to this document
     pub tags: Vec<TagPair>,
+    /// When the document was published, if known. Set by importers (e.g.
+    /// browser history) where the source has a meaningful timestamp.
+    pub published_at: Option<DateTime<Utc>>,
 }
 
 impl CrawlResult {
@@ -131,6 +143,56 @@ impl CrawlResult {
     }
 }
 
+/// Parse a `Retry-After` header value, either a number of seconds or an
+/// HTTP date, into a duration from now.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<i64>() {
+        return Some(Duration::seconds(secs.max(0)));
+    }
+
+    // Not a number? Try parsing as an HTTP date.
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        let delta = date.with_timezone(&Utc) - Utc::now();
+        return Some(delta.max(Duration::zero()));
+    }
+
+    None
+}
+
+/// Error from a single fetch attempt, w/ enough of the response kept around
+/// to decide whether a retry is worthwhile.
+#[derive(Debug)]
+enum FetchFailure {
+    /// Server rate limited us (HTTP 429); `Retry-After` hint in ms when one
+    /// was sent, 0 otherwise.
+    RateLimited { retry_after_ms: i64 },
+    /// An error status that'll repeat on every attempt (403/404).
+    Permanent(String),
+    /// Anything else: timeouts, connection errors, 5xx, etc.
+    Transient(String),
+}
+
+impl From<FetchFailure> for CrawlError {
+    fn from(err: FetchFailure) -> Self {
+        match err {
+            FetchFailure::RateLimited { retry_after_ms } => {
+                CrawlError::RateLimited { retry_after_ms }
+            }
+            FetchFailure::Permanent(msg) | FetchFailure::Transient(msg) => {
+                CrawlError::FetchError(msg)
+            }
+        }
+    }
+}
+
+/// Whether a failed fetch attempt is worth retrying. Permanent errors fail
+/// the same way every time & rate limits are handled by cooling down the
+/// whole domain in the queue, not by retrying inline.
+fn should_retry(err: &FetchFailure) -> bool {
+    matches!(err, FetchFailure::Transient(_))
+}
+
 fn normalize_href(url: &str, href: &str) -> Option<String> {
     // Force HTTPS, crawler will fallback to HTTP if necessary.
     if let Ok(url) = Url::parse(url) {
@@ -257,26 +319,112 @@ impl Crawler {
     }
 
     /// Fetches and parses the content of a page.
-    async fn crawl(&self, url: &Url, parse_results: bool) -> Result<CrawlResult, CrawlError> {
-        match handle_crawl(&self.client, None, self.limiter.clone(), url).await {
-            Ok(crawl) => {
-                if parse_results {
-                    let result = self.scrape_page(url, &crawl.headers, &crawl.content).await;
-                    match result {
-                        Some(crawl) => Ok(crawl),
-                        None => Err(CrawlError::Unsupported(format!(
-                            "Content Type unsupported {url:?}"
-                        ))),
-                    }
-                } else {
-                    Ok(CrawlResult {
-                        url: crawl.url.clone(),
-                        open_url: Some(crawl.url),
-                        ..Default::default()
-                    })
-                }
+    async fn crawl(
+        &self,
+        url: &Url,
+        parse_results: bool,
+        lens: Option<&LensConfig>,
+    ) -> Result<CrawlResult, CrawlError> {
+        let crawl = self.fetch_page(url).await?;
+        if parse_results {
+            let result = self
+                .scrape_page(url, &crawl.headers, &crawl.content, lens)
+                .await;
+            match result {
+                Some(crawl) => Ok(crawl),
+                None => Err(CrawlError::Unsupported(format!(
+                    "Content Type unsupported {url:?}"
+                ))),
             }
-            Err(err) => Err(CrawlError::FetchError(err.to_string())),
+        } else {
+            Ok(CrawlResult {
+                url: crawl.url.clone(),
+                open_url: Some(crawl.url),
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Fetches `url`, trying the Internet Archive first & falling back to
+    /// the origin (mirroring netrunner's handle_crawl). Implemented here so
+    /// the failing response itself is available: a 429 is detected off the
+    /// status code & the server's `Retry-After` hint read straight from the
+    /// response instead of probing the server w/ a second request.
+    async fn fetch_page(&self, url: &Url) -> Result<ArchiveRecord, CrawlError> {
+        let domain = url.domain().unwrap_or_default().to_string();
+        let retry_strat = ExponentialBackoff::from_millis(100)
+            .max_delay(std::time::Duration::from_secs(5))
+            .take(3);
+
+        // Try the Internet Archive first; if anything goes wrong there
+        // (including the archive rate limiting us), hit the origin directly.
+        let archive_url = create_archive_url(url.as_ref());
+        let archived = RetryIf::spawn(
+            retry_strat.clone(),
+            || async {
+                // Wait for when we can crawl this based on the domain
+                self.limiter.until_key_ready(&domain).await;
+                self.fetch_once(&archive_url, Some(url.to_string())).await
+            },
+            should_retry,
+        )
+        .await;
+        if let Ok(record) = archived {
+            return Ok(record);
+        }
+
+        RetryIf::spawn(
+            retry_strat,
+            || async {
+                self.limiter.until_key_ready(&domain).await;
+                self.fetch_once(url.as_ref(), None).await
+            },
+            should_retry,
+        )
+        .await
+        .map_err(CrawlError::from)
+    }
+
+    /// A single request w/ the response status & headers inspected directly.
+    async fn fetch_once(
+        &self,
+        url: &str,
+        url_override: Option<String>,
+    ) -> Result<ArchiveRecord, FetchFailure> {
+        let resp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| FetchFailure::Transient(err.to_string()))?;
+
+        // We're hitting this host too hard & should back off before
+        // retrying; surface the server's hint so the queue can cool the
+        // whole domain down.
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after_ms = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after)
+                .map(|delay| delay.num_milliseconds())
+                .unwrap_or(0);
+            return Err(FetchFailure::RateLimited { retry_after_ms });
+        }
+
+        if let Err(err) = resp.error_for_status_ref() {
+            let msg = err.to_string();
+            return Err(match resp.status() {
+                StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => FetchFailure::Permanent(msg),
+                _ => FetchFailure::Transient(msg),
+            });
+        }
+
+        match ArchiveRecord::from_response(resp, url_override).await {
+            Ok(record) => Ok(record),
+            Err(err) => Err(FetchFailure::Transient(format!(
+                "Unable to create ArchiveRecord: {err}"
+            ))),
         }
     }
 
@@ -285,6 +433,7 @@ impl Crawler {
         url: &Url,
         headers: &[(String, String)],
         raw_body: &str,
+        lens: Option<&LensConfig>,
     ) -> Option<CrawlResult> {
         // Parse the html.
         log::debug!("Scraping page {:?}", url);
@@ -297,7 +446,15 @@ impl Crawler {
                 return None;
             }
         }
-        let parse_result = html_to_text(url.as_ref(), raw_body);
+
+        // When the matching lens scopes content w/ CSS selectors, hand only
+        // that markup to the parser so nav & boilerplate stay out of the
+        // index. If nothing matches, fall back to whole-page extraction.
+        let scoped = lens.and_then(|lens| extract_with_selectors(raw_body, lens));
+        let parse_result = match &scoped {
+            Some(scoped) => html_to_text(url.as_ref(), scoped),
+            None => html_to_text(url.as_ref(), raw_body),
+        };
         log::debug!("content hash: {:?}", parse_result.content_hash);
 
         let extracted = parse_result.canonical_url.and_then(|s| Url::parse(&s).ok());
@@ -362,7 +519,10 @@ impl Crawler {
             "api" => self.handle_api_fetch(state, &crawl, &url).await,
             "file" => self.handle_file_fetch(state, &crawl, &url).await,
             "http" | "https" => {
-                self.handle_http_fetch(&state.db, &crawl, &url, parse_results)
+                // Lenses can scope content extraction w/ CSS selectors; find
+                // the one that matches this URL (if any) before parsing.
+                let lens = find_content_selectors(state, &url);
+                self.handle_http_fetch(&state.db, &crawl, &url, parse_results, lens.as_ref())
                     .await
             }
             // unknown scheme, ignore
@@ -431,6 +591,7 @@ impl Crawler {
         crawl: &crawl_queue::Model,
         url: &Url,
         parse_results: bool,
+        lens: Option<&LensConfig>,
     ) -> Result<CrawlResult, CrawlError> {
         // Modify bootstrapped URLs to pull from the Internet Archive
         let url: Url = if crawl.crawl_type == crawl_queue::CrawlType::Bootstrap {
@@ -451,7 +612,7 @@ impl Crawler {
         }
 
         // Crawl & save the data
-        match self.crawl(&url, parse_results).await {
+        match self.crawl(&url, parse_results, lens).await {
             Err(err) => {
                 log::debug!("issue fetching {:?} - {}", url, err.to_string());
                 Err(err)
@@ -647,6 +808,7 @@ async fn _process_file(
         open_url: Some(url.to_string()),
         links: Default::default(),
         tags,
+        published_at: None,
     })
 }
 
@@ -672,6 +834,86 @@ fn _matches_ext(path: &Path, extension: &HashSet<String>) -> bool {
     extension.contains(ext)
 }
 
+/// Finds the first enabled lens declaring content selectors whose filters
+/// match `url`. Most lenses don't declare selectors, so the common case
+/// never builds a regex set.
+fn find_content_selectors(state: &AppState, url: &Url) -> Option<LensConfig> {
+    for entry in state.lenses.iter() {
+        let lens = entry.value();
+        if !lens.is_enabled
+            || (lens.content_selector.is_none() && lens.exclude_selectors.is_empty())
+        {
+            continue;
+        }
+
+        let filters = lens.into_regexes();
+        if let (Ok(allowed), Ok(skipped)) = (
+            RegexSet::new(filters.allowed),
+            RegexSet::new(filters.skipped),
+        ) {
+            if allowed.is_match(url.as_str()) && !skipped.is_match(url.as_str()) {
+                return Some(lens.clone());
+            }
+        }
+    }
+
+    None
+}
+
+/// Reduces `raw_body` to the markup selected by the lens, w/ any excluded
+/// subtrees removed first. Returns `None` when the lens has no applicable
+/// selectors or the content selector matches nothing, so callers can fall
+/// back to whole-page extraction.
+fn extract_with_selectors(raw_body: &str, lens: &LensConfig) -> Option<String> {
+    let mut doc = Html::parse_document(raw_body);
+
+    // Drop excluded subtrees first so they can't leak into the content.
+    for selector in &lens.exclude_selectors {
+        let selector = match Selector::parse(selector) {
+            Ok(selector) => selector,
+            Err(err) => {
+                log::warn!("<{}> invalid exclude selector: {}", lens.name, err);
+                continue;
+            }
+        };
+
+        let matches = doc
+            .select(&selector)
+            .map(|element| element.id())
+            .collect::<Vec<_>>();
+        for id in matches {
+            if let Some(mut node) = doc.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+    }
+
+    match lens.content_selector.as_deref() {
+        Some(selector) => {
+            let selector = match Selector::parse(selector) {
+                Ok(selector) => selector,
+                Err(err) => {
+                    log::warn!("<{}> invalid content selector: {}", lens.name, err);
+                    return None;
+                }
+            };
+
+            let matched = doc
+                .select(&selector)
+                .map(|element| element.html())
+                .collect::<Vec<String>>();
+            if matched.is_empty() {
+                return None;
+            }
+
+            Some(matched.join("\n"))
+        }
+        // Exclusions alone still apply against the whole page.
+        None if !lens.exclude_selectors.is_empty() => Some(doc.html()),
+        None => None,
+    }
+}
+
 fn is_html_content(content_type: &str) -> bool {
     content_type.contains("text/html") || content_type.contains("application/xhtml+xml")
 }
@@ -685,9 +927,12 @@ mod test {
     use entities::sea_orm::{ActiveModelTrait, Set};
     use entities::test::setup_test_db;
 
-    use crate::crawler::{determine_canonical, normalize_href, Crawler};
+    use crate::crawler::{
+        determine_canonical, extract_with_selectors, normalize_href, parse_retry_after, Crawler,
+    };
     use crate::filesystem::utils::path_to_uri;
     use crate::state::AppState;
+    use shared::config::LensConfig;
     use url::Url;
 
     #[tokio::test]
@@ -695,7 +940,7 @@ mod test {
     async fn test_crawl() {
         let crawler = Crawler::default();
         let url = Url::parse("https://oldschool.runescape.wiki").unwrap();
-        let result = crawler.crawl(&url, true).await.expect("success");
+        let result = crawler.crawl(&url, true, None).await.expect("success");
 
         assert_eq!(result.title, Some("Old School RuneScape Wiki".to_string()));
         assert_eq!(result.url, "https://oldschool.runescape.wiki/".to_string());
@@ -809,6 +1054,59 @@ mod test {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_extract_with_selectors() {
+        let html = include_str!("../../../../fixtures/html/selector_test.html");
+        let lens = LensConfig {
+            content_selector: Some("#main-content".into()),
+            exclude_selectors: vec![".sidebar".into(), ".comments".into()],
+            ..Default::default()
+        };
+
+        let scoped = extract_with_selectors(html, &lens).expect("selector should match");
+        assert!(scoped.contains("The useful article body."));
+        assert!(!scoped.contains("Navigation junk"));
+        assert!(!scoped.contains("First comment!"));
+        assert!(!scoped.contains("Footer boilerplate"));
+
+        // Nothing matches -> fall back to whole-page extraction.
+        let missing = LensConfig {
+            content_selector: Some("#no-such-node".into()),
+            ..Default::default()
+        };
+        assert!(extract_with_selectors(html, &missing).is_none());
+
+        // Exclusions alone still strip boilerplate from the whole page.
+        let excludes_only = LensConfig {
+            exclude_selectors: vec![".sidebar".into()],
+            ..Default::default()
+        };
+        let scoped = extract_with_selectors(html, &excludes_only).expect("should apply");
+        assert!(scoped.contains("Footer boilerplate"));
+        assert!(!scoped.contains("Navigation junk"));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_page_with_selectors() {
+        let crawler = Crawler::default();
+        let html = include_str!("../../../../fixtures/html/selector_test.html");
+        let url = Url::parse("https://example.com/article").unwrap();
+        let lens = LensConfig {
+            content_selector: Some("#main-content".into()),
+            exclude_selectors: vec![".comments".into()],
+            ..Default::default()
+        };
+
+        let result = crawler
+            .scrape_page(&url, &[], html, Some(&lens))
+            .await
+            .expect("scrape failed");
+        let content = result.content.unwrap_or_default();
+        assert!(content.contains("The useful article body."));
+        assert!(!content.contains("Navigation junk"));
+        assert!(!content.contains("First comment!"));
+    }
+
     #[test]
     fn test_normalize_href() {
         let url = "https://example.com";
@@ -839,6 +1137,25 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_retry_after() {
+        // Number of seconds
+        let res = parse_retry_after("120").expect("parse failed");
+        assert_eq!(res.num_seconds(), 120);
+
+        // HTTP date, should be clamped to zero since it's in the past.
+        let res = parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT").expect("parse failed");
+        assert_eq!(res.num_seconds(), 0);
+
+        // HTTP date in the future
+        let future = chrono::Utc::now() + chrono::Duration::minutes(5);
+        let res = parse_retry_after(&future.to_rfc2822()).expect("parse failed");
+        assert!(res.num_seconds() > 0 && res.num_seconds() <= 5 * 60);
+
+        // Garbage
+        assert!(parse_retry_after("not-a-date").is_none());
+    }
+
     #[test]
     fn test_determine_canonical() {
         // Test a correct override
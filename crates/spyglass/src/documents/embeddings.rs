@@ -1,87 +1,235 @@
 use entities::{
     models::{embedding_queue, vec_documents, vec_to_indexed},
-    sea_orm::EntityTrait,
+    sea_orm::{ColumnTrait, EntityTrait, QueryFilter},
 };
-use spyglass_model_interface::embedding_api::EmbeddingContentType;
+use sha2::{Digest, Sha256};
+use spyglass_model_interface::embedding_api::{EmbeddingContentType, SegmentEmbedding};
 
 use crate::state::AppState;
 
-pub async fn trigger_processing_embedding(state: &AppState, job_id: i64) {
-    let _ = tokio::spawn(processing_embedding(state.clone(), job_id)).await;
+/// Hash used to key cached segment vectors, matches the content hashing done
+/// by the crawler.
+fn embedding_content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(&hasher.finalize()[..])
 }
 
-pub async fn processing_embedding(state: AppState, job_id: i64) {
-    match embedding_queue::Entity::find_by_id(job_id)
-        .one(&state.db)
+pub async fn trigger_processing_embedding_batch(state: &AppState, job_ids: Vec<i64>) {
+    let _ = tokio::spawn(processing_embedding_batch(state.clone(), job_ids)).await;
+}
+
+/// Generates embeddings for a batch of queued documents in a single model
+/// pass, see `EmbeddingApi::embed_batch`.
+pub async fn processing_embedding_batch(state: AppState, job_ids: Vec<i64>) {
+    let jobs = match embedding_queue::Entity::find()
+        .filter(embedding_queue::Column::Id.is_in(job_ids.clone()))
+        .all(&state.db)
         .await
     {
-        Ok(Some(job)) => match job.content {
-            Some(content) => {
-                let embeddings = if let Some(api) = state.embedding_api.load_full().as_ref() {
-                    api.embed(&content, EmbeddingContentType::Document)
-                } else {
-                    Err(anyhow::format_err!(
-                        "Embedding Model is not properly configured"
-                    ))
-                };
-                match embeddings {
-                    Ok(embeddings) => {
-                        if let Err(error) = vec_to_indexed::delete_all_for_document(
-                            &state.db,
-                            job.indexed_document_id,
-                        )
-                        .await
-                        {
-                            log::error!("Error deleting document vectors {:?}", error);
-                        }
-
-                        for embedding in embeddings {
-                            match vec_to_indexed::insert_embedding_mapping(
-                                &state.db,
-                                job.indexed_document_id,
-                                embedding.start,
-                                embedding.end,
-                            )
-                            .await
-                            {
-                                Ok(insert_result) => {
-                                    let id: i64 = insert_result.last_insert_id;
-                                    match vec_documents::insert_embedding(
-                                        &state.db,
-                                        id,
-                                        &embedding.embedding,
-                                    )
-                                    .await
-                                    {
-                                        Ok(_) => {
-                                            let _ =
-                                                embedding_queue::mark_done(&state.db, job_id).await;
-                                        }
-                                        Err(error) => {
-                                            let _ = embedding_queue::mark_failed(
-                                                &state.db,
-                                                job_id,
-                                                Some(format!(
-                                                    "Error storing embedding for {}. Error {:?}",
-                                                    job.document_id, error
-                                                )),
-                                            )
-                                            .await;
-                                        }
-                                    }
-                                }
-                                Err(error) => {
-                                    log::error!("Error inserting mapping {:?}", error);
-                                }
-                            }
-                        }
+        Ok(jobs) => jobs,
+        Err(error) => {
+            for job_id in job_ids {
+                let _ = embedding_queue::mark_failed(
+                    &state.db,
+                    job_id,
+                    Some(format!(
+                        "Unable to access job {}. Error {:?}",
+                        job_id, error
+                    )),
+                )
+                .await;
+            }
+            return;
+        }
+    };
+
+    let mut with_content = Vec::new();
+    for job in jobs {
+        match job.content.clone() {
+            Some(content) => with_content.push((job, content)),
+            None => {
+                let _ = embedding_queue::mark_failed(
+                    &state.db,
+                    job.id,
+                    Some(format!("No content found for document {}", job.document_id)),
+                )
+                .await;
+            }
+        }
+    }
+
+    // Vectors are only reused if they were generated by the active model,
+    // embeddings from different models can't be mixed.
+    let model_id = state.user_settings.load().embedding_settings.model_id();
+
+    // Check the cache before running the model. Documents whose content
+    // hasn't changed since their vectors were generated (or that match
+    // another document's content exactly) can reuse the stored embeddings.
+    let mut to_embed = Vec::new();
+    let mut cache_hits: usize = 0;
+    for (job, content) in with_content {
+        let content_hash = embedding_content_hash(&content);
+        match vec_to_indexed::find_by_content_hash(&state.db, &model_id, &content_hash).await {
+            Ok(cached) if !cached.is_empty() => {
+                if cached
+                    .iter()
+                    .any(|row| row.indexed_id == job.indexed_document_id)
+                {
+                    // The stored vectors are already current for this document.
+                    embedding_queue::mark_done(&state.db, job.id, true).await;
+                    cache_hits += 1;
+                    continue;
+                }
+
+                match copy_cached_embeddings(&state, &job, &model_id, &content_hash, &cached).await
+                {
+                    Ok(()) => {
+                        embedding_queue::mark_done(&state.db, job.id, true).await;
+                        cache_hits += 1;
+                        continue;
+                    }
+                    Err(error) => {
+                        // Fall through & regenerate.
+                        log::warn!(
+                            "Unable to reuse cached embeddings for {}: {:?}",
+                            job.document_id,
+                            error
+                        );
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(error) => log::warn!("Error checking embedding cache: {:?}", error),
+        }
+
+        to_embed.push((job, content, content_hash));
+    }
+
+    if cache_hits > 0 {
+        log::debug!(
+            "Embedding cache: {} hits, {} misses",
+            cache_hits,
+            to_embed.len()
+        );
+    }
+
+    if to_embed.is_empty() {
+        return;
+    }
+
+    let contents = to_embed
+        .iter()
+        .map(|(_, content, _)| content.clone())
+        .collect::<Vec<String>>();
+    let embedded = if let Some(api) = state.embedding_api.load_full().as_ref() {
+        api.embed_batch(&contents, EmbeddingContentType::Document)
+    } else {
+        Err(anyhow::format_err!(
+            "Embedding Model is not properly configured"
+        ))
+    };
+
+    match embedded {
+        Ok(embedded) => {
+            for ((job, _, content_hash), embeddings) in to_embed.iter().zip(embedded) {
+                if embeddings.iter().any(|segment| segment.truncated) {
+                    log::warn!(
+                        "Segments for {} were truncated to the model's max sequence length, consider a smaller segment size",
+                        job.document_id
+                    );
+                }
+
+                store_embeddings(&state, job, &model_id, content_hash, embeddings).await;
+            }
+        }
+        Err(error) => {
+            for (job, _, _) in &to_embed {
+                let _ = embedding_queue::mark_failed(
+                    &state.db,
+                    job.id,
+                    Some(format!(
+                        "Error generating embedding for {}. Error {:?}",
+                        job.document_id, error
+                    )),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+/// Copies another document's stored vectors for identical content, so the
+/// model doesn't have to rerun. The copies are owned by this document &
+/// cascade w/ it on delete like freshly generated vectors.
+async fn copy_cached_embeddings(
+    state: &AppState,
+    job: &embedding_queue::Model,
+    model_id: &str,
+    content_hash: &str,
+    cached: &[vec_to_indexed::Model],
+) -> anyhow::Result<()> {
+    // Rows can come from several documents w/ the same content; copy from
+    // just one of them.
+    let source_id = cached[0].indexed_id;
+
+    vec_to_indexed::delete_all_for_document(&state.db, job.indexed_document_id).await?;
+    for row in cached.iter().filter(|row| row.indexed_id == source_id) {
+        let insert_result = vec_to_indexed::insert_embedding_mapping(
+            &state.db,
+            job.indexed_document_id,
+            model_id,
+            content_hash,
+            row.segment_start as usize,
+            row.segment_end as usize,
+            row.dims.unwrap_or_default() as usize,
+        )
+        .await?;
+        vec_documents::copy_embedding(&state.db, row.id, insert_result.last_insert_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the stored vectors for a job's document w/ freshly generated
+/// embeddings & marks the job done (or failed if storage errors out).
+async fn store_embeddings(
+    state: &AppState,
+    job: &embedding_queue::Model,
+    model_id: &str,
+    content_hash: &str,
+    embeddings: Vec<SegmentEmbedding>,
+) {
+    if let Err(error) =
+        vec_to_indexed::delete_all_for_document(&state.db, job.indexed_document_id).await
+    {
+        log::error!("Error deleting document vectors {:?}", error);
+    }
+
+    for embedding in embeddings {
+        match vec_to_indexed::insert_embedding_mapping(
+            &state.db,
+            job.indexed_document_id,
+            model_id,
+            content_hash,
+            embedding.start,
+            embedding.end,
+            embedding.embedding.len(),
+        )
+        .await
+        {
+            Ok(insert_result) => {
+                let id: i64 = insert_result.last_insert_id;
+                match vec_documents::insert_embedding(&state.db, id, &embedding.embedding).await {
+                    Ok(_) => {
+                        let _ = embedding_queue::mark_done(&state.db, job.id, false).await;
                     }
                     Err(error) => {
                         let _ = embedding_queue::mark_failed(
                             &state.db,
-                            job_id,
+                            job.id,
                             Some(format!(
-                                "Error generating embedding for {}. Error {:?}",
+                                "Error storing embedding for {}. Error {:?}",
                                 job.document_id, error
                             )),
                         )
@@ -89,33 +237,9 @@ pub async fn processing_embedding(state: AppState, job_id: i64) {
                     }
                 }
             }
-            None => {
-                let _ = embedding_queue::mark_failed(
-                    &state.db,
-                    job_id,
-                    Some(format!("No content found for document {}", job.document_id)),
-                )
-                .await;
+            Err(error) => {
+                log::error!("Error inserting mapping {:?}", error);
             }
-        },
-        Ok(None) => {
-            let _ = embedding_queue::mark_failed(
-                &state.db,
-                job_id,
-                Some(format!("Job {} not found", job_id)),
-            )
-            .await;
-        }
-        Err(error) => {
-            let _ = embedding_queue::mark_failed(
-                &state.db,
-                job_id,
-                Some(format!(
-                    "Unable to access job {}. Error {:?}",
-                    job_id, error
-                )),
-            )
-            .await;
         }
     }
 }
@@ -11,7 +11,11 @@ use entities::{
 };
 use serde::{Deserialize, Serialize};
 use shared::config::LensConfig;
-use std::{collections::HashMap, str::FromStr, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Instant,
+};
 
 use libnetrunner::parser::ParseResult;
 use url::Url;
@@ -28,6 +32,53 @@ pub mod embeddings;
 
 pub type Tag = (String, String);
 
+/// Detect the language of a document's content. Returns a two-letter
+/// (ISO 639-1) language code, e.g. "en" or "de", or None if the detection
+/// was unreliable.
+pub fn detect_language(content: &str) -> Option<String> {
+    use whatlang::Lang;
+
+    let info = whatlang::detect(content)?;
+    if !info.is_reliable() {
+        return None;
+    }
+
+    // whatlang only exposes ISO 639-3 codes, map the common ones to their
+    // two-letter equivalents & fall back to the three-letter code otherwise.
+    let code = match info.lang() {
+        Lang::Ara => "ar",
+        Lang::Ces => "cs",
+        Lang::Dan => "da",
+        Lang::Deu => "de",
+        Lang::Ell => "el",
+        Lang::Eng => "en",
+        Lang::Fin => "fi",
+        Lang::Fra => "fr",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Hun => "hu",
+        Lang::Ind => "id",
+        Lang::Ita => "it",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Nld => "nl",
+        Lang::Nob => "no",
+        Lang::Pol => "pl",
+        Lang::Por => "pt",
+        Lang::Rus => "ru",
+        Lang::Spa => "es",
+        Lang::Swe => "sv",
+        Lang::Tha => "th",
+        Lang::Tur => "tr",
+        Lang::Ukr => "uk",
+        Lang::Vie => "vi",
+        Lang::Cmn => "zh",
+        other => other.code(),
+    };
+
+    Some(code.to_string())
+}
+
 /// Defines a Tag modification request. Tags can be added or deleted
 #[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct TagModification {
@@ -124,6 +175,22 @@ pub async fn process_crawl_results(
         return Ok(AddUpdateResult::default());
     }
 
+    // Normalize URLs before upserting so variants that differ only by
+    // tracking params or fragments collapse into a single document.
+    let mut results = results.to_vec();
+    if !state.user_settings.load().disable_url_normalization {
+        let mut seen: HashSet<String> = HashSet::with_capacity(results.len());
+        for result in results.iter_mut() {
+            if let Ok(mut url) = Url::parse(&result.url) {
+                crawl_queue::normalize_url(&mut url);
+                result.url = url.to_string();
+            }
+        }
+        // Variants within this batch may have collapsed into the same URL.
+        results.retain(|result| seen.insert(result.url.clone()));
+    }
+    let results = &results;
+
     let now = Instant::now();
     // get a list of all urls
     let parsed_urls = results
@@ -146,8 +213,27 @@ pub async fn process_crawl_results(
         model_map.insert(model.doc_id.to_string(), model.clone());
     }
 
+    // Documents whose content hash matches what we indexed last time don't
+    // need to be reindexed; leave them in the index untouched.
+    let unchanged: HashSet<String> = results
+        .iter()
+        .filter_map(|result| {
+            id_map
+                .get(&result.url)
+                .and_then(|doc_id| model_map.get(doc_id))
+                .filter(|model| {
+                    result.content_hash.is_some() && model.content_hash == result.content_hash
+                })
+                .map(|model| model.doc_id.clone())
+        })
+        .collect();
+
     // build a list of doc ids to delete from the index
-    let doc_id_list = id_map.values().cloned().collect::<Vec<String>>();
+    let doc_id_list = id_map
+        .values()
+        .filter(|doc_id| !unchanged.contains(*doc_id))
+        .cloned()
+        .collect::<Vec<String>>();
 
     // Delete existing docs
     let _ = state.index.delete_many_by_id(&doc_id_list).await;
@@ -167,9 +253,35 @@ pub async fn process_crawl_results(
 
     let tx = state.db.begin().await?;
     for crawl_result in results {
+        // Nothing changed since the last crawl; touch the document so we
+        // know it was checked & skip the index write, embedding generation
+        // and tag churn entirely.
+        if let Some(model) = id_map
+            .get(&crawl_result.url)
+            .filter(|doc_id| unchanged.contains(*doc_id))
+            .and_then(|doc_id| model_map.get(doc_id))
+        {
+            let mut update: indexed_document::ActiveModel = model.to_owned().into();
+            update.updated_at = Set(Utc::now());
+            updates.push(update);
+            continue;
+        }
+
         // Fetch the tag ids to apply to this crawl.
         let mut tags_for_crawl = _get_tag_ids(&state.db, &crawl_result.tags, &mut tag_cache).await;
         tags_for_crawl.extend(global_tids.clone());
+
+        // Tag the document with its detected language, skipped when detection
+        // is unreliable.
+        if let Some(lang) = crawl_result
+            .content
+            .as_deref()
+            .and_then(detect_language)
+        {
+            let lang_tag = vec![(TagType::Language, lang)];
+            tags_for_crawl.extend(_get_tag_ids(&state.db, &lang_tag, &mut tag_cache).await);
+        }
+
         tag_map.insert(crawl_result.url.clone(), tags_for_crawl.clone());
 
         // Add document to index
@@ -187,7 +299,7 @@ pub async fn process_crawl_results(
                     url: url.as_str(),
                     content: &crawl_result.content.clone().unwrap_or_default(),
                     tags: &tags_for_crawl.clone(),
-                    published_at: None,
+                    published_at: crawl_result.published_at,
                     last_modified: None,
                 }
                 .to_document(),
@@ -206,12 +318,14 @@ pub async fn process_crawl_results(
                 open_url: Set(crawl_result.open_url.clone()),
                 doc_id: Set(doc_id),
                 updated_at: Set(Utc::now()),
+                content_hash: Set(crawl_result.content_hash.clone()),
                 ..Default::default()
             });
         } else if let Some(model) = model_map.get(&doc_id) {
             // Touch the existing model so we know it's been checked recently.
             let mut update: indexed_document::ActiveModel = model.to_owned().into();
             update.updated_at = Set(Utc::now());
+            update.content_hash = Set(crawl_result.content_hash.clone());
             updates.push(update);
         }
     }
@@ -401,8 +515,12 @@ pub async fn process_records(
     // Save the data
     indexed_document::insert_many(&transaction, &updates).await?;
     transaction.commit().await?;
-    if let Ok(mut writer) = state.index.lock_writer() {
-        let _ = writer.commit();
+    // With write buffering enabled (large imports) the index decides when to
+    // commit; otherwise flush this batch right away.
+    if !state.index.is_write_buffered() {
+        if let Ok(mut writer) = state.index.lock_writer() {
+            let _ = writer.commit();
+        }
     }
 
     let added_entries: Vec<indexed_document::Model> = indexed_document::Entity::find()
@@ -520,8 +638,9 @@ pub async fn update_tags(
                         url: &doc.url,
                         content: &doc.content,
                         tags: ids,
-                        published_at: None,
-                        last_modified: None,
+                        // Keep whatever dates the document already had.
+                        published_at: doc.published_at,
+                        last_modified: doc.last_modified,
                     }
                     .to_document(),
                 )
@@ -609,3 +728,87 @@ async fn _get_tag_ids(
 
     tids
 }
+
+#[cfg(test)]
+mod test {
+    use super::process_crawl_results;
+    use crate::crawler::CrawlResult;
+    use crate::state::AppState;
+    use entities::models::indexed_document;
+    use entities::sea_orm::EntityTrait;
+    use entities::test::setup_test_db;
+    use spyglass_searcher::SearchTrait;
+
+    #[tokio::test]
+    async fn test_process_crawl_results_skips_unchanged_content() {
+        let db = setup_test_db().await;
+        let state = AppState::builder().with_db(db.clone()).build();
+
+        let crawl = CrawlResult {
+            url: "https://example.com/test".into(),
+            open_url: Some("https://example.com/test".into()),
+            title: Some("test title".into()),
+            content: Some("test content".into()),
+            content_hash: Some("abc123".into()),
+            ..Default::default()
+        };
+
+        process_crawl_results(&state, &[crawl.clone()], &[])
+            .await
+            .expect("Unable to process crawl");
+        let _ = state.index.save().await;
+        let _ = state.index.reader.reload();
+
+        let model = indexed_document::Entity::find()
+            .one(&db)
+            .await
+            .expect("query failed")
+            .expect("doc not indexed");
+        assert_eq!(model.content_hash, Some("abc123".to_string()));
+
+        // Same hash but different content: the hash says nothing changed, so
+        // the index must not be rewritten & the old title should survive.
+        let mut unchanged = crawl.clone();
+        unchanged.title = Some("updated title".into());
+        unchanged.content = Some("updated content".into());
+        process_crawl_results(&state, &[unchanged], &[])
+            .await
+            .expect("Unable to process crawl");
+        let _ = state.index.save().await;
+        let _ = state.index.reader.reload();
+
+        assert_eq!(state.index.reader.searcher().num_docs(), 1);
+        let doc = state
+            .index
+            .get(&model.doc_id)
+            .await
+            .expect("doc missing from index");
+        assert_eq!(doc.title, "test title");
+
+        // A new hash reindexes as usual.
+        let mut changed = crawl.clone();
+        changed.title = Some("updated title".into());
+        changed.content = Some("updated content".into());
+        changed.content_hash = Some("def456".into());
+        process_crawl_results(&state, &[changed], &[])
+            .await
+            .expect("Unable to process crawl");
+        let _ = state.index.save().await;
+        let _ = state.index.reader.reload();
+
+        assert_eq!(state.index.reader.searcher().num_docs(), 1);
+        let doc = state
+            .index
+            .get(&model.doc_id)
+            .await
+            .expect("doc missing from index");
+        assert_eq!(doc.title, "updated title");
+
+        let model = indexed_document::Entity::find()
+            .one(&db)
+            .await
+            .expect("query failed")
+            .expect("doc not indexed");
+        assert_eq!(model.content_hash, Some("def456".to_string()));
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::anyhow;
+use entities::models::{indexed_document, lens};
+use entities::sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use libnetrunner::parser::ParseResult;
+use shared::config::LensConfig;
+use shared::response::LensExportResult;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::pipeline::cache_pipeline::process_update;
+use crate::state::AppState;
+use crate::task::lens::read_lenses;
+
+/// Name of the lens config entry inside an exported archive.
+pub const LENS_FILE: &str = "lens.ron";
+/// Name of the document cache entry inside an exported archive; same format
+/// the cache pipeline already consumes (gzipped `ParseResult` records, one
+/// RON record per line).
+pub const PARSED_FILE: &str = "parsed.ron.gz";
+
+/// How many documents are pulled from the db & index per batch while
+/// streaming an export.
+const BATCH_SIZE: usize = 500;
+
+/// Exports a lens & its indexed documents into a shareable `tar.gz` bundle at
+/// `dest`: the lens RON plus a document cache in the format `LoadArchive` &
+/// the cache pipeline consume. Documents are streamed out in batches so a
+/// large lens doesn't have to fit in memory. Embeddings aren't included;
+/// they're regenerated on import.
+pub async fn export_lens(
+    state: &AppState,
+    name: &str,
+    dest: &Path,
+) -> anyhow::Result<LensExportResult> {
+    let lens = match find_lens_config(state, name).await {
+        Some(lens) => lens,
+        None => return Err(anyhow!("lens {name} is not installed")),
+    };
+
+    let doc_ids = indexed_document::find_by_lens(state.db.clone(), name).await?;
+    let num_docs = doc_ids.len() as u64;
+    log::info!("exporting lens {} w/ {} documents", name, num_docs);
+
+    // Stream the document cache into a scratch file next to the archive so
+    // only one batch is in memory at a time.
+    let parsed_path = dest.with_extension("parsed.tmp");
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&parsed_path)?),
+        Compression::default(),
+    );
+    for chunk in doc_ids.chunks(BATCH_SIZE) {
+        let ids = chunk.iter().map(|doc| doc.id).collect::<Vec<i64>>();
+        let models = indexed_document::Entity::find()
+            .filter(indexed_document::Column::Id.is_in(ids))
+            .all(&state.db)
+            .await?;
+
+        // Content only lives in the index, metadata in the db.
+        let indexed_ids = chunk
+            .iter()
+            .map(|doc| doc.doc_id.clone())
+            .collect::<Vec<String>>();
+        let indexed = state
+            .index
+            .search_by_query(None, Some(indexed_ids), &[], &[])
+            .await;
+        let mut doc_map = HashMap::new();
+        for (_, result) in indexed {
+            doc_map.insert(result.doc_id.clone(), result);
+        }
+
+        for model in models {
+            // Nothing indexed for this document, nothing to share.
+            let doc = match doc_map.remove(&model.doc_id) {
+                Some(doc) => doc,
+                None => continue,
+            };
+
+            let record = ParseResult {
+                canonical_url: Some(model.url.clone()),
+                content: doc.content,
+                content_hash: model.content_hash.unwrap_or_default(),
+                description: doc.description,
+                title: if doc.title.is_empty() {
+                    None
+                } else {
+                    Some(doc.title)
+                },
+                links: HashSet::new(),
+                // Meta tags aren't kept after indexing, so there's nothing to
+                // carry over into the bundle.
+                meta: HashMap::new(),
+            };
+
+            encoder.write_all(ron::ser::to_string(&record)?.as_bytes())?;
+            encoder.write_all(b"\n")?;
+        }
+    }
+    encoder.finish()?.flush()?;
+
+    // Prefer the author's original file when it's still on disk, falling back
+    // to re-serializing the in-memory config.
+    let lens_ron = match std::fs::read_to_string(&lens.file_path) {
+        Ok(contents) if !contents.is_empty() => contents,
+        _ => ron::ser::to_string_pretty(&lens, ron::ser::PrettyConfig::new())?,
+    };
+
+    let file = File::create(dest)?;
+    let encoder = GzEncoder::new(file, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(lens_ron.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, LENS_FILE, lens_ron.as_bytes())?;
+    archive.append_path_with_name(&parsed_path, PARSED_FILE)?;
+
+    let file = archive.into_inner()?.finish()?;
+    file.sync_all()?;
+    let bytes_written = file.metadata()?.len();
+    let _ = std::fs::remove_file(&parsed_path);
+
+    Ok(LensExportResult {
+        archive_path: dest.to_path_buf(),
+        num_docs,
+        bytes_written,
+    })
+}
+
+/// Reads just the lens config out of an exported archive. Returns `None` when
+/// the file isn't one of our bundles (e.g. a bare `parsed.ron.gz` cache), so
+/// callers can fall back to the legacy format.
+pub fn read_archive_lens(archive: &Path) -> Option<LensConfig> {
+    let file = File::open(archive).ok()?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(file));
+    for entry in tarball.entries().ok()? {
+        let mut entry = entry.ok()?;
+        if entry.path().ok()?.as_ref() == Path::new(LENS_FILE) {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).ok()?;
+            return LensConfig::from_string(&contents).ok();
+        }
+    }
+
+    None
+}
+
+/// Installs an exported lens archive end-to-end: registers the lens in the
+/// database, writes its RON file into the lens directory & feeds the bundled
+/// document cache through the cache pipeline, which also creates the lens
+/// tags.
+pub async fn import_lens_archive(state: &AppState, archive: &Path) -> anyhow::Result<LensConfig> {
+    let config = match read_archive_lens(archive) {
+        Some(config) => config,
+        None => {
+            return Err(anyhow!(
+                "{} is not an exported lens archive",
+                archive.display()
+            ))
+        }
+    };
+
+    let cache_dir = state.config.cache_dir().join(&config.name);
+    std::fs::create_dir_all(&cache_dir)?;
+    let parsed_path = cache_dir.join(PARSED_FILE);
+
+    let mut tarball = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut lens_ron = String::new();
+    let mut have_parsed = false;
+    for entry in tarball.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if path == Path::new(LENS_FILE) {
+            entry.read_to_string(&mut lens_ron)?;
+        } else if path == Path::new(PARSED_FILE) {
+            let mut out = File::create(&parsed_path)?;
+            std::io::copy(&mut entry, &mut out)?;
+            have_parsed = true;
+        }
+    }
+
+    if !have_parsed {
+        return Err(anyhow!(
+            "{} has no document cache ({PARSED_FILE})",
+            archive.display()
+        ));
+    }
+
+    // Register in the db & write the lens file first so the standard lens
+    // loading process picks it up, mirroring a normal install.
+    let _ = lens::install_or_update(&state.db, &config, lens::LensType::Simple, None).await?;
+    std::fs::write(
+        state.config.lenses_dir().join(format!("{}.ron", config.name)),
+        lens_ron,
+    )?;
+
+    process_update(state.clone(), &config, parsed_path, false).await;
+    let _ = state.index.save().await;
+
+    Ok(config)
+}
+
+/// Finds the config for an installed lens, reading straight from disk when
+/// the lens loading task hasn't populated the lens map (e.g. the CLI).
+async fn find_lens_config(state: &AppState, name: &str) -> Option<LensConfig> {
+    if let Some(entry) = state.lenses.get(name) {
+        return Some(entry.value().clone());
+    }
+
+    match read_lenses(&state.config).await {
+        Ok(lenses) => lenses.get(name).map(|entry| entry.value().clone()),
+        Err(_) => None,
+    }
+}
@@ -27,7 +27,7 @@ use std::{
 };
 
 use notify::RecommendedWatcher;
-use shared::config::Config;
+use shared::config::{Config, LensConfig};
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -651,7 +651,12 @@ pub async fn configure_watcher(state: AppState) {
                     let updates = watcher.initialize_path(path.as_path()).await;
                     let rx1 = watcher.watch_path(path.as_path(), None, true).await;
 
-                    tokio::spawn(_process_messages(state.clone(), rx1, updates));
+                    tokio::spawn(_process_messages(
+                        state.clone(),
+                        rx1,
+                        updates,
+                        vec![(TagType::Lens, String::from(FILES_LENS))],
+                    ));
                 }
             }
             watcher.remove_unwatched_paths(&paths).await;
@@ -700,6 +705,89 @@ pub async fn configure_watcher(state: AppState) {
     }
 }
 
+/// Registers watchers for the local folders declared by a lens. Folders are
+/// watched regardless of the global filesystem scanning setting & documents
+/// found under them are tagged w/ the lens tags instead of the standard file
+/// lens, so uninstalling the lens removes them.
+pub async fn watch_lens_folders(state: &AppState, lens: &LensConfig) {
+    if lens.folders.is_empty() {
+        return;
+    }
+
+    let tags = lens_folder_tags(lens);
+    let mut watcher = state.file_watcher.lock().await;
+    if let Some(watcher) = watcher.as_mut() {
+        for folder in &lens.folders {
+            // Folders are validated at lens load, but may disappear between
+            // restarts (e.g. an unmounted drive).
+            if !folder.is_dir() {
+                log::warn!(
+                    "lens {} folder {:?} is not a directory, skipping",
+                    lens.name,
+                    folder
+                );
+                continue;
+            }
+
+            if !watcher.is_path_initialized(folder.as_path()) {
+                log::debug!("Adding lens folder {:?} to watch list", folder);
+                let updates = watcher.initialize_path(folder.as_path()).await;
+                let rx = watcher.watch_path(folder.as_path(), None, true).await;
+
+                tokio::spawn(_process_messages(state.clone(), rx, updates, tags.clone()));
+            }
+        }
+    } else {
+        log::error!("Watcher is missing");
+    }
+}
+
+/// Stops watching a lens' folders & removes their file records from the
+/// database. Called on lens uninstall; the indexed documents themselves are
+/// removed via the lens tag.
+pub async fn unwatch_lens_folders(state: &AppState, lens: &LensConfig) {
+    if lens.folders.is_empty() {
+        return;
+    }
+
+    {
+        let mut watcher = state.file_watcher.lock().await;
+        if let Some(watcher) = watcher.as_mut() {
+            for folder in &lens.folders {
+                watcher.remove_path(folder.as_path()).await;
+            }
+        }
+    }
+
+    // Clear the processed file records so a reinstall re-enqueues everything
+    // under the folder instead of seeing it as already handled.
+    for folder in &lens.folders {
+        let prefix = utils::path_to_uri(folder);
+        match processed_files::remove_with_prefix(&state.db, &prefix).await {
+            Ok(removed) => {
+                let uri_list = removed
+                    .iter()
+                    .map(|model| model.file_path.clone())
+                    .collect::<Vec<String>>();
+                documents::delete_documents_by_uri(state, uri_list).await;
+            }
+            Err(error) => log::warn!(
+                "Error removing files for lens folder {:?}: {:?}",
+                folder,
+                error
+            ),
+        }
+    }
+}
+
+/// Tags applied to documents found under a lens' folders.
+fn lens_folder_tags(lens: &LensConfig) -> Vec<TagPair> {
+    lens.all_tags()
+        .iter()
+        .map(|(label, value)| (TagType::string_to_tag_type(label), value.clone()))
+        .collect::<Vec<TagPair>>()
+}
+
 /// Helper method used to process any updates required for changes in the configured
 /// extensions
 async fn _handle_extension_reprocessing(state: &AppState) {
@@ -720,7 +808,8 @@ async fn _handle_extension_reprocessing(state: &AppState) {
     }
 
     if !updates.is_empty() {
-        if let Err(err) = _process_file_and_dir(state, updates).await {
+        let tags = vec![(TagType::Lens, String::from(FILES_LENS))];
+        if let Err(err) = _process_file_and_dir(state, updates, &tags).await {
             log::warn!("Error processing updates for newly added extensions {err}");
         }
     }
@@ -738,14 +827,17 @@ pub fn is_watcher_enabled() -> bool {
     config.filesystem_settings.enable_filesystem_scanning
 }
 
-/// Helper method use to process updates from a watched path
+/// Helper method use to process updates from a watched path. Documents are
+/// tagged w/ `tags`, either the standard file lens or the tags of the lens
+/// that declared the folder.
 async fn _process_messages(
     state: AppState,
     mut rx: Receiver<Vec<DebouncedEvent>>,
     initial: Vec<DebouncedEvent>,
+    tags: Vec<TagPair>,
 ) {
     log::info!("Processing {:?} initial updates.", initial.len());
-    if let Err(error) = _process_file_and_dir(&state, initial).await {
+    if let Err(error) = _process_file_and_dir(&state, initial, &tags).await {
         log::error!("Error processing initial files {:?}", error);
     }
 
@@ -753,7 +845,7 @@ async fn _process_messages(
         let msg = rx.recv().await;
         match msg {
             Some(event) => {
-                if let Err(error) = _process_file_and_dir(&state, event).await {
+                if let Err(error) = _process_file_and_dir(&state, event, &tags).await {
                     log::error!("Error processing updates {:?}", error);
                 }
             }
@@ -769,6 +861,7 @@ async fn _process_messages(
 async fn _process_file_and_dir(
     state: &AppState,
     events: Vec<DebouncedEvent>,
+    tags: &[TagPair],
 ) -> anyhow::Result<()> {
     log::info!("Processing received updates");
     let mut enqueue_list = Vec::new();
@@ -807,12 +900,12 @@ async fn _process_file_and_dir(
     }
 
     if !enqueue_list.is_empty() {
-        let tags = vec![(TagType::Lens, String::from(FILES_LENS))];
         let enqueue_settings = EnqueueSettings {
             crawl_type: CrawlType::Normal,
             is_recrawl: true,
-            tags,
+            tags: tags.to_vec(),
             force_allow: true,
+            ..Default::default()
         };
         if let Err(error) =
             crawl_queue::enqueue_local_files(&state.db, &enqueue_list, &enqueue_settings, None)
@@ -825,7 +918,7 @@ async fn _process_file_and_dir(
     if !general_processing.is_empty() {
         log::info!("Adding {} general documents", general_processing.len());
         for general_chunk in general_processing.chunks(500) {
-            _process_general_file(state, general_chunk).await;
+            _process_general_file(state, general_chunk, tags).await;
         }
     }
 
@@ -867,7 +960,7 @@ pub fn build_file_tags(path: &Path) -> Vec<TagPair> {
 }
 
 // Helper method used process files
-async fn _process_general_file(state: &AppState, file_uri: &[String]) {
+async fn _process_general_file(state: &AppState, file_uri: &[String], tags: &[TagPair]) {
     let crawl_results = file_uri
         .iter()
         .filter_map(|uri| match Url::parse(uri) {
@@ -879,13 +972,7 @@ async fn _process_general_file(state: &AppState, file_uri: &[String]) {
         })
         .collect::<Vec<CrawlResult>>();
 
-    if let Err(err) = documents::process_crawl_results(
-        state,
-        &crawl_results,
-        &[(TagType::Lens, FILES_LENS.to_string())],
-    )
-    .await
-    {
+    if let Err(err) = documents::process_crawl_results(state, &crawl_results, tags).await {
         log::error!("Unable to add crawl results: {:?}", err);
     }
 }
@@ -918,6 +1005,7 @@ fn _path_to_result(url: &Url, path: &Path) -> Option<CrawlResult> {
             open_url: Some(url.to_string()),
             links: Default::default(),
             tags,
+            published_at: None,
         })
     } else {
         None
@@ -37,6 +37,18 @@ pub fn path_string_to_uri(path_str: String) -> String {
     new_url.to_string()
 }
 
+/// Expands a leading `~` in a path to the user's home directory. Paths w/o a
+/// tilde (or when no home directory can be found) are returned unchanged.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    if let Ok(stripped) = path.strip_prefix("~") {
+        if let Some(base_dirs) = directories::BaseDirs::new() {
+            return base_dirs.home_dir().join(stripped);
+        }
+    }
+
+    path.to_path_buf()
+}
+
 /// Converts a uri to a valid path buf
 pub fn uri_to_path(uri: &str) -> anyhow::Result<PathBuf> {
     match Url::parse(uri) {
@@ -194,9 +206,21 @@ pub fn is_hidden(path: &Path) -> bool {
 mod test {
     use std::path::Path;
 
-    use super::path_to_uri;
+    use super::{expand_tilde, path_to_uri};
     use url::Url;
 
+    #[test]
+    fn test_expand_tilde() {
+        if let Some(base_dirs) = directories::BaseDirs::new() {
+            let expanded = expand_tilde(Path::new("~/Documents/notes"));
+            assert_eq!(expanded, base_dirs.home_dir().join("Documents/notes"));
+        }
+
+        // Paths w/o a tilde are untouched.
+        let plain = Path::new("/tmp/path_to_uri");
+        assert_eq!(expand_tilde(plain), plain.to_path_buf());
+    }
+
     #[test]
     fn test_path_to_uri() {
         #[cfg(target_os = "windows")]
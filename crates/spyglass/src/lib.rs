@@ -1,8 +1,15 @@
+pub mod backup;
 pub mod connection;
 pub mod crawler;
 pub mod documents;
+pub mod export;
 pub mod filesystem;
+pub mod llm;
+pub mod model_files;
 pub mod pipeline;
 pub mod platform;
+pub mod plugin;
+pub mod reindex;
 pub mod state;
+pub mod sync;
 pub mod task;
@@ -41,6 +41,10 @@ struct CliArgs {
     /// Only enable readonly functionality
     #[arg(long)]
     read_only: bool,
+    /// Restore a backup archive into the data dir before starting up. Only
+    /// runs against an empty install; use the debug CLI to force overwrite.
+    #[arg(long, value_name = "ARCHIVE")]
+    restore: Option<std::path::PathBuf>,
 }
 
 #[cfg(feature = "tokio-console")]
@@ -134,6 +138,17 @@ async fn main() -> Result<(), ()> {
         }
     }
 
+    // Restore before anything opens the index or database.
+    if let Some(archive) = &args.restore {
+        match libspyglass::backup::restore_backup(&config, archive, false).await {
+            Ok(num_docs) => log::info!("Restored {} docs from {}", num_docs, archive.display()),
+            Err(err) => {
+                log::error!("Unable to restore backup: {}", err);
+                return Err(());
+            }
+        }
+    }
+
     // Initialize/Load user preferences
     let state = AppState::new(&config, args.read_only).await;
     // Only startup API server if we're in readonly mode.
@@ -185,6 +200,9 @@ async fn start_backend(state: AppState, config: Config) {
     // Channel for pipeline commands
     let (pipeline_cmd_tx, pipeline_cmd_rx) = mpsc::channel(16);
 
+    // Channel for plugin commands
+    let (plugin_cmd_tx, plugin_cmd_rx) = mpsc::channel(16);
+
     {
         state
             .manager_cmd_tx
@@ -205,6 +223,14 @@ async fn start_backend(state: AppState, config: Config) {
             .replace(pipeline_cmd_tx.clone());
     }
 
+    {
+        state
+            .plugin_cmd_tx
+            .lock()
+            .await
+            .replace(plugin_cmd_tx.clone());
+    }
+
     // Work scheduler
     let manager_handle = tokio::spawn(task::manager_task(
         state.clone(),
@@ -218,6 +244,10 @@ async fn start_backend(state: AppState, config: Config) {
 
     let embedding_handler = tokio::spawn(task::embedding_task(state.clone(), worker_cmd_tx));
 
+    // Sync user data (favorites, settings) through the user's sync folder,
+    // if configured.
+    let sync_handle = tokio::spawn(libspyglass::sync::sync_task(state.clone()));
+
     // Crawlers
     let worker_handle = tokio::spawn(task::worker_task(
         state.clone(),
@@ -240,6 +270,15 @@ async fn start_backend(state: AppState, config: Config) {
         pipeline_cmd_rx,
     ));
 
+    // Loads plugins & processes plugin commands, shuts itself down on the
+    // shutdown broadcast.
+    let _plugin_handle = tokio::spawn(libspyglass::plugin::plugin_event_loop(
+        state.clone(),
+        config.clone(),
+        plugin_cmd_tx.clone(),
+        plugin_cmd_rx,
+    ));
+
     let watcher = libspyglass::filesystem::SpyglassFileWatcher::new(&state);
     {
         state.file_watcher.lock().await.replace(watcher);
@@ -282,5 +321,6 @@ async fn start_backend(state: AppState, config: Config) {
         lens_watcher_handle,
         config_handle,
         embedding_handler,
+        sync_handle,
     );
 }
@@ -0,0 +1,154 @@
+//! Helpers for managing downloaded model files. Each verified download gets
+//! a sidecar file recording its sha256, which lets us detect corrupt or
+//! truncated files later instead of crashing the backend when it tries to
+//! load them.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use shared::config::Config;
+
+/// Path of the sidecar file recording a model file's sha256, written after
+/// a verified download.
+pub fn checksum_path(model_path: &Path) -> PathBuf {
+    let mut path = model_path.as_os_str().to_owned();
+    path.push(".sha256");
+    PathBuf::from(path)
+}
+
+/// Path a model file is downloaded to before it's verified & renamed into
+/// place.
+pub fn partial_path(model_path: &Path) -> PathBuf {
+    let mut path = model_path.as_os_str().to_owned();
+    path.push(".part");
+    PathBuf::from(path)
+}
+
+pub fn sha256_of_file(path: &Path) -> anyhow::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Whether `model_path` matches its recorded checksum. Files without a
+/// sidecar (downloaded before checksums were recorded) pass verification.
+pub fn verify_model_file(model_path: &Path) -> bool {
+    let sidecar = checksum_path(model_path);
+    if !model_path.is_file() || !sidecar.is_file() {
+        return true;
+    }
+
+    match (std::fs::read_to_string(&sidecar), sha256_of_file(model_path)) {
+        (Ok(expected), Ok(actual)) => expected.trim() == actual,
+        // If either side can't be read, leave the file alone.
+        _ => true,
+    }
+}
+
+/// Cheap version of [`verify_model_file`] that avoids re-reading multi-GB
+/// model files on every check: the sidecar is written right after a verified
+/// download, so a model file that's older than its sidecar hasn't changed
+/// since it was last hashed. Files modified after the sidecar get a full
+/// re-hash, w/ the sidecar refreshed on success so the next check is cheap
+/// again.
+pub fn verify_model_file_quick(model_path: &Path) -> bool {
+    let sidecar = checksum_path(model_path);
+    if !model_path.is_file() || !sidecar.is_file() {
+        return true;
+    }
+
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    if let (Some(model_time), Some(sidecar_time)) = (modified(model_path), modified(&sidecar)) {
+        if model_time <= sidecar_time {
+            return true;
+        }
+    }
+
+    let verified = verify_model_file(model_path);
+    if verified {
+        if let Ok(expected) = std::fs::read_to_string(&sidecar) {
+            let _ = std::fs::write(&sidecar, expected);
+        }
+    }
+
+    verified
+}
+
+/// Removes model files in `dir` that fail checksum verification so the next
+/// download pass fetches a fresh copy. Partial downloads are left in place,
+/// they're resumed instead. Returns the paths removed.
+pub fn remove_corrupt_model_files(dir: &Path) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    for path in model_files_in(dir) {
+        if !verify_model_file(&path) {
+            log::warn!(
+                "Model file {} failed checksum verification, removing for re-download",
+                path.display()
+            );
+            let _ = std::fs::remove_file(&path);
+            let _ = std::fs::remove_file(checksum_path(&path));
+            removed.push(path);
+        }
+    }
+
+    removed
+}
+
+/// Verifies every downloaded model file against its recorded checksum,
+/// returning the paths that fail. Surfaced via the `system_health` RPC, so
+/// this uses the quick mtime-based check; full hashing only happens for
+/// files changed since their last verification.
+pub fn verify_models(config: &Config) -> Vec<PathBuf> {
+    let mut corrupt = Vec::new();
+    for dir in [
+        config.embedding_model_dir(),
+        config.llm_model_dir(),
+        config.rerank_model_dir(),
+        config.model_dir().join("whisper"),
+    ] {
+        for path in model_files_in(&dir) {
+            if !verify_model_file_quick(&path) {
+                corrupt.push(path);
+            }
+        }
+    }
+
+    corrupt
+}
+
+/// Model files directly in `dir` or one level down (HuggingFace models get
+/// their own subdirectory), skipping checksum sidecars & partial downloads.
+pub fn model_files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return files,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Ok(subentries) = std::fs::read_dir(&path) {
+                for subentry in subentries.flatten() {
+                    let subpath = subentry.path();
+                    if subpath.is_file() && !is_sidecar(&subpath) {
+                        files.push(subpath);
+                    }
+                }
+            }
+        } else if path.is_file() && !is_sidecar(&path) {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+fn is_sidecar(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("sha256") | Some("part")
+    )
+}
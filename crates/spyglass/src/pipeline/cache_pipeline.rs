@@ -9,6 +9,7 @@ use crate::state::AppState;
 
 use libnetrunner::parser::ParseResult;
 use shared::config::LensConfig;
+use spyglass_searcher::client::WriteBufferSettings;
 use tokio::task::JoinHandle;
 
 use super::parser::DefaultParser;
@@ -88,12 +89,30 @@ pub async fn process_update(
     let now = Instant::now();
     let mut total_processed = 0;
 
+    // Buffer index commits for the duration of the import. Committing every
+    // batch writes a pile of tiny segments that all get merged again later.
+    state
+        .index
+        .enable_write_buffering(WriteBufferSettings::default());
+
+    // Content post-processing configured for the lens' pipeline (boilerplate
+    // stripping etc.) is applied before records hit the index.
+    let post_process = lens
+        .pipeline
+        .as_ref()
+        .and_then(|name| state.pipelines.get(name).map(|entry| entry.value().clone()))
+        .and_then(|config| config.post_process);
+
     let records = archive::read_parsed(&cache_path);
     if let Ok(mut record_iter) = records {
         let mut record_list: Vec<ParseResult> = Vec::new();
-        for record in record_iter.by_ref() {
+        for mut record in record_iter.by_ref() {
             total_processed += 1;
 
+            if let Some(post_process) = &post_process {
+                record.content = post_process.process(&record.content);
+            }
+
             record_list.push(record);
             if record_list.len() >= 5000 {
                 if let Err(err) = documents::process_records(&state, lens, &mut record_list).await {
@@ -110,6 +129,12 @@ pub async fn process_update(
         }
     }
 
+    // Commit anything still buffered before the archive is considered loaded.
+    if let Err(err) = state.index.flush().await {
+        log::warn!("Unable to flush index writes: {err}");
+    }
+    state.index.disable_write_buffering();
+
     // attempt to remove processed cache file
     if !keep_archive {
         let _ = cache::delete_cache(&cache_path);
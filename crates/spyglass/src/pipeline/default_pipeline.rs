@@ -22,7 +22,7 @@ pub async fn pipeline_loop(
     state: AppState,
     _config: Config,
     pipeline: String,
-    _pipeline_cfg: PipelineConfiguration,
+    pipeline_cfg: PipelineConfiguration,
     mut pipeline_queue: mpsc::Receiver<PipelineCommand>,
 ) {
     let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
@@ -51,7 +51,15 @@ pub async fn pipeline_loop(
                         "Processing pipeline crawl command for pipeline {}",
                         pipeline
                     );
-                    start_crawl(state.clone(), &pipeline, &collector, &parser, crawl_task).await;
+                    start_crawl(
+                        state.clone(),
+                        &pipeline,
+                        &pipeline_cfg,
+                        &collector,
+                        &parser,
+                        crawl_task,
+                    )
+                    .await;
                 }
                 PipelineCommand::ProcessCache(_, _) => {
                     // noop
@@ -68,6 +76,7 @@ pub async fn pipeline_loop(
 async fn start_crawl(
     state: AppState,
     pipeline_name: &str,
+    pipeline_cfg: &PipelineConfiguration,
     collector: &DefaultCollector,
     parser: &DefaultParser,
     task: CrawlTask,
@@ -115,6 +124,12 @@ async fn start_crawl(
                     // Add / update search index w/ crawl result.
                     if let Some(content) = crawl_result.content {
                         log::debug!("Pipeline got content");
+                        // Run the configured post-processing stage before
+                        // indexing, e.g. stripping boilerplate.
+                        let content = match &pipeline_cfg.post_process {
+                            Some(post_process) => post_process.process(&content),
+                            None => content,
+                        };
                         let url = Url::parse(&crawl_result.url).expect("Invalid crawl URL");
                         let url_host = url.host_str().expect("Invalid URL host");
 
@@ -1,28 +1,52 @@
 use crate::crawler::CrawlResult;
 use crate::documents;
+use crate::filesystem;
+use chrono::{DateTime, Utc};
 use entities::models::indexed_document;
+use entities::models::lens;
+use entities::models::processed_files;
 use entities::models::tag;
 use entities::models::tag::TagPair;
 use entities::models::tag::TagType;
 use entities::sea_orm::ColumnTrait;
+use entities::sea_orm::DatabaseConnection;
 use entities::sea_orm::EntityTrait;
 use entities::sea_orm::ModelTrait;
 use entities::sea_orm::QueryFilter;
+use base64::prelude::*;
+use notify_debouncer_mini::DebouncedEvent;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
-use spyglass_searcher::{RetrievedDocument, WriteTrait};
-use std::path::Path;
+use spyglass_rpc::{PluginStatusPayload, RpcEvent, RpcEventType};
+use spyglass_searcher::{RetrievedDocument, SearchTrait, WriteTrait};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use tokio::sync::mpsc::Sender;
 use url::Url;
+use uuid::Uuid;
 use wasmer::{Exports, Function, Store};
 use wasmer_wasi::WasiEnv;
 
-use super::{wasi_read, wasi_read_string, PluginCommand, PluginConfig, PluginEnv, PluginId};
+use super::{
+    wasi_read, wasi_read_string, PluginCommand, PluginConfig, PluginEnv, PluginId,
+    PluginPermissions,
+};
 use crate::state::AppState;
 
-use entities::models::crawl_queue::{enqueue_all, EnqueueSettings};
-use spyglass_plugin::{DocumentQuery, PluginCommandRequest};
+use entities::models::crawl_queue::{self, enqueue_all, EnqueueSettings};
+use shared::plugin::PLUGIN_LOG_LEVEL_SETTING;
+use spyglass_plugin::{
+    Authentication, DocumentContent, DocumentQuery, DocumentResult, DocumentUpdate, HttpMethod,
+    LogLevel, PluginCommandRequest, PluginEvent,
+};
+
+/// Per-document cap on the content returned for a `GetDocumentContent`
+/// request.
+const MAX_CONTENT_BYTES: usize = 64 * 1024;
+/// Total content budget for one `DocumentContentResponse`, so a plugin
+/// requesting a huge batch of ids can't balloon the server's memory.
+const MAX_RESPONSE_BYTES: usize = 512 * 1024;
 
 pub fn register_exports(
     plugin_id: PluginId,
@@ -38,6 +62,13 @@ pub fn register_exports(
         name: plugin.name.clone(),
         app_state: state.clone(),
         _data_dir: plugin.data_folder(),
+        permissions: plugin.permissions.clone(),
+        log_level: plugin
+            .user_settings
+            .get(PLUGIN_LOG_LEVEL_SETTING)
+            .and_then(|opts| LogLevel::parse(&opts.value))
+            .unwrap_or(LogLevel::Info),
+        log_file: state.config.plugin_log_file(&plugin.name),
         wasi_env: env.clone(),
         cmd_writer: cmd_writer.clone(),
     };
@@ -57,6 +88,20 @@ async fn handle_plugin_cmd_request(
     cmd: &PluginCommandRequest,
     env: &PluginEnv,
 ) -> anyhow::Result<()> {
+    if let Err((command, reason)) = check_permissions(cmd, &env.permissions) {
+        log::warn!("<{}> denied {} request: {}", env.name, command, reason);
+        env.cmd_writer
+            .send(PluginCommand::HandleUpdate {
+                plugin_id: env.id,
+                event: PluginEvent::PermissionDenied {
+                    command: command.to_string(),
+                    reason,
+                },
+            })
+            .await?;
+        return Ok(());
+    }
+
     match cmd {
         // Delete document from index
         PluginCommandRequest::DeleteDoc { url } => {
@@ -69,13 +114,106 @@ async fn handle_plugin_cmd_request(
             let doc_ids = docs.iter().map(|x| x.doc_id.to_owned()).collect::<Vec<_>>();
             env.app_state.index.delete_many_by_id(&doc_ids).await?;
         }
+        // Bulk delete, resolved w/ the same query machinery as `ModifyTags`.
+        PluginCommandRequest::DeleteDocsByQuery { query } => {
+            if query.is_empty() {
+                // An unconstrained query matches everything; refuse to wipe
+                // the library.
+                log::warn!("<{}> rejected DeleteDocsByQuery w/ empty query", env.name);
+                env.cmd_writer
+                    .send(PluginCommand::HandleUpdate {
+                        plugin_id: env.id,
+                        event: PluginEvent::PermissionDenied {
+                            command: "DeleteDocsByQuery".to_string(),
+                            reason: "an empty query would delete the entire library".to_string(),
+                        },
+                    })
+                    .await?;
+                return Ok(());
+            }
+
+            let tag_ids = query.has_tags.clone().unwrap_or_default();
+            let tag_ids = tag::get_tags_by_value(&env.app_state.db, &tag_ids)
+                .await
+                .unwrap_or_default()
+                .iter()
+                .map(|model| model.id as u64)
+                .collect::<Vec<u64>>();
+
+            let exclude_tags = query.exclude_tags.clone().unwrap_or_default();
+            let exclude_tags = tag::get_tags_by_value(&env.app_state.db, &exclude_tags)
+                .await
+                .unwrap_or_default()
+                .iter()
+                .map(|model| model.id as u64)
+                .collect::<Vec<u64>>();
+
+            let docs = env
+                .app_state
+                .index
+                .search_by_query(
+                    query.urls.clone(),
+                    query.ids.clone(),
+                    &tag_ids,
+                    &exclude_tags,
+                )
+                .await;
+
+            let urls = docs
+                .iter()
+                .map(|(_, doc)| doc.url.clone())
+                .collect::<Vec<String>>();
+            let count = urls.len();
+            log::info!("<{}> deleting {} documents by query", env.name, count);
+            if count > 0 {
+                documents::delete_documents_by_uri(&env.app_state, urls).await;
+            }
+
+            env.cmd_writer
+                .send(PluginCommand::HandleUpdate {
+                    plugin_id: env.id,
+                    event: PluginEvent::DeleteResponse { count },
+                })
+                .await?;
+        }
         // Enqueue a list of URLs to be crawled
         PluginCommandRequest::Enqueue { urls } => handle_plugin_enqueue(env, urls),
         PluginCommandRequest::QueryDocuments { query, subscribe } => {
+            // Remember the query so the plugin can ask for later pages.
+            let request_id = Uuid::new_v4().as_hyphenated().to_string();
+            {
+                let manager = env.app_state.plugin_manager.lock().await;
+                manager
+                    .doc_queries
+                    .insert((env.id, request_id.clone()), query.clone());
+            }
+
             if *subscribe {
-                tokio::spawn(query_document_and_send_loop(env.clone(), query.clone()));
+                tokio::spawn(query_document_and_send_loop(
+                    env.clone(),
+                    request_id,
+                    query.clone(),
+                ));
             } else {
-                query_documents_and_send(env, query, true).await;
+                query_documents_and_send(env, &request_id, query, 0, true).await;
+            }
+        }
+        PluginCommandRequest::RequestDocumentPage { request_id, page } => {
+            let query = {
+                let manager = env.app_state.plugin_manager.lock().await;
+                manager
+                    .doc_queries
+                    .get(&(env.id, request_id.clone()))
+                    .map(|query| query.clone())
+            };
+
+            match query {
+                Some(query) => query_documents_and_send(env, request_id, &query, *page, true).await,
+                None => log::warn!(
+                    "<{}> unknown document query request id: {}",
+                    env.name,
+                    request_id
+                ),
             }
         }
         PluginCommandRequest::HttpRequest {
@@ -108,13 +246,35 @@ async fn handle_plugin_cmd_request(
             let result = request.send().await;
 
             match result {
-                Ok(response) => {
+                Ok(mut response) => {
                     let headers = convert_headers(response.headers());
-                    let txt = response.text().await.ok();
+                    let content_type = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|val| val.to_str().ok())
+                        .map(|val| val.to_string());
+
+                    let max_bytes =
+                        env.app_state.user_settings.load().plugin_http_max_bytes as usize;
+                    let result = match read_body_capped(&mut response, max_bytes).await {
+                        Ok((body, truncated)) => {
+                            // Valid UTF-8 bodies are delivered as text; anything
+                            // else is base64-encoded so plugins can fetch binary
+                            // payloads.
+                            let (response, body_base64) = match String::from_utf8(body) {
+                                Ok(text) => (Some(text), None),
+                                Err(err) => (None, Some(BASE64_STANDARD.encode(err.as_bytes()))),
+                            };
 
-                    let http_response = spyglass_plugin::HttpResponse {
-                        response: txt,
-                        headers,
+                            Ok(spyglass_plugin::HttpResponse {
+                                headers,
+                                response,
+                                body_base64,
+                                content_type,
+                                truncated,
+                            })
+                        }
+                        Err(error) => Err(format!("{}", error)),
                     };
 
                     env.cmd_writer
@@ -122,7 +282,7 @@ async fn handle_plugin_cmd_request(
                             plugin_id: env.id,
                             event: PluginEvent::HttpResponse {
                                 url: url.clone(),
-                                result: Ok(http_response),
+                                result,
                             },
                         })
                         .await?;
@@ -179,8 +339,13 @@ async fn handle_plugin_cmd_request(
                     .cloned()
                     .collect::<Vec<RetrievedDocument>>();
 
+                let modification = documents::TagModification {
+                    add: tag_modifications.add.clone(),
+                    remove: tag_modifications.remove.clone(),
+                };
+
                 if let Err(error) =
-                    documents::update_tags(&env.app_state, &docs, tag_modifications).await
+                    documents::update_tags(&env.app_state, &docs, &modification).await
                 {
                     log::error!("Error updating document tags {:?}", error);
                 }
@@ -197,11 +362,107 @@ async fn handle_plugin_cmd_request(
                 }
             }
         }
-        PluginCommandRequest::SubscribeForUpdates => {
+        PluginCommandRequest::ReportStatus {
+            state,
+            message,
+            progress,
+        } => {
+            log::info!("<{}> status: {} - {}", env.name, state.as_str(), message);
+            if let Err(error) =
+                lens::update_status(&env.name, state.as_str(), message, &env.app_state.db).await
+            {
+                log::error!("Unable to store status for <{}>: {}", env.name, error);
+            }
+
+            env.app_state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::PluginStatus,
+                    payload: serde_json::to_value(PluginStatusPayload {
+                        plugin_name: env.name.clone(),
+                        state: state.as_str().to_owned(),
+                        message: message.clone(),
+                        progress: *progress,
+                    })
+                    .ok(),
+                })
+                .await;
+        }
+        PluginCommandRequest::SyncFile { dst, src } => handle_sync_file(env, dst, src),
+        PluginCommandRequest::SqliteQuery {
+            request_id,
+            db,
+            query,
+            params,
+        } => {
+            let (columns, rows) = match resolve_data_dir_path(&env._data_dir, db)
+                .and_then(|db_path| run_sqlite_query(&db_path, query, params))
+            {
+                Ok(results) => results,
+                Err(error) => {
+                    // Deliver an empty result set so the plugin isn't left
+                    // waiting on a response that will never come.
+                    log::error!("<{}> sqlite query failed: {}", env.name, error);
+                    (Vec::new(), Vec::new())
+                }
+            };
+
             env.cmd_writer
-                .send(PluginCommand::SubscribeForUpdates(env.id))
+                .send(PluginCommand::HandleUpdate {
+                    plugin_id: env.id,
+                    event: PluginEvent::SqlResponse {
+                        request_id: request_id.clone(),
+                        columns,
+                        rows,
+                    },
+                })
                 .await?;
         }
+        PluginCommandRequest::SubscribeForUpdates { interval_seconds } => {
+            env.cmd_writer
+                .send(PluginCommand::SubscribeForUpdates {
+                    plugin_id: env.id,
+                    interval_seconds: *interval_seconds,
+                })
+                .await?;
+        }
+        PluginCommandRequest::GetDocumentContent { ids } => {
+            let mut remaining = MAX_RESPONSE_BYTES;
+            let mut results = Vec::new();
+            for doc_id in ids {
+                if let Some(doc) = env.app_state.index.get(doc_id).await {
+                    let (content, truncated) =
+                        cap_content(&doc.content, MAX_CONTENT_BYTES.min(remaining));
+                    remaining = remaining.saturating_sub(content.len());
+                    results.push(DocumentContent {
+                        doc_id: doc.doc_id,
+                        url: doc.url,
+                        title: doc.title,
+                        description: doc.description,
+                        content,
+                        truncated,
+                    });
+                }
+            }
+
+            env.cmd_writer
+                .send(PluginCommand::HandleUpdate {
+                    plugin_id: env.id,
+                    event: PluginEvent::DocumentContentResponse { documents: results },
+                })
+                .await?;
+        }
+        PluginCommandRequest::WatchPath {
+            path,
+            extensions,
+            recursive,
+        } => {
+            tokio::spawn(watch_path_and_send(
+                env.clone(),
+                PathBuf::from(path),
+                extensions.clone(),
+                *recursive,
+            ));
+        }
     }
 
     Ok(())
@@ -238,6 +499,157 @@ fn build_headermap(headers: &Vec<(String, String)>, plugin: &str) -> HeaderMap {
     header_map
 }
 
+/// Checks a request against the permissions declared in the plugin
+/// manifest, returning the denied command name & a reason on failure.
+/// Requests that only touch the index (queries, tagging, etc.) are always
+/// allowed.
+fn check_permissions(
+    cmd: &PluginCommandRequest,
+    permissions: &PluginPermissions,
+) -> Result<(), (&'static str, String)> {
+    match cmd {
+        PluginCommandRequest::HttpRequest { url, .. } => {
+            let host = Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_string()))
+                .unwrap_or_default();
+            if !host_allowed(&host, &permissions.http_hosts) {
+                return Err((
+                    "HttpRequest",
+                    format!("\"{host}\" is not in the allowed host list"),
+                ));
+            }
+        }
+        PluginCommandRequest::Enqueue { .. } if !permissions.enqueue => {
+            return Err(("Enqueue", "enqueue permission not requested".into()));
+        }
+        PluginCommandRequest::SyncFile { src, .. } => {
+            if !permissions.sync_files {
+                return Err(("SyncFile", "sync_files permission not requested".into()));
+            } else if src.ends_with(".sqlite") && !permissions.sqlite {
+                return Err(("SyncFile", "sqlite permission not requested".into()));
+            }
+        }
+        PluginCommandRequest::SqliteQuery { .. } if !permissions.sqlite => {
+            return Err(("SqliteQuery", "sqlite permission not requested".into()));
+        }
+        PluginCommandRequest::WatchPath { .. } if !permissions.sync_files => {
+            return Err(("WatchPath", "sync_files permission not requested".into()));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Resolves a plugin-supplied path against the plugin's data dir, rejecting
+/// anything (`..` components, symlinks) that resolves outside of it.
+fn resolve_data_dir_path(data_dir: &Path, path: &str) -> anyhow::Result<PathBuf> {
+    let resolved = data_dir.join(path.trim_start_matches('/')).canonicalize()?;
+    if !resolved.starts_with(data_dir.canonicalize()?) {
+        return Err(anyhow::anyhow!(
+            "{path} resolves outside the plugin data dir"
+        ));
+    }
+
+    Ok(resolved)
+}
+
+/// Runs a read-only query against a sqlite file in the plugin data dir,
+/// returning the column names & rows as JSON values.
+fn run_sqlite_query(
+    db_path: &Path,
+    query: &str,
+    params: &[serde_json::Value],
+) -> anyhow::Result<(Vec<String>, Vec<Vec<serde_json::Value>>)> {
+    use rusqlite::types::ValueRef;
+
+    let conn =
+        rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(query)?;
+
+    let columns = stmt
+        .column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+
+    for (idx, param) in params.iter().enumerate() {
+        // Placeholders are 1-indexed.
+        let idx = idx + 1;
+        match param {
+            serde_json::Value::Null => stmt.raw_bind_parameter(idx, rusqlite::types::Null)?,
+            serde_json::Value::Bool(value) => stmt.raw_bind_parameter(idx, value)?,
+            serde_json::Value::Number(value) => {
+                if let Some(value) = value.as_i64() {
+                    stmt.raw_bind_parameter(idx, value)?
+                } else {
+                    stmt.raw_bind_parameter(idx, value.as_f64())?
+                }
+            }
+            serde_json::Value::String(value) => stmt.raw_bind_parameter(idx, value)?,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "unsupported bind parameter type: {}",
+                    param
+                ))
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut raw_rows = stmt.raw_query();
+    while let Some(row) = raw_rows.next()? {
+        let mut values = Vec::new();
+        for idx in 0..columns.len() {
+            values.push(match row.get_ref(idx)? {
+                ValueRef::Null => serde_json::Value::Null,
+                ValueRef::Integer(value) => serde_json::Value::from(value),
+                ValueRef::Real(value) => serde_json::Value::from(value),
+                ValueRef::Text(value) => {
+                    serde_json::Value::from(String::from_utf8_lossy(value).to_string())
+                }
+                ValueRef::Blob(value) => serde_json::Value::from(hex::encode(value)),
+            });
+        }
+        rows.push(values);
+    }
+
+    Ok((columns, rows))
+}
+
+/// Whether `host` matches an entry in the allowed host list. Entries match
+/// exactly, `*.example.com` also matches subdomains & `*` matches
+/// everything.
+fn host_allowed(host: &str, allowed: &[String]) -> bool {
+    allowed.iter().any(|entry| {
+        entry == "*"
+            || entry == host
+            || entry
+                .strip_prefix("*.")
+                .map(|suffix| host == suffix || host.ends_with(&format!(".{suffix}")))
+                .unwrap_or(false)
+    })
+}
+
+/// Reads a response body up to `max_bytes`; returns the bytes & whether the
+/// body was cut off.
+async fn read_body_capped(
+    response: &mut reqwest::Response,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, bool), reqwest::Error> {
+    let mut body: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            body.extend_from_slice(&chunk[..max_bytes - body.len()]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok((body, false))
+}
+
 // Converts header map to header list
 fn convert_headers(headers: &HeaderMap) -> Vec<(String, String)> {
     headers
@@ -264,6 +676,11 @@ fn convert_docs_to_crawl(
                         doc.description.clone(),
                     );
                     crawl.tags = tags;
+                    crawl.published_at = doc
+                        .published_at
+                        .as_deref()
+                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                        .map(|ts| ts.with_timezone(&Utc));
                     Some(crawl)
                 }
                 Err(error) => {
@@ -287,7 +704,7 @@ fn convert_tags(tags: &[(String, String)]) -> Vec<TagPair> {
         .collect::<Vec<TagPair>>()
 }
 
-async fn query_document_and_send_loop(env: PluginEnv, query: DocumentQuery) {
+async fn query_document_and_send_loop(env: PluginEnv, request_id: String, query: DocumentQuery) {
     let mut timer = tokio::time::interval(tokio::time::Duration::from_secs(60));
     loop {
         timer.tick().await;
@@ -298,11 +715,19 @@ async fn query_document_and_send_loop(env: PluginEnv, query: DocumentQuery) {
                 break;
             }
         }
-        query_documents_and_send(&env, &query, false).await;
+        // Each interval delivers the first page; the plugin can walk the
+        // rest via `RequestDocumentPage`.
+        query_documents_and_send(&env, &request_id, &query, 0, false).await;
     }
 }
 
-async fn query_documents_and_send(env: &PluginEnv, query: &DocumentQuery, send_empty: bool) {
+async fn query_documents_and_send(
+    env: &PluginEnv,
+    request_id: &str,
+    query: &DocumentQuery,
+    page: usize,
+    send_empty: bool,
+) {
     let tag_ids = query.has_tags.clone().unwrap_or_default();
     let tag_ids = tag::get_tags_by_value(&env.app_state.db, &tag_ids)
         .await
@@ -364,21 +789,137 @@ async fn query_documents_and_send(env: &PluginEnv, query: &DocumentQuery, send_e
     }
 
     if !results.is_empty() || send_empty {
+        let (page_count, documents) = paginate(results, query.page_size, page);
         let _ = env
             .cmd_writer
             .send(PluginCommand::HandleUpdate {
                 plugin_id: env.id,
                 event: PluginEvent::DocumentResponse {
-                    request_id: String::from("ahhh_my_id"),
-                    page_count: 1,
-                    page: 0,
-                    documents: results,
+                    request_id: request_id.to_string(),
+                    page_count,
+                    page,
+                    documents,
                 },
             })
             .await;
     }
 }
 
+/// Splits `results` into pages of `page_size`. Out-of-range pages return an
+/// empty document list w/ the correct page count so a plugin can tell it
+/// walked off the end. No `page_size` keeps everything on a single page.
+fn paginate<T>(results: Vec<T>, page_size: Option<usize>, page: usize) -> (usize, Vec<T>) {
+    let page_size = match page_size {
+        Some(size) if size > 0 => size,
+        _ => return (1, if page == 0 { results } else { Vec::new() }),
+    };
+
+    let page_count = results.len().div_ceil(page_size).max(1);
+    let page_results = results
+        .into_iter()
+        .skip(page * page_size)
+        .take(page_size)
+        .collect();
+    (page_count, page_results)
+}
+
+/// Caps `content` to at most `max_bytes`, cutting on a char boundary.
+/// Returns the (possibly shortened) content & whether anything was cut.
+fn cap_content(content: &str, max_bytes: usize) -> (String, bool) {
+    if content.len() <= max_bytes {
+        return (content.to_string(), false);
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    (content[..end].to_string(), true)
+}
+
+/// Forwards debounced filesystem events under `path` to the plugin until the
+/// plugin is disabled. Multiple listeners can watch the same path; only one
+/// OS-level watch is registered per path.
+async fn watch_path_and_send(
+    env: PluginEnv,
+    path: PathBuf,
+    extensions: Vec<String>,
+    recursive: bool,
+) {
+    let extensions = if extensions.is_empty() {
+        None
+    } else {
+        Some(extensions.into_iter().collect::<HashSet<String>>())
+    };
+
+    let mut rx = {
+        let mut watcher = env.app_state.file_watcher.lock().await;
+        match watcher.as_mut() {
+            Some(watcher) => watcher.watch_path(&path, extensions, recursive).await,
+            None => {
+                log::warn!(
+                    "<{}> file watcher not running, unable to watch: {}",
+                    env.name,
+                    path.display()
+                );
+                return;
+            }
+        }
+    };
+
+    while let Some(events) = rx.recv().await {
+        {
+            let manager = env.app_state.plugin_manager.lock().await;
+            if !manager.is_enabled(env.id) {
+                log::debug!("Plugin has been disabled removing file watch");
+                break;
+            }
+        }
+
+        for event in debounced_to_plugin_events(&env.app_state.db, &events).await {
+            let _ = env
+                .cmd_writer
+                .send(PluginCommand::HandleUpdate {
+                    plugin_id: env.id,
+                    event,
+                })
+                .await;
+        }
+    }
+}
+
+/// Maps debounced filesystem events onto plugin events. A path that no
+/// longer exists was deleted; otherwise the processed files table decides
+/// between created & updated.
+async fn debounced_to_plugin_events(
+    db: &DatabaseConnection,
+    events: &[DebouncedEvent],
+) -> Vec<PluginEvent> {
+    let mut plugin_events = Vec::new();
+    for event in events {
+        let uri = filesystem::utils::path_to_uri(&event.path);
+        if !event.path.exists() {
+            plugin_events.push(PluginEvent::FileDeleted(uri));
+            continue;
+        }
+
+        let is_processed = processed_files::Entity::find()
+            .filter(processed_files::Column::FilePath.eq(uri.clone()))
+            .one(db)
+            .await
+            .unwrap_or_default()
+            .is_some();
+
+        if is_processed {
+            plugin_events.push(PluginEvent::FileUpdated(uri));
+        } else {
+            plugin_events.push(PluginEvent::FileCreated(uri));
+        }
+    }
+
+    plugin_events
+}
+
 /// Handle plugin calls into the host environment. These are run as separate tokio tasks
 /// so we don't block the main thread.
 pub(crate) fn plugin_cmd(env: &PluginEnv) {
@@ -408,24 +949,82 @@ async fn handle_plugin_cmd(cmd: PluginCommandRequest, env: PluginEnv) {
 }
 
 /// Log call from the plugin. This is a utility function since the plugin has
-/// has direct stdio/stdout access.
+/// has direct stdio/stdout access. Lines land in the plugin's own log file
+/// (& the server log), filtered by the per-plugin log-level setting.
 pub(crate) fn plugin_log(env: &PluginEnv) {
-    if let Ok(msg) = wasi_read_string(&env.wasi_env) {
-        log::info!("{}: {}", env.name, msg);
+    if let Ok(line) = wasi_read_string(&env.wasi_env) {
+        // Lines from the `log_*` shims are `<level>\t<message>`; anything
+        // else (e.g. a plugin writing to stdout directly) is info.
+        let (level, msg) = match line.split_once('\t') {
+            Some((token, msg)) => match LogLevel::parse(token) {
+                Some(level) => (level, msg),
+                None => (LogLevel::Info, line.as_str()),
+            },
+            None => (LogLevel::Info, line.as_str()),
+        };
+
+        if level < env.log_level {
+            return;
+        }
+
+        let server_level = match level {
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        };
+        log::log!(server_level, "{}: {}", env.name, msg);
+
+        if let Err(error) = append_plugin_log(&env.log_file, level, msg) {
+            log::warn!("<{}> unable to write plugin log: {}", env.name, error);
+        }
     }
 }
 
+/// Max size of a plugin's log file before it's rolled over; one previous
+/// file is kept as `<name>.log.1`.
+const MAX_PLUGIN_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Appends a timestamped line to the plugin's log file, rolling the file
+/// over once it grows past `MAX_PLUGIN_LOG_BYTES`.
+fn append_plugin_log(path: &Path, level: LogLevel, msg: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    if let Ok(meta) = std::fs::metadata(path) {
+        if meta.len() >= MAX_PLUGIN_LOG_BYTES {
+            let _ = std::fs::rename(path, path.with_extension("log.1"));
+        }
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(
+        file,
+        "{} {} {}",
+        Utc::now().to_rfc3339(),
+        level.as_str().to_uppercase(),
+        msg
+    )
+}
+
 /// Adds a file into the plugin data directory. Use this to copy files from elsewhere
 /// in the filesystem so that it can be processed by the plugin.
-fn _handle_sync_file(env: &PluginEnv, dst: &str, src: &str) {
+fn handle_sync_file(env: &PluginEnv, dst: &str, src: &str) {
     log::info!("<{}> requesting access to file: {}", env.name, src);
     let dst = Path::new(dst.trim_start_matches('/'));
     let src = Path::new(&src);
 
     if let Some(file_name) = src.file_name() {
-        let dst = env._data_dir.join(dst).join(file_name);
+        let dst_dir = env._data_dir.join(dst);
+        let _ = std::fs::create_dir_all(&dst_dir);
         // Attempt to copy file into plugin data directory
-        if let Err(e) = std::fs::copy(src, dst) {
+        if let Err(e) = std::fs::copy(src, dst_dir.join(file_name)) {
             log::error!("Unable to copy into plugin data dir: {}", e);
         }
     } else {
@@ -433,30 +1032,74 @@ fn _handle_sync_file(env: &PluginEnv, dst: &str, src: &str) {
     }
 }
 
+/// Urls per `IN` clause when bulk-checking for already-known urls; sqlite
+/// caps the number of bound variables in a single statement.
+const ENQUEUE_CHUNK_SIZE: usize = 500;
+
+/// Filters `urls` down to ones not already in the crawl queue or index.
+/// Importers resend their full list every sync, so checking in bulk here
+/// avoids hitting the queue w/ thousands of per-url existence checks.
+async fn filter_known_urls(db: &DatabaseConnection, urls: &[String]) -> Vec<String> {
+    let mut known: HashSet<String> = HashSet::new();
+    for chunk in urls.chunks(ENQUEUE_CHUNK_SIZE) {
+        let queued = crawl_queue::Entity::find()
+            .filter(crawl_queue::Column::Url.is_in(chunk.to_owned()))
+            .all(db)
+            .await
+            .unwrap_or_default();
+        known.extend(queued.into_iter().map(|model| model.url));
+
+        let indexed = indexed_document::Entity::find()
+            .filter(indexed_document::Column::Url.is_in(chunk.to_owned()))
+            .all(db)
+            .await
+            .unwrap_or_default();
+        known.extend(indexed.into_iter().map(|model| model.url));
+    }
+
+    // `insert` also drops duplicates within the batch itself.
+    urls.iter()
+        .filter(|url| known.insert((*url).clone()))
+        .cloned()
+        .collect::<Vec<String>>()
+}
+
 fn handle_plugin_enqueue(env: &PluginEnv, urls: &[String]) {
     log::info!("{} enqueuing {} urls", env.name, urls.len());
-    let state = env.app_state.clone();
+    let env = env.clone();
     // Grab a handle to the plugin manager runtime
     let rt = tokio::runtime::Handle::current();
     let urls = urls.to_owned();
 
     rt.spawn(async move {
-        let state = state.clone();
-        if let Err(e) = enqueue_all(
-            &state.db.clone(),
-            &urls,
-            &[],
-            &state.user_settings.load(),
-            &EnqueueSettings {
-                force_allow: true,
-                ..Default::default()
-            },
-            Option::None,
-        )
-        .await
-        {
-            log::error!("error adding to queue: {}", e);
+        let state = env.app_state.clone();
+        let new_urls = filter_known_urls(&state.db, &urls).await;
+        let count = new_urls.len();
+        if !new_urls.is_empty() {
+            if let Err(e) = enqueue_all(
+                &state.db.clone(),
+                &new_urls,
+                &[],
+                &state.user_settings.load(),
+                &EnqueueSettings {
+                    force_allow: true,
+                    ..Default::default()
+                },
+                Option::None,
+            )
+            .await
+            {
+                log::error!("error adding to queue: {}", e);
+            }
         }
+
+        let _ = env
+            .cmd_writer
+            .send(PluginCommand::HandleUpdate {
+                plugin_id: env.id,
+                event: PluginEvent::EnqueueResponse { count },
+            })
+            .await;
     });
 }
 
@@ -466,3 +1109,283 @@ pub struct WalkStats {
     pub files: i32,
     pub skipped: i32,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        append_plugin_log, cap_content, check_permissions, convert_docs_to_crawl,
+        debounced_to_plugin_events, filter_known_urls, host_allowed, paginate, run_sqlite_query,
+        PluginPermissions,
+    };
+    use crate::filesystem::utils::path_to_uri;
+    use entities::models::{crawl_queue, indexed_document, processed_files};
+    use entities::sea_orm::{ActiveModelBehavior, ActiveModelTrait, EntityTrait, Set};
+    use entities::test::setup_test_db;
+    use notify_debouncer_mini::{DebouncedEvent, DebouncedEventKind};
+    use spyglass_plugin::PluginEvent;
+
+    #[test]
+    fn test_paginate() {
+        let (page_count, page) = paginate(vec![1, 2, 3, 4, 5], Some(2), 1);
+        assert_eq!(page_count, 3);
+        assert_eq!(page, vec![3, 4]);
+
+        // Out-of-range pages return the correct count & no documents.
+        let (page_count, page) = paginate(vec![1, 2, 3, 4, 5], Some(2), 7);
+        assert_eq!(page_count, 3);
+        assert!(page.is_empty());
+
+        // No page size keeps everything on a single page.
+        let (page_count, page) = paginate(vec![1, 2, 3], None, 0);
+        assert_eq!(page_count, 1);
+        assert_eq!(page, vec![1, 2, 3]);
+        let (page_count, page) = paginate(vec![1, 2, 3], None, 1);
+        assert_eq!(page_count, 1);
+        assert!(page.is_empty());
+    }
+
+    #[test]
+    fn test_run_sqlite_query() {
+        use serde_json::json;
+
+        let dir = std::env::temp_dir().join("spyglass-sqlite-query-test");
+        std::fs::create_dir_all(&dir).expect("Unable to create temp dir");
+        let db_path = dir.join("fixture.sqlite");
+        let _ = std::fs::remove_file(&db_path);
+
+        let conn = rusqlite::Connection::open(&db_path).expect("Unable to create fixture db");
+        conn.execute_batch(
+            "CREATE TABLE moz_places (url TEXT, title TEXT, visit_count INTEGER);
+             INSERT INTO moz_places VALUES ('https://example.com/a', 'A', 10);
+             INSERT INTO moz_places VALUES ('https://example.com/b', NULL, 2);",
+        )
+        .expect("Unable to fill fixture db");
+        drop(conn);
+
+        let (columns, rows) = run_sqlite_query(
+            &db_path,
+            "SELECT url, title, visit_count FROM moz_places WHERE visit_count >= ?1",
+            &[json!(5)],
+        )
+        .expect("Query failed");
+        assert_eq!(columns, vec!["url", "title", "visit_count"]);
+        assert_eq!(rows, vec![vec![json!("https://example.com/a"), json!("A"), json!(10)]]);
+
+        // NULLs come through as JSON nulls, not empty strings.
+        let (_, rows) = run_sqlite_query(
+            &db_path,
+            "SELECT title FROM moz_places WHERE url = ?1",
+            &[json!("https://example.com/b")],
+        )
+        .expect("Query failed");
+        assert_eq!(rows, vec![vec![json!(null)]]);
+
+        // The connection is opened read-only; writes are rejected.
+        assert!(run_sqlite_query(&db_path, "DELETE FROM moz_places", &[]).is_err());
+    }
+
+    #[test]
+    fn test_append_plugin_log() {
+        use spyglass_plugin::LogLevel;
+
+        let dir = std::env::temp_dir().join("spyglass-plugin-log-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        // The plugins log folder is created on first write.
+        let log_file = dir.join("plugins").join("test-plugin.log");
+
+        append_plugin_log(&log_file, LogLevel::Info, "hello").expect("Unable to write log");
+        append_plugin_log(&log_file, LogLevel::Error, "it broke").expect("Unable to write log");
+
+        let contents = std::fs::read_to_string(&log_file).expect("Unable to read log");
+        let lines = contents.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("INFO hello"));
+        assert!(lines[1].contains("ERROR it broke"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let allowed = vec!["example.com".to_string(), "*.nuget.org".to_string()];
+        assert!(host_allowed("example.com", &allowed));
+        assert!(host_allowed("nuget.org", &allowed));
+        assert!(host_allowed("azuresearch-usnc.nuget.org", &allowed));
+        assert!(!host_allowed("sub.example.com", &allowed));
+        assert!(!host_allowed("evil-nuget.org", &allowed));
+        assert!(host_allowed("anything.dev", &["*".to_string()]));
+        assert!(!host_allowed("anything.dev", &[]));
+    }
+
+    #[test]
+    fn test_check_permissions() {
+        use spyglass_plugin::PluginCommandRequest;
+
+        // Everything is denied by default...
+        let denied = PluginPermissions::default();
+        let enqueue = PluginCommandRequest::Enqueue { urls: Vec::new() };
+        assert!(check_permissions(&enqueue, &denied).is_err());
+        assert!(check_permissions(&enqueue, &PluginPermissions {
+            enqueue: true,
+            ..Default::default()
+        })
+        .is_ok());
+
+        // ...except requests that only touch the index.
+        let delete = PluginCommandRequest::DeleteDoc {
+            url: "https://example.com".into(),
+        };
+        assert!(check_permissions(&delete, &denied).is_ok());
+
+        // Syncing a sqlite database needs both permissions.
+        let sync = PluginCommandRequest::SyncFile {
+            dst: "/".into(),
+            src: "/home/user/places.sqlite".into(),
+        };
+        let sync_only = PluginPermissions {
+            sync_files: true,
+            ..Default::default()
+        };
+        assert!(check_permissions(&sync, &sync_only).is_err());
+        assert!(check_permissions(&sync, &PluginPermissions {
+            sqlite: true,
+            ..sync_only
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_convert_docs_published_at() {
+        let doc = spyglass_plugin::DocumentUpdate {
+            url: "https://example.com/one".into(),
+            open_url: None,
+            content: None,
+            title: None,
+            description: None,
+            tags: Vec::new(),
+            published_at: Some("2023-01-02T03:04:05+00:00".into()),
+        };
+        let (crawls, _) = convert_docs_to_crawl(&[doc.clone()], &[]);
+        assert_eq!(
+            crawls[0].published_at,
+            Some(chrono::DateTime::parse_from_rfc3339("2023-01-02T03:04:05+00:00").unwrap().into())
+        );
+
+        // Invalid timestamps are dropped rather than failing the whole doc.
+        let doc = spyglass_plugin::DocumentUpdate {
+            published_at: Some("last tuesday".into()),
+            ..doc
+        };
+        let (crawls, _) = convert_docs_to_crawl(&[doc], &[]);
+        assert!(crawls[0].published_at.is_none());
+    }
+
+    #[test]
+    fn test_cap_content() {
+        let (content, truncated) = cap_content("hello", 16);
+        assert_eq!(content, "hello");
+        assert!(!truncated);
+
+        // Cuts land on a char boundary, not mid-codepoint.
+        let (content, truncated) = cap_content("héllo", 2);
+        assert_eq!(content, "h");
+        assert!(truncated);
+    }
+
+    #[tokio::test]
+    async fn test_filter_known_urls() {
+        let db = setup_test_db().await;
+
+        // An importer-sized sync: 10k urls, all but the last few hundred
+        // already queued or indexed.
+        let urls = (0..10_000)
+            .map(|idx| format!("https://example.com/bookmarks/{idx}"))
+            .collect::<Vec<String>>();
+
+        let queued = urls[..9_500]
+            .iter()
+            .map(|url| {
+                let mut model = crawl_queue::ActiveModel::new();
+                model.domain = Set("example.com".to_string());
+                model.url = Set(url.clone());
+                model
+            })
+            .collect::<Vec<crawl_queue::ActiveModel>>();
+        for chunk in queued.chunks(500) {
+            crawl_queue::Entity::insert_many(chunk.to_vec())
+                .exec(&db)
+                .await
+                .expect("Unable to seed crawl queue");
+        }
+
+        let indexed = urls[9_500..9_800]
+            .iter()
+            .map(|url| {
+                let mut model = indexed_document::ActiveModel::new();
+                model.domain = Set("example.com".to_string());
+                model.url = Set(url.clone());
+                model.doc_id = Set(format!("doc-{url}"));
+                model
+            })
+            .collect::<Vec<indexed_document::ActiveModel>>();
+        indexed_document::Entity::insert_many(indexed)
+            .exec(&db)
+            .await
+            .expect("Unable to seed indexed documents");
+
+        let start = std::time::Instant::now();
+        let new_urls = filter_known_urls(&db, &urls).await;
+        println!("filtered 10k urls in {:?}", start.elapsed());
+        assert_eq!(new_urls, urls[9_800..].to_vec());
+
+        // Duplicates within one batch collapse to a single url.
+        let duped = vec![
+            "https://example.com/new".to_string(),
+            "https://example.com/new".to_string(),
+        ];
+        assert_eq!(filter_known_urls(&db, &duped).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_debounced_to_plugin_events() {
+        let db = setup_test_db().await;
+
+        let dir = std::env::temp_dir().join("spyglass-plugin-events-test");
+        std::fs::create_dir_all(&dir).expect("Unable to create test dir");
+        let known_file = dir.join("known.md");
+        let new_file = dir.join("new.md");
+        std::fs::write(&known_file, "known").expect("Unable to write test file");
+        std::fs::write(&new_file, "new").expect("Unable to write test file");
+        let missing_file = dir.join("missing.md");
+        let _ = std::fs::remove_file(&missing_file);
+
+        // Mark one of the files as already processed.
+        let mut model = processed_files::ActiveModel::new();
+        model.file_path = Set(path_to_uri(&known_file));
+        model.last_modified = Set(chrono::Utc::now());
+        model
+            .insert(&db)
+            .await
+            .expect("Unable to insert processed file");
+
+        let events = [&known_file, &new_file, &missing_file]
+            .iter()
+            .map(|path| DebouncedEvent {
+                path: path.to_path_buf(),
+                kind: DebouncedEventKind::Any,
+            })
+            .collect::<Vec<DebouncedEvent>>();
+
+        let plugin_events = debounced_to_plugin_events(&db, &events).await;
+        assert_eq!(
+            plugin_events,
+            vec![
+                PluginEvent::FileUpdated(path_to_uri(&known_file)),
+                PluginEvent::FileCreated(path_to_uri(&new_file)),
+                PluginEvent::FileDeleted(path_to_uri(&missing_file)),
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
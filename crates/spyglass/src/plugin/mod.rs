@@ -1,29 +1,43 @@
 use std::collections::HashSet;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use entities::sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use spyglass_plugin::SearchFilter;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tokio::time::Duration;
-use wasmer::{Instance, Module, Store, WasmerEnv};
+use wasmer::{CompilerConfig, Cranelift, Instance, Module, Store, Universal, WasmerEnv};
+use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
+use wasmer_middlewares::Metering;
 use wasmer_wasi::{Pipe, WasiEnv, WasiState};
 
 use entities::models::lens;
 use shared::config::{Config, LensConfig};
-use shared::plugin::{PluginConfig, PluginType};
-use spyglass_plugin::{consts::env, PluginEvent};
+use shared::plugin::{PluginConfig, PluginPermissions, PluginType};
+use spyglass_plugin::{consts::env, DocumentQuery, LogLevel, PluginEvent, PluginState};
+use spyglass_rpc::{PluginDisabledPayload, PluginStatusPayload, RpcEvent, RpcEventType};
 
 use crate::state::AppState;
 
 mod exports;
 
 type PluginId = usize;
+
+/// Interval between `IntervalUpdate` events when a plugin doesn't ask for
+/// anything specific.
+const DEFAULT_UPDATE_INTERVAL_SECS: u64 = 10 * 60;
+/// Smallest interval a plugin can request; also the scheduler's tick rate.
+const MIN_UPDATE_INTERVAL_SECS: u64 = 60;
+/// Number of fuel-metering failures before a plugin is auto-disabled.
+const PLUGIN_FAILURE_LIMIT: i64 = 3;
+
 #[derive(Debug)]
 pub enum PluginCommand {
     DisablePlugin(String),
@@ -35,7 +49,15 @@ pub enum PluginCommand {
         event: PluginEvent,
     },
     QueueIntervalCheck,
-    SubscribeForUpdates(PluginId),
+    /// Tear down & re-instantiate a plugin after its wasm/manifest changed.
+    ReloadPlugin(String),
+    /// Fire an immediate `IntervalUpdate` for one plugin by name.
+    RunPluginNow(String),
+    SubscribeForUpdates {
+        plugin_id: PluginId,
+        /// Requested seconds between updates; `None` for the default.
+        interval_seconds: Option<u64>,
+    },
 }
 
 /// Plugin context whenever we get a call from the one of the plugins
@@ -49,6 +71,12 @@ pub(crate) struct PluginEnv {
     app_state: AppState,
     /// Where the plugin stores data
     _data_dir: PathBuf,
+    /// Permissions declared in the plugin manifest
+    permissions: PluginPermissions,
+    /// Minimum level written to the plugin's log file
+    log_level: LogLevel,
+    /// Where the plugin's own log output goes (`logs/plugins/<name>.log`)
+    log_file: PathBuf,
     /// wasi connection for communications
     wasi_env: WasiEnv,
     /// host specific requests
@@ -61,12 +89,16 @@ pub struct PluginInstance {
     pub config: PluginConfig,
     pub instance: Instance,
     pub env: WasiEnv,
+    /// Max wasm instructions per call into this plugin; refreshed before
+    /// every invocation.
+    pub fuel_budget: u64,
 }
 
 impl PluginInstance {
     pub async fn search_filters(&self) -> Vec<SearchFilter> {
         if let Err(e) =
-            PluginManager::call_plugin_func(self.instance.clone(), "search_filter").await
+            PluginManager::call_plugin_func(self.instance.clone(), "search_filter", self.fuel_budget)
+                .await
         {
             log::error!("search_filters: {}", e);
             return Vec::new();
@@ -85,9 +117,11 @@ impl PluginInstance {
         }
     }
 
-    pub fn update(&mut self, event: PluginEvent) {
+    /// Sends `event` to the plugin's `update` func. Returns true if the call
+    /// was terminated for running past its fuel budget.
+    pub fn update(&mut self, event: PluginEvent) -> bool {
         if !self.config.is_enabled {
-            return;
+            return false;
         }
 
         if let Ok(func) = self.instance.exports.get_function("update") {
@@ -96,18 +130,55 @@ impl PluginInstance {
                     log::error!("unable to request update from plugin: {}", e)
                 }
                 Ok(_) => {
+                    set_remaining_points(&self.instance, self.fuel_budget);
                     if let Err(e) = func.call(&[]) {
+                        if exhausted_fuel(&self.instance) {
+                            return true;
+                        }
                         log::error!("update failed: {}", e);
                     }
                 }
             }
         }
+
+        false
+    }
+}
+
+/// An interval subscription for a plugin.
+#[derive(Clone, Debug)]
+pub struct UpdateSubscription {
+    pub interval_seconds: u64,
+    /// When the plugin last received an `IntervalUpdate`.
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+impl UpdateSubscription {
+    /// When the next `IntervalUpdate` is due.
+    pub fn next_run(&self) -> DateTime<Utc> {
+        match self.last_run {
+            Some(last_run) => last_run + chrono::Duration::seconds(self.interval_seconds as i64),
+            None => Utc::now(),
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.last_run {
+            Some(last_run) => {
+                now - last_run >= chrono::Duration::seconds(self.interval_seconds as i64)
+            }
+            None => true,
+        }
     }
 }
 
 pub struct PluginManager {
-    check_update_subs: HashSet<PluginId>,
+    /// Interval subscriptions per plugin, each w/ its own cadence.
+    update_subs: DashMap<PluginId, UpdateSubscription>,
     plugins: DashMap<PluginId, PluginInstance>,
+    /// Active document queries by (plugin, request id), kept so a plugin can
+    /// ask for later pages of an earlier query.
+    doc_queries: DashMap<(PluginId, String), DocumentQuery>,
 }
 
 impl Default for PluginManager {
@@ -117,9 +188,16 @@ impl Default for PluginManager {
 }
 
 impl PluginManager {
-    pub async fn call_plugin_func(instance: Instance, func_name: &str) -> anyhow::Result<()> {
+    pub async fn call_plugin_func(
+        instance: Instance,
+        func_name: &str,
+        fuel: u64,
+    ) -> anyhow::Result<()> {
         let exports = instance.exports.clone();
         let func = func_name.to_owned();
+        // Give the call a fresh fuel budget; the metering middleware traps
+        // the call once it's used up.
+        set_remaining_points(&instance, fuel);
         // Wrap this bad boy in something we can send across threads.
         let async_exports = Arc::new(Mutex::new(exports));
         // Spawn a thread so that plugins don't hold up the main thread.
@@ -131,17 +209,24 @@ impl PluginManager {
 
             Ok(())
         });
-        let _ = handle.await?;
+        handle.await??;
         Ok(())
     }
 
     pub fn new() -> Self {
         PluginManager {
-            check_update_subs: Default::default(),
+            update_subs: Default::default(),
             plugins: Default::default(),
+            doc_queries: Default::default(),
         }
     }
 
+    /// The interval subscription for a plugin, if it has one.
+    pub fn update_schedule(&self, name: &str) -> Option<UpdateSubscription> {
+        let plugin = self.find_by_name(name.to_string())?;
+        self.update_subs.get(&plugin.id).map(|sub| sub.clone())
+    }
+
     pub fn find_by_name(&self, name: String) -> Option<PluginInstance> {
         for entry in &self.plugins {
             if entry.config.name == name {
@@ -183,7 +268,17 @@ pub async fn plugin_event_loop(
     let mut config = config.clone();
     plugin_load(&state, &mut config, &cmd_writer).await;
 
-    let mut interval = tokio::time::interval(Duration::from_secs(10 * 60));
+    // Hot-reload plugins when their wasm build or manifest changes so plugin
+    // development doesn't require a backend restart.
+    tokio::spawn(watch_plugins_dir(
+        state.clone(),
+        config.clone(),
+        cmd_writer.clone(),
+    ));
+
+    // The scheduler ticks at the minimum interval; each subscription keeps
+    // its own cadence & is only updated when due.
+    let mut interval = tokio::time::interval(Duration::from_secs(MIN_UPDATE_INTERVAL_SECS));
     let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
 
     loop {
@@ -204,7 +299,7 @@ pub async fn plugin_event_loop(
                 log::info!("disabling plugin <{}>", plugin_name);
 
                 let mut disabled = Vec::new();
-                let mut manager = state.plugin_manager.lock().await;
+                let manager = state.plugin_manager.lock().await;
                 if let Some(plugin) = manager.find_by_name(plugin_name.clone()) {
                     if let Some(mut instance) = manager.plugins.get_mut(&plugin.id) {
                         instance.config.is_enabled = false;
@@ -213,12 +308,21 @@ pub async fn plugin_event_loop(
                 }
 
                 disabled.iter().for_each(|pid| {
-                    manager.check_update_subs.remove(pid);
+                    manager.update_subs.remove(pid);
                 });
+                manager
+                    .doc_queries
+                    .retain(|(pid, _), _| !disabled.contains(pid));
             }
             Some(PluginCommand::EnablePlugin(plugin_name)) => {
                 log::info!("enabling plugin <{}>", plugin_name);
 
+                // An explicit re-enable wipes the slate clean for plugins
+                // that were auto-disabled for misbehaving.
+                if let Err(e) = lens::clear_failures(&plugin_name, &state.db).await {
+                    log::error!("Unable to clear failures for <{}>: {}", plugin_name, e);
+                }
+
                 let manager = state.plugin_manager.lock().await;
                 if let Some(plugin) = manager.find_by_name(plugin_name.clone()) {
                     if let Some(mut instance) = manager.plugins.get_mut(&plugin.id) {
@@ -227,23 +331,41 @@ pub async fn plugin_event_loop(
                             config.user_settings = user_settings;
                             instance.config.set_user_config(&config.user_settings);
                         }
-                        instance.config.is_enabled = true;
-                        // Re-initialize plugin
-                        let _ = cmd_writer
-                            .send(PluginCommand::Initialize(instance.config.clone()))
-                            .await;
+                        // Invalid settings block the enable; the error is
+                        // surfaced through the plugin's status.
+                        if validate_plugin_settings(&state, &instance.config).await {
+                            instance.config.is_enabled = true;
+                            // Re-initialize plugin
+                            let _ = cmd_writer
+                                .send(PluginCommand::Initialize(instance.config.clone()))
+                                .await;
+                        }
                     }
                 }
             }
             Some(PluginCommand::HandleUpdate { plugin_id, event }) => {
                 let manager = state.plugin_manager.lock().await;
+                let mut exhausted = None;
                 if let Some(mut plugin) = manager.plugins.get_mut(&plugin_id) {
-                    plugin.update(event);
+                    if plugin.update(event) {
+                        exhausted = Some(plugin.config.name.clone());
+                    }
                 } else {
                     log::error!("Unable to find plugin id: {}", plugin_id);
                 };
+
+                if let Some(plugin_name) = exhausted {
+                    record_plugin_failure(&state, &cmd_writer, &plugin_name).await;
+                }
             }
-            Some(PluginCommand::Initialize(plugin)) => {
+            Some(PluginCommand::Initialize(mut plugin)) => {
+                // Don't run a plugin w/ settings that fail validation; keep
+                // the instance around (so settings can be fixed) but
+                // disabled.
+                if plugin.is_enabled && !validate_plugin_settings(&state, &plugin).await {
+                    plugin.is_enabled = false;
+                }
+
                 let manager = state.plugin_manager.lock().await;
                 let plugin_id = manager.plugins.len();
                 match plugin_init(plugin_id, &state, &cmd_writer, &plugin).await {
@@ -255,6 +377,7 @@ pub async fn plugin_event_loop(
                                 config: plugin.clone(),
                                 instance: instance.clone(),
                                 env: env.clone(),
+                                fuel_budget: state.user_settings.load().plugin_fuel_budget,
                             },
                         );
                     }
@@ -263,18 +386,101 @@ pub async fn plugin_event_loop(
             }
             Some(PluginCommand::QueueIntervalCheck) => {
                 let manager = state.plugin_manager.lock().await;
-                for plugin_id in &manager.check_update_subs {
-                    let _ = cmd_writer
-                        .send(PluginCommand::HandleUpdate {
-                            plugin_id: *plugin_id,
-                            event: PluginEvent::IntervalUpdate,
-                        })
-                        .await;
+                let now = Utc::now();
+                for mut entry in manager.update_subs.iter_mut() {
+                    let plugin_id = *entry.key();
+                    if entry.value().is_due(now) {
+                        entry.value_mut().last_run = Some(now);
+                        let _ = cmd_writer
+                            .send(PluginCommand::HandleUpdate {
+                                plugin_id,
+                                event: PluginEvent::IntervalUpdate,
+                            })
+                            .await;
+                    }
+                }
+            }
+            Some(PluginCommand::ReloadPlugin(plugin_name)) => {
+                // Re-read the manifest so setting & permission changes are
+                // picked up alongside the new wasm build.
+                let plugin_configs = config.load_plugin_config();
+                let manager = state.plugin_manager.lock().await;
+                if let Some(plugin) = manager.find_by_name(plugin_name.clone()) {
+                    let mut plug = plugin_configs
+                        .get(&plugin_name)
+                        .cloned()
+                        .unwrap_or_else(|| plugin.config.clone());
+                    plug.is_enabled = plugin.config.is_enabled;
+                    plug.set_user_config(&config.user_settings);
+
+                    // Drop state tied to the old instance; the new one
+                    // re-subscribes in its `load()`. The data dir is derived
+                    // from the wasm path, so anything persisted carries over.
+                    manager.update_subs.remove(&plugin.id);
+                    manager.doc_queries.retain(|(pid, _), _| *pid != plugin.id);
+
+                    match plugin_init(plugin.id, &state, &cmd_writer, &plug).await {
+                        Ok((instance, env)) => {
+                            log::info!(
+                                "plugin {} reloaded (build hash {})",
+                                plug.name,
+                                build_hash(&plug)
+                            );
+                            manager.plugins.insert(
+                                plugin.id,
+                                PluginInstance {
+                                    id: plugin.id,
+                                    config: plug,
+                                    instance,
+                                    env,
+                                    fuel_budget: state.user_settings.load().plugin_fuel_budget,
+                                },
+                            );
+                        }
+                        // Keep the old instance around; a broken build
+                        // shouldn't take the plugin down.
+                        Err(e) => log::warn!("Unable to reload plugin <{}>: {}", plug.name, e),
+                    }
+                } else {
+                    log::warn!("reload: unknown plugin <{}>", plugin_name);
+                }
+            }
+            Some(PluginCommand::RunPluginNow(plugin_name)) => {
+                let manager = state.plugin_manager.lock().await;
+                match manager.find_by_name(plugin_name.clone()) {
+                    Some(plugin) => {
+                        if let Some(mut sub) = manager.update_subs.get_mut(&plugin.id) {
+                            sub.last_run = Some(Utc::now());
+                        }
+                        let _ = cmd_writer
+                            .send(PluginCommand::HandleUpdate {
+                                plugin_id: plugin.id,
+                                event: PluginEvent::IntervalUpdate,
+                            })
+                            .await;
+                    }
+                    None => log::warn!("run now: unknown plugin <{}>", plugin_name),
                 }
             }
-            Some(PluginCommand::SubscribeForUpdates(plugin_id)) => {
-                let mut manager = state.plugin_manager.lock().await;
-                manager.check_update_subs.insert(plugin_id);
+            Some(PluginCommand::SubscribeForUpdates {
+                plugin_id,
+                interval_seconds,
+            }) => {
+                // Clamp to the host minimum so a plugin can't spin the
+                // scheduler.
+                let interval_seconds = interval_seconds
+                    .unwrap_or(DEFAULT_UPDATE_INTERVAL_SECS)
+                    .max(MIN_UPDATE_INTERVAL_SECS);
+
+                let manager = state.plugin_manager.lock().await;
+                manager.update_subs.insert(
+                    plugin_id,
+                    UpdateSubscription {
+                        interval_seconds,
+                        last_run: Some(Utc::now()),
+                    },
+                );
+                // Fire the first update immediately.
                 let _ = cmd_writer
                     .send(PluginCommand::HandleUpdate {
                         plugin_id,
@@ -343,7 +549,7 @@ pub async fn plugin_load(
                 name: plug.name.clone(),
                 author: plug.author,
                 description: Some(plug.description.clone()),
-                trigger: plug.trigger.clone(),
+                trigger: plug.trigger.clone().into(),
                 ..Default::default()
             };
 
@@ -375,6 +581,118 @@ pub async fn plugin_load(
     }
 }
 
+/// Validates a plugin's settings against the types declared in its manifest.
+/// Failures are stored as the plugin's status so clients can surface the
+/// error inline & the plugin is kept disabled until the settings are fixed.
+async fn validate_plugin_settings(state: &AppState, plugin: &PluginConfig) -> bool {
+    let errors = match plugin.validate_user_settings() {
+        Ok(()) => return true,
+        Err(errors) => errors,
+    };
+
+    let summary = errors
+        .iter()
+        .map(|(name, error)| format!("{name}: {error}"))
+        .collect::<Vec<String>>()
+        .join("; ");
+    log::warn!("<{}> has invalid settings: {}", plugin.name, summary);
+
+    let message = format!("Invalid settings - {summary}");
+    if let Err(e) =
+        lens::update_status(&plugin.name, PluginState::Error.as_str(), &message, &state.db).await
+    {
+        log::error!("Unable to store status for <{}>: {}", plugin.name, e);
+    }
+
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::PluginStatus,
+            payload: serde_json::to_value(PluginStatusPayload {
+                plugin_name: plugin.name.clone(),
+                state: PluginState::Error.as_str().to_owned(),
+                message,
+                progress: None,
+            })
+            .ok(),
+        })
+        .await;
+
+    false
+}
+
+/// Watches the plugins directory & queues a reload whenever a plugin's wasm
+/// build or manifest changes. Events are already debounced by the file
+/// watcher, which keeps incremental builds from triggering a reload storm.
+async fn watch_plugins_dir(
+    state: AppState,
+    config: Config,
+    cmd_writer: mpsc::Sender<PluginCommand>,
+) {
+    let plugins_dir = config.plugins_dir();
+    let extensions: HashSet<String> = HashSet::from(["wasm".into(), "ron".into()]);
+    let mut rx = loop {
+        {
+            let mut watcher = state.file_watcher.lock().await;
+            if let Some(watcher) = watcher.as_mut() {
+                break watcher
+                    .watch_path(&plugins_dir, Some(extensions.clone()), true)
+                    .await;
+            }
+        }
+        // The file watcher is installed shortly after startup; check back.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    };
+
+    while let Some(events) = rx.recv().await {
+        // One reload per plugin no matter how many of its files changed in
+        // the batch.
+        let mut changed: Vec<String> = Vec::new();
+        for event in events {
+            let file_name = event.path.file_name().and_then(|name| name.to_str());
+            if !matches!(file_name, Some("main.wasm") | Some("manifest.ron")) {
+                continue;
+            }
+
+            // Plugins are identified in the manager by their wasm path, so
+            // map a manifest change back to its sibling wasm file.
+            let wasm_path = match event.path.parent() {
+                Some(folder) => folder.join("main.wasm"),
+                None => continue,
+            };
+
+            let manager = state.plugin_manager.lock().await;
+            for entry in &manager.plugins {
+                if entry.config.path.as_deref() == Some(wasm_path.as_path())
+                    && !changed.contains(&entry.config.name)
+                {
+                    changed.push(entry.config.name.clone());
+                }
+            }
+        }
+
+        for plugin_name in changed {
+            let _ = cmd_writer
+                .send(PluginCommand::ReloadPlugin(plugin_name))
+                .await;
+        }
+    }
+}
+
+/// Short content hash of a plugin's wasm build, logged on reload so devs can
+/// confirm the new build was picked up.
+fn build_hash(plugin: &PluginConfig) -> String {
+    let bytes = plugin
+        .path
+        .as_ref()
+        .and_then(|path| std::fs::read(path).ok())
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    let hash = hex::encode(&hasher.finalize()[..]);
+    hash[..8].to_string()
+}
+
 pub async fn plugin_init(
     plugin_id: PluginId,
     state: &AppState,
@@ -396,7 +714,14 @@ pub async fn plugin_init(
     let output = Pipe::new();
     let input = Pipe::new();
 
-    let store = Store::default();
+    // Meter execution so a plugin stuck in an infinite loop is terminated
+    // instead of hanging the host. The budget is refreshed before each call
+    // into the plugin.
+    let fuel_budget = state.user_settings.load().plugin_fuel_budget;
+    let metering = Arc::new(Metering::new(fuel_budget, |_operator| 1));
+    let mut compiler = Cranelift::default();
+    compiler.push_middleware(metering);
+    let store = Store::new(&Universal::new(compiler).engine());
     let module = Module::from_file(&store, path)?;
     let user_settings = &plugin.user_settings;
 
@@ -446,12 +771,76 @@ pub async fn plugin_init(
     // Lets call the `_start` function, which is our `main` function in Rust
     if plugin.is_enabled {
         log::info!("STARTING <{}>", plugin.name);
-        PluginManager::call_plugin_func(instance.clone(), "_start").await?;
+        if let Err(e) =
+            PluginManager::call_plugin_func(instance.clone(), "_start", fuel_budget).await
+        {
+            if exhausted_fuel(&instance) {
+                record_plugin_failure(state, cmd_writer, &plugin.name).await;
+            }
+            return Err(e);
+        }
     }
 
     Ok((instance.clone(), wasi_env))
 }
 
+/// True if the last trap out of `instance` was caused by running out of fuel
+/// rather than a plugin-side error.
+fn exhausted_fuel(instance: &Instance) -> bool {
+    matches!(get_remaining_points(instance), MeteringPoints::Exhausted)
+}
+
+/// Records a fuel-metering failure against the plugin's lens row. Once a
+/// plugin hits `PLUGIN_FAILURE_LIMIT` failures it's disabled & an event is
+/// published so clients can let the user know.
+async fn record_plugin_failure(
+    state: &AppState,
+    cmd_writer: &mpsc::Sender<PluginCommand>,
+    plugin_name: &str,
+) {
+    log::warn!("terminated <{}>: exceeded fuel budget", plugin_name);
+
+    let count = match lens::record_failure(plugin_name, &state.db).await {
+        Ok(Some(count)) => count,
+        Ok(None) => return,
+        Err(e) => {
+            log::error!("Unable to record failure for <{}>: {}", plugin_name, e);
+            return;
+        }
+    };
+
+    if count < PLUGIN_FAILURE_LIMIT {
+        return;
+    }
+
+    log::warn!(
+        "disabling plugin <{}> after {} failures",
+        plugin_name,
+        count
+    );
+    if let Err(e) = lens::disable(plugin_name, &state.db).await {
+        log::error!("Unable to disable <{}>: {}", plugin_name, e);
+    }
+
+    let _ = cmd_writer
+        .send(PluginCommand::DisablePlugin(plugin_name.to_owned()))
+        .await;
+
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::PluginDisabled,
+            payload: serde_json::to_value(PluginDisabledPayload {
+                plugin_name: plugin_name.to_owned(),
+                reason: format!(
+                    "Terminated {} times for exceeding its execution budget",
+                    count
+                ),
+            })
+            .ok(),
+        })
+        .await;
+}
+
 // --------------------------------------------------------------------------------
 // Utility functions for wasi <> spyglass comms
 // --------------------------------------------------------------------------------
@@ -548,21 +937,26 @@ pub async fn lens_to_filters(state: AppState, trigger: &str) -> Vec<SearchFilter
 #[cfg(test)]
 mod test {
     use entities::models::lens;
-    use entities::sea_orm::EntityTrait;
+    use entities::sea_orm::{ActiveModelTrait, EntityTrait, Set};
     use entities::test::setup_test_db;
     use shared::config::{LensConfig, UserSettings};
-    use spyglass_plugin::SearchFilter;
+    use shared::plugin::{PluginConfig, PluginType};
+    use spyglass_plugin::{PluginEvent, SearchFilter};
     use spyglass_searcher::schema::SearchDocument;
     use spyglass_searcher::{schema::DocFields, IndexBackend};
+    use tokio::sync::mpsc;
 
-    use super::{lens_to_filters, AppState};
+    use super::{
+        lens_to_filters, plugin_init, record_plugin_failure, validate_plugin_settings, AppState,
+        PluginCommand, PluginInstance, PLUGIN_FAILURE_LIMIT,
+    };
 
     #[tokio::test]
     async fn test_lens_to_filter() {
         let db = setup_test_db().await;
         let test_lens = LensConfig {
             name: "test_lens".to_owned(),
-            trigger: "test".to_owned(),
+            trigger: "test".into(),
             urls: vec!["https://oldschool.runescape.wiki/wiki/".to_string()],
             ..Default::default()
         };
@@ -589,4 +983,145 @@ mod test {
             SearchFilter::URLRegexAllow("^https://oldschool.runescape.wiki/wiki/.*".to_owned())
         );
     }
+
+    #[tokio::test]
+    async fn test_validate_plugin_settings() {
+        use shared::form::{FormType, SettingOpts};
+
+        let db = setup_test_db().await;
+        let test_lens = LensConfig {
+            name: "settings-test".to_owned(),
+            trigger: "settings".into(),
+            ..Default::default()
+        };
+        let _ = lens::add_or_enable(&db, &test_lens, lens::LensType::Plugin)
+            .await
+            .expect("Unable to add lens");
+
+        let state = AppState::builder()
+            .with_db(db.clone())
+            .with_user_settings(&UserSettings::default())
+            .with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+            .build();
+
+        let mut plugin_config = PluginConfig {
+            name: "settings-test".to_owned(),
+            author: "spyglass".to_owned(),
+            description: "".to_owned(),
+            version: "1".to_owned(),
+            trigger: "settings".into(),
+            path: None,
+            plugin_type: PluginType::Lens,
+            user_settings: Default::default(),
+            permissions: Default::default(),
+            is_enabled: true,
+        };
+        plugin_config.user_settings.insert(
+            "VISIT_COUNT_THRESHOLD".into(),
+            SettingOpts {
+                label: "Minimum visit count".into(),
+                value: "not a number".into(),
+                form_type: FormType::Number,
+                help_text: None,
+                restart_required: false,
+            },
+        );
+
+        // A bad value blocks the enable & lands in the plugin's status.
+        assert!(!validate_plugin_settings(&state, &plugin_config).await);
+        let row = lens::find_by_name("settings-test", &db)
+            .await
+            .expect("Unable to query lens")
+            .expect("Missing lens row");
+        assert_eq!(row.status_state.as_deref(), Some("error"));
+        assert!(row
+            .status_message
+            .unwrap_or_default()
+            .contains("VISIT_COUNT_THRESHOLD"));
+
+        // Fixed (or cleared) values pass.
+        if let Some(opts) = plugin_config.user_settings.get_mut("VISIT_COUNT_THRESHOLD") {
+            opts.value = "3".into();
+        }
+        assert!(validate_plugin_settings(&state, &plugin_config).await);
+    }
+
+    #[tokio::test]
+    async fn test_fuel_exhaustion_disables_plugin() {
+        let db = setup_test_db().await;
+        let test_lens = LensConfig {
+            name: "spin-test".to_owned(),
+            trigger: "spin".into(),
+            ..Default::default()
+        };
+
+        let (_, model) = lens::add_or_enable(&db, &test_lens, lens::LensType::Plugin)
+            .await
+            .expect("Unable to add lens");
+        // Plugins aren't auto-enabled on install; flip it on so we can see
+        // the auto-disable kick in.
+        let mut enabled: lens::ActiveModel = model.into();
+        enabled.is_enabled = Set(true);
+        enabled.update(&db).await.expect("Unable to enable lens");
+
+        let state = AppState::builder()
+            .with_db(db.clone())
+            .with_user_settings(&UserSettings::default())
+            .with_index(&IndexBackend::Memory, DocFields::as_schema(), false)
+            .build();
+
+        // Copy the fixture into a scratch dir so the plugin's data folder
+        // isn't created inside the repo checkout.
+        let temp_dir = std::env::temp_dir().join("spyglass-fuel-test");
+        std::fs::create_dir_all(&temp_dir).expect("Unable to create temp dir");
+        let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../plugins/test-plugin/spin.wat");
+        let wasm_path = temp_dir.join("spin.wat");
+        std::fs::copy(fixture, &wasm_path).expect("Unable to copy fixture");
+
+        let plugin_config = PluginConfig {
+            name: "spin-test".to_owned(),
+            author: "spyglass".to_owned(),
+            description: "Spins forever inside update()".to_owned(),
+            version: "1".to_owned(),
+            trigger: "spin".into(),
+            path: Some(wasm_path),
+            plugin_type: PluginType::Lens,
+            user_settings: Default::default(),
+            permissions: Default::default(),
+            is_enabled: true,
+        };
+
+        let (cmd_writer, mut cmd_queue) = mpsc::channel(10);
+        let (instance, env) = plugin_init(0, &state, &cmd_writer, &plugin_config)
+            .await
+            .expect("Unable to init plugin");
+
+        // Give it a tiny budget; the spin loop burns through it almost
+        // immediately & the host gets control back instead of hanging.
+        let mut plugin = PluginInstance {
+            id: 0,
+            config: plugin_config,
+            instance,
+            env,
+            fuel_budget: 10_000,
+        };
+        assert!(plugin.update(PluginEvent::IntervalUpdate));
+
+        // Each termination is recorded; the last one disables the plugin.
+        for _ in 0..PLUGIN_FAILURE_LIMIT {
+            record_plugin_failure(&state, &cmd_writer, "spin-test").await;
+        }
+
+        let row = lens::find_by_name("spin-test", &db)
+            .await
+            .expect("Unable to query lens")
+            .expect("Missing lens row");
+        assert_eq!(row.failure_count, PLUGIN_FAILURE_LIMIT);
+        assert!(!row.is_enabled);
+        assert!(matches!(
+            cmd_queue.try_recv(),
+            Ok(PluginCommand::DisablePlugin(_))
+        ));
+    }
 }
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use entities::models::indexed_document;
+use entities::sea_orm::entity::prelude::*;
+use shared::config::Config;
+use spyglass_rpc::{ReindexPayload, RpcEvent, RpcEventType};
+use spyglass_searcher::client::Searcher;
+use spyglass_searcher::schema::{schema_for_languages, DocumentUpdate, ToDocument};
+use spyglass_searcher::{IndexBackend, SearchTrait, WriteTrait};
+
+use crate::state::AppState;
+
+/// Directory the rebuilt index is staged in, next to the live index dir so
+/// the final swap is a rename on the same filesystem.
+const STAGING_DIR: &str = "reindex_staging";
+
+/// Where the rebuilt index is staged before the swap.
+fn staging_dir(config: &Config) -> anyhow::Result<PathBuf> {
+    config
+        .index_dir()
+        .parent()
+        .map(|parent| parent.join(STAGING_DIR))
+        .ok_or_else(|| anyhow!("Index directory has no parent"))
+}
+
+/// Rebuilds the search index from the database & the content cached in the
+/// current index, writing into a staging directory that's swapped in once
+/// every document has been written. Interrupted runs resume where they left
+/// off: anything already committed to the staging index is skipped.
+///
+/// The running instance keeps serving from the old (renamed) segment files
+/// until it's restarted. Returns the number of documents in the new index.
+pub async fn reindex(state: &AppState) -> anyhow::Result<u64> {
+    let config = &state.config;
+    let index_dir = config.index_dir();
+    let staging_dir = staging_dir(config)?;
+    std::fs::create_dir_all(&staging_dir)?;
+
+    let staging = Searcher::with_index(
+        &IndexBackend::LocalPath(staging_dir.clone()),
+        schema_for_languages(&config.user_settings.index_languages),
+        false,
+    )?;
+
+    // Resume support: skip anything a previously interrupted run already
+    // committed to the staging index.
+    let mut staged: HashSet<String> = HashSet::new();
+    if staging.reader.searcher().num_docs() > 0 {
+        for (_, doc) in staging.search_by_query(None, None, &[], &[]).await {
+            staged.insert(doc.doc_id);
+        }
+        log::info!("Resuming reindex, {} docs already staged", staged.len());
+    }
+
+    let total_docs = indexed_document::Entity::find().count(&state.db).await?;
+    publish(state, ReindexPayload::Started { total_docs }).await;
+
+    let mut processed = 0_u64;
+    let mut skipped = 0_u64;
+    let mut pages =
+        indexed_document::Entity::find().paginate(&state.db, entities::BATCH_SIZE as u64);
+    while let Some(models) = pages.fetch_and_next().await? {
+        for model in models {
+            processed += 1;
+            if staged.contains(&model.doc_id) {
+                continue;
+            }
+
+            // Content, title & dates live in the current index; a document
+            // missing from it has nothing cached to rebuild from & needs a
+            // recrawl instead.
+            let cached = match state.index.get(&model.doc_id).await {
+                Some(cached) => cached,
+                None => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            let tags = cached
+                .tags
+                .iter()
+                .map(|tag_id| *tag_id as i64)
+                .collect::<Vec<i64>>();
+
+            staging
+                .upsert(
+                    &DocumentUpdate {
+                        doc_id: Some(model.doc_id.clone()),
+                        title: &cached.title,
+                        domain: &cached.domain,
+                        url: &cached.url,
+                        content: &cached.content,
+                        tags: &tags,
+                        published_at: cached.published_at,
+                        last_modified: cached.last_modified,
+                    }
+                    .to_document(),
+                )
+                .await?;
+        }
+
+        // Commit after each batch so an interrupted run resumes from the
+        // last batch instead of starting over.
+        staging.save().await?;
+        publish(
+            state,
+            ReindexPayload::Progress {
+                processed,
+                total_docs,
+            },
+        )
+        .await;
+    }
+
+    staging.save().await?;
+    let _ = staging.reader.reload();
+    let num_docs = staging.reader.searcher().num_docs();
+    drop(staging);
+
+    // Swap the rebuilt index into place, keeping the old one as a backup
+    // until the next run. Renames, so both live on the same filesystem & the
+    // old mmapped segments stay valid for the running instance.
+    let backup_dir = index_dir.with_extension("bak");
+    if backup_dir.exists() {
+        std::fs::remove_dir_all(&backup_dir)?;
+    }
+    std::fs::rename(&index_dir, &backup_dir)?;
+    std::fs::rename(&staging_dir, &index_dir)?;
+
+    publish(state, ReindexPayload::Finished { num_docs, skipped }).await;
+    Ok(num_docs)
+}
+
+async fn publish(state: &AppState, payload: ReindexPayload) {
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::Reindex,
+            payload: Some(serde_json::to_value(&payload).unwrap_or_default()),
+        })
+        .await;
+}
@@ -1,19 +1,23 @@
 use arc_swap::ArcSwap;
 use dashmap::DashMap;
 use entities::models::create_connection;
+use entities::models::tag::TagCount;
 use entities::sea_orm::DatabaseConnection;
-use spyglass_llm::LlmClient;
-use spyglass_model_interface::embedding_api::EmbeddingApi;
+use spyglass_llm::LlmBackend;
+use spyglass_model_interface::embedding_api::{EmbeddingApi, SegmentationConfig};
+use spyglass_model_interface::rerank_api::RerankApi;
 use spyglass_rpc::RpcEvent;
-use spyglass_searcher::schema::DocFields;
+use spyglass_searcher::schema::{schema_for_languages, DocFields};
 use spyglass_searcher::schema::SearchDocument;
 use std::sync::Arc;
+use std::time::Instant;
 use tantivy::schema::Schema;
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::Mutex;
 use tokio::sync::{broadcast, mpsc};
 
 use crate::filesystem::SpyglassFileWatcher;
+use crate::plugin::{PluginCommand, PluginManager};
 use crate::task::{AppShutdown, UserSettingsChange};
 use crate::{
     pipeline::PipelineCommand,
@@ -23,6 +27,36 @@ use shared::config::{Config, LensConfig, PipelineConfiguration, UserSettings};
 use shared::metrics::Metrics;
 use spyglass_searcher::{client::Searcher, IndexBackend};
 
+/// Outcome of the last attempt to load the embedding model, surfaced via the
+/// `system_health` RPC so clients can tell users why semantic search is
+/// unavailable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModelLoadStatus {
+    /// Embeddings are turned off in the user's settings.
+    Disabled,
+    /// Model loaded & ready to embed.
+    Loaded,
+    /// Loading failed, w/ a human readable reason.
+    Failed(String),
+}
+
+impl ModelLoadStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ModelLoadStatus::Disabled => "disabled",
+            ModelLoadStatus::Loaded => "loaded",
+            ModelLoadStatus::Failed(_) => "failed",
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            ModelLoadStatus::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
 /// Used to track inflight requests and limit things
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum FetchLimitType {
@@ -59,13 +93,15 @@ impl FetchLimitType {
 pub struct AppState {
     pub db: DatabaseConnection,
     pub embedding_api: Arc<ArcSwap<Option<EmbeddingApi>>>,
+    pub embedding_status: Arc<ArcSwap<ModelLoadStatus>>,
+    pub rerank_api: Arc<ArcSwap<Option<RerankApi>>>,
     pub app_state: Arc<DashMap<String, String>>,
     pub lenses: Arc<DashMap<String, LensConfig>>,
     pub pipelines: Arc<DashMap<String, PipelineConfiguration>>,
     pub user_settings: Arc<ArcSwap<UserSettings>>,
     pub index: Searcher,
-    // Language model client. lazy loaded.
-    pub llm: Arc<Mutex<Option<LlmClient>>>,
+    // Language model backend (local or remote). lazy loaded.
+    pub llm: Arc<Mutex<Option<Box<dyn LlmBackend>>>>,
     pub metrics: Metrics,
     pub config: Config,
     // Task scheduler command/control
@@ -78,9 +114,15 @@ pub struct AppState {
     pub pause_cmd_tx: Arc<Mutex<Option<broadcast::Sender<AppPause>>>>,
     // Pipeline command/control
     pub pipeline_cmd_tx: Arc<Mutex<Option<mpsc::Sender<PipelineCommand>>>>,
+    // Plugin command/control
+    pub plugin_cmd_tx: Arc<Mutex<Option<mpsc::Sender<PluginCommand>>>>,
+    pub plugin_manager: Arc<Mutex<PluginManager>>,
     pub file_watcher: Arc<Mutex<Option<SpyglassFileWatcher>>>,
     // Keep track of in-flight tasks
     pub fetch_limits: Arc<DashMap<FetchLimitType, usize>>,
+    // Cached tag counts for `list_tags` w/ when they were fetched, so
+    // searchbar autocomplete isn't an aggregate query per keystroke.
+    pub tag_cache: Arc<ArcSwap<Option<(Instant, Vec<TagCount>)>>>,
     pub readonly_mode: bool,
 }
 
@@ -97,7 +139,9 @@ impl AppState {
             .with_db(db)
             .with_index(
                 &IndexBackend::LocalPath(config.index_dir()),
-                DocFields::as_schema(),
+                // The tokenizer is baked into the schema, pick it based on
+                // the user's configured index languages.
+                schema_for_languages(&config.user_settings.index_languages),
                 readonly_mode,
             )
             .with_lenses(&config.lenses.values().cloned().collect())
@@ -124,8 +168,12 @@ impl AppState {
     }
 
     pub fn reload_model(&mut self) {
-        let embedding_api = load_model(self.user_settings.load_full().as_ref());
+        let (embedding_api, status) = load_model(self.user_settings.load_full().as_ref());
         self.embedding_api.store(Arc::new(embedding_api));
+        self.embedding_status.store(Arc::new(status));
+
+        let rerank_api = load_rerank_model(self.user_settings.load_full().as_ref());
+        self.rerank_api.store(Arc::new(rerank_api));
     }
 
     pub fn builder() -> AppStateBuilder {
@@ -192,7 +240,8 @@ impl AppStateBuilder {
             UserSettings::default()
         };
 
-        let embedding_api = load_model(&user_settings);
+        let (embedding_api, embedding_status) = load_model(&user_settings);
+        let rerank_api = load_rerank_model(&user_settings);
 
         let (shutdown_tx, _) = broadcast::channel::<AppShutdown>(16);
         let (config_tx, _) = broadcast::channel::<UserSettingsChange>(16);
@@ -213,14 +262,19 @@ impl AppStateBuilder {
             pause_cmd_tx: Arc::new(Mutex::new(None)),
             pipeline_cmd_tx: Arc::new(Mutex::new(None)),
             pipelines: Arc::new(pipelines),
+            plugin_cmd_tx: Arc::new(Mutex::new(None)),
+            plugin_manager: Arc::new(Mutex::new(PluginManager::new())),
             rpc_events: Arc::new(std::sync::Mutex::new(rpc_events)),
             shutdown_cmd_tx: Arc::new(Mutex::new(shutdown_tx)),
             config_cmd_tx: Arc::new(Mutex::new(config_tx)),
             file_watcher: Arc::new(Mutex::new(None)),
             user_settings: Arc::new(ArcSwap::from_pointee(user_settings)),
             fetch_limits: Arc::new(DashMap::new()),
+            tag_cache: Arc::new(ArcSwap::from_pointee(None)),
             readonly_mode: self.readonly_mode.unwrap_or_default(),
             embedding_api: Arc::new(ArcSwap::from_pointee(embedding_api)),
+            embedding_status: Arc::new(ArcSwap::from_pointee(embedding_status)),
+            rerank_api: Arc::new(ArcSwap::from_pointee(rerank_api)),
         }
     }
 
@@ -265,16 +319,28 @@ impl AppStateBuilder {
             log::error!("Error connecting to index {index:?}. Error: {error:?}");
         }
 
-        self.index = Some(searcher.expect("Unable to open index"));
+        let searcher = searcher.expect("Unable to open index");
+        // Apply any user stop word overrides to the registered tokenizers.
+        if let Some(overrides) = Config::load_stop_word_overrides() {
+            searcher.reload_stop_words(Some(&overrides));
+        }
+
+        // Load any user-defined synonym groups for query expansion.
+        if let Some(synonyms) = Config::load_synonyms() {
+            searcher.reload_synonyms(Some(&synonyms));
+        }
+
+        self.index = Some(searcher);
         self
     }
 }
 
-fn load_model(user_settings: &UserSettings) -> Option<EmbeddingApi> {
+fn load_model(user_settings: &UserSettings) -> (Option<EmbeddingApi>, ModelLoadStatus) {
     if user_settings.embedding_settings.enable_embeddings {
         let mut model_root = user_settings.data_directory.clone();
         model_root.push("models");
         model_root.push("embeddings");
+        let model_root = user_settings.embedding_settings.model_root(&model_root);
 
         let mut tokenizer_file = model_root.clone();
         tokenizer_file.push("tokenizer.json");
@@ -282,18 +348,99 @@ fn load_model(user_settings: &UserSettings) -> Option<EmbeddingApi> {
         model.push("model.safetensors");
 
         if tokenizer_file.exists() && model.exists() {
-            match EmbeddingApi::new(model_root.clone()) {
+            // A corrupt download would make the backend die w/ an opaque
+            // error; remove it so the next download pass fetches it fresh.
+            if !crate::model_files::verify_model_file(&model) {
+                log::error!(
+                    "Model file {} failed checksum verification, removing for re-download",
+                    model.display()
+                );
+                let _ = std::fs::remove_file(&model);
+                let _ = std::fs::remove_file(crate::model_files::checksum_path(&model));
+                return (
+                    None,
+                    ModelLoadStatus::Failed(
+                        "Model file failed checksum verification, it will be re-downloaded"
+                            .to_string(),
+                    ),
+                );
+            }
+
+            let segmentation = SegmentationConfig {
+                target_tokens: user_settings.embedding_settings.segment_tokens,
+                overlap_tokens: user_settings.embedding_settings.segment_overlap_tokens,
+                split_on_boundaries: user_settings.embedding_settings.split_on_boundaries,
+            };
+
+            match EmbeddingApi::new(
+                model_root.clone(),
+                segmentation,
+                user_settings.embedding_settings.embedding_dimensions,
+                user_settings.embedding_settings.device,
+                user_settings.embedding_settings.force_cpu,
+            ) {
                 Ok(embedding_api) => {
-                    log::info!("Embedding Model Loaded");
-                    Some(embedding_api)
+                    log::info!("Embedding Model Loaded on {}", embedding_api.device_name());
+                    (Some(embedding_api), ModelLoadStatus::Loaded)
                 }
                 Err(error) => {
                     log::error!("Error Loading Embedding Model {:?}", error);
-                    None
+                    (None, ModelLoadStatus::Failed(format!("{error:?}")))
                 }
             }
         } else {
             log::warn!("Model does not exist");
+            (
+                None,
+                ModelLoadStatus::Failed(format!(
+                    "Model files missing from {}",
+                    model_root.display()
+                )),
+            )
+        }
+    } else {
+        (None, ModelLoadStatus::Disabled)
+    }
+}
+
+fn load_rerank_model(user_settings: &UserSettings) -> Option<RerankApi> {
+    if user_settings.embedding_settings.enable_reranking {
+        let mut model_root = user_settings.data_directory.clone();
+        model_root.push("models");
+        model_root.push("reranker");
+
+        let mut tokenizer_file = model_root.clone();
+        tokenizer_file.push("tokenizer.json");
+        let mut model = model_root.clone();
+        model.push("model.safetensors");
+
+        if tokenizer_file.exists() && model.exists() {
+            if !crate::model_files::verify_model_file(&model) {
+                log::error!(
+                    "Model file {} failed checksum verification, removing for re-download",
+                    model.display()
+                );
+                let _ = std::fs::remove_file(&model);
+                let _ = std::fs::remove_file(crate::model_files::checksum_path(&model));
+                return None;
+            }
+
+            match RerankApi::new(
+                model_root.clone(),
+                user_settings.embedding_settings.device,
+                user_settings.embedding_settings.force_cpu,
+            ) {
+                Ok(rerank_api) => {
+                    log::info!("Rerank Model Loaded on {}", rerank_api.device_name());
+                    Some(rerank_api)
+                }
+                Err(error) => {
+                    log::error!("Error Loading Rerank Model {:?}", error);
+                    None
+                }
+            }
+        } else {
+            log::warn!("Rerank model does not exist");
             None
         }
     } else {
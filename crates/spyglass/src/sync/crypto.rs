@@ -0,0 +1,122 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{AeadCore, XChaCha20Poly1305, XNonce};
+use thiserror::Error;
+
+/// Magic bytes prefixed to every envelope so we can sanity check files before
+/// attempting a decrypt.
+const MAGIC: &[u8; 6] = b"SPSYNC";
+/// Bump when the envelope layout changes.
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    #[error("unable to derive key: {0}")]
+    KeyDerivation(String),
+    #[error("envelope is malformed or truncated")]
+    InvalidEnvelope,
+    #[error("unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("unable to decrypt: wrong passphrase or corrupted data")]
+    DecryptFailed,
+}
+
+/// Derive an encryption key from the user's passphrase + a per-file salt.
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], CryptoError> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| CryptoError::KeyDerivation(err.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypt `plaintext` into a self-contained envelope:
+/// `MAGIC | VERSION | salt | nonce | ciphertext`
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| CryptoError::DecryptFailed)?;
+
+    let mut envelope = Vec::with_capacity(MAGIC.len() + 1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+    envelope.extend_from_slice(MAGIC);
+    envelope.push(VERSION);
+    envelope.extend_from_slice(&salt);
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// Decrypt an envelope produced by [`seal`].
+pub fn open(passphrase: &str, envelope: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let header_len = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+    if envelope.len() <= header_len || &envelope[0..MAGIC.len()] != MAGIC {
+        return Err(CryptoError::InvalidEnvelope);
+    }
+
+    let version = envelope[MAGIC.len()];
+    if version != VERSION {
+        return Err(CryptoError::UnsupportedVersion(version));
+    }
+
+    let salt_start = MAGIC.len() + 1;
+    let nonce_start = salt_start + SALT_LEN;
+    let salt = &envelope[salt_start..nonce_start];
+    let nonce = XNonce::from_slice(&envelope[nonce_start..header_len]);
+
+    let key = derive_key(passphrase, salt)?;
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    cipher
+        .decrypt(nonce, &envelope[header_len..])
+        .map_err(|_| CryptoError::DecryptFailed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{open, seal, CryptoError};
+
+    #[test]
+    fn test_roundtrip() {
+        let envelope = seal("hunter2", b"some user data").expect("seal failed");
+        // Make sure we're not writing plaintext into the envelope.
+        assert!(!envelope
+            .windows("some user data".len())
+            .any(|w| w == b"some user data"));
+
+        let plaintext = open("hunter2", &envelope).expect("open failed");
+        assert_eq!(plaintext, b"some user data");
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let envelope = seal("hunter2", b"some user data").expect("seal failed");
+        let res = open("*******", &envelope);
+        assert!(matches!(res, Err(CryptoError::DecryptFailed)));
+    }
+
+    #[test]
+    fn test_corrupted_envelope() {
+        let mut envelope = seal("hunter2", b"some user data").expect("seal failed");
+        let last = envelope.len() - 1;
+        envelope[last] ^= 0xFF;
+        assert!(matches!(
+            open("hunter2", &envelope),
+            Err(CryptoError::DecryptFailed)
+        ));
+
+        // Not an envelope at all.
+        assert!(matches!(
+            open("hunter2", b"not an envelope"),
+            Err(CryptoError::InvalidEnvelope)
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SyncItemKind {
+    /// A favorited document, identified by URL.
+    Favorite,
+    /// The user settings bundle.
+    Settings,
+}
+
+/// A single piece of user-created data in a sync bundle.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SyncItem {
+    /// Stable identifier, e.g. "favorite:<url>" or "settings".
+    pub id: String,
+    pub kind: SyncItemKind,
+    pub payload: serde_json::Value,
+    /// When this item was last written.
+    pub updated_at: DateTime<Utc>,
+    /// Device that last wrote this item.
+    pub device_id: String,
+    /// Tombstone: item was deleted on `device_id` at `updated_at`. Kept
+    /// around so deletions propagate to devices that haven't synced yet.
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+/// Everything one device knows about its user-created data.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SyncBundle {
+    pub device_id: String,
+    pub generated_at: DateTime<Utc>,
+    pub items: Vec<SyncItem>,
+}
+
+/// Two devices wrote the same item within the conflict window. The winner is
+/// kept in the merged state; the loser is kept alongside so no data is lost.
+#[derive(Clone, Debug)]
+pub struct SyncConflict {
+    pub id: String,
+    pub winner: SyncItem,
+    pub loser: SyncItem,
+}
+
+#[derive(Debug, Default)]
+pub struct MergeResult {
+    pub merged: Vec<SyncItem>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Merge bundles from any number of devices into a single state using
+/// last-writer-wins per item. Tombstones participate like any other write, so
+/// the newest deletion beats an older edit (and vice versa). If the two newest
+/// versions of an item come from different devices within `conflict_window`
+/// and disagree, both are kept & reported as a conflict.
+pub fn merge(bundles: &[SyncBundle], conflict_window: Duration) -> MergeResult {
+    let mut by_id: HashMap<String, Vec<SyncItem>> = HashMap::new();
+    for bundle in bundles {
+        for item in &bundle.items {
+            by_id.entry(item.id.clone()).or_default().push(item.clone());
+        }
+    }
+
+    let mut result = MergeResult::default();
+    for (id, mut versions) in by_id {
+        // Newest first; tie-break on device id so merges are deterministic
+        // regardless of bundle ordering.
+        versions.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| b.device_id.cmp(&a.device_id))
+        });
+
+        let winner = versions.remove(0);
+
+        // Check the runner-up for a concurrent, disagreeing write.
+        if let Some(loser) = versions.into_iter().find(|other| {
+            other.device_id != winner.device_id
+                && (winner.updated_at - other.updated_at) <= conflict_window
+                && (other.payload != winner.payload || other.deleted != winner.deleted)
+        }) {
+            result.conflicts.push(SyncConflict {
+                id: id.clone(),
+                winner: winner.clone(),
+                loser,
+            });
+        }
+
+        result.merged.push(winner);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merge, SyncBundle, SyncItem, SyncItemKind};
+    use chrono::{Duration, Utc};
+
+    fn favorite(id: &str, device: &str, age_secs: i64, deleted: bool) -> SyncItem {
+        SyncItem {
+            id: id.to_string(),
+            kind: SyncItemKind::Favorite,
+            payload: serde_json::json!({ "url": id.trim_start_matches("favorite:") }),
+            updated_at: Utc::now() - Duration::seconds(age_secs),
+            device_id: device.to_string(),
+            deleted,
+        }
+    }
+
+    fn bundle(device: &str, items: Vec<SyncItem>) -> SyncBundle {
+        SyncBundle {
+            device_id: device.to_string(),
+            generated_at: Utc::now(),
+            items,
+        }
+    }
+
+    #[test]
+    fn test_last_writer_wins() {
+        let bundles = vec![
+            bundle("laptop", vec![favorite("favorite:a", "laptop", 60, false)]),
+            bundle("desktop", vec![favorite("favorite:a", "desktop", 3600, false)]),
+        ];
+
+        let res = merge(&bundles, Duration::seconds(0));
+        assert_eq!(res.merged.len(), 1);
+        assert_eq!(res.merged[0].device_id, "laptop");
+        assert!(res.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_tombstone_beats_older_write() {
+        let bundles = vec![
+            bundle("laptop", vec![favorite("favorite:a", "laptop", 60, true)]),
+            bundle("desktop", vec![favorite("favorite:a", "desktop", 3600, false)]),
+        ];
+
+        let res = merge(&bundles, Duration::seconds(0));
+        assert_eq!(res.merged.len(), 1);
+        assert!(res.merged[0].deleted);
+
+        // An edit newer than the tombstone revives the item.
+        let bundles = vec![
+            bundle("laptop", vec![favorite("favorite:a", "laptop", 3600, true)]),
+            bundle("desktop", vec![favorite("favorite:a", "desktop", 60, false)]),
+        ];
+        let res = merge(&bundles, Duration::seconds(0));
+        assert!(!res.merged[0].deleted);
+    }
+
+    #[test]
+    fn test_concurrent_writes_conflict() {
+        // Delete & edit within the window should surface a conflict.
+        let bundles = vec![
+            bundle("laptop", vec![favorite("favorite:a", "laptop", 30, true)]),
+            bundle("desktop", vec![favorite("favorite:a", "desktop", 60, false)]),
+        ];
+
+        let res = merge(&bundles, Duration::minutes(5));
+        assert_eq!(res.conflicts.len(), 1);
+        let conflict = &res.conflicts[0];
+        assert_eq!(conflict.winner.device_id, "laptop");
+        assert_eq!(conflict.loser.device_id, "desktop");
+
+        // Outside the window, it's a clean last-writer-wins.
+        let res = merge(&bundles, Duration::seconds(10));
+        assert!(res.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_identical_writes_do_not_conflict() {
+        let mut a = favorite("favorite:a", "laptop", 30, false);
+        let mut b = favorite("favorite:a", "desktop", 60, false);
+        a.payload = serde_json::json!({ "url": "a" });
+        b.payload = serde_json::json!({ "url": "a" });
+
+        let bundles = vec![bundle("laptop", vec![a]), bundle("desktop", vec![b])];
+        let res = merge(&bundles, Duration::minutes(5));
+        assert!(res.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_three_device_divergence() {
+        // Three devices that haven't seen each other in a while:
+        // - laptop favorited "a" & deleted "b"
+        // - desktop edited "b" long before the deletion & favorited "c"
+        // - tablet favorited "d" and, concurrently with laptop, "a"
+        let laptop = bundle(
+            "laptop",
+            vec![
+                favorite("favorite:a", "laptop", 30, false),
+                favorite("favorite:b", "laptop", 600, true),
+            ],
+        );
+        let desktop = bundle(
+            "desktop",
+            vec![
+                favorite("favorite:b", "desktop", 7200, false),
+                favorite("favorite:c", "desktop", 3600, false),
+            ],
+        );
+        let mut concurrent = favorite("favorite:a", "tablet", 45, false);
+        concurrent.payload = serde_json::json!({ "url": "a", "note": "from tablet" });
+        let tablet = bundle(
+            "tablet",
+            vec![concurrent, favorite("favorite:d", "tablet", 3600, false)],
+        );
+
+        let res = merge(&[laptop, desktop, tablet], Duration::minutes(5));
+
+        // a, b (tombstone), c, d
+        assert_eq!(res.merged.len(), 4);
+
+        let b = res.merged.iter().find(|i| i.id == "favorite:b").unwrap();
+        assert!(b.deleted, "deletion should win over the older edit");
+
+        let a = res.merged.iter().find(|i| i.id == "favorite:a").unwrap();
+        assert_eq!(a.device_id, "laptop");
+
+        // The concurrent, differing writes to "a" should conflict.
+        assert_eq!(res.conflicts.len(), 1);
+        assert_eq!(res.conflicts[0].id, "favorite:a");
+        assert_eq!(res.conflicts[0].loser.device_id, "tablet");
+    }
+}
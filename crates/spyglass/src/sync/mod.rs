@@ -0,0 +1,527 @@
+use std::path::Path;
+use std::time::Duration;
+
+use entities::models::tag::{self, TagType, TagValue};
+use entities::models::{document_tag, indexed_document};
+use entities::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use shared::config::{Config, UserSettings};
+use spyglass_rpc::{RpcEvent, RpcEventType, SyncConflictPayload};
+
+use crate::state::AppState;
+
+pub mod crypto;
+pub mod merge;
+
+use merge::{SyncBundle, SyncConflict, SyncItem, SyncItemKind};
+
+/// How often we scan the sync folder & publish local changes.
+const SYNC_INTERVAL_S: u64 = 60;
+/// Writes to the same item closer together than this are treated as a
+/// conflict rather than a clean last-writer-wins.
+const CONFLICT_WINDOW_S: i64 = 5 * 60;
+/// Extension used for the per-device bundle files.
+const BUNDLE_EXT: &str = "spsync";
+/// Merged view across all devices. Also acts as the baseline for detecting
+/// local deletions (tombstones).
+const MERGED_STATE: &str = "merged.state";
+/// Id of the settings item in a bundle.
+const SETTINGS_ITEM_ID: &str = "settings";
+/// Marker appended to the id of the losing side of a conflict.
+const CONFLICT_MARKER: &str = "|conflict:";
+
+fn favorite_item_id(url: &str) -> String {
+    format!("favorite:{url}")
+}
+
+/// Background task that syncs user-created data (favorites & settings) through
+/// the user's sync folder. Each device writes an encrypted `<device>.spsync`
+/// bundle plus a shared `merged.state`; remote changes are merged in with
+/// last-writer-wins + tombstones and applied locally.
+#[tracing::instrument(skip_all)]
+pub async fn sync_task(state: AppState) {
+    log::info!("Folder sync task started");
+
+    let device_id = Config::machine_identifier();
+    let mut interval = tokio::time::interval(Duration::from_secs(SYNC_INTERVAL_S));
+    let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let settings = state.user_settings.load_full();
+                if !settings.sync_settings.is_configured() {
+                    continue;
+                }
+
+                let sync_dir = settings
+                    .sync_settings
+                    .sync_directory
+                    .clone()
+                    .expect("checked by is_configured");
+                let passphrase = settings
+                    .sync_settings
+                    .passphrase
+                    .clone()
+                    .expect("checked by is_configured");
+
+                if !sync_dir.exists() {
+                    log::warn!("Sync folder {} does not exist, skipping", sync_dir.display());
+                    continue;
+                }
+
+                match run_sync(&state, &sync_dir, &passphrase, &device_id).await {
+                    Ok(conflicts) => {
+                        for conflict in conflicts {
+                            state
+                                .publish_event(&RpcEvent {
+                                    event_type: RpcEventType::SyncConflict,
+                                    payload: Some(
+                                        serde_json::to_value(&SyncConflictPayload {
+                                            item_id: conflict.id.clone(),
+                                            winner_device: conflict.winner.device_id.clone(),
+                                            loser_device: conflict.loser.device_id.clone(),
+                                        })
+                                        .unwrap_or_default(),
+                                    ),
+                                })
+                                .await;
+                        }
+                    }
+                    Err(err) => log::warn!("Unable to sync: {err}"),
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                log::info!("🛑 Shutting down sync task");
+                return;
+            }
+        }
+    }
+}
+
+/// Run a single sync pass: read remote bundles, merge with the previous state
+/// & our local data, apply remote changes locally, and write our bundle + the
+/// merged state back out. Returns any conflicts found during the merge.
+pub async fn run_sync(
+    state: &AppState,
+    sync_dir: &Path,
+    passphrase: &str,
+    device_id: &str,
+) -> anyhow::Result<Vec<SyncConflict>> {
+    let prior = read_bundle(&sync_dir.join(MERGED_STATE), passphrase).unwrap_or_default();
+
+    let settings = state.user_settings.load_full();
+    let local = collect_local_bundle(&state.db, &settings, device_id, &prior.items).await;
+
+    let mut bundles = read_remote_bundles(sync_dir, passphrase, device_id);
+    bundles.push(prior);
+    bundles.push(local.clone());
+
+    let result = merge::merge(&bundles, chrono::Duration::seconds(CONFLICT_WINDOW_S));
+    apply_merged(state, &result.merged, device_id).await;
+
+    // Keep the losing side of each conflict around (with a marker) so the
+    // user can recover it from the client.
+    let mut merged_items = result.merged;
+    for conflict in &result.conflicts {
+        let mut marked = conflict.loser.clone();
+        marked.id = format!("{}{}{}", marked.id, CONFLICT_MARKER, marked.device_id);
+        merged_items.push(marked);
+    }
+
+    write_bundle(
+        &sync_dir.join(format!("{device_id}.{BUNDLE_EXT}")),
+        passphrase,
+        &local,
+    )?;
+    write_bundle(
+        &sync_dir.join(MERGED_STATE),
+        passphrase,
+        &SyncBundle {
+            device_id: device_id.to_string(),
+            generated_at: chrono::Utc::now(),
+            items: merged_items,
+        },
+    )?;
+
+    Ok(result.conflicts)
+}
+
+/// Read & decrypt every other device's bundle in the sync folder. Files that
+/// fail to decrypt (wrong passphrase, partial sync write) are skipped.
+fn read_remote_bundles(sync_dir: &Path, passphrase: &str, device_id: &str) -> Vec<SyncBundle> {
+    let own_file = format!("{device_id}.{BUNDLE_EXT}");
+    let mut bundles = Vec::new();
+
+    let entries = match std::fs::read_dir(sync_dir) {
+        Ok(entries) => entries,
+        Err(_) => return bundles,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_bundle = path
+            .extension()
+            .map(|ext| ext == BUNDLE_EXT)
+            .unwrap_or(false);
+        let is_own = path
+            .file_name()
+            .map(|name| name == own_file.as_str())
+            .unwrap_or(false);
+
+        if !is_bundle || is_own {
+            continue;
+        }
+
+        match read_bundle(&path, passphrase) {
+            Ok(bundle) => bundles.push(bundle),
+            Err(err) => log::warn!("Skipping sync bundle {}: {err}", path.display()),
+        }
+    }
+
+    bundles
+}
+
+fn read_bundle(path: &Path, passphrase: &str) -> anyhow::Result<SyncBundle> {
+    let envelope = std::fs::read(path)?;
+    let plaintext = crypto::open(passphrase, &envelope)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Encrypt & write a bundle, skipping the write if the contents haven't
+/// changed so we don't generate endless churn for the sync provider.
+fn write_bundle(path: &Path, passphrase: &str, bundle: &SyncBundle) -> anyhow::Result<()> {
+    if let Ok(existing) = read_bundle(path, passphrase) {
+        if existing.items == bundle.items {
+            return Ok(());
+        }
+    }
+
+    let plaintext = serde_json::to_vec(bundle)?;
+    let envelope = crypto::seal(passphrase, &plaintext)?;
+    std::fs::write(path, envelope)?;
+    Ok(())
+}
+
+/// Build this device's bundle from the local database. Items that came from
+/// other devices (or haven't changed) echo the timestamp from the previous
+/// merged state; only genuinely new writes get fresh timestamps. Favorites
+/// present in the previous state but missing locally become tombstones.
+async fn collect_local_bundle(
+    db: &DatabaseConnection,
+    settings: &UserSettings,
+    device_id: &str,
+    prior: &[SyncItem],
+) -> SyncBundle {
+    let now = chrono::Utc::now();
+    let mut items = Vec::new();
+
+    let favorites = local_favorites(db).await;
+    for doc in &favorites {
+        let id = favorite_item_id(&doc.url);
+        let payload = serde_json::json!({ "url": doc.url });
+        if let Some(existing) = prior.iter().find(|item| item.id == id && !item.deleted) {
+            // Already synced, echo the original write.
+            items.push(existing.clone());
+        } else {
+            items.push(SyncItem {
+                id,
+                kind: SyncItemKind::Favorite,
+                payload,
+                updated_at: now,
+                device_id: device_id.to_string(),
+                deleted: false,
+            });
+        }
+    }
+
+    // Anything favorited in the previous state but no longer favorited
+    // locally was deleted here; write a tombstone so it propagates.
+    for item in prior {
+        if item.kind != SyncItemKind::Favorite
+            || item.deleted
+            || item.id.contains(CONFLICT_MARKER)
+        {
+            continue;
+        }
+
+        let still_local = favorites
+            .iter()
+            .any(|doc| favorite_item_id(&doc.url) == item.id);
+        if !still_local {
+            items.push(SyncItem {
+                id: item.id.clone(),
+                kind: SyncItemKind::Favorite,
+                payload: item.payload.clone(),
+                updated_at: now,
+                device_id: device_id.to_string(),
+                deleted: true,
+            });
+        }
+    }
+
+    // User settings are synced as a single item.
+    if let Ok(payload) = serde_json::to_value(settings) {
+        if let Some(existing) = prior
+            .iter()
+            .find(|item| item.id == SETTINGS_ITEM_ID && item.payload == payload)
+        {
+            items.push(existing.clone());
+        } else {
+            items.push(SyncItem {
+                id: SETTINGS_ITEM_ID.to_string(),
+                kind: SyncItemKind::Settings,
+                payload,
+                updated_at: now,
+                device_id: device_id.to_string(),
+                deleted: false,
+            });
+        }
+    }
+
+    SyncBundle {
+        device_id: device_id.to_string(),
+        generated_at: now,
+        items,
+    }
+}
+
+/// Apply the merged state to the local database/preferences.
+async fn apply_merged(state: &AppState, merged: &[SyncItem], device_id: &str) {
+    for item in merged {
+        if item.id.contains(CONFLICT_MARKER) {
+            continue;
+        }
+
+        match item.kind {
+            SyncItemKind::Favorite => {
+                let url = item
+                    .payload
+                    .get("url")
+                    .and_then(|val| val.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                if url.is_empty() {
+                    continue;
+                }
+
+                if item.deleted {
+                    remove_favorite(&state.db, &url).await;
+                } else {
+                    apply_favorite(&state.db, &url).await;
+                }
+            }
+            SyncItemKind::Settings => {
+                // Only apply settings written by another device.
+                if item.device_id == device_id {
+                    continue;
+                }
+
+                if let Ok(remote) = serde_json::from_value::<UserSettings>(item.payload.clone()) {
+                    let local = state.user_settings.load_full();
+                    let merged_settings = merge_remote_settings(&local, remote);
+                    if let Err(err) = Config::save_user_settings(&merged_settings) {
+                        log::warn!("Unable to apply synced settings: {err}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Merge remote settings into the local ones, keeping anything machine
+/// specific (paths, ports, the sync config itself) untouched.
+fn merge_remote_settings(local: &UserSettings, remote: UserSettings) -> UserSettings {
+    UserSettings {
+        data_directory: local.data_directory.clone(),
+        port: local.port,
+        sync_settings: local.sync_settings.clone(),
+        filesystem_settings: local.filesystem_settings.clone(),
+        ..remote
+    }
+}
+
+/// All locally favorited documents, regardless of favorite type
+/// (saved/upvoted/etc. for connections).
+async fn local_favorites(db: &DatabaseConnection) -> Vec<indexed_document::Model> {
+    let fav_tags = tag::Entity::find()
+        .filter(tag::Column::Label.eq(TagType::Favorited.to_string()))
+        .all(db)
+        .await
+        .unwrap_or_default();
+    if fav_tags.is_empty() {
+        return Vec::new();
+    }
+
+    let tag_ids = fav_tags.iter().map(|t| t.id).collect::<Vec<_>>();
+    let doc_ids = document_tag::Entity::find()
+        .filter(document_tag::Column::TagId.is_in(tag_ids))
+        .all(db)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|row| row.indexed_document_id)
+        .collect::<Vec<_>>();
+
+    indexed_document::Entity::find()
+        .filter(indexed_document::Column::Id.is_in(doc_ids))
+        .all(db)
+        .await
+        .unwrap_or_default()
+}
+
+async fn apply_favorite(db: &DatabaseConnection, url: &str) {
+    if let Ok(Some(doc)) = indexed_document::Entity::find()
+        .filter(indexed_document::Column::Url.eq(url))
+        .one(db)
+        .await
+    {
+        let _ = doc
+            .insert_tags(db, &[(TagType::Favorited, TagValue::Favorited.to_string())])
+            .await;
+    }
+}
+
+async fn remove_favorite(db: &DatabaseConnection, url: &str) {
+    if let Ok(Some(doc)) = indexed_document::Entity::find()
+        .filter(indexed_document::Column::Url.eq(url))
+        .one(db)
+        .await
+    {
+        let fav_tags = tag::Entity::find()
+            .filter(tag::Column::Label.eq(TagType::Favorited.to_string()))
+            .all(db)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+
+        let _ = document_tag::Entity::delete_many()
+            .filter(document_tag::Column::IndexedDocumentId.eq(doc.id))
+            .filter(document_tag::Column::TagId.is_in(fav_tags))
+            .exec(db)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::merge::{SyncBundle, SyncItem, SyncItemKind};
+    use super::{favorite_item_id, run_sync, BUNDLE_EXT, MERGED_STATE};
+    use crate::state::AppState;
+    use entities::models::indexed_document;
+    use entities::models::tag::{self, TagType};
+    use entities::sea_orm::{ActiveModelTrait, ModelTrait, Set};
+    use entities::test::setup_test_db;
+    use std::path::PathBuf;
+
+    fn test_sync_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("spyglass-sync-tests").join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Unable to create test dir");
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_applies_remote_favorite() {
+        let db = setup_test_db().await;
+        let state = AppState::builder().with_db(db.clone()).build();
+        let sync_dir = test_sync_dir("applies_remote_favorite");
+        let passphrase = "hunter2";
+
+        // Local document that another device has favorited.
+        let doc = indexed_document::ActiveModel {
+            domain: Set("example.com".into()),
+            url: Set("https://example.com/fav".into()),
+            doc_id: Set("fav-doc-id".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("Unable to insert");
+
+        // Simulate the other device's bundle.
+        let remote = SyncBundle {
+            device_id: "desktop".into(),
+            generated_at: chrono::Utc::now(),
+            items: vec![SyncItem {
+                id: favorite_item_id("https://example.com/fav"),
+                kind: SyncItemKind::Favorite,
+                payload: serde_json::json!({ "url": "https://example.com/fav" }),
+                updated_at: chrono::Utc::now(),
+                device_id: "desktop".into(),
+                deleted: false,
+            }],
+        };
+        let plaintext = serde_json::to_vec(&remote).expect("serialize");
+        let envelope = super::crypto::seal(passphrase, &plaintext).expect("seal");
+        std::fs::write(sync_dir.join(format!("desktop.{BUNDLE_EXT}")), envelope)
+            .expect("Unable to write bundle");
+
+        let conflicts = run_sync(&state, &sync_dir, passphrase, "laptop")
+            .await
+            .expect("sync failed");
+        assert!(conflicts.is_empty());
+
+        // The document should now be favorited locally.
+        let tags = doc
+            .find_related(tag::Entity)
+            .all(&db)
+            .await
+            .unwrap_or_default();
+        assert!(tags
+            .iter()
+            .any(|t| t.label == TagType::Favorited.to_string()));
+
+        // We should have written our own bundle & the merged state.
+        assert!(sync_dir.join(format!("laptop.{BUNDLE_EXT}")).exists());
+        assert!(sync_dir.join(MERGED_STATE).exists());
+
+        // Merged state should decrypt & contain the favorite.
+        let merged = super::read_bundle(&sync_dir.join(MERGED_STATE), passphrase).expect("read");
+        assert!(merged
+            .items
+            .iter()
+            .any(|item| item.id == favorite_item_id("https://example.com/fav") && !item.deleted));
+    }
+
+    #[tokio::test]
+    async fn test_run_sync_propagates_tombstone() {
+        let db = setup_test_db().await;
+        let state = AppState::builder().with_db(db.clone()).build();
+        let sync_dir = test_sync_dir("propagates_tombstone");
+        let passphrase = "hunter2";
+
+        // Previously synced favorite that was removed locally: seed the
+        // merged state with it, but don't favorite anything in the db.
+        let merged = SyncBundle {
+            device_id: "laptop".into(),
+            generated_at: chrono::Utc::now(),
+            items: vec![SyncItem {
+                id: favorite_item_id("https://example.com/old"),
+                kind: SyncItemKind::Favorite,
+                payload: serde_json::json!({ "url": "https://example.com/old" }),
+                updated_at: chrono::Utc::now() - chrono::Duration::hours(1),
+                device_id: "desktop".into(),
+                deleted: false,
+            }],
+        };
+        let plaintext = serde_json::to_vec(&merged).expect("serialize");
+        let envelope = super::crypto::seal(passphrase, &plaintext).expect("seal");
+        std::fs::write(sync_dir.join(MERGED_STATE), envelope).expect("Unable to write state");
+
+        let _ = run_sync(&state, &sync_dir, passphrase, "laptop")
+            .await
+            .expect("sync failed");
+
+        // The merged state should now carry a tombstone for the favorite.
+        let merged = super::read_bundle(&sync_dir.join(MERGED_STATE), passphrase).expect("read");
+        let item = merged
+            .items
+            .iter()
+            .find(|item| item.id == favorite_item_id("https://example.com/old"))
+            .expect("item should still be in the merged state");
+        assert!(item.deleted);
+        assert_eq!(item.device_id, "laptop");
+    }
+}
@@ -1,15 +1,20 @@
 use anyhow::anyhow;
+use entities::get_library_stats;
 use entities::models::crawl_queue::CrawlStatus;
 use entities::models::{
-    bootstrap_queue, connection, crawl_queue, embedding_queue, indexed_document,
+    bootstrap_queue, connection, crawl_queue, embedding_queue, indexed_document, vec_to_indexed,
 };
 use entities::sea_orm::Set;
 use entities::sea_orm::{sea_query::Expr, ColumnTrait, Condition, EntityTrait, QueryFilter};
 use futures::StreamExt;
 use notify::event::ModifyKind;
 use notify::{EventKind, RecursiveMode, Watcher};
-use shared::config::{Config, LensConfig, UserSettings, UserSettingsDiff};
-use spyglass_rpc::{ModelDownloadStatusPayload, RpcEvent, RpcEventType};
+use shared::config::{Config, UserSettings, UserSettingsDiff};
+use spyglass_rpc::{
+    CrawlBatchCompletedPayload, EmbeddingProgressPayload, IndexCommittedPayload,
+    LensBootstrapFinishedPayload, LensBootstrapProgressPayload, LensLoadErrorPayload,
+    ModelDownloadStatusPayload, RpcEvent, RpcEventType,
+};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
@@ -24,6 +29,7 @@ use crate::connection::{api_id_to_label, load_connection};
 use crate::crawler::bootstrap;
 use crate::documents::embeddings;
 use crate::filesystem;
+use crate::model_files;
 use crate::state::AppState;
 use crate::task::worker::FetchResult;
 use diff::Diff;
@@ -35,6 +41,17 @@ mod manager;
 pub mod worker;
 use lens::{load_lenses, read_lenses};
 
+/// Number of embedding jobs claimed & embedded in a single model pass, see
+/// `EmbeddingApi::embed_batch`.
+const EMBEDDING_JOB_BATCH_SIZE: u64 = 8;
+
+/// How often embedding progress is published while there's work in the queue.
+const EMBEDDING_PROGRESS_INTERVAL_S: u64 = 5;
+
+/// How often per-lens crawl progress is published while lenses have queued
+/// work.
+const CRAWL_PROGRESS_INTERVAL_S: u64 = 5;
+
 #[derive(Debug, Clone)]
 pub struct CrawlTask {
     pub id: i64,
@@ -100,9 +117,9 @@ pub enum WorkerCommand {
     /// Updates the document store for indexed document database table to
     /// cleanup inconsistencies
     CleanupDatabase(CleanupTask),
-    // Generates an embedding for a document
+    // Generates embeddings for a batch of documents in one model pass.
     Embedding {
-        id: i64,
+        ids: Vec<i64>,
     },
 }
 
@@ -129,7 +146,14 @@ pub async fn manager_task(
 
     let mut queue_check_interval = tokio::time::interval(Duration::from_millis(100));
     let mut commit_check_interval = tokio::time::interval(Duration::from_secs(10));
+    let mut lens_refresh_interval = tokio::time::interval(Duration::from_secs(5 * 60));
+    let mut crawl_progress_interval =
+        tokio::time::interval(Duration::from_secs(CRAWL_PROGRESS_INTERVAL_S));
     let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
+
+    // Crawled count per lens at the last progress report, used to derive a
+    // crawl rate & detect when a lens' queue drains.
+    let mut last_crawled: HashMap<String, u64> = HashMap::new();
     // Startup filesystem watcher
     filesystem::configure_watcher(state.clone()).await;
 
@@ -172,6 +196,14 @@ pub async fn manager_task(
             _ = commit_check_interval.tick() => {
                 let _ = queue.send(WorkerCommand::CommitIndex).await;
             }
+            // Refresh lenses w/ a recrawl interval when they come due.
+            _ = lens_refresh_interval.tick() => {
+                lens::check_lens_refreshes(&state).await;
+            }
+            // Let clients know how far along any in-progress crawls are.
+            _ = crawl_progress_interval.tick() => {
+                publish_crawl_progress(&state, &mut last_crawled).await;
+            }
             // If we're not handling anything, continually poll for jobs.
             _ = queue_check_interval.tick() => {
                 if let Err(err) = manager_cmd_tx.send(ManagerCommand::CheckForJobs) {
@@ -192,18 +224,26 @@ pub async fn embedding_task(state: AppState, queue: mpsc::Sender<WorkerCommand>)
     log::info!("Embedding Task Tracker Started");
 
     let mut queue_check_interval = tokio::time::interval(Duration::from_millis(500));
+    let mut progress_interval =
+        tokio::time::interval(Duration::from_secs(EMBEDDING_PROGRESS_INTERVAL_S));
     let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
 
+    // Completed count at the last progress report, used to derive a
+    // completion rate. None while the queue is idle.
+    let mut last_completed: Option<u64> = None;
+
     // first is always instant
     queue_check_interval.tick().await;
+    progress_interval.tick().await;
     loop {
         tokio::select! {
             // Listen for manager level commands. This can be sent internally (i.e. CheckForJobs) or
             // externally (e.g. Collect)
-            job = embedding_queue::check_for_embedding_jobs(&state.db) => {
-                match job {
-                    Ok(Some(job)) => {
-                        let _ = queue.send(WorkerCommand::Embedding{id: job.id}).await;
+            jobs = embedding_queue::check_for_embedding_jobs(&state.db, EMBEDDING_JOB_BATCH_SIZE) => {
+                match jobs {
+                    Ok(jobs) if !jobs.is_empty() => {
+                        let ids = jobs.iter().map(|job| job.id).collect::<Vec<i64>>();
+                        let _ = queue.send(WorkerCommand::Embedding { ids }).await;
                         queue_check_interval.tick().await;
                     }
                     Err(error) => {
@@ -215,6 +255,9 @@ pub async fn embedding_task(state: AppState, queue: mpsc::Sender<WorkerCommand>)
                     }
                 }
             }
+            _ = progress_interval.tick() => {
+                publish_embedding_progress(&state, &mut last_completed).await;
+            }
             _ = shutdown_rx.recv() => {
                 log::info!("🛑 Shutting down manager");
                 return;
@@ -223,6 +266,154 @@ pub async fn embedding_task(state: AppState, queue: mpsc::Sender<WorkerCommand>)
     }
 }
 
+/// Publishes how far along the background embedding work is. Only published
+/// while there's work in the queue (plus one final event when it drains so
+/// clients can clear any progress UI).
+async fn publish_embedding_progress(state: &AppState, last_completed: &mut Option<u64>) {
+    match embedding_queue::queue_counts(&state.db).await {
+        Ok(Some(counts)) => {
+            let remaining = counts.remaining.max(0) as u64;
+            let completed = counts.completed.max(0) as u64;
+
+            if remaining == 0 && last_completed.is_none() {
+                return;
+            }
+
+            let rate = last_completed.map(|last| {
+                completed.saturating_sub(last) as f32 / EMBEDDING_PROGRESS_INTERVAL_S as f32
+            });
+            // Reset between batches of work so the rate doesn't span idle
+            // time.
+            *last_completed = if remaining > 0 { Some(completed) } else { None };
+
+            state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::EmbeddingProgress,
+                    payload: Some(
+                        serde_json::to_value(&EmbeddingProgressPayload {
+                            remaining,
+                            completed,
+                            rate,
+                        })
+                        .unwrap_or_default(),
+                    ),
+                })
+                .await;
+        }
+        Ok(None) => {}
+        Err(error) => log::warn!("Error checking embedding queue counts {:?}", error),
+    }
+}
+
+/// Publishes per-lens crawl progress while any lens has queued work, plus a
+/// final `LensBootstrapFinished` event when a lens' queue drains so clients
+/// can finalize progress bars w/o polling `get_library_stats`.
+async fn publish_crawl_progress(state: &AppState, last_crawled: &mut HashMap<String, u64>) {
+    let stats = match get_library_stats(&state.db, 0).await {
+        Ok(stats) => stats,
+        Err(error) => {
+            log::warn!("Error checking crawl progress {:?}", error);
+            return;
+        }
+    };
+
+    // Report lenses whose queues drained since the last check before
+    // dropping them from tracking.
+    let drained = last_crawled
+        .keys()
+        .filter(|lens| {
+            stats
+                .get(*lens)
+                .map(|stat| stat.enqueued == 0)
+                .unwrap_or(true)
+        })
+        .cloned()
+        .collect::<Vec<String>>();
+
+    for lens in drained {
+        last_crawled.remove(&lens);
+        let indexed = stats
+            .get(&lens)
+            .map(|stat| stat.indexed.max(0) as u64)
+            .unwrap_or_default();
+
+        state
+            .publish_event(&RpcEvent {
+                event_type: RpcEventType::LensBootstrapFinished,
+                payload: Some(
+                    serde_json::to_value(&LensBootstrapFinishedPayload { lens, indexed })
+                        .unwrap_or_default(),
+                ),
+            })
+            .await;
+    }
+
+    for (lens, stat) in stats {
+        if stat.enqueued <= 0 {
+            continue;
+        }
+
+        let crawled = stat.crawled.max(0) as u64;
+        let total = stat.total_docs().max(0) as u64;
+        let rate = last_crawled
+            .get(&lens)
+            .map(|last| crawled.saturating_sub(*last) as f32 / CRAWL_PROGRESS_INTERVAL_S as f32);
+        last_crawled.insert(lens.clone(), crawled);
+
+        state
+            .publish_event(&RpcEvent {
+                event_type: RpcEventType::LensBootstrapProgress,
+                payload: Some(
+                    serde_json::to_value(&LensBootstrapProgressPayload {
+                        lens,
+                        crawled,
+                        total,
+                        rate,
+                    })
+                    .unwrap_or_default(),
+                ),
+            })
+            .await;
+    }
+}
+
+/// Publishes what a just-committed batch of crawls contained. Sent after the
+/// index save, so everything counted here is searchable.
+async fn publish_batch_events(
+    state: &AppState,
+    num_docs: u64,
+    since: chrono::DateTime<chrono::Utc>,
+) {
+    match crawl_queue::num_completed_by_lens(&state.db, since).await {
+        Ok(counts) => {
+            let lenses = counts
+                .into_iter()
+                .map(|entry| (entry.lens, entry.count.max(0) as u64))
+                .collect::<HashMap<String, u64>>();
+
+            state
+                .publish_event(&RpcEvent {
+                    event_type: RpcEventType::CrawlBatchCompleted,
+                    payload: Some(
+                        serde_json::to_value(&CrawlBatchCompletedPayload { lenses, num_docs })
+                            .unwrap_or_default(),
+                    ),
+                })
+                .await;
+        }
+        Err(error) => log::warn!("Error counting completed tasks {:?}", error),
+    }
+
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::IndexCommitted,
+            payload: Some(
+                serde_json::to_value(&IndexCommittedPayload { num_docs }).unwrap_or_default(),
+            ),
+        })
+        .await;
+}
+
 /// Manages changes to the user's settings
 #[tracing::instrument(skip_all)]
 pub async fn config_task(mut state: AppState) {
@@ -240,13 +431,21 @@ pub async fn config_task(mut state: AppState) {
 
                     if Config::save_user_settings(&new_settings).is_ok() {
                         state.reload_config();
+                        // Pick up any edits to the stop word overrides file.
+                        let stop_words = Config::load_stop_word_overrides();
+                        state.index.reload_stop_words(stop_words.as_deref());
+                        // ... & to the user's synonym groups.
+                        let synonyms = Config::load_synonyms();
+                        state.index.reload_synonyms(synonyms.as_deref());
                         let diff = new_settings.diff(&old_config);
                         // Process any new added paths
                         process_filesystem_changes(&state, &diff).await;
                         // Audio transcriptions enabled?
                         if new_settings.audio_settings.enable_audio_transcription {
-                            // Do we already have this model?
+                            // Do we already have this model? Corrupt files
+                            // are removed first so they get re-downloaded.
                             let model_dir = state.config.model_dir().join("whisper");
+                            let _ = model_files::remove_corrupt_model_files(&model_dir);
                             let model_path = model_dir.join("model.safetensors");
                             let tokenizer_path = model_dir.join("tokenizer.json");
                             let model_config_path = model_dir.join("config.json");
@@ -290,12 +489,74 @@ pub async fn config_task(mut state: AppState) {
                         }
 
                         if new_settings.embedding_settings.enable_embeddings {
-                            let model_dir = state.config.embedding_model_dir();
-                            let model_path = state.config.embedding_model_dir().join("model.safetensors");
-                            let tokenizer_path = state.config.embedding_model_dir().join("tokenizer.json");
-                            let model_config_path = state.config.embedding_model_dir().join("config.json");
+                            // Existing embeddings cover chunk boundaries (or
+                            // vector dimensions) that no longer match the
+                            // config; wipe them so `add_missing_embeddings`
+                            // requeues everything.
+                            if new_settings.embedding_settings.segmentation_changed(&old_config.embedding_settings)
+                                || new_settings.embedding_settings.dimensions_changed(&old_config.embedding_settings)
+                            {
+                                match vec_to_indexed::delete_all(&state.db).await {
+                                    Ok(purged) if purged > 0 => {
+                                        log::info!("Embedding config changed, wiped {} vectors for re-embed", purged);
+                                    }
+                                    Ok(_) => {}
+                                    Err(error) => log::error!("Error wiping embeddings {:?}", error),
+                                }
+                            }
+
+                            let model_dir = new_settings.embedding_settings.model_root(&state.config.embedding_model_dir());
+                            let _ = model_files::remove_corrupt_model_files(&model_dir);
+                            let model_path = model_dir.join("model.safetensors");
+                            let tokenizer_path = model_dir.join("tokenizer.json");
+                            let model_config_path = model_dir.join("config.json");
+                            if !model_path.exists() || !tokenizer_path.exists() || !model_config_path.exists() {
+                                if let Some((model_url, config_url, tokenizer_url)) = embedding_model_urls(&new_settings) {
+                                    log::debug!("Loading Embedding Models...");
+                                    let mut state_clone = state.clone();
+                                    let settings_clone = new_settings.clone();
+
+                                    if !model_dir.exists() {
+                                        let _ = std::fs::create_dir_all(model_dir);
+                                    }
+
+                                    tokio::spawn(async move {
+
+                                        if let Err(error) = download_model(&state_clone, "Embedding Model", model_path, &model_url).await {
+                                            log::error!("Error downloading Embedding model {:?}", error);
+                                        }
+                                        if let Err(error) = download_model(&state_clone, "Embedding Model Config", model_config_path, &config_url).await {
+                                            log::error!("Error downloading Embedding model config {:?}", error);
+                                        }
+                                        if let Err(error) = download_model(&state_clone, "Embedding Model Tokenizer", tokenizer_path, &tokenizer_url).await {
+                                            log::error!("Error downloading Embedding model tokenizer config {:?}", error);
+                                        }
+
+                                        state_clone.reload_model();
+
+                                        purge_stale_embeddings(&state_clone, &settings_clone).await;
+                                        add_missing_embeddings(&state_clone).await;
+                                    });
+                                } else {
+                                    // Local model directories aren't downloadable, the
+                                    // files have to already be there.
+                                    log::error!("Embedding model files missing from {}", model_dir.display());
+                                }
+                            } else {
+                                state.reload_model();
+                                purge_stale_embeddings(&state, &new_settings).await;
+                                add_missing_embeddings(&state).await;
+                            }
+                        }
+
+                        if new_settings.embedding_settings.enable_reranking {
+                            let model_dir = state.config.rerank_model_dir();
+                            let _ = model_files::remove_corrupt_model_files(&model_dir);
+                            let model_path = model_dir.join("model.safetensors");
+                            let tokenizer_path = model_dir.join("tokenizer.json");
+                            let model_config_path = model_dir.join("config.json");
                             if !model_path.exists() || !tokenizer_path.exists() || !model_config_path.exists() {
-                                log::debug!("Loading Embedding Models...");
+                                log::debug!("Loading Rerank Model...");
                                 let mut state_clone = state.clone();
 
                                 if !model_dir.exists() {
@@ -303,24 +564,20 @@ pub async fn config_task(mut state: AppState) {
                                 }
 
                                 tokio::spawn(async move {
-
-                                    if let Err(error) = download_model(&state_clone, "Embedding Model", model_path, shared::constants::EMBEDDING_MODEL).await {
-                                        log::error!("Error downloading Embedding model {:?}", error);
+                                    if let Err(error) = download_model(&state_clone, "Rerank Model", model_path, shared::constants::RERANK_MODEL).await {
+                                        log::error!("Error downloading Rerank model {:?}", error);
                                     }
-                                    if let Err(error) = download_model(&state_clone, "Embedding Model Config", model_config_path, shared::constants::EMBEDDING_MODEL_CONFIG).await {
-                                        log::error!("Error downloading Embedding model config {:?}", error);
+                                    if let Err(error) = download_model(&state_clone, "Rerank Model Config", model_config_path, shared::constants::RERANK_MODEL_CONFIG).await {
+                                        log::error!("Error downloading Rerank model config {:?}", error);
                                     }
-                                    if let Err(error) = download_model(&state_clone, "Embedding Model Tokenizer", tokenizer_path, shared::constants::EMBEDDING_MODEL_TOKENIZER).await {
-                                        log::error!("Error downloading Embedding model tokenizer config {:?}", error);
+                                    if let Err(error) = download_model(&state_clone, "Rerank Model Tokenizer", tokenizer_path, shared::constants::RERANK_MODEL_TOKENIZER).await {
+                                        log::error!("Error downloading Rerank model tokenizer {:?}", error);
                                     }
 
                                     state_clone.reload_model();
-
-                                    add_missing_embeddings(&state_clone).await;
                                 });
                             } else {
                                 state.reload_model();
-                                add_missing_embeddings(&state).await;
                             }
                         }
                     }
@@ -334,6 +591,45 @@ pub async fn config_task(mut state: AppState) {
     }
 }
 
+/// Download URLs for the configured embedding model. The default model &
+/// any named model are pulled from HuggingFace; local model directories
+/// return None, there's nothing to download.
+fn embedding_model_urls(settings: &UserSettings) -> Option<(String, String, String)> {
+    match &settings.embedding_settings.embedding_model {
+        None => Some((
+            shared::constants::EMBEDDING_MODEL.into(),
+            shared::constants::EMBEDDING_MODEL_CONFIG.into(),
+            shared::constants::EMBEDDING_MODEL_TOKENIZER.into(),
+        )),
+        Some(model) if std::path::Path::new(model).is_dir() => None,
+        Some(model) => Some((
+            format!("https://huggingface.co/{model}/resolve/main/model.safetensors"),
+            format!("https://huggingface.co/{model}/resolve/main/config.json"),
+            format!("https://huggingface.co/{model}/resolve/main/tokenizer.json"),
+        )),
+    }
+}
+
+/// Purges vectors generated by a different embedding model & leaves the
+/// affected documents w/o embeddings so `add_missing_embeddings` requeues
+/// them. Embeddings from different models can't be meaningfully compared,
+/// so the vector table only ever holds vectors from the active model.
+async fn purge_stale_embeddings(state: &AppState, settings: &UserSettings) {
+    let model_id = settings.embedding_settings.model_id();
+    // Rows from before model ids were tracked came from the default model.
+    let allow_untracked = settings.embedding_settings.embedding_model.is_none();
+    match vec_to_indexed::delete_all_for_other_models(&state.db, &model_id, allow_untracked).await {
+        Ok(purged) if purged > 0 => {
+            log::info!(
+                "Purged {} vectors from previous embedding models, queueing re-embed",
+                purged
+            );
+        }
+        Ok(_) => {}
+        Err(error) => log::error!("Error purging stale embeddings {:?}", error),
+    }
+}
+
 async fn add_missing_embeddings(state: &AppState) {
     match indexed_document::get_documents_missing_embeddings(&state.db).await {
         Ok(missing_embeddings) => {
@@ -377,21 +673,76 @@ async fn add_missing_embeddings(state: &AppState) {
 }
 
 /// Downloads a model from our assets S3 bucket
-async fn download_model(
+/// Expected sha256 for a HuggingFace-hosted file, read from the repo's LFS
+/// pointer file (`/raw/` serves `oid sha256:<hash>` for LFS files). Returns
+/// None for non-HF URLs & small files that aren't stored in LFS.
+async fn fetch_expected_sha256(model_url: &str) -> Option<String> {
+    if !model_url.starts_with("https://huggingface.co/") {
+        return None;
+    }
+
+    let pointer_url = model_url.replace("/resolve/", "/raw/");
+    if pointer_url == model_url {
+        return None;
+    }
+
+    let body = reqwest::get(&pointer_url).await.ok()?.text().await.ok()?;
+    body.lines().find_map(|line| {
+        line.strip_prefix("oid sha256:")
+            .map(|hash| hash.trim().to_string())
+    })
+}
+
+/// Downloads a model file, resuming a previous partial download via a range
+/// request when possible. The file is verified against the repo's published
+/// sha256 & only renamed into place once the checksum matches, so a flaky
+/// connection never leaves a corrupt model where the backend would load it.
+/// Progress is reported via `RpcEventType::ModelDownloadStatus` events.
+pub async fn download_model(
     state: &AppState,
     model_name: &str,
     model_path: PathBuf,
     model_url: &str,
 ) -> anyhow::Result<()> {
-    match reqwest::get(model_url).await {
+    let expected_sha = fetch_expected_sha256(model_url).await;
+    let part_path = model_files::partial_path(&model_path);
+    let existing = std::fs::metadata(&part_path)
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+
+    let client = reqwest::Client::new();
+    let mut request = client.get(model_url);
+    if existing > 0 {
+        log::debug!("Resuming {} download from byte {}", model_name, existing);
+        request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+    }
+
+    match request.send().await {
         Ok(res) => {
-            let total_size = res.content_length().expect("Unable to get content length");
-            let mut file = File::create(model_path).or(Err(anyhow!("Failed to create file")))?;
+            // A server that doesn't honor the range request sends the whole
+            // file back, start over in that case.
+            let resuming = existing > 0 && res.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+            let total_size = if resuming {
+                existing + res.content_length().unwrap_or(0)
+            } else {
+                res.content_length().unwrap_or(0)
+            };
 
-            let mut downloaded: u64 = 0;
+            let mut file = if resuming {
+                std::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&part_path)
+                    .or(Err(anyhow!("Failed to open partial download")))?
+            } else {
+                File::create(&part_path).or(Err(anyhow!("Failed to create file")))?
+            };
+
+            let mut downloaded: u64 = if resuming { existing } else { 0 };
             let mut stream = res.bytes_stream();
 
-            // Download model in chunks, writing to model path.
+            // Download model in chunks, writing to the partial file. An error
+            // here leaves the partial file behind so the next attempt resumes
+            // instead of starting from zero.
 
             // Set the last update to some time in the past so we immediately send an update
             let mut last_update = std::time::Instant::now() - std::time::Duration::from_secs(100);
@@ -404,7 +755,7 @@ async fn download_model(
                 downloaded = new;
 
                 // Send an update to client every ~10 secs
-                if last_update.elapsed().as_secs() > 10 {
+                if last_update.elapsed().as_secs() > 10 && total_size > 0 {
                     let percent = ((downloaded as f32 / total_size as f32) * 100f32) as u8;
                     state
                         .publish_event(&RpcEvent {
@@ -422,6 +773,32 @@ async fn download_model(
                 }
             }
 
+            // Verify before swapping the file into place. A mismatch throws
+            // the partial file away, it can't be trusted as a resume point.
+            let actual = model_files::sha256_of_file(&part_path)?;
+            if let Some(expected) = &expected_sha {
+                if &actual != expected {
+                    let _ = std::fs::remove_file(&part_path);
+                    state
+                        .publish_event(&RpcEvent {
+                            event_type: RpcEventType::ModelDownloadStatus,
+                            payload: Some(
+                                serde_json::to_value(&ModelDownloadStatusPayload::Error {
+                                    model_name: model_name.into(),
+                                    msg: "Checksum verification failed".into(),
+                                })
+                                .unwrap(),
+                            ),
+                        })
+                        .await;
+                    return Err(anyhow!("Checksum mismatch for {}", model_name));
+                }
+            }
+
+            let _ = std::fs::write(model_files::checksum_path(&model_path), &actual);
+            std::fs::rename(&part_path, &model_path)
+                .or(Err(anyhow!("Failed to move model into place")))?;
+
             // finished download!
             state
                 .publish_event(&RpcEvent {
@@ -477,6 +854,8 @@ pub async fn worker_task(
     log::info!("worker started");
     let mut is_paused = false;
     let updated_docs: Arc<AtomicI32> = Arc::new(AtomicI32::new(0i32));
+    // When the current batch of updates started, i.e. the last commit.
+    let mut batch_started_at = chrono::Utc::now();
     let mut shutdown_rx = state.shutdown_cmd_tx.lock().await.subscribe();
 
     loop {
@@ -571,8 +950,11 @@ pub async fn worker_task(
                             if num_updated > 0 {
                                 log::debug!("committing {} new/updated docs in index", num_updated);
                                 updated_docs.store(0, Ordering::Relaxed);
+                                let since = batch_started_at;
+                                batch_started_at = chrono::Utc::now();
                                 tokio::spawn(async move {
                                     let _ = state.index.save().await;
+                                    publish_batch_events(&state, num_updated as u64, since).await;
                                 });
                             }
                         }
@@ -611,8 +993,8 @@ pub async fn worker_task(
                             });
                         }
                         WorkerCommand::Tag => {},
-                        WorkerCommand::Embedding { id } => {
-                            embeddings::trigger_processing_embedding(&state, id).await;
+                        WorkerCommand::Embedding { ids } => {
+                            embeddings::trigger_processing_embedding_batch(&state, ids).await;
                         },
 
                     }
@@ -705,15 +1087,34 @@ pub async fn lens_watcher(
                 Ok(event) => {
                     let mut updated_lens = false;
                     for path in &event.paths {
-                        if path.extension().unwrap_or_default() == "ron" {
+                        if path.extension().unwrap_or_default() == "ron" && path.exists() {
                             // Make sure it's a valid lens file before reloading
-                            let updated_lens_config = std::fs::read_to_string(path)
-                                .map(|s| ron::from_str::<LensConfig>(&s));
+                            let check = lens::check_lens_file(path);
+                            for warning in &check.warnings {
+                                log::warn!("lens file {}: {}", path.display(), warning);
+                            }
 
-                            if let Ok(Ok(lens_config)) = updated_lens_config {
+                            if !check.errors.is_empty() {
+                                // Surface the broken lens to clients instead
+                                // of silently skipping it.
+                                log::warn!(
+                                    "lens file {} failed validation: {}",
+                                    path.display(),
+                                    check.errors.join("; ")
+                                );
+                                state
+                                    .publish_event(&RpcEvent {
+                                        event_type: RpcEventType::LensLoadError,
+                                        payload: serde_json::to_value(LensLoadErrorPayload {
+                                            path: path.display().to_string(),
+                                            errors: check.errors.clone(),
+                                        })
+                                        .ok(),
+                                    })
+                                    .await;
+                            } else if let Some(name) = &check.name {
                                 // remove from bootstrap queue so the config is rechecked.
-                                let _ =
-                                    bootstrap_queue::dequeue(&state.db, &lens_config.name).await;
+                                let _ = bootstrap_queue::dequeue(&state.db, name).await;
                                 updated_lens = true;
                             }
                         }
@@ -1,20 +1,49 @@
 use dashmap::DashMap;
-use entities::models::lens;
-use shared::response::InstallableLens;
+use entities::models::{crawl_queue, indexed_document, lens};
+use entities::sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use shared::response::{InstallableLens, LensUpdateDiff};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use url::Url;
 
 use crate::{
+    filesystem,
     state::AppState,
     task::{CollectTask, ManagerCommand},
 };
 use reqwest::Client;
 use shared::config::{Config, LensConfig, LensSource};
 use shared::constants;
+use spyglass_searcher::WriteTrait;
 
 /// Loop through lenses in the AppState. Update our internal db & bootstrap anything
 /// that hasn't been bootstrapped.
 pub async fn load_lenses(lens_map: &DashMap<String, LensConfig>, state: AppState) {
+    // Report trigger aliases claimed by more than one lens. Sharing a trigger
+    // is allowed (it merges the lenses under one label) but is usually a
+    // copy-paste mistake, so surface it.
+    let mut alias_owner: HashMap<String, String> = HashMap::new();
+    for entry in lens_map.iter() {
+        let lens = entry.value();
+        for alias in lens.trigger.aliases() {
+            match alias_owner.get(&alias) {
+                Some(owner) if owner != &lens.name => {
+                    log::warn!(
+                        "trigger \"{}\" is used by both \"{}\" & \"{}\"",
+                        alias,
+                        owner,
+                        lens.name
+                    );
+                }
+                Some(_) => {}
+                None => {
+                    alias_owner.insert(alias, lens.name.clone());
+                }
+            }
+        }
+    }
+
     let mut new_lenses: Vec<LensConfig> = Vec::new();
     for entry in lens_map.iter() {
         let mut lens = entry.value().clone();
@@ -31,9 +60,23 @@ pub async fn load_lenses(lens_map: &DashMap<String, LensConfig>, state: AppState
                         lens.lens_source = LensSource::Local;
                     }
                 }
+                // Respect the user's enable/disable toggle. Disabled lenses
+                // stay in the lens map so searches & the library UI know
+                // about them, but aren't watched or bootstrapped until
+                // they're re-enabled.
+                lens.is_enabled = !model.user_disabled;
+
+                if lens.is_enabled {
+                    // Watch any local folder trees this lens declares. Already
+                    // watched folders are skipped, so this is safe on reload.
+                    filesystem::watch_lens_folders(&state, &lens).await;
+                }
+
                 if is_new {
                     state.lenses.insert(lens.name.to_owned(), lens.clone());
-                    new_lenses.push(lens);
+                    if lens.is_enabled {
+                        new_lenses.push(lens);
+                    }
                 } else if !state.lenses.contains_key(&lens.name) {
                     state.lenses.insert(lens.name.to_owned(), lens.clone());
                 }
@@ -57,6 +100,72 @@ pub async fn load_lenses(lens_map: &DashMap<String, LensConfig>, state: AppState
     log::info!("✅ finished lens checks")
 }
 
+/// Checks each lens w/ a `recrawl_interval_hours` & refreshes any whose
+/// interval has elapsed. A lens seen w/ a schedule for the first time starts
+/// its timer w/o an immediate recrawl.
+pub async fn check_lens_refreshes(state: &AppState) {
+    // Collect first, refresh after; holding a DashMap ref across the await
+    // points below invites a deadlock.
+    let scheduled = state
+        .lenses
+        .iter()
+        .filter_map(|entry| {
+            let lens = entry.value();
+            match lens.recrawl_interval_hours {
+                Some(hours) if hours > 0 && lens.is_enabled => Some((entry.key().clone(), hours)),
+                _ => None,
+            }
+        })
+        .collect::<Vec<(String, u32)>>();
+
+    let now = chrono::Utc::now();
+    for (name, hours) in scheduled {
+        let model = match lens::find_by_name(&name, &state.db).await {
+            Ok(Some(model)) => model,
+            _ => continue,
+        };
+
+        match model.next_refresh {
+            None => {
+                let next = now + chrono::Duration::hours(hours as i64);
+                if let Err(err) = lens::update_refresh_schedule(
+                    &name,
+                    model.last_refreshed,
+                    Some(next),
+                    &state.db,
+                )
+                .await
+                {
+                    log::warn!("Unable to schedule refresh for lens {}: {}", name, err);
+                }
+            }
+            Some(next) if next <= now => {
+                if let Err(err) = refresh_lens(state, &name).await {
+                    log::warn!("Unable to refresh lens {}: {}", name, err);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Requeues every crawl entry tagged w/ the lens & resets its refresh timer.
+/// Returns the number of entries queued for a recrawl.
+pub async fn refresh_lens(state: &AppState, name: &str) -> anyhow::Result<u64> {
+    let requeued = crawl_queue::mark_recrawl_by_lens(&state.db, name).await?;
+    log::info!("refreshing lens {}: requeued {} tasks", name, requeued);
+
+    let now = chrono::Utc::now();
+    let next = state.lenses.get(name).and_then(|entry| {
+        entry
+            .recrawl_interval_hours
+            .map(|hours| now + chrono::Duration::hours(hours as i64))
+    });
+    lens::update_refresh_schedule(name, Some(now), next, &state.db).await?;
+
+    Ok(requeued)
+}
+
 /// Installs a new lens or updates the current lens. The requested lens will be
 /// downloaded from the lens store and added to the database. The actually lens
 /// loading will happen through the normal file system watch mechanism.
@@ -128,6 +237,18 @@ async fn install_lens_to_path(
     // File name should match lens name for consistency
     let file_name = format!("{}.ron", config.name);
 
+    // When this is an update, clean up documents & crawls for any domains the
+    // new version dropped. Added entries are picked up by the normal
+    // bootstrap when the lens reloads.
+    let installed = state
+        .lenses
+        .get(&config.name)
+        .map(|entry| entry.value().clone());
+    if let Some(installed) = installed {
+        let diff = diff_lens_update(&installed, &config);
+        cleanup_removed_domains(state, &diff).await;
+    }
+
     // Add to database
     let (is_new, model) = lens::install_or_update(
         &state.db,
@@ -162,25 +283,335 @@ async fn install_lens_to_path(
     Ok(())
 }
 
+/// Fetches the latest version of an installed lens from the lens directory &
+/// diffs it against the installed version w/o applying anything. Errors if
+/// the lens isn't installed, isn't in the directory or is already up to date.
+pub async fn check_lens_update(
+    app_state: &AppState,
+    lens_name: &str,
+) -> anyhow::Result<LensUpdateDiff> {
+    let installed = match app_state.lenses.get(lens_name) {
+        Some(entry) => entry.value().clone(),
+        None => anyhow::bail!("lens {} is not installed", lens_name),
+    };
+
+    let client = reqwest::Client::builder()
+        .user_agent(constants::APP_USER_AGENT)
+        .build()
+        .expect("Unable to create reqwest client");
+
+    let resp = client
+        .get(constants::LENS_DIRECTORY_INDEX_URL)
+        .send()
+        .await?;
+    let available_lens = ron::from_str::<Vec<InstallableLens>>(&resp.text().await?)?;
+
+    let installable = match available_lens.iter().find(|entry| entry.name.eq(lens_name)) {
+        Some(installable) => installable,
+        None => anyhow::bail!("lens {} is not in the lens directory", lens_name),
+    };
+
+    if installable.sha == installed.hash {
+        anyhow::bail!("lens {} is already up to date", lens_name);
+    }
+
+    let resp = client
+        .get(installable.download_url.as_str())
+        .send()
+        .await?;
+    let update = LensConfig::from_string(&resp.text().await?)?;
+
+    Ok(diff_lens_update(&installed, &update))
+}
+
+/// Computes a changelog-style diff between the installed version of a lens &
+/// an update: which domains, urls & rules were added or removed.
+pub fn diff_lens_update(installed: &LensConfig, update: &LensConfig) -> LensUpdateDiff {
+    let (added_domains, removed_domains) = diff_list(&installed.domains, &update.domains);
+    let (added_urls, removed_urls) = diff_list(&installed.urls, &update.urls);
+
+    let installed_rules = installed
+        .rules
+        .iter()
+        .map(|rule| rule.to_string())
+        .collect::<Vec<String>>();
+    let update_rules = update
+        .rules
+        .iter()
+        .map(|rule| rule.to_string())
+        .collect::<Vec<String>>();
+    let (added_rules, removed_rules) = diff_list(&installed_rules, &update_rules);
+
+    LensUpdateDiff {
+        name: installed.name.clone(),
+        old_version: installed.version.clone(),
+        new_version: update.version.clone(),
+        added_domains,
+        removed_domains,
+        added_urls,
+        removed_urls,
+        added_rules,
+        removed_rules,
+    }
+}
+
+/// Entries in `new` but not in `old` & vice versa.
+fn diff_list(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = new
+        .iter()
+        .filter(|item| !old.contains(item))
+        .cloned()
+        .collect::<Vec<String>>();
+    let removed = old
+        .iter()
+        .filter(|item| !new.contains(item))
+        .cloned()
+        .collect::<Vec<String>>();
+    (added, removed)
+}
+
+/// Removes crawls & indexed documents for domains dropped by a lens update,
+/// mirroring the `delete_domain` cleanup. Nothing is requeued since the
+/// domain is no longer part of the lens.
+async fn cleanup_removed_domains(state: &AppState, diff: &LensUpdateDiff) {
+    for domain in &diff.removed_domains {
+        let res = crawl_queue::Entity::delete_many()
+            .filter(crawl_queue::Column::Domain.eq(domain.clone()))
+            .exec(&state.db)
+            .await;
+        if let Ok(res) = res {
+            log::info!(
+                "lens {} dropped {}: removed {} crawls",
+                diff.name,
+                domain,
+                res.rows_affected
+            );
+        }
+
+        let indexed = indexed_document::Entity::find()
+            .filter(indexed_document::Column::Domain.eq(domain.clone()))
+            .all(&state.db)
+            .await
+            .unwrap_or_default();
+        if !indexed.is_empty() {
+            let doc_ids = indexed
+                .iter()
+                .map(|doc| doc.doc_id.to_string())
+                .collect::<Vec<String>>();
+            let _ = state.index.delete_many_by_id(&doc_ids).await;
+            let _ = indexed_document::delete_many_by_doc_id(&state.db, &doc_ids).await;
+            log::info!(
+                "lens {} dropped {}: removed {} docs",
+                diff.name,
+                domain,
+                doc_ids.len()
+            );
+        }
+    }
+}
+
 /// Reads lens directly from disk and provides the map lenses
 pub async fn read_lenses(config: &Config) -> anyhow::Result<DashMap<String, LensConfig>> {
     let lens_map = DashMap::new();
     let lense_dir = config.lenses_dir();
 
+    // Load everything first so `extends` references can be resolved against
+    // the other lenses in the directory.
     // Keep track of failures and report to user?
+    let mut loaded: HashMap<String, LensConfig> = HashMap::new();
     for entry in (fs::read_dir(lense_dir)?).flatten() {
         let path = entry.path();
         if path.is_file() && path.extension().unwrap_or_default() == "ron" {
             match LensConfig::from_path(path) {
                 Err(err) => log::warn!("Unable to load lens {:?}: {}", entry.path(), err),
                 Ok(lens) => {
-                    if lens.is_enabled {
-                        lens_map.insert(lens.name.clone(), lens);
-                    }
+                    loaded.insert(lens.name.clone(), lens);
                 }
             }
         }
     }
 
+    for lens in loaded.values() {
+        if !lens.is_enabled {
+            continue;
+        }
+
+        match lens.resolve_extends(&loaded) {
+            Ok(mut merged) => {
+                expand_lens_folders(&mut merged);
+                lens_map.insert(merged.name.clone(), merged);
+            }
+            Err(err) => log::warn!("Unable to load lens {}: {}", lens.name, err),
+        }
+    }
+
     Ok(lens_map)
 }
+
+/// Expands `~` in lens folder paths & drops anything that doesn't point at a
+/// directory, so the watcher only ever sees valid paths.
+fn expand_lens_folders(lens: &mut LensConfig) {
+    lens.folders = lens
+        .folders
+        .iter()
+        .filter_map(|folder| {
+            let expanded = filesystem::utils::expand_tilde(folder);
+            if expanded.is_dir() {
+                Some(expanded)
+            } else {
+                log::warn!(
+                    "lens {} folder {:?} is not a directory, ignoring",
+                    lens.name,
+                    folder
+                );
+                None
+            }
+        })
+        .collect::<Vec<PathBuf>>();
+}
+
+/// Fields a lens file is allowed to declare; anything else is silently
+/// ignored at load time, which usually means a typo'd field name.
+const KNOWN_LENS_FIELDS: [&str; 20] = [
+    "author",
+    "categories",
+    "content_selector",
+    "description",
+    "domains",
+    "exclude_selectors",
+    "extends",
+    "folders",
+    "is_enabled",
+    "label",
+    "lens_source",
+    "name",
+    "pipeline",
+    "recrawl_interval_hours",
+    "rules",
+    "tags",
+    "trigger",
+    "url_pattern",
+    "urls",
+    "version",
+];
+
+/// Outcome of validating a single lens file.
+pub struct LensCheck {
+    /// Lens name, when the file parsed far enough to have one.
+    pub name: Option<String>,
+    /// Problems that stop the lens from loading correctly.
+    pub errors: Vec<String>,
+    /// Suspicious but non-fatal issues, e.g. unknown fields.
+    pub warnings: Vec<String>,
+}
+
+/// Validates a lens file beyond what deserialization catches: syntax errors
+/// are reported w/ their line & column, rule regexes are compiled, urls &
+/// domains are sanity checked and unknown fields are flagged.
+pub fn check_lens_file(path: &Path) -> LensCheck {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            return LensCheck {
+                name: None,
+                errors: vec![format!("unable to read file: {err}")],
+                warnings,
+            }
+        }
+    };
+
+    let lens = match ron::from_str::<LensConfig>(&contents) {
+        Ok(lens) => Some(lens),
+        Err(err) => {
+            errors.push(format!(
+                "syntax error at line {}, column {}: {}",
+                err.position.line, err.position.col, err.code
+            ));
+            None
+        }
+    };
+
+    if let Some(lens) = &lens {
+        if let Err(err) = lens.validate() {
+            errors.push(err.to_string());
+        }
+
+        for url in &lens.urls {
+            // A trailing `$` marks an exact-match url, not part of the url
+            // itself.
+            if Url::parse(url.trim_end_matches('$')).is_err() {
+                errors.push(format!("invalid url: {url}"));
+            }
+        }
+
+        for domain in &lens.domains {
+            if domain.contains("://") || domain.contains('/') {
+                errors.push(format!(
+                    "domains should be bare hostnames, not urls: {domain}"
+                ));
+            }
+        }
+
+        for folder in &lens.folders {
+            let expanded = filesystem::utils::expand_tilde(folder);
+            if !expanded.is_dir() {
+                errors.push(format!("folder is not a directory: {}", folder.display()));
+            }
+        }
+    }
+
+    // Unknown fields don't fail the load, but flag them so typos aren't
+    // silently ignored.
+    if let Ok(ron::Value::Map(map)) = ron::from_str::<ron::Value>(&contents) {
+        for (key, _) in map.iter() {
+            if let ron::Value::String(key) = key {
+                if !KNOWN_LENS_FIELDS.contains(&key.as_str()) {
+                    warnings.push(format!("unknown field `{key}` is ignored"));
+                }
+            }
+        }
+    }
+
+    LensCheck {
+        name: lens.map(|lens| lens.name),
+        errors,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::diff_lens_update;
+    use shared::config::{LensConfig, LensRule};
+
+    #[test]
+    fn test_diff_lens_update() {
+        let installed = LensConfig {
+            name: "test".into(),
+            version: "1".into(),
+            domains: vec!["example.com".into(), "dropped.com".into()],
+            urls: vec!["https://docs.example.com/".into()],
+            ..Default::default()
+        };
+        let update = LensConfig {
+            name: "test".into(),
+            version: "2".into(),
+            domains: vec!["example.com".into(), "added.com".into()],
+            urls: vec!["https://docs.example.com/".into()],
+            rules: vec![LensRule::SkipURL("*://example.com/login*".into())],
+            ..Default::default()
+        };
+
+        let diff = diff_lens_update(&installed, &update);
+        assert_eq!(diff.old_version, "1");
+        assert_eq!(diff.new_version, "2");
+        assert_eq!(diff.added_domains, vec!["added.com"]);
+        assert_eq!(diff.removed_domains, vec!["dropped.com"]);
+        assert!(diff.added_urls.is_empty() && diff.removed_urls.is_empty());
+        assert_eq!(diff.added_rules, vec![r#"SkipURL("*://example.com/login*")"#]);
+        assert!(diff.has_changes());
+    }
+}
@@ -1,12 +1,13 @@
 use entities::models::crawl_queue::EnqueueSettings;
 
 use entities::models::{
-    bootstrap_queue, crawl_queue, crawl_tag, indexed_document,
+    bootstrap_queue, crawl_queue, crawl_tag, embedding_queue, indexed_document,
     tag::{self, TagPair},
 };
 use entities::sea_orm::prelude::*;
 use entities::sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Set};
 use shared::config::{Config, LensConfig, LensSource};
+use spyglass_rpc::{LensBootstrapStartedPayload, RpcEvent, RpcEventType, TaskProgressPayload};
 use spyglass_searcher::{SearchTrait, WriteTrait};
 
 use super::{bootstrap, CollectTask, ManagerCommand};
@@ -24,6 +25,20 @@ use crate::{
 #[tracing::instrument(skip(state, config, lens))]
 pub async fn handle_bootstrap_lens(state: &AppState, config: &Config, lens: &LensConfig) {
     log::debug!("Bootstrapping Lens {:?}", lens);
+    // Let clients know a bootstrap kicked off; the manager follows up w/
+    // per-lens progress while the lens' queue has work.
+    state
+        .publish_event(&RpcEvent {
+            event_type: RpcEventType::LensBootstrapStarted,
+            payload: Some(
+                serde_json::to_value(&LensBootstrapStartedPayload {
+                    lens: lens.name.clone(),
+                })
+                .unwrap_or_default(),
+            ),
+        })
+        .await;
+
     match &lens.lens_source {
         LensSource::Remote(_) => {
             if !(bootstrap::bootstrap_lens_cache(state, config, lens).await) {
@@ -175,7 +190,7 @@ pub async fn process_crawl(
         };
     }
 
-    let task_tags = task
+    let mut task_tags = task
         .find_related(tag::Entity)
         .all(&state.db)
         .await
@@ -184,6 +199,17 @@ pub async fn process_crawl(
         .map(|t| t.tag_pair())
         .collect::<Vec<TagPair>>();
 
+    // Resolve tag templates (e.g. `("org", "$1")`) against the crawled url
+    // for any lens w/ a `url_pattern`.
+    for entry in state.lenses.iter() {
+        for (label, value) in entry.value().template_tags_for_url(&crawl_result.url) {
+            let pair = (tag::TagType::string_to_tag_type(&label), value);
+            if !task_tags.contains(&pair) {
+                task_tags.push(pair);
+            }
+        }
+    }
+
     // Add all valid, non-duplicate, non-indexed links found to crawl queue
     let to_enqueue: Vec<String> = crawl_result.links.clone().into_iter().collect();
 
@@ -191,7 +217,7 @@ pub async fn process_crawl(
     let lenses: Vec<LensConfig> = state
         .lenses
         .iter()
-        .filter(|entry| entry.value().pipeline.is_none())
+        .filter(|entry| entry.value().is_enabled && entry.value().pipeline.is_none())
         .map(|entry| entry.value().clone())
         .collect();
 
@@ -218,6 +244,32 @@ pub async fn process_crawl(
 
     match process_crawl_results(state, &[crawl_result.clone()], &task_tags).await {
         Ok(res) => {
+            // For user-initiated tasks, carry the attribution over to any
+            // embedding work & let the client know their request progressed.
+            if let Some(request_id) = &task.initiated_by {
+                if let Ok(Some(doc)) = indexed_document::Entity::find()
+                    .filter(indexed_document::Column::Url.eq(crawl_result.url.clone()))
+                    .one(&state.db)
+                    .await
+                {
+                    embedding_queue::mark_initiated_by(&state.db, &doc.doc_id, request_id).await;
+                }
+
+                state
+                    .publish_event(&RpcEvent {
+                        event_type: RpcEventType::TaskProgress,
+                        payload: Some(
+                            serde_json::to_value(&TaskProgressPayload {
+                                request_id: Some(request_id.clone()),
+                                url: crawl_result.url.clone(),
+                                status: "Completed".into(),
+                            })
+                            .unwrap_or_default(),
+                        ),
+                    })
+                    .await;
+            }
+
             if res.num_updated > 0 {
                 Ok(FetchResult::Updated)
             } else {
@@ -256,6 +308,12 @@ pub async fn handle_fetch(state: AppState, task: CrawlTask) -> FetchResult {
                     let _ = crawl_queue::mark_done(&state.db, task.id, None).await;
                     FetchResult::NotFound
                 }
+                // Server told us to slow down, requeue w/ a not-before
+                // timestamp & cool down the domain.
+                CrawlError::RateLimited { retry_after_ms } => {
+                    crawl_queue::mark_rate_limited(&state.db, task.id, retry_after_ms).await;
+                    FetchResult::Ignore
+                }
                 // Retry timeouts, might be a network issue
                 CrawlError::Timeout => {
                     log::info!("Retrying task {} if possible", task.id);
@@ -192,7 +192,11 @@ pub fn search_result_component(props: &SearchResultProps) -> Html {
                 <div class="text-xs text-cyan-500">{domain}</div>
                 <h2 class={title_classes}>{title}</h2>
                 <div class="text-sm leading-relaxed text-neutral-400 max-h-10 overflow-hidden">
-                    {Html::from_html_unchecked(result.description.clone().into())}
+                    {if result.snippet.is_empty() {
+                        Html::from_html_unchecked(result.description.clone().into())
+                    } else {
+                        render_snippet(&result.snippet, &result.highlights)
+                    }}
                 </div>
                 {metadata}
                 <div class="text-neutral-600 text-xs pt-1">{result.score}</div>
@@ -240,6 +244,26 @@ pub fn lens_result_component(props: &LensResultProps) -> Html {
     }
 }
 
+/// Render a snippet, bolding each of the highlighted byte ranges.
+fn render_snippet(snippet: &str, highlights: &[(usize, usize)]) -> Html {
+    let mut pieces: Vec<Html> = Vec::new();
+    let mut cursor = 0;
+    for (start, end) in highlights {
+        if let (Some(before), Some(hit)) = (snippet.get(cursor..*start), snippet.get(*start..*end))
+        {
+            pieces.push(html! { <span>{before.to_string()}</span> });
+            pieces.push(html! { <b class="font-bold text-white">{hit.to_string()}</b> });
+            cursor = *end;
+        }
+    }
+
+    if let Some(rest) = snippet.get(cursor..) {
+        pieces.push(html! { <span>{rest.to_string()}</span> });
+    }
+
+    html! { <span>{pieces}</span> }
+}
+
 fn shorten_file_path(url: &Url, max_segments: usize, show_file_name: bool) -> Option<String> {
     if url.scheme() == "file" {
         // Attempt to grab the folder this file resides
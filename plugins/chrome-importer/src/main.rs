@@ -0,0 +1,385 @@
+use serde_json::Value;
+use spyglass_plugin::*;
+use std::path::{Path, PathBuf};
+
+/// Chrome timestamps count microseconds from 1601-01-01; this shifts them
+/// onto the unix epoch.
+const CHROME_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+/// Visit count cutoff used when `VISIT_COUNT_THRESHOLD` isn't set.
+const DEFAULT_VISIT_THRESHOLD: i64 = 3;
+
+#[derive(Default)]
+struct Plugin;
+
+register_plugin!(Plugin);
+
+/// Chromium-variant browsers we know how to find:
+/// (name, linux data root, macos data root, windows data root). The name is
+/// what shows up in the `browser` tag & what the `CHROMIUM_BROWSERS` setting
+/// matches against. Roots are relative to the host home directory.
+const BROWSERS: &[(&str, &str, &str, &str)] = &[
+    (
+        "chrome",
+        ".config/google-chrome",
+        "Library/Application Support/Google/Chrome",
+        "AppData/Local/Google/Chrome/User Data",
+    ),
+    (
+        "brave",
+        ".config/BraveSoftware/Brave-Browser",
+        "Library/Application Support/BraveSoftware/Brave-Browser",
+        "AppData/Local/BraveSoftware/Brave-Browser/User Data",
+    ),
+    (
+        "edge",
+        ".config/microsoft-edge",
+        "Library/Application Support/Microsoft Edge",
+        "AppData/Local/Microsoft/Edge/User Data",
+    ),
+    (
+        "chromium",
+        ".config/chromium",
+        "Library/Application Support/Chromium",
+        "AppData/Local/Chromium/User Data",
+    ),
+    (
+        "vivaldi",
+        ".config/vivaldi",
+        "Library/Application Support/Vivaldi",
+        "AppData/Local/Vivaldi/User Data",
+    ),
+];
+
+/// Profiles checked when `CHROME_PROFILES` isn't set. The wasm sandbox can't
+/// list host directories, so we ask the host to sync each candidate & let
+/// the ones that don't exist fail quietly.
+const DEFAULT_PROFILES: &[&str] = &[
+    "Default",
+    "Profile 1",
+    "Profile 2",
+    "Profile 3",
+    "Profile 4",
+    "Profile 5",
+];
+
+impl SpyglassPlugin for Plugin {
+    fn load(&mut self) {
+        // Sync often enough to pick up recent browsing; the watermarks keep
+        // each run cheap.
+        let _ = subscribe_for_updates_every(5 * 60);
+        // Kick off the first sync so there's something to process on the
+        // first interval tick.
+        request_syncs();
+    }
+
+    fn update(&mut self, event: PluginEvent) {
+        match event {
+            PluginEvent::IntervalUpdate => {
+                // Process whatever the host synced since last time, then ask
+                // for fresh copies for the next interval.
+                for (browser, profile) in enabled_browsers_and_profiles() {
+                    process_bookmarks(&browser, &profile);
+                    request_history_query(&browser, &profile);
+                }
+                request_syncs();
+            }
+            PluginEvent::SqlResponse {
+                request_id, rows, ..
+            } => {
+                // The browser/profile pair is baked into the request id so
+                // responses can be routed without any plugin-side state.
+                if let Some((browser, profile)) = request_id
+                    .strip_prefix("history:")
+                    .and_then(|rest| rest.split_once(':'))
+                {
+                    process_history(browser, profile, &rows);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The browser/profile combinations to import, after applying the
+/// `CHROMIUM_BROWSERS` & `CHROME_PROFILES` settings.
+fn enabled_browsers_and_profiles() -> Vec<(String, String)> {
+    let browsers = setting_list("CHROMIUM_BROWSERS");
+    let profiles = setting_list("CHROME_PROFILES");
+    let profiles = if profiles.is_empty() {
+        DEFAULT_PROFILES
+            .iter()
+            .map(|profile| profile.to_string())
+            .collect::<Vec<String>>()
+    } else {
+        profiles
+    };
+
+    let mut combos = Vec::new();
+    for (name, _, _, _) in BROWSERS {
+        if !browsers.is_empty() && !browsers.contains(&name.to_string()) {
+            continue;
+        }
+
+        for profile in &profiles {
+            combos.push((name.to_string(), profile.clone()));
+        }
+    }
+
+    combos
+}
+
+/// Parses a comma-separated plugin setting into a list, empty if unset.
+fn setting_list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Data root for a browser on the host OS, relative to the home directory.
+fn browser_root(browser: &str, host_os: &str) -> Option<&'static str> {
+    BROWSERS
+        .iter()
+        .find(|(name, _, _, _)| *name == browser)
+        .map(|(_, linux_root, macos_root, windows_root)| match host_os {
+            "macos" => *macos_root,
+            "windows" => *windows_root,
+            _ => *linux_root,
+        })
+}
+
+/// Asks the host to copy each profile's Bookmarks file into our data dir.
+fn request_syncs() {
+    let home = std::env::var(consts::env::HOST_HOME_DIR).unwrap_or_default();
+    let host_os = std::env::var(consts::env::HOST_OS).unwrap_or_default();
+
+    for (browser, profile) in enabled_browsers_and_profiles() {
+        if let Some(root) = browser_root(&browser, &host_os) {
+            let dst = sync_dir(&browser, &profile);
+            let _ = sync_file(&dst, &format!("{home}/{root}/{profile}/Bookmarks"));
+            let _ = sync_file(&dst, &format!("{home}/{root}/{profile}/History"));
+        }
+    }
+}
+
+/// Folder inside the plugin data dir a profile's Bookmarks file is synced
+/// to. Each browser/profile pair gets its own folder so their files (and
+/// checksums) don't clobber each other.
+fn sync_dir(browser: &str, profile: &str) -> String {
+    format!("/{browser}/{}", profile.replace(' ', "_"))
+}
+
+/// Reads a synced Bookmarks file & adds its urls to the index, skipping the
+/// work when the file hasn't changed since the last run.
+fn process_bookmarks(browser: &str, profile: &str) {
+    let dir = PathBuf::from(sync_dir(browser, profile));
+    let bookmarks_path = dir.join("Bookmarks");
+    let contents = match std::fs::read_to_string(&bookmarks_path) {
+        Ok(contents) => contents,
+        // Nothing synced for this browser/profile combo (yet).
+        Err(_) => return,
+    };
+
+    // Each synced file keeps its own checksum so an unchanged profile
+    // doesn't get reparsed & readded every interval.
+    let checksum = content_hash(&contents);
+    let checksum_path = dir.join("Bookmarks.checksum");
+    if std::fs::read_to_string(&checksum_path).ok().as_deref() == Some(checksum.as_str()) {
+        return;
+    }
+
+    let parsed: Value = match serde_json::from_str(&contents) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            log(format!("Unable to parse {browser}/{profile} bookmarks: {error}").as_str());
+            return;
+        }
+    };
+
+    let mut bookmarks = Vec::new();
+    if let Some(roots) = parsed["roots"].as_object() {
+        for root in roots.values() {
+            collect_bookmarks(root, &mut bookmarks);
+        }
+    }
+
+    let count = bookmarks.len();
+    log(format!("Importing {count} bookmarks from {browser}/{profile}").as_str());
+    let docs = bookmarks
+        .into_iter()
+        .map(|(url, title)| DocumentUpdate {
+            open_url: Some(url.clone()),
+            url,
+            title: Some(title),
+            content: None,
+            description: None,
+            tags: Vec::new(),
+            published_at: None,
+        })
+        .collect::<Vec<DocumentUpdate>>();
+
+    // Tag everything w/ the originating browser so users can filter by
+    // source.
+    let tags = vec![
+        (String::from("lens"), String::from("bookmarks")),
+        (String::from("browser"), browser.to_string()),
+    ];
+    let _ = add_document(docs, tags);
+    let _ = std::fs::write(&checksum_path, checksum);
+    let _ = report_status(
+        PluginState::Idle,
+        &format!("Imported {count} bookmarks from {browser}/{profile}"),
+        None,
+    );
+}
+
+/// Urls w/ at least the threshold visits that were visited after the
+/// watermark (in Chrome's native microsecond timestamps).
+const HISTORY_QUERY: &str = "
+    SELECT url, title, visit_count, last_visit_time
+    FROM urls
+    WHERE hidden = 0 AND visit_count >= ?1 AND last_visit_time > ?2
+    ORDER BY last_visit_time ASC";
+
+/// Kicks off the history query for a synced History database. Only rows
+/// visited since the last run are pulled so the interval doesn't rescan the
+/// whole table; a watermark file in the data dir tracks how far we got.
+fn request_history_query(browser: &str, profile: &str) {
+    let dir = PathBuf::from(sync_dir(browser, profile));
+    let history_db = dir.join("History");
+    if !history_db.exists() {
+        // Nothing synced for this browser/profile combo (yet).
+        return;
+    }
+
+    let _ = sqlite3_query(
+        &format!("history:{browser}:{profile}"),
+        &history_db.display().to_string(),
+        HISTORY_QUERY,
+        &[
+            serde_json::json!(visit_threshold()),
+            serde_json::json!(read_watermark(&dir.join("History.watermark"))),
+        ],
+    );
+}
+
+/// Imports history rows visited since the last run.
+fn process_history(browser: &str, profile: &str, rows: &[Vec<Value>]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    log(format!("Importing {} history entries from {browser}/{profile}", rows.len()).as_str());
+    let watermark_path = PathBuf::from(sync_dir(browser, profile)).join("History.watermark");
+    let mut max_visit_time = read_watermark(&watermark_path);
+    let docs = rows
+        .iter()
+        .filter_map(|row| {
+            let url = row.first().and_then(|value| value.as_str())?.to_string();
+            let title = row.get(1).and_then(|value| value.as_str()).unwrap_or("");
+            let visit_count = row.get(2).and_then(|value| value.as_i64()).unwrap_or(0);
+            let last_visit_time = row.get(3).and_then(|value| value.as_i64()).unwrap_or(0);
+            max_visit_time = max_visit_time.max(last_visit_time);
+            Some(DocumentUpdate {
+                open_url: Some(url.clone()),
+                title: Some(if title.is_empty() {
+                    url.clone()
+                } else {
+                    title.to_string()
+                }),
+                url,
+                content: None,
+                description: None,
+                // Tag each url w/ a visit count bucket so heavily visited
+                // pages can be boosted or filtered on.
+                tags: vec![(String::from("visits"), visit_bucket(visit_count))],
+                published_at: chrome_time_to_rfc3339(last_visit_time),
+            })
+        })
+        .collect::<Vec<DocumentUpdate>>();
+
+    let tags = vec![
+        (String::from("source"), String::from("history")),
+        (String::from("browser"), browser.to_string()),
+    ];
+    let _ = add_document(docs, tags);
+    let _ = std::fs::write(&watermark_path, max_visit_time.to_string());
+    let _ = report_status(
+        PluginState::Idle,
+        &format!(
+            "Imported {} history entries from {browser}/{profile}",
+            rows.len()
+        ),
+        None,
+    );
+}
+
+/// The `VISIT_COUNT_THRESHOLD` setting, falling back to the default when
+/// unset or unparseable.
+fn visit_threshold() -> i64 {
+    std::env::var("VISIT_COUNT_THRESHOLD")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_VISIT_THRESHOLD)
+}
+
+/// The last-visit timestamp we've already imported up to, 0 on the first
+/// run.
+fn read_watermark(path: &Path) -> i64 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(0)
+}
+
+/// Buckets a visit count so we don't mint a unique tag per count.
+fn visit_bucket(visit_count: i64) -> String {
+    match visit_count {
+        count if count >= 1000 => "1000+".into(),
+        count if count >= 100 => "100+".into(),
+        count if count >= 10 => "10+".into(),
+        _ => "1+".into(),
+    }
+}
+
+/// Converts a Chrome timestamp (microseconds since 1601) to an RFC 3339
+/// string, `None` for timestamps before the unix epoch.
+fn chrome_time_to_rfc3339(chrome_micros: i64) -> Option<String> {
+    let unix_micros = chrome_micros.checked_sub(CHROME_EPOCH_OFFSET_MICROS)?;
+    if unix_micros < 0 {
+        return None;
+    }
+
+    chrono::DateTime::from_timestamp_micros(unix_micros).map(|ts| ts.to_rfc3339())
+}
+
+/// Walks a bookmark tree node, collecting `(url, title)` pairs.
+fn collect_bookmarks(node: &Value, bookmarks: &mut Vec<(String, String)>) {
+    match node["type"].as_str() {
+        Some("url") => {
+            if let Some(url) = node["url"].as_str() {
+                let title = node["name"].as_str().unwrap_or(url);
+                bookmarks.push((url.to_string(), title.to_string()));
+            }
+        }
+        _ => {
+            if let Some(children) = node["children"].as_array() {
+                for child in children {
+                    collect_bookmarks(child, bookmarks);
+                }
+            }
+        }
+    }
+}
+
+/// FNV-1a hash of the file contents; cheap & good enough to detect changes.
+fn content_hash(contents: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in contents.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
@@ -21,6 +21,9 @@ impl SpyglassPlugin for Plugin {
         // request can also be made using the query_documents function.
         let _ = subscribe_for_documents(DocumentQuery {
             has_tags: Some(vec![(String::from("lens"), String::from("nuget"))]),
+            // Matches come back in pages of 25; see the DocumentResponse
+            // handler below for walking the rest of the pages.
+            page_size: Some(25),
             ..Default::default()
         });
 
@@ -58,9 +61,17 @@ impl SpyglassPlugin for Plugin {
                     .get()
                     .run();
             }
-            PluginEvent::HttpResponse { url: _, result } => {
-                // When a response is received from an http call it will be received asynchronously
+            PluginEvent::HttpResponse { url, result } => {
+                // When a response is received from an http call it will be received asynchronously.
+                // Text responses show up in `response`; binary payloads (favicons, archives, etc.)
+                // come back base64-encoded in `body_base64` and can be decoded w/ `as_bytes()`.
+                // Bodies larger than the host's configured limit are cut off and flagged via
+                // `truncated`, so check that before parsing anything you expect to be complete.
                 if let Ok(rslt) = result {
+                    if rslt.truncated {
+                        log(format!("response from {url} was truncated by the host").as_str());
+                    }
+
                     if let Some(json) = rslt.as_json() {
                         let packages = json["data"].as_array().unwrap();
                         let mut docs = Vec::new();
@@ -86,6 +97,7 @@ impl SpyglassPlugin for Plugin {
                                     url: String::from(url_name),
                                     open_url: Some(String::from(url_name)),
                                     tags,
+                                    published_at: None,
                                 };
                                 docs.push(doc);
                             }
@@ -101,9 +113,9 @@ impl SpyglassPlugin for Plugin {
                 }
             }
             PluginEvent::DocumentResponse {
-                request_id: _,
-                page_count: _,
-                page: _,
+                request_id,
+                page_count,
+                page,
                 documents,
             } => {
                 // Response to a request for documents, there are also methods used to modify the tags on
@@ -114,7 +126,17 @@ impl SpyglassPlugin for Plugin {
                     .map(|doc| doc.url.clone())
                     .collect::<Vec<String>>();
                 log(format!("Saved documents {:?}", urls).as_str());
+
+                // Each response carries one page of matches; keep asking for the next page
+                // until we've walked them all.
+                if page + 1 < page_count {
+                    let _ = request_document_page(&request_id, page + 1);
+                }
             }
+            // This plugin doesn't watch any paths, so no filesystem events
+            // will show up here. See the local-file-indexer plugin for an
+            // example of those.
+            _ => {}
         }
     }
 }
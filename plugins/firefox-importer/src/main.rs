@@ -0,0 +1,295 @@
+use serde_json::{json, Value};
+use spyglass_plugin::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+struct Plugin;
+
+register_plugin!(Plugin);
+
+/// Visit count cutoff used when `VISIT_COUNT_THRESHOLD` isn't set.
+const DEFAULT_VISIT_THRESHOLD: i64 = 3;
+
+/// Bookmarks modified after the watermark as `(url, title, lastModified)`,
+/// skipping non-http schemes like `place:` queries.
+const BOOKMARK_QUERY: &str = "
+    SELECT p.url, IFNULL(b.title, IFNULL(p.title, '')), b.lastModified
+    FROM moz_bookmarks b
+    JOIN moz_places p ON b.fk = p.id
+    WHERE b.type = 1 AND p.url LIKE 'http%' AND b.lastModified > ?1
+    ORDER BY b.lastModified ASC";
+
+/// Urls w/ at least the threshold visits that were visited after the
+/// watermark (microseconds since the unix epoch).
+const HISTORY_QUERY: &str = "
+    SELECT url, IFNULL(title, ''), visit_count, last_visit_date
+    FROM moz_places
+    WHERE hidden = 0
+      AND last_visit_date IS NOT NULL
+      AND visit_count >= ?1
+      AND last_visit_date > ?2
+    ORDER BY last_visit_date ASC";
+
+impl SpyglassPlugin for Plugin {
+    fn load(&mut self) {
+        // Sync often enough to pick up recent browsing; the watermarks keep
+        // each run cheap.
+        let _ = subscribe_for_updates_every(5 * 60);
+        // Profiles live under randomly named folders, so we need
+        // `profiles.ini` synced before we know what to ask for. The first
+        // interval tick picks up from there.
+        let _ = sync_file("/", &format!("{}/profiles.ini", firefox_root()));
+    }
+
+    fn update(&mut self, event: PluginEvent) {
+        match event {
+            PluginEvent::IntervalUpdate => {
+                // Query whatever the host synced since last time, then ask
+                // for fresh copies for the next interval.
+                for profile in profiles() {
+                    request_queries(&profile);
+                }
+                request_syncs();
+            }
+            PluginEvent::SqlResponse {
+                request_id, rows, ..
+            } => {
+                // The profile is baked into the request id so responses can
+                // be routed without any plugin-side state.
+                if let Some(profile) = request_id.strip_prefix("bookmarks:") {
+                    process_bookmarks(profile, &rows);
+                } else if let Some(profile) = request_id.strip_prefix("history:") {
+                    process_history(profile, &rows);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Root of the Firefox data dir on the host, where `profiles.ini` lives.
+fn firefox_root() -> String {
+    let home = std::env::var(consts::env::HOST_HOME_DIR).unwrap_or_default();
+    let root = match std::env::var(consts::env::HOST_OS).as_deref() {
+        Ok("macos") => "Library/Application Support/Firefox",
+        Ok("windows") => "AppData/Roaming/Mozilla/Firefox",
+        _ => ".mozilla/firefox",
+    };
+
+    format!("{home}/{root}")
+}
+
+/// Profile folders to import, either from the `FIREFOX_PROFILES` setting or
+/// by parsing the synced `profiles.ini`.
+fn profiles() -> Vec<String> {
+    let configured = std::env::var("FIREFOX_PROFILES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<String>>();
+    if !configured.is_empty() {
+        return configured;
+    }
+
+    let contents = match std::fs::read_to_string("/profiles.ini") {
+        Ok(contents) => contents,
+        // Not synced yet; `request_syncs` will ask for it again.
+        Err(_) => return Vec::new(),
+    };
+
+    parse_profiles_ini(&contents)
+}
+
+/// Pulls the `Path=` entries out of the `[ProfileN]` sections of a
+/// `profiles.ini`.
+fn parse_profiles_ini(contents: &str) -> Vec<String> {
+    let mut profiles = Vec::new();
+    let mut in_profile_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_profile_section = line.starts_with("[Profile");
+        } else if in_profile_section {
+            if let Some(path) = line.strip_prefix("Path=") {
+                profiles.push(path.to_string());
+            }
+        }
+    }
+
+    profiles
+}
+
+/// Asks the host to re-copy `profiles.ini` & each profile's `places.sqlite`
+/// into our data dir.
+fn request_syncs() {
+    let root = firefox_root();
+    let _ = sync_file("/", &format!("{root}/profiles.ini"));
+    for profile in profiles() {
+        let src = format!("{root}/{profile}/places.sqlite");
+        let _ = sync_file(&sync_dir(&profile), &src);
+    }
+}
+
+/// Folder inside the plugin data dir a profile's `places.sqlite` is synced
+/// to.
+fn sync_dir(profile: &str) -> String {
+    format!("/{}", profile.replace(['/', ' '], "_"))
+}
+
+/// Kicks off the bookmark & history queries for a synced `places.sqlite`.
+/// Both imports are incremental: watermark files in the data dir record how
+/// far we got, so the interval only touches rows added or visited since the
+/// last run instead of rescanning the whole table.
+fn request_queries(profile: &str) {
+    let dir = PathBuf::from(sync_dir(profile));
+    let places_db = dir.join("places.sqlite");
+    if !places_db.exists() {
+        // Nothing synced for this profile (yet); let the user know instead
+        // of failing silently.
+        let _ = report_status(
+            PluginState::Error,
+            &format!("No places.sqlite synced for profile {profile} yet"),
+            None,
+        );
+        return;
+    }
+
+    let db = places_db.display().to_string();
+    let _ = sqlite3_query(
+        &format!("bookmarks:{profile}"),
+        &db,
+        BOOKMARK_QUERY,
+        &[json!(read_watermark(&dir.join("bookmarks.watermark")))],
+    );
+    let _ = sqlite3_query(
+        &format!("history:{profile}"),
+        &db,
+        HISTORY_QUERY,
+        &[
+            json!(visit_threshold()),
+            json!(read_watermark(&dir.join("history.watermark"))),
+        ],
+    );
+}
+
+/// Imports bookmark rows added or renamed since the last run.
+fn process_bookmarks(profile: &str, rows: &[Vec<Value>]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    log(format!("Importing {} bookmarks from {profile}", rows.len()).as_str());
+    let watermark_path = PathBuf::from(sync_dir(profile)).join("bookmarks.watermark");
+    let mut max_modified = read_watermark(&watermark_path);
+    let docs = rows
+        .iter()
+        .filter_map(|row| {
+            let url = row.first().and_then(|value| value.as_str())?.to_string();
+            let title = row.get(1).and_then(|value| value.as_str()).unwrap_or("");
+            max_modified =
+                max_modified.max(row.get(2).and_then(|value| value.as_i64()).unwrap_or(0));
+            Some(DocumentUpdate {
+                open_url: Some(url.clone()),
+                title: Some(if title.is_empty() {
+                    url.clone()
+                } else {
+                    title.to_string()
+                }),
+                url,
+                content: None,
+                description: None,
+                tags: Vec::new(),
+                published_at: None,
+            })
+        })
+        .collect::<Vec<DocumentUpdate>>();
+
+    let tags = vec![
+        (String::from("lens"), String::from("bookmarks")),
+        (String::from("browser"), String::from("firefox")),
+    ];
+    let _ = add_document(docs, tags);
+    let _ = std::fs::write(&watermark_path, max_modified.to_string());
+    let _ = report_status(
+        PluginState::Idle,
+        &format!("Imported {} bookmarks from {profile}", rows.len()),
+        None,
+    );
+}
+
+/// Imports history rows visited since the last run.
+fn process_history(profile: &str, rows: &[Vec<Value>]) {
+    if rows.is_empty() {
+        return;
+    }
+
+    log(format!("Importing {} history entries from {profile}", rows.len()).as_str());
+    let watermark_path = PathBuf::from(sync_dir(profile)).join("history.watermark");
+    let mut max_visit_date = read_watermark(&watermark_path);
+    let docs = rows
+        .iter()
+        .filter_map(|row| {
+            let url = row.first().and_then(|value| value.as_str())?.to_string();
+            let title = row.get(1).and_then(|value| value.as_str()).unwrap_or("");
+            let visit_count = row.get(2).and_then(|value| value.as_i64()).unwrap_or(0);
+            let last_visit_date = row.get(3).and_then(|value| value.as_i64()).unwrap_or(0);
+            max_visit_date = max_visit_date.max(last_visit_date);
+            Some(DocumentUpdate {
+                open_url: Some(url.clone()),
+                title: Some(if title.is_empty() {
+                    url.clone()
+                } else {
+                    title.to_string()
+                }),
+                url,
+                content: None,
+                description: None,
+                // Tag each url w/ a visit count bucket so heavily visited
+                // pages can be boosted or filtered on.
+                tags: vec![(String::from("visits"), visit_bucket(visit_count))],
+                published_at: chrono::DateTime::from_timestamp_micros(last_visit_date)
+                    .map(|ts| ts.to_rfc3339()),
+            })
+        })
+        .collect::<Vec<DocumentUpdate>>();
+
+    let tags = vec![
+        (String::from("source"), String::from("history")),
+        (String::from("browser"), String::from("firefox")),
+    ];
+    let _ = add_document(docs, tags);
+    let _ = std::fs::write(&watermark_path, max_visit_date.to_string());
+    let _ = report_status(
+        PluginState::Idle,
+        &format!("Imported {} history entries from {profile}", rows.len()),
+        None,
+    );
+}
+
+/// The `VISIT_COUNT_THRESHOLD` setting, falling back to the default when
+/// unset or unparseable.
+fn visit_threshold() -> i64 {
+    std::env::var("VISIT_COUNT_THRESHOLD")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_VISIT_THRESHOLD)
+}
+
+/// The watermark we've imported up to, 0 on the first run.
+fn read_watermark(path: &Path) -> i64 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(0)
+}
+
+/// Buckets a visit count so we don't mint a unique tag per count.
+fn visit_bucket(visit_count: i64) -> String {
+    match visit_count {
+        count if count >= 1000 => "1000+".into(),
+        count if count >= 100 => "100+".into(),
+        count if count >= 10 => "10+".into(),
+        _ => "1+".into(),
+    }
+}
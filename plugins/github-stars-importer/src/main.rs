@@ -0,0 +1,305 @@
+use serde_json::Value;
+use spyglass_plugin::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// GitHub's starred repos endpoint; the `star+json` media type includes when
+/// each repo was starred. Results come back newest first.
+const STARRED_ENDPOINT: &str = "https://api.github.com/user/starred";
+/// Repos per page when walking the starred list.
+const PAGE_SIZE: usize = 50;
+/// Check for new stars every hour.
+const UPDATE_INTERVAL_SECS: u64 = 60 * 60;
+/// `starred_at` of the newest repo from the last completed sync; later runs
+/// stop paging once they reach it.
+const STARRED_AT_WATERMARK: &str = "/starred_at.watermark";
+/// Unix timestamp GitHub's rate limit resets at; interval updates are
+/// skipped until it has passed.
+const RATE_LIMIT_RESET: &str = "/ratelimit.reset";
+
+#[derive(Default)]
+struct Plugin {
+    /// Page of the starred list currently in flight (1-indexed).
+    page: usize,
+    /// `starred_at` of the newest repo this sync, persisted as the watermark
+    /// once the walk finishes.
+    latest_starred_at: Option<String>,
+    /// Docs waiting on their README fetch, keyed by the readme request url.
+    pending: HashMap<String, DocumentUpdate>,
+    /// Repos imported so far this sync.
+    imported: usize,
+}
+
+register_plugin!(Plugin);
+
+impl SpyglassPlugin for Plugin {
+    fn load(&mut self) {
+        let _ = subscribe_for_updates_every(UPDATE_INTERVAL_SECS);
+    }
+
+    fn update(&mut self, event: PluginEvent) {
+        match event {
+            PluginEvent::IntervalUpdate => {
+                // Respect any rate-limit backoff recorded on an earlier run.
+                if let Some(reset) = read_timestamp(Path::new(RATE_LIMIT_RESET)) {
+                    if unix_now() < reset {
+                        log_debug("Still rate limited by GitHub, skipping this sync");
+                        return;
+                    }
+                }
+
+                self.page = 1;
+                self.latest_starred_at = None;
+                self.pending.clear();
+                self.imported = 0;
+                self.request_page(1);
+            }
+            PluginEvent::HttpResponse { url, result } => match result {
+                Ok(response) if url.starts_with(STARRED_ENDPOINT) => {
+                    self.process_starred(&response)
+                }
+                Ok(response) if self.pending.contains_key(&url) => {
+                    self.process_readme(&url, &response)
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    let _ = report_status(
+                        PluginState::Error,
+                        &format!("GitHub request failed: {err}"),
+                        None,
+                    );
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Plugin {
+    fn request_page(&self, page: usize) {
+        let token = match access_token() {
+            Some(token) => token,
+            None => {
+                let _ = report_status(
+                    PluginState::Error,
+                    "No GitHub personal access token configured; see the plugin settings",
+                    None,
+                );
+                return;
+            }
+        };
+
+        Http::request(&format!(
+            "{STARRED_ENDPOINT}?per_page={PAGE_SIZE}&page={page}"
+        ))
+        .header("Accept", "application/vnd.github.star+json")
+        .bearer_auth(&token)
+        .run();
+    }
+
+    fn process_starred(&mut self, response: &HttpResponse) {
+        if check_rate_limit(&response.headers) {
+            return;
+        }
+
+        let items = match response.as_json() {
+            Some(Value::Array(items)) => items,
+            _ => {
+                let _ = report_status(
+                    PluginState::Error,
+                    "Unexpected response from GitHub, skipping this sync",
+                    None,
+                );
+                return;
+            }
+        };
+
+        // Newest star first; remember it for the next sync's cursor.
+        if self.latest_starred_at.is_none() {
+            self.latest_starred_at = items
+                .first()
+                .and_then(|item| item["starred_at"].as_str())
+                .map(String::from);
+        }
+
+        let watermark = read_watermark();
+        let page_len = items.len();
+        let mut reached_watermark = false;
+        let mut docs = Vec::new();
+        for item in &items {
+            // Stars are sorted newest first, so everything from here on was
+            // already imported by an earlier sync.
+            if let (Some(starred_at), Some(watermark)) =
+                (item["starred_at"].as_str(), watermark.as_deref())
+            {
+                if starred_at <= watermark {
+                    reached_watermark = true;
+                    break;
+                }
+            }
+
+            if let Some((doc, readme_url)) = to_document(&item["repo"]) {
+                // Index the repo right away w/ its description; the doc is
+                // upgraded w/ the README content once that fetch lands.
+                self.pending.insert(readme_url.clone(), doc.clone());
+                docs.push(doc);
+                request_readme(&readme_url);
+            }
+        }
+
+        if !docs.is_empty() {
+            self.imported += docs.len();
+            log(format!("Importing {} starred repos", docs.len()).as_str());
+            let _ = add_document(docs, source_tags());
+        }
+
+        if !reached_watermark && page_len == PAGE_SIZE {
+            // Full page & still above the watermark, keep walking.
+            self.page += 1;
+            let _ = report_status(
+                PluginState::Running,
+                &format!("Imported {} starred repos so far", self.imported),
+                None,
+            );
+            self.request_page(self.page);
+        } else {
+            if let Some(latest) = &self.latest_starred_at {
+                let _ = std::fs::write(STARRED_AT_WATERMARK, latest);
+            }
+            let _ = report_status(
+                PluginState::Idle,
+                &format!("Imported {} starred repos", self.imported),
+                None,
+            );
+        }
+    }
+
+    fn process_readme(&mut self, url: &str, response: &HttpResponse) {
+        let mut doc = match self.pending.remove(url) {
+            Some(doc) => doc,
+            None => return,
+        };
+
+        if check_rate_limit(&response.headers) {
+            return;
+        }
+
+        // READMEs are requested as raw markdown; a JSON object here is the
+        // API telling us the repo doesn't have one.
+        let readme = match &response.response {
+            Some(text) if !text.trim_start().starts_with('{') => text,
+            _ => {
+                log_debug(format!("No README for {}", doc.url).as_str());
+                return;
+            }
+        };
+
+        doc.content = Some(readme.clone());
+        let _ = add_document(vec![doc], source_tags());
+    }
+}
+
+/// A starred repo as a document, plus the url its README can be fetched
+/// from. Topics come along as `topic` tags.
+fn to_document(repo: &Value) -> Option<(DocumentUpdate, String)> {
+    let url = repo["html_url"].as_str()?.to_string();
+    let full_name = repo["full_name"].as_str()?.to_string();
+    let description = repo["description"].as_str().map(String::from);
+
+    let mut tags = repo["topics"]
+        .as_array()
+        .map(|topics| {
+            topics
+                .iter()
+                .filter_map(|topic| topic.as_str())
+                .map(|topic| (String::from("topic"), topic.to_string()))
+                .collect::<Vec<(String, String)>>()
+        })
+        .unwrap_or_default();
+    if let Some(language) = repo["language"].as_str() {
+        tags.push((String::from("language"), language.to_string()));
+    }
+
+    let doc = DocumentUpdate {
+        content: description.clone(),
+        description,
+        title: Some(full_name.clone()),
+        open_url: Some(url.clone()),
+        url,
+        tags,
+        published_at: repo["created_at"].as_str().map(String::from),
+    };
+
+    Some((doc, format!("https://api.github.com/repos/{full_name}/readme")))
+}
+
+fn request_readme(url: &str) {
+    if let Some(token) = access_token() {
+        Http::request(url)
+            .header("Accept", "application/vnd.github.raw+json")
+            .bearer_auth(&token)
+            .run();
+    }
+}
+
+/// Tags applied to everything this plugin imports.
+fn source_tags() -> Vec<(String, String)> {
+    vec![(String::from("source"), String::from("github"))]
+}
+
+/// True when GitHub says we're out of API calls; records when the limit
+/// resets so interval updates back off until then.
+fn check_rate_limit(headers: &[(String, String)]) -> bool {
+    let remaining = headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("x-ratelimit-remaining") && value.trim() == "0"
+    });
+    if !remaining {
+        return false;
+    }
+
+    let reset = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("x-ratelimit-reset"))
+        .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| unix_now() + UPDATE_INTERVAL_SECS);
+    let _ = std::fs::write(RATE_LIMIT_RESET, reset.to_string());
+    let _ = report_status(
+        PluginState::Idle,
+        "Rate limited by GitHub, will retry after the limit resets",
+        None,
+    );
+
+    true
+}
+
+/// The `GITHUB_PAT` setting, if the user has filled it in.
+fn access_token() -> Option<String> {
+    std::env::var("GITHUB_PAT")
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// The `starred_at` watermark from the last completed sync, if there was
+/// one.
+fn read_watermark() -> Option<String> {
+    std::fs::read_to_string(STARRED_AT_WATERMARK)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// A unix timestamp persisted on an earlier run, e.g. the rate-limit reset.
+fn read_timestamp(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
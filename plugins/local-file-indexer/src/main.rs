@@ -0,0 +1,40 @@
+use spyglass_plugin::*;
+
+#[derive(Default)]
+struct Plugin;
+
+register_plugin!(Plugin);
+
+const WATCHED_EXTS: &[&str] = &["md", "txt"];
+
+// Example plugin showing how to subscribe to filesystem events. On load the
+// plugin asks the host to watch the user's home directory for markdown &
+// text files. The host debounces the raw notifications & delivers one event
+// per changed file, so a large `git checkout` or editor save storm doesn't
+// flood the plugin.
+impl SpyglassPlugin for Plugin {
+    fn load(&mut self) {
+        let home = std::env::var(consts::env::HOST_HOME_DIR).unwrap_or_default();
+        let extensions = WATCHED_EXTS
+            .iter()
+            .map(|ext| ext.to_string())
+            .collect::<Vec<String>>();
+        if let Err(error) = watch_path(&home, &extensions, true) {
+            log(format!("Unable to watch {home}: {error}").as_str());
+        }
+    }
+
+    fn update(&mut self, event: PluginEvent) {
+        match event {
+            // New & changed files are queued for (re)indexing.
+            PluginEvent::FileCreated(uri) | PluginEvent::FileUpdated(uri) => {
+                let _ = enqueue_all(&[uri]);
+            }
+            // Removed files are dropped from the index.
+            PluginEvent::FileDeleted(uri) => {
+                let _ = delete_doc(&uri);
+            }
+            _ => {}
+        }
+    }
+}
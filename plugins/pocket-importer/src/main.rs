@@ -0,0 +1,234 @@
+use serde_json::{json, Value};
+use spyglass_plugin::*;
+use std::path::Path;
+
+/// Pocket's retrieve endpoint; see https://getpocket.com/developer/docs/v3/retrieve
+const RETRIEVE_ENDPOINT: &str = "https://getpocket.com/v3/get";
+/// Articles per page when walking the archive.
+const PAGE_SIZE: usize = 30;
+/// Check for new saves every 30 minutes.
+const UPDATE_INTERVAL_SECS: u64 = 30 * 60;
+/// Unix timestamp of the last completed sync; Pocket's `since` parameter
+/// limits later runs to articles saved or changed after it.
+const SINCE_WATERMARK: &str = "/since.watermark";
+
+#[derive(Default)]
+struct Plugin {
+    /// Offset of the page currently in flight.
+    offset: usize,
+    /// `since` value reported by the API for this sync, persisted once the
+    /// last page has been processed.
+    latest_since: Option<u64>,
+    /// Articles imported so far this sync.
+    imported: usize,
+}
+
+register_plugin!(Plugin);
+
+impl SpyglassPlugin for Plugin {
+    fn load(&mut self) {
+        let _ = subscribe_for_updates_every(UPDATE_INTERVAL_SECS);
+    }
+
+    fn update(&mut self, event: PluginEvent) {
+        match event {
+            PluginEvent::IntervalUpdate => {
+                // The watermark keeps each sync incremental: the first run
+                // walks the whole archive, later runs only see new saves.
+                self.offset = 0;
+                self.latest_since = None;
+                self.imported = 0;
+                self.request_page(0);
+            }
+            PluginEvent::HttpResponse { url, result } if url.starts_with(RETRIEVE_ENDPOINT) => {
+                match result {
+                    Ok(response) => self.process_response(&response),
+                    Err(err) => {
+                        let _ = report_status(
+                            PluginState::Error,
+                            &format!("Pocket request failed: {err}"),
+                            None,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Plugin {
+    fn request_page(&self, offset: usize) {
+        let token = match access_token() {
+            Some(token) => token,
+            None => {
+                let _ = report_status(
+                    PluginState::Error,
+                    "No Pocket access token configured; see the plugin settings",
+                    None,
+                );
+                return;
+            }
+        };
+
+        let mut body = json!({
+            "state": "all",
+            "detailType": "complete",
+            "sort": "oldest",
+            "count": PAGE_SIZE,
+            "offset": offset,
+        });
+        if let Some(since) = read_watermark(Path::new(SINCE_WATERMARK)) {
+            body["since"] = since.into();
+        }
+
+        Http::request(RETRIEVE_ENDPOINT)
+            .post()
+            .header("Content-Type", "application/json")
+            .bearer_auth(&token)
+            .body(&body.to_string())
+            .run();
+    }
+
+    fn process_response(&mut self, response: &HttpResponse) {
+        // Out of API calls; skip the rest of this sync & let the next
+        // interval pick up where the watermark left off.
+        if rate_limited(&response.headers) {
+            let _ = report_status(
+                PluginState::Idle,
+                "Rate limited by Pocket, will retry next interval",
+                None,
+            );
+            return;
+        }
+
+        let parsed = match response.as_json() {
+            Some(parsed) => parsed,
+            None => {
+                let _ = report_status(
+                    PluginState::Error,
+                    "Unexpected response from Pocket, skipping this sync",
+                    None,
+                );
+                return;
+            }
+        };
+
+        // Pocket reports the server time of the response; saving it once the
+        // walk finishes means the next sync only sees newer articles.
+        if self.latest_since.is_none() {
+            self.latest_since = parsed["since"].as_u64();
+        }
+
+        // An empty list comes back as `[]` instead of an object.
+        let items = parsed["list"]
+            .as_object()
+            .map(|list| list.values().collect::<Vec<&Value>>())
+            .unwrap_or_default();
+
+        let docs = items
+            .iter()
+            .filter_map(|item| to_document(item))
+            .collect::<Vec<DocumentUpdate>>();
+        if !docs.is_empty() {
+            self.imported += docs.len();
+            log(format!("Importing {} saved articles", docs.len()).as_str());
+            let _ = add_document(
+                docs,
+                vec![(String::from("source"), String::from("pocket"))],
+            );
+        }
+
+        if items.len() == PAGE_SIZE {
+            // Full page, there's probably more.
+            self.offset += PAGE_SIZE;
+            let _ = report_status(
+                PluginState::Running,
+                &format!("Imported {} saved articles so far", self.imported),
+                None,
+            );
+            self.request_page(self.offset);
+        } else {
+            if let Some(since) = self.latest_since {
+                let _ = std::fs::write(SINCE_WATERMARK, since.to_string());
+            }
+            let _ = report_status(
+                PluginState::Idle,
+                &format!("Imported {} saved articles", self.imported),
+                None,
+            );
+        }
+    }
+}
+
+/// A saved article as a document, or `None` for items deleted from Pocket.
+fn to_document(item: &Value) -> Option<DocumentUpdate> {
+    // status 2 == deleted
+    if item["status"].as_str() == Some("2") {
+        return None;
+    }
+
+    let url = item["resolved_url"]
+        .as_str()
+        .filter(|url| !url.is_empty())
+        .or_else(|| item["given_url"].as_str())
+        .filter(|url| !url.is_empty())?
+        .to_string();
+    let title = item["resolved_title"]
+        .as_str()
+        .filter(|title| !title.is_empty())
+        .or_else(|| item["given_title"].as_str())
+        .filter(|title| !title.is_empty())
+        .unwrap_or(&url)
+        .to_string();
+    let excerpt = item["excerpt"]
+        .as_str()
+        .filter(|excerpt| !excerpt.is_empty())
+        .map(String::from);
+
+    // The user's own Pocket tags, keyed by label.
+    let tags = item["tags"]
+        .as_object()
+        .map(|tags| {
+            tags.keys()
+                .map(|tag| (String::from("tag"), tag.clone()))
+                .collect::<Vec<(String, String)>>()
+        })
+        .unwrap_or_default();
+
+    Some(DocumentUpdate {
+        content: excerpt.clone(),
+        description: excerpt,
+        title: Some(title),
+        open_url: Some(url.clone()),
+        url,
+        tags,
+        published_at: item["time_added"]
+            .as_str()
+            .and_then(|ts| ts.trim().parse::<i64>().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|ts| ts.to_rfc3339()),
+    })
+}
+
+/// The `POCKET_ACCESS_TOKEN` setting, if the user has filled it in.
+fn access_token() -> Option<String> {
+    std::env::var("POCKET_ACCESS_TOKEN")
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// True when Pocket's rate limit headers say we're out of calls.
+fn rate_limited(headers: &[(String, String)]) -> bool {
+    headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("x-limit-user-remaining") && value.trim() == "0"
+    })
+}
+
+/// The `since` watermark from the last completed sync, if there was one.
+fn read_watermark(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
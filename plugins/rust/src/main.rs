@@ -90,6 +90,7 @@ impl SpyglassPlugin for Plugin {
                     },
                 );
             }
+            _ => {}
         }
     }
 }
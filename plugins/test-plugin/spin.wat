@@ -0,0 +1,7 @@
+;; Test fixture for the plugin fuel metering: `update` spins forever. Used to
+;; verify the host terminates runaway plugins instead of hanging.
+(module
+  (func (export "_start"))
+  (func (export "update")
+    (loop $spin
+      (br $spin))))